@@ -0,0 +1,72 @@
+//! Compares operations made one system call at a time with the same operations made by a [`Batch`].
+
+#![feature(test)]
+
+extern crate test;
+
+use lilium_sys::{
+    batch::Batch,
+    info::{self, ArchInfo, KernelVendor, OsVersion},
+    time::{MonotonicClock, SystemClock, TimePoint},
+};
+use test::{black_box, Bencher};
+
+#[bench]
+fn clocks_separately(b: &mut Bencher) {
+    b.iter(|| {
+        let wall = TimePoint::<SystemClock>::now().unwrap();
+        let mono = TimePoint::<MonotonicClock>::now().unwrap();
+        black_box((wall, mono))
+    });
+}
+
+#[bench]
+fn clocks_batched(b: &mut Bencher) {
+    b.iter(|| {
+        let mut batch = Batch::new();
+        let wall = batch.now::<SystemClock>();
+        let mono = batch.now::<MonotonicClock>();
+        let done = batch.submit().unwrap();
+        black_box((done.time(wall), done.time(mono)))
+    });
+}
+
+#[bench]
+fn info_separately(b: &mut Bencher) {
+    b.iter(|| {
+        let version = info::os_version().unwrap();
+        let vendor = info::kernel_vendor().unwrap();
+        let arch = info::arch_info().unwrap();
+        black_box((version, vendor, arch))
+    });
+}
+
+#[bench]
+fn info_batched(b: &mut Bencher) {
+    b.iter(|| {
+        let mut batch = Batch::new();
+        let version = batch.info::<OsVersion>();
+        let vendor = batch.info::<KernelVendor>();
+        let arch = batch.info::<ArchInfo>();
+        let done = batch.submit().unwrap();
+        black_box((done.info(version), done.info(vendor), done.info(arch)))
+    });
+}
+
+#[bench]
+fn clocks_and_info_batched(b: &mut Bencher) {
+    b.iter(|| {
+        let mut batch = Batch::new();
+        let wall = batch.now::<SystemClock>();
+        let mono = batch.now::<MonotonicClock>();
+        let version = batch.info::<OsVersion>();
+        let arch = batch.info::<ArchInfo>();
+        let done = batch.submit().unwrap();
+        black_box((
+            done.time(wall),
+            done.time(mono),
+            done.info(version),
+            done.info(arch),
+        ))
+    });
+}
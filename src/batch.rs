@@ -0,0 +1,196 @@
+//! Coalescing of several operations into as few system calls as possible.
+//!
+//! Many reads that are commonly made together have a system call that performs several of them at once.
+//! A [`Batch`] collects typed operations, and [`Batch::submit`] makes each group of operations with a single system call:
+//! * Clock reads ([`Batch::now`]) are made with one [`GetClockOffsets`][sys::GetClockOffsets] call, which also reads every clock at the same point in time,
+//! * System information requests ([`Batch::info`]) are made with one [`GetSystemInfo`][crate::sys::info::GetSystemInfo] call, as by [`RequestBuilder`].
+//!
+//! Each operation returns a [`Ticket`], which obtains its result from the [`Completed`] batch.
+//!
+//! ## Example
+//! ```rust,ignore
+//! let mut batch = Batch::new();
+//! let wall = batch.now::<SystemClock>();
+//! let mono = batch.now::<MonotonicClock>();
+//! let version = batch.info::<OsVersion>();
+//! let done = batch.submit()?;
+//! println!("{:?} ({:?}) on {:?}", done.time(wall), done.time(mono), done.info(version));
+//! ```
+
+use core::{
+    ffi::c_ulong,
+    marker::PhantomData,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use alloc::vec::Vec;
+
+use crate::{
+    info::{FromRequest, RequestBuilder, RequestResults},
+    result::{Error, Result},
+    sys::time::{self as sys, ClockOffset},
+    time::{Clock, Duration, TimePoint},
+};
+
+/// Identifies the result of an operation in a [`Batch`], which is obtained from the [`Completed`] batch.
+///
+/// A ticket is only meaningful for the batch that created it.
+pub struct Ticket<T> {
+    batch: usize,
+    idx: usize,
+    _result: PhantomData<fn() -> T>,
+}
+
+impl<T> Ticket<T> {
+    const fn new(batch: usize, idx: usize) -> Self {
+        Self {
+            batch,
+            idx,
+            _result: PhantomData,
+        }
+    }
+}
+
+impl<T> Clone for Ticket<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for Ticket<T> {}
+
+impl<T> core::fmt::Debug for Ticket<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.debug_struct("Ticket")
+            .field("batch", &self.batch)
+            .field("idx", &self.idx)
+            .finish()
+    }
+}
+
+/// The id of the next batch that creates a ticket. `0` is never used, and marks a batch that has not created a ticket yet.
+static NEXT_BATCH: AtomicUsize = AtomicUsize::new(1);
+
+/// A set of operations that are made together by [`Batch::submit`]. See the [module docs][self].
+pub struct Batch {
+    id: usize,
+    clocks: Vec<ClockOffset>,
+    info: RequestBuilder,
+    info_requested: bool,
+}
+
+impl Batch {
+    pub const fn new() -> Self {
+        Self {
+            id: 0,
+            clocks: Vec::new(),
+            info: RequestBuilder::new(),
+            info_requested: false,
+        }
+    }
+
+    /// Creates a ticket for the result at `idx`, assigning the batch an id first if needed
+    fn ticket<T>(&mut self, idx: usize) -> Ticket<T> {
+        if self.id == 0 {
+            self.id = NEXT_BATCH.fetch_add(1, Ordering::Relaxed);
+        }
+        Ticket::new(self.id, idx)
+    }
+
+    /// Reads the current time of the clock `C`.
+    ///
+    /// Every clock read in the batch is read at the same point in time.
+    pub fn now<C: Clock>(&mut self) -> Ticket<TimePoint<C>> {
+        let idx = self.clocks.len();
+        self.clocks.push(ClockOffset {
+            clockid: C::clock_uuid(),
+        });
+        self.ticket(idx)
+    }
+
+    /// Requests the system information `T`.
+    pub fn info<T: FromRequest>(&mut self) -> Ticket<T> {
+        let info = core::mem::replace(&mut self.info, RequestBuilder::new());
+        self.info = info.request::<T>();
+        self.info_requested = true;
+        self.ticket(0)
+    }
+
+    /// Performs the operations in the batch, with one system call for each group of operations that has at least one operation.
+    ///
+    /// ## Errors
+    /// Returns the first error returned by any of the system calls. No results are available if any system call fails.
+    pub fn submit(self) -> Result<Completed> {
+        let Self {
+            id,
+            mut clocks,
+            info,
+            info_requested,
+        } = self;
+
+        if !clocks.is_empty() {
+            Error::from_code(unsafe {
+                syscall!(sys::GetClockOffsets(
+                    clocks.as_mut_ptr(),
+                    clocks.len() as c_ulong
+                ))
+            })?;
+        }
+
+        let info = if info_requested {
+            Some(info.resolve()?)
+        } else {
+            None
+        };
+
+        Ok(Completed { id, clocks, info })
+    }
+}
+
+impl Default for Batch {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The results of a submitted [`Batch`]
+pub struct Completed {
+    id: usize,
+    clocks: Vec<ClockOffset>,
+    info: Option<RequestResults>,
+}
+
+impl Completed {
+    fn check_ticket<T>(&self, ticket: &Ticket<T>) {
+        assert_eq!(
+            ticket.batch, self.id,
+            "Attempt to obtain results with a ticket from another batch"
+        );
+    }
+
+    /// The time read by [`Batch::now`]
+    ///
+    /// ## Panics
+    /// Panics if `ticket` was not created by the batch that produced `self`.
+    pub fn time<C: Clock>(&self, ticket: Ticket<TimePoint<C>>) -> TimePoint<C> {
+        self.check_ticket(&ticket);
+        // SAFETY: `GetClockOffsets` succeeded, so it overwrote each `clockid` with the `offset`
+        let offset = unsafe { self.clocks[ticket.idx].offset };
+        TimePoint::from_epoch_offset(Duration::from_system(offset))
+    }
+
+    /// The information requested by [`Batch::info`]
+    ///
+    /// ## Panics
+    /// Panics if `ticket` was not created by the batch that produced `self`.
+    pub fn info<T: FromRequest>(&self, ticket: Ticket<T>) -> T {
+        self.check_ticket(&ticket);
+        match &self.info {
+            Some(info) => info.get::<T>(),
+            None => panic!(
+                "Attempt to obtain results from request `{}`, which was not made",
+                core::any::type_name::<T>()
+            ),
+        }
+    }
+}
@@ -0,0 +1,204 @@
+//! Debugging of other threads, such as by a debugger frontend.
+//!
+//! A [`Debugger`] is attached to a thread, and can suspend it, inspect and modify its memory and registers, capture the exceptions it raises, and resume or single-step it.
+//!
+//! ## Example
+//! ```rust,ignore
+//! let mut dbg = Debugger::attach(&thread)?;
+//! dbg.capture_exceptions()?;
+//! let except = dbg.await_capture()?;
+//! if except.kind() == ExceptionKind::DebugTrap {
+//!     dbg.step()?;
+//!     // Suppress the breakpoint, rather than delivering it to the thread
+//!     dbg.resume_with_exception(None)?;
+//! }
+//! ```
+
+use core::{cell::UnsafeCell, ffi::c_void, mem::MaybeUninit};
+
+use alloc::boxed::Box;
+
+pub mod coredump;
+pub mod regs;
+
+use regs::{Register, RegisterValue};
+
+use crate::{
+    except::Exception,
+    handle::{AsHandle, OwnedHandle},
+    result::{Error, Result},
+    sys::{
+        debug::{self as sys, DebugHandle},
+        except::ExceptionInfo,
+        handle::HandlePtr,
+        thread::ThreadHandle,
+    },
+};
+
+/// A debugger attached to a thread.
+///
+/// The debugger is detached when it is dropped.
+#[derive(Debug)]
+pub struct Debugger {
+    // Declared before `capture`, so that the handle is detached before the capture buffer is freed
+    hdl: OwnedHandle<DebugHandle>,
+    capture: Option<Box<UnsafeCell<MaybeUninit<ExceptionInfo>>>>,
+}
+
+impl Debugger {
+    /// Attaches a debugger to `th`.
+    ///
+    /// ## Errors
+    /// Returns [`Error::Permission`] if the `DEBUG_ATTACH` right for `th` is denied to the current thread.
+    pub fn attach<'a, H: AsHandle<'a, ThreadHandle>>(th: H) -> Result<Self> {
+        let mut hdl = MaybeUninit::uninit();
+        Error::from_code(unsafe { sys::DebugAttach(th.as_handle(), hdl.as_mut_ptr()) })?;
+        Ok(Self {
+            hdl: unsafe { OwnedHandle::take_ownership(hdl.assume_init()) },
+            capture: None,
+        })
+    }
+
+    pub fn as_raw(&self) -> HandlePtr<DebugHandle> {
+        self.hdl.as_raw()
+    }
+
+    /// Suspends the debugged thread, and waits until it is suspended
+    pub fn suspend(&self) -> Result<()> {
+        Error::from_code(unsafe { sys::DebugSuspend(self.as_raw()) })
+    }
+
+    /// Suspends every thread in the process of the debugged thread
+    pub fn suspend_all(&self) -> Result<()> {
+        Error::from_code(unsafe { sys::DebugSuspendAll(self.as_raw()) })
+    }
+
+    /// Resumes every thread suspended by the debugger.
+    ///
+    /// What happens to an exception captured by [`Debugger::capture_exceptions`] is unspecified - use [`Debugger::resume_with_exception`] to deliver or suppress it.
+    pub fn resume(&self) -> Result<()> {
+        Error::from_code(unsafe { sys::DebugResume(self.as_raw()) })
+    }
+
+    /// Resumes every thread suspended by the debugger, and delivers `except` to the debugged thread in place of the captured exception.
+    ///
+    /// If `except` is `None`, the captured exception is suppressed, and the thread continues as though it did not occur.
+    /// Passing the exception returned by [`Debugger::await_capture`] delivers it unmodified, as though the debugger was not attached.
+    pub fn resume_with_exception(&self, except: Option<Exception>) -> Result<()> {
+        let except = except.map(Exception::into_raw);
+        let ptr = except
+            .as_ref()
+            .map_or(core::ptr::null(), |except| except as *const _);
+        Error::from_code(unsafe { sys::DebugResumeWithException(self.as_raw(), ptr) })
+    }
+
+    /// Executes a single instruction of the debugged thread, which must be suspended, and suspends it again.
+    ///
+    /// ## Errors
+    /// Returns [`Error::InvalidState`] if the thread is not suspended, and [`Error::Killed`] if it terminated while executing the instruction.
+    pub fn step(&self) -> Result<()> {
+        Error::from_code(unsafe { sys::DebugStep(self.as_raw()) })
+    }
+
+    /// Reads `buf.len()` bytes at `addr` in the address space of the debugged thread, which must be suspended
+    pub fn read_memory(&self, addr: usize, buf: &mut [u8]) -> Result<()> {
+        Error::from_code(unsafe {
+            sys::DebugReadMemory(
+                self.as_raw(),
+                addr,
+                buf.as_mut_ptr().cast::<c_void>(),
+                buf.len(),
+            )
+        })
+    }
+
+    /// Writes `buf` at `addr` in the address space of the debugged thread, which must be suspended.
+    ///
+    /// Memory that is not mapped writable can be modified, such as to insert breakpoints.
+    pub fn write_memory(&self, addr: usize, buf: &[u8]) -> Result<()> {
+        Error::from_code(unsafe {
+            sys::DebugWriteMemory(
+                self.as_raw(),
+                addr,
+                buf.as_ptr().cast::<c_void>(),
+                buf.len(),
+            )
+        })
+    }
+
+    /// Reads the register with the DWARF register number `regno` from the debugged thread. See [`DebugReadRegister`][sys::DebugReadRegister] for `meta` and the size of `buf`.
+    pub fn read_register(&self, regno: u32, meta: u32, buf: &mut [u8]) -> Result<()> {
+        Error::from_code(unsafe {
+            sys::DebugReadRegister(
+                self.as_raw(),
+                regno,
+                buf.as_mut_ptr().cast::<c_void>(),
+                meta,
+            )
+        })
+    }
+
+    /// Writes the register with the DWARF register number `regno` of the debugged thread. See [`DebugWriteRegister`][sys::DebugWriteRegister] for the size of `buf`.
+    pub fn write_register(&self, regno: u32, buf: &[u8]) -> Result<()> {
+        Error::from_code(unsafe {
+            sys::DebugWriteRegister(self.as_raw(), regno, buf.as_ptr().cast::<c_void>())
+        })
+    }
+
+    /// Reads the register `reg` from the debugged thread, which must be suspended
+    pub fn read_reg(&self, reg: Register) -> Result<RegisterValue> {
+        let mut val = RegisterValue::zeroed(reg);
+        self.read_register(reg.regno(), reg.meta(), val.as_bytes_mut())?;
+        Ok(val)
+    }
+
+    /// Writes `val` to the register `reg` of the debugged thread, which must be suspended.
+    ///
+    /// ## Errors
+    /// Returns [`Error::InvalidOperation`] if the size of `val` is not the size of `reg`.
+    pub fn write_reg(&self, reg: Register, val: &RegisterValue) -> Result<()> {
+        if val.size() != reg.size() {
+            return Err(Error::InvalidOperation);
+        }
+        self.write_register(reg.regno(), val.as_bytes())
+    }
+
+    /// Captures the exceptions raised by the debugged thread, which suspends it before the exception is handled.
+    ///
+    /// The captured exception is obtained from [`Debugger::await_capture`] or [`Debugger::poll_capture`].
+    pub fn capture_exceptions(&mut self) -> Result<()> {
+        let buf = self
+            .capture
+            .get_or_insert_with(|| Box::new(UnsafeCell::new(MaybeUninit::uninit())))
+            .get()
+            .cast();
+        Error::from_code(unsafe { sys::DebugCaptureSignal(self.hdl.as_raw(), buf) })
+    }
+
+    fn captured(&self) -> Exception {
+        let buf = self
+            .capture
+            .as_ref()
+            .expect("A successful capture requires `capture_exceptions` to have been called");
+        // SAFETY: The capture synchronizes-with the write to the buffer by the kernel
+        Exception::from_raw(unsafe { (*buf.get()).assume_init_ref().status })
+    }
+
+    /// Blocks until the debugged thread raises an exception captured by [`Debugger::capture_exceptions`], and returns the exception.
+    ///
+    /// ## Errors
+    /// Returns [`Error::InvalidState`] if exceptions are not being captured.
+    pub fn await_capture(&self) -> Result<Exception> {
+        Error::from_code(unsafe { sys::DebugAwaitCapture(self.as_raw()) })?;
+        Ok(self.captured())
+    }
+
+    /// Returns the exception captured by [`Debugger::capture_exceptions`], or `None` if no exception has been captured since exceptions were captured or the thread was resumed.
+    pub fn poll_capture(&self) -> Result<Option<Exception>> {
+        match Error::from_code(unsafe { sys::DebugPollCapture(self.as_raw()) }) {
+            Ok(()) => Ok(Some(self.captured())),
+            Err(Error::Pending) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+}
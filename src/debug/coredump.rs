@@ -0,0 +1,211 @@
+//! Writing ELF core files of debugged processes, for post-mortem debugging.
+//!
+//! The core file is an [`ET_CORE`] image with a [`PT_LOAD`] segment for each mapping of the debugged thread, and a [`PT_NOTE`] segment with a [`NT_LILIUM_REGISTERS`] note that holds the registers of the thread.
+//!
+//! The content of `MAP_KIND_SECURE` and `MAP_KIND_ENCRYPTED` mappings, and of reserved mappings, is never written. Their segments are present, but have no file content.
+//!
+//! ## Example
+//! ```rust,ignore
+//! let dbg = Debugger::attach(&thread)?;
+//! dbg.suspend_all()?;
+//! let file = OwnedFile::open_with("/var/crash/core", FileAccess::WRITE | FileAccess::CREATE, FileOp::DataAccess)?;
+//! coredump::write(&dbg, &file.as_io_handle())?;
+//! ```
+
+use core::ffi::c_ulong;
+
+use alloc::{vec, vec::Vec};
+
+use crate::{
+    handle::{AsHandle, BorrowedHandle},
+    io::IOHandle,
+    os::elf::{NATIVE_DATA, NATIVE_MACHINE},
+    result::{Error, Result},
+    sys::{
+        debug::{self as sys, DebugMappingInfo, DEBUG_MAPPING_KIND_SHIFT},
+        elf::{
+            CoreRegister, Elf64Ehdr, Elf64Phdr, ElfNhdr, EI_CLASS, EI_DATA, EI_NIDENT, EI_VERSION,
+            ELFCLASS64, ELFMAG, ET_CORE, EV_CURRENT, NOTE_NAME_LILIUM, NT_LILIUM_REGISTERS, PF_R,
+            PF_W, PF_X, PT_LOAD, PT_NOTE,
+        },
+        kstr::KStrPtr,
+        process::{
+            MAP_ATTR_EXEC, MAP_ATTR_READ, MAP_ATTR_RESERVE, MAP_ATTR_WRITE, MAP_KIND_ENCRYPTED,
+            MAP_KIND_SECURE,
+        },
+    },
+};
+
+use super::{regs::native, Debugger};
+
+/// The size of the chunks memory is copied to the core file in
+const CHUNK_SIZE: usize = 64 * 1024;
+
+struct Mapping {
+    lo: u64,
+    hi: u64,
+    kind: u32,
+    attrs: u32,
+}
+
+impl Mapping {
+    /// Whether the content of the mapping is written to the core file
+    fn dumped(&self) -> bool {
+        self.kind != MAP_KIND_SECURE
+            && self.kind != MAP_KIND_ENCRYPTED
+            && (self.attrs & MAP_ATTR_RESERVE) == 0
+            && (self.attrs & MAP_ATTR_READ) != 0
+    }
+
+    fn flags(&self) -> u32 {
+        let mut flags = 0;
+        if (self.attrs & MAP_ATTR_READ) != 0 {
+            flags |= PF_R;
+        }
+        if (self.attrs & MAP_ATTR_WRITE) != 0 {
+            flags |= PF_W;
+        }
+        if (self.attrs & MAP_ATTR_EXEC) != 0 {
+            flags |= PF_X;
+        }
+        flags
+    }
+}
+
+fn mappings(debugger: &Debugger) -> Result<Vec<Mapping>> {
+    let mut mappings = Vec::new();
+    loop {
+        let mut info = DebugMappingInfo {
+            vaddr_lo: 0,
+            vaddr_hi: 0,
+            mapping_name: KStrPtr::empty(),
+            kind_and_attrs: 0,
+            page_status: 0,
+            backing_paddr: 0,
+        };
+        match Error::from_code(unsafe {
+            sys::DebugGetMapping(debugger.as_raw(), mappings.len() as c_ulong, &mut info)
+        }) {
+            // The name is not needed, so the error for the empty name buffer is expected
+            Ok(()) | Err(Error::InsufficientLength) => {}
+            Err(Error::FinishedEnumerate) => break Ok(mappings),
+            Err(e) => break Err(e),
+        }
+        mappings.push(Mapping {
+            lo: info.vaddr_lo as u64,
+            hi: info.vaddr_hi as u64,
+            kind: info.kind_and_attrs >> DEBUG_MAPPING_KIND_SHIFT,
+            attrs: info.kind_and_attrs & ((1 << DEBUG_MAPPING_KIND_SHIFT) - 1),
+        });
+    }
+}
+
+fn registers_note(debugger: &Debugger) -> Result<Vec<u8>> {
+    let mut desc = Vec::new();
+    for &reg in native::GENERAL_PURPOSE {
+        let val = debugger.read_reg(reg)?;
+        desc.extend_from_slice(bytemuck::bytes_of(&CoreRegister {
+            regno: reg.regno(),
+            meta: reg.meta(),
+            size: reg.size() as u32,
+            __reserved: 0,
+        }));
+        desc.extend_from_slice(val.as_bytes());
+        desc.resize(desc.len().next_multiple_of(8), 0);
+    }
+
+    let mut note = Vec::new();
+    note.extend_from_slice(bytemuck::bytes_of(&ElfNhdr {
+        n_namesz: NOTE_NAME_LILIUM.len() as u32,
+        n_descsz: desc.len() as u32,
+        n_type: NT_LILIUM_REGISTERS,
+    }));
+    note.extend_from_slice(NOTE_NAME_LILIUM);
+    note.resize(note.len().next_multiple_of(4), 0);
+    note.extend_from_slice(&desc);
+    Ok(note)
+}
+
+/// Writes a core file of the process of the thread debugged by `debugger` to `writer`. See the [module docs][self] for the content of the file.
+///
+/// The debugged thread must be suspended, such as by [`Debugger::suspend_all`], and remains suspended. The core file is written sequentially from the current position of `writer`.
+///
+/// ## Errors
+/// Returns an error if the mappings, registers, or memory of the thread cannot be read, or `writer` cannot be written to.
+/// Part of the core file may have been written when an error is returned.
+pub fn write<'a, W: AsHandle<'a, IOHandle>>(debugger: &Debugger, writer: W) -> Result<()> {
+    let writer = unsafe { BorrowedHandle::from_raw(writer.as_handle()) };
+    let mappings = mappings(debugger)?;
+    let note = registers_note(debugger)?;
+
+    let phnum = mappings.len() + 1;
+    let phoff = size_of::<Elf64Ehdr>() as u64;
+    let note_off = phoff + (phnum * size_of::<Elf64Phdr>()) as u64;
+
+    let mut e_ident = [0; EI_NIDENT];
+    e_ident[..4].copy_from_slice(&ELFMAG);
+    e_ident[EI_CLASS] = ELFCLASS64;
+    e_ident[EI_DATA] = NATIVE_DATA;
+    e_ident[EI_VERSION] = EV_CURRENT as u8;
+    let ehdr = Elf64Ehdr {
+        e_ident,
+        e_type: ET_CORE,
+        e_machine: NATIVE_MACHINE,
+        e_version: EV_CURRENT,
+        e_entry: 0,
+        e_phoff: phoff,
+        e_shoff: 0,
+        e_flags: 0,
+        e_ehsize: size_of::<Elf64Ehdr>() as u16,
+        e_phentsize: size_of::<Elf64Phdr>() as u16,
+        e_phnum: u16::try_from(phnum).map_err(|_| Error::InsufficientLength)?,
+        e_shentsize: 0,
+        e_shnum: 0,
+        e_shstrndx: 0,
+    };
+
+    let mut phdrs = Vec::with_capacity(phnum);
+    phdrs.push(Elf64Phdr {
+        p_type: PT_NOTE,
+        p_flags: 0,
+        p_offset: note_off,
+        p_vaddr: 0,
+        p_paddr: 0,
+        p_filesz: note.len() as u64,
+        p_memsz: 0,
+        p_align: 4,
+    });
+    let mut off = note_off + note.len() as u64;
+    for map in &mappings {
+        let size = map.hi - map.lo;
+        let filesz = if map.dumped() { size } else { 0 };
+        phdrs.push(Elf64Phdr {
+            p_type: PT_LOAD,
+            p_flags: map.flags(),
+            p_offset: off,
+            p_vaddr: map.lo,
+            p_paddr: 0,
+            p_filesz: filesz,
+            p_memsz: size,
+            p_align: 1,
+        });
+        off += filesz;
+    }
+
+    writer.write_all(bytemuck::bytes_of(&ehdr))?;
+    writer.write_all(bytemuck::cast_slice(&phdrs))?;
+    writer.write_all(&note)?;
+
+    let mut buf = vec![0u8; CHUNK_SIZE];
+    for map in mappings.iter().filter(|map| map.dumped()) {
+        let mut addr = map.lo;
+        while addr < map.hi {
+            let len = (map.hi - addr).min(CHUNK_SIZE as u64) as usize;
+            debugger.read_memory(addr as usize, &mut buf[..len])?;
+            writer.write_all(&buf[..len])?;
+            addr += len as u64;
+        }
+    }
+
+    Ok(())
+}
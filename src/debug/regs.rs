@@ -0,0 +1,144 @@
+//! The registers accessed by [`Debugger::read_reg`][super::Debugger::read_reg] and [`Debugger::write_reg`][super::Debugger::write_reg], for each architecture.
+//!
+//! A [`Register`] pairs the DWARF register number with the `meta` value and the size of the buffer [`DebugReadRegister`][crate::sys::debug::DebugReadRegister] needs for it.
+//! The registers of the architecture the crate is compiled for are also available as [`native`].
+
+pub mod aarch64;
+pub mod clever;
+pub mod riscv64;
+pub mod x86_64;
+
+#[cfg(target_arch = "aarch64")]
+pub use aarch64 as native;
+#[cfg(target_arch = "clever")]
+pub use clever as native;
+#[cfg(target_arch = "riscv64")]
+pub use riscv64 as native;
+#[cfg(target_arch = "x86_64")]
+pub use x86_64 as native;
+
+/// The registers of the architecture the crate is compiled for, which has no register table
+#[cfg(not(any(
+    target_arch = "aarch64",
+    target_arch = "clever",
+    target_arch = "riscv64",
+    target_arch = "x86_64"
+)))]
+pub mod native {
+    use super::Register;
+
+    /// The registers saved in a core dump, which are none
+    pub const GENERAL_PURPOSE: &[Register] = &[];
+}
+
+/// A register of the debugged thread
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+pub struct Register {
+    regno: u32,
+    meta: u32,
+    size: usize,
+}
+
+impl Register {
+    /// A register with the DWARF register number `regno`, accessed with `meta`, which needs `size` bytes to store.
+    ///
+    /// ## Panics
+    /// Panics if `size` is not the size of a [`RegisterValue`]
+    pub const fn new(regno: u32, meta: u32, size: usize) -> Self {
+        assert!(
+            matches!(size, 1 | 2 | 4 | 8 | 16 | 32 | 64),
+            "The size of a register must be the size of a `RegisterValue`"
+        );
+        Self { regno, meta, size }
+    }
+
+    pub const fn regno(&self) -> u32 {
+        self.regno
+    }
+
+    pub const fn meta(&self) -> u32 {
+        self.meta
+    }
+
+    /// The number of bytes needed to store the register
+    pub const fn size(&self) -> usize {
+        self.size
+    }
+}
+
+/// The value of a register, in the native byte order of the debugged thread
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+pub enum RegisterValue {
+    U8(u8),
+    U16(u16),
+    U32(u32),
+    U64(u64),
+    U128(u128),
+    V256([u8; 32]),
+    V512([u8; 64]),
+}
+
+impl RegisterValue {
+    /// A zero value of the size of `reg`
+    pub const fn zeroed(reg: Register) -> Self {
+        match reg.size {
+            1 => Self::U8(0),
+            2 => Self::U16(0),
+            4 => Self::U32(0),
+            8 => Self::U64(0),
+            16 => Self::U128(0),
+            32 => Self::V256([0; 32]),
+            64 => Self::V512([0; 64]),
+            _ => unreachable!(),
+        }
+    }
+
+    /// The number of bytes in the value
+    pub const fn size(&self) -> usize {
+        match self {
+            Self::U8(_) => 1,
+            Self::U16(_) => 2,
+            Self::U32(_) => 4,
+            Self::U64(_) => 8,
+            Self::U128(_) => 16,
+            Self::V256(_) => 32,
+            Self::V512(_) => 64,
+        }
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        match self {
+            Self::U8(v) => bytemuck::bytes_of(v),
+            Self::U16(v) => bytemuck::bytes_of(v),
+            Self::U32(v) => bytemuck::bytes_of(v),
+            Self::U64(v) => bytemuck::bytes_of(v),
+            Self::U128(v) => bytemuck::bytes_of(v),
+            Self::V256(v) => v,
+            Self::V512(v) => v,
+        }
+    }
+
+    pub fn as_bytes_mut(&mut self) -> &mut [u8] {
+        match self {
+            Self::U8(v) => bytemuck::bytes_of_mut(v),
+            Self::U16(v) => bytemuck::bytes_of_mut(v),
+            Self::U32(v) => bytemuck::bytes_of_mut(v),
+            Self::U64(v) => bytemuck::bytes_of_mut(v),
+            Self::U128(v) => bytemuck::bytes_of_mut(v),
+            Self::V256(v) => v,
+            Self::V512(v) => v,
+        }
+    }
+
+    /// The value as an integer, or `None` if it is larger than 16 bytes
+    pub const fn as_u128(&self) -> Option<u128> {
+        match *self {
+            Self::U8(v) => Some(v as u128),
+            Self::U16(v) => Some(v as u128),
+            Self::U32(v) => Some(v as u128),
+            Self::U64(v) => Some(v as u128),
+            Self::U128(v) => Some(v),
+            Self::V256(_) | Self::V512(_) => None,
+        }
+    }
+}
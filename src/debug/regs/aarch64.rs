@@ -0,0 +1,142 @@
+//! Registers of aarch64, numbered as in the DWARF for the Arm 64-bit Architecture.
+
+use super::Register;
+
+/// `x0`
+pub const X0: Register = Register::new(0, 0, 8);
+/// `x1`
+pub const X1: Register = Register::new(1, 0, 8);
+/// `x2`
+pub const X2: Register = Register::new(2, 0, 8);
+/// `x3`
+pub const X3: Register = Register::new(3, 0, 8);
+/// `x4`
+pub const X4: Register = Register::new(4, 0, 8);
+/// `x5`
+pub const X5: Register = Register::new(5, 0, 8);
+/// `x6`
+pub const X6: Register = Register::new(6, 0, 8);
+/// `x7`
+pub const X7: Register = Register::new(7, 0, 8);
+/// `x8`
+pub const X8: Register = Register::new(8, 0, 8);
+/// `x9`
+pub const X9: Register = Register::new(9, 0, 8);
+/// `x10`
+pub const X10: Register = Register::new(10, 0, 8);
+/// `x11`
+pub const X11: Register = Register::new(11, 0, 8);
+/// `x12`
+pub const X12: Register = Register::new(12, 0, 8);
+/// `x13`
+pub const X13: Register = Register::new(13, 0, 8);
+/// `x14`
+pub const X14: Register = Register::new(14, 0, 8);
+/// `x15`
+pub const X15: Register = Register::new(15, 0, 8);
+/// `x16`
+pub const X16: Register = Register::new(16, 0, 8);
+/// `x17`
+pub const X17: Register = Register::new(17, 0, 8);
+/// `x18`
+pub const X18: Register = Register::new(18, 0, 8);
+/// `x19`
+pub const X19: Register = Register::new(19, 0, 8);
+/// `x20`
+pub const X20: Register = Register::new(20, 0, 8);
+/// `x21`
+pub const X21: Register = Register::new(21, 0, 8);
+/// `x22`
+pub const X22: Register = Register::new(22, 0, 8);
+/// `x23`
+pub const X23: Register = Register::new(23, 0, 8);
+/// `x24`
+pub const X24: Register = Register::new(24, 0, 8);
+/// `x25`
+pub const X25: Register = Register::new(25, 0, 8);
+/// `x26`
+pub const X26: Register = Register::new(26, 0, 8);
+/// `x27`
+pub const X27: Register = Register::new(27, 0, 8);
+/// `x28`
+pub const X28: Register = Register::new(28, 0, 8);
+/// `x29`
+pub const X29: Register = Register::new(29, 0, 8);
+/// `x30`
+pub const X30: Register = Register::new(30, 0, 8);
+/// `sp`
+pub const SP: Register = Register::new(31, 0, 8);
+/// `pc`
+pub const PC: Register = Register::new(32, 0, 8);
+/// `tpidr_el0`
+pub const TPIDR_EL0: Register = Register::new(36, 0, 8);
+/// `v0`
+pub const V0: Register = Register::new(64, 0, 16);
+/// `v1`
+pub const V1: Register = Register::new(65, 0, 16);
+/// `v2`
+pub const V2: Register = Register::new(66, 0, 16);
+/// `v3`
+pub const V3: Register = Register::new(67, 0, 16);
+/// `v4`
+pub const V4: Register = Register::new(68, 0, 16);
+/// `v5`
+pub const V5: Register = Register::new(69, 0, 16);
+/// `v6`
+pub const V6: Register = Register::new(70, 0, 16);
+/// `v7`
+pub const V7: Register = Register::new(71, 0, 16);
+/// `v8`
+pub const V8: Register = Register::new(72, 0, 16);
+/// `v9`
+pub const V9: Register = Register::new(73, 0, 16);
+/// `v10`
+pub const V10: Register = Register::new(74, 0, 16);
+/// `v11`
+pub const V11: Register = Register::new(75, 0, 16);
+/// `v12`
+pub const V12: Register = Register::new(76, 0, 16);
+/// `v13`
+pub const V13: Register = Register::new(77, 0, 16);
+/// `v14`
+pub const V14: Register = Register::new(78, 0, 16);
+/// `v15`
+pub const V15: Register = Register::new(79, 0, 16);
+/// `v16`
+pub const V16: Register = Register::new(80, 0, 16);
+/// `v17`
+pub const V17: Register = Register::new(81, 0, 16);
+/// `v18`
+pub const V18: Register = Register::new(82, 0, 16);
+/// `v19`
+pub const V19: Register = Register::new(83, 0, 16);
+/// `v20`
+pub const V20: Register = Register::new(84, 0, 16);
+/// `v21`
+pub const V21: Register = Register::new(85, 0, 16);
+/// `v22`
+pub const V22: Register = Register::new(86, 0, 16);
+/// `v23`
+pub const V23: Register = Register::new(87, 0, 16);
+/// `v24`
+pub const V24: Register = Register::new(88, 0, 16);
+/// `v25`
+pub const V25: Register = Register::new(89, 0, 16);
+/// `v26`
+pub const V26: Register = Register::new(90, 0, 16);
+/// `v27`
+pub const V27: Register = Register::new(91, 0, 16);
+/// `v28`
+pub const V28: Register = Register::new(92, 0, 16);
+/// `v29`
+pub const V29: Register = Register::new(93, 0, 16);
+/// `v30`
+pub const V30: Register = Register::new(94, 0, 16);
+/// `v31`
+pub const V31: Register = Register::new(95, 0, 16);
+
+/// The general purpose registers, together with `sp`, `pc` and `tpidr_el0`, which are saved in a core dump
+pub const GENERAL_PURPOSE: &[Register] = &[
+    X0, X1, X2, X3, X4, X5, X6, X7, X8, X9, X10, X11, X12, X13, X14, X15, X16, X17, X18, X19, X20,
+    X21, X22, X23, X24, X25, X26, X27, X28, X29, X30, SP, PC, TPIDR_EL0,
+];
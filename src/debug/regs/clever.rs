@@ -0,0 +1,63 @@
+//! Registers of Clever-ISA, which are numbered by their register number in the ISA.
+
+use super::Register;
+
+/// `r0`
+pub const R0: Register = Register::new(0, 0, 8);
+/// `r1`
+pub const R1: Register = Register::new(1, 0, 8);
+/// `r2`
+pub const R2: Register = Register::new(2, 0, 8);
+/// `r3`
+pub const R3: Register = Register::new(3, 0, 8);
+/// `r4`
+pub const R4: Register = Register::new(4, 0, 8);
+/// `r5`
+pub const R5: Register = Register::new(5, 0, 8);
+/// `r6`
+pub const R6: Register = Register::new(6, 0, 8);
+/// `r7`
+pub const R7: Register = Register::new(7, 0, 8);
+/// `r8`
+pub const R8: Register = Register::new(8, 0, 8);
+/// `r9`
+pub const R9: Register = Register::new(9, 0, 8);
+/// `r10`
+pub const R10: Register = Register::new(10, 0, 8);
+/// `r11`
+pub const R11: Register = Register::new(11, 0, 8);
+/// `r12`
+pub const R12: Register = Register::new(12, 0, 8);
+/// `r13`
+pub const R13: Register = Register::new(13, 0, 8);
+/// `r14`
+pub const R14: Register = Register::new(14, 0, 8);
+/// `r15`
+pub const R15: Register = Register::new(15, 0, 8);
+/// `ip`
+pub const IP: Register = Register::new(16, 0, 8);
+/// `flags`
+pub const FLAGS: Register = Register::new(17, 0, 8);
+/// `fpcw`
+pub const FPCW: Register = Register::new(19, 0, 8);
+/// `f0`
+pub const F0: Register = Register::new(24, 0, 8);
+/// `f1`
+pub const F1: Register = Register::new(25, 0, 8);
+/// `f2`
+pub const F2: Register = Register::new(26, 0, 8);
+/// `f3`
+pub const F3: Register = Register::new(27, 0, 8);
+/// `f4`
+pub const F4: Register = Register::new(28, 0, 8);
+/// `f5`
+pub const F5: Register = Register::new(29, 0, 8);
+/// `f6`
+pub const F6: Register = Register::new(30, 0, 8);
+/// `f7`
+pub const F7: Register = Register::new(31, 0, 8);
+
+/// The general purpose registers, together with `ip` and `flags`, which are saved in a core dump
+pub const GENERAL_PURPOSE: &[Register] = &[
+    R0, R1, R2, R3, R4, R5, R6, R7, R8, R9, R10, R11, R12, R13, R14, R15, IP, FLAGS,
+];
@@ -0,0 +1,140 @@
+//! Registers of riscv64, numbered as in the RISC-V ELF psABI.
+//!
+//! The psABI does not number the `pc` or the vector registers.
+
+use super::Register;
+
+/// `x0` (`zero`)
+pub const X0: Register = Register::new(0, 0, 8);
+/// `x1` (`ra`)
+pub const X1: Register = Register::new(1, 0, 8);
+/// `x2` (`sp`)
+pub const X2: Register = Register::new(2, 0, 8);
+/// `x3` (`gp`)
+pub const X3: Register = Register::new(3, 0, 8);
+/// `x4` (`tp`)
+pub const X4: Register = Register::new(4, 0, 8);
+/// `x5` (`t0`)
+pub const X5: Register = Register::new(5, 0, 8);
+/// `x6` (`t1`)
+pub const X6: Register = Register::new(6, 0, 8);
+/// `x7` (`t2`)
+pub const X7: Register = Register::new(7, 0, 8);
+/// `x8` (`s0`)
+pub const X8: Register = Register::new(8, 0, 8);
+/// `x9` (`s1`)
+pub const X9: Register = Register::new(9, 0, 8);
+/// `x10` (`a0`)
+pub const X10: Register = Register::new(10, 0, 8);
+/// `x11` (`a1`)
+pub const X11: Register = Register::new(11, 0, 8);
+/// `x12` (`a2`)
+pub const X12: Register = Register::new(12, 0, 8);
+/// `x13` (`a3`)
+pub const X13: Register = Register::new(13, 0, 8);
+/// `x14` (`a4`)
+pub const X14: Register = Register::new(14, 0, 8);
+/// `x15` (`a5`)
+pub const X15: Register = Register::new(15, 0, 8);
+/// `x16` (`a6`)
+pub const X16: Register = Register::new(16, 0, 8);
+/// `x17` (`a7`)
+pub const X17: Register = Register::new(17, 0, 8);
+/// `x18` (`s2`)
+pub const X18: Register = Register::new(18, 0, 8);
+/// `x19` (`s3`)
+pub const X19: Register = Register::new(19, 0, 8);
+/// `x20` (`s4`)
+pub const X20: Register = Register::new(20, 0, 8);
+/// `x21` (`s5`)
+pub const X21: Register = Register::new(21, 0, 8);
+/// `x22` (`s6`)
+pub const X22: Register = Register::new(22, 0, 8);
+/// `x23` (`s7`)
+pub const X23: Register = Register::new(23, 0, 8);
+/// `x24` (`s8`)
+pub const X24: Register = Register::new(24, 0, 8);
+/// `x25` (`s9`)
+pub const X25: Register = Register::new(25, 0, 8);
+/// `x26` (`s10`)
+pub const X26: Register = Register::new(26, 0, 8);
+/// `x27` (`s11`)
+pub const X27: Register = Register::new(27, 0, 8);
+/// `x28` (`t3`)
+pub const X28: Register = Register::new(28, 0, 8);
+/// `x29` (`t4`)
+pub const X29: Register = Register::new(29, 0, 8);
+/// `x30` (`t5`)
+pub const X30: Register = Register::new(30, 0, 8);
+/// `x31` (`t6`)
+pub const X31: Register = Register::new(31, 0, 8);
+/// `f0`, which is 8 bytes with the `D` extension
+pub const F0: Register = Register::new(32, 0, 8);
+/// `f1`, which is 8 bytes with the `D` extension
+pub const F1: Register = Register::new(33, 0, 8);
+/// `f2`, which is 8 bytes with the `D` extension
+pub const F2: Register = Register::new(34, 0, 8);
+/// `f3`, which is 8 bytes with the `D` extension
+pub const F3: Register = Register::new(35, 0, 8);
+/// `f4`, which is 8 bytes with the `D` extension
+pub const F4: Register = Register::new(36, 0, 8);
+/// `f5`, which is 8 bytes with the `D` extension
+pub const F5: Register = Register::new(37, 0, 8);
+/// `f6`, which is 8 bytes with the `D` extension
+pub const F6: Register = Register::new(38, 0, 8);
+/// `f7`, which is 8 bytes with the `D` extension
+pub const F7: Register = Register::new(39, 0, 8);
+/// `f8`, which is 8 bytes with the `D` extension
+pub const F8: Register = Register::new(40, 0, 8);
+/// `f9`, which is 8 bytes with the `D` extension
+pub const F9: Register = Register::new(41, 0, 8);
+/// `f10`, which is 8 bytes with the `D` extension
+pub const F10: Register = Register::new(42, 0, 8);
+/// `f11`, which is 8 bytes with the `D` extension
+pub const F11: Register = Register::new(43, 0, 8);
+/// `f12`, which is 8 bytes with the `D` extension
+pub const F12: Register = Register::new(44, 0, 8);
+/// `f13`, which is 8 bytes with the `D` extension
+pub const F13: Register = Register::new(45, 0, 8);
+/// `f14`, which is 8 bytes with the `D` extension
+pub const F14: Register = Register::new(46, 0, 8);
+/// `f15`, which is 8 bytes with the `D` extension
+pub const F15: Register = Register::new(47, 0, 8);
+/// `f16`, which is 8 bytes with the `D` extension
+pub const F16: Register = Register::new(48, 0, 8);
+/// `f17`, which is 8 bytes with the `D` extension
+pub const F17: Register = Register::new(49, 0, 8);
+/// `f18`, which is 8 bytes with the `D` extension
+pub const F18: Register = Register::new(50, 0, 8);
+/// `f19`, which is 8 bytes with the `D` extension
+pub const F19: Register = Register::new(51, 0, 8);
+/// `f20`, which is 8 bytes with the `D` extension
+pub const F20: Register = Register::new(52, 0, 8);
+/// `f21`, which is 8 bytes with the `D` extension
+pub const F21: Register = Register::new(53, 0, 8);
+/// `f22`, which is 8 bytes with the `D` extension
+pub const F22: Register = Register::new(54, 0, 8);
+/// `f23`, which is 8 bytes with the `D` extension
+pub const F23: Register = Register::new(55, 0, 8);
+/// `f24`, which is 8 bytes with the `D` extension
+pub const F24: Register = Register::new(56, 0, 8);
+/// `f25`, which is 8 bytes with the `D` extension
+pub const F25: Register = Register::new(57, 0, 8);
+/// `f26`, which is 8 bytes with the `D` extension
+pub const F26: Register = Register::new(58, 0, 8);
+/// `f27`, which is 8 bytes with the `D` extension
+pub const F27: Register = Register::new(59, 0, 8);
+/// `f28`, which is 8 bytes with the `D` extension
+pub const F28: Register = Register::new(60, 0, 8);
+/// `f29`, which is 8 bytes with the `D` extension
+pub const F29: Register = Register::new(61, 0, 8);
+/// `f30`, which is 8 bytes with the `D` extension
+pub const F30: Register = Register::new(62, 0, 8);
+/// `f31`, which is 8 bytes with the `D` extension
+pub const F31: Register = Register::new(63, 0, 8);
+
+/// The general purpose registers other than `x0`, which are saved in a core dump
+pub const GENERAL_PURPOSE: &[Register] = &[
+    X1, X2, X3, X4, X5, X6, X7, X8, X9, X10, X11, X12, X13, X14, X15, X16, X17, X18, X19, X20, X21,
+    X22, X23, X24, X25, X26, X27, X28, X29, X30, X31,
+];
@@ -0,0 +1,103 @@
+//! Registers of x86_64, numbered as in the System V psABI.
+//!
+//! Vector registers are accessed as `xmm`, `ymm` or `zmm` registers by the `meta` value, so they are selected by [`xmm`], [`ymm`] and [`zmm`] rather than constants.
+//! `xmm16` through `xmm31`, and the `ymm` and `zmm` views, are only available if the processor supports them.
+
+use super::Register;
+
+/// `rax`
+pub const RAX: Register = Register::new(0, 0, 8);
+/// `rdx`
+pub const RDX: Register = Register::new(1, 0, 8);
+/// `rcx`
+pub const RCX: Register = Register::new(2, 0, 8);
+/// `rbx`
+pub const RBX: Register = Register::new(3, 0, 8);
+/// `rsi`
+pub const RSI: Register = Register::new(4, 0, 8);
+/// `rdi`
+pub const RDI: Register = Register::new(5, 0, 8);
+/// `rbp`
+pub const RBP: Register = Register::new(6, 0, 8);
+/// `rsp`
+pub const RSP: Register = Register::new(7, 0, 8);
+/// `r8`
+pub const R8: Register = Register::new(8, 0, 8);
+/// `r9`
+pub const R9: Register = Register::new(9, 0, 8);
+/// `r10`
+pub const R10: Register = Register::new(10, 0, 8);
+/// `r11`
+pub const R11: Register = Register::new(11, 0, 8);
+/// `r12`
+pub const R12: Register = Register::new(12, 0, 8);
+/// `r13`
+pub const R13: Register = Register::new(13, 0, 8);
+/// `r14`
+pub const R14: Register = Register::new(14, 0, 8);
+/// `r15`
+pub const R15: Register = Register::new(15, 0, 8);
+/// `rip`, the return address column
+pub const RIP: Register = Register::new(16, 0, 8);
+/// `rflags`. Writes to the system flags are ignored, see [`DebugWriteRegister`][crate::sys::debug::DebugWriteRegister]
+pub const RFLAGS: Register = Register::new(49, 0, 8);
+/// `es`, which can be read but not written
+pub const ES: Register = Register::new(50, 0, 8);
+/// `cs`, which can be read but not written
+pub const CS: Register = Register::new(51, 0, 8);
+/// `ss`, which can be read but not written
+pub const SS: Register = Register::new(52, 0, 8);
+/// `ds`, which can be read but not written
+pub const DS: Register = Register::new(53, 0, 8);
+/// `fs`, which can be read but not written
+pub const FS: Register = Register::new(54, 0, 8);
+/// `gs`, which can be read but not written
+pub const GS: Register = Register::new(55, 0, 8);
+/// `fs.base`
+pub const FS_BASE: Register = Register::new(58, 0, 8);
+/// `gs.base`
+pub const GS_BASE: Register = Register::new(59, 0, 8);
+/// `mxcsr`
+pub const MXCSR: Register = Register::new(64, 0, 4);
+/// `fcw`
+pub const FCW: Register = Register::new(65, 0, 2);
+/// `fsw`
+pub const FSW: Register = Register::new(66, 0, 2);
+
+const fn vector_regno(n: u32) -> u32 {
+    match n {
+        0..16 => 17 + n,
+        16..32 => 67 + (n - 16),
+        _ => panic!("x86_64 has 32 vector registers"),
+    }
+}
+
+/// The 16-byte register `xmm{n}`
+///
+/// ## Panics
+/// Panics if `n` is not less than 32
+pub const fn xmm(n: u32) -> Register {
+    Register::new(vector_regno(n), 0, 16)
+}
+
+/// The 32-byte register `ymm{n}`
+///
+/// ## Panics
+/// Panics if `n` is not less than 32
+pub const fn ymm(n: u32) -> Register {
+    Register::new(vector_regno(n), 1, 32)
+}
+
+/// The 64-byte register `zmm{n}`
+///
+/// ## Panics
+/// Panics if `n` is not less than 32
+pub const fn zmm(n: u32) -> Register {
+    Register::new(vector_regno(n), 2, 64)
+}
+
+/// The general purpose registers, together with `rip`, `rflags` and the segment bases, which are saved in a core dump
+pub const GENERAL_PURPOSE: &[Register] = &[
+    RAX, RDX, RCX, RBX, RSI, RDI, RBP, RSP, R8, R9, R10, R11, R12, R13, R14, R15, RIP, RFLAGS,
+    FS_BASE, GS_BASE,
+];
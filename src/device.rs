@@ -0,0 +1,408 @@
+//! Safe interfaces for opening and using devices
+
+use core::{
+    ffi::{c_int, c_long, c_uint, c_ulong, c_void},
+    marker::PhantomData,
+    mem::MaybeUninit,
+};
+
+use alloc::{string::String, vec::Vec};
+
+pub mod loopback;
+pub mod provider;
+
+use crate::{
+    handle::{
+        AsHandle, AsRawHandlePtr, BorrowedHandle, FromRawLiliumHandle, IntoRawLiliumHandle,
+        OwnedHandle,
+    },
+    kstr::read_kstr,
+    result::{Error, Result, ResultExt},
+    sys::{
+        device::{
+            self as sys,
+            udev::{
+                DeviceCommandParameter, DIR_IN, DIR_INOUT, DIR_OUT, PARAM_BUFFER,
+                PARAM_BUFFER_SIZE, PARAM_TY_HANDLE, PARAM_TY_INT, PARAM_TY_KSTR, PARAM_TY_LONG,
+                PARAM_TY_UUID,
+            },
+            DeviceFeature, DeviceHandle,
+        },
+        fs::FileHandle,
+        handle::HandlePtr,
+        io::IOHandle,
+        kstr::{KCSlice, KStrCPtr, KStrPtr},
+        result::SysResult,
+    },
+    uuid::Uuid,
+};
+
+bitflags::bitflags! {
+    /// The modes a [`Feature`] is tested in by [`Device::supports`]
+    #[repr(transparent)]
+    #[derive(Copy, Clone, Hash, PartialEq, Eq, Debug)]
+    pub struct FeatureOptions : u32{
+        const READ = sys::DEVICE_FEATURE_OPTION_READ;
+        const WRITE = sys::DEVICE_FEATURE_OPTION_WRITE;
+        const IGNORE_ACCESS_CONTROL = sys::DEVICE_FEATURE_OPTION_IGNORE_AC;
+    }
+}
+
+/// A named device feature, together with the modes the feature is required in
+#[derive(Copy, Clone, Hash, PartialEq, Eq, Debug)]
+pub struct Feature<'a> {
+    name: &'a str,
+    options: FeatureOptions,
+}
+
+impl<'a> Feature<'a> {
+    pub const fn new(name: &'a str, options: FeatureOptions) -> Self {
+        Self { name, options }
+    }
+
+    pub const fn name(&self) -> &'a str {
+        self.name
+    }
+
+    pub const fn options(&self) -> FeatureOptions {
+        self.options
+    }
+
+    fn into_raw(self) -> DeviceFeature {
+        DeviceFeature {
+            feature_name: KStrCPtr::from_str(self.name),
+            feature_options: self.options.bits(),
+        }
+    }
+}
+
+/// An open device
+#[derive(Debug, Hash, PartialEq, Eq)]
+pub struct Device(OwnedHandle<DeviceHandle>);
+
+impl Device {
+    /// Opens the device with the given `id` in the device scope of the current thread
+    pub fn open(id: Uuid) -> Result<Self> {
+        let mut hdl = MaybeUninit::uninit();
+
+        Error::from_code(unsafe { sys::OpenDevice(hdl.as_mut_ptr(), id) })?;
+
+        Ok(Self(unsafe { OwnedHandle::take_ownership(hdl.assume_init()) }))
+    }
+
+    /// Opens the device that backs the given file (such as a block or character device file)
+    pub fn open_from_file<'a, H: AsHandle<'a, FileHandle>>(file: H) -> Result<Self> {
+        let mut hdl = MaybeUninit::uninit();
+
+        Error::from_code(unsafe { sys::OpenDeviceFromFile(hdl.as_mut_ptr(), file.as_handle()) })?;
+
+        Ok(Self(unsafe { OwnedHandle::take_ownership(hdl.assume_init()) }))
+    }
+
+    /// Opens the device that the stream `hdl` performs I/O on, such as the terminal of a standard stream
+    pub fn open_from_io<'a, H: AsHandle<'a, IOHandle>>(hdl: H) -> Result<Self> {
+        let mut devhdl = MaybeUninit::uninit();
+
+        Error::from_code(unsafe {
+            sys::OpenDeviceFromIOHandle(devhdl.as_mut_ptr(), hdl.as_handle())
+        })?;
+
+        Ok(Self(unsafe {
+            OwnedHandle::take_ownership(devhdl.assume_init())
+        }))
+    }
+
+    /// Takes ownership of the device handle `hdl`.
+    ///
+    /// # Safety
+    /// `hdl` must be a valid device handle that is owned by the caller, and must not be used or closed after this call except through the returned [`Device`].
+    pub const unsafe fn from_handle(hdl: HandlePtr<DeviceHandle>) -> Self {
+        Self(OwnedHandle::take_ownership(hdl))
+    }
+
+    pub fn as_raw(&self) -> HandlePtr<DeviceHandle> {
+        self.0.as_raw()
+    }
+
+    pub fn into_raw(self) -> HandlePtr<DeviceHandle> {
+        self.0.release_ownership()
+    }
+
+    pub fn borrow(&self) -> BorrowedHandle<'_, DeviceHandle> {
+        self.0.borrow()
+    }
+
+    /// Obtains the id of the device
+    pub fn id(&self) -> Result<Uuid> {
+        let mut id = MaybeUninit::uninit();
+
+        Error::from_code(unsafe { sys::GetDeviceId(self.as_raw(), id.as_mut_ptr()) })?;
+
+        Ok(unsafe { id.assume_init() })
+    }
+
+    /// Obtains the user-friendly label of the device
+    pub fn label(&self) -> Result<String> {
+        read_kstr(|kstr| unsafe { sys::GetDeviceLabel(self.as_raw(), kstr) }).map(String::from)
+    }
+
+    /// Obtains the number of bytes which the device reports as "Optimistic",
+    /// IE. performing I/O operations of this size is at least as efficient as performing I/O operations of any smaller size
+    pub fn optimistic_io_size(&self) -> Result<u64> {
+        let mut size = 0;
+
+        Error::from_code(unsafe { sys::GetOptimisticIOSize(self.as_raw(), &mut size) })?;
+
+        Ok(size)
+    }
+
+    /// Tests whether the device supports each of the specified `features` in the requested modes.
+    ///
+    /// Returns `Ok(false)` if any feature is unsupported, or is supported but not in the requested mode.
+    /// Returns `Err(Error::Permission)` if any feature is access controlled and the required right cannot be obtained.
+    pub fn supports(&self, features: &[Feature]) -> Result<bool> {
+        let features = features
+            .iter()
+            .copied()
+            .map(Feature::into_raw)
+            .collect::<Vec<_>>();
+
+        match Error::from_code(unsafe {
+            sys::TestDeviceFeature(self.as_raw(), &KCSlice::from_slice(&features))
+        }) {
+            Ok(()) => Ok(true),
+            Err(Error::UnsupportedOperation | Error::InvalidOperation) => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Issues the device command `cmd` to the device, with the given arguments.
+    ///
+    /// Returns the non-negative result of the command on success. The meaning of the value is command specific.
+    pub fn invoke<A: CommandArgs>(&self, cmd: &DeviceCommand<A>, args: A) -> Result<usize> {
+        let code = unsafe { args.issue(self.as_raw(), &cmd.id) };
+        code.into_value()
+    }
+}
+
+impl AsRawHandlePtr for Device {
+    type Handle = DeviceHandle;
+    fn as_raw_handle_ptr(&self) -> HandlePtr<DeviceHandle> {
+        self.as_raw()
+    }
+}
+
+impl IntoRawLiliumHandle for Device {
+    fn into_raw_lilium_handle(self) -> HandlePtr<DeviceHandle> {
+        self.into_raw()
+    }
+}
+
+impl FromRawLiliumHandle for Device {
+    unsafe fn from_raw_lilium_handle(hdl: HandlePtr<DeviceHandle>) -> Self {
+        unsafe { Self::from_handle(hdl) }
+    }
+}
+
+unsafe impl<'a> AsHandle<'a, DeviceHandle> for &'a Device {
+    fn as_handle(&self) -> HandlePtr<DeviceHandle> {
+        self.as_raw()
+    }
+}
+
+/// A typed device command, which can be issued to a device by [`Device::invoke`].
+pub struct DeviceCommand<A> {
+    id: Uuid,
+    _args: PhantomData<fn(A)>,
+}
+
+impl<A> Clone for DeviceCommand<A> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<A> Copy for DeviceCommand<A> {}
+
+impl<A> core::fmt::Debug for DeviceCommand<A> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_tuple("DeviceCommand").field(&self.id).finish()
+    }
+}
+
+impl<A: CommandArgs> DeviceCommand<A> {
+    /// Declares the device command with the given `id`, which accepts arguments `A`.
+    ///
+    /// # Safety
+    /// The parameters of the command designated by `id` must match [`A::signature`][CommandArgs::signature] on every device it is issued to.
+    /// In particular, the kernel may write through any output parameter according to the signature the command was registered with.
+    pub const unsafe fn new(id: Uuid) -> Self {
+        Self {
+            id,
+            _args: PhantomData,
+        }
+    }
+
+    pub const fn id(&self) -> Uuid {
+        self.id
+    }
+}
+
+mod private {
+    pub trait Sealed {}
+}
+
+/// A single parameter to a device command.
+///
+/// # Safety
+/// [`CommandArg::Raw`] must be the type the kernel passes or expects for a parameter with the direction [`CommandArg::DIRECTION`] and type [`CommandArg::TYPE`],
+///  as described in [`udev`][crate::sys::device::udev].
+pub unsafe trait CommandArg: private::Sealed {
+    /// The type passed to the kernel
+    type Raw: Copy;
+    /// The direction of the parameter (one of [`DIR_IN`], [`DIR_OUT`], or [`DIR_INOUT`])
+    const DIRECTION: u32;
+    /// The type of the parameter (one of the `PARAM_*` constants)
+    const TYPE: u32;
+
+    fn into_raw(self) -> Self::Raw;
+}
+
+macro_rules! impl_command_arg{
+    ($(impl$(<$($gen:tt),*>)? for $ty:ty: ($dir:expr, $param_ty:expr) => $raw:ty = |$this:ident| $conv:expr;)*) => {
+        $(
+            impl$(<$($gen),*>)? private::Sealed for $ty{}
+            unsafe impl$(<$($gen),*>)? CommandArg for $ty{
+                type Raw = $raw;
+                const DIRECTION: u32 = $dir;
+                const TYPE: u32 = $param_ty;
+
+                fn into_raw(self) -> $raw{
+                    let $this = self;
+                    $conv
+                }
+            }
+        )*
+    }
+}
+
+impl_command_arg! {
+    impl for c_int: (DIR_IN, PARAM_TY_INT) => c_int = |this| this;
+    impl for c_uint: (DIR_IN, PARAM_TY_INT) => c_uint = |this| this;
+    impl for c_long: (DIR_IN, PARAM_TY_LONG) => c_long = |this| this;
+    impl for c_ulong: (DIR_IN, PARAM_TY_LONG) => c_ulong = |this| this;
+    impl<'a> for &'a Uuid: (DIR_IN, PARAM_TY_UUID) => *const Uuid = |this| this;
+    impl<'a> for &'a str: (DIR_IN, PARAM_TY_KSTR) => KStrCPtr = |this| KStrCPtr::from_str(this);
+    impl<'a> for &'a [u8]: (DIR_IN, PARAM_BUFFER) => *const c_void = |this| this.as_ptr().cast();
+    impl<T> for HandlePtr<T>: (DIR_IN, PARAM_TY_HANDLE) => HandlePtr<T> = |this| this;
+    impl<'a, T> for BorrowedHandle<'a, T>: (DIR_IN, PARAM_TY_HANDLE) => HandlePtr<T> = |this| this.as_raw();
+    impl for BufferSize: (DIR_IN, PARAM_BUFFER_SIZE) => c_ulong = |this| this.0 as c_ulong;
+    impl<'a> for &'a mut c_int: (DIR_INOUT, PARAM_TY_INT) => *mut c_int = |this| this;
+    impl<'a> for &'a mut c_uint: (DIR_INOUT, PARAM_TY_INT) => *mut c_uint = |this| this;
+    impl<'a> for &'a mut c_long: (DIR_INOUT, PARAM_TY_LONG) => *mut c_long = |this| this;
+    impl<'a> for &'a mut c_ulong: (DIR_INOUT, PARAM_TY_LONG) => *mut c_ulong = |this| this;
+    impl<'a> for &'a mut Uuid: (DIR_INOUT, PARAM_TY_UUID) => *mut Uuid = |this| this;
+    impl<'a> for &'a mut [u8]: (DIR_INOUT, PARAM_BUFFER) => *mut c_void = |this| this.as_mut_ptr().cast();
+    impl<'a, T> for &'a mut HandlePtr<T>: (DIR_INOUT, PARAM_TY_HANDLE) => *mut HandlePtr<T> = |this| this;
+    impl<'a> for &'a mut KStrPtr: (DIR_OUT, PARAM_TY_KSTR) => *mut KStrPtr = |this| this;
+}
+
+/// The size of a buffer parameter. This is passed to the device as a separate parameter, which is designated by the buffer parameter.
+///
+/// Each buffer parameter is paired with the first `BufferSize` after it that is not paired with an earlier buffer, so `(&[u8], BufferSize)` and `(&[u8], &mut [u8], BufferSize, BufferSize)` are both valid.
+/// Every buffer must be paired with a size, and every size with a buffer, or [`CommandArgs::signature`] fails to compile.
+#[derive(Copy, Clone, Hash, PartialEq, Eq, Debug)]
+pub struct BufferSize(pub usize);
+
+/// The full set of parameters to a device command.
+///
+/// This is implemented for tuples of up to 8 [`CommandArg`]s.
+///
+/// # Safety
+/// [`CommandArgs::issue`] must pass each argument to [`IssueDeviceCommand`][sys::IssueDeviceCommand] in order, as its [`CommandArg::Raw`] type.
+pub unsafe trait CommandArgs: private::Sealed {
+    /// The number of parameters
+    const LEN: usize;
+
+    /// Returns the signature of the command, suitable for registering a command with the same parameters
+    fn signature() -> Vec<DeviceCommandParameter>;
+
+    /// Issues the command to `hdl`
+    ///
+    /// # Safety
+    /// The arguments must be valid for `cmd` on the device designated by `hdl`.
+    unsafe fn issue(self, hdl: HandlePtr<DeviceHandle>, cmd: &Uuid) -> SysResult;
+}
+
+/// Checks that the buffer and buffer size parameters among `types` can be paired as described by [`BufferSize`]
+const fn buffer_sizes_paired(types: &[u32]) -> bool {
+    let mut unpaired = 0usize;
+    let mut i = 0;
+    while i < types.len() {
+        if types[i] == PARAM_BUFFER {
+            unpaired += 1;
+        } else if types[i] == PARAM_BUFFER_SIZE {
+            if unpaired == 0 {
+                return false;
+            }
+            unpaired -= 1;
+        }
+        i += 1;
+    }
+    unpaired == 0
+}
+
+/// Sets the `related` field of each buffer parameter to the index of its buffer size parameter, as described by [`BufferSize`]
+fn link_buffer_sizes(sig: &mut [DeviceCommandParameter]) {
+    for size in 0..sig.len() {
+        if sig[size].ty == PARAM_BUFFER_SIZE {
+            // Buffers are only linked to sizes after them, so a linked buffer never has `related == 0`
+            let buf = sig[..size]
+                .iter()
+                .position(|p| p.ty == PARAM_BUFFER && p.related == 0)
+                .expect("buffer size parameter without a buffer");
+            sig[buf].related = size as c_ulong;
+        }
+    }
+}
+
+macro_rules! impl_command_args{
+    ($($arg:ident),*) => {
+        impl<$($arg: CommandArg),*> private::Sealed for ($($arg,)*){}
+        unsafe impl<$($arg: CommandArg),*> CommandArgs for ($($arg,)*){
+            const LEN: usize = <[&str]>::len(&[$(stringify!($arg)),*]);
+
+            fn signature() -> Vec<DeviceCommandParameter>{
+                const {
+                    assert!(
+                        buffer_sizes_paired(&[$($arg::TYPE),*]),
+                        "every buffer parameter must be paired with a `BufferSize` after it",
+                    )
+                };
+                let mut sig = alloc::vec![$(DeviceCommandParameter{
+                    direction: $arg::DIRECTION,
+                    ty: $arg::TYPE,
+                    related: 0,
+                }),*];
+                link_buffer_sizes(&mut sig);
+                sig
+            }
+
+            #[allow(non_snake_case)]
+            unsafe fn issue(self, hdl: HandlePtr<DeviceHandle>, cmd: &Uuid) -> SysResult{
+                let ($($arg,)*) = self;
+                unsafe{sys::IssueDeviceCommand(hdl, cmd, $($arg.into_raw()),*)}
+            }
+        }
+    }
+}
+
+impl_command_args!();
+impl_command_args!(A);
+impl_command_args!(A, B);
+impl_command_args!(A, B, C);
+impl_command_args!(A, B, C, D);
+impl_command_args!(A, B, C, D, E);
+impl_command_args!(A, B, C, D, E, F);
+impl_command_args!(A, B, C, D, E, F, G);
+impl_command_args!(A, B, C, D, E, F, G, H);
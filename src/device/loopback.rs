@@ -0,0 +1,235 @@
+//! Loopback block devices, which expose a file or other stream as a block device so that a filesystem image can be mounted.
+//!
+//! ## Example
+//! ```rust,ignore
+//! let image = mount_image(Path::new("/home/user/disk.img"), "/mnt/disk", &MountOptions::new())?;
+//! // The filesystem in `disk.img` is accessible at `/mnt/disk`
+//! image.unmount()?;
+//! ```
+
+use core::{ffi::c_ulong, mem::ManuallyDrop, mem::MaybeUninit};
+
+use crate::{
+    fs::{os_path, FileAccess, FileOp, MountOptions, OwnedFile, Path, PathBuf},
+    handle::{AsHandle, BorrowedHandle},
+    io::{IOHandle, SeekFrom},
+    kstr::OsStr,
+    result::{Error, Result},
+    sys::{
+        device::{self as sys, BlockDeviceConfiguration},
+        handle::HandlePtr,
+        kstr::KStrCPtr,
+    },
+    uuid::Uuid,
+};
+
+use super::Device;
+
+#[derive(Debug)]
+enum Backing<'a> {
+    Path(&'a Path),
+    Stream(BorrowedHandle<'a, IOHandle>),
+}
+
+/// The backing storage of a loopback device, and the range of it that is exposed by the device
+#[derive(Debug)]
+pub struct Image<'a> {
+    backing: Backing<'a>,
+    base: u64,
+    extent: Option<u64>,
+}
+
+impl<'a> Image<'a> {
+    /// An image stored in the file at `path`, which is opened for reading and writing when the image is mounted
+    pub fn path<P: AsRef<Path> + ?Sized>(path: &'a P) -> Self {
+        Self {
+            backing: Backing::Path(path.as_ref()),
+            base: 0,
+            extent: None,
+        }
+    }
+
+    /// An image read from and written to `hdl`, such as an open file or a [`MemoryBuffer`][crate::io::MemoryBuffer].
+    ///
+    /// `hdl` must be seekable.
+    pub fn stream<H: AsHandle<'a, IOHandle>>(hdl: H) -> Self {
+        Self {
+            backing: Backing::Stream(unsafe { BorrowedHandle::from_raw(hdl.as_handle()) }),
+            base: 0,
+            extent: None,
+        }
+    }
+
+    /// Exposes only the `extent` bytes of the image starting at offset `base`, such as a single partition of a disk image.
+    ///
+    /// A nonzero `base` requires the backing stream to be random access.
+    pub const fn range(mut self, base: u64, extent: u64) -> Self {
+        self.base = base;
+        self.extent = Some(extent);
+        self
+    }
+}
+
+impl<'a> From<&'a Path> for Image<'a> {
+    fn from(path: &'a Path) -> Self {
+        Self::path(path)
+    }
+}
+
+impl<'a, H: AsHandle<'a, IOHandle>> From<H> for Image<'a> {
+    fn from(hdl: H) -> Self {
+        Self::stream(hdl)
+    }
+}
+
+/// A filesystem image mounted by [`mount_image`].
+///
+/// When dropped, the filesystem is unmounted, and the loopback device is removed. Errors doing so are ignored - use [`MountedImage::unmount`] to handle them.
+#[derive(Debug)]
+pub struct MountedImage<'a> {
+    mount_point: PathBuf,
+    id: Uuid,
+    device: ManuallyDrop<Device>,
+    _file: Option<OwnedFile>,
+    backing: BorrowedHandle<'a, IOHandle>,
+    unmounted: bool,
+    removed: bool,
+}
+
+impl MountedImage<'_> {
+    /// The id of the loopback device
+    pub const fn id(&self) -> Uuid {
+        self.id
+    }
+
+    /// The loopback device
+    pub fn device(&self) -> &Device {
+        &self.device
+    }
+
+    /// The path the filesystem is mounted at
+    pub fn mount_point(&self) -> &Path {
+        &self.mount_point
+    }
+
+    /// Unmounts the filesystem, and removes the loopback device.
+    ///
+    /// If the filesystem cannot be unmounted, the error is returned, and the loopback device is removed anyway.
+    pub fn unmount(mut self) -> Result<()> {
+        self.release()
+    }
+
+    fn release(&mut self) -> Result<()> {
+        if !self.unmounted {
+            crate::fs::unmount(&self.mount_point)?;
+            self.unmounted = true;
+        }
+        self.remove_device()
+    }
+
+    /// Closes the handle to the loopback device, and removes it. Does nothing after the first call.
+    fn remove_device(&mut self) -> Result<()> {
+        if self.removed {
+            return Ok(());
+        }
+        self.removed = true;
+        unsafe { ManuallyDrop::drop(&mut self.device) };
+        Error::from_code(unsafe { sys::RemoveBlockDevice(self.backing.as_raw()) })
+    }
+}
+
+impl Drop for MountedImage<'_> {
+    fn drop(&mut self) {
+        if self.release().is_err() {
+            // Don't leak the device if the filesystem could not be unmounted
+            let _ = self.remove_device();
+        }
+    }
+}
+
+fn create_block_device(
+    backing: HandlePtr<IOHandle>,
+    base: u64,
+    extent: u64,
+) -> Result<(Device, Uuid)> {
+    let extent = i64::try_from(extent).map_err(|_| Error::InvalidOperation)?;
+    let mut hdl = MaybeUninit::uninit();
+    let mut id = Uuid::NIL;
+    let cfg = BlockDeviceConfiguration {
+        label: KStrCPtr::empty(),
+        acl: HandlePtr::null(),
+        optimistic_io_size: 0,
+        base: base as c_ulong,
+        extent,
+    };
+
+    Error::from_code(unsafe {
+        sys::CreateBlockDevice(hdl.as_mut_ptr(), &mut id, backing, &cfg, HandlePtr::null())
+    })?;
+
+    Ok((unsafe { Device::from_handle(hdl.assume_init()) }, id))
+}
+
+/// Exposes `image` as a block device, and mounts the filesystem on it at `mount_point`.
+///
+/// If the image does not specify a [range][Image::range], the device covers the image from its start to its current end.
+///
+/// The returned [`MountedImage`] unmounts the filesystem and removes the device when it is dropped.
+///
+/// ## Errors
+/// Returns an error if the backing stream of `image` is not seekable, or a nonzero base is specified for a stream that is not random access.
+///
+/// If the filesystem cannot be mounted, the device is removed before the error is returned.
+pub fn mount_image<'a, I: Into<Image<'a>>, P: AsRef<OsStr>>(
+    image: I,
+    mount_point: P,
+    options: &MountOptions,
+) -> Result<MountedImage<'a>> {
+    let mount_point = os_path(&mount_point)?;
+    let Image {
+        backing,
+        base,
+        extent,
+    } = image.into();
+
+    let (file, backing) = match backing {
+        Backing::Path(path) => {
+            let file = OwnedFile::open_with(
+                path,
+                FileAccess::READ | FileAccess::WRITE,
+                FileOp::DataAccess,
+            )?;
+            let hdl = file.as_io_handle().as_raw();
+            (Some(file), unsafe { BorrowedHandle::from_raw(hdl) })
+        }
+        Backing::Stream(hdl) => (None, hdl),
+    };
+
+    let extent = match extent {
+        Some(extent) => extent,
+        None => {
+            let pos = backing.seek(SeekFrom::Current(0))?;
+            let end = backing.seek(SeekFrom::End(0))?;
+            backing.seek(SeekFrom::Start(pos))?;
+            end.saturating_sub(base)
+        }
+    };
+
+    let (device, id) = create_block_device(backing.as_raw(), base, extent)?;
+
+    if let Err(e) = crate::fs::mount(mount_point, id, options) {
+        drop(device);
+        let _ = Error::from_code(unsafe { sys::RemoveBlockDevice(backing.as_raw()) });
+        return Err(e);
+    }
+
+    Ok(MountedImage {
+        mount_point: mount_point.to_path_buf(),
+        id,
+        device: ManuallyDrop::new(device),
+        _file: file,
+        backing,
+        unmounted: false,
+        removed: false,
+    })
+}
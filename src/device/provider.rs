@@ -0,0 +1,449 @@
+//! Support for implementing device commands in userspace.
+//!
+//! A [`Provider`] collects callbacks for a set of commands on a device, and registers them with the kernel.
+//! When another thread issues one of the commands, the kernel invokes a dispatcher installed by this module,
+//!  which decodes the parameters according to the signature the command was registered with, and passes a [`CommandContext`] to the callback.
+//!
+//! Registered commands remain registered until the device is removed, and the callbacks are never dropped.
+//! Each registration has its own stack, which the dispatcher uses to find the callback, so commands with the same id can be provided for several devices.
+
+use core::{
+    ffi::{c_int, c_long, c_uint, c_ulong, c_void, VaList},
+    mem::{size_of, MaybeUninit},
+    sync::atomic::{AtomicPtr, Ordering},
+};
+
+use alloc::{boxed::Box, vec::Vec};
+
+use crate::{
+    handle::BorrowedHandle,
+    result::{Error, Result},
+    sys::{
+        device::{
+            self as sys,
+            udev::{
+                DeviceCommandParameter, DIR_IN, DIR_INOUT, DIR_OUT, PARAM_BUFFER,
+                PARAM_BUFFER_SIZE, PARAM_TY_HANDLE, PARAM_TY_INT, PARAM_TY_KSTR, PARAM_TY_LONG,
+                PARAM_TY_UUID,
+            },
+        },
+        handle::{Handle, HandlePtr},
+        kstr::{KStrCPtr, KStrPtr},
+        permission::SecurityContext,
+        result::{errors::UNSUPPORTED_OPERATION, SysResult},
+    },
+    uuid::Uuid,
+};
+
+use super::{CommandArgs, DeviceCommand};
+
+/// The default size of the stack each command callback runs on
+pub const DEFAULT_STACK_SIZE: usize = 64 * 1024;
+
+type Callback = dyn Fn(&mut CommandContext) -> Result<usize> + Send + Sync;
+
+struct Command {
+    device: Uuid,
+    id: Uuid,
+    signature: Vec<DeviceCommandParameter>,
+    callback: Box<Callback>,
+}
+
+/// A command registered for a device by this process.
+///
+/// The kernel does not tell the dispatcher which device a command was issued to, but it runs each registered command on the stack it was registered with.
+/// The entry is found from the stack the dispatcher runs on, so the same command id can be registered for several devices.
+struct Entry {
+    stack: *mut [MaybeUninit<u8>],
+    /// The command, or null if registering it failed
+    cmd: AtomicPtr<Command>,
+    next: *mut Entry,
+}
+
+impl Entry {
+    fn contains(&self, addr: usize) -> bool {
+        let base = self.stack.cast::<u8>() as usize;
+        (base..base + self.stack.len()).contains(&addr)
+    }
+}
+
+/// The list of every command registered by this process.
+///
+/// Entries are never removed, as the dispatcher may be traversing them on another thread. The command and stack of an entry whose registration failed are freed.
+static COMMANDS: AtomicPtr<Entry> = AtomicPtr::new(core::ptr::null_mut());
+
+fn publish(entry: &'static mut Entry) -> &'static Entry {
+    let mut head = COMMANDS.load(Ordering::Relaxed);
+    loop {
+        entry.next = head;
+        match COMMANDS.compare_exchange_weak(head, entry, Ordering::Release, Ordering::Relaxed) {
+            Ok(_) => break entry,
+            Err(cur) => head = cur,
+        }
+    }
+}
+
+/// Frees the command and stack of `entry`, after registering the command failed.
+///
+/// # Safety
+/// The kernel must never dispatch a command on the stack of `entry`
+unsafe fn unpublish(entry: &Entry) {
+    let cmd = entry.cmd.swap(core::ptr::null_mut(), Ordering::AcqRel);
+    drop(unsafe { Box::from_raw(cmd) });
+    drop(unsafe { Box::from_raw(entry.stack) });
+}
+
+/// Finds the command whose stack contains `addr`, which is an address on the stack of the current dispatch
+fn lookup(addr: usize) -> Option<&'static Command> {
+    let mut cur = COMMANDS.load(Ordering::Acquire);
+    while let Some(entry) = unsafe { cur.as_ref() } {
+        if entry.contains(addr) {
+            return unsafe { entry.cmd.load(Ordering::Acquire).as_ref() };
+        }
+        cur = entry.next;
+    }
+    None
+}
+
+#[derive(Copy, Clone)]
+enum RawParam {
+    Int(c_int),
+    Word(c_ulong),
+    Str(KStrCPtr),
+    Ptr(*mut c_void),
+}
+
+impl RawParam {
+    unsafe fn read(param: &DeviceCommandParameter, args: &mut VaList) -> Self {
+        unsafe {
+            match (param.direction, param.ty) {
+                (DIR_IN, PARAM_TY_INT) => Self::Int(args.next_arg::<c_int>()),
+                (DIR_IN, PARAM_TY_LONG | PARAM_BUFFER_SIZE | PARAM_TY_HANDLE) => {
+                    Self::Word(args.next_arg::<c_ulong>())
+                }
+                // `VaList` cannot read aggregates, so a `KStrCPtr` is read as its two fields in order
+                (DIR_IN, PARAM_TY_KSTR) => {
+                    let str_ptr = args.next_arg::<*const u8>();
+                    let len = args.next_arg::<usize>();
+                    Self::Str(KStrCPtr { str_ptr, len })
+                }
+                _ => Self::Ptr(args.next_arg::<*mut c_void>()),
+            }
+        }
+    }
+}
+
+unsafe extern "C" fn dispatch(
+    cmdid: *const Uuid,
+    callctx: HandlePtr<SecurityContext>,
+    mut args: ...
+) -> SysResult {
+    let id = unsafe { *cmdid };
+    let marker = 0u8;
+    let Some(cmd) = lookup(core::ptr::addr_of!(marker) as usize) else {
+        return UNSUPPORTED_OPERATION;
+    };
+
+    let params = cmd
+        .signature
+        .iter()
+        .map(|param| unsafe { RawParam::read(param, &mut args) })
+        .collect::<Vec<_>>();
+
+    let mut ctx = CommandContext {
+        id,
+        security_context: unsafe { BorrowedHandle::from_raw(callctx) },
+        signature: &cmd.signature,
+        params: &params,
+    };
+
+    match (cmd.callback)(&mut ctx) {
+        Ok(val) => val as SysResult,
+        Err(e) => e.into_code(),
+    }
+}
+
+mod private {
+    pub trait Sealed {}
+}
+
+/// A scalar value that can be passed as a device command parameter, and accessed through a [`CommandContext`].
+///
+/// # Safety
+/// [`ParamValue::TYPE`] must designate a parameter type that the kernel passes with the same size and representation as `Self`.
+pub unsafe trait ParamValue: Copy + private::Sealed {
+    /// The type of the parameter (one of the `PARAM_*` constants)
+    const TYPE: u32;
+}
+
+macro_rules! impl_param_value {
+    ($($ty:ty => $param_ty:expr),* $(,)?) => {
+        $(
+            impl private::Sealed for $ty {}
+            unsafe impl ParamValue for $ty {
+                const TYPE: u32 = $param_ty;
+            }
+        )*
+    };
+}
+
+impl_param_value! {
+    c_int => PARAM_TY_INT,
+    c_uint => PARAM_TY_INT,
+    c_long => PARAM_TY_LONG,
+    c_ulong => PARAM_TY_LONG,
+    Uuid => PARAM_TY_UUID,
+}
+
+impl<T> private::Sealed for HandlePtr<T> {}
+unsafe impl<T> ParamValue for HandlePtr<T> {
+    const TYPE: u32 = PARAM_TY_HANDLE;
+}
+
+/// A view of a device command invocation, passed to the callback registered for the command.
+///
+/// Parameters are accessed by index. Each accessor returns `None` if the index is out of range,
+///  or if the parameter was registered with a different type or direction than the accessor expects.
+pub struct CommandContext<'a> {
+    id: Uuid,
+    security_context: BorrowedHandle<'a, SecurityContext>,
+    signature: &'a [DeviceCommandParameter],
+    params: &'a [RawParam],
+}
+
+impl<'a> CommandContext<'a> {
+    /// The id of the command being invoked
+    pub fn id(&self) -> Uuid {
+        self.id
+    }
+
+    /// The security context of the thread that issued the command
+    pub fn security_context(&self) -> BorrowedHandle<'a, SecurityContext> {
+        self.security_context
+    }
+
+    /// The number of parameters the command was registered with
+    pub fn param_count(&self) -> usize {
+        self.params.len()
+    }
+
+    fn param(&self, n: usize, dirs: &[u32], ty: u32) -> Option<RawParam> {
+        let sig = self.signature.get(n)?;
+        (sig.ty == ty && dirs.contains(&sig.direction)).then(|| self.params[n])
+    }
+
+    fn buffer_len(&self, n: usize) -> Option<usize> {
+        let size = self.signature[n].related as usize;
+        match self.param(size, &[DIR_IN], PARAM_BUFFER_SIZE)? {
+            RawParam::Word(len) => Some(len as usize),
+            _ => None,
+        }
+    }
+
+    /// Reads the input parameter `n`
+    pub fn get<T: ParamValue>(&self, n: usize) -> Option<T> {
+        match self.param(n, &[DIR_IN], T::TYPE)? {
+            RawParam::Int(val) if size_of::<T>() == size_of::<c_int>() => {
+                Some(unsafe { core::mem::transmute_copy(&val) })
+            }
+            RawParam::Word(val) if size_of::<T>() == size_of::<c_ulong>() => {
+                Some(unsafe { core::mem::transmute_copy(&val) })
+            }
+            RawParam::Ptr(ptr) => Some(unsafe { ptr.cast::<T>().read() }),
+            _ => None,
+        }
+    }
+
+    /// Obtains the handle passed as parameter `n`.
+    ///
+    /// The handle is only valid for the duration of the command.
+    pub fn handle(&self, n: usize) -> Option<HandlePtr<Handle>> {
+        self.get(n)
+    }
+
+    /// Accesses the bidirectional parameter `n`
+    pub fn get_mut<T: ParamValue>(&mut self, n: usize) -> Option<&mut T> {
+        match self.param(n, &[DIR_INOUT], T::TYPE)? {
+            RawParam::Ptr(ptr) => Some(unsafe { &mut *ptr.cast::<T>() }),
+            _ => None,
+        }
+    }
+
+    /// Writes `val` to the output or bidirectional parameter `n`.
+    ///
+    /// Returns `None` without writing anything if the parameter is not an output of type `T`.
+    pub fn set<T: ParamValue>(&mut self, n: usize, val: T) -> Option<()> {
+        match self.param(n, &[DIR_OUT, DIR_INOUT], T::TYPE)? {
+            RawParam::Ptr(ptr) => {
+                unsafe { ptr.cast::<T>().write(val) };
+                Some(())
+            }
+            _ => None,
+        }
+    }
+
+    /// Obtains the string passed as the input parameter `n`.
+    ///
+    /// Returns [`Error::InvalidOperation`] if the parameter is not an input string, and [`Error::InvalidString`] if the caller passed a string that is not valid UTF-8.
+    pub fn str(&self, n: usize) -> Result<&'a str> {
+        let Some(RawParam::Str(st)) = self.param(n, &[DIR_IN], PARAM_TY_KSTR) else {
+            return Err(Error::InvalidOperation);
+        };
+        // The string comes from the calling process, so it cannot be trusted to be UTF-8
+        core::str::from_utf8(unsafe { core::slice::from_raw_parts(st.str_ptr, st.len) })
+            .map_err(|_| Error::InvalidString)
+    }
+
+    /// Writes `st` to the output string parameter `n`.
+    ///
+    /// If the buffer provided by the caller is too small, the length required is stored in the parameter, and an [`Error::InsufficientLength`] is returned.
+    /// The caller may then issue the command again with a larger buffer.
+    ///
+    /// Returns [`Error::InvalidOperation`] if the parameter is not an output string.
+    pub fn write_str(&mut self, n: usize, st: &str) -> Result<()> {
+        let Some(RawParam::Ptr(ptr)) = self.param(n, &[DIR_OUT, DIR_INOUT], PARAM_TY_KSTR) else {
+            return Err(Error::InvalidOperation);
+        };
+        let out = unsafe { &mut *ptr.cast::<KStrPtr>() };
+
+        let avail = out.len;
+        out.len = st.len();
+        if avail < st.len() {
+            return Err(Error::InsufficientLength);
+        }
+        unsafe { core::ptr::copy_nonoverlapping(st.as_ptr(), out.str_ptr, st.len()) };
+        Ok(())
+    }
+
+    /// Obtains the input or bidirectional buffer parameter `n`.
+    ///
+    /// The length of the buffer is given by the buffer size parameter designated by the buffer's signature.
+    pub fn buffer(&self, n: usize) -> Option<&[u8]> {
+        let ptr = match self.param(n, &[DIR_IN, DIR_INOUT], PARAM_BUFFER)? {
+            RawParam::Ptr(ptr) => ptr,
+            _ => return None,
+        };
+        let len = self.buffer_len(n)?;
+        Some(unsafe { core::slice::from_raw_parts(ptr.cast(), len) })
+    }
+
+    /// Obtains the output or bidirectional buffer parameter `n`.
+    ///
+    /// The contents of an output-only buffer are unspecified.
+    pub fn buffer_mut(&mut self, n: usize) -> Option<&mut [u8]> {
+        let ptr = match self.param(n, &[DIR_OUT, DIR_INOUT], PARAM_BUFFER)? {
+            RawParam::Ptr(ptr) => ptr,
+            _ => return None,
+        };
+        let len = self.buffer_len(n)?;
+        Some(unsafe { core::slice::from_raw_parts_mut(ptr.cast(), len) })
+    }
+}
+
+/// Builds the set of commands a process implements for a device.
+pub struct Provider {
+    device: Uuid,
+    stack_size: usize,
+    commands: Vec<Command>,
+}
+
+impl core::fmt::Debug for Provider {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Provider")
+            .field("device", &self.device)
+            .field("stack_size", &self.stack_size)
+            .field(
+                "commands",
+                &self.commands.iter().map(|cmd| cmd.id).collect::<Vec<_>>(),
+            )
+            .finish()
+    }
+}
+
+impl Provider {
+    /// Starts building the commands for the device with the given `id`
+    pub fn new(device: Uuid) -> Self {
+        Self {
+            device,
+            stack_size: DEFAULT_STACK_SIZE,
+            commands: Vec::new(),
+        }
+    }
+
+    /// Sets the size of the stack allocated for each command callback.
+    ///
+    /// The default is [`DEFAULT_STACK_SIZE`].
+    pub fn stack_size(mut self, size: usize) -> Self {
+        self.stack_size = size;
+        self
+    }
+
+    /// Adds a command with the parameters of `A`, which invokes `f` when issued.
+    pub fn command<A: CommandArgs, F>(self, cmd: &DeviceCommand<A>, f: F) -> Self
+    where
+        F: Fn(&mut CommandContext) -> Result<usize> + Send + Sync + 'static,
+    {
+        self.command_with_signature(cmd.id(), A::signature(), f)
+    }
+
+    /// Adds a command with the id `id` and the parameters described by `signature`, which invokes `f` when issued.
+    ///
+    /// The value returned by `f` on success is returned to the thread that issued the command, and must not exceed `isize::MAX`.
+    /// A panic in `f` aborts the process.
+    pub fn command_with_signature<F>(
+        mut self,
+        id: Uuid,
+        signature: Vec<DeviceCommandParameter>,
+        f: F,
+    ) -> Self
+    where
+        F: Fn(&mut CommandContext) -> Result<usize> + Send + Sync + 'static,
+    {
+        self.commands.push(Command {
+            device: self.device,
+            id,
+            signature,
+            callback: Box::new(f),
+        });
+        self
+    }
+
+    /// Registers each command with the kernel, and returns the ids of the commands in the order they were added.
+    ///
+    /// Each command is made available to the dispatcher before it is registered, so it can be issued as soon as the kernel registers it.
+    /// If registering a command fails, the commands registered before it remain registered, and the failed command is freed.
+    pub fn register(self) -> Result<Vec<Uuid>> {
+        let mut ids = Vec::with_capacity(self.commands.len());
+        for cmd in self.commands {
+            let mut id = cmd.id;
+            let cmd = Box::into_raw(Box::new(cmd));
+            let stack = Box::into_raw(Box::<[u8]>::new_uninit_slice(self.stack_size));
+            let entry = Box::leak(Box::new(Entry {
+                stack,
+                cmd: AtomicPtr::new(cmd),
+                next: core::ptr::null_mut(),
+            }));
+            let stack_top = stack.cast::<u8>().wrapping_add(self.stack_size);
+            let stack_top = stack_top.wrapping_sub(stack_top as usize % 16);
+            let signature = unsafe { &(*cmd).signature };
+            let entry = publish(entry);
+
+            if let Err(e) = Error::from_code(unsafe {
+                sys::RegisterDeviceCommand(
+                    &(*cmd).device,
+                    &mut id,
+                    dispatch,
+                    stack_top.cast(),
+                    signature.as_ptr(),
+                    signature.len() as c_ulong,
+                )
+            }) {
+                // SAFETY: The command was not registered, so the kernel never runs it on its stack
+                unsafe { unpublish(entry) };
+                return Err(e);
+            }
+            ids.push(id);
+        }
+        Ok(ids)
+    }
+}
@@ -0,0 +1,599 @@
+//! Raising and handling Lilium exceptions.
+//!
+//! The exception handler installed by this module runs the registered [hooks][register_hook] first, then transfers control to the innermost [`catch_exception`] on the thread.
+//! If neither handles the exception, it is reported to the kernel as unmanaged, which terminates the thread.
+
+use core::{
+    cell::Cell,
+    ffi::{c_ulong, c_void},
+    mem::MaybeUninit,
+};
+
+use alloc::{boxed::Box, sync::Arc};
+
+use crate::{
+    handle::AsHandle,
+    result::{Error, Result},
+    sys::{
+        except::{self as sys, ExceptionContextHandle, ExceptionInfo, ExceptionStatusInfo},
+        handle::HandlePtr,
+        thread::ThreadHandle,
+    },
+    uuid::Uuid,
+};
+
+pub(crate) mod registry;
+pub mod signals;
+
+/// A well-known kind of exception, identified by its code
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ExceptionKind {
+    ProcessAbort,
+    RemoteStop,
+    DebugTrap,
+    MemoryAccessViolation,
+    IllegalInstruction,
+    ArithmeticError,
+    StackOverflow,
+    /// An exception code not known to this crate
+    Other(Uuid),
+}
+
+impl ExceptionKind {
+    pub const fn from_code(code: Uuid) -> Self {
+        match code {
+            sys::EXCEPT_PROCESS_ABORT => Self::ProcessAbort,
+            sys::EXCEPT_REMOTE_STOP => Self::RemoteStop,
+            sys::EXCEPT_DEBUG_TRAP => Self::DebugTrap,
+            sys::EXCEPT_MEMORY_ACCESS_VIOLATION => Self::MemoryAccessViolation,
+            sys::EXCEPT_ILLEGAL_INSTRUCTION => Self::IllegalInstruction,
+            sys::EXCEPT_ARITHMETIC_ERROR => Self::ArithmeticError,
+            sys::EXCEPT_STACK_OVERFLOW => Self::StackOverflow,
+            code => Self::Other(code),
+        }
+    }
+
+    pub const fn into_code(self) -> Uuid {
+        match self {
+            Self::ProcessAbort => sys::EXCEPT_PROCESS_ABORT,
+            Self::RemoteStop => sys::EXCEPT_REMOTE_STOP,
+            Self::DebugTrap => sys::EXCEPT_DEBUG_TRAP,
+            Self::MemoryAccessViolation => sys::EXCEPT_MEMORY_ACCESS_VIOLATION,
+            Self::IllegalInstruction => sys::EXCEPT_ILLEGAL_INSTRUCTION,
+            Self::ArithmeticError => sys::EXCEPT_ARITHMETIC_ERROR,
+            Self::StackOverflow => sys::EXCEPT_STACK_OVERFLOW,
+            Self::Other(code) => code,
+        }
+    }
+}
+
+/// A Lilium exception, identified by its code, with two words of information that depend on the code.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[repr(transparent)]
+pub struct Exception(ExceptionStatusInfo);
+
+impl Exception {
+    pub const fn new(code: Uuid, info: u64, reason: u64) -> Self {
+        Self(ExceptionStatusInfo {
+            except_code: code,
+            except_info: info,
+            except_reason: reason,
+        })
+    }
+
+    pub const fn from_kind(kind: ExceptionKind, info: u64, reason: u64) -> Self {
+        Self::new(kind.into_code(), info, reason)
+    }
+
+    pub const fn from_raw(status: ExceptionStatusInfo) -> Self {
+        Self(status)
+    }
+
+    pub const fn into_raw(self) -> ExceptionStatusInfo {
+        self.0
+    }
+
+    pub const fn as_raw(&self) -> &ExceptionStatusInfo {
+        &self.0
+    }
+
+    pub const fn code(&self) -> Uuid {
+        self.0.except_code
+    }
+
+    pub const fn kind(&self) -> ExceptionKind {
+        ExceptionKind::from_code(self.0.except_code)
+    }
+
+    pub const fn info(&self) -> u64 {
+        self.0.except_info
+    }
+
+    pub const fn reason(&self) -> u64 {
+        self.0.except_reason
+    }
+}
+
+impl core::fmt::Display for Exception {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self.kind() {
+            ExceptionKind::Other(code) => f.write_fmt(format_args!("exception {:?}", code))?,
+            kind => f.write_fmt(format_args!("{:?}", kind))?,
+        }
+        f.write_fmt(format_args!(
+            " (info {:#x}, reason {:#x})",
+            self.0.except_info, self.0.except_reason
+        ))
+    }
+}
+
+/// Raises `except` synchronously on the current thread.
+///
+/// Returns if a [hook][register_hook] resumes the thread. Otherwise, control is transferred to the innermost [`catch_exception`], or the thread is terminated.
+pub fn raise(except: &Exception) -> Result<()> {
+    install_handler()?;
+    Error::from_code(unsafe { sys::ExceptHandleSynchronous(except.as_raw(), core::ptr::null()) })
+}
+
+/// Raises `except` asynchronously on the thread designated by `th`.
+///
+/// The exception is handled by the exception handler of that thread, at such a time as the thread might receive a signal.
+pub fn raise_async<'a, H: AsHandle<'a, ThreadHandle>>(th: H, except: &Exception) -> Result<()> {
+    Error::from_code(unsafe {
+        sys::ExceptRaiseAsynchronous(th.as_handle(), except.as_raw(), core::ptr::null(), 0)
+    })
+}
+
+/// DWARF register numbers for the current architecture, for use with [`ExceptionContext`]
+pub mod regs {
+    cfg_if::cfg_if! {
+        if #[cfg(target_arch = "x86_64")]{
+            pub const STACK_POINTER: u32 = 7;
+            pub const FRAME_POINTER: u32 = 6;
+        }else if #[cfg(target_arch = "x86")]{
+            pub const STACK_POINTER: u32 = 4;
+            pub const FRAME_POINTER: u32 = 5;
+        }else if #[cfg(target_arch = "aarch64")]{
+            pub const STACK_POINTER: u32 = 31;
+            pub const FRAME_POINTER: u32 = 29;
+        }else if #[cfg(any(target_arch = "riscv32", target_arch = "riscv64"))]{
+            pub const STACK_POINTER: u32 = 2;
+            pub const FRAME_POINTER: u32 = 8;
+        }
+    }
+}
+
+/// The register state of a thread stopped by an exception, which the thread resumes with.
+///
+/// Modifying the context changes the state of the code that was interrupted by the exception, so each modification is `unsafe`.
+#[derive(Debug)]
+pub struct ExceptionContext {
+    hdl: HandlePtr<ExceptionContextHandle>,
+    resume_addr: Option<*mut c_void>,
+}
+
+impl ExceptionContext {
+    /// ## Safety
+    /// `hdl` must be the context passed to the exception handler of the current thread, and must not be used by anything else.
+    pub const unsafe fn from_raw(hdl: HandlePtr<ExceptionContextHandle>) -> Self {
+        Self {
+            hdl,
+            resume_addr: None,
+        }
+    }
+
+    pub const fn as_raw(&self) -> HandlePtr<ExceptionContextHandle> {
+        self.hdl
+    }
+
+    /// The address of the instruction the thread was stopped at
+    pub fn stop_addr(&self) -> Result<*mut c_void> {
+        let mut addr = core::ptr::null_mut();
+        Error::from_code(unsafe { sys::ExceptGetStopAddr(self.hdl, &mut addr) })?;
+        Ok(addr)
+    }
+
+    /// The address [`ExceptionContext::resume`] resumes the thread at. This is [`ExceptionContext::stop_addr`], unless changed by [`ExceptionContext::set_instruction_pointer`]
+    pub fn instruction_pointer(&self) -> Result<*mut c_void> {
+        match self.resume_addr {
+            Some(addr) => Ok(addr),
+            None => self.stop_addr(),
+        }
+    }
+
+    /// Sets the address [`ExceptionContext::resume`] resumes the thread at.
+    ///
+    /// ## Safety
+    /// Resuming at `addr` with the registers of the context must be sound for the interrupted code.
+    pub unsafe fn set_instruction_pointer(&mut self, addr: *mut c_void) {
+        self.resume_addr = Some(addr);
+    }
+
+    /// Reads the register `regno`, which must be at most the size of a machine word.
+    ///
+    /// ## Errors
+    /// Returns [`Error::InvalidOperation`] if `regno` is not a valid register, or cannot be read.
+    pub fn gpr(&self, regno: u32) -> Result<usize> {
+        let mut val = 0usize;
+        self.register(regno, bytemuck::bytes_of_mut(&mut val))?;
+        Ok(val)
+    }
+
+    /// Sets the register `regno`, which must be at most the size of a machine word.
+    ///
+    /// ## Safety
+    /// The new value must be sound for the interrupted code when the thread resumes.
+    pub unsafe fn set_gpr(&mut self, regno: u32, value: usize) -> Result<()> {
+        Error::from_code(unsafe { sys::ExceptSetGPR(self.hdl, regno, value as c_ulong) })
+    }
+
+    /// Reads the register `regno` into `buf`. The required length of `buf` depends on `regno` and the architecture.
+    pub fn register(&self, regno: u32, buf: &mut [u8]) -> Result<()> {
+        Error::from_code(unsafe {
+            sys::ExceptGetRegister(
+                self.hdl,
+                regno,
+                buf.as_mut_ptr().cast(),
+                buf.len() as c_ulong,
+            )
+        })
+    }
+
+    /// Sets the register `regno` from `value`. The required length of `value` depends on `regno` and the architecture.
+    ///
+    /// ## Safety
+    /// The new value must be sound for the interrupted code when the thread resumes.
+    pub unsafe fn set_register(&mut self, regno: u32, value: &[u8]) -> Result<()> {
+        Error::from_code(unsafe {
+            sys::ExceptSetRegister(
+                self.hdl,
+                regno,
+                value.as_ptr().cast(),
+                value.len() as c_ulong,
+            )
+        })
+    }
+
+    #[cfg(any(
+        target_arch = "x86_64",
+        target_arch = "x86",
+        target_arch = "aarch64",
+        target_arch = "riscv32",
+        target_arch = "riscv64"
+    ))]
+    pub fn stack_pointer(&self) -> Result<*mut c_void> {
+        self.gpr(regs::STACK_POINTER).map(|sp| sp as *mut c_void)
+    }
+
+    /// ## Safety
+    /// The new stack pointer must be sound for the interrupted code when the thread resumes.
+    #[cfg(any(
+        target_arch = "x86_64",
+        target_arch = "x86",
+        target_arch = "aarch64",
+        target_arch = "riscv32",
+        target_arch = "riscv64"
+    ))]
+    pub unsafe fn set_stack_pointer(&mut self, sp: *mut c_void) -> Result<()> {
+        Error::from_code(unsafe { sys::ExceptSetPointerReg(self.hdl, regs::STACK_POINTER, sp) })
+    }
+
+    /// Loads the registers of the context, and resumes the thread at `addr`. The context is released.
+    ///
+    /// Only returns if the thread cannot be resumed, and returns the reason.
+    ///
+    /// ## Safety
+    /// Resuming at `addr` with the registers of the context must be sound for the interrupted code.
+    pub unsafe fn resume_at(self, addr: *mut c_void) -> Error {
+        let code = unsafe { sys::ExceptResumeAt(addr, self.hdl) };
+        match Error::from_code(code.get()) {
+            Err(e) => e,
+            Ok(()) => Error::Unknown(code.get()),
+        }
+    }
+
+    /// Loads the registers of the context, and resumes the thread at [`ExceptionContext::instruction_pointer`]. The context is released.
+    ///
+    /// Only returns if the thread cannot be resumed, and returns the reason.
+    ///
+    /// ## Safety
+    /// Same as [`ExceptionContext::resume_at`].
+    pub unsafe fn resume(self) -> Error {
+        match self.instruction_pointer() {
+            Ok(addr) => unsafe { self.resume_at(addr) },
+            Err(e) => e,
+        }
+    }
+}
+
+/// What the exception handler does after a hook runs
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+pub enum HookAction {
+    /// Run the next hook, then transfer control to the innermost [`catch_exception`]
+    Continue,
+    /// Resume the thread with the [`ExceptionContext`] passed to the hook, which is where it was stopped by the exception, unless the hook modified the context.
+    ///
+    /// For synchronous exceptions caused by an instruction, resuming at the same address executes the instruction again, so the hook must first correct the cause.
+    Resume,
+}
+
+type HookFn = dyn Fn(&Exception, &mut ExceptionContext) -> HookAction + Send + Sync;
+
+static HOOKS: registry::Registry<HookFn> = registry::Registry::new();
+
+/// Removes a hook registered by [`register_hook`] when dropped, and drops the hook.
+#[must_use = "the hook is removed immediately if the handle is dropped"]
+#[derive(Debug)]
+pub struct HookHandle(usize);
+
+impl HookHandle {
+    /// Keeps the hook registered for the remainder of the process
+    pub fn forget(self) {
+        core::mem::forget(self)
+    }
+}
+
+impl Drop for HookHandle {
+    fn drop(&mut self) {
+        HOOKS.remove(self.0);
+    }
+}
+
+/// Registers a hook which is called with every exception received by threads that have installed the exception handler of this module,
+///  before the exception is passed to [`catch_exception`].
+///
+/// Hooks are called in the order they were registered. A hook must not register or remove hooks.
+/// Registering or removing a hook waits for hooks that are running on other threads to return.
+///
+/// The calling thread installs the exception handler, as by [`install_handler`].
+pub fn register_hook<
+    F: Fn(&Exception, &mut ExceptionContext) -> HookAction + Send + Sync + 'static,
+>(
+    f: F,
+) -> Result<HookHandle> {
+    install_handler()?;
+    Ok(HookHandle(HOOKS.register(Arc::new(f))))
+}
+
+fn run_hooks(except: &Exception, ctx: &mut ExceptionContext) -> HookAction {
+    HOOKS.read(|hooks| {
+        for hook in hooks {
+            if hook(except, ctx) == HookAction::Resume {
+                return HookAction::Resume;
+            }
+        }
+        HookAction::Continue
+    })
+}
+
+struct CatchFrame {
+    jmp: arch::JumpBuf,
+    prev: *mut CatchFrame,
+    caught: MaybeUninit<ExceptionStatusInfo>,
+}
+
+#[thread_local]
+static CATCH_TOP: Cell<*mut CatchFrame> = Cell::new(core::ptr::null_mut());
+
+#[thread_local]
+static HANDLER_INSTALLED: Cell<bool> = Cell::new(false);
+
+/// Installs the exception handler of this module on the current thread, if it is not already installed.
+///
+/// [`catch_exception`], [`register_hook`], and [`raise`] call this automatically.
+pub fn install_handler() -> Result<()> {
+    if !HANDLER_INSTALLED.get() {
+        Error::from_code(unsafe {
+            sys::ExceptInstallHandler(Some(handle_exception), core::ptr::null())
+        })?;
+        HANDLER_INSTALLED.set(true);
+    }
+    Ok(())
+}
+
+unsafe extern "system" fn handle_exception(
+    info: *mut ExceptionInfo,
+    ctx: HandlePtr<ExceptionContextHandle>,
+) -> ! {
+    let except = Exception(unsafe { (*info).status });
+    let mut context = unsafe { ExceptionContext::from_raw(ctx) };
+
+    if run_hooks(&except, &mut context) == HookAction::Resume {
+        unsafe { context.resume() };
+    } else {
+        let frame = CATCH_TOP.get();
+        if !frame.is_null() {
+            unsafe {
+                (*frame).caught.write(except.0);
+                CATCH_TOP.set((*frame).prev);
+                arch::resume_frame(core::ptr::addr_of!((*frame).jmp), ctx);
+            }
+        }
+    }
+
+    unsafe { sys::UnmanagedException(&except.0) }
+}
+
+#[cfg(feature = "std")]
+type Outcome<T> = core::result::Result<T, Box<dyn core::any::Any + Send + 'static>>;
+#[cfg(not(feature = "std"))]
+type Outcome<T> = core::result::Result<T, !>;
+
+struct CatchState<R, F> {
+    f: Option<F>,
+    ret: Option<Outcome<R>>,
+}
+
+extern "C" fn call_catch<R, F: FnOnce() -> R>(data: *mut c_void) {
+    let state = unsafe { &mut *data.cast::<CatchState<R, F>>() };
+    let f = state.f.take().unwrap();
+    // Panics cannot unwind through the catch frame, so they are carried across it and resumed afterwards.
+    cfg_if::cfg_if! {
+        if #[cfg(feature = "std")]{
+            state.ret = Some(std::panic::catch_unwind(std::panic::AssertUnwindSafe(f)));
+        }else{
+            state.ret = Some(Ok(f()));
+        }
+    }
+}
+
+/// Calls `f`, and returns any exception raised on the current thread while it runs instead of terminating the thread.
+///
+/// When an exception is caught, the stack frames between the exception and this call are discarded without running destructors, so any values they own are leaked.
+///
+/// Exceptions can only be caught on x86_64. On other architectures, `f` is called directly, and exceptions are reported to the kernel as unmanaged.
+///
+/// # Safety
+/// No stack frame between the point where an exception may be raised inside `f` and this call may have a pending drop whose skipping is unsound,
+///  such as a [`thread::scope`][crate::thread::scope] that must join its threads, a pinned value that must be dropped before its memory is reused, or a lock guard.
+/// The simplest way to satisfy this is for `f` to only call code that holds no such values while it may raise an exception.
+pub unsafe fn catch_exception<R, F: FnOnce() -> R>(f: F) -> core::result::Result<R, Exception> {
+    if install_handler().is_err() {
+        return Ok(f());
+    }
+
+    let mut state = CatchState {
+        f: Some(f),
+        ret: None,
+    };
+    let mut frame = CatchFrame {
+        jmp: arch::JumpBuf::new(),
+        prev: CATCH_TOP.get(),
+        caught: MaybeUninit::uninit(),
+    };
+    let frame = core::ptr::addr_of_mut!(frame);
+    CATCH_TOP.set(frame);
+
+    let caught = unsafe {
+        arch::catch_trampoline(
+            core::ptr::addr_of_mut!((*frame).jmp),
+            call_catch::<R, F>,
+            core::ptr::addr_of_mut!(state).cast(),
+        )
+    };
+
+    if caught != 0 {
+        // The exception handler already removed the frame
+        return Err(Exception(unsafe { (*frame).caught.assume_init() }));
+    }
+
+    CATCH_TOP.set(unsafe { (*frame).prev });
+    match state.ret.take().unwrap() {
+        Ok(val) => Ok(val),
+        #[cfg(feature = "std")]
+        Err(payload) => std::panic::resume_unwind(payload),
+    }
+}
+
+cfg_if::cfg_if! {
+    if #[cfg(all(target_arch = "x86_64", target_pointer_width = "64"))]{
+        mod arch {
+            use core::ffi::{c_ulong, c_void};
+
+            use crate::sys::{except::{ExceptResumeAt, ExceptSetGPR, ExceptionContextHandle}, handle::HandlePtr};
+
+            /// The callee-saved registers, stack pointer, and landing address of a catch frame
+            #[repr(C)]
+            pub struct JumpBuf {
+                rbx: u64,
+                rbp: u64,
+                r12: u64,
+                r13: u64,
+                r14: u64,
+                r15: u64,
+                rsp: u64,
+                landing: u64,
+            }
+
+            impl JumpBuf {
+                pub const fn new() -> Self {
+                    Self { rbx: 0, rbp: 0, r12: 0, r13: 0, r14: 0, r15: 0, rsp: 0, landing: 0 }
+                }
+            }
+
+            /// Saves the frame into `buf`, then calls `f(data)` and returns `0`. Returns `1` instead if [`resume_frame`] is called with `buf` while `f` is running.
+            #[unsafe(naked)]
+            pub unsafe extern "C" fn catch_trampoline(
+                buf: *mut JumpBuf,
+                f: extern "C" fn(*mut c_void),
+                data: *mut c_void,
+            ) -> usize {
+                core::arch::naked_asm!(
+                    "mov [rdi], rbx",
+                    "mov [rdi+8], rbp",
+                    "mov [rdi+16], r12",
+                    "mov [rdi+24], r13",
+                    "mov [rdi+32], r14",
+                    "mov [rdi+40], r15",
+                    "sub rsp, 8",
+                    "mov [rdi+48], rsp",
+                    "lea rax, [rip+2f]",
+                    "mov [rdi+56], rax",
+                    "mov rdi, rdx",
+                    "call rsi",
+                    "add rsp, 8",
+                    "xor eax, eax",
+                    "ret",
+                    "2:",
+                    "add rsp, 8",
+                    "mov eax, 1",
+                    "ret",
+                )
+            }
+
+            // DWARF register numbers
+            const RBX: u32 = 3;
+            const RBP: u32 = 6;
+            const RSP: u32 = 7;
+            const R12: u32 = 12;
+            const R13: u32 = 13;
+            const R14: u32 = 14;
+            const R15: u32 = 15;
+
+            /// Resumes execution at the landing address of the frame saved in `buf`. Returns only if the context cannot be modified.
+            pub unsafe fn resume_frame(buf: *const JumpBuf, ctx: HandlePtr<ExceptionContextHandle>) {
+                let buf = unsafe { &*buf };
+                for (regno, val) in [
+                    (RBX, buf.rbx),
+                    (RBP, buf.rbp),
+                    (R12, buf.r12),
+                    (R13, buf.r13),
+                    (R14, buf.r14),
+                    (R15, buf.r15),
+                    (RSP, buf.rsp),
+                ] {
+                    if unsafe { ExceptSetGPR(ctx, regno, val as c_ulong) } < 0 {
+                        return;
+                    }
+                }
+                unsafe { ExceptResumeAt(buf.landing as *mut c_void, ctx) };
+            }
+        }
+    }else{
+        mod arch {
+            use core::ffi::c_void;
+
+            use crate::sys::{except::ExceptionContextHandle, handle::HandlePtr};
+
+            pub struct JumpBuf;
+
+            impl JumpBuf {
+                pub const fn new() -> Self {
+                    Self
+                }
+            }
+
+            pub unsafe extern "C" fn catch_trampoline(
+                _: *mut JumpBuf,
+                f: extern "C" fn(*mut c_void),
+                data: *mut c_void,
+            ) -> usize {
+                f(data);
+                0
+            }
+
+            pub unsafe fn resume_frame(_: *const JumpBuf, _: HandlePtr<ExceptionContextHandle>) {}
+        }
+    }
+}
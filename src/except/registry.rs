@@ -0,0 +1,121 @@
+//! A set of callbacks that can be read from exception context.
+//!
+//! Callbacks run by an exception hook can interrupt any code on the thread, including code that is registering or removing a callback of the same set.
+//! A lock held by the interrupted code would never be released, so readers of a [`Registry`] never block: registering or removing a callback publishes a new copy of the set,
+//!  and the old copy is only freed once no reader can still be using it.
+
+use core::sync::atomic::{AtomicPtr, AtomicUsize, Ordering};
+
+use alloc::{boxed::Box, sync::Arc, vec::Vec};
+
+use crate::{
+    result::Result,
+    sync::{Mutex, OnceLock},
+    sys::thread::YieldThread,
+};
+
+use super::{register_hook, Exception, ExceptionContext, HookAction, HookHandle};
+
+type Entries<T> = Vec<(usize, Arc<T>)>;
+
+pub(crate) struct Registry<T: ?Sized> {
+    current: AtomicPtr<Entries<T>>,
+    readers: AtomicUsize,
+    writer: Mutex<()>,
+    next_id: AtomicUsize,
+    except_hook: OnceLock<Result<()>>,
+}
+
+// SAFETY: The entries are only shared as `&T` between threads, and dropped on the thread that removes them or the last reader
+unsafe impl<T: ?Sized + Send + Sync> Send for Registry<T> {}
+unsafe impl<T: ?Sized + Send + Sync> Sync for Registry<T> {}
+
+struct ReadGuard<'a>(&'a AtomicUsize);
+
+impl Drop for ReadGuard<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::Release);
+    }
+}
+
+impl<T: ?Sized> Registry<T> {
+    pub(crate) const fn new() -> Self {
+        Self {
+            current: AtomicPtr::new(core::ptr::null_mut()),
+            readers: AtomicUsize::new(0),
+            writer: Mutex::new(()),
+            next_id: AtomicUsize::new(0),
+            except_hook: OnceLock::new(),
+        }
+    }
+
+    /// Calls `f` with every callback in the set, in the order they were registered. Never blocks.
+    ///
+    /// `f` must not register or remove callbacks of the same set.
+    pub(crate) fn read<R>(&self, f: impl FnOnce(&mut dyn Iterator<Item = &T>) -> R) -> R {
+        // Both `SeqCst`: either `update` sees this reader, or this reader sees the set it published
+        self.readers.fetch_add(1, Ordering::SeqCst);
+        let _guard = ReadGuard(&self.readers);
+        let entries = self.current.load(Ordering::SeqCst);
+        let entries: &[(usize, Arc<T>)] = if entries.is_null() {
+            &[]
+        } else {
+            unsafe { &*entries }
+        };
+        f(&mut entries.iter().map(|(_, val)| &**val))
+    }
+
+    fn update(&self, f: impl FnOnce(&mut Entries<T>)) {
+        let _lock = self.writer.lock();
+        let old = self.current.load(Ordering::Acquire);
+        let mut entries = if old.is_null() {
+            Vec::new()
+        } else {
+            unsafe { (*old).clone() }
+        };
+        f(&mut entries);
+        self.current
+            .store(Box::into_raw(Box::new(entries)), Ordering::SeqCst);
+
+        // A reader that loaded `old` counted itself before doing so
+        while self.readers.load(Ordering::SeqCst) != 0 {
+            unsafe { YieldThread() }
+        }
+        if !old.is_null() {
+            drop(unsafe { Box::from_raw(old) });
+        }
+    }
+
+    /// Adds `val` to the end of the set, and returns the id to [remove][Registry::remove] it with
+    pub(crate) fn register(&self, val: Arc<T>) -> usize {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        self.update(|entries| entries.push((id, val)));
+        id
+    }
+
+    /// Adds `val` to the end of the set as by [`Registry::register`], after registering `hook` as an [exception hook][register_hook] once for the whole set
+    pub(crate) fn register_with_hook(
+        &self,
+        hook: fn(&Exception, &mut ExceptionContext) -> HookAction,
+        val: Arc<T>,
+    ) -> Result<usize> {
+        (*self
+            .except_hook
+            .get_or_init(|| register_hook(hook).map(HookHandle::forget)))?;
+        Ok(self.register(val))
+    }
+
+    /// Removes the callback registered with `id`, and drops it once no reader is using it
+    pub(crate) fn remove(&self, id: usize) {
+        self.update(|entries| entries.retain(|(entry, _)| *entry != id));
+    }
+}
+
+impl<T: ?Sized> Drop for Registry<T> {
+    fn drop(&mut self) {
+        let entries = *self.current.get_mut();
+        if !entries.is_null() {
+            drop(unsafe { Box::from_raw(entries) });
+        }
+    }
+}
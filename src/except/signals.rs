@@ -0,0 +1,435 @@
+//! POSIX-style signals, delivered as Lilium exceptions.
+//!
+//! Each [`Signal`] corresponds to an exception code. Asynchronous signals (such as [`Signal::Interrupt`]) are raised in the signal handling thread of the process
+//! (see [`SetSignalHandlingThread`][crate::sys::signal::SetSignalHandlingThread]), and synchronous signals (such as [`Signal::SegmentationFault`]) are the exceptions raised by faulting instructions.
+//!
+//! Handlers registered by [`on_signal`] are called from the exception handler of the [`except`][super] module, which must be installed on the thread that receives the signal (see [`install_handler`][super::install_handler]).
+//! They run while the thread is interrupted, so they should do as little as possible. A [`SignalStream`] instead forwards signals to an [`Event`], so they can be handled in an event loop.
+//!
+//! ## Example
+//! ```rust,ignore
+//! let signals = SignalStream::new(&[Signal::Interrupt, Signal::Terminate])?;
+//! let input = Readable::new(stdin);
+//! loop {
+//!     let ready = block_on_any(&[&signals, &input])?;
+//!     match ready.index() {
+//!         0 => match signals.try_next()? {
+//!             Some(Signal::Interrupt) => cancel_current_command(),
+//!             Some(_) => break,
+//!             None => {}
+//!         },
+//!         _ => on_input(ready.output(&input)?),
+//!     }
+//! }
+//! ```
+
+use core::{
+    mem::MaybeUninit,
+    sync::atomic::{AtomicU32, Ordering},
+};
+
+use alloc::{boxed::Box, sync::Arc};
+
+use crate::{
+    handle::OwnedHandle,
+    io::IOHandle,
+    result::{Error, Result, ResultExt},
+    sync::event::{block_on, handle_event, Event, TypedEvent},
+    sys::{
+        event::{EventInfo, EVENT_IO_READABLE},
+        except as sys,
+        io::{CreatePipe, MODE_NONBLOCKING},
+        result::SysResult,
+    },
+    uuid::Uuid,
+};
+
+use super::{registry::Registry, Exception, HookAction};
+
+/// A POSIX-style signal
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Signal {
+    /// `SIGHUP`: The controlling terminal was closed
+    Hangup,
+    /// `SIGINT`: An interrupt was requested, such as by `Ctrl+C`
+    Interrupt,
+    /// `SIGQUIT`: A quit was requested, such as by `Ctrl+\`
+    Quit,
+    /// `SIGILL`: An illegal instruction was executed. This signal is synchronous.
+    IllegalInstruction,
+    /// `SIGTRAP`: A breakpoint was hit. This signal is synchronous.
+    Trap,
+    /// `SIGABRT`: The process is being aborted
+    Abort,
+    /// `SIGFPE`: An arithmetic instruction faulted. This signal is synchronous.
+    ArithmeticError,
+    /// `SIGUSR1`: Defined by the program
+    User1,
+    /// `SIGSEGV`: Memory was accessed without the required access. This signal is synchronous.
+    SegmentationFault,
+    /// `SIGUSR2`: Defined by the program
+    User2,
+    /// `SIGPIPE`: A pipe or socket whose other end is closed was written to
+    BrokenPipe,
+    /// `SIGALRM`: An alarm expired
+    Alarm,
+    /// `SIGTERM`: Termination of the process was requested
+    Terminate,
+    /// `SIGCHLD`: A child process exited
+    Child,
+    /// `SIGWINCH`: The size of the controlling terminal changed
+    WindowChange,
+}
+
+impl Signal {
+    /// Every signal known to this crate, in the order they are declared
+    pub const ALL: [Signal; 15] = [
+        Self::Hangup,
+        Self::Interrupt,
+        Self::Quit,
+        Self::IllegalInstruction,
+        Self::Trap,
+        Self::Abort,
+        Self::ArithmeticError,
+        Self::User1,
+        Self::SegmentationFault,
+        Self::User2,
+        Self::BrokenPipe,
+        Self::Alarm,
+        Self::Terminate,
+        Self::Child,
+        Self::WindowChange,
+    ];
+
+    /// The signal raised as the exception `code`, if any
+    pub const fn from_code(code: Uuid) -> Option<Self> {
+        Some(match code {
+            sys::EXCEPT_SIGNAL_HANGUP => Self::Hangup,
+            sys::EXCEPT_SIGNAL_INTERRUPT => Self::Interrupt,
+            sys::EXCEPT_SIGNAL_QUIT => Self::Quit,
+            sys::EXCEPT_ILLEGAL_INSTRUCTION => Self::IllegalInstruction,
+            sys::EXCEPT_DEBUG_TRAP => Self::Trap,
+            sys::EXCEPT_PROCESS_ABORT => Self::Abort,
+            sys::EXCEPT_ARITHMETIC_ERROR => Self::ArithmeticError,
+            sys::EXCEPT_SIGNAL_USER1 => Self::User1,
+            sys::EXCEPT_MEMORY_ACCESS_VIOLATION => Self::SegmentationFault,
+            sys::EXCEPT_SIGNAL_USER2 => Self::User2,
+            sys::EXCEPT_SIGNAL_BROKEN_PIPE => Self::BrokenPipe,
+            sys::EXCEPT_SIGNAL_ALARM => Self::Alarm,
+            sys::EXCEPT_SIGNAL_TERMINATE => Self::Terminate,
+            sys::EXCEPT_SIGNAL_CHILD => Self::Child,
+            sys::EXCEPT_SIGNAL_WINDOW_CHANGE => Self::WindowChange,
+            _ => return None,
+        })
+    }
+
+    /// The code of the exception the signal is raised as
+    pub const fn code(self) -> Uuid {
+        match self {
+            Self::Hangup => sys::EXCEPT_SIGNAL_HANGUP,
+            Self::Interrupt => sys::EXCEPT_SIGNAL_INTERRUPT,
+            Self::Quit => sys::EXCEPT_SIGNAL_QUIT,
+            Self::IllegalInstruction => sys::EXCEPT_ILLEGAL_INSTRUCTION,
+            Self::Trap => sys::EXCEPT_DEBUG_TRAP,
+            Self::Abort => sys::EXCEPT_PROCESS_ABORT,
+            Self::ArithmeticError => sys::EXCEPT_ARITHMETIC_ERROR,
+            Self::User1 => sys::EXCEPT_SIGNAL_USER1,
+            Self::SegmentationFault => sys::EXCEPT_MEMORY_ACCESS_VIOLATION,
+            Self::User2 => sys::EXCEPT_SIGNAL_USER2,
+            Self::BrokenPipe => sys::EXCEPT_SIGNAL_BROKEN_PIPE,
+            Self::Alarm => sys::EXCEPT_SIGNAL_ALARM,
+            Self::Terminate => sys::EXCEPT_SIGNAL_TERMINATE,
+            Self::Child => sys::EXCEPT_SIGNAL_CHILD,
+            Self::WindowChange => sys::EXCEPT_SIGNAL_WINDOW_CHANGE,
+        }
+    }
+
+    /// The signal number used by Linux and most POSIX systems, such as `2` for [`Signal::Interrupt`]
+    pub const fn number(self) -> i32 {
+        match self {
+            Self::Hangup => 1,
+            Self::Interrupt => 2,
+            Self::Quit => 3,
+            Self::IllegalInstruction => 4,
+            Self::Trap => 5,
+            Self::Abort => 6,
+            Self::ArithmeticError => 8,
+            Self::User1 => 10,
+            Self::SegmentationFault => 11,
+            Self::User2 => 12,
+            Self::BrokenPipe => 13,
+            Self::Alarm => 14,
+            Self::Terminate => 15,
+            Self::Child => 17,
+            Self::WindowChange => 28,
+        }
+    }
+
+    /// The signal with the POSIX signal number `signo`, if any. See [`Signal::number`].
+    pub const fn from_number(signo: i32) -> Option<Self> {
+        let mut i = 0;
+        while i < Self::ALL.len() {
+            if Self::ALL[i].number() == signo {
+                return Some(Self::ALL[i]);
+            }
+            i += 1;
+        }
+        None
+    }
+
+    /// Checks if the signal is raised by a faulting instruction of the thread that receives it.
+    ///
+    /// Synchronous signals cannot be blocked, and the thread is not resumed after they are handled, as it would fault again.
+    pub const fn is_synchronous(self) -> bool {
+        matches!(
+            self,
+            Self::IllegalInstruction | Self::Trap | Self::ArithmeticError | Self::SegmentationFault
+        )
+    }
+
+    const fn bit(self) -> u32 {
+        1 << self as u32
+    }
+}
+
+impl core::fmt::Display for Signal {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(match self {
+            Self::Hangup => "SIGHUP",
+            Self::Interrupt => "SIGINT",
+            Self::Quit => "SIGQUIT",
+            Self::IllegalInstruction => "SIGILL",
+            Self::Trap => "SIGTRAP",
+            Self::Abort => "SIGABRT",
+            Self::ArithmeticError => "SIGFPE",
+            Self::User1 => "SIGUSR1",
+            Self::SegmentationFault => "SIGSEGV",
+            Self::User2 => "SIGUSR2",
+            Self::BrokenPipe => "SIGPIPE",
+            Self::Alarm => "SIGALRM",
+            Self::Terminate => "SIGTERM",
+            Self::Child => "SIGCHLD",
+            Self::WindowChange => "SIGWINCH",
+        })
+    }
+}
+
+fn mask_of(signals: &[Signal]) -> u32 {
+    signals.iter().fold(0, |mask, sig| mask | sig.bit())
+}
+
+type HandlerFn = dyn Fn(Signal) + Send + Sync;
+
+struct Handler {
+    mask: u32,
+    f: Box<HandlerFn>,
+}
+
+static HANDLERS: Registry<Handler> = Registry::new();
+static BLOCKED: AtomicU32 = AtomicU32::new(0);
+static PENDING: AtomicU32 = AtomicU32::new(0);
+
+/// Calls the handlers of `sig`, and returns whether there were any
+fn dispatch(sig: Signal) -> bool {
+    HANDLERS.read(|handlers| {
+        let mut handled = false;
+        for handler in handlers {
+            if handler.mask & sig.bit() != 0 {
+                (handler.f)(sig);
+                handled = true;
+            }
+        }
+        handled
+    })
+}
+
+fn signal_hook(except: &Exception, _: &mut super::ExceptionContext) -> HookAction {
+    let Some(sig) = Signal::from_code(except.code()) else {
+        return HookAction::Continue;
+    };
+
+    if !sig.is_synchronous() && BLOCKED.load(Ordering::Acquire) & sig.bit() != 0 {
+        PENDING.fetch_or(sig.bit(), Ordering::AcqRel);
+        return HookAction::Resume;
+    }
+
+    if dispatch(sig) && !sig.is_synchronous() {
+        HookAction::Resume
+    } else {
+        HookAction::Continue
+    }
+}
+
+/// Removes a handler registered by [`on_signal`] when dropped.
+#[must_use = "the handler is removed immediately if the handle is dropped"]
+#[derive(Debug)]
+pub struct SignalHandle(usize);
+
+impl SignalHandle {
+    /// Keeps the handler registered for the remainder of the process
+    pub fn forget(self) {
+        core::mem::forget(self)
+    }
+}
+
+impl Drop for SignalHandle {
+    fn drop(&mut self) {
+        HANDLERS.remove(self.0);
+    }
+}
+
+/// Registers `handler` to be called when `signal` is received.
+///
+/// Every handler registered for a signal is called, in the order they were registered. Once the handlers of an asynchronous signal return, the thread resumes.
+/// A synchronous signal is then passed on to [`catch_exception`][super::catch_exception], or terminates the thread.
+/// A signal without handlers has its default effect as an exception, which is usually to terminate the thread.
+///
+/// A handler must not register or remove handlers.
+///
+/// The calling thread installs the exception handler, as by [`install_handler`][super::install_handler].
+pub fn on_signal<F: Fn(Signal) + Send + Sync + 'static>(
+    signal: Signal,
+    handler: F,
+) -> Result<SignalHandle> {
+    on_signals(&[signal], handler)
+}
+
+/// Registers `handler` to be called when any of `signals` is received. See [`on_signal`].
+pub fn on_signals<F: Fn(Signal) + Send + Sync + 'static>(
+    signals: &[Signal],
+    handler: F,
+) -> Result<SignalHandle> {
+    let id = HANDLERS.register_with_hook(
+        signal_hook,
+        Arc::new(Handler {
+            mask: mask_of(signals),
+            f: Box::new(handler),
+        }),
+    )?;
+    Ok(SignalHandle(id))
+}
+
+/// Blocks `signals` for the whole process. A blocked signal that is received is held pending, and delivered once it is [unblocked][unblock].
+///
+/// Synchronous signals cannot be blocked, and are ignored.
+pub fn block(signals: &[Signal]) {
+    let mask = signals
+        .iter()
+        .filter(|sig| !sig.is_synchronous())
+        .fold(0, |mask, sig| mask | sig.bit());
+    BLOCKED.fetch_or(mask, Ordering::AcqRel);
+}
+
+/// Unblocks `signals`, and calls the handlers of those that were received while blocked on the current thread.
+///
+/// A pending signal is delivered once, however many times it was received while blocked.
+pub fn unblock(signals: &[Signal]) {
+    let mask = mask_of(signals);
+    BLOCKED.fetch_and(!mask, Ordering::AcqRel);
+    let pending = PENDING.fetch_and(!mask, Ordering::AcqRel) & mask;
+    for sig in Signal::ALL {
+        if pending & sig.bit() != 0 {
+            dispatch(sig);
+        }
+    }
+}
+
+/// Checks if `signal` is blocked
+pub fn is_blocked(signal: Signal) -> bool {
+    BLOCKED.load(Ordering::Acquire) & signal.bit() != 0
+}
+
+/// Checks if `signal` was received while blocked, and has not been delivered yet
+pub fn is_pending(signal: Signal) -> bool {
+    PENDING.load(Ordering::Acquire) & signal.bit() != 0
+}
+
+/// Queues received signals so they can be handled in an event loop.
+///
+/// Signals are written to an internal pipe by a handler registered with [`on_signals`], and read back by [`SignalStream::try_next`].
+/// A `SignalStream` is an [`Event`] that occurs when signals are queued, so it can be waited on alongside other events with [`block_on_any`][crate::sync::event::block_on_any].
+///
+/// If the pipe is full, further signals are dropped until it is read.
+#[derive(Debug)]
+pub struct SignalStream {
+    // Removed before the pipe is closed
+    _handler: SignalHandle,
+    read: OwnedHandle<IOHandle>,
+    _write: OwnedHandle<IOHandle>,
+}
+
+/// The write end of the pipe of a [`SignalStream`], used by its handler
+struct PipeWriter(crate::sys::handle::HandlePtr<IOHandle>);
+
+// SAFETY: Handles can be used from any thread of the process, and the handler is removed before the handle is closed
+unsafe impl Send for PipeWriter {}
+unsafe impl Sync for PipeWriter {}
+
+impl PipeWriter {
+    fn send(&self, sig: Signal) {
+        let idx = sig as u8;
+        let _ = unsafe { crate::sys::io::IOWrite(self.0, (&idx as *const u8).cast(), 1) };
+    }
+}
+
+impl SignalStream {
+    /// Creates a stream that queues each of `signals` when it is received
+    pub fn new(signals: &[Signal]) -> Result<Self> {
+        let mut write = MaybeUninit::uninit();
+        let mut read = MaybeUninit::uninit();
+        Error::from_code(unsafe {
+            CreatePipe(write.as_mut_ptr(), read.as_mut_ptr(), MODE_NONBLOCKING, 0)
+        })?;
+        let (read, write) = unsafe {
+            (
+                OwnedHandle::<IOHandle>::take_ownership(read.assume_init()),
+                OwnedHandle::<IOHandle>::take_ownership(write.assume_init()),
+            )
+        };
+
+        let writer = PipeWriter(write.as_raw());
+        let handler = on_signals(signals, move |sig| writer.send(sig))?;
+
+        Ok(Self {
+            _handler: handler,
+            read,
+            _write: write,
+        })
+    }
+
+    /// Reads the oldest queued signal, or returns `None` without blocking if no signal is queued
+    pub fn try_next(&self) -> Result<Option<Signal>> {
+        let mut idx = [0u8];
+        match self.read.read(&mut idx) {
+            Ok(0) | Err(Error::WouldBlock) => Ok(None),
+            Ok(_) => Ok(Signal::ALL.get(idx[0] as usize).copied()),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Blocks the current thread until a signal is queued, and reads it. See [`block_on`].
+    pub fn next_signal(&self) -> Result<Signal> {
+        loop {
+            block_on(self)?;
+            if let Some(sig) = self.try_next()? {
+                return Ok(sig);
+            }
+        }
+    }
+}
+
+unsafe impl Event for SignalStream {
+    fn to_raw(&self) -> EventInfo {
+        handle_event(EVENT_IO_READABLE, self.read.as_raw())
+    }
+}
+
+impl TypedEvent for SignalStream {
+    /// The number of queued signals
+    type Output = usize;
+
+    fn output(&self, result: SysResult) -> Result<usize> {
+        result.into_value()
+    }
+}
@@ -1,7 +1,8 @@
 use core::{
     borrow::Borrow,
     ffi::{c_long, c_void},
-    ops::Deref,
+    marker::PhantomData,
+    ops::{Deref, Range},
     str::Split,
 };
 
@@ -14,12 +15,21 @@ use alloc::{
 use core::mem::MaybeUninit;
 
 use crate::{
-    handle::{AsHandle, OwnedHandle, SharedHandle},
-    result::{Error, Result},
+    handle::{
+        AsHandle, AsRawHandlePtr, BorrowedHandle, FromRawLiliumHandle, HandleRef,
+        IntoRawLiliumHandle, OwnedHandle, SharedHandle,
+    },
+    io::BlockingMode,
+    kstr::{with_growing_buffer, OsStr, OsString},
+    result::{ContextError, Error, ErrorContext, Result, ResultExt},
+    security::SecurityContext,
     sys::{
+        device,
         fs::{self as sys, DirectoryInfo, DirectoryNext, DirectoryRead, FileHandle},
         handle::{Handle, HandlePtr},
-        kstr::{KCSlice, KStrCPtr, KStrPtr},
+        io::IOHandle,
+        kstr::{KCSlice, KStrCPtr},
+        permission::{DecodeSecurityContext, EncodeSecurityContext},
         result::errors::DOES_NOT_EXIST,
     },
     thread::TlsKey,
@@ -27,6 +37,11 @@ use crate::{
     uuid::Uuid,
 };
 
+pub mod named_pipe;
+mod watch;
+
+pub use watch::{FsEvent, FsEventKind, WatchId, Watcher};
+
 #[derive(Debug, Hash, PartialEq, Eq)]
 pub struct OwnedFile(OwnedHandle<FileHandle>);
 
@@ -38,6 +53,222 @@ impl OwnedFile {
     pub fn as_raw(&self) -> HandlePtr<FileHandle> {
         self.0.as_raw()
     }
+
+    /// Borrows the file as an [`IOHandle`], to read, write, and seek it
+    pub fn as_io_handle(&self) -> BorrowedHandle<'_, IOHandle> {
+        unsafe { BorrowedHandle::from_raw(self.0.as_raw().cast()) }
+    }
+}
+
+impl OwnedFile {
+    /// Opens the directory at `path`, for use as a resolution base
+    pub fn open_dir<P: AsRef<OsStr>>(path: P) -> Result<Self> {
+        let mut hdl = MaybeUninit::uninit();
+        Error::from_code(unsafe {
+            syscall!(sys::OpenFile(
+                hdl.as_mut_ptr(),
+                HandlePtr::null(),
+                os_path(&path)?.to_kstr_raw(),
+                &sys::FileOpenOptions {
+                    stream_override: KStrCPtr::empty(),
+                    access_mode: sys::ACCESS_READ,
+                    op_mode: sys::OP_DIRECTORY_ACCESS,
+                    blocking_mode: sys::MODE_BLOCKING,
+                    create_acl: HandlePtr::null(),
+                    extended_options: KCSlice::empty(),
+                },
+            ))
+        })?;
+        Ok(unsafe { Self::from_handle(hdl.assume_init()) })
+    }
+
+    /// Iterates over the entries of the directory, which must be opened with [`FileOp::DirectoryAccess`], such as by [`OwnedFile::open_dir`].
+    ///
+    /// Each [`DirEntry`] refers to this handle as its parent, so a traversal that descends into an entry with [`DirEntry::open`] never resolves a path from the top again.
+    pub fn read_dir(&self) -> DirIterator<'_> {
+        DirIterator {
+            dir: unsafe { BorrowedHandle::from_raw(self.0.as_raw()) },
+            state: core::ptr::null_mut(),
+            finished: false,
+        }
+    }
+
+    /// Opens the file at `path` in the access mode `access`, for the operation `op`
+    pub fn open_with<P: AsRef<OsStr>>(path: P, access: FileAccess, op: FileOp) -> Result<Self> {
+        Self::open_at(HandlePtr::null(), path, access, op)
+    }
+
+    /// Reopens the file in place with the access mode, operation, and blocking mode in `opts`.
+    ///
+    /// The handle continues to refer to the same object or stream, and the change is visible through every handle shared from it.
+    /// If an error occurs, the file remains open as it was before.
+    pub fn reopen(&mut self, opts: &ReopenOptions) -> Result<()> {
+        Error::from_code(unsafe {
+            syscall!(sys::ReopenFile(
+                self.0.as_raw(),
+                &sys::FileOpenOptions {
+                    stream_override: KStrCPtr::empty(),
+                    access_mode: opts.access.bits(),
+                    op_mode: opts.op.into_raw(),
+                    blocking_mode: opts.blocking.into_raw(),
+                    create_acl: HandlePtr::null(),
+                    extended_options: KCSlice::empty(),
+                },
+            ))
+        })
+    }
+
+    /// Opens a new handle to the same object or stream as the file, in the access mode `access` and for the operation `op`, without resolving its path again.
+    ///
+    /// [`FileOp::StreamDefault`] keeps the operation of the file. Permissions are only checked again if the operation changes, or `access` adds modes the file was not opened in,
+    ///  so this can be used to obtain a handle with less access than the file.
+    pub fn with_access(&self, access: FileAccess, op: FileOp) -> Result<OwnedFile> {
+        let mut hdl = MaybeUninit::uninit();
+        Error::from_code(unsafe {
+            syscall!(sys::ChangeFileAccessMode(
+                hdl.as_mut_ptr(),
+                self.0.as_raw(),
+                access.bits(),
+                op.into_raw(),
+            ))
+        })?;
+        Ok(unsafe { OwnedFile::from_handle(hdl.assume_init()) })
+    }
+
+    /// Opens the file at `path`, resolved against the directory `dir`, in the access mode `access`, for the operation `op`
+    pub fn open_at<'a, D: AsHandle<'a, FileHandle>, P: AsRef<OsStr>>(
+        dir: D,
+        path: P,
+        access: FileAccess,
+        op: FileOp,
+    ) -> Result<Self> {
+        let mut hdl = MaybeUninit::uninit();
+        Error::from_code(unsafe {
+            syscall!(sys::OpenFile(
+                hdl.as_mut_ptr(),
+                dir.as_handle(),
+                os_path(&path)?.to_kstr_raw(),
+                &sys::FileOpenOptions {
+                    stream_override: KStrCPtr::empty(),
+                    access_mode: access.bits(),
+                    op_mode: op.into_raw(),
+                    blocking_mode: sys::MODE_BLOCKING,
+                    create_acl: HandlePtr::null(),
+                    extended_options: KCSlice::empty(),
+                },
+            ))
+        })?;
+        Ok(unsafe { Self::from_handle(hdl.assume_init()) })
+    }
+
+    /// Creates a weak link at `name` to the object designated by the file. See [`weak_link_handle`] for the behaviour of weak links.
+    ///
+    /// This may be used for objects that have no name, such as a directory created by [`CreatePrivateDirectory`][sys::CreatePrivateDirectory].
+    pub fn associate_weak_name<P: AsRef<OsStr>>(&self, name: P) -> Result<()> {
+        Error::from_code(unsafe {
+            syscall!(sys::AssociateWeakName(
+                self.0.as_raw(),
+                HandlePtr::null(),
+                KStrCPtr::from_str(os_path(&name)?.as_str()),
+            ))
+        })
+    }
+}
+
+impl OwnedFile {
+    fn lock_with(&self, access: FileAccess) -> Result<FileLockGuard<'_>> {
+        let mut hdl = MaybeUninit::uninit();
+        Error::from_code(unsafe {
+            syscall!(sys::ChangeFileAccessMode(
+                hdl.as_mut_ptr(),
+                self.0.as_raw(),
+                access.bits(),
+                0
+            ))
+        })?;
+        Ok(FileLockGuard {
+            _lock: unsafe { OwnedFile::from_handle(hdl.assume_init()) },
+            _file: PhantomData,
+        })
+    }
+
+    /// Locks the stream shared with other shared locks, blocking until any exclusive lock is released.
+    ///
+    /// ## Interaction with other locks
+    /// The locks taken by this function and [`OwnedFile::lock_exclusive`] are soft locks (`ACCESS_LOCK_SOFT`): they only exclude other attempts to lock the stream,
+    ///  whether by these functions or by opening the stream with `ACCESS_LOCK_SOFT` or `ACCESS_LOCK_HARD`. Opening the stream without a lock is never blocked by them.
+    ///
+    /// `ACCESS_OVERRIDE_LOCK` has no effect on these locks. It only allows opening a stream that another handle has hard-locked, and only when the open does not request a lock itself.
+    pub fn lock_shared(&self) -> Result<FileLockGuard<'_>> {
+        self.lock_with(FileAccess::LOCK_SOFT | FileAccess::LOCK_SHARED)
+    }
+
+    /// Locks the stream exclusively, blocking until every other lock is released. See [`OwnedFile::lock_shared`] for how the lock interacts with other locks.
+    pub fn lock_exclusive(&self) -> Result<FileLockGuard<'_>> {
+        self.lock_with(FileAccess::LOCK_SOFT)
+    }
+
+    /// Locks the stream exclusively if no other lock is held, or returns `None` without blocking
+    pub fn try_lock(&self) -> Result<Option<FileLockGuard<'_>>> {
+        match self.lock_with(FileAccess::LOCK_SOFT | FileAccess::LOCK_TRY) {
+            Ok(guard) => Ok(Some(guard)),
+            Err(Error::WouldBlock) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Locks the stream shared if no exclusive lock is held, or returns `None` without blocking
+    pub fn try_lock_shared(&self) -> Result<Option<FileLockGuard<'_>>> {
+        match self.lock_with(FileAccess::LOCK_SOFT | FileAccess::LOCK_SHARED | FileAccess::LOCK_TRY)
+        {
+            Ok(guard) => Ok(Some(guard)),
+            Err(Error::WouldBlock) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+impl OwnedFile {
+    /// Sets the size of the file to `len` bytes, truncating it, or extending it with zero bytes. The file must be open for writing.
+    pub fn set_len(&self, len: u64) -> Result<()> {
+        Error::from_code(unsafe { syscall!(sys::TruncateStream(self.0.as_raw(), len as u128)) })
+    }
+
+    /// Allocates storage for `range` of the file, so that writes to it do not fail because the device is full. The file must be open for writing.
+    ///
+    /// If `range` extends past the end of the file, the file is extended with zero bytes. The content within `range` is not modified.
+    pub fn allocate(&self, range: Range<u64>) -> Result<()> {
+        let len = range.end.saturating_sub(range.start);
+        Error::from_code(unsafe {
+            syscall!(sys::AllocateStream(
+                self.0.as_raw(),
+                range.start as u128,
+                len as u128
+            ))
+        })
+    }
+
+    /// Blocks until the content and metadata of the file are written to the device
+    pub fn sync_all(&self) -> Result<()> {
+        Error::from_code(unsafe { syscall!(sys::SyncStream(self.0.as_raw(), 0)) })
+    }
+
+    /// Blocks until the content of the file, and only the metadata needed to read it back, is written to the device.
+    ///
+    /// This may be faster than [`OwnedFile::sync_all`], as metadata such as modification times is not written.
+    pub fn sync_data(&self) -> Result<()> {
+        Error::from_code(unsafe { syscall!(sys::SyncStream(self.0.as_raw(), sys::SYNC_DATA_ONLY)) })
+    }
+}
+
+/// A lock on the stream of an [`OwnedFile`], released when the guard is dropped.
+///
+/// The lock is held by a separate handle to the stream, opened by [`ChangeFileAccessMode`][sys::ChangeFileAccessMode], independently of the access mode of the locked handle.
+#[derive(Debug)]
+#[must_use = "The lock is released as soon as the guard is dropped"]
+pub struct FileLockGuard<'a> {
+    _lock: OwnedFile,
+    _file: PhantomData<&'a OwnedFile>,
 }
 
 unsafe impl<'a> AsHandle<'a, FileHandle> for &'a OwnedFile {
@@ -46,11 +277,34 @@ unsafe impl<'a> AsHandle<'a, FileHandle> for &'a OwnedFile {
     }
 }
 
+impl AsRawHandlePtr for OwnedFile {
+    type Handle = FileHandle;
+    fn as_raw_handle_ptr(&self) -> HandlePtr<FileHandle> {
+        self.as_raw()
+    }
+}
+
+impl IntoRawLiliumHandle for OwnedFile {
+    fn into_raw_lilium_handle(self) -> HandlePtr<FileHandle> {
+        self.0.release_ownership()
+    }
+}
+
+impl FromRawLiliumHandle for OwnedFile {
+    unsafe fn from_raw_lilium_handle(hdl: HandlePtr<FileHandle>) -> Self {
+        unsafe { Self::from_handle(hdl) }
+    }
+}
+
 impl Clone for OwnedFile {
     fn clone(&self) -> Self {
         let mut ptr = MaybeUninit::uninit();
         unsafe {
-            Error::from_code(sys::DuplicateFile(ptr.as_mut_ptr(), self.0.as_raw())).unwrap();
+            Error::from_code(syscall!(sys::DuplicateFile(
+                ptr.as_mut_ptr(),
+                self.0.as_raw()
+            )))
+            .unwrap();
         }
 
         Self(unsafe { OwnedHandle::take_ownership(ptr.assume_init()) })
@@ -104,6 +358,12 @@ impl<'a> Iterator for Components<'a> {
     }
 }
 
+impl AsRef<Path> for Path {
+    fn as_ref(&self) -> &Path {
+        self
+    }
+}
+
 impl AsRef<Path> for str {
     fn as_ref(&self) -> &Path {
         Path::new(self)
@@ -152,6 +412,72 @@ impl Path {
         self.0.len()
     }
 
+    pub const fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Normalizes the path lexically, without accessing the filesystem.
+    ///
+    /// If the path is relative, it is first joined to `base`, unless `base` is empty, in which case the result stays relative. Then `.` components and empty components are removed, and each `..` component is removed together with the preceding component,
+    ///  as in logical path resolution. A `..` component never removes the root, or the root that follows a `//` prefix designator.
+    ///
+    /// Because symbolic links are not followed, the result may name a different object than the original path when it is resolved physically. See [`canonicalize`] to resolve the path using the filesystem.
+    pub fn absolutize<B: AsRef<Path>>(&self, base: B) -> PathBuf {
+        let joined;
+        let base = base.as_ref();
+        let path = if self.is_absolute() || base.is_empty() {
+            self.as_str()
+        } else {
+            joined = alloc::format!("{}/{}", base, self);
+            &joined
+        };
+
+        let (root, rest) = match path.strip_prefix("//") {
+            Some(rest) => match rest.split_once('/') {
+                Some((prefix, rest)) => (&path[..prefix.len() + 2], rest),
+                None => (path, ""),
+            },
+            None if path.starts_with('/') => ("/", path),
+            None => ("", path),
+        };
+
+        let mut components = Vec::<&str>::new();
+        for comp in rest.split('/') {
+            match comp {
+                "" | "." => {}
+                ".." => match components.last() {
+                    Some(&"..") | None if root.is_empty() => components.push(".."),
+                    Some(_) => {
+                        components.pop();
+                    }
+                    None => {}
+                },
+                comp => components.push(comp),
+            }
+        }
+
+        let mut out = String::from(root);
+        for comp in components {
+            if !out.is_empty() && !out.ends_with('/') {
+                out.push('/');
+            }
+            out.push_str(comp);
+        }
+        if out.is_empty() {
+            out.push('.');
+        }
+        PathBuf(out)
+    }
+
+    /// Checks if the path starts at the root (with `/`), rather than being resolved against a base directory
+    pub fn is_absolute(&self) -> bool {
+        self.0.starts_with('/')
+    }
+
+    pub fn is_relative(&self) -> bool {
+        !self.is_absolute()
+    }
+
     pub const fn to_kstr_raw(&self) -> KStrCPtr {
         KStrCPtr::from_str(self.as_str())
     }
@@ -169,6 +495,46 @@ impl AsRef<str> for Path {
     }
 }
 
+impl AsRef<OsStr> for Path {
+    fn as_ref(&self) -> &OsStr {
+        OsStr::from_str(&self.0)
+    }
+}
+
+impl AsRef<OsStr> for PathBuf {
+    fn as_ref(&self) -> &OsStr {
+        OsStr::from_str(&self.0)
+    }
+}
+
+impl<'a> TryFrom<&'a OsStr> for &'a Path {
+    type Error = Error;
+
+    /// Borrows `st` as a path. Fails with [`Error::InvalidString`] if it is not valid UTF-8, as the kernel only accepts UTF-8 paths.
+    fn try_from(st: &'a OsStr) -> Result<Self> {
+        st.as_kstr().map(|st| Path::new(st.as_str()))
+    }
+}
+
+/// Borrows `path` as a [`Path`], failing with [`Error::InvalidString`] if it is not valid UTF-8. Used by the functions that accept any `AsRef<OsStr>` as a path.
+pub(crate) fn os_path<P: AsRef<OsStr> + ?Sized>(path: &P) -> Result<&Path> {
+    path.as_ref().try_into()
+}
+
+impl TryFrom<OsString> for PathBuf {
+    type Error = OsString;
+
+    fn try_from(st: OsString) -> core::result::Result<Self, OsString> {
+        st.into_string().map(PathBuf)
+    }
+}
+
+impl From<PathBuf> for OsString {
+    fn from(path: PathBuf) -> Self {
+        OsString::from(path.0)
+    }
+}
+
 impl AsRef<[u8]> for Path {
     fn as_ref(&self) -> &[u8] {
         self.0.as_bytes()
@@ -221,115 +587,749 @@ impl Borrow<Path> for PathBuf {
     }
 }
 
-pub fn read_link<P: AsRef<Path>>(path: P) -> crate::result::Result<PathBuf> {
-    let path = path.as_ref();
-
-    let mut buf = Vec::<u8>::with_capacity(256);
+#[cfg(feature = "serde")]
+impl serde::Serialize for Path {
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> core::result::Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
 
-    let mut kstr = KStrPtr {
-        str_ptr: buf.as_mut_ptr(),
-        len: 256,
-    };
+#[cfg(feature = "serde")]
+impl<'de: 'a, 'a> serde::Deserialize<'de> for &'a Path {
+    fn deserialize<D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> core::result::Result<Self, D::Error> {
+        <&str>::deserialize(deserializer).map(Path::new)
+    }
+}
 
-    match crate::result::Error::from_code(unsafe {
-        sys::ReadSymbolicLink(
-            HandlePtr::null(),
-            KStrCPtr::from_str(path.as_ref()),
-            &mut kstr,
-        )
-    }) {
-        Ok(()) => {
-            if kstr.len > 256 {
-                buf.reserve(kstr.len as usize);
-                kstr.str_ptr = buf.as_mut_ptr();
-                crate::result::Error::from_code(unsafe {
-                    sys::ReadSymbolicLink(
-                        HandlePtr::null(),
-                        KStrCPtr::from_str(path.as_ref()),
-                        &mut kstr,
-                    )
-                })?;
-            }
-        }
-        Err(Error::InsufficientLength) => {
-            buf.reserve(kstr.len as usize);
-            kstr.str_ptr = buf.as_mut_ptr();
-            crate::result::Error::from_code(unsafe {
-                sys::ReadSymbolicLink(
-                    HandlePtr::null(),
-                    KStrCPtr::from_str(path.as_ref()),
-                    &mut kstr,
-                )
-            })?;
-        }
-        Err(e) => return Err(e),
+#[cfg(feature = "serde")]
+impl serde::Serialize for PathBuf {
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> core::result::Result<S::Ok, S::Error> {
+        self.as_path().serialize(serializer)
     }
+}
 
-    // SAFETY:
-    // The kernel wrote exactly kstr.len bytes
-    unsafe {
-        buf.set_len(kstr.len as usize);
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for PathBuf {
+    fn deserialize<D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> core::result::Result<Self, D::Error> {
+        String::deserialize(deserializer).map(PathBuf::from_string)
     }
+}
+
+pub fn read_link<P: AsRef<OsStr>>(path: P) -> crate::result::Result<PathBuf> {
+    let path = os_path(&path)?;
 
-    buf.shrink_to_fit();
+    let mut st = with_growing_buffer(256, |kstr| unsafe {
+        syscall!(sys::ReadSymbolicLink(
+            HandlePtr::null(),
+            path.to_kstr_raw(),
+            kstr
+        ))
+    })?;
 
-    // SAFETY:
-    // The Lillium kernel guarantees that a non-truncated strings returned from kernel space to userspace are valid UTF-8
-    let st = unsafe { String::from_utf8_unchecked(buf) };
+    st.shrink_to_fit();
 
     Ok(PathBuf(st))
 }
 
-pub fn hard_link<P: AsRef<Path>, Q: AsRef<Path>>(
+pub fn hard_link<P: AsRef<OsStr>, Q: AsRef<OsStr>>(
     original: P,
     link: Q,
 ) -> crate::result::Result<()> {
     crate::result::Error::from_code(unsafe {
-        sys::CreateHardLink(
+        syscall!(sys::CreateHardLink(
             core::ptr::null_mut(),
             HandlePtr::null(),
-            KStrCPtr::from_str(link.as_ref().as_str()),
+            KStrCPtr::from_str(os_path(&link)?.as_str()),
             HandlePtr::null(),
-            KStrCPtr::from_str(original.as_ref().as_str()),
-        )
+            KStrCPtr::from_str(os_path(&original)?.as_str()),
+        ))
     })
 }
 
-pub fn weak_link<P: AsRef<Path>, Q: AsRef<Path>>(
+pub fn weak_link<P: AsRef<OsStr>, Q: AsRef<OsStr>>(
     original: P,
     link: Q,
 ) -> crate::result::Result<()> {
     crate::result::Error::from_code(unsafe {
-        crate::sys::fs::CreateWeakLink(
+        syscall!(crate::sys::fs::CreateWeakLink(
             core::ptr::null_mut(),
             HandlePtr::null(),
-            KStrCPtr::from_str(link.as_ref().as_str()),
+            KStrCPtr::from_str(os_path(&link)?.as_str()),
+            HandlePtr::null(),
+            KStrCPtr::from_str(os_path(&original)?.as_str()),
+        ))
+    })
+}
+
+/// Creates a weak link at `link` to the object at `original`, and opens the object.
+///
+/// A weak link does not keep the object alive: once every strong link to the object is removed, the object is destroyed,
+///  and resolving the weak link fails with [`Error::DoesNotExist`], even though the weak link itself remains.
+pub fn weak_link_handle<P: AsRef<OsStr>, Q: AsRef<OsStr>>(
+    original: P,
+    link: Q,
+) -> Result<OwnedFile> {
+    let mut hdl = MaybeUninit::uninit();
+    Error::from_code(unsafe {
+        syscall!(sys::CreateWeakLink(
+            hdl.as_mut_ptr(),
+            HandlePtr::null(),
+            KStrCPtr::from_str(os_path(&link)?.as_str()),
+            HandlePtr::null(),
+            KStrCPtr::from_str(os_path(&original)?.as_str()),
+        ))
+    })?;
+    Ok(unsafe { OwnedFile::from_handle(hdl.assume_init()) })
+}
+
+/// Converts the weak link at `path` into a strong link, and opens the object it refers to.
+///
+/// ## Errors
+/// Returns [`Error::DoesNotExist`] if the object the weak link referred to has been destroyed. The weak link is left in place, and can be removed with [`RemoveLink`][sys::RemoveLink].
+pub fn upgrade_link<P: AsRef<OsStr>>(path: P) -> Result<OwnedFile> {
+    let mut hdl = MaybeUninit::uninit();
+    Error::from_code(unsafe {
+        syscall!(sys::UpgradeLink(
+            hdl.as_mut_ptr(),
+            HandlePtr::null(),
+            KStrCPtr::from_str(os_path(&path)?.as_str()),
+        ))
+    })?;
+    Ok(unsafe { OwnedFile::from_handle(hdl.assume_init()) })
+}
+
+/// Converts the strong link at `path` into a weak link.
+///
+/// If it was the last strong link to the object, the object is destroyed once every handle to it is closed, and resolving the link then fails with [`Error::DoesNotExist`].
+pub fn downgrade_link<P: AsRef<OsStr>>(path: P) -> Result<()> {
+    Error::from_code(unsafe {
+        syscall!(sys::DowngradeLink(
             HandlePtr::null(),
-            KStrCPtr::from_str(original.as_ref().as_str()),
-        )
+            KStrCPtr::from_str(os_path(&path)?.as_str()),
+        ))
     })
 }
 
-pub fn symlink<P: AsRef<Path>, Q: AsRef<Path>>(original: P, link: Q) -> crate::result::Result<()> {
+pub fn symlink<P: AsRef<OsStr>, Q: AsRef<OsStr>>(
+    original: P,
+    link: Q,
+) -> crate::result::Result<()> {
     crate::result::Error::from_code(unsafe {
-        crate::sys::fs::CreateSymbolicLink(
+        syscall!(crate::sys::fs::CreateSymbolicLink(
+            HandlePtr::null(),
+            KStrCPtr::from_str(os_path(&link)?.as_str()),
+            KStrCPtr::from_str(os_path(&original)?.as_str()),
+        ))
+    })
+}
+
+/// Resolves `path` to an absolute physical path, following every symbolic link, and removing every `.` and `..` component.
+///
+/// The path is resolved logically, then physically, as a system call would resolve it. If the object is reached through a `//` prefix designator, the result keeps that prefix designator.
+///
+/// ## Errors
+/// Returns [`Error::DoesNotExist`] if any component of `path` does not exist, and [`Error::LinkResolutionLoop`] if resolving symbolic links does not terminate.
+pub fn canonicalize<P: AsRef<OsStr>>(path: P) -> Result<PathBuf> {
+    let path = os_path(&path)?;
+
+    let mut st = with_growing_buffer(256, |kstr| unsafe {
+        syscall!(sys::ResolvePath(
+            HandlePtr::null(),
+            path.to_kstr_raw(),
+            kstr
+        ))
+    })?;
+
+    st.shrink_to_fit();
+
+    Ok(PathBuf(st))
+}
+
+/// Reads the target of the symbolic link at `path`, resolved against the directory `dir`
+pub fn read_link_at<'a, D: AsHandle<'a, FileHandle>, P: AsRef<OsStr>>(
+    dir: D,
+    path: P,
+) -> Result<PathBuf> {
+    let path = os_path(&path)?;
+
+    let mut st = with_growing_buffer(256, |kstr| unsafe {
+        syscall!(sys::ReadSymbolicLink(
+            dir.as_handle(),
+            path.to_kstr_raw(),
+            kstr
+        ))
+    })?;
+
+    st.shrink_to_fit();
+
+    Ok(PathBuf(st))
+}
+
+/// Creates a symbolic link at `link`, resolved against the directory `dir`, which points to `original`.
+///
+/// `original` is not resolved when the link is created. If it is relative, it is resolved against the directory containing the link whenever the link is followed.
+pub fn symlink_at<'a, P: AsRef<OsStr>, D: AsHandle<'a, FileHandle>, Q: AsRef<OsStr>>(
+    original: P,
+    dir: D,
+    link: Q,
+) -> Result<()> {
+    Error::from_code(unsafe {
+        syscall!(sys::CreateSymbolicLink(
+            dir.as_handle(),
+            KStrCPtr::from_str(os_path(&link)?.as_str()),
+            KStrCPtr::from_str(os_path(&original)?.as_str()),
+        ))
+    })
+}
+
+/// Creates a hard link at `link`, resolved against the directory `link_dir`, to the object at `original`, resolved against the directory `original_dir`
+pub fn hard_link_at<
+    'a,
+    'b,
+    D: AsHandle<'a, FileHandle>,
+    P: AsRef<OsStr>,
+    E: AsHandle<'b, FileHandle>,
+    Q: AsRef<OsStr>,
+>(
+    original_dir: D,
+    original: P,
+    link_dir: E,
+    link: Q,
+) -> Result<()> {
+    Error::from_code(unsafe {
+        syscall!(sys::CreateHardLink(
+            core::ptr::null_mut(),
+            link_dir.as_handle(),
+            os_path(&link)?.to_kstr_raw(),
+            original_dir.as_handle(),
+            os_path(&original)?.to_kstr_raw(),
+        ))
+    })
+}
+
+/// Renames the object at `from`, resolved against the directory `from_dir`, to `to`, resolved against the directory `to_dir`
+pub fn rename_at<
+    'a,
+    'b,
+    D: AsHandle<'a, FileHandle>,
+    P: AsRef<OsStr>,
+    E: AsHandle<'b, FileHandle>,
+    Q: AsRef<OsStr>,
+>(
+    from_dir: D,
+    from: P,
+    to_dir: E,
+    to: Q,
+) -> Result<()> {
+    Error::from_code(unsafe {
+        syscall!(sys::RenameObject(
+            to_dir.as_handle(),
+            os_path(&to)?.to_kstr_raw(),
+            from_dir.as_handle(),
+            os_path(&from)?.to_kstr_raw(),
+        ))
+    })
+}
+
+/// Removes the link at `path`, resolved against the directory `dir`.
+///
+/// If it was the last link to the object, the object is destroyed once every handle to it is closed.
+pub fn remove_at<'a, D: AsHandle<'a, FileHandle>, P: AsRef<OsStr>>(dir: D, path: P) -> Result<()> {
+    Error::from_code(unsafe {
+        syscall!(sys::RemoveLink(
+            dir.as_handle(),
+            os_path(&path)?.to_kstr_raw()
+        ))
+    })
+}
+
+/// Creates a directory at `path`, resolved against the directory `dir`, and opens it
+pub fn create_dir_at<'a, D: AsHandle<'a, FileHandle>, P: AsRef<OsStr>>(
+    dir: D,
+    path: P,
+) -> Result<OwnedFile> {
+    let mut hdl = MaybeUninit::uninit();
+    Error::from_code(unsafe {
+        syscall!(sys::CreateDirectory(
+            hdl.as_mut_ptr(),
+            dir.as_handle(),
+            os_path(&path)?.to_kstr_raw(),
+            HandlePtr::null(),
+        ))
+    })?;
+    Ok(unsafe { OwnedFile::from_handle(hdl.assume_init()) })
+}
+
+/// How the target of a symbolic link is resolved when the link is followed
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+pub enum LinkKind {
+    /// The target does not start with `/`, and is resolved against the directory containing the link.
+    ///
+    /// During logical resolution, `..` components of the target are removed together with the preceding components of the path of the link,
+    ///  so they step out of the directory the link was reached through. During physical resolution, they step out of the directory that contains the link.
+    Relative,
+    /// The target starts with a single `/`, and is resolved against the resolution root
+    Absolute,
+    /// The target starts with the `//` prefix designator. The root that follows the designator cannot be removed by `..` components.
+    Prefixed,
+}
+
+impl LinkKind {
+    /// Classifies the symbolic link target `target`
+    pub fn of<P: AsRef<Path> + ?Sized>(target: &P) -> Self {
+        let target = target.as_ref().as_str();
+        if target.starts_with("//") {
+            Self::Prefixed
+        } else if target.starts_with('/') {
+            Self::Absolute
+        } else {
+            Self::Relative
+        }
+    }
+}
+
+/// A symbolic link, opened directly rather than followed.
+///
+/// The link is opened with `ACCESS_LINK_STREAM_DIRECT` on its `SymbolicLinkContent` stream, which holds the target of the link.
+#[derive(Debug, Hash, PartialEq, Eq)]
+pub struct Symlink(OwnedFile);
+
+impl Symlink {
+    /// Opens the symbolic link at `path`
+    pub fn open<P: AsRef<OsStr>>(path: P) -> Result<Self> {
+        Self::open_in(HandlePtr::null(), os_path(&path)?)
+    }
+
+    /// Opens the symbolic link at `path`, resolved against the directory `dir`
+    pub fn open_at<'a, D: AsHandle<'a, FileHandle>, P: AsRef<OsStr>>(
+        dir: D,
+        path: P,
+    ) -> Result<Self> {
+        Self::open_in(dir.as_handle(), os_path(&path)?)
+    }
+
+    fn open_in(base: HandlePtr<FileHandle>, path: &Path) -> Result<Self> {
+        let mut hdl = MaybeUninit::uninit();
+        Error::from_code(unsafe {
+            syscall!(sys::OpenFile(
+                hdl.as_mut_ptr(),
+                base,
+                path.to_kstr_raw(),
+                &sys::FileOpenOptions {
+                    stream_override: KStrCPtr::from_str("SymbolicLinkContent"),
+                    access_mode: sys::ACCESS_READ | sys::ACCESS_LINK_STREAM_DIRECT,
+                    op_mode: sys::OP_DATA_ACCESS,
+                    blocking_mode: sys::MODE_BLOCKING,
+                    create_acl: HandlePtr::null(),
+                    extended_options: KCSlice::empty(),
+                },
+            ))
+        })?;
+        Ok(Self(unsafe { OwnedFile::from_handle(hdl.assume_init()) }))
+    }
+
+    /// Reads the target of the link.
+    ///
+    /// The content is always read from the start of the link, so this can be called more than once.
+    pub fn target(&self) -> Result<PathBuf> {
+        let io = self.0.as_io_handle();
+        io.seek(crate::io::SeekFrom::Start(0))?;
+        let mut buf = Vec::new();
+        let mut chunk = [0u8; 256];
+        loop {
+            match io.read(&mut chunk) {
+                Ok(0) => break,
+                Ok(n) => buf.extend_from_slice(&chunk[..n]),
+                Err(Error::Interrupted) => continue,
+                Err(e) => return Err(e),
+            }
+        }
+        String::from_utf8(buf)
+            .map(PathBuf)
+            .map_err(|_| Error::InvalidString)
+    }
+
+    /// Reads the target of the link, and classifies how it is resolved
+    pub fn kind(&self) -> Result<LinkKind> {
+        self.target().map(|target| LinkKind::of(&target))
+    }
+
+    pub fn as_file(&self) -> &OwnedFile {
+        &self.0
+    }
+
+    pub fn into_file(self) -> OwnedFile {
+        self.0
+    }
+}
+
+bitflags::bitflags! {
+    /// The access mode a file is opened in, made of the `ACCESS_*` constants
+    #[repr(transparent)]
+    #[derive(Copy, Clone, Hash, PartialEq, Eq, Debug)]
+    pub struct FileAccess : u32 {
+        const READ = sys::ACCESS_READ;
+        const WRITE = sys::ACCESS_WRITE;
+        const CREATE = sys::ACCESS_CREATE;
+        const CREATE_EXCLUSIVE = sys::ACCESS_CREATE_EXCLUSIVE;
+        const LOCK_SOFT = sys::ACCESS_LOCK_SOFT;
+        const LOCK_HARD = sys::ACCESS_LOCK_HARD;
+        const LOCK_SHARED = sys::ACCESS_LOCK_SHARED;
+        const NO_BYPASS_ACL = sys::ACCESS_NO_BYPASS_ACL;
+        const OVERRIDE_LOCK = sys::ACCESS_OVERRIDE_LOCK;
+        const CREATE_STREAM_ONLY = sys::ACCESS_CREATE_STREAM_ONLY;
+        const LINK_STREAM_DIRECT = sys::ACCESS_LINK_STREAM_DIRECT;
+        const TRUNCATE = sys::ACCESS_TRUNCATE;
+        const START_END = sys::ACCESS_START_END;
+        const LOCK_TRY = sys::ACCESS_LOCK_TRY;
+    }
+}
+
+/// The operation a file is opened for, one of the `OP_*` constants.
+///
+/// Unlike the access mode, the operation is a single value rather than a set of flags.
+#[derive(Copy, Clone, Debug, Default, Hash, PartialEq, Eq)]
+pub enum FileOp {
+    /// The default operation of the stream
+    #[default]
+    StreamDefault,
+    /// The raw data of the stream
+    DataAccess,
+    /// The stream viewed as a directory
+    DirectoryAccess,
+    /// The access control list of the file or stream
+    AclAccess,
+    /// The object itself, without opening a stream
+    NoAccess,
+}
+
+impl FileOp {
+    /// The `OP_*` constant for the operation
+    pub const fn into_raw(self) -> u32 {
+        match self {
+            Self::StreamDefault => sys::OP_STREAM_DEFAULT,
+            Self::DataAccess => sys::OP_DATA_ACCESS,
+            Self::DirectoryAccess => sys::OP_DIRECTORY_ACCESS,
+            Self::AclAccess => sys::OP_ACL_ACCESS,
+            Self::NoAccess => sys::OP_NO_ACCESS,
+        }
+    }
+
+    /// The operation for an `OP_*` constant, or `None` if `op` is not an operation
+    pub const fn from_raw(op: u32) -> Option<Self> {
+        match op {
+            sys::OP_STREAM_DEFAULT => Some(Self::StreamDefault),
+            sys::OP_DATA_ACCESS => Some(Self::DataAccess),
+            sys::OP_DIRECTORY_ACCESS => Some(Self::DirectoryAccess),
+            sys::OP_ACL_ACCESS => Some(Self::AclAccess),
+            sys::OP_NO_ACCESS => Some(Self::NoAccess),
+            _ => None,
+        }
+    }
+}
+
+/// Options for reopening a file in place with [`OwnedFile::reopen`].
+///
+/// Reopening never changes which object or stream the handle refers to, so unlike opening a file, there is no way to set a stream override or a creation ACL.
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+pub struct ReopenOptions {
+    access: FileAccess,
+    op: FileOp,
+    blocking: BlockingMode,
+}
+
+impl Default for ReopenOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ReopenOptions {
+    pub const fn new() -> Self {
+        Self {
+            access: FileAccess::empty(),
+            op: FileOp::StreamDefault,
+            blocking: BlockingMode::Blocking,
+        }
+    }
+
+    /// Sets the access mode the file is reopened in
+    pub fn access(&mut self, access: FileAccess) -> &mut Self {
+        self.access = access;
+        self
+    }
+
+    /// Sets the operation the file is reopened for
+    pub fn op(&mut self, op: FileOp) -> &mut Self {
+        self.op = op;
+        self
+    }
+
+    /// Sets how operations on the reopened file block
+    pub fn blocking(&mut self, mode: BlockingMode) -> &mut Self {
+        self.blocking = mode;
+        self
+    }
+}
+
+bitflags::bitflags! {
+    /// Flags for mounting a filesystem, made of the `MOUNT_*` constants. See [`MountOptions`].
+    #[repr(transparent)]
+    #[derive(Copy, Clone, Hash, PartialEq, Eq, Debug)]
+    pub struct MountFlags : u32{
+        const REPLACE_ACLS = device::MOUNT_REPLACE_ACLS;
+        const ALLOW_PRIVILAGED = device::MOUNT_ALLOW_PRIVILAGED;
+        const REPLACE_LEGACY_PERMISSIONS = device::MOUNT_REPLACE_LEGACY_PERMISSIONS;
+    }
+}
+
+/// Options for mounting a filesystem with [`mount`]
+#[derive(Clone, Debug)]
+pub struct MountOptions<'a> {
+    flags: MountFlags,
+    default_acl: Option<&'a Permissions>,
+    principal_map: HandlePtr<IOHandle>,
+    _map: PhantomData<&'a IOHandle>,
+}
+
+impl<'a> Default for MountOptions<'a> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a> MountOptions<'a> {
+    pub const fn new() -> Self {
+        Self {
+            flags: MountFlags::empty(),
+            default_acl: None,
+            principal_map: HandlePtr::null(),
+            _map: PhantomData,
+        }
+    }
+
+    /// Replaces the ACLs of every object in the mounted filesystem with the default ACL
+    pub fn replace_acls(&mut self, replace: bool) -> &mut Self {
+        self.flags.set(MountFlags::REPLACE_ACLS, replace);
+        self
+    }
+
+    /// Allows the use of `InstallSecurityContext` and legacy SUID/SGID bits on objects in the mounted filesystem.
+    ///
+    /// Mounting fails with [`Error::Permission`] unless the current thread has the `MountPrivilagedExec` kernel permission.
+    pub fn allow_privileged(&mut self, allow: bool) -> &mut Self {
+        self.flags.set(MountFlags::ALLOW_PRIVILAGED, allow);
+        self
+    }
+
+    /// Treats every object as having the default ACL if the filesystem only supports legacy permissions
+    pub fn replace_legacy_permissions(&mut self, replace: bool) -> &mut Self {
+        self.flags.set(MountFlags::REPLACE_LEGACY_PERMISSIONS, replace);
+        self
+    }
+
+    /// Replaces all of the mount flags with `flags`
+    pub fn flags(&mut self, flags: MountFlags) -> &mut Self {
+        self.flags = flags;
+        self
+    }
+
+    /// Sets the ACL used for objects without permissions, or whose permissions are replaced
+    pub fn default_acl(&mut self, acl: &'a Permissions) -> &mut Self {
+        self.default_acl = Some(acl);
+        self
+    }
+
+    /// Sets the stream used to map legacy uids and gids on the filesystem to Lilium principals.
+    ///
+    /// The stream must be readable and seekable.
+    pub fn principal_map<H: AsHandle<'a, IOHandle>>(&mut self, map: H) -> &mut Self {
+        self.principal_map = map.as_handle();
+        self
+    }
+
+    fn as_raw(&self) -> device::MountOptions {
+        device::MountOptions {
+            default_acl: self
+                .default_acl
+                .map_or_else(HandlePtr::null, |acl| acl.0.as_raw()),
+            flags: self.flags.bits(),
+            legacy_principal_map: self.principal_map,
+        }
+    }
+}
+
+/// Mounts the filesystem on the device designated by `devid` at `path`
+pub fn mount<P: AsRef<OsStr>>(path: P, devid: Uuid, options: &MountOptions) -> Result<()> {
+    let opts = options.as_raw();
+    Error::from_code(unsafe {
+        syscall!(device::MountFilesystem(
             HandlePtr::null(),
-            KStrCPtr::from_str(link.as_ref().as_str()),
-            KStrCPtr::from_str(original.as_ref().as_str()),
-        )
+            KStrCPtr::from_str(os_path(&path)?.as_str()),
+            devid,
+            &opts,
+        ))
     })
 }
 
-pub fn create_dir_all<P: AsRef<Path>>(path: P) -> crate::result::Result<()> {
-    let path = path.as_ref();
+/// Unmounts the filesystem mounted at `path`
+pub fn unmount<P: AsRef<OsStr>>(path: P) -> Result<()> {
+    Error::from_code(unsafe {
+        syscall!(device::UnmountFilesystem(
+            HandlePtr::null(),
+            KStrCPtr::from_str(os_path(&path)?.as_str()),
+        ))
+    })
+}
+
+/// The identity of a filesystem object, returned by [`OwnedFile::object_id`]
+///
+/// Two files with the same `ObjectId` are the same object, even if they were opened through different paths.
+#[derive(Copy, Clone, Hash, PartialEq, Eq, Debug)]
+pub struct ObjectId {
+    filesystem: Uuid,
+    object: u128,
+}
+
+impl ObjectId {
+    /// The id of the mounted filesystem the object resides on
+    pub const fn filesystem(&self) -> Uuid {
+        self.filesystem
+    }
+
+    /// The id of the object within its filesystem
+    pub const fn object(&self) -> u128 {
+        self.object
+    }
+}
+
+impl OwnedFile {
+    /// Determines the identity of the object the file refers to
+    pub fn object_id(&self) -> Result<ObjectId> {
+        let mut id = MaybeUninit::<sys::ObjectId>::uninit();
+        Error::from_code(unsafe { syscall!(sys::GetObjectId(self.0.as_raw(), id.as_mut_ptr())) })?;
+        let id = unsafe { id.assume_init() };
+
+        Ok(ObjectId {
+            filesystem: id.filesystem,
+            object: id.object,
+        })
+    }
+}
+
+/// Determines whether the paths `a` and `b` refer to the same filesystem object, such as through hard links or symbolic links.
+pub fn same_object<P: AsRef<OsStr>, Q: AsRef<OsStr>>(a: P, b: Q) -> Result<bool> {
+    let a = OwnedFile::open_with(a, FileAccess::empty(), FileOp::NoAccess)?;
+    let b = OwnedFile::open_with(b, FileAccess::empty(), FileOp::NoAccess)?;
+    Ok(a.object_id()? == b.object_id()?)
+}
+
+bitflags::bitflags! {
+    /// The optional features supported by a filesystem, made of the `FS_FEATURE_*` constants
+    #[repr(transparent)]
+    #[derive(Copy, Clone, Hash, PartialEq, Eq, Debug)]
+    pub struct FsFeatures : u64 {
+        const STREAMS = sys::FS_FEATURE_STREAMS;
+        const ACLS = sys::FS_FEATURE_ACLS;
+        const ANONYMOUS_OBJECTS = sys::FS_FEATURE_ANONYMOUS_OBJECTS;
+    }
+}
+
+/// Information about a mounted filesystem, returned by [`fs_stats`] or [`OwnedFile::fs_stats`]
+#[derive(Copy, Clone, Hash, PartialEq, Eq, Debug)]
+pub struct FsStats {
+    fs_type: Uuid,
+    device_id: Uuid,
+    block_size: u64,
+    total_blocks: u64,
+    free_blocks: u64,
+    available_blocks: u64,
+    features: FsFeatures,
+}
+
+impl FsStats {
+    /// The id of the filesystem type
+    pub const fn fs_type(&self) -> Uuid {
+        self.fs_type
+    }
+
+    /// The id of the device the filesystem is mounted from, or `None` for a virtual filesystem
+    pub fn device_id(&self) -> Option<Uuid> {
+        Some(self.device_id).filter(|id| *id != Uuid::NIL)
+    }
+
+    /// The size in bytes of the blocks the filesystem allocates storage in
+    pub const fn block_size(&self) -> u64 {
+        self.block_size
+    }
+
+    /// The total size of the filesystem, in bytes
+    pub const fn total_space(&self) -> u64 {
+        self.total_blocks.saturating_mul(self.block_size)
+    }
+
+    /// The number of bytes that are not allocated
+    pub const fn free_space(&self) -> u64 {
+        self.free_blocks.saturating_mul(self.block_size)
+    }
+
+    /// The number of bytes the current thread can allocate, which may be less than [`FsStats::free_space`] if the filesystem reserves space
+    pub const fn available_space(&self) -> u64 {
+        self.available_blocks.saturating_mul(self.block_size)
+    }
+
+    /// The optional features the filesystem supports
+    pub const fn features(&self) -> FsFeatures {
+        self.features
+    }
+}
+
+impl OwnedFile {
+    /// Queries information about the filesystem the file resides on
+    pub fn fs_stats(&self) -> Result<FsStats> {
+        let mut info = MaybeUninit::<sys::FilesystemInfo>::uninit();
+        Error::from_code(unsafe {
+            syscall!(sys::GetFilesystemInfo(self.0.as_raw(), info.as_mut_ptr()))
+        })?;
+        let info = unsafe { info.assume_init() };
+
+        Ok(FsStats {
+            fs_type: info.fs_type,
+            device_id: info.device_id,
+            block_size: info.block_size,
+            total_blocks: info.total_blocks,
+            free_blocks: info.free_blocks,
+            available_blocks: info.available_blocks,
+            features: FsFeatures::from_bits_retain(info.features),
+        })
+    }
+}
+
+/// Queries information about the filesystem that the object at `path` resides on, similar to `statvfs`
+pub fn fs_stats<P: AsRef<OsStr>>(path: P) -> Result<FsStats> {
+    OwnedFile::open_with(path, FileAccess::empty(), FileOp::NoAccess)?.fs_stats()
+}
+
+pub fn create_dir_all<P: AsRef<OsStr>>(path: P) -> crate::result::Result<()> {
+    let path = os_path(&path)?;
 
     let mut cur_base = HandlePtr::null();
 
     for seg in path.components() {
         loop {
             match crate::result::Error::from_code(unsafe {
-                sys::OpenFile(
-                    &mut cur_base,
+                syscall!(sys::OpenFile(
+                    core::ptr::addr_of_mut!(cur_base),
                     cur_base,
                     KStrCPtr::from_str(seg.as_str()),
                     &sys::FileOpenOptions {
@@ -340,17 +1340,17 @@ pub fn create_dir_all<P: AsRef<Path>>(path: P) -> crate::result::Result<()> {
                         create_acl: HandlePtr::null(),
                         extended_options: KCSlice::empty(),
                     },
-                )
+                ))
             }) {
                 Ok(()) => break,
                 Err(crate::result::Error::DoesNotExist) => {
                     match crate::result::Error::from_code(unsafe {
-                        sys::CreateDirectory(
-                            &mut cur_base,
+                        syscall!(sys::CreateDirectory(
+                            core::ptr::addr_of_mut!(cur_base),
                             cur_base,
                             KStrCPtr::from_str(seg.as_str()),
                             HandlePtr::null(),
-                        )
+                        ))
                     }) {
                         Ok(()) => break,
                         Err(crate::result::Error::AlreadyExists) => continue,
@@ -365,12 +1365,267 @@ pub fn create_dir_all<P: AsRef<Path>>(path: P) -> crate::result::Result<()> {
     Ok(())
 }
 
-pub struct DirIterator {
-    dirhdl: HandlePtr<FileHandle>,
-    base_path: PathBuf,
+/// Reads the entire content of the file at `path`.
+///
+/// The buffer is sized from the size of the stream when it is opened, and the file is read until the end of the stream, even if it grows while it is read.
+pub fn read<P: AsRef<OsStr>>(path: P) -> Result<Vec<u8>> {
+    let file = OwnedFile::open_with(path, FileAccess::READ, FileOp::DataAccess)?;
+    let size = unsafe { syscall!(sys::StreamSize(file.as_raw())) }.into_size()?;
+
+    let mut buf = Vec::new();
+    buf.try_reserve_exact(size as usize)
+        .map_err(|_| Error::InsufficientMemory)?;
+
+    let stream = file.as_io_handle();
+    loop {
+        if buf.len() == buf.capacity() {
+            buf.try_reserve(32).map_err(|_| Error::InsufficientMemory)?;
+        }
+        let len = buf.len();
+        buf.resize(buf.capacity(), 0);
+        match stream.read(&mut buf[len..]) {
+            Ok(0) => {
+                buf.truncate(len);
+                break Ok(buf);
+            }
+            Ok(n) => buf.truncate(len + n),
+            Err(Error::Interrupted) => buf.truncate(len),
+            Err(e) => break Err(e),
+        }
+    }
+}
+
+/// Reads the entire content of the file at `path` as a string.
+///
+/// ## Errors
+/// Returns [`Error::InvalidString`] if the content is not valid UTF-8, and otherwise the errors of [`read`].
+pub fn read_to_string<P: AsRef<OsStr>>(path: P) -> Result<String> {
+    String::from_utf8(read(path)?).map_err(|_| Error::InvalidString)
+}
+
+/// Writes `contents` to the file at `path`, creating it if it does not exist, and replacing its content if it does.
+pub fn write<P: AsRef<OsStr>, C: AsRef<[u8]>>(path: P, contents: C) -> Result<()> {
+    let file = OwnedFile::open_with(
+        path,
+        FileAccess::WRITE | FileAccess::CREATE | FileAccess::TRUNCATE,
+        FileOp::DataAccess,
+    )?;
+
+    file.as_io_handle().write_all(contents.as_ref())
+}
+
+/// The name of the stream that holds the security context a program is started with, as by [`install_security_context`]
+const INSTALL_SECURITY_CONTEXT: &str = "InstallSecurityContext";
+
+/// The step of [`install_security_context`] or [`installed_security_context`] that failed
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum InstallContextStep {
+    /// Opening the file
+    Open,
+    /// Encoding the security context
+    Encode,
+    /// Writing the `InstallSecurityContext` stream
+    Write,
+    /// Reading the `InstallSecurityContext` stream
+    Read,
+    /// Decoding the content of the stream
+    Decode,
+}
+
+/// An error from [`install_security_context`] or [`installed_security_context`], with the step that failed.
+///
+/// The [`Display`][core::fmt::Display] impl describes the likely cause of an [`Error::Permission`] for the step, followed by the context the kernel recorded for the error, if any.
+/// As the error is part of that message, it is not also reported as the [`source`][core::error::Error::source].
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub struct InstallContextError {
+    error: ContextError,
+    step: InstallContextStep,
+}
+
+impl InstallContextError {
+    /// Captures the context of `error`, so it must be called right after the system call that failed
+    const fn new(step: InstallContextStep) -> impl FnOnce(Error) -> Self {
+        move |error| Self {
+            error: error.with_context(),
+            step,
+        }
+    }
+
+    pub fn error(&self) -> Error {
+        self.error.error()
+    }
+
+    /// The context the kernel recorded for the error, such as the permission that was denied
+    pub fn context(&self) -> &[ErrorContext] {
+        self.error.context()
+    }
+
+    pub fn step(&self) -> InstallContextStep {
+        self.step
+    }
+
+    /// Whether the step failed because the current thread lacks a permission
+    pub fn is_permission(&self) -> bool {
+        self.error() == Error::Permission
+    }
+}
+
+impl core::fmt::Display for InstallContextError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let step = match self.step {
+            InstallContextStep::Open => "opening the file",
+            InstallContextStep::Encode => "encoding the security context",
+            InstallContextStep::Write => "writing the InstallSecurityContext stream",
+            InstallContextStep::Read => "reading the InstallSecurityContext stream",
+            InstallContextStep::Decode => "decoding the InstallSecurityContext stream",
+        };
+        f.write_fmt(format_args!("{} while {}", self.error(), step))?;
+        if self.is_permission() {
+            let hint = match self.step {
+                InstallContextStep::Open => "the current thread cannot resolve the path",
+                InstallContextStep::Encode => "the security context has a principal or permission the current thread does not have, and the current thread does not have the SECURITY_SET_CREDENTIAL kernel permission",
+                InstallContextStep::Write => "the access control list of the file does not allow the current thread to create or write the InstallSecurityContext stream",
+                InstallContextStep::Read => "the access control list of the file does not allow the current thread to read the InstallSecurityContext stream",
+                InstallContextStep::Decode => "the stream could not be decoded",
+            };
+            f.write_fmt(format_args!(" ({})", hint))?;
+        }
+        for ctx in self.context() {
+            f.write_fmt(format_args!(", {}", ctx))?;
+        }
+        Ok(())
+    }
+}
+
+impl core::error::Error for InstallContextError {}
+
+impl From<InstallContextError> for Error {
+    fn from(value: InstallContextError) -> Self {
+        value.error()
+    }
+}
+
+/// Installs `ctx` as the security context the program at `path` is started with, by writing the `InstallSecurityContext` stream of the file.
+///
+/// When the program is spawned without [`ProcessStartFlags::NON_PRIVLAGED`][crate::process::ProcessStartFlags::NON_PRIVLAGED], the installed context is the base security context of the process,
+///  as described by [`ProcessStartContext::start_security_context`][crate::sys::process::ProcessStartContext::start_security_context].
+/// The stream is ignored on filesystems that are not mounted with [`MountOptions::allow_privileged`].
+///
+/// ## Errors
+/// Returns an [`InstallContextError`] with the step that failed. The most common failures are [`Error::Permission`] when encoding `ctx`,
+///  if it grants more than the current thread has, and when writing the stream, if the file does not allow it.
+pub fn install_security_context<P: AsRef<OsStr>>(
+    path: P,
+    ctx: &HandleRef<SecurityContext>,
+) -> core::result::Result<(), InstallContextError> {
+    let file = OwnedFile::open_with(path, FileAccess::empty(), FileOp::NoAccess)
+        .map_err(InstallContextError::new(InstallContextStep::Open))?;
+
+    let mut buf = Vec::new();
+    loop {
+        let mut len = buf.len();
+        match Error::from_code(unsafe {
+            syscall!(EncodeSecurityContext(
+                ctx.as_raw(),
+                buf.as_mut_ptr(),
+                &mut len
+            ))
+        }) {
+            Ok(()) => {
+                buf.truncate(len);
+                break;
+            }
+            Err(Error::InsufficientLength) if len > buf.len() => buf.resize(len, 0),
+            Err(e) => return Err(InstallContextError::new(InstallContextStep::Encode)(e)),
+        }
+    }
+
+    file.write_metadata_stream(INSTALL_SECURITY_CONTEXT, &buf)
+        .map_err(InstallContextError::new(InstallContextStep::Write))
+}
+
+/// Reads the security context installed on the program at `path` by [`install_security_context`], or returns `None` if the file has no `InstallSecurityContext` stream.
+///
+/// ## Errors
+/// Returns an [`InstallContextError`] with the step that failed. Reading the stream fails with [`Error::Permission`] if the file does not allow the current thread to read it.
+pub fn installed_security_context<P: AsRef<OsStr>>(
+    path: P,
+) -> core::result::Result<Option<OwnedHandle<SecurityContext>>, InstallContextError> {
+    let file = OwnedFile::open_with(path, FileAccess::empty(), FileOp::NoAccess)
+        .map_err(InstallContextError::new(InstallContextStep::Open))?;
+
+    let buf = match file.read_metadata_stream(INSTALL_SECURITY_CONTEXT) {
+        Ok(buf) => buf,
+        Err(Error::DoesNotExist) => return Ok(None),
+        Err(e) => return Err(InstallContextError::new(InstallContextStep::Read)(e)),
+    };
+
+    let mut hdl = MaybeUninit::uninit();
+    Error::from_code(unsafe {
+        syscall!(DecodeSecurityContext(
+            hdl.as_mut_ptr(),
+            buf.as_ptr(),
+            buf.len()
+        ))
+    })
+    .map_err(InstallContextError::new(InstallContextStep::Decode))?;
+    Ok(Some(unsafe {
+        OwnedHandle::take_ownership(hdl.assume_init())
+    }))
+}
+
+/// An iterator over the entries of a directory, returned by [`OwnedFile::read_dir`]
+#[derive(Debug)]
+pub struct DirIterator<'a> {
+    dir: BorrowedHandle<'a, FileHandle>,
     state: *mut c_void,
+    finished: bool,
+}
+
+impl<'a> DirIterator<'a> {
+    fn read_entry(&self) -> Result<DirEntry<'a>> {
+        let name = with_growing_buffer(256, |kstr| {
+            let mut info = DirectoryInfo {
+                fname: *kstr,
+                flags: 0,
+                acl_handle: HandlePtr::null(),
+            };
+            let res = unsafe { syscall!(DirectoryRead(self.dir.as_raw(), self.state, &mut info)) };
+            if info.acl_handle != HandlePtr::null() {
+                unsafe { syscall!(sys::CloseFile(info.acl_handle)) };
+            }
+            kstr.len = info.fname.len;
+            res
+        })?;
+        Ok(DirEntry {
+            parent: self.dir,
+            name: PathBuf(name),
+        })
+    }
 }
 
+impl<'a> Iterator for DirIterator<'a> {
+    type Item = Result<DirEntry<'a>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+        match Error::from_code(unsafe {
+            syscall!(DirectoryNext(self.dir.as_raw(), &mut self.state))
+        }) {
+            Ok(()) => Some(self.read_entry()),
+            Err(e) => {
+                self.finished = true;
+                (e != Error::FinishedEnumerate).then_some(Err(e))
+            }
+        }
+    }
+}
+
+impl core::iter::FusedIterator for DirIterator<'_> {}
+
 #[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
 pub struct FileType(u16);
 
@@ -419,6 +1674,166 @@ pub enum MetadataEntry {
     Unknown(String),
 }
 
+fn encode_time(tp: TimePoint<SystemClock>) -> Vec<u8> {
+    let dur = tp.since_epoch().into_system();
+    let mut bytes = Vec::with_capacity(12);
+    bytes.extend_from_slice(&dur.seconds.to_le_bytes());
+    bytes.extend_from_slice(&dur.nanos_of_second.to_le_bytes());
+    bytes
+}
+
+fn decode_time(bytes: &[u8]) -> Option<TimePoint<SystemClock>> {
+    let (seconds, nanos) = bytes.split_first_chunk::<8>()?;
+    let nanos = <[u8; 4]>::try_from(nanos).ok()?;
+    let nanos = u32::from_le_bytes(nanos);
+    if nanos >= 1_000_000_000 {
+        return None;
+    }
+    Some(TimePoint::from_epoch_offset(
+        Duration::from_seconds_and_nanos(i64::from_le_bytes(*seconds), nanos),
+    ))
+}
+
+impl MetadataEntry {
+    /// The name of the metadata stream that stores [`MetadataEntry::AccessTime`]
+    pub const ACCESS_TIME: &'static str = "AccessTime";
+    /// The name of the metadata stream that stores [`MetadataEntry::CreationTime`]
+    pub const CREATION_TIME: &'static str = "CreationTime";
+    /// The name of the metadata stream that stores [`MetadataEntry::ModificationTime`]
+    pub const MODIFICATION_TIME: &'static str = "ModificationTime";
+    /// The name of the metadata stream that stores [`MetadataEntry::CreatedBy`]
+    pub const CREATED_BY: &'static str = "CreatedBy";
+
+    /// The name of the metadata stream that stores the entry
+    pub fn stream_name(&self) -> &str {
+        match self {
+            Self::AccessTime(_) => Self::ACCESS_TIME,
+            Self::CreationTime(_) => Self::CREATION_TIME,
+            Self::ModificationTime(_) => Self::MODIFICATION_TIME,
+            Self::CreatedBy(_) => Self::CREATED_BY,
+            Self::Unknown(name) => name,
+        }
+    }
+
+    /// Encodes the value of the entry as the content of its metadata stream.
+    ///
+    /// Timestamps are stored as the seconds since the epoch of [`SystemClock`], as a little-endian `i64`, followed by the nanoseconds as a little-endian `u32`.
+    /// [`MetadataEntry::CreatedBy`] is stored as the name in UTF-8. [`MetadataEntry::Unknown`] has no value, so returns `None`.
+    pub fn encode(&self) -> Option<Vec<u8>> {
+        match self {
+            Self::AccessTime(tp) | Self::CreationTime(tp) | Self::ModificationTime(tp) => {
+                Some(encode_time(*tp))
+            }
+            Self::CreatedBy(name) => Some(name.as_bytes().to_vec()),
+            Self::Unknown(_) => None,
+        }
+    }
+
+    /// Decodes the content of the metadata stream `name`, as encoded by [`MetadataEntry::encode`].
+    ///
+    /// Returns [`MetadataEntry::Unknown`] if `name` is not a standard entry, and `None` if the content is malformed.
+    pub fn decode(name: &str, bytes: &[u8]) -> Option<Self> {
+        match name {
+            Self::ACCESS_TIME => decode_time(bytes).map(Self::AccessTime),
+            Self::CREATION_TIME => decode_time(bytes).map(Self::CreationTime),
+            Self::MODIFICATION_TIME => decode_time(bytes).map(Self::ModificationTime),
+            Self::CREATED_BY => core::str::from_utf8(bytes)
+                .ok()
+                .map(|name| Self::CreatedBy(name.to_string())),
+            name => Some(Self::Unknown(name.to_string())),
+        }
+    }
+}
+
+impl OwnedFile {
+    fn open_stream(&self, name: &str, access: FileAccess) -> Result<OwnedFile> {
+        let mut hdl = MaybeUninit::uninit();
+        Error::from_code(unsafe {
+            syscall!(sys::OpenStream(
+                hdl.as_mut_ptr(),
+                self.0.as_raw(),
+                KStrCPtr::from_str(name),
+                &sys::FileOpenOptions {
+                    stream_override: KStrCPtr::empty(),
+                    access_mode: access.bits(),
+                    op_mode: FileOp::DataAccess.into_raw(),
+                    blocking_mode: sys::MODE_BLOCKING,
+                    create_acl: HandlePtr::null(),
+                    extended_options: KCSlice::empty(),
+                },
+            ))
+        })?;
+        Ok(unsafe { OwnedFile::from_handle(hdl.assume_init()) })
+    }
+
+    /// Reads the raw content of the metadata stream `name` of the file
+    pub fn read_metadata_stream(&self, name: &str) -> Result<Vec<u8>> {
+        let stream = self.open_stream(name, FileAccess::READ)?;
+        let io = stream.as_io_handle();
+        let mut buf = Vec::new();
+        let mut chunk = [0u8; 64];
+        loop {
+            match io.read(&mut chunk)? {
+                0 => return Ok(buf),
+                n => buf.extend_from_slice(&chunk[..n]),
+            }
+        }
+    }
+
+    /// Replaces the content of the metadata stream `name` of the file with `data`, creating the stream if necessary
+    pub fn write_metadata_stream(&self, name: &str, data: &[u8]) -> Result<()> {
+        let stream = self.open_stream(
+            name,
+            FileAccess::WRITE | FileAccess::CREATE | FileAccess::TRUNCATE,
+        )?;
+        stream.as_io_handle().write_all(data)
+    }
+
+    /// Reads and decodes the metadata entry stored in the stream `name`, or returns `None` if the file has no such stream.
+    ///
+    /// Returns [`Error::InvalidState`] if the content of the stream is malformed.
+    pub fn metadata_entry(&self, name: &str) -> Result<Option<MetadataEntry>> {
+        match self.read_metadata_stream(name) {
+            Ok(bytes) => MetadataEntry::decode(name, &bytes)
+                .map(Some)
+                .ok_or(Error::InvalidState),
+            Err(Error::DoesNotExist) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Encodes and writes `entry` to its metadata stream.
+    ///
+    /// Returns [`Error::InvalidOperation`] for [`MetadataEntry::Unknown`], which has no value to write.
+    pub fn set_metadata_entry(&self, entry: &MetadataEntry) -> Result<()> {
+        let bytes = entry.encode().ok_or(Error::InvalidOperation)?;
+        self.write_metadata_stream(entry.stream_name(), &bytes)
+    }
+
+    /// Sets the access and modification times of the file. A time that is `None` is left unchanged.
+    pub fn set_times(
+        &self,
+        access: Option<TimePoint<SystemClock>>,
+        modify: Option<TimePoint<SystemClock>>,
+    ) -> Result<()> {
+        if let Some(access) = access {
+            self.set_metadata_entry(&MetadataEntry::AccessTime(access))?;
+        }
+        if let Some(modify) = modify {
+            self.set_metadata_entry(&MetadataEntry::ModificationTime(modify))?;
+        }
+        Ok(())
+    }
+
+    /// The name of the principal that created the file, or `None` if it was not recorded
+    pub fn created_by(&self) -> Result<Option<String>> {
+        match self.metadata_entry(MetadataEntry::CREATED_BY)? {
+            Some(MetadataEntry::CreatedBy(name)) => Ok(Some(name)),
+            _ => Ok(None),
+        }
+    }
+}
+
 #[derive(Clone, Debug, Hash, PartialEq, Eq)]
 pub struct Metadata {
     entries: Vec<MetadataEntry>,
@@ -433,11 +1848,11 @@ pub struct Permissions(OwnedFile);
 impl Permissions {
     pub fn readonly(&self) -> bool {
         unsafe {
-            sys::AclTestPermission(
+            syscall!(sys::AclTestPermission(
                 self.0.as_raw(),
                 KStrCPtr::from_str("Write"),
                 KStrCPtr::empty(),
-            ) == 0
+            )) == 0
         }
     }
 
@@ -451,7 +1866,7 @@ impl Permissions {
     pub fn empty() -> Result<Self> {
         let mut hdl = MaybeUninit::uninit();
 
-        Error::from_code(unsafe { sys::CreateAcl(hdl.as_mut_ptr()) })?;
+        Error::from_code(unsafe { syscall!(sys::CreateAcl(hdl.as_mut_ptr())) })?;
 
         Ok(Self(unsafe { OwnedFile::from_handle(hdl.assume_init()) }))
     }
@@ -459,7 +1874,7 @@ impl Permissions {
     pub fn default_acl() -> Result<Self> {
         let mut hdl = MaybeUninit::uninit();
 
-        Error::from_code(unsafe { sys::DefaultAcl(hdl.as_mut_ptr()) })?;
+        Error::from_code(unsafe { syscall!(sys::DefaultAcl(hdl.as_mut_ptr())) })?;
 
         Ok(Self(unsafe { OwnedFile::from_handle(hdl.assume_init()) }))
     }
@@ -467,7 +1882,7 @@ impl Permissions {
     pub unsafe fn from_file_handle(base: HandlePtr<FileHandle>) -> Result<Self> {
         let mut hdl = MaybeUninit::uninit();
 
-        Error::from_code(unsafe { sys::CopyAcl(hdl.as_mut_ptr(), base) })?;
+        Error::from_code(unsafe { syscall!(sys::CopyAcl(hdl.as_mut_ptr(), base)) })?;
 
         Ok(Self(unsafe { OwnedFile::from_handle(hdl.assume_init()) }))
     }
@@ -478,7 +1893,11 @@ impl Permissions {
     ///
     pub fn test_permission(&self, name: &str) -> Result<bool> {
         match Error::from_code(unsafe {
-            sys::AclTestPermission(self.0.as_raw(), KStrCPtr::from_str(name), KStrCPtr::empty())
+            syscall!(sys::AclTestPermission(
+                self.0.as_raw(),
+                KStrCPtr::from_str(name),
+                KStrCPtr::empty()
+            ))
         }) {
             Ok(()) => Ok(true),
             Err(Error::Permission) => Ok(false),
@@ -488,11 +1907,11 @@ impl Permissions {
 
     pub fn test_stream_permission(&self, name: &str, stream: &str) -> Result<bool> {
         match Error::from_code(unsafe {
-            sys::AclTestPermission(
+            syscall!(sys::AclTestPermission(
                 self.0.as_raw(),
                 KStrCPtr::from_str(name),
                 KStrCPtr::from_str(stream),
-            )
+            ))
         }) {
             Ok(()) => Ok(true),
             Err(Error::Permission) => Ok(false),
@@ -500,61 +1919,64 @@ impl Permissions {
         }
     }
 
-    pub fn legacy_mode(&self) -> Option<u32> {
-        let mode = unsafe { sys::AclLegacyMode(self.0.as_raw()) };
+    pub fn legacy_mode(&self) -> Result<Option<u32>> {
+        let mode = unsafe { syscall!(sys::AclLegacyMode(self.0.as_raw())) };
 
-        match Error::from_code(mode) {
-            Ok(()) => Some(mode as u32),
-            Err(Error::DoesNotExist) => None,
-            Err(e) => Err(e).unwrap(),
+        match mode.into_value() {
+            Ok(mode) => Ok(Some(mode)),
+            Err(Error::DoesNotExist) => Ok(None),
+            Err(e) => Err(e),
         }
     }
 
-    pub fn legacy_uid(&self) -> Option<u32> {
-        let mode = unsafe { sys::AclLegacyUid(self.0.as_raw()) };
+    pub fn legacy_uid(&self) -> Result<Option<u32>> {
+        let mode = unsafe { syscall!(sys::AclLegacyUid(self.0.as_raw())) };
 
-        match Error::from_code(mode) {
-            Ok(()) => Some(mode as u32),
-            Err(Error::DoesNotExist) => None,
-            Err(e) => Err(e).unwrap(),
+        match mode.into_value() {
+            Ok(mode) => Ok(Some(mode)),
+            Err(Error::DoesNotExist) => Ok(None),
+            Err(e) => Err(e),
         }
     }
 
-    pub fn legacy_gid(&self) -> Option<u32> {
-        let mode = unsafe { sys::AclLegacyGid(self.0.as_raw()) };
+    pub fn legacy_gid(&self) -> Result<Option<u32>> {
+        let mode = unsafe { syscall!(sys::AclLegacyGid(self.0.as_raw())) };
 
-        match Error::from_code(mode) {
-            Ok(()) => Some(mode as u32),
-            Err(Error::DoesNotExist) => None,
-            Err(e) => Err(e).unwrap(),
+        match mode.into_value() {
+            Ok(mode) => Ok(Some(mode)),
+            Err(Error::DoesNotExist) => Ok(None),
+            Err(e) => Err(e),
         }
     }
 
     pub fn set_legacy_mode(&mut self, mode: u32) -> Result<()> {
-        Error::from_code(unsafe { sys::AclSetLegacyMode(self.0.as_raw(), mode) })
+        Error::from_code(unsafe { syscall!(sys::AclSetLegacyMode(self.0.as_raw(), mode)) })
     }
 
     pub fn set_legacy_uid(&mut self, uid: u32) -> Result<()> {
-        Error::from_code(unsafe { sys::AclSetLegacyUid(self.0.as_raw(), uid as c_long) })
+        Error::from_code(unsafe { syscall!(sys::AclSetLegacyUid(self.0.as_raw(), uid as c_long)) })
     }
 
     pub fn set_legacy_gid(&mut self, gid: u32) -> Result<()> {
-        Error::from_code(unsafe { sys::AclSetLegacyUid(self.0.as_raw(), gid as c_long) })
+        Error::from_code(unsafe { syscall!(sys::AclSetLegacyUid(self.0.as_raw(), gid as c_long)) })
     }
 
     pub fn set_owner(&mut self, uuid: Uuid) -> Result<()> {
-        Error::from_code(unsafe { sys::SetObjectOwner(self.0.as_raw(), &uuid) })
+        Error::from_code(unsafe { syscall!(sys::SetObjectOwner(self.0.as_raw(), &uuid)) })
     }
 
     pub fn take_ownership(&mut self) -> Result<()> {
         let mut uuid = MaybeUninit::uninit();
         Error::from_code(unsafe {
-            crate::sys::permission::GetPrimaryPrincipal(HandlePtr::null(), uuid.as_mut_ptr())
+            syscall!(crate::sys::permission::GetPrimaryPrincipal(
+                HandlePtr::null(),
+                uuid.as_mut_ptr()
+            ))
         })?;
 
         let uuid = unsafe { uuid.assume_init() };
 
-        Error::from_code(unsafe { sys::SetObjectOwner(self.0.as_raw(), &uuid) })
+        Error::from_code(unsafe { syscall!(sys::SetObjectOwner(self.0.as_raw(), &uuid)) })
     }
 
     /// Determines the owner of the file represented by this [`Permissions`] structure.
@@ -571,7 +1993,9 @@ impl Permissions {
     /// This always returns a principal in the enhanced permission space.
     pub fn owner(&self) -> Option<Uuid> {
         let mut uuid = MaybeUninit::uninit();
-        match Error::from_code(unsafe { sys::ObjectOwner(self.0.as_raw(), uuid.as_mut_ptr()) }) {
+        match Error::from_code(unsafe {
+            syscall!(sys::ObjectOwner(self.0.as_raw(), uuid.as_mut_ptr()))
+        }) {
             Ok(()) => Some(unsafe { uuid.assume_init() }),
             Err(Error::DoesNotExist) => None,
             Err(e) => panic!("Unexpected error: {:?}", e),
@@ -579,4 +2003,42 @@ impl Permissions {
     }
 }
 
-pub struct DirEntry(OwnedFile);
+/// An entry of a directory, named relative to the directory that contains it.
+///
+/// Operations on the entry resolve its name against the handle to the parent directory, rather than a path,
+///  so they are not affected by concurrent renames of the directories above it. Entries are produced by [`OwnedFile::read_dir`].
+#[derive(Debug)]
+pub struct DirEntry<'a> {
+    parent: BorrowedHandle<'a, FileHandle>,
+    name: PathBuf,
+}
+
+impl<'a> DirEntry<'a> {
+    /// The entry called `name` in the directory `parent`
+    pub fn new<D: AsHandle<'a, FileHandle>, P: AsRef<Path>>(parent: D, name: P) -> Self {
+        Self {
+            parent: unsafe { BorrowedHandle::from_raw(parent.as_handle()) },
+            name: name.as_ref().to_path_buf(),
+        }
+    }
+
+    /// The directory that contains the entry
+    pub fn parent(&self) -> BorrowedHandle<'a, FileHandle> {
+        self.parent
+    }
+
+    /// The name of the entry in its parent directory
+    pub fn name(&self) -> &Path {
+        &self.name
+    }
+
+    /// Opens the entry in the access mode `access`, for the operation `op`
+    pub fn open(&self, access: FileAccess, op: FileOp) -> Result<OwnedFile> {
+        OwnedFile::open_at(self.parent, &self.name, access, op)
+    }
+
+    /// Removes the entry from its parent directory
+    pub fn remove(&self) -> Result<()> {
+        remove_at(self.parent, &self.name)
+    }
+}
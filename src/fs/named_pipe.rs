@@ -0,0 +1,145 @@
+//! Named pipes (FIFOs), which are pipes that can be opened by name from the filesystem.
+//!
+//! Data written to the write end of a named pipe by one thread is read from the read end by another, in order, as with an anonymous [`pipe`][crate::io::pipe].
+//!
+//! ## Example
+//! ```rust,ignore
+//! named_pipe::create("/run/myservice.fifo", &PipeOptions::new())?;
+//! let reader = named_pipe::open_reader("/run/myservice.fifo", &PipeOptions::new())?;
+//! ```
+
+use core::mem::MaybeUninit;
+
+use crate::{
+    handle::OwnedHandle,
+    io::{BlockingMode, IOHandle},
+    result::{Error, Result},
+    sys::{
+        fs as sys,
+        handle::HandlePtr,
+        io::SetIOBlockingMode,
+        kstr::{KCSlice, KStrCPtr},
+    },
+};
+
+use super::{os_path, OsStr, Path, Permissions};
+
+/// Options for creating and opening named pipes
+#[derive(Clone, Debug, Default)]
+pub struct PipeOptions<'a> {
+    blocking: BlockingMode,
+    acl: Option<&'a Permissions>,
+    create: bool,
+}
+
+impl<'a> PipeOptions<'a> {
+    pub const fn new() -> Self {
+        Self {
+            blocking: BlockingMode::Blocking,
+            acl: None,
+            create: false,
+        }
+    }
+
+    /// Sets how operations on the opened end of the pipe behave when they cannot complete immediately, including opening it.
+    ///
+    /// Opening one end of a blocking named pipe blocks until the other end is opened.
+    pub fn blocking_mode(&mut self, mode: BlockingMode) -> &mut Self {
+        self.blocking = mode;
+        self
+    }
+
+    /// Sets the ACL of the pipe when it is created, instead of the default ACL
+    pub fn acl(&mut self, acl: &'a Permissions) -> &mut Self {
+        self.acl = Some(acl);
+        self
+    }
+
+    /// Makes [`open_reader`] and [`open_writer`] create the pipe if it does not exist
+    pub fn create(&mut self, create: bool) -> &mut Self {
+        self.create = create;
+        self
+    }
+
+    fn raw_acl(&self) -> HandlePtr<sys::FileHandle> {
+        self.acl.map_or_else(HandlePtr::null, |acl| acl.0.as_raw())
+    }
+}
+
+/// Creates a named pipe at `path`, without opening it.
+///
+/// ## Errors
+/// Returns [`Error::AlreadyExists`] if an object already exists at `path`.
+pub fn create<P: AsRef<OsStr>>(path: P, options: &PipeOptions) -> Result<()> {
+    Error::from_code(unsafe {
+        syscall!(sys::CreateNamedPipe(
+            core::ptr::null_mut(),
+            0,
+            HandlePtr::null(),
+            os_path(&path)?.to_kstr_raw(),
+            options.raw_acl(),
+        ))
+    })
+}
+
+fn open(path: &Path, access_mode: u32, options: &PipeOptions) -> Result<OwnedHandle<IOHandle>> {
+    let mut hdl = MaybeUninit::uninit();
+    let created = if options.create {
+        match Error::from_code(unsafe {
+            syscall!(sys::CreateNamedPipe(
+                hdl.as_mut_ptr(),
+                access_mode,
+                HandlePtr::null(),
+                path.to_kstr_raw(),
+                options.raw_acl(),
+            ))
+        }) {
+            Ok(()) => true,
+            Err(Error::AlreadyExists) => false,
+            Err(e) => return Err(e),
+        }
+    } else {
+        false
+    };
+
+    if created {
+        let hdl = unsafe { OwnedHandle::<IOHandle>::take_ownership(hdl.assume_init().cast()) };
+        Error::from_code(unsafe {
+            syscall!(SetIOBlockingMode(hdl.as_raw(), options.blocking.into_raw()))
+        })?;
+        return Ok(hdl);
+    }
+
+    Error::from_code(unsafe {
+        syscall!(sys::OpenFile(
+            hdl.as_mut_ptr(),
+            HandlePtr::null(),
+            path.to_kstr_raw(),
+            &sys::FileOpenOptions {
+                stream_override: KStrCPtr::empty(),
+                access_mode,
+                op_mode: sys::OP_STREAM_DEFAULT,
+                blocking_mode: options.blocking.into_raw(),
+                create_acl: HandlePtr::null(),
+                extended_options: KCSlice::empty(),
+            },
+        ))
+    })?;
+    Ok(unsafe { OwnedHandle::take_ownership(hdl.assume_init().cast()) })
+}
+
+/// Opens the read end of the named pipe at `path`
+pub fn open_reader<P: AsRef<OsStr>>(
+    path: P,
+    options: &PipeOptions,
+) -> Result<OwnedHandle<IOHandle>> {
+    open(os_path(&path)?, sys::ACCESS_READ, options)
+}
+
+/// Opens the write end of the named pipe at `path`
+pub fn open_writer<P: AsRef<OsStr>>(
+    path: P,
+    options: &PipeOptions,
+) -> Result<OwnedHandle<IOHandle>> {
+    open(os_path(&path)?, sys::ACCESS_WRITE, options)
+}
@@ -0,0 +1,231 @@
+use alloc::{collections::BTreeMap, string::String, vec::Vec};
+
+use core::mem::MaybeUninit;
+
+use crate::{
+    handle::{AsHandle, OwnedHandle},
+    kstr::grow_buffer,
+    result::{Error, Result, ResultExt},
+    sync::event::{block_on, handle_event, Event, TypedEvent},
+    sys::{
+        event::{EventInfo, EVENT_FS_WATCH},
+        fs::{self as sys, FileHandle, WatchHandle},
+        handle::HandlePtr,
+        kstr::{KCSlice, KStrCPtr, KStrPtr},
+        result::SysResult,
+    },
+};
+
+use super::{os_path, OsStr, Path, PathBuf};
+
+/// The kind of change reported by an [`FsEvent`]
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum FsEventKind {
+    /// The object was created
+    Created,
+    /// The object was written to, or had its metadata changed
+    Modified,
+    /// The object was removed
+    Removed,
+    /// The object was renamed to `to`
+    Renamed { to: PathBuf },
+}
+
+/// A change to a watched object, or to an object in a watched directory
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub struct FsEvent {
+    /// The path of the object that changed, formed from the path the watch was registered with
+    pub path: PathBuf,
+    pub kind: FsEventKind,
+}
+
+/// Identifies an object watched by a [`Watcher`]
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct WatchId(u32);
+
+/// Watches files and directories for changes.
+///
+/// A watched directory reports changes to the objects directly contained in it, as well as to itself.
+/// A `Watcher` is an [`Event`] that occurs when changes are queued, so it can be waited on alongside other events with [`block_on_any`][crate::sync::event::block_on_any].
+///
+/// ## Example
+/// ```rust,ignore
+/// let mut watcher = Watcher::new()?;
+/// watcher.watch("src")?;
+/// loop {
+///     let event = watcher.next_event()?;
+///     println!("{}: {:?}", event.path, event.kind);
+/// }
+/// ```
+#[derive(Debug)]
+pub struct Watcher {
+    hdl: OwnedHandle<WatchHandle>,
+    paths: BTreeMap<u32, PathBuf>,
+}
+
+fn join(base: &Path, name: String) -> PathBuf {
+    if name.is_empty() {
+        base.to_path_buf()
+    } else if base.as_str().is_empty() || base.as_str().ends_with('/') {
+        PathBuf::from_string(alloc::format!("{}{}", base, name))
+    } else {
+        PathBuf::from_string(alloc::format!("{}/{}", base, name))
+    }
+}
+
+impl Watcher {
+    /// Creates a watcher that does not watch any object
+    pub fn new() -> Result<Self> {
+        let mut hdl = MaybeUninit::uninit();
+        Error::from_code(unsafe { syscall!(sys::CreateWatch(hdl.as_mut_ptr())) })?;
+        Ok(Self {
+            hdl: unsafe { OwnedHandle::take_ownership(hdl.assume_init()) },
+            paths: BTreeMap::new(),
+        })
+    }
+
+    /// Watches the object at `path` for every kind of change
+    pub fn watch<P: AsRef<OsStr>>(&mut self, path: P) -> Result<WatchId> {
+        let path = os_path(&path)?;
+        let mut file = MaybeUninit::uninit();
+        Error::from_code(unsafe {
+            syscall!(sys::OpenFile(
+                file.as_mut_ptr(),
+                HandlePtr::null(),
+                path.to_kstr_raw(),
+                &sys::FileOpenOptions {
+                    stream_override: KStrCPtr::empty(),
+                    access_mode: sys::ACCESS_READ,
+                    op_mode: sys::OP_NO_ACCESS,
+                    blocking_mode: sys::MODE_BLOCKING,
+                    create_acl: HandlePtr::null(),
+                    extended_options: KCSlice::empty(),
+                },
+            ))
+        })?;
+        let file = unsafe { OwnedHandle::<FileHandle>::take_ownership(file.assume_init()) };
+        self.watch_handle(&file, path.as_str())
+    }
+
+    /// Watches the object designated by the open file `file` for every kind of change.
+    ///
+    /// The paths of the reported events are formed from `path`, which need not be the path `file` was opened with. The handle may be closed once the watch is added.
+    pub fn watch_handle<'a, H: AsHandle<'a, FileHandle>, P: AsRef<OsStr>>(
+        &mut self,
+        file: H,
+        path: P,
+    ) -> Result<WatchId> {
+        let id = unsafe {
+            syscall!(sys::AddWatch(
+                self.hdl.as_raw(),
+                file.as_handle(),
+                sys::WATCH_ALL
+            ))
+        };
+        Error::from_code(id)?;
+        self.paths.insert(id as u32, os_path(&path)?.to_path_buf());
+        Ok(WatchId(id as u32))
+    }
+
+    /// Stops watching the object identified by `id`. Changes to it that are already queued are discarded.
+    pub fn unwatch(&mut self, id: WatchId) -> Result<()> {
+        Error::from_code(unsafe { syscall!(sys::RemoveWatch(self.hdl.as_raw(), id.0)) })?;
+        self.paths.remove(&id.0);
+        Ok(())
+    }
+
+    /// Reads the oldest queued change, or returns `None` without blocking if no change is queued.
+    ///
+    /// Fails with [`Error::InvalidString`] if a name the kernel reported for the change is not valid UTF-8. The change is discarded.
+    pub fn try_next(&self) -> Result<Option<FsEvent>> {
+        let mut name = Vec::<u8>::with_capacity(64);
+        let mut new_name = Vec::<u8>::new();
+        loop {
+            let mut raw = sys::WatchEvent {
+                watch_id: 0,
+                kind: 0,
+                name: KStrPtr {
+                    str_ptr: name.as_mut_ptr(),
+                    len: name.capacity(),
+                },
+                new_name: KStrPtr {
+                    str_ptr: new_name.as_mut_ptr(),
+                    len: new_name.capacity(),
+                },
+            };
+
+            match Error::from_code(unsafe {
+                syscall!(sys::ReadWatchEvent(self.hdl.as_raw(), &mut raw))
+            }) {
+                Ok(()) => {}
+                Err(Error::WouldBlock) => return Ok(None),
+                Err(Error::InsufficientLength) => {
+                    let grew_name = grow_buffer(&mut raw.name, &mut name);
+                    let grew_new_name = grow_buffer(&mut raw.new_name, &mut new_name);
+                    if grew_name || grew_new_name {
+                        continue;
+                    }
+                    return Err(Error::InsufficientLength);
+                }
+                Err(e) => return Err(e),
+            }
+
+            // SAFETY:
+            // The kernel wrote exactly `len` bytes to each buffer
+            unsafe {
+                name.set_len(raw.name.len);
+                new_name.set_len(raw.new_name.len);
+            }
+            let name =
+                String::from_utf8(core::mem::take(&mut name)).map_err(|_| Error::InvalidString)?;
+            let new_name = String::from_utf8(core::mem::take(&mut new_name))
+                .map_err(|_| Error::InvalidString)?;
+
+            // Changes to an object that was unwatched after they were queued are dropped
+            let Some(base) = self.paths.get(&raw.watch_id) else {
+                continue;
+            };
+
+            let kind = match raw.kind {
+                sys::WATCH_CREATED => FsEventKind::Created,
+                sys::WATCH_MODIFIED => FsEventKind::Modified,
+                sys::WATCH_REMOVED => FsEventKind::Removed,
+                sys::WATCH_RENAMED => FsEventKind::Renamed {
+                    to: join(base, new_name),
+                },
+                _ => continue,
+            };
+
+            return Ok(Some(FsEvent {
+                path: join(base, name),
+                kind,
+            }));
+        }
+    }
+
+    /// Blocks the current thread until a change is queued, and reads it. See [`block_on`].
+    pub fn next_event(&self) -> Result<FsEvent> {
+        loop {
+            block_on(self)?;
+            if let Some(event) = self.try_next()? {
+                return Ok(event);
+            }
+        }
+    }
+}
+
+unsafe impl Event for Watcher {
+    fn to_raw(&self) -> EventInfo {
+        handle_event(EVENT_FS_WATCH, self.hdl.as_raw())
+    }
+}
+
+impl TypedEvent for Watcher {
+    /// The number of queued changes
+    type Output = usize;
+
+    fn output(&self, result: SysResult) -> Result<usize> {
+        result.into_value()
+    }
+}
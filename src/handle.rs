@@ -2,18 +2,24 @@ mod private {
     pub trait Sealed {}
 }
 
-use core::{borrow::Borrow, hash::Hash, marker::PhantomData, mem::MaybeUninit, ops::Deref};
+use core::{
+    borrow::Borrow, ffi::c_ulong, hash::Hash, marker::PhantomData, mem::MaybeUninit, ops::Deref,
+};
 
 use private::Sealed;
 
 use crate::{
+    process::Command,
     sys::{
         debug::{DebugDetach, DebugHandle},
         device::DeviceHandle,
-        fs::FileHandle,
+        fs::{CloseWatch, FileHandle, WatchHandle},
         handle::{self as sys, HandlePtr},
         io::{CloseIOStream, IOHandle},
+        isolation::{DisposeNamespace, NamespaceHandle},
+        kstr::KStrCPtr,
         permission::{DestroySecurityContext, SecurityContext},
+        socket::SocketHandle,
         thread::{DetachThread, ThreadHandle},
     },
     thread::TlsKey,
@@ -22,49 +28,177 @@ use crate::{
 use crate::result::{Error, Result};
 
 pub trait HandleType: Sized + Sealed {
+    /// The rights defined for handles of this type.
+    ///
+    /// Rights that are not defined for a handle type can be named, but are never present on the handle.
+    type Right: HandleRight;
+
     unsafe fn destroy(ptr: HandlePtr<Self>);
 }
 
 pub trait UpcastHandle<T>: HandleType {}
 
+/// A right that can be present on a handle
+pub trait HandleRight: Copy + Eq + Sealed + 'static {
+    /// Every right of this type
+    const ALL: &'static [Self];
+
+    /// The name of the right
+    fn as_str(&self) -> &'static str;
+
+    /// The name of the right, as a kernel string
+    fn as_kstr(&self) -> KStrCPtr {
+        KStrCPtr::from_str(self.as_str())
+    }
+}
+
+/// The rights of a handle type that defines no rights
+#[derive(Copy, Clone, Hash, PartialEq, Eq, Debug)]
+pub enum NoRights {}
+
+impl Sealed for NoRights {}
+
+impl HandleRight for NoRights {
+    const ALL: &'static [Self] = &[];
+
+    fn as_str(&self) -> &'static str {
+        match *self {}
+    }
+}
+
+macro_rules! def_rights {
+    ($(#[$meta:meta])* $vis:vis enum $name:ident { $($(#[$vmeta:meta])* $variant:ident = $str:literal),* $(,)? }) => {
+        $(#[$meta])*
+        #[derive(Copy, Clone, Hash, PartialEq, Eq, Debug)]
+        $vis enum $name {
+            $($(#[$vmeta])* $variant),*
+        }
+
+        impl Sealed for $name {}
+
+        impl HandleRight for $name {
+            const ALL: &'static [Self] = &[$(Self::$variant),*];
+
+            fn as_str(&self) -> &'static str {
+                match self {
+                    $(Self::$variant => $str),*
+                }
+            }
+        }
+    };
+}
+
+def_rights! {
+    /// The rights of a [`ThreadHandle`]
+    pub enum ThreadRight {
+        /// Allows attaching a debugger to the thread
+        DebugAttach = "DEBUG_ATTACH",
+    }
+}
+
+def_rights! {
+    /// The rights of a [`FileHandle`]
+    pub enum FileRight {
+        /// Allows reading from the file
+        Read = "Read",
+        /// Allows writing to the file
+        Write = "Write",
+        /// Allows creating objects in the directory
+        CreateObject = "CreateObject",
+        /// Allows opening streams of the file with a hard lock
+        StrictLock = "StrictLock",
+    }
+}
+
+def_rights! {
+    /// The rights of a [`DeviceHandle`]
+    pub enum DeviceRight {
+        /// Allows mounting a filesystem from the device
+        MountFilesystem = "MOUNT_FILESYSTEM",
+        /// Allows reading random bytes from the device
+        GetRandomBytes = "GetRandomBytes",
+        /// Allows reseeding the device
+        SeedRandomBytes = "SeedRandomBytes",
+    }
+}
+
 impl Sealed for ThreadHandle {}
 impl Sealed for DebugHandle {}
 impl Sealed for SecurityContext {}
 impl Sealed for IOHandle {}
 impl Sealed for FileHandle {}
+impl Sealed for WatchHandle {}
 impl Sealed for DeviceHandle {}
+impl Sealed for NamespaceHandle {}
+impl Sealed for SocketHandle {}
 
 impl HandleType for ThreadHandle {
+    type Right = ThreadRight;
+
     unsafe fn destroy(ptr: HandlePtr<Self>) {
         DetachThread(ptr);
     }
 }
 
 impl HandleType for DebugHandle {
+    type Right = NoRights;
+
     unsafe fn destroy(ptr: HandlePtr<Self>) {
         DebugDetach(ptr);
     }
 }
 
 impl HandleType for SecurityContext {
+    type Right = NoRights;
+
     unsafe fn destroy(ptr: HandlePtr<Self>) {
         DestroySecurityContext(ptr);
     }
 }
 
 impl HandleType for IOHandle {
+    type Right = NoRights;
+
     unsafe fn destroy(ptr: HandlePtr<Self>) {
         CloseIOStream(ptr);
     }
 }
 
 impl HandleType for FileHandle {
+    type Right = FileRight;
+
     unsafe fn destroy(ptr: HandlePtr<Self>) {
         CloseIOStream(ptr.cast());
     }
 }
 
+impl HandleType for WatchHandle {
+    type Right = NoRights;
+
+    unsafe fn destroy(ptr: HandlePtr<Self>) {
+        CloseWatch(ptr);
+    }
+}
+
 impl HandleType for DeviceHandle {
+    type Right = DeviceRight;
+
+    unsafe fn destroy(ptr: HandlePtr<Self>) {
+        CloseIOStream(ptr.cast());
+    }
+}
+
+impl HandleType for NamespaceHandle {
+    type Right = NoRights;
+
+    unsafe fn destroy(ptr: HandlePtr<Self>) {
+        DisposeNamespace(ptr);
+    }
+}
+
+impl HandleType for SocketHandle {
+    type Right = NoRights;
+
     unsafe fn destroy(ptr: HandlePtr<Self>) {
         CloseIOStream(ptr.cast());
     }
@@ -79,6 +213,63 @@ impl<T> HandleRef<T> {
     }
 }
 
+impl<T> HandleRef<T> {
+    /// Checks whether the handle has the right `right`.
+    ///
+    /// The rights defined for a handle type are listed in [`HandleType::Right`]. Rights that are not defined for the handle type are never present.
+    pub fn has_right(&self, right: &str) -> Result<bool> {
+        match Error::from_code(unsafe {
+            sys::CheckHandleRight(self.0.cast(), KStrCPtr::from_str(right))
+        }) {
+            Ok(()) => Ok(true),
+            Err(Error::Permission) => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Drops the right `right` from the handle, if it is present.
+    ///
+    /// Note that this affects every reference to the handle, including the [`OwnedHandle`] it is borrowed from.
+    pub fn drop_right(&self, right: &str) -> Result<()> {
+        Error::from_code(unsafe { sys::DropHandleRight(self.0.cast(), KStrCPtr::from_str(right)) })
+    }
+
+    /// Drops every right from the handle.
+    ///
+    /// Note that this affects every reference to the handle, including the [`OwnedHandle`] it is borrowed from.
+    pub fn drop_all_rights(&self) -> Result<()> {
+        Error::from_code(unsafe { sys::DropAllHandleRights(self.0.cast()) })
+    }
+
+    /// Grants the right `right` to the handle, if the current thread has the permission required for it.
+    ///
+    /// Returns `Ok(false)` if the current thread does not have the required permission.
+    /// Returns [`Error::InvalidOperation`] if `right` cannot be applied to the handle.
+    pub fn grant_right(&self, right: &str) -> Result<bool> {
+        match Error::from_code(unsafe {
+            sys::GrantHandleRight(self.0.cast(), KStrCPtr::from_str(right))
+        }) {
+            Ok(()) => Ok(true),
+            Err(Error::Permission) => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+impl<T: HandleType> HandleRef<T> {
+    /// Drops every right defined for the handle type, except for those in `keep`.
+    ///
+    /// Note that this affects every reference to the handle, including the [`OwnedHandle`] it is borrowed from.
+    pub fn restrict(&self, keep: &[T::Right]) -> Result<()> {
+        for right in T::Right::ALL {
+            if !keep.contains(right) {
+                Error::from_code(unsafe { sys::DropHandleRight(self.0.cast(), right.as_kstr()) })?;
+            }
+        }
+        Ok(())
+    }
+}
+
 impl<T> HandleRef<T> {
     pub fn borrow<'a>(&'a self) -> BorrowedHandle<'a, T> {
         BorrowedHandle(self.0, PhantomData)
@@ -183,7 +374,15 @@ impl<'a, T> Clone for BorrowedHandle<'a, T> {
 
 impl<'a, T> Copy for BorrowedHandle<'a, T> {}
 
-impl<'a, T: HandleType> BorrowedHandle<'a, T> {}
+impl<'a, T> BorrowedHandle<'a, T> {
+    /// Borrows the handle designated by `hdl` for `'a`.
+    ///
+    /// # Safety
+    /// `hdl` must be a valid handle (or null) for the duration of `'a`.
+    pub const unsafe fn from_raw(hdl: HandlePtr<T>) -> Self {
+        Self(hdl, PhantomData)
+    }
+}
 
 impl<'a, T> Deref for BorrowedHandle<'a, T> {
     type Target = HandleRef<T>;
@@ -255,6 +454,65 @@ where
     }
 }
 
+/// Types that hold a raw handle, analogous to `AsRawFd` on unix.
+///
+/// These traits allow a port of the standard library to Lilium, and other crates with their own handle wrappers, to exchange handles with this crate's ownership types.
+pub trait AsRawHandlePtr {
+    /// The type of the handle
+    type Handle;
+
+    /// Extracts the raw handle, without affecting ownership
+    fn as_raw_handle_ptr(&self) -> HandlePtr<Self::Handle>;
+}
+
+/// Types that own a raw handle and can release it, analogous to `IntoRawFd` on unix
+pub trait IntoRawLiliumHandle: AsRawHandlePtr {
+    /// Releases ownership of the handle. The caller becomes responsible for closing it.
+    fn into_raw_lilium_handle(self) -> HandlePtr<Self::Handle>;
+}
+
+/// Types that can take ownership of a raw handle, analogous to `FromRawFd` on unix
+pub trait FromRawLiliumHandle: AsRawHandlePtr {
+    /// Takes ownership of `hdl`.
+    ///
+    /// # Safety
+    /// `hdl` must be a valid, owned handle of the appropriate type, which is not used or closed by anything else afterwards.
+    unsafe fn from_raw_lilium_handle(hdl: HandlePtr<Self::Handle>) -> Self;
+}
+
+impl<T> AsRawHandlePtr for HandleRef<T> {
+    type Handle = T;
+    fn as_raw_handle_ptr(&self) -> HandlePtr<T> {
+        self.as_raw()
+    }
+}
+
+impl<T> AsRawHandlePtr for BorrowedHandle<'_, T> {
+    type Handle = T;
+    fn as_raw_handle_ptr(&self) -> HandlePtr<T> {
+        self.as_raw()
+    }
+}
+
+impl<T: HandleType> AsRawHandlePtr for OwnedHandle<T> {
+    type Handle = T;
+    fn as_raw_handle_ptr(&self) -> HandlePtr<T> {
+        self.as_raw()
+    }
+}
+
+impl<T: HandleType> IntoRawLiliumHandle for OwnedHandle<T> {
+    fn into_raw_lilium_handle(self) -> HandlePtr<T> {
+        self.release_ownership()
+    }
+}
+
+impl<T: HandleType> FromRawLiliumHandle for OwnedHandle<T> {
+    unsafe fn from_raw_lilium_handle(hdl: HandlePtr<T>) -> Self {
+        unsafe { Self::take_ownership(hdl) }
+    }
+}
+
 pub struct SharedHandle<T: HandleType>(sys::SharedHandlePtr, TlsKey<HandlePtr<T>>);
 
 impl<T: HandleType> core::fmt::Debug for SharedHandle<T> {
@@ -272,7 +530,7 @@ impl<T: HandleType> Drop for SharedHandle<T> {
 }
 
 impl<T: HandleType> SharedHandle<T> {
-    pub fn share(file: OwnedHandle<T>) -> Result<Self> {
+    fn share_with_flags(file: OwnedHandle<T>, flags: u32) -> Result<Self> {
         let loc = TlsKey::<HandlePtr<T>>::try_alloc()?;
 
         let hdl = file.release_ownership();
@@ -281,7 +539,7 @@ impl<T: HandleType> SharedHandle<T> {
 
         let mut shared = MaybeUninit::uninit();
 
-        Error::from_code(unsafe { sys::ShareHandle(shared.as_mut_ptr(), bare_hdl, 0) })?;
+        Error::from_code(unsafe { sys::ShareHandle(shared.as_mut_ptr(), bare_hdl, flags) })?;
         let shared = unsafe { shared.assume_init() };
 
         unsafe {
@@ -291,6 +549,39 @@ impl<T: HandleType> SharedHandle<T> {
         Ok(Self(shared, loc))
     }
 
+    pub fn share(file: OwnedHandle<T>) -> Result<Self> {
+        Self::share_with_flags(file, 0)
+    }
+
+    /// Shares `file` such that other threads upgrading the shared handle skip the privilege checks that would otherwise be performed.
+    pub fn upgrade_privileged(file: OwnedHandle<T>) -> Result<Self> {
+        Self::share_with_flags(file, sys::SHARE_FLAG_UPGRADE_PRIVILEGED)
+    }
+
+    /// Identifies the dynamic type of the shared handle, as one of the `HANDLE_TYPE_*` or `HANDLE_SUBTYPE_*` constants in [`crate::sys::handle`].
+    pub fn ident(&self) -> Result<c_ulong> {
+        let hdl = self.try_get()?;
+        let ty = unsafe { sys::IdentHandle(hdl.cast()) };
+        Error::from_code(ty)?;
+        Ok(ty as c_ulong)
+    }
+
+    /// Stops sharing the handle, and returns the handle for the current thread.
+    ///
+    /// Other threads can no longer upgrade the shared handle, but handles they have already obtained from it remain valid.
+    pub fn unshare(self) -> Result<OwnedHandle<T>> {
+        let hdl = self.try_get()?;
+        Error::from_code(unsafe { sys::UnshareHandle(hdl.cast()) })?;
+        Ok(unsafe { OwnedHandle::take_ownership(hdl) })
+    }
+
+    /// Passes the handle to the process spawned by `cmd`, by appending it to the init handles of `cmd`.
+    pub fn send_to<'a>(&'a self, cmd: &mut Command<'a>) -> Result<()> {
+        let hdl = self.try_get()?;
+        cmd.init_handle(hdl);
+        Ok(())
+    }
+
     pub fn try_get(&self) -> Result<HandlePtr<T>> {
         let val = unsafe { self.1.get().read() };
 
@@ -1,6 +1,9 @@
 use core::{
+    alloc::Layout,
     any::{Any, TypeId},
     mem::MaybeUninit,
+    ptr::NonNull,
+    sync::atomic::{AtomicUsize, Ordering},
 };
 
 use alloc::collections::BTreeMap;
@@ -9,46 +12,151 @@ use bytemuck::Zeroable;
 use sptr::Strict;
 
 use crate::{
+    result::{Error, Result},
     sys::{
         info as sys,
         kstr::{KSlice, KStrPtr},
         option::{ExtendedOptionHead, OPTION_FLAG_IGNORE},
+        result::SysResult,
     },
     uuid::Uuid,
 };
 
-#[derive(Copy, Clone, Hash, PartialEq, Eq)]
-struct StringIndex(usize);
+#[cfg(any(target_arch = "x86_64", target_arch = "x86"))]
+pub mod x86;
+
+/// An output buffer of a request, such as a string or an array, which the kernel writes to.
+///
+/// [`RequestBuilder`] allocates the storage of each buffer found by [`FromRequest::find_buffers`] (or [`FromProcRequest::find_buffers`]),
+///  and grows it if the kernel reports that it is too short.
+pub struct RequestBuffer<'a> {
+    raw: &'a mut KSlice<u8>,
+    elem: Layout,
+}
 
-impl StringIndex {
-    fn into_parts(self) -> (usize, usize) {
-        (self.0 >> 6, self.0 & 63)
+impl<'a> RequestBuffer<'a> {
+    /// A string buffer, whose length is measured in bytes
+    pub fn string(st: &'a mut KStrPtr) -> Self {
+        Self {
+            // SAFETY: `KStrPtr` and `KSlice<u8>` are both `#[repr(C)]` pairs of a pointer to `u8` and a length
+            raw: unsafe { &mut *(st as *mut KStrPtr).cast::<KSlice<u8>>() },
+            elem: Layout::new::<u8>(),
+        }
+    }
+
+    /// An array buffer, whose length is measured in elements of `T`
+    pub fn slice<T>(sl: &'a mut KSlice<T>) -> Self {
+        Self {
+            // SAFETY: `KSlice<T>` has the same layout for every `T`. The pointer is only ever set to storage suitable for `T`.
+            raw: unsafe { &mut *(sl as *mut KSlice<T>).cast::<KSlice<u8>>() },
+            elem: Layout::new::<T>(),
+        }
     }
 }
 
 pub trait FromRequest: Any {
     const REQ_ID: Uuid;
 
-    /// Finds each `KStrPtr` in `x` and returns an array to them.
-    /// The trait implementation may use `init_array` to store the references to the pointer.
+    /// The number of output buffers reported by [`FromRequest::find_buffers`].
+    ///
+    /// It is a compile error to make a request that declares more buffers than fit in a [`SysInfoRequest`][sys::SysInfoRequest].
+    const BUFFER_COUNT: usize = 0;
+
+    /// The initial capacity, in bytes, of each output buffer. Buffers that are too short are grown (at least doubling in size) until the results fit.
+    const BUFFER_CAPACITY: usize = 32;
+
+    /// Initializes the input fields of `x`, after the header is initialized and before [`FromRequest::find_buffers`] is called.
+    ///
+    /// The default implementation does nothing, which is correct for requests that have no input fields.
+    fn init_request(_x: &mut sys::SysInfoRequest) {}
+
+    /// Finds each output buffer (each [`KStrPtr`] or [`KSlice`]) in `x`, and pushes it to `buffers`.
+    ///
+    /// The default implementation finds nothing, which is correct for requests that have no output buffers.
     ///
     /// # Safety
     ///
     /// `x` must be a valid [`SysInfoRequest`][sys::SysInfoRequest] corresponding to [`Self::REQ_ID`][FromRequest::REQ_ID].
-    unsafe fn find_strings<'a, 'b>(
-        x: &'a mut sys::SysInfoRequest,
-        init_arr: &'b mut [Option<&'a mut KStrPtr>],
-    ) -> &'b mut [&'a mut KStrPtr];
+    unsafe fn find_buffers<'a>(
+        _x: &'a mut sys::SysInfoRequest,
+        _buffers: &mut Vec<RequestBuffer<'a>>,
+    ) {
+    }
 
     /// # Safety
     /// `x` must be a valid [`SysInfoRequest`][sys::SysInfoRequest] corresponding to [`Self::REQ_ID`][FromRequest::REQ_ID] that was fulfilled,
-    /// and all strings indicated by `find_strings` are fully valid (point to fully populated memory that is valid UTF-8).
+    /// and all buffers indicated by `find_buffers` are fully valid (point to fully populated memory, and strings are valid UTF-8).
     ///
-    /// Note: Any [`KStrPtr`] referred to by `x` is not guaranteed to outlive the function call
+    /// Note: Any buffer referred to by `x` is not guaranteed to outlive the function call
     unsafe fn from_request(x: &sys::SysInfoRequest) -> Self;
 }
 
+/// A request about a single processor, made with [`RequestBuilder::proc_request`],
+///  or about every processor of the system with [`CommonProcInfo`].
+pub trait FromProcRequest: Any {
+    const REQ_ID: Uuid;
+
+    /// The number of output buffers reported by [`FromProcRequest::find_buffers`].
+    ///
+    /// It is a compile error to make a request that declares more buffers than fit in a [`ProcInfoRequest`][sys::ProcInfoRequest].
+    const BUFFER_COUNT: usize = 0;
+
+    /// The initial capacity, in bytes, of each output buffer. Buffers that are too short are grown (at least doubling in size) until the results fit.
+    const BUFFER_CAPACITY: usize = 32;
+
+    /// Initializes the input fields of `x`, after the header is initialized and before [`FromProcRequest::find_buffers`] is called.
+    ///
+    /// The default implementation does nothing, which is correct for requests that have no input fields.
+    fn init_request(_x: &mut sys::ProcInfoRequest) {}
+
+    /// Finds each output buffer (each [`KStrPtr`] or [`KSlice`]) in `x`, and pushes it to `buffers`.
+    ///
+    /// The default implementation finds nothing, which is correct for requests that have no output buffers.
+    ///
+    /// # Safety
+    ///
+    /// `x` must be a valid [`ProcInfoRequest`][sys::ProcInfoRequest] corresponding to [`Self::REQ_ID`][FromProcRequest::REQ_ID].
+    unsafe fn find_buffers<'a>(
+        _x: &'a mut sys::ProcInfoRequest,
+        _buffers: &mut Vec<RequestBuffer<'a>>,
+    ) {
+    }
+
+    /// # Safety
+    /// `x` must be a valid [`ProcInfoRequest`][sys::ProcInfoRequest] corresponding to [`Self::REQ_ID`][FromProcRequest::REQ_ID] that was fulfilled,
+    /// and all buffers indicated by `find_buffers` are fully valid (point to fully populated memory, and strings are valid UTF-8).
+    ///
+    /// Note: Any buffer referred to by `x` is not guaranteed to outlive the function call
+    unsafe fn from_request(x: &sys::ProcInfoRequest) -> Self;
+}
+
+/// Makes the processor request `T` as a system request, which obtains the information common to every processor of the system.
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+pub struct CommonProcInfo<T>(pub T);
+
+impl<T: FromProcRequest> FromRequest for CommonProcInfo<T> {
+    const REQ_ID: Uuid = T::REQ_ID;
+    const BUFFER_COUNT: usize = T::BUFFER_COUNT;
+    const BUFFER_CAPACITY: usize = T::BUFFER_CAPACITY;
+
+    fn init_request(x: &mut sys::SysInfoRequest) {
+        T::init_request(unsafe { &mut x.common_processor_info })
+    }
+
+    unsafe fn find_buffers<'a>(
+        x: &'a mut sys::SysInfoRequest,
+        buffers: &mut Vec<RequestBuffer<'a>>,
+    ) {
+        unsafe { T::find_buffers(&mut x.common_processor_info, buffers) }
+    }
+
+    unsafe fn from_request(x: &sys::SysInfoRequest) -> Self {
+        Self(unsafe { T::from_request(&x.common_processor_info) })
+    }
+}
+
 #[derive(Clone, Debug, Hash, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct OsVersion {
     pub vendor: String,
     pub major_version: u32,
@@ -57,14 +165,13 @@ pub struct OsVersion {
 
 impl FromRequest for OsVersion {
     const REQ_ID: Uuid = sys::SYSINFO_REQUEST_OSVER;
+    const BUFFER_COUNT: usize = 1;
 
-    unsafe fn find_strings<'a, 'b>(
+    unsafe fn find_buffers<'a>(
         x: &'a mut sys::SysInfoRequest,
-        init_arr: &'b mut [Option<&'a mut KStrPtr>],
-    ) -> &'b mut [&'a mut KStrPtr] {
-        init_arr[0] = Some(&mut x.os_version.osvendor_name);
-
-        unsafe { core::slice::from_raw_parts_mut(init_arr.as_mut_ptr() as *mut &'a mut KStrPtr, 1) }
+        buffers: &mut Vec<RequestBuffer<'a>>,
+    ) {
+        buffers.push(RequestBuffer::string(&mut x.os_version.osvendor_name));
     }
 
     unsafe fn from_request(x: &sys::SysInfoRequest) -> Self {
@@ -86,6 +193,7 @@ impl FromRequest for OsVersion {
 }
 
 #[derive(Clone, Debug, Hash, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct KernelVendor {
     pub vendor: String,
     pub build_id: Uuid,
@@ -95,14 +203,13 @@ pub struct KernelVendor {
 
 impl FromRequest for KernelVendor {
     const REQ_ID: Uuid = sys::SYSINFO_REQUEST_KVENDOR;
+    const BUFFER_COUNT: usize = 1;
 
-    unsafe fn find_strings<'a, 'b>(
+    unsafe fn find_buffers<'a>(
         x: &'a mut sys::SysInfoRequest,
-        init_arr: &'b mut [Option<&'a mut KStrPtr>],
-    ) -> &'b mut [&'a mut KStrPtr] {
-        init_arr[0] = Some(&mut x.kernel_vendor.kvendor_name);
-
-        unsafe { core::slice::from_raw_parts_mut(init_arr.as_mut_ptr() as *mut &'a mut KStrPtr, 1) }
+        buffers: &mut Vec<RequestBuffer<'a>>,
+    ) {
+        buffers.push(RequestBuffer::string(&mut x.kernel_vendor.kvendor_name));
     }
 
     unsafe fn from_request(x: &sys::SysInfoRequest) -> Self {
@@ -126,6 +233,7 @@ impl FromRequest for KernelVendor {
 }
 
 #[derive(Copy, Clone, Hash, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ArchInfo {
     pub arch_id: Uuid,
     pub version: u32,
@@ -134,13 +242,6 @@ pub struct ArchInfo {
 impl FromRequest for ArchInfo {
     const REQ_ID: Uuid = sys::SYSINFO_REQUEST_ARCH_INFO;
 
-    unsafe fn find_strings<'a, 'b>(
-        _: &'a mut sys::SysInfoRequest,
-        _: &'b mut [Option<&'a mut KStrPtr>],
-    ) -> &'b mut [&'a mut KStrPtr] {
-        &mut []
-    }
-
     unsafe fn from_request(x: &sys::SysInfoRequest) -> Self {
         Self {
             arch_id: x.arch_info.arch_type,
@@ -204,6 +305,7 @@ impl core::fmt::Debug for ArchInfo {
 }
 
 #[derive(Clone, Debug, Hash, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ComputerName {
     pub hostname: String,
     pub display_name: String,
@@ -213,17 +315,16 @@ pub struct ComputerName {
 
 impl FromRequest for ComputerName {
     const REQ_ID: Uuid = sys::SYSINFO_REQUEST_COMPUTER_NAME;
-    unsafe fn find_strings<'a, 'b>(
+    const BUFFER_COUNT: usize = 3;
+    unsafe fn find_buffers<'a>(
         x: &'a mut sys::SysInfoRequest,
-        init_arr: &'b mut [Option<&'a mut KStrPtr>],
-    ) -> &'b mut [&'a mut KStrPtr] {
+        buffers: &mut Vec<RequestBuffer<'a>>,
+    ) {
         let req = &mut x.computer_name;
 
-        init_arr[0] = Some(&mut req.hostname);
-        init_arr[1] = Some(&mut req.sys_display_name);
-        init_arr[2] = Some(&mut req.sys_label);
-
-        unsafe { core::slice::from_raw_parts_mut(init_arr.as_mut_ptr() as *mut &'a mut KStrPtr, 3) }
+        buffers.push(RequestBuffer::string(&mut req.hostname));
+        buffers.push(RequestBuffer::string(&mut req.sys_display_name));
+        buffers.push(RequestBuffer::string(&mut req.sys_label));
     }
 
     unsafe fn from_request(x: &sys::SysInfoRequest) -> Self {
@@ -248,55 +349,562 @@ impl FromRequest for ComputerName {
     }
 }
 
-pub struct RequestBuilder {
-    requests: Vec<sys::SysInfoRequest>,
-    strings: Vec<(StringIndex, Vec<u8>)>,
-    impls: BTreeMap<TypeId, (usize, fn(*mut (), &sys::SysInfoRequest))>,
+/// Information about the physical processors of the system
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+pub struct PhysicalInfo {
+    /// The number of physical cores across all active processors
+    pub physical_core_count: u32,
+    /// The number of logical cores (threads), which may be greater than `physical_core_count` if the processor supports hyperthreading
+    pub logical_core_count: u32,
+    /// The number of physically installed discrete processors
+    pub discrete_processor_count: u32,
 }
 
-impl RequestBuilder {
-    pub const fn new() -> Self {
+impl FromRequest for PhysicalInfo {
+    const REQ_ID: Uuid = sys::SYSINFO_REQUEST_PHYSICAL_INFO;
+
+    unsafe fn from_request(x: &sys::SysInfoRequest) -> Self {
+        let sys::SysInfoRequestPhysicalInfo {
+            physical_core_count,
+            logical_core_count,
+            discrete_processor_count,
+            ..
+        } = x.processor_info;
+
         Self {
-            requests: Vec::new(),
-            strings: Vec::new(),
-            impls: BTreeMap::new(),
+            physical_core_count,
+            logical_core_count,
+            discrete_processor_count,
         }
     }
+}
 
-    pub fn request<T: FromRequest>(mut self) -> Self {
-        let id = TypeId::of::<T>();
-        if !self.impls.contains_key(&id) {
-            let idx = self.requests.len();
-            let mut req = sys::SysInfoRequest {
+/// Information about the address space available to userspace
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+pub struct AddressSpace {
+    /// The minimum virtual address that a userspace program can map
+    pub min_mapping_addr: usize,
+    /// The maximum virtual address that a userspace program can map
+    pub max_mapping_addr: usize,
+    /// The size of a page of memory
+    pub page_size: usize,
+}
+
+impl FromRequest for AddressSpace {
+    const REQ_ID: Uuid = sys::SYSINFO_REQUEST_ADDRESS_SPACE;
+
+    unsafe fn from_request(x: &sys::SysInfoRequest) -> Self {
+        let sys::SysInfoRequestAddressSpace {
+            min_mapping_addr,
+            max_mapping_addr,
+            page_size,
+            ..
+        } = x.addr_space;
+
+        Self {
+            min_mapping_addr,
+            max_mapping_addr,
+            page_size,
+        }
+    }
+}
+
+/// Information about the kernel subsystem numbered `NUMBER` (see the `SUBSYSTEM_*` constants in [`crate::sys::kmgmt`]).
+///
+/// Subsystems are identified by number rather than by UUID, as [`SysInfoRequestSupportedSubsystem`][sys::SysInfoRequestSupportedSubsystem] selects the subsystem by its number.
+///
+/// The request is only fulfilled if the kernel supports the subsystem, so it should be made with [`RequestBuilder::opt_request`].
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub struct SupportedSubsystem<const NUMBER: u16> {
+    /// The name of the subsystem
+    pub name: String,
+    /// The version of the subsystem implemented by the kernel
+    pub version: u32,
+    /// The highest system call number in the subsystem that the kernel supports
+    pub max_sysno: u16,
+}
+
+impl<const NUMBER: u16> FromRequest for SupportedSubsystem<NUMBER> {
+    const REQ_ID: Uuid = sys::SYSINFO_REQUEST_SUPPORTED_SUBSYSTEM;
+    const BUFFER_COUNT: usize = 1;
+
+    fn init_request(x: &mut sys::SysInfoRequest) {
+        x.supported_subsystem.subsystem_no = NUMBER;
+    }
+
+    unsafe fn find_buffers<'a>(
+        x: &'a mut sys::SysInfoRequest,
+        buffers: &mut Vec<RequestBuffer<'a>>,
+    ) {
+        buffers.push(RequestBuffer::string(
+            &mut x.supported_subsystem.subsystem_name,
+        ));
+    }
+
+    unsafe fn from_request(x: &sys::SysInfoRequest) -> Self {
+        let sys::SysInfoRequestSupportedSubsystem {
+            subsystem_name,
+            subsystem_version,
+            max_sysno,
+            ..
+        } = x.supported_subsystem;
+
+        Self {
+            name: subsystem_name.as_str().to_string(),
+            version: subsystem_version,
+            max_sysno,
+        }
+    }
+}
+
+/// A subsystem supported by the kernel, as enumerated by [`supported_subsystems`]
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub struct Subsystem {
+    /// The subsystem number, which is the high bits of each system call number in the subsystem
+    pub number: u16,
+    /// The name of the subsystem
+    pub name: String,
+    /// The version of the subsystem implemented by the kernel
+    pub version: u32,
+    /// The highest system call number in the subsystem that the kernel supports
+    pub max_sysno: u16,
+}
+
+/// Queries whether the kernel supports the subsystem `number`, without naming it in a [`SupportedSubsystem`] type.
+pub fn supported_subsystem(number: u16) -> crate::result::Result<Option<Subsystem>> {
+    query_subsystems(number..=number).map(|mut subsystems| subsystems.pop())
+}
+
+/// Enumerates every subsystem supported by the kernel, in order of subsystem number.
+///
+/// This issues a single batch of [`SupportedSubsystem`] requests for every possible subsystem number.
+pub fn supported_subsystems() -> crate::result::Result<Vec<Subsystem>> {
+    query_subsystems(0..=crate::sys::kmgmt::SUBSYSTEM_LIMIT - 1)
+}
+
+fn query_subsystems(
+    numbers: core::ops::RangeInclusive<u16>,
+) -> crate::result::Result<Vec<Subsystem>> {
+    let mut names = numbers
+        .clone()
+        .map(|_| Vec::<u8>::with_capacity(32))
+        .collect::<Vec<_>>();
+    let mut requests = numbers
+        .zip(&mut names)
+        .map(|(subsystem_no, name)| sys::SysInfoRequest {
+            supported_subsystem: sys::SysInfoRequestSupportedSubsystem {
                 head: ExtendedOptionHead {
-                    ty: T::REQ_ID,
-                    flags: 0,
+                    ty: sys::SYSINFO_REQUEST_SUPPORTED_SUBSYSTEM,
+                    flags: OPTION_FLAG_IGNORE,
                     ..Zeroable::zeroed()
                 },
-            };
+                subsystem_no,
+                max_sysno: 0,
+                subsystem_version: 0,
+                subsystem_name: KStrPtr {
+                    str_ptr: name.as_mut_ptr(),
+                    len: name.capacity(),
+                },
+            },
+        })
+        .collect::<Vec<_>>();
 
-            let mut storage_buffer = [None, None, None, None];
+    while let Err(e) = crate::result::Error::from_code(unsafe {
+        sys::GetSystemInfo(KSlice::from_slice_mut(&mut requests))
+    }) {
+        if e != crate::result::Error::InsufficientLength {
+            return Err(e);
+        }
 
-            let addr = core::ptr::addr_of!(req).addr();
+        let mut work_done = false;
+        for (req, name) in requests.iter_mut().zip(&mut names) {
+            work_done |= crate::kstr::grow_buffer(
+                unsafe { &mut req.supported_subsystem.subsystem_name },
+                name,
+            );
+        }
 
-            let strings = unsafe { T::find_strings(&mut req, &mut storage_buffer) };
+        if !work_done {
+            return Err(e);
+        }
+    }
 
-            for str in strings {
-                let offset = core::ptr::addr_of!(*str).addr().wrapping_sub(addr);
+    Ok(requests
+        .iter()
+        .map(|req| unsafe { &req.supported_subsystem })
+        .filter(|req| (req.head.flags & OPTION_FLAG_IGNORE) == 0)
+        .map(|req| Subsystem {
+            number: req.subsystem_no,
+            name: unsafe { req.subsystem_name.as_str() }.to_string(),
+            version: req.subsystem_version,
+            max_sysno: req.max_sysno,
+        })
+        .collect())
+}
 
-                if offset > 96 {
-                    panic!("Wrong index of string. {} attempted to designate string at address {:p} ({} bytes away from the base of the request)", core::any::type_name::<T>(), str as *mut _, offset as isize)
-                }
+/// Obtains the operating system version
+pub fn os_version() -> crate::result::Result<OsVersion> {
+    RequestBuilder::new()
+        .request::<OsVersion>()
+        .resolve()
+        .map(|res| res.get())
+}
+
+/// Obtains information about the vendor of the kernel build
+pub fn kernel_vendor() -> crate::result::Result<KernelVendor> {
+    RequestBuilder::new()
+        .request::<KernelVendor>()
+        .resolve()
+        .map(|res| res.get())
+}
+
+/// Obtains the processor architecture of the system
+pub fn arch_info() -> crate::result::Result<ArchInfo> {
+    RequestBuilder::new()
+        .request::<ArchInfo>()
+        .resolve()
+        .map(|res| res.get())
+}
 
-                let index = StringIndex((idx << 6) | (offset - 32));
-                // Most SysRequests will return up to 32 bytes, so this is a reasonable base address
-                let mut vec = Vec::with_capacity(32);
-                str.len = 32;
-                str.str_ptr = vec.as_mut_ptr();
-                self.strings.push((index, vec));
+/// Obtains the names of the computer, or `None` if the kernel does not support the request
+pub fn computer_name() -> crate::result::Result<Option<ComputerName>> {
+    RequestBuilder::new()
+        .opt_request::<ComputerName>()
+        .resolve()
+        .map(|res| res.get_opt())
+}
+
+/// Obtains information about the physical processors, or `None` if the kernel does not support the request
+pub fn physical_info() -> crate::result::Result<Option<PhysicalInfo>> {
+    RequestBuilder::new()
+        .opt_request::<PhysicalInfo>()
+        .resolve()
+        .map(|res| res.get_opt())
+}
+
+/// Obtains information about the userspace address space, or `None` if the kernel does not support the request
+pub fn address_space() -> crate::result::Result<Option<AddressSpace>> {
+    RequestBuilder::new()
+        .opt_request::<AddressSpace>()
+        .resolve()
+        .map(|res| res.get_opt())
+}
+
+/// The size of a page of memory.
+///
+/// This is determined on first use from the auxiliary vector, or from an [`AddressSpace`] request if the auxiliary vector does not have it,
+///  and falls back to 4096 bytes if neither is available. The result is cached.
+pub fn page_size() -> usize {
+    static PAGE_SIZE: AtomicUsize = AtomicUsize::new(0);
+
+    match PAGE_SIZE.load(Ordering::Relaxed) {
+        0 => {
+            let size = crate::os::auxv::page_size()
+                .or_else(|| address_space().ok().flatten().map(|a| a.page_size))
+                .filter(|&size| size != 0)
+                .unwrap_or(4096);
+            PAGE_SIZE.store(size, Ordering::Relaxed);
+            size
+        }
+        size => size,
+    }
+}
+
+/// A request union that begins with an [`ExtendedOptionHead`]
+trait RawRequest: Copy {
+    fn with_head(head: ExtendedOptionHead) -> Self;
+}
+
+impl RawRequest for sys::SysInfoRequest {
+    fn with_head(head: ExtendedOptionHead) -> Self {
+        Self { head }
+    }
+}
+
+impl RawRequest for sys::ProcInfoRequest {
+    fn with_head(head: ExtendedOptionHead) -> Self {
+        Self { head }
+    }
+}
+
+/// The storage of a [`RequestBuffer`], whose descriptor is `offset` bytes into request `index` of a [`Batch`]
+struct OutputBuffer {
+    index: usize,
+    offset: usize,
+    elem: Layout,
+    ptr: NonNull<u8>,
+    cap: usize,
+}
+
+impl OutputBuffer {
+    fn layout(elem: Layout, cap: usize) -> Layout {
+        elem.size()
+            .checked_mul(cap)
+            .and_then(|size| Layout::from_size_align(size, elem.align()).ok())
+            .expect("Request buffer too large")
+    }
+
+    fn alloc(elem: Layout, cap: usize) -> NonNull<u8> {
+        let layout = Self::layout(elem, cap);
+        if layout.size() == 0 {
+            // SAFETY: Alignments are never zero
+            unsafe { NonNull::new_unchecked(sptr::invalid_mut(layout.align())) }
+        } else {
+            NonNull::new(unsafe { alloc::alloc::alloc(layout) })
+                .unwrap_or_else(|| alloc::alloc::handle_alloc_error(layout))
+        }
+    }
+
+    fn new(index: usize, offset: usize, capacity: usize, buf: RequestBuffer) -> Self {
+        let cap = capacity / buf.elem.size().max(1);
+        let ptr = Self::alloc(buf.elem, cap);
+        buf.raw.arr_ptr = ptr.as_ptr();
+        buf.raw.len = cap;
+        Self {
+            index,
+            offset,
+            elem: buf.elem,
+            ptr,
+            cap,
+        }
+    }
+
+    /// Grows the storage to hold the length the kernel stored in `raw` after an [`INSUFFICIENT_LENGTH`][crate::sys::result::errors::INSUFFICIENT_LENGTH] error,
+    ///  and points `raw` at the grown storage. The capacity is at least doubled, so that results that change between calls settle quickly.
+    ///
+    /// Returns `false` if the storage was already large enough, in which case the error was not caused by this buffer.
+    fn grow(&mut self, raw: &mut KSlice<u8>) -> bool {
+        if raw.len <= self.cap {
+            return false;
+        }
+        let cap = raw.len.max(self.cap.saturating_mul(2));
+        let ptr = Self::alloc(self.elem, cap);
+        self.dealloc();
+        self.ptr = ptr;
+        self.cap = cap;
+        raw.arr_ptr = ptr.as_ptr();
+        raw.len = cap;
+        true
+    }
+
+    fn dealloc(&mut self) {
+        let layout = Self::layout(self.elem, self.cap);
+        if layout.size() != 0 {
+            unsafe { alloc::alloc::dealloc(self.ptr.as_ptr(), layout) }
+        }
+    }
+}
+
+impl Drop for OutputBuffer {
+    fn drop(&mut self) {
+        self.dealloc()
+    }
+}
+
+/// The properties of a request type needed to add it to a [`Batch`]
+struct RequestInfo<R> {
+    ty: Uuid,
+    buffer_count: usize,
+    buffer_capacity: usize,
+    init: fn(&mut R),
+    find_buffers: for<'a> unsafe fn(&'a mut R, &mut Vec<RequestBuffer<'a>>),
+    name: fn() -> &'static str,
+}
+
+const fn max_buffers<R>() -> usize {
+    (core::mem::size_of::<R>() - core::mem::size_of::<ExtendedOptionHead>())
+        / core::mem::size_of::<KSlice<u8>>()
+}
+
+impl RequestInfo<sys::SysInfoRequest> {
+    const fn system<T: FromRequest>() -> Self {
+        assert!(
+            T::BUFFER_COUNT <= max_buffers::<sys::SysInfoRequest>(),
+            "Request declares more buffers than fit in a SysInfoRequest"
+        );
+        Self {
+            ty: T::REQ_ID,
+            buffer_count: T::BUFFER_COUNT,
+            buffer_capacity: T::BUFFER_CAPACITY,
+            init: T::init_request,
+            find_buffers: T::find_buffers,
+            name: core::any::type_name::<T>,
+        }
+    }
+}
+
+impl RequestInfo<sys::ProcInfoRequest> {
+    const fn processor<T: FromProcRequest>() -> Self {
+        assert!(
+            T::BUFFER_COUNT <= max_buffers::<sys::ProcInfoRequest>(),
+            "Request declares more buffers than fit in a ProcInfoRequest"
+        );
+        Self {
+            ty: T::REQ_ID,
+            buffer_count: T::BUFFER_COUNT,
+            buffer_capacity: T::BUFFER_CAPACITY,
+            init: T::init_request,
+            find_buffers: T::find_buffers,
+            name: core::any::type_name::<T>,
+        }
+    }
+}
+
+/// Requests made with a single system call, and the storage for their output buffers
+struct Batch<R> {
+    requests: Vec<R>,
+    buffers: Vec<OutputBuffer>,
+}
+
+impl<R: RawRequest> Batch<R> {
+    const fn new() -> Self {
+        Self {
+            requests: Vec::new(),
+            buffers: Vec::new(),
+        }
+    }
+
+    fn push(&mut self, info: &RequestInfo<R>, flags: u32) -> usize {
+        let index = self.requests.len();
+        let mut req = R::with_head(ExtendedOptionHead {
+            ty: info.ty,
+            flags,
+            ..Zeroable::zeroed()
+        });
+
+        (info.init)(&mut req);
+
+        let addr = core::ptr::addr_of!(req).addr();
+
+        let mut found = Vec::with_capacity(info.buffer_count);
+        unsafe { (info.find_buffers)(&mut req, &mut found) };
+
+        debug_assert!(
+            found.len() <= info.buffer_count,
+            "{} found {} buffers, but declared {}",
+            (info.name)(),
+            found.len(),
+            info.buffer_count
+        );
+
+        for buf in found {
+            let offset = core::ptr::addr_of!(*buf.raw).addr().wrapping_sub(addr);
+
+            debug_assert!(
+                offset <= core::mem::size_of::<R>() - core::mem::size_of::<KSlice<u8>>(),
+                "{} designated a buffer {} bytes away from the base of the request",
+                (info.name)(),
+                offset as isize
+            );
+
+            self.buffers
+                .push(OutputBuffer::new(index, offset, info.buffer_capacity, buf));
+        }
+
+        self.requests.push(req);
+        index
+    }
+
+    /// The descriptor of `buf` in `requests`
+    ///
+    /// # Safety
+    /// `buf` must have been found in a request at the same position of `requests`
+    unsafe fn raw_buffer<'a>(requests: &'a mut [R], buf: &OutputBuffer) -> &'a mut KSlice<u8> {
+        unsafe {
+            &mut *core::ptr::addr_of_mut!(requests[buf.index])
+                .cast::<u8>()
+                .add(buf.offset)
+                .cast::<KSlice<u8>>()
+        }
+    }
+
+    fn resolve<F: FnMut(KSlice<R>) -> SysResult>(&mut self, mut f: F) -> Result<()> {
+        while let Err(e) = Error::from_code(f(KSlice::from_slice_mut(&mut self.requests))) {
+            if e != Error::InsufficientLength {
+                return Err(e);
+            }
+
+            let mut work_done = false;
+
+            for buf in &mut self.buffers {
+                let raw = unsafe { Self::raw_buffer(&mut self.requests, buf) };
+                work_done |= buf.grow(raw);
+            }
+
+            if !work_done {
+                // We don't know what else is causing an `INSUFFICIENT_LENGTH` error, so just forward it back to the caller
+                return Err(e);
             }
+        }
+        Ok(())
+    }
+}
+
+impl<R: RawRequest> Clone for Batch<R> {
+    fn clone(&self) -> Self {
+        let mut requests = self.requests.clone();
+        let buffers = self
+            .buffers
+            .iter()
+            .map(|buf| {
+                let raw = unsafe { Self::raw_buffer(&mut requests, buf) };
+                let ptr = OutputBuffer::alloc(buf.elem, buf.cap);
+                let len = raw.len.min(buf.cap) * buf.elem.size();
+                unsafe { core::ptr::copy_nonoverlapping(buf.ptr.as_ptr(), ptr.as_ptr(), len) }
+                raw.arr_ptr = ptr.as_ptr();
+                OutputBuffer {
+                    index: buf.index,
+                    offset: buf.offset,
+                    elem: buf.elem,
+                    ptr,
+                    cap: buf.cap,
+                }
+            })
+            .collect();
+        Self { requests, buffers }
+    }
+}
 
-            self.requests.push(req);
+/// The index of a request in its [`Batch`], and the function that constructs the result from it
+type Ctor<R> = (usize, fn(*mut (), &R));
+
+const OPTIONAL_REQUEST: usize = 1 << (usize::BITS - 1);
+
+/// Builds a batch of information requests, which are made together by [`RequestBuilder::resolve`].
+///
+/// System requests ([`FromRequest`]) are made with a single [`GetSystemInfo`][sys::GetSystemInfo] call,
+///  and processor requests ([`FromProcRequest`]) with a single [`GetProcessorInfo`][sys::GetProcessorInfo] call for each processor named.
+///
+/// ## Example
+/// ```rust,ignore
+/// let results = RequestBuilder::new()
+///     .request::<OsVersion>()
+///     .proc_request::<CpuFeatures>(0)
+///     .resolve()?;
+/// let version = results.get::<OsVersion>();
+/// let features = results.get_proc::<CpuFeatures>(0);
+/// ```
+pub struct RequestBuilder {
+    system: Batch<sys::SysInfoRequest>,
+    processors: BTreeMap<u32, Batch<sys::ProcInfoRequest>>,
+    impls: BTreeMap<TypeId, Ctor<sys::SysInfoRequest>>,
+    proc_impls: BTreeMap<(u32, TypeId), Ctor<sys::ProcInfoRequest>>,
+}
+
+impl RequestBuilder {
+    pub const fn new() -> Self {
+        Self {
+            system: Batch::new(),
+            processors: BTreeMap::new(),
+            impls: BTreeMap::new(),
+            proc_impls: BTreeMap::new(),
+        }
+    }
+
+    pub fn request<T: FromRequest>(mut self) -> Self {
+        let id = TypeId::of::<T>();
+        if !self.impls.contains_key(&id) {
+            let idx = self.system.push(&const { RequestInfo::system::<T>() }, 0);
             let ctor_fn: fn(*mut (), &sys::SysInfoRequest) =
                 |ptr, req| unsafe { ptr.cast::<T>().write(T::from_request(req)) };
 
@@ -308,37 +916,52 @@ impl RequestBuilder {
     pub fn opt_request<T: FromRequest>(mut self) -> Self {
         let id = TypeId::of::<T>();
         if !self.impls.contains_key(&id) {
-            let idx = self.requests.len() | (1 << (usize::BITS - 1));
-            let mut req = sys::SysInfoRequest {
-                head: ExtendedOptionHead {
-                    ty: T::REQ_ID,
-                    flags: OPTION_FLAG_IGNORE,
-                    ..Zeroable::zeroed()
-                },
+            let idx = self
+                .system
+                .push(&const { RequestInfo::system::<T>() }, OPTION_FLAG_IGNORE)
+                | OPTIONAL_REQUEST;
+            let ctor_fn: fn(*mut (), &sys::SysInfoRequest) = |ptr, req| unsafe {
+                // Check if the kernel/USI impl has unset the ignore flag, indicating that the request has been fulfilled
+                if (req.head.flags & OPTION_FLAG_IGNORE) == 0 {
+                    ptr.cast::<Option<T>>().write(Some(T::from_request(req)));
+                } else {
+                    ptr.cast::<Option<T>>().write(None)
+                }
             };
 
-            let mut storage_buffer = [None, None, None, None];
-
-            let addr = core::ptr::addr_of!(req).addr();
-
-            let strings = unsafe { T::find_strings(&mut req, &mut storage_buffer) };
-
-            for str in strings {
-                let offset = core::ptr::addr_of!(*str).addr().wrapping_sub(addr);
+            self.impls.insert(id, (idx, ctor_fn));
+        }
+        self
+    }
 
-                if offset > 96 {
-                    panic!("Wrong index of string. {} attempted to designate string at address {:p} ({} bytes away from the base of the request)", core::any::type_name::<T>(), str as *mut _, offset as isize)
-                }
+    /// Requests `T` about the processor `proc_id`.
+    pub fn proc_request<T: FromProcRequest>(mut self, proc_id: u32) -> Self {
+        let id = (proc_id, TypeId::of::<T>());
+        if !self.proc_impls.contains_key(&id) {
+            let idx = self
+                .processors
+                .entry(proc_id)
+                .or_insert_with(Batch::new)
+                .push(&const { RequestInfo::processor::<T>() }, 0);
+            let ctor_fn: fn(*mut (), &sys::ProcInfoRequest) =
+                |ptr, req| unsafe { ptr.cast::<T>().write(T::from_request(req)) };
 
-                let index = StringIndex((idx << 6) | (offset - 32));
-                // Most SysRequests will return up to 32 bytes, so this is a reasonable base address
-                let mut vec = Vec::with_capacity(32);
-                str.len = 32;
-                str.str_ptr = vec.as_mut_ptr();
-                self.strings.push((index, vec));
-            }
+            self.proc_impls.insert(id, (idx, ctor_fn));
+        }
+        self
+    }
 
-            let ctor_fn: fn(*mut (), &sys::SysInfoRequest) = |ptr, req| unsafe {
+    /// Requests `T` about the processor `proc_id`, if the kernel supports the request.
+    pub fn opt_proc_request<T: FromProcRequest>(mut self, proc_id: u32) -> Self {
+        let id = (proc_id, TypeId::of::<T>());
+        if !self.proc_impls.contains_key(&id) {
+            let idx = self
+                .processors
+                .entry(proc_id)
+                .or_insert_with(Batch::new)
+                .push(&const { RequestInfo::processor::<T>() }, OPTION_FLAG_IGNORE)
+                | OPTIONAL_REQUEST;
+            let ctor_fn: fn(*mut (), &sys::ProcInfoRequest) = |ptr, req| unsafe {
                 // Check if the kernel/USI impl has unset the ignore flag, indicating that the request has been fulfilled
                 if (req.head.flags & OPTION_FLAG_IGNORE) == 0 {
                     ptr.cast::<Option<T>>().write(Some(T::from_request(req)));
@@ -347,113 +970,111 @@ impl RequestBuilder {
                 }
             };
 
-            self.requests.push(req);
-
-            self.impls.insert(id, (idx, ctor_fn));
+            self.proc_impls.insert(id, (idx, ctor_fn));
         }
         self
     }
 
-    pub fn resolve(self) -> crate::result::Result<RequestResults> {
+    pub fn resolve(self) -> Result<RequestResults> {
         let Self {
-            mut requests,
-            mut strings,
+            mut system,
+            mut processors,
             impls,
+            proc_impls,
         } = self;
 
-        while let Err(e) = crate::result::Error::from_code(unsafe {
-            sys::GetSystemInfo(KSlice::from_slice_mut(&mut requests))
-        }) {
-            match e {
-                crate::result::Error::InsufficientLength => {
-                    let mut work_done = false;
-
-                    for (offset, string) in &mut strings {
-                        let (index, offset) = offset.into_parts();
-
-                        let st = unsafe {
-                            &mut *core::ptr::addr_of_mut!(requests[index])
-                                .cast::<u8>()
-                                .add(offset)
-                                .cast::<KStrPtr>()
-                        };
-
-                        if st.len > string.capacity() {
-                            string.reserve(st.len as usize);
-                            st.str_ptr = string.as_mut_ptr();
-                            work_done = true
-                        }
-                    }
-
-                    if !work_done {
-                        // We don't know what else is causing an `INVALID_LENGTH` error, so just forward it back to the caller
-                        return Err(crate::result::Error::InsufficientLength);
-                    }
-                }
-                e => return Err(e),
-            }
+        if !system.requests.is_empty() {
+            system.resolve(|reqs| unsafe { sys::GetSystemInfo(reqs) })?;
+        }
+
+        for (&proc_id, batch) in &mut processors {
+            batch.resolve(|reqs| unsafe { sys::GetProcessorInfo(proc_id, reqs) })?;
         }
 
         Ok(RequestResults {
-            requests,
-            strings,
+            system,
+            processors,
             impls,
+            proc_impls,
         })
     }
 }
 
 #[derive(Clone)]
 pub struct RequestResults {
-    requests: Vec<sys::SysInfoRequest>,
-    strings: Vec<(StringIndex, Vec<u8>)>,
-    impls: BTreeMap<TypeId, (usize, fn(*mut (), &sys::SysInfoRequest))>,
+    system: Batch<sys::SysInfoRequest>,
+    processors: BTreeMap<u32, Batch<sys::ProcInfoRequest>>,
+    impls: BTreeMap<TypeId, Ctor<sys::SysInfoRequest>>,
+    proc_impls: BTreeMap<(u32, TypeId), Ctor<sys::ProcInfoRequest>>,
 }
 
-impl RequestResults {
-    pub fn get<T: FromRequest>(&self) -> T {
-        let ty = TypeId::of::<T>();
+fn check_optional<T>(idx: usize, optional: bool) -> usize {
+    match (idx & OPTIONAL_REQUEST != 0, optional) {
+        (true, false) => panic!(
+            "Attempted to obtain results from request `{}`, but that request was optional",
+            core::any::type_name::<T>()
+        ),
+        (false, true) => panic!("Attempted to obtain results from optional request `{}`, but that request was not marked optional", core::any::type_name::<T>()),
+        _ => idx & !OPTIONAL_REQUEST,
+    }
+}
+
+fn not_requested<T>() -> ! {
+    panic!(
+        "Attempt to obtain results from request `{}`, which was not made",
+        core::any::type_name::<T>()
+    )
+}
 
-        let (idx, ctor_fn) = match self.impls.get(&ty) {
+impl RequestResults {
+    fn construct<T: 'static, U>(&self, optional: bool) -> U {
+        let (idx, ctor_fn) = match self.impls.get(&TypeId::of::<T>()) {
             Some(&data) => data,
-            None => panic!(
-                "Attempt to obtain results from request `{}`, which was not made",
-                core::any::type_name::<T>()
-            ),
+            None => not_requested::<T>(),
         };
 
-        if idx & (1 << (usize::BITS - 1)) != 0 {
-            panic!(
-                "Attempted to obtain results from request `{}`, but that request was optional",
-                core::any::type_name::<T>()
-            )
-        }
+        let idx = check_optional::<T>(idx, optional);
 
-        let mut buf = MaybeUninit::<T>::uninit();
+        let mut buf = MaybeUninit::<U>::uninit();
 
-        ctor_fn(buf.as_mut_ptr().cast(), &self.requests[idx]);
+        ctor_fn(buf.as_mut_ptr().cast(), &self.system.requests[idx]);
 
         unsafe { buf.assume_init() }
     }
 
-    pub fn get_opt<T: FromRequest>(&self) -> Option<T> {
-        let ty = TypeId::of::<T>();
-
-        let (idx, ctor_fn) = match self.impls.get(&ty) {
+    fn construct_proc<T: 'static, U>(&self, proc_id: u32, optional: bool) -> U {
+        let (idx, ctor_fn) = match self.proc_impls.get(&(proc_id, TypeId::of::<T>())) {
             Some(&data) => data,
-            None => panic!(
-                "Attempt to obtain results from request `{}`, which was not made",
-                core::any::type_name::<T>()
-            ),
+            None => not_requested::<T>(),
         };
 
-        if idx & (1 << (usize::BITS - 1)) == 0 {
-            panic!("Attempted to obtain results from optional request `{}`, but that request was not marked optional", core::any::type_name::<T>())
-        }
+        let idx = check_optional::<T>(idx, optional);
 
-        let mut buf = MaybeUninit::<Option<T>>::uninit();
+        let mut buf = MaybeUninit::<U>::uninit();
 
-        ctor_fn(buf.as_mut_ptr().cast(), &self.requests[idx]);
+        ctor_fn(
+            buf.as_mut_ptr().cast(),
+            &self.processors[&proc_id].requests[idx],
+        );
 
         unsafe { buf.assume_init() }
     }
+
+    pub fn get<T: FromRequest>(&self) -> T {
+        self.construct::<T, T>(false)
+    }
+
+    pub fn get_opt<T: FromRequest>(&self) -> Option<T> {
+        self.construct::<T, Option<T>>(true)
+    }
+
+    /// Obtains the result of the request `T` about the processor `proc_id`, made with [`RequestBuilder::proc_request`]
+    pub fn get_proc<T: FromProcRequest>(&self, proc_id: u32) -> T {
+        self.construct_proc::<T, T>(proc_id, false)
+    }
+
+    /// Obtains the result of the request `T` about the processor `proc_id`, made with [`RequestBuilder::opt_proc_request`]
+    pub fn get_proc_opt<T: FromProcRequest>(&self, proc_id: u32) -> Option<T> {
+        self.construct_proc::<T, Option<T>>(proc_id, true)
+    }
 }
@@ -0,0 +1,189 @@
+//! Feature detection for x86 and x86_64 processors.
+//!
+//! Features are reported by the kernel through a [`ProcInfoX86CpuidFeatures`][sys::ProcInfoX86CpuidFeatures] request for the features common to all processors,
+//!  so detection works without `std`, and without executing `cpuid` directly.
+
+use crate::{result::Result, sync::OnceLock, sys::info as sys, uuid::Uuid};
+
+use super::{CommonProcInfo, FromProcRequest, RequestBuilder};
+
+/// A single cpu feature, identified by its position in the [`cpu_feature_info`][sys::ProcInfoX86CpuidFeatures::cpu_feature_info] array
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+pub struct Feature {
+    word: u8,
+    bit: u8,
+}
+
+/// A set of cpu features, either of a single processor, or common to every processor of the system
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+pub struct CpuFeatures([u32; 16]);
+
+macro_rules! def_features {
+    ($($(#[$meta:meta])* $name:ident = ($word:literal, $bit:literal);)*) => {
+        impl Feature {
+            $($(#[$meta])* #[allow(non_upper_case_globals)] pub const $name: Self = Self { word: $word, bit: $bit };)*
+
+            /// Looks up a feature by its name, as used by `is_x86_feature_detected!`.
+            ///
+            /// ## Panics
+            /// Panics if `name` is not a known feature. In a constant context (such as [`is_x86_feature_detected_lilium!`][crate::is_x86_feature_detected_lilium]), this is a compile error.
+            pub const fn from_name(name: &str) -> Self {
+                $(if const_str_eq(name, stringify!($name)) {
+                    return Self::$name;
+                })*
+                panic!("Unknown x86 feature")
+            }
+        }
+
+        impl CpuFeatures {
+            $($(#[$meta])* pub const fn $name(&self) -> bool {
+                self.has(Feature::$name)
+            })*
+        }
+    };
+}
+
+const fn const_str_eq(a: &str, b: &str) -> bool {
+    let (a, b) = (a.as_bytes(), b.as_bytes());
+    if a.len() != b.len() {
+        return false;
+    }
+    let mut i = 0;
+    while i < a.len() {
+        if a[i] != b[i] {
+            return false;
+        }
+        i += 1;
+    }
+    true
+}
+
+def_features! {
+    sse3 = (0, 0);
+    pclmulqdq = (0, 1);
+    ssse3 = (0, 9);
+    fma = (0, 12);
+    cmpxchg16b = (0, 13);
+    sse4_1 = (0, 19);
+    sse4_2 = (0, 20);
+    movbe = (0, 22);
+    popcnt = (0, 23);
+    aes = (0, 25);
+    xsave = (0, 26);
+    avx = (0, 28);
+    f16c = (0, 29);
+    rdrand = (0, 30);
+    tsc = (1, 4);
+    cmpxchg8b = (1, 8);
+    cmov = (1, 15);
+    mmx = (1, 23);
+    fxsr = (1, 24);
+    sse = (1, 25);
+    sse2 = (1, 26);
+    avx512vbmi = (2, 1);
+    avx512vbmi2 = (2, 6);
+    gfni = (2, 8);
+    vaes = (2, 9);
+    vpclmulqdq = (2, 10);
+    avx512vnni = (2, 11);
+    avx512bitalg = (2, 12);
+    avx512vpopcntdq = (2, 14);
+    rdpid = (2, 22);
+    avx512vp2intersect = (3, 8);
+    avx512fp16 = (3, 23);
+    fsgsbase = (4, 0);
+    bmi1 = (4, 3);
+    hle = (4, 4);
+    avx2 = (4, 5);
+    bmi2 = (4, 8);
+    ermsb = (4, 9);
+    rtm = (4, 11);
+    avx512f = (4, 16);
+    avx512dq = (4, 17);
+    rdseed = (4, 18);
+    adx = (4, 19);
+    avx512ifma = (4, 21);
+    avx512pf = (4, 26);
+    avx512er = (4, 27);
+    avx512cd = (4, 28);
+    sha = (4, 29);
+    avx512bw = (4, 30);
+    avx512vl = (4, 31);
+    sha512 = (5, 0);
+    sm3 = (5, 1);
+    sm4 = (5, 2);
+    avxvnni = (5, 4);
+    avx512bf16 = (5, 5);
+    // The kernel clears most of word 14 (see `cpu_feature_info`), so only the features in bits it reports are listed
+    tbm = (14, 21);
+    rdtscp = (15, 27);
+}
+
+impl CpuFeatures {
+    /// A feature set with no features
+    pub const NONE: Self = Self([0; 16]);
+
+    /// Constructs the feature set from the raw [`cpu_feature_info`][sys::ProcInfoX86CpuidFeatures::cpu_feature_info] array
+    pub const fn from_raw(info: [u32; 16]) -> Self {
+        Self(info)
+    }
+
+    pub const fn into_raw(self) -> [u32; 16] {
+        self.0
+    }
+
+    /// Tests whether `feature` is present
+    pub const fn has(&self, feature: Feature) -> bool {
+        (self.0[feature.word as usize] & (1 << feature.bit)) != 0
+    }
+}
+
+impl FromProcRequest for CpuFeatures {
+    const REQ_ID: Uuid = sys::PROCINFO_REQUEST_X86_CPUID_FEATURES;
+
+    unsafe fn from_request(x: &sys::ProcInfoRequest) -> Self {
+        Self(x.arch.cpuid_features.cpu_feature_info)
+    }
+}
+
+/// Queries the kernel for the cpu features common to every processor of the system
+pub fn features() -> Result<CpuFeatures> {
+    RequestBuilder::new()
+        .request::<CommonProcInfo<CpuFeatures>>()
+        .resolve()
+        .map(|res| res.get::<CommonProcInfo<CpuFeatures>>().0)
+}
+
+/// Queries the kernel for the cpu features of the processor `proc_id`
+pub fn processor_features(proc_id: u32) -> Result<CpuFeatures> {
+    RequestBuilder::new()
+        .proc_request::<CpuFeatures>(proc_id)
+        .resolve()
+        .map(|res| res.get_proc(proc_id))
+}
+
+/// The cpu features common to every processor of the system, queried once and cached.
+///
+/// If the kernel cannot report the features, no features are detected.
+pub fn detected_features() -> &'static CpuFeatures {
+    static FEATURES: OnceLock<CpuFeatures> = OnceLock::new();
+    FEATURES.get_or_init(|| features().unwrap_or(CpuFeatures::NONE))
+}
+
+/// Tests whether the named x86 feature is supported by every processor of the system, like `std::is_x86_feature_detected!`, but usable without `std`.
+///
+/// The feature names are the same as those of [`CpuFeatures`]. Naming an unknown feature is a compile error.
+///
+/// ## Example
+/// ```rust,ignore
+/// if lilium_sys::is_x86_feature_detected_lilium!("avx2") {
+///     // use the avx2 implementation
+/// }
+/// ```
+#[macro_export]
+macro_rules! is_x86_feature_detected_lilium {
+    ($feature:literal) => {{
+        const FEATURE: $crate::info::x86::Feature = $crate::info::x86::Feature::from_name($feature);
+        $crate::info::x86::detected_features().has(FEATURE)
+    }};
+}
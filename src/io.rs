@@ -7,13 +7,27 @@ use core::{
 pub use crate::sys::io::IOHandle;
 use crate::{
     handle::{AsHandle, HandleRef, OwnedHandle},
+    result::{Error, Result, ResultExt},
     sys::{
         fs::FileHandle,
         handle::HandlePtr,
-        io::{CloseIOStream, IOAbort, IORead},
+        io::{
+            CloseIOStream, CreatePipe, DuplicateIOHandle, GetIOCharacteristics, IOAbort, IORead,
+            IOSeek, IOSeekFar, IOSetMinReadSize, IOWrite, CHAR_RANDOMACCESS, CHAR_READABLE,
+            CHAR_SEEKABLE, CHAR_WRITABLE, MODE_ASYNC, MODE_BLOCKING, MODE_NONBLOCKING,
+            SEEK_FROM_CURRENT, SEEK_FROM_END, SEEK_FROM_START,
+        },
     },
 };
 
+mod membuf;
+mod poll;
+pub mod stdio;
+pub mod tty;
+
+pub use membuf::MemoryBuffer;
+pub use poll::{Interest, PollSet, Readiness, Trigger};
+
 unsafe impl<'a, H> AsHandle<'a, IOHandle> for H
 where
     H: AsHandle<'a, FileHandle>,
@@ -27,28 +41,325 @@ impl HandleRef<IOHandle> {
     pub fn read(&self, buf: &mut [u8]) -> crate::result::Result<usize> {
         let len = buf.len() as c_ulong;
         let code = unsafe {
-            IORead(
+            syscall!(IORead(
                 self.as_raw(),
                 buf as *mut [u8] as *mut u8 as *mut c_void,
                 len,
-            )
+            ))
+        };
+
+        if code == crate::sys::result::errors::PENDING {
+            unsafe {
+                syscall!(IOAbort(self.as_raw()));
+            }
+        }
+
+        code.into_value()
+    }
+
+    pub fn write(&self, buf: &[u8]) -> Result<usize> {
+        let code = unsafe {
+            syscall!(IOWrite(
+                self.as_raw(),
+                buf.as_ptr().cast(),
+                buf.len() as c_ulong
+            ))
         };
 
         if code == crate::sys::result::errors::PENDING {
             unsafe {
-                IOAbort(self.as_raw());
+                syscall!(IOAbort(self.as_raw()));
             }
         }
 
-        crate::result::Error::from_code(code).map(|()| code as usize)
+        code.into_value()
     }
+
+    /// Reads exactly enough bytes to fill `buf`, retrying reads that are interrupted.
+    ///
+    /// Returns [`Error::ClosedRemotely`] if the end of the stream is reached before `buf` is filled. The content of `buf` is unspecified if an error is returned.
+    pub fn read_exact(&self, mut buf: &mut [u8]) -> Result<()> {
+        while !buf.is_empty() {
+            match self.read(buf) {
+                Ok(0) => return Err(Error::ClosedRemotely),
+                Ok(n) => buf = &mut buf[n..],
+                Err(Error::Interrupted) => continue,
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(())
+    }
+
+    /// Writes all of `buf` to the stream, retrying writes that are interrupted.
+    ///
+    /// Returns [`Error::DeviceFull`] if the stream stops accepting bytes before all of `buf` is written.
+    pub fn write_all(&self, mut buf: &[u8]) -> Result<()> {
+        while !buf.is_empty() {
+            match self.write(buf) {
+                Ok(0) => return Err(Error::DeviceFull),
+                Ok(n) => buf = &buf[n..],
+                Err(Error::Interrupted) => continue,
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(())
+    }
+
+    /// Moves the position of the stream, and returns the new position from the start of the stream
+    pub fn seek(&self, pos: SeekFrom) -> Result<u64> {
+        let code = unsafe {
+            match pos {
+                SeekFrom::Start(off) => match i64::try_from(off) {
+                    Ok(off) => syscall!(IOSeek(self.as_raw(), SEEK_FROM_START, off)),
+                    Err(_) => syscall!(IOSeekFar(self.as_raw(), SEEK_FROM_START, off as i128)),
+                },
+                SeekFrom::End(off) => syscall!(IOSeek(self.as_raw(), SEEK_FROM_END, off)),
+                SeekFrom::Current(off) => syscall!(IOSeek(self.as_raw(), SEEK_FROM_CURRENT, off)),
+            }
+        };
+
+        code.into_size()
+    }
+
+    /// Sets the minimum number of bytes a read waits for when partial data is available. See [`IOSetMinReadSize`].
+    ///
+    /// Reads of fewer bytes, and reads at the end of the stream, may return less.
+    pub fn set_min_read_size(&self, size: usize) -> Result<()> {
+        Error::from_code(unsafe { syscall!(IOSetMinReadSize(self.as_raw(), size as c_ulong)) })
+    }
+
+    /// Opens a new handle to the same stream, which can only perform the operations in `chars_mask` that this handle can perform. See [`DuplicateIOHandle`].
+    ///
+    /// This allows handing out a restricted view of a stream, such as a read-only handle to a stream open for reading and writing.
+    pub fn duplicate_restricted(&self, chars_mask: IoChars) -> Result<OwnedHandle<IOHandle>> {
+        let mut hdl = MaybeUninit::uninit();
+        Error::from_code(unsafe {
+            syscall!(DuplicateIOHandle(
+                hdl.as_mut_ptr(),
+                self.as_raw(),
+                chars_mask.bits()
+            ))
+        })?;
+        Ok(unsafe { OwnedHandle::take_ownership(hdl.assume_init()) })
+    }
+}
+
+bitflags::bitflags! {
+    /// The operations supported by a stream, made of the `CHAR_*` constants
+    #[repr(transparent)]
+    #[derive(Copy, Clone, Hash, PartialEq, Eq, Debug)]
+    pub struct IoChars : u32 {
+        const READABLE = CHAR_READABLE;
+        const WRITABLE = CHAR_WRITABLE;
+        const SEEKABLE = CHAR_SEEKABLE;
+        const RANDOM_ACCESS = CHAR_RANDOMACCESS;
+    }
+}
+
+/// The operations supported by a stream, returned by [`characteristics`]
+#[derive(Copy, Clone, Hash, PartialEq, Eq, Debug)]
+pub struct IoCharacteristics(IoChars);
+
+impl IoCharacteristics {
+    /// The raw characteristics
+    pub const fn chars(self) -> IoChars {
+        self.0
+    }
+
+    /// Checks if the stream can be read from
+    pub const fn readable(self) -> bool {
+        self.0.contains(IoChars::READABLE)
+    }
+
+    /// Checks if the stream can be written to
+    pub const fn writable(self) -> bool {
+        self.0.contains(IoChars::WRITABLE)
+    }
+
+    /// Checks if the position of the stream can be moved by [`HandleRef::seek`]
+    pub const fn seekable(self) -> bool {
+        self.0.contains(IoChars::SEEKABLE)
+    }
+
+    /// Checks if the stream can be read or written at any position without moving the position of the stream
+    pub const fn random_access(self) -> bool {
+        self.0.contains(IoChars::RANDOM_ACCESS)
+    }
+}
+
+/// Returns the operations the stream `hdl` supports
+pub fn characteristics<'a, H: AsHandle<'a, IOHandle>>(hdl: H) -> Result<IoCharacteristics> {
+    unsafe { syscall!(GetIOCharacteristics(hdl.as_handle())) }
+        .into_value()
+        .map(|chars| IoCharacteristics(IoChars::from_bits_retain(chars)))
+}
+
+/// Seeks `io` for the `std::io::Seek` impls, failing with [`Error::UnsupportedOperation`] without seeking if the stream is not seekable
+#[cfg(feature = "std")]
+fn std_seek(io: &HandleRef<IOHandle>, pos: std::io::SeekFrom) -> std::io::Result<u64> {
+    if !characteristics(io)?.seekable() {
+        return Err(Error::UnsupportedOperation.into());
+    }
+    io.seek(pos.into()).map_err(Into::into)
+}
+
+/// A position to move a stream to by [`HandleRef::seek`]
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+pub enum SeekFrom {
+    /// Offset from the start of the stream
+    Start(u64),
+    /// Offset from the end of the stream
+    End(i64),
+    /// Offset from the current position of the stream
+    Current(i64),
+}
+
+/// How operations on a stream behave when they cannot complete immediately
+#[derive(Copy, Clone, Debug, Default, Hash, PartialEq, Eq)]
+pub enum BlockingMode {
+    /// The operation blocks the current thread until it completes
+    #[default]
+    Blocking,
+    /// The operation fails with [`Error::WouldBlock`]
+    NonBlocking,
+    /// The operation fails with [`Error::Pending`], and continues in the background
+    Async,
+}
+
+impl BlockingMode {
+    /// The `MODE_*` constant for the blocking mode
+    pub const fn into_raw(self) -> u32 {
+        match self {
+            Self::Blocking => MODE_BLOCKING,
+            Self::NonBlocking => MODE_NONBLOCKING,
+            Self::Async => MODE_ASYNC,
+        }
+    }
+
+    /// The blocking mode for a `MODE_*` constant, or `None` if `mode` is not a blocking mode
+    pub const fn from_raw(mode: u32) -> Option<Self> {
+        match mode {
+            MODE_BLOCKING => Some(Self::Blocking),
+            MODE_NONBLOCKING => Some(Self::NonBlocking),
+            MODE_ASYNC => Some(Self::Async),
+            _ => None,
+        }
+    }
+}
+
+/// Creates a blocking anonymous pipe, and returns the read end and the write end, in that order.
+pub fn pipe() -> Result<(OwnedHandle<IOHandle>, OwnedHandle<IOHandle>)> {
+    let mut write = MaybeUninit::uninit();
+    let mut read = MaybeUninit::uninit();
+    Error::from_code(unsafe {
+        syscall!(CreatePipe(
+            write.as_mut_ptr(),
+            read.as_mut_ptr(),
+            MODE_BLOCKING,
+            0
+        ))
+    })?;
+    Ok(unsafe {
+        (
+            OwnedHandle::take_ownership(read.assume_init()),
+            OwnedHandle::take_ownership(write.assume_init()),
+        )
+    })
+}
+
+#[cfg(feature = "std")]
+impl From<SeekFrom> for std::io::SeekFrom {
+    fn from(pos: SeekFrom) -> Self {
+        match pos {
+            SeekFrom::Start(off) => Self::Start(off),
+            SeekFrom::End(off) => Self::End(off),
+            SeekFrom::Current(off) => Self::Current(off),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<std::io::SeekFrom> for SeekFrom {
+    fn from(pos: std::io::SeekFrom) -> Self {
+        match pos {
+            std::io::SeekFrom::Start(off) => Self::Start(off),
+            std::io::SeekFrom::End(off) => Self::End(off),
+            std::io::SeekFrom::Current(off) => Self::Current(off),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::io::Read for &HandleRef<IOHandle> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        HandleRef::read(self, buf).map_err(Into::into)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::io::Write for &HandleRef<IOHandle> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        HandleRef::write(self, buf).map_err(Into::into)
+    }
+
+    /// Writes to an `IOHandle` are not buffered in userspace, so this does nothing
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::io::Seek for &HandleRef<IOHandle> {
+    fn seek(&mut self, pos: std::io::SeekFrom) -> std::io::Result<u64> {
+        std_seek(self, pos)
+    }
+}
+
+/// Implements the `std::io` traits for a type that can be borrowed as a `&HandleRef<IOHandle>` by `$borrow`
+#[cfg(feature = "std")]
+macro_rules! impl_std_io {
+    ($(impl$(<$lt:lifetime>)? for $ty:ty = |$this:ident| $borrow:expr;)*) => {
+        $(
+            impl$(<$lt>)? std::io::Read for $ty {
+                fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+                    let $this = &*self;
+                    ($borrow).read(buf).map_err(Into::into)
+                }
+            }
+
+            impl$(<$lt>)? std::io::Write for $ty {
+                fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                    let $this = &*self;
+                    ($borrow).write(buf).map_err(Into::into)
+                }
+
+                fn flush(&mut self) -> std::io::Result<()> {
+                    Ok(())
+                }
+            }
+
+            impl$(<$lt>)? std::io::Seek for $ty {
+                fn seek(&mut self, pos: std::io::SeekFrom) -> std::io::Result<u64> {
+                    let $this = &*self;
+                    std_seek(&$borrow, pos)
+                }
+            }
+        )*
+    };
+}
+
+#[cfg(feature = "std")]
+impl_std_io! {
+    impl for OwnedHandle<IOHandle> = |this| &**this;
+    impl for crate::fs::OwnedFile = |this| *this.as_io_handle();
+    impl<'a> for &'a crate::fs::OwnedFile = |this| *this.as_io_handle();
 }
 
 pub struct ReadMemBuf<'a>(HandlePtr<IOHandle>, PhantomData<&'a [u8]>);
 
 impl<'a> Drop for ReadMemBuf<'a> {
     fn drop(&mut self) {
-        let code = unsafe { CloseIOStream(self.0) };
+        let code = unsafe { syscall!(CloseIOStream(self.0)) };
         debug_assert_eq!(
             code,
             0,
@@ -66,15 +377,13 @@ impl<'a> ReadMemBuf<'a> {
         let mut hdl = MaybeUninit::uninit();
 
         crate::result::Error::from_code(unsafe {
-            crate::sys::io::CreateMemoryBuffer(
+            syscall!(crate::sys::io::CreateMemoryBuffer(
                 hdl.as_mut_ptr(),
                 crate::sys::io::MODE_BLOCKING,
                 buf.cast::<c_void>().cast_mut(),
                 len,
-                crate::sys::io::CHAR_READABLE
-                    | crate::sys::io::CHAR_RANDOMACCESS
-                    | crate::sys::io::CHAR_SEEKABLE,
-            )
+                (IoChars::READABLE | IoChars::RANDOM_ACCESS | IoChars::SEEKABLE).bits(),
+            ))
         })?;
 
         Ok(Self(unsafe { hdl.assume_init() }, PhantomData))
@@ -0,0 +1,147 @@
+use core::{
+    ffi::{c_ulong, c_void},
+    marker::PhantomData,
+    mem::{ManuallyDrop, MaybeUninit},
+    ops::Deref,
+    ptr::NonNull,
+};
+
+use alloc::boxed::Box;
+
+use crate::{
+    handle::{AsHandle, HandleRef, OwnedHandle},
+    result::{Error, Result},
+    sys::{handle::HandlePtr, io::CreateMemoryBuffer},
+};
+
+use super::{BlockingMode, IOHandle, IoChars};
+
+/// An [`IOHandle`] that reads and writes a buffer in memory, similar to `std::io::Cursor`.
+///
+/// This allows passing in-memory data to functions that expect a stream, such as to back a device with it.
+/// The buffer is borrowed (or owned) by the `MemoryBuffer`, so it cannot be accessed or freed while the handle is open.
+/// The handle is closed when the `MemoryBuffer` is dropped.
+///
+/// ## Example
+/// ```rust,ignore
+/// let buf = MemoryBuffer::new(b"Hello, world!")?;
+/// let mut out = [0u8; 5];
+/// buf.read(&mut out)?;
+/// assert_eq!(&out, b"Hello");
+/// ```
+pub struct MemoryBuffer<'a> {
+    hdl: ManuallyDrop<OwnedHandle<IOHandle>>,
+    owned: Option<NonNull<[u8]>>,
+    _buf: PhantomData<&'a mut [u8]>,
+}
+
+impl<'a> MemoryBuffer<'a> {
+    /// # Safety
+    /// `buf` must be valid for the operations in `chars` until the handle is closed
+    unsafe fn create(buf: NonNull<[u8]>, chars: IoChars, mode: BlockingMode) -> Result<Self> {
+        let mut hdl = MaybeUninit::uninit();
+        Error::from_code(unsafe {
+            CreateMemoryBuffer(
+                hdl.as_mut_ptr(),
+                mode.into_raw(),
+                buf.as_ptr().cast::<c_void>(),
+                buf.len() as c_ulong,
+                chars.bits(),
+            )
+        })?;
+        Ok(Self {
+            hdl: ManuallyDrop::new(unsafe { OwnedHandle::take_ownership(hdl.assume_init()) }),
+            owned: None,
+            _buf: PhantomData,
+        })
+    }
+
+    /// Opens a blocking, read-only stream over `buf`, that can be seeked and accessed at any position
+    pub fn new(buf: &'a [u8]) -> Result<Self> {
+        Self::with_chars(
+            buf,
+            IoChars::READABLE | IoChars::SEEKABLE | IoChars::RANDOM_ACCESS,
+        )
+    }
+
+    /// Opens a blocking stream over `buf` that supports the operations in `chars`.
+    ///
+    /// ## Errors
+    /// Returns [`Error::UnsupportedOperation`] if `chars` contains [`IoChars::WRITABLE`]. Use [`MemoryBuffer::new_mut`] for a writable stream.
+    pub fn with_chars(buf: &'a [u8], chars: IoChars) -> Result<Self> {
+        if chars.contains(IoChars::WRITABLE) {
+            return Err(Error::UnsupportedOperation);
+        }
+        unsafe { Self::create(NonNull::from(buf), chars, BlockingMode::Blocking) }
+    }
+
+    /// Opens a stream over `buf` that supports the operations in `chars`, and blocks as specified by `mode`.
+    ///
+    /// Writes to the stream modify `buf`. The stream cannot be extended past the end of `buf`.
+    pub fn new_mut(buf: &'a mut [u8], chars: IoChars, mode: BlockingMode) -> Result<Self> {
+        unsafe { Self::create(NonNull::from(buf), chars, mode) }
+    }
+}
+
+impl MemoryBuffer<'static> {
+    /// Opens a stream over `buf`, which is owned by the `MemoryBuffer`, that supports the operations in `chars` and blocks as specified by `mode`.
+    ///
+    /// The buffer can be recovered by [`MemoryBuffer::into_inner`].
+    pub fn from_boxed(buf: Box<[u8]>, chars: IoChars, mode: BlockingMode) -> Result<Self> {
+        // SAFETY: `Box::into_raw` is never null
+        let buf = unsafe { NonNull::new_unchecked(Box::into_raw(buf)) };
+        match unsafe { Self::create(buf, chars, mode) } {
+            Ok(mut this) => {
+                this.owned = Some(buf);
+                Ok(this)
+            }
+            Err(e) => {
+                drop(unsafe { Box::from_raw(buf.as_ptr()) });
+                Err(e)
+            }
+        }
+    }
+}
+
+impl MemoryBuffer<'_> {
+    /// Closes the stream, and returns the buffer if it is owned by the `MemoryBuffer`
+    pub fn into_inner(mut self) -> Option<Box<[u8]>> {
+        let owned = self.owned.take();
+        drop(self);
+        // SAFETY: The handle was closed when `self` was dropped, so the buffer is no longer accessed through it
+        owned.map(|buf| unsafe { Box::from_raw(buf.as_ptr()) })
+    }
+}
+
+impl Drop for MemoryBuffer<'_> {
+    fn drop(&mut self) {
+        // The handle must be closed before an owned buffer is freed
+        unsafe { ManuallyDrop::drop(&mut self.hdl) };
+        if let Some(buf) = self.owned {
+            drop(unsafe { Box::from_raw(buf.as_ptr()) });
+        }
+    }
+}
+
+impl Deref for MemoryBuffer<'_> {
+    type Target = HandleRef<IOHandle>;
+
+    fn deref(&self) -> &HandleRef<IOHandle> {
+        &self.hdl
+    }
+}
+
+unsafe impl<'a> AsHandle<'a, IOHandle> for &'a MemoryBuffer<'_> {
+    fn as_handle(&self) -> HandlePtr<IOHandle> {
+        self.hdl.as_raw()
+    }
+}
+
+impl core::fmt::Debug for MemoryBuffer<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.debug_struct("MemoryBuffer")
+            .field("hdl", &self.hdl.as_raw())
+            .field("owned", &self.owned.is_some())
+            .finish()
+    }
+}
@@ -0,0 +1,338 @@
+use core::{ffi::c_ulong, marker::PhantomData};
+
+use alloc::vec::Vec;
+
+use crate::{
+    handle::AsHandle,
+    result::{Error, Result, ResultExt},
+    sys::{
+        event::{self, EventData, EventHandle, EventInfo},
+        handle::HandlePtr,
+        io::{IOPollAll, PollInfo},
+        result::errors::{PENDING, WOULD_BLOCK},
+    },
+    time::Duration,
+};
+
+use super::IOHandle;
+
+bitflags::bitflags! {
+    /// The kinds of readiness a [`PollSet`] waits for on a handle
+    #[repr(transparent)]
+    #[derive(Copy, Clone, Hash, PartialEq, Eq, Debug)]
+    pub struct Interest : u32 {
+        /// The handle has data available to read, or has reached the end of the stream
+        const READABLE = 0x01;
+        /// The handle can accept a write without blocking
+        const WRITABLE = 0x02;
+    }
+}
+
+/// How a [`PollSet`] reports readiness that persists between calls to [`PollSet::poll`].
+///
+/// Lilium reports the current readiness of a handle, so edge-triggered registrations are emulated by the [`PollSet`].
+#[derive(Copy, Clone, Hash, PartialEq, Eq, Debug)]
+pub enum Trigger {
+    /// The handle is reported by every poll while it is ready.
+    Level,
+    /// Once an interest is reported for the handle, that interest is not reported again until the handle stops being ready, or the handle is re-registered.
+    ///
+    /// The [`PollSet`] checks whether a reported handle is still readable with [`IOPollAll`] at the start of each poll.
+    /// Writability cannot be checked without blocking, so [`Interest::WRITABLE`] is only reported again after [`PollSet::reregister`].
+    Edge,
+}
+
+/// A handle that is ready, returned by [`PollSet::poll`]
+#[derive(Copy, Clone, Debug)]
+pub struct Readiness {
+    token: usize,
+    ready: Interest,
+    read_bytes: usize,
+    error: Option<Error>,
+}
+
+impl Readiness {
+    /// The token the handle was registered with
+    pub fn token(&self) -> usize {
+        self.token
+    }
+
+    /// The interests the handle is ready for
+    pub fn ready(&self) -> Interest {
+        self.ready
+    }
+
+    pub fn is_readable(&self) -> bool {
+        self.ready.contains(Interest::READABLE)
+    }
+
+    pub fn is_writable(&self) -> bool {
+        self.ready.contains(Interest::WRITABLE)
+    }
+
+    /// The number of bytes available to read, if the handle is readable
+    pub fn bytes_available(&self) -> usize {
+        self.read_bytes
+    }
+
+    /// The error reported for the handle, if any. A handle with an error is reported as ready for each of its interests, so the error is also returned by the next operation on the handle.
+    pub fn error(&self) -> Option<Error> {
+        self.error
+    }
+}
+
+struct Registration {
+    hdl: HandlePtr<IOHandle>,
+    token: usize,
+    interest: Interest,
+    trigger: Trigger,
+    /// Interests of an edge-triggered handle which have been reported, and are not waited on until cleared
+    reported: Interest,
+}
+
+impl Registration {
+    fn armed(&self) -> Interest {
+        self.interest - self.reported
+    }
+}
+
+/// A long-lived set of [`IOHandle`]s to wait on, similar to `epoll` on other systems.
+///
+/// Each handle is registered with a token that identifies it in the results of [`PollSet::poll`].
+/// The events passed to [`BlockOnEventsAny`][event::BlockOnEventsAny] are only rebuilt when the set of armed registrations changes.
+pub struct PollSet<'a> {
+    regs: Vec<Registration>,
+    events: Vec<EventInfo>,
+    /// The index of the registration for each element of `events`
+    owners: Vec<usize>,
+    dirty: bool,
+    _handles: PhantomData<&'a IOHandle>,
+}
+
+impl core::fmt::Debug for PollSet<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("PollSet")
+            .field("len", &self.regs.len())
+            .finish_non_exhaustive()
+    }
+}
+
+impl Default for PollSet<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a> PollSet<'a> {
+    pub const fn new() -> Self {
+        Self {
+            regs: Vec::new(),
+            events: Vec::new(),
+            owners: Vec::new(),
+            dirty: false,
+            _handles: PhantomData,
+        }
+    }
+
+    /// The number of registered handles
+    pub fn len(&self) -> usize {
+        self.regs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.regs.is_empty()
+    }
+
+    fn find(&self, hdl: HandlePtr<IOHandle>) -> Option<usize> {
+        self.regs.iter().position(|reg| reg.hdl == hdl)
+    }
+
+    /// Registers `hdl` to be waited on for `interest`, reported with `token`.
+    ///
+    /// ## Errors
+    /// Returns [`Error::AlreadyExists`] if `hdl` is already registered.
+    pub fn register<H: AsHandle<'a, IOHandle>>(
+        &mut self,
+        hdl: H,
+        token: usize,
+        interest: Interest,
+        trigger: Trigger,
+    ) -> Result<()> {
+        let hdl = hdl.as_handle();
+        if self.find(hdl).is_some() {
+            return Err(Error::AlreadyExists);
+        }
+        self.regs.push(Registration {
+            hdl,
+            token,
+            interest,
+            trigger,
+            reported: Interest::empty(),
+        });
+        self.dirty = true;
+        Ok(())
+    }
+
+    /// Replaces the token, interest, and trigger of a registered handle. This re-arms any interest suppressed by [`Trigger::Edge`].
+    ///
+    /// ## Errors
+    /// Returns [`Error::DoesNotExist`] if `hdl` is not registered.
+    pub fn reregister<H: AsHandle<'a, IOHandle>>(
+        &mut self,
+        hdl: H,
+        token: usize,
+        interest: Interest,
+        trigger: Trigger,
+    ) -> Result<()> {
+        let idx = self.find(hdl.as_handle()).ok_or(Error::DoesNotExist)?;
+        let reg = &mut self.regs[idx];
+        reg.token = token;
+        reg.interest = interest;
+        reg.trigger = trigger;
+        reg.reported = Interest::empty();
+        self.dirty = true;
+        Ok(())
+    }
+
+    /// Removes `hdl` from the set.
+    ///
+    /// ## Errors
+    /// Returns [`Error::DoesNotExist`] if `hdl` is not registered.
+    pub fn deregister<H: AsHandle<'a, IOHandle>>(&mut self, hdl: H) -> Result<()> {
+        let idx = self.find(hdl.as_handle()).ok_or(Error::DoesNotExist)?;
+        self.regs.swap_remove(idx);
+        self.dirty = true;
+        Ok(())
+    }
+
+    /// Re-arms readable interests suppressed by [`Trigger::Edge`] on handles which are no longer readable
+    fn rearm_edges(&mut self) -> Result<()> {
+        let mut polls = self
+            .regs
+            .iter()
+            .filter(|reg| reg.reported.contains(Interest::READABLE))
+            .map(|reg| PollInfo {
+                hdl: reg.hdl,
+                read_bytes: 0,
+                status: 0,
+            })
+            .collect::<Vec<_>>();
+        if polls.is_empty() {
+            return Ok(());
+        }
+        Error::from_code(unsafe {
+            syscall!(IOPollAll(polls.as_mut_ptr(), polls.len() as c_ulong))
+        })?;
+
+        for poll in polls {
+            if poll.status == WOULD_BLOCK || poll.status == PENDING {
+                let reg = self
+                    .regs
+                    .iter_mut()
+                    .find(|reg| reg.hdl == poll.hdl)
+                    .unwrap();
+                reg.reported.remove(Interest::READABLE);
+                self.dirty = true;
+            }
+        }
+        Ok(())
+    }
+
+    fn rebuild(&mut self) {
+        self.events.clear();
+        self.owners.clear();
+        for (idx, reg) in self.regs.iter().enumerate() {
+            let armed = reg.armed();
+            for (interest, kind) in [
+                (Interest::READABLE, event::EVENT_IO_READABLE),
+                (Interest::WRITABLE, event::EVENT_IO_WRITABLE),
+            ] {
+                if armed.contains(interest) {
+                    self.events.push(EventInfo {
+                        kind,
+                        flags: 0,
+                        data: EventData {
+                            handle: EventHandle {
+                                hdl: reg.hdl.cast(),
+                                out: core::ptr::null_mut(),
+                            },
+                        },
+                        result: PENDING,
+                    });
+                    self.owners.push(idx);
+                }
+            }
+        }
+        self.dirty = false;
+    }
+
+    /// Blocks until at least one registered handle is ready, and appends the ready handles to `out`. Returns the number of handles appended.
+    ///
+    /// If `timeout` is set, returns `0` if no handle becomes ready within `timeout`. Also returns `0` immediately if no interest is armed.
+    ///
+    /// ## Errors
+    /// Returns [`Error::Timeout`] if the blocking timeout of the current thread elapses, and [`Error::Interrupted`] if the thread is interrupted.
+    pub fn poll(&mut self, out: &mut Vec<Readiness>, timeout: Option<Duration>) -> Result<usize> {
+        self.rearm_edges()?;
+        if self.dirty {
+            self.rebuild();
+        }
+        if self.events.is_empty() {
+            return Ok(0);
+        }
+
+        let events = &mut self.events;
+        let mut block = || {
+            Error::from_code(unsafe {
+                syscall!(event::BlockOnEventsAny(
+                    events.as_mut_ptr(),
+                    events.len() as c_ulong
+                ))
+            })
+        };
+        match timeout {
+            Some(dur) => match crate::thread::with_timeout(dur, block) {
+                Err(Error::Timeout) => return Ok(0),
+                res => res?,
+            },
+            None => block()?,
+        }
+
+        let start = out.len();
+        let mut last_idx = None;
+        for (ev, &idx) in self.events.iter().zip(&self.owners) {
+            if ev.result == PENDING {
+                continue;
+            }
+            let reg = &mut self.regs[idx];
+            let interest = if ev.kind == event::EVENT_IO_READABLE {
+                Interest::READABLE
+            } else {
+                Interest::WRITABLE
+            };
+            if reg.trigger == Trigger::Edge {
+                reg.reported |= interest;
+                self.dirty = true;
+            }
+
+            // The events for a registration are adjacent, so both interests are merged into one readiness
+            if last_idx != Some(idx) {
+                out.push(Readiness {
+                    token: reg.token,
+                    ready: Interest::empty(),
+                    read_bytes: 0,
+                    error: None,
+                });
+                last_idx = Some(idx);
+            }
+            let readiness = out.last_mut().unwrap();
+            readiness.ready |= interest;
+            match ev.result.into_value() {
+                Ok(bytes) if interest == Interest::READABLE => readiness.read_bytes = bytes,
+                Ok(_) => {}
+                Err(e) => readiness.error = Some(e),
+            }
+        }
+        Ok(out.len() - start)
+    }
+}
@@ -0,0 +1,396 @@
+//! Buffered access to the standard streams of the current thread, and the [`lilium_print!`][crate::lilium_print] family of macros.
+//!
+//! The standard streams are the thread-local handles [`__HANDLE_IO_STDIN`], [`__HANDLE_IO_STDOUT`], and [`__HANDLE_IO_STDERR`].
+//! Each stream has a single process-wide buffer, which is locked for as long as the value returned by [`Stdin::lock`], [`stdout`], or [`stderr`] is live.
+//! Buffered output is written to the stream of the thread that wrote it, even if the buffer is flushed by another thread.
+//! Locking the same stream again on the same thread while the lock is held deadlocks.
+
+use alloc::{string::String, vec::Vec};
+
+use crate::{
+    handle::BorrowedHandle,
+    result::{Error, Result},
+    sync::{Mutex, MutexGuard},
+    sys::{
+        handle::HandlePtr,
+        io::{__HANDLE_IO_STDERR, __HANDLE_IO_STDIN, __HANDLE_IO_STDOUT},
+    },
+};
+
+use super::IOHandle;
+
+static STDIN: Mutex<ReadBuffer> = Mutex::new(ReadBuffer {
+    buf: Vec::new(),
+    pos: 0,
+});
+static STDOUT: Mutex<WriteBuffer> = Mutex::new(WriteBuffer::new());
+static STDERR: Mutex<WriteBuffer> = Mutex::new(WriteBuffer::new());
+
+/// The size of the reads made to refill the buffer of [`StdinLock`]
+const READ_CHUNK: usize = 1024;
+
+/// Buffered output, and the stream it is written to
+#[derive(Debug)]
+struct WriteBuffer {
+    buf: Vec<u8>,
+    hdl: HandlePtr<IOHandle>,
+}
+
+// SAFETY: The handle is only used to write the buffered output, and is valid on every thread of the process
+unsafe impl Send for WriteBuffer {}
+
+impl WriteBuffer {
+    const fn new() -> Self {
+        Self {
+            buf: Vec::new(),
+            hdl: HandlePtr::null(),
+        }
+    }
+
+    /// Prepares to buffer output for `hdl`, first writing output buffered for another stream to that stream
+    fn target(&mut self, hdl: HandlePtr<IOHandle>) -> Result<()> {
+        let res = if self.hdl != hdl && !self.buf.is_empty() {
+            self.flush()
+        } else {
+            Ok(())
+        };
+        self.hdl = hdl;
+        res
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        // SAFETY: The standard stream handles are valid (or null) for the lifetime of the thread
+        let res = unsafe { BorrowedHandle::from_raw(self.hdl) }.write_all(&self.buf);
+        self.buf.clear();
+        res
+    }
+}
+
+/// Appends formatted output to a buffer
+struct Appender<'a>(&'a mut Vec<u8>);
+
+impl core::fmt::Write for Appender<'_> {
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        self.0.extend_from_slice(s.as_bytes());
+        Ok(())
+    }
+}
+
+macro_rules! impl_writer {
+    ($($ty:ident = $hdl:ident;)*) => {
+        $(
+            impl $ty<'_> {
+                /// Writes `bytes` to the buffer
+                pub fn write(&mut self, bytes: &[u8]) -> Result<()> {
+                    self.buf.target(unsafe { $hdl })?;
+                    let start = self.buf.buf.len();
+                    self.buf.buf.extend_from_slice(bytes);
+                    self.written(start)
+                }
+
+                /// Writes formatted output to the buffer, as by [`write!`]
+                pub fn write_fmt(&mut self, args: core::fmt::Arguments) -> Result<()> {
+                    self.buf.target(unsafe { $hdl })?;
+                    let start = self.buf.buf.len();
+                    if core::fmt::write(&mut Appender(&mut self.buf.buf), args).is_err() {
+                        // A formatting trait implementation returned an error, so the output is incomplete
+                        self.buf.buf.truncate(start);
+                        return Err(Error::InvalidOperation);
+                    }
+                    self.written(start)
+                }
+
+                /// Writes any buffered output to the stream of the thread that wrote it
+                pub fn flush(&mut self) -> Result<()> {
+                    self.buf.flush()
+                }
+            }
+
+            impl core::fmt::Write for $ty<'_> {
+                fn write_str(&mut self, s: &str) -> core::fmt::Result {
+                    self.write(s.as_bytes()).map_err(|_| core::fmt::Error)
+                }
+            }
+
+            impl Drop for $ty<'_> {
+                fn drop(&mut self) {
+                    let _ = self.flush();
+                }
+            }
+        )*
+    };
+}
+
+/// A locked, line-buffered writer to the standard output stream. See [`stdout`].
+///
+/// Output is written to the stream when a newline is written, when [`StdoutLock::flush`] is called, and when the lock is dropped.
+#[derive(Debug)]
+pub struct StdoutLock<'a> {
+    buf: MutexGuard<'a, WriteBuffer>,
+}
+
+impl StdoutLock<'_> {
+    fn written(&mut self, start: usize) -> Result<()> {
+        if self.buf.buf[start..].contains(&b'\n') {
+            self.flush()
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// A locked writer to the standard error stream. See [`stderr`].
+///
+/// Each call to [`StderrLock::write`] or [`StderrLock::write_fmt`] is written to the stream as a single write where possible, so that messages from concurrent processes are not interleaved.
+#[derive(Debug)]
+pub struct StderrLock<'a> {
+    buf: MutexGuard<'a, WriteBuffer>,
+}
+
+impl StderrLock<'_> {
+    fn written(&mut self, _: usize) -> Result<()> {
+        self.flush()
+    }
+}
+
+impl_writer! {
+    StdoutLock = __HANDLE_IO_STDOUT;
+    StderrLock = __HANDLE_IO_STDERR;
+}
+
+#[derive(Debug)]
+struct ReadBuffer {
+    buf: Vec<u8>,
+    pos: usize,
+}
+
+/// The standard input stream of the current thread, read through the process-wide buffer. See [`stdin`].
+#[derive(Copy, Clone, Debug)]
+pub struct Stdin(());
+
+impl Stdin {
+    /// Locks the buffer of the standard input stream
+    pub fn lock(&self) -> StdinLock<'static> {
+        StdinLock { buf: STDIN.lock() }
+    }
+
+    /// Locks the buffer, and reads a line as by [`StdinLock::read_line`]
+    pub fn read_line(&self, line: &mut String) -> Result<usize> {
+        self.lock().read_line(line)
+    }
+
+    /// Locks the buffer, and returns an iterator over the remaining lines. See [`StdinLock::lines`].
+    pub fn lines(self) -> Lines<'static> {
+        self.lock().lines()
+    }
+
+    /// Sets the minimum number of bytes a read of the stream waits for, if the stream supports it. See [`HandleRef::set_min_read_size`][crate::handle::HandleRef::set_min_read_size].
+    ///
+    /// A value of `1` returns input as soon as any is available, which suits interactive use, while larger values reduce the number of reads made for bulk input.
+    pub fn set_min_read_size(&self, size: usize) -> Result<()> {
+        // SAFETY: The standard stream handles are valid (or null) for the lifetime of the thread
+        unsafe { BorrowedHandle::from_raw(__HANDLE_IO_STDIN) }.set_min_read_size(size)
+    }
+}
+
+/// A locked, buffered reader of the standard input stream. See [`Stdin::lock`].
+///
+/// Reads that are interrupted are retried. If the stream is non-blocking, reads fail with [`Error::WouldBlock`] when no input is available,
+/// and input that was already read (such as an incomplete line) is kept in the buffer for the next read.
+#[derive(Debug)]
+pub struct StdinLock<'a> {
+    buf: MutexGuard<'a, ReadBuffer>,
+}
+
+impl<'a> StdinLock<'a> {
+    /// Reads more input from the stream, appending it to the buffer, and returns the number of bytes read, or `0` at the end of the stream
+    fn fill_more(&mut self) -> Result<usize> {
+        let buf = &mut *self.buf;
+        buf.buf.drain(..buf.pos);
+        buf.pos = 0;
+
+        let len = buf.buf.len();
+        buf.buf.resize(len + READ_CHUNK, 0);
+        // SAFETY: The standard stream handles are valid (or null) for the lifetime of the thread
+        let hdl = unsafe { BorrowedHandle::from_raw(__HANDLE_IO_STDIN) };
+        let res = loop {
+            match hdl.read(&mut buf.buf[len..]) {
+                Err(Error::Interrupted) => continue,
+                res => break res,
+            }
+        };
+        buf.buf.truncate(len + *res.as_ref().unwrap_or(&0));
+        res
+    }
+
+    /// Returns the buffered input, reading more from the stream if the buffer is empty.
+    ///
+    /// An empty slice is returned at the end of the stream.
+    pub fn fill_buf(&mut self) -> Result<&[u8]> {
+        if self.buf.pos == self.buf.buf.len() {
+            self.fill_more()?;
+        }
+        Ok(&self.buf.buf[self.buf.pos..])
+    }
+
+    /// Marks `amt` bytes returned by [`StdinLock::fill_buf`] as read
+    pub fn consume(&mut self, amt: usize) {
+        self.buf.pos = (self.buf.pos + amt).min(self.buf.buf.len());
+    }
+
+    /// Reads into `out`, and returns the number of bytes read, or `0` at the end of the stream
+    pub fn read(&mut self, out: &mut [u8]) -> Result<usize> {
+        let avail = self.fill_buf()?;
+        let n = avail.len().min(out.len());
+        out[..n].copy_from_slice(&avail[..n]);
+        self.consume(n);
+        Ok(n)
+    }
+
+    /// Reads a line, including its terminating newline (if any), and appends it to `line`.
+    ///
+    /// Returns the number of bytes read, which is `0` at the end of the stream.
+    /// The line is only decoded once it is complete, so a UTF-8 sequence split across reads of the stream is decoded correctly.
+    ///
+    /// ## Errors
+    /// Returns [`Error::InvalidString`] if the line is not valid UTF-8, in which case nothing is appended to `line`, but the line is consumed.
+    ///
+    /// Returns [`Error::WouldBlock`] if the stream is non-blocking and the line is incomplete. Nothing is appended to `line`, and the partial line remains buffered.
+    pub fn read_line(&mut self, line: &mut String) -> Result<usize> {
+        // The number of bytes after `pos` known not to contain a newline. This is not changed by `fill_more`, which only moves the unread input to the start of the buffer.
+        let mut scanned = 0;
+        let end = loop {
+            let unread = &self.buf.buf[self.buf.pos..];
+            if let Some(idx) = unread[scanned..].iter().position(|&b| b == b'\n') {
+                break scanned + idx + 1;
+            }
+            scanned = unread.len();
+            if self.fill_more()? == 0 {
+                break scanned;
+            }
+        };
+
+        let res = match core::str::from_utf8(&self.buf.buf[self.buf.pos..][..end]) {
+            Ok(st) => {
+                line.push_str(st);
+                Ok(end)
+            }
+            Err(_) => Err(Error::InvalidString),
+        };
+        self.consume(end);
+        res
+    }
+
+    /// Returns an iterator over the remaining lines of the stream, without their terminating newline (`\n` or `\r\n`).
+    ///
+    /// The iterator yields the errors of [`StdinLock::read_line`]. After an error (such as [`Error::WouldBlock`]), it may be resumed by calling [`Iterator::next`] again.
+    pub fn lines(self) -> Lines<'a> {
+        Lines { lock: self }
+    }
+}
+
+/// An iterator over the lines of the standard input stream. See [`StdinLock::lines`].
+#[derive(Debug)]
+pub struct Lines<'a> {
+    lock: StdinLock<'a>,
+}
+
+impl Iterator for Lines<'_> {
+    type Item = Result<String>;
+
+    fn next(&mut self) -> Option<Result<String>> {
+        let mut line = String::new();
+        match self.lock.read_line(&mut line) {
+            Ok(0) => None,
+            Ok(_) => {
+                if line.ends_with('\n') {
+                    line.pop();
+                    if line.ends_with('\r') {
+                        line.pop();
+                    }
+                }
+                Some(Ok(line))
+            }
+            Err(e) => Some(Err(e)),
+        }
+    }
+}
+
+/// Returns a handle to the standard input stream
+pub fn stdin() -> Stdin {
+    Stdin(())
+}
+
+/// Locks the buffer of the standard output stream
+pub fn stdout() -> StdoutLock<'static> {
+    StdoutLock { buf: STDOUT.lock() }
+}
+
+/// Locks the buffer of the standard error stream
+pub fn stderr() -> StderrLock<'static> {
+    StderrLock { buf: STDERR.lock() }
+}
+
+#[doc(hidden)]
+pub fn _print(args: core::fmt::Arguments) {
+    if let Err(e) = stdout().write_fmt(args) {
+        panic!("failed printing to stdout: {}", e);
+    }
+}
+
+#[doc(hidden)]
+pub fn _eprint(args: core::fmt::Arguments) {
+    if let Err(e) = stderr().write_fmt(args) {
+        panic!("failed printing to stderr: {}", e);
+    }
+}
+
+/// Prints to the standard output stream, as by `std`'s `print!`, without using `std`.
+///
+/// ## Panics
+/// Panics if writing to the standard output stream fails.
+#[macro_export]
+macro_rules! lilium_print {
+    ($($arg:tt)*) => {
+        $crate::io::stdio::_print(::core::format_args!($($arg)*))
+    };
+}
+
+/// Prints to the standard output stream, followed by a newline, as by `std`'s `println!`, without using `std`.
+///
+/// ## Panics
+/// Panics if writing to the standard output stream fails.
+#[macro_export]
+macro_rules! lilium_println {
+    () => {
+        $crate::lilium_print!("\n")
+    };
+    ($($arg:tt)*) => {
+        $crate::io::stdio::_print(::core::format_args!("{}\n", ::core::format_args!($($arg)*)))
+    };
+}
+
+/// Prints to the standard error stream, as by `std`'s `eprint!`, without using `std`.
+///
+/// ## Panics
+/// Panics if writing to the standard error stream fails.
+#[macro_export]
+macro_rules! lilium_eprint {
+    ($($arg:tt)*) => {
+        $crate::io::stdio::_eprint(::core::format_args!($($arg)*))
+    };
+}
+
+/// Prints to the standard error stream, followed by a newline, as by `std`'s `eprintln!`, without using `std`.
+///
+/// ## Panics
+/// Panics if writing to the standard error stream fails.
+#[macro_export]
+macro_rules! lilium_eprintln {
+    () => {
+        $crate::lilium_eprint!("\n")
+    };
+    ($($arg:tt)*) => {
+        $crate::io::stdio::_eprint(::core::format_args!("{}\n", ::core::format_args!($($arg)*)))
+    };
+}
@@ -0,0 +1,145 @@
+//! Control of terminal devices, for shells, REPLs, and other interactive programs.
+//!
+//! A [`Terminal`] is opened from a stream that refers to a terminal device, such as an inherited standard stream.
+//! The mode of a terminal is shared by every stream that refers to it, so a program that changes it should restore it before exiting,
+//! which [`Terminal::enter_raw_mode`] does when the returned guard is dropped.
+//!
+//! ## Example
+//! ```rust,ignore
+//! let stdin = unsafe { BorrowedHandle::from_raw(__HANDLE_IO_STDIN) };
+//! if is_tty(&stdin) {
+//!     let term = Terminal::open(&stdin)?;
+//!     let size = term.window_size()?;
+//!     let _raw = term.enter_raw_mode()?;
+//!     // Read keys one at a time
+//! }
+//! ```
+
+use core::ffi::{c_uint, c_ulong};
+
+use crate::{
+    device::{Device, DeviceCommand, Feature, FeatureOptions},
+    handle::HandleRef,
+    result::{Error, Result},
+    sys::device::tty as sys,
+};
+
+use super::IOHandle;
+
+bitflags::bitflags! {
+    /// The mode of a [`Terminal`], which determines how input and output are processed
+    #[repr(transparent)]
+    #[derive(Copy, Clone, Hash, PartialEq, Eq, Debug)]
+    pub struct TerminalMode : u32 {
+        const ECHO = sys::TERMINAL_MODE_ECHO;
+        const CANONICAL = sys::TERMINAL_MODE_CANONICAL;
+        const SIGNALS = sys::TERMINAL_MODE_SIGNALS;
+        const OUTPUT_PROCESSING = sys::TERMINAL_MODE_OUTPUT_PROCESSING;
+    }
+}
+
+/// The size of a terminal, in character cells
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+pub struct WindowSize {
+    pub rows: u32,
+    pub cols: u32,
+}
+
+/// A terminal device
+#[derive(Debug)]
+pub struct Terminal(Device);
+
+/// Checks if `hdl` refers to a terminal device
+pub fn is_tty(hdl: &HandleRef<IOHandle>) -> bool {
+    Terminal::open(hdl).is_ok()
+}
+
+impl Terminal {
+    /// Opens the terminal device that `hdl` refers to.
+    ///
+    /// ## Errors
+    /// Returns [`Error::UnsupportedOperation`] if `hdl` does not refer to a terminal.
+    pub fn open(hdl: &HandleRef<IOHandle>) -> Result<Self> {
+        let dev = Device::open_from_io(hdl)?;
+        if dev.supports(&[Feature::new(sys::TERMINAL_FEATURE, FeatureOptions::READ)])? {
+            Ok(Self(dev))
+        } else {
+            Err(Error::UnsupportedOperation)
+        }
+    }
+
+    /// The underlying device
+    pub fn device(&self) -> &Device {
+        &self.0
+    }
+
+    /// The current size of the terminal
+    pub fn window_size(&self) -> Result<WindowSize> {
+        let cmd = unsafe {
+            DeviceCommand::<(&mut c_ulong, &mut c_ulong)>::new(sys::TERMINAL_CMD_GET_WINDOW_SIZE)
+        };
+        let (mut rows, mut cols) = (0, 0);
+        self.0.invoke(&cmd, (&mut rows, &mut cols))?;
+        Ok(WindowSize {
+            rows: rows as u32,
+            cols: cols as u32,
+        })
+    }
+
+    /// The current mode of the terminal
+    pub fn mode(&self) -> Result<TerminalMode> {
+        let cmd = unsafe { DeviceCommand::<(&mut c_uint,)>::new(sys::TERMINAL_CMD_GET_MODE) };
+        let mut mode = 0;
+        self.0.invoke(&cmd, (&mut mode,))?;
+        Ok(TerminalMode::from_bits_retain(mode))
+    }
+
+    /// Sets the mode of the terminal
+    pub fn set_mode(&self, mode: TerminalMode) -> Result<()> {
+        let cmd = unsafe { DeviceCommand::<(c_uint,)>::new(sys::TERMINAL_CMD_SET_MODE) };
+        self.0.invoke(&cmd, (mode.bits(),)).map(|_| ())
+    }
+
+    /// Enables or disables echoing of input, such as to read a password
+    pub fn set_echo(&self, echo: bool) -> Result<()> {
+        let mut mode = self.mode()?;
+        mode.set(TerminalMode::ECHO, echo);
+        self.set_mode(mode)
+    }
+
+    /// Puts the terminal in raw mode, where input is read byte by byte without echo, line editing, or signal keys, and output is not processed.
+    ///
+    /// The previous mode is restored when the returned guard is dropped, or by [`RawMode::exit`].
+    pub fn enter_raw_mode(&self) -> Result<RawMode<'_>> {
+        let prev = self.mode()?;
+        self.set_mode(TerminalMode::empty())?;
+        Ok(RawMode { term: self, prev })
+    }
+}
+
+/// Keeps a [`Terminal`] in raw mode, and restores its previous mode when dropped. See [`Terminal::enter_raw_mode`].
+#[must_use]
+#[derive(Debug)]
+pub struct RawMode<'a> {
+    term: &'a Terminal,
+    prev: TerminalMode,
+}
+
+impl RawMode<'_> {
+    /// The mode the terminal is restored to
+    pub fn previous_mode(&self) -> TerminalMode {
+        self.prev
+    }
+
+    /// Restores the previous mode of the terminal, and reports whether that succeeded
+    pub fn exit(self) -> Result<()> {
+        let this = core::mem::ManuallyDrop::new(self);
+        this.term.set_mode(this.prev)
+    }
+}
+
+impl Drop for RawMode<'_> {
+    fn drop(&mut self) {
+        let _ = self.term.set_mode(self.prev);
+    }
+}
@@ -0,0 +1,143 @@
+//! Isolation namespaces, which restrict the devices, filesystem, and processes visible to the threads running in them.
+//!
+//! A namespace is created with [`NamespaceHandle::new`], configured with the `isolate_*` methods, and then either installed on the current thread with
+//!  [`HandleRef::install`], or used to spawn a process with [`Command::namespace`][crate::process::Command::namespace].
+
+use core::{ffi::c_ulong, marker::PhantomData, mem::MaybeUninit};
+
+use alloc::vec::Vec;
+
+pub use crate::sys::isolation::NamespaceHandle;
+
+use crate::{
+    handle::{AsHandle, HandleRef, OwnedHandle},
+    result::{Error, Result},
+    sys::{
+        device::DeviceHandle,
+        fs::FileHandle,
+        handle::HandlePtr,
+        isolation::{self as sys, IsolationDeviceDescriptor},
+        kstr::KStrCPtr,
+    },
+    uuid::Uuid,
+};
+
+bitflags::bitflags! {
+    /// Groups of devices that remain visible in a namespace after [`HandleRef::isolate_devices`]
+    #[repr(transparent)]
+    #[derive(Copy, Clone, Hash, PartialEq, Eq, Debug)]
+    pub struct DeviceGroup : u32 {
+        const STORAGE_DEVICES = sys::DEVICE_GROUP_STORAGE_DEVICES;
+        const VIRTUAL_FILESYSTEM = sys::DEVICE_GROUP_VIRTUAL_FILESYSTEM;
+        const ALL_FILESYSTEMS = sys::DEVICE_GROUP_ALL_FILESYSTEMS;
+        const CLOCKS = sys::DEVICE_GROUP_CLOCKS;
+        const RAND_DEVICES = sys::DEVICE_GROUP_RAND_DEVICES;
+        const STANDARD = sys::DEVICE_GROUP_STANDARD;
+        const ALL = sys::DEVICE_GROUP_ALL;
+    }
+}
+
+/// A device exposed in a namespace by [`HandleRef::isolate_devices`], in addition to the device groups that remain visible
+#[repr(transparent)]
+pub struct ExposedDevice<'a>(IsolationDeviceDescriptor, PhantomData<&'a DeviceHandle>);
+
+impl<'a> ExposedDevice<'a> {
+    /// Exposes the device `devid` in the namespace
+    pub const fn passthrough(devid: Uuid) -> Self {
+        Self(
+            IsolationDeviceDescriptor {
+                devid,
+                redirect_dev: HandlePtr::null(),
+            },
+            PhantomData,
+        )
+    }
+
+    /// Exposes `dev` in the namespace under the device id `devid`
+    pub fn redirect<H: AsHandle<'a, DeviceHandle>>(devid: Uuid, dev: H) -> Self {
+        Self(
+            IsolationDeviceDescriptor {
+                devid,
+                redirect_dev: dev.as_handle(),
+            },
+            PhantomData,
+        )
+    }
+
+    /// The device id the device is exposed as
+    pub const fn devid(&self) -> Uuid {
+        self.0.devid
+    }
+}
+
+impl core::fmt::Debug for ExposedDevice<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("ExposedDevice")
+            .field("devid", &self.0.devid)
+            .field("redirect_dev", &self.0.redirect_dev)
+            .finish()
+    }
+}
+
+impl NamespaceHandle {
+    /// Creates a new namespace, which initially isolates nothing
+    pub fn new() -> Result<OwnedHandle<Self>> {
+        let mut ns = MaybeUninit::uninit();
+        Error::from_code(unsafe { sys::CreateNamespace(ns.as_mut_ptr()) })?;
+        Ok(unsafe { OwnedHandle::take_ownership(ns.assume_init()) })
+    }
+
+    /// Opens the namespace the current thread is running in
+    pub fn current() -> Result<OwnedHandle<Self>> {
+        let mut ns = MaybeUninit::uninit();
+        Error::from_code(unsafe { sys::GetCurrentNamespace(ns.as_mut_ptr()) })?;
+        Ok(unsafe { OwnedHandle::take_ownership(ns.assume_init()) })
+    }
+}
+
+impl HandleRef<NamespaceHandle> {
+    /// Hides every device from the namespace, except for those in `groups` and those in `expose`
+    pub fn isolate_devices(&self, groups: DeviceGroup, expose: &[ExposedDevice]) -> Result<()> {
+        Error::from_code(unsafe {
+            sys::IsolateDevices(
+                self.as_raw(),
+                groups.bits(),
+                expose.as_ptr().cast(),
+                expose.len() as c_ulong,
+            )
+        })
+    }
+
+    /// Hides every mount point from the namespace, except for those in `allowed`
+    pub fn isolate_mounts<S: AsRef<str>>(&self, allowed: &[S]) -> Result<()> {
+        let allowed = allowed
+            .iter()
+            .map(|s| KStrCPtr::from_str(s.as_ref()))
+            .collect::<Vec<_>>();
+        Error::from_code(unsafe {
+            sys::IsolateMounts(self.as_raw(), allowed.as_ptr(), allowed.len())
+        })
+    }
+
+    /// Makes `base` the root of the filesystem visible in the namespace
+    pub fn isolate_filesystem<'a, H: AsHandle<'a, FileHandle>>(&self, base: H) -> Result<()> {
+        Error::from_code(unsafe { sys::IsolateFileSystem(self.as_raw(), base.as_handle()) })
+    }
+
+    /// Hides every process outside of the namespace from threads in the namespace.
+    ///
+    /// If `expose_self` is `true`, the process that installs the namespace (or spawns a process into it) remains visible.
+    pub fn isolate_processes(&self, expose_self: bool) -> Result<()> {
+        let flags = if expose_self {
+            sys::ISOLATE_PROCESSES_EXPOSE_SELF
+        } else {
+            0
+        };
+        Error::from_code(unsafe { sys::IsolateProcesses(self.as_raw(), flags) })
+    }
+
+    /// Moves the current thread into the namespace
+    pub fn install(&self) -> Result<()> {
+        Error::from_code(unsafe { sys::InstallNamespace(self.as_raw()) })
+    }
+}
@@ -0,0 +1,71 @@
+//! Kernel management, which loads and unloads kernel modules, adjusts system-wide limits, and enumerates the subsystems supported by the kernel.
+//!
+//! Operations that change the kernel require a kernel permission (see [`crate::security::kernel_perm`]),
+//!  and return [`Error::Permission`] if the current thread's security context does not hold it.
+
+use core::mem::MaybeUninit;
+
+use alloc::vec::Vec;
+
+use crate::{
+    handle::AsHandle,
+    result::{Error, Result},
+    sys::{io::IOHandle, kmgmt as sys, kstr::KStrCPtr},
+    uuid::Uuid,
+};
+
+pub use crate::sys::kmgmt::{
+    SUBSYSTEM_DEBUG, SUBSYSTEM_IO, SUBSYSTEM_PROCESS, SUBSYSTEM_THREAD,
+    SYSTEM_LIMIT_HANDLES_PER_PROCESS, SYSTEM_LIMIT_OPEN_FILES, SYSTEM_LIMIT_PROCESSES,
+    SYSTEM_LIMIT_THREADS,
+};
+
+/// Loads the kernel module contained in `file`, and returns the id the kernel assigned to it.
+///
+/// Returns [`Error::Permission`] if the current thread does not have the [`LOAD_KERNEL_MODULE`][crate::security::kernel_perm::LOAD_KERNEL_MODULE] kernel permission,
+///  or cannot read `file`.
+pub fn load_module<'a, H: AsHandle<'a, IOHandle>>(file: H) -> Result<Uuid> {
+    let mut id = MaybeUninit::uninit();
+    Error::from_code(unsafe { sys::LoadKernelModule(id.as_mut_ptr(), file.as_handle()) })?;
+    Ok(unsafe { id.assume_init() })
+}
+
+/// Unloads the kernel module `module_id`, previously returned by [`load_module`].
+///
+/// Returns [`Error::Permission`] if the current thread does not have the [`UNLOAD_KERNEL_MODULE`][crate::security::kernel_perm::UNLOAD_KERNEL_MODULE] kernel permission,
+///  [`Error::DoesNotExist`] if the module is not loaded, and [`Error::Busy`] if the module is in use.
+pub fn unload_module(module_id: Uuid) -> Result<()> {
+    Error::from_code(unsafe { sys::UnloadKernelModule(&module_id) })
+}
+
+/// Reads the current value of the system-wide limit `name`, such as [`SYSTEM_LIMIT_THREADS`].
+///
+/// Returns `Ok(None)` if the kernel does not support the limit.
+pub fn system_limit(name: &str) -> Result<Option<u64>> {
+    let mut value = 0;
+    match Error::from_code(unsafe { sys::GetSystemLimit(KStrCPtr::from_str(name), &mut value) }) {
+        Ok(()) => Ok(Some(value)),
+        Err(Error::DoesNotExist) => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+/// Sets the system-wide limit `name` to `value`.
+///
+/// Returns [`Error::Permission`] if the current thread does not have the [`SET_SYSTEM_LIMIT`][crate::security::kernel_perm::SET_SYSTEM_LIMIT] kernel permission,
+///  and [`Error::DoesNotExist`] if the kernel does not support the limit.
+pub fn set_system_limit(name: &str, value: u64) -> Result<()> {
+    Error::from_code(unsafe { sys::SetSystemLimit(KStrCPtr::from_str(name), value) })
+}
+
+pub use crate::info::Subsystem;
+
+/// Queries whether the kernel supports the subsystem `number`. See [`crate::info::supported_subsystem`].
+pub fn subsystem(number: u16) -> Result<Option<Subsystem>> {
+    crate::info::supported_subsystem(number)
+}
+
+/// Enumerates every subsystem supported by the kernel, in order of subsystem number. See [`crate::info::supported_subsystems`].
+pub fn subsystems() -> Result<Vec<Subsystem>> {
+    crate::info::supported_subsystems()
+}
@@ -1 +1,513 @@
+//! Owned and borrowed kernel strings, and helpers for reading strings returned by the kernel.
+//!
+//! Kernel strings are UTF-8 strings passed by pointer and length (see [`KStrCPtr`] and [`KStrPtr`]). They are not nul-terminated, and may contain nul bytes.
+//!
+//! [`OsStr`] and [`OsString`] hold strings that may not be valid UTF-8, such as those used by C libraries, and convert to kernel strings when they are valid UTF-8.
+//! The functions of [`fs`][crate::fs] accept any `AsRef<OsStr>` as a path, and fail with [`Error::InvalidString`][crate::result::Error::InvalidString] if it is not valid UTF-8.
 
+use core::{ops::Deref, str::Utf8Error};
+
+use alloc::{
+    borrow::Cow,
+    string::{FromUtf8Error, String},
+    vec::Vec,
+};
+
+use crate::{
+    result::{Error, Result},
+    sys::{
+        kstr::{KStrCPtr, KStrPtr},
+        result::SysResult,
+    },
+};
+
+/// A borrowed kernel string, which can be passed to the kernel as a [`KStrCPtr`]
+#[derive(Copy, Clone, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct KStr<'a>(&'a str);
+
+impl<'a> KStr<'a> {
+    pub const fn new(st: &'a str) -> Self {
+        Self(st)
+    }
+
+    /// Checks that `bytes` are valid UTF-8, and borrows them as a kernel string
+    pub const fn from_bytes(bytes: &'a [u8]) -> core::result::Result<Self, Utf8Error> {
+        match core::str::from_utf8(bytes) {
+            Ok(st) => Ok(Self(st)),
+            Err(e) => Err(e),
+        }
+    }
+
+    pub const fn as_str(&self) -> &'a str {
+        self.0
+    }
+
+    /// The kernel representation of the string, which borrows from `self`
+    pub const fn as_raw(&self) -> KStrCPtr {
+        KStrCPtr::from_str(self.0)
+    }
+
+    /// Borrows the string referred to by `raw`.
+    ///
+    /// # Safety
+    /// `raw` must refer to a valid UTF-8 string that is live and not mutated for `'a`.
+    pub unsafe fn from_raw(raw: KStrCPtr) -> Self {
+        Self(unsafe {
+            core::str::from_utf8_unchecked(core::slice::from_raw_parts(raw.str_ptr, raw.len))
+        })
+    }
+}
+
+impl Deref for KStr<'_> {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        self.0
+    }
+}
+
+impl core::fmt::Debug for KStr<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl core::fmt::Display for KStr<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl<'a> From<&'a str> for KStr<'a> {
+    fn from(st: &'a str) -> Self {
+        Self(st)
+    }
+}
+
+impl<'a> TryFrom<&'a [u8]> for KStr<'a> {
+    type Error = Utf8Error;
+
+    fn try_from(bytes: &'a [u8]) -> core::result::Result<Self, Utf8Error> {
+        Self::from_bytes(bytes)
+    }
+}
+
+impl<'a> From<&'a KString> for KStr<'a> {
+    fn from(st: &'a KString) -> Self {
+        st.as_kstr()
+    }
+}
+
+impl<'a> From<KStr<'a>> for Cow<'a, str> {
+    fn from(st: KStr<'a>) -> Self {
+        Cow::Borrowed(st.0)
+    }
+}
+
+/// An owned kernel string
+#[derive(Clone, Default, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct KString(String);
+
+impl KString {
+    pub const fn new() -> Self {
+        Self(String::new())
+    }
+
+    pub fn with_capacity(cap: usize) -> Self {
+        Self(String::with_capacity(cap))
+    }
+
+    pub fn as_kstr(&self) -> KStr<'_> {
+        KStr(&self.0)
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// The kernel representation of the string, which borrows from `self`
+    pub fn as_raw(&self) -> KStrCPtr {
+        KStrCPtr::from_str(&self.0)
+    }
+
+    pub fn into_string(self) -> String {
+        self.0
+    }
+}
+
+impl Deref for KString {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl core::fmt::Debug for KString {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl core::fmt::Display for KString {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl From<String> for KString {
+    fn from(st: String) -> Self {
+        Self(st)
+    }
+}
+
+impl From<&str> for KString {
+    fn from(st: &str) -> Self {
+        Self(st.into())
+    }
+}
+
+impl From<KStr<'_>> for KString {
+    fn from(st: KStr) -> Self {
+        Self(st.0.into())
+    }
+}
+
+impl From<Cow<'_, str>> for KString {
+    fn from(st: Cow<str>) -> Self {
+        Self(st.into_owned())
+    }
+}
+
+impl TryFrom<Vec<u8>> for KString {
+    type Error = FromUtf8Error;
+
+    fn try_from(bytes: Vec<u8>) -> core::result::Result<Self, FromUtf8Error> {
+        String::from_utf8(bytes).map(Self)
+    }
+}
+
+impl From<KString> for String {
+    fn from(st: KString) -> Self {
+        st.0
+    }
+}
+
+impl From<KString> for Cow<'_, str> {
+    fn from(st: KString) -> Self {
+        Cow::Owned(st.0)
+    }
+}
+
+/// A borrowed string of arbitrary bytes, such as a file name from a legacy filesystem, or a string from a C library, which may not be valid UTF-8.
+///
+/// The kernel only accepts UTF-8 strings, so an `OsStr` can be passed to the kernel (by [`OsStr::as_kstr`]) only if it is valid UTF-8.
+/// Converting a `str` to an `OsStr` and back is always lossless.
+#[repr(transparent)]
+#[derive(Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct OsStr([u8]);
+
+impl OsStr {
+    pub fn new<S: AsRef<OsStr> + ?Sized>(s: &S) -> &OsStr {
+        s.as_ref()
+    }
+
+    pub const fn from_bytes(bytes: &[u8]) -> &OsStr {
+        unsafe { &*(bytes as *const [u8] as *const OsStr) }
+    }
+
+    pub const fn from_str(st: &str) -> &OsStr {
+        Self::from_bytes(st.as_bytes())
+    }
+
+    pub const fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+
+    pub const fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Borrows the string as a `str`, if it is valid UTF-8
+    pub const fn to_str(&self) -> Option<&str> {
+        match core::str::from_utf8(&self.0) {
+            Ok(st) => Some(st),
+            Err(_) => None,
+        }
+    }
+
+    /// Converts the string to UTF-8, replacing invalid sequences with `U+FFFD REPLACEMENT CHARACTER`
+    pub fn to_string_lossy(&self) -> Cow<'_, str> {
+        String::from_utf8_lossy(&self.0)
+    }
+
+    /// Borrows the string as a kernel string.
+    ///
+    /// ## Errors
+    /// Returns [`Error::InvalidString`], as the kernel would, if the string is not valid UTF-8.
+    pub const fn as_kstr(&self) -> Result<KStr<'_>> {
+        match self.to_str() {
+            Some(st) => Ok(KStr(st)),
+            None => Err(Error::InvalidString),
+        }
+    }
+
+    pub fn to_os_string(&self) -> OsString {
+        OsString(self.0.to_vec())
+    }
+}
+
+impl AsRef<OsStr> for OsStr {
+    fn as_ref(&self) -> &OsStr {
+        self
+    }
+}
+
+impl AsRef<OsStr> for str {
+    fn as_ref(&self) -> &OsStr {
+        OsStr::from_str(self)
+    }
+}
+
+impl AsRef<OsStr> for String {
+    fn as_ref(&self) -> &OsStr {
+        OsStr::from_str(self)
+    }
+}
+
+impl AsRef<OsStr> for Cow<'_, str> {
+    fn as_ref(&self) -> &OsStr {
+        OsStr::from_str(self)
+    }
+}
+
+impl AsRef<OsStr> for [u8] {
+    fn as_ref(&self) -> &OsStr {
+        OsStr::from_bytes(self)
+    }
+}
+
+impl AsRef<[u8]> for OsStr {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl<'a> TryFrom<&'a OsStr> for &'a str {
+    type Error = Utf8Error;
+
+    fn try_from(st: &'a OsStr) -> core::result::Result<Self, Utf8Error> {
+        core::str::from_utf8(&st.0)
+    }
+}
+
+impl<'a> From<KStr<'a>> for &'a OsStr {
+    fn from(st: KStr<'a>) -> Self {
+        OsStr::from_str(st.0)
+    }
+}
+
+impl core::fmt::Debug for OsStr {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str("\"")?;
+        for chunk in self.0.utf8_chunks() {
+            for c in chunk.valid().chars() {
+                f.write_fmt(format_args!("{}", c.escape_debug()))?;
+            }
+            for b in chunk.invalid() {
+                f.write_fmt(format_args!("\\x{:02X}", b))?;
+            }
+        }
+        f.write_str("\"")
+    }
+}
+
+/// Writes the string, replacing invalid UTF-8 sequences with `U+FFFD REPLACEMENT CHARACTER`
+impl core::fmt::Display for OsStr {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        for chunk in self.0.utf8_chunks() {
+            f.write_str(chunk.valid())?;
+            if !chunk.invalid().is_empty() {
+                f.write_str("\u{FFFD}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// An owned string of arbitrary bytes. See [`OsStr`].
+#[derive(Clone, Default, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct OsString(Vec<u8>);
+
+impl OsString {
+    pub const fn new() -> Self {
+        Self(Vec::new())
+    }
+
+    pub const fn from_vec(bytes: Vec<u8>) -> Self {
+        Self(bytes)
+    }
+
+    pub fn into_vec(self) -> Vec<u8> {
+        self.0
+    }
+
+    pub fn as_os_str(&self) -> &OsStr {
+        OsStr::from_bytes(&self.0)
+    }
+
+    /// Converts the string to a `String`, or returns it unchanged if it is not valid UTF-8
+    pub fn into_string(self) -> core::result::Result<String, OsString> {
+        String::from_utf8(self.0).map_err(|e| Self(e.into_bytes()))
+    }
+
+    /// Converts the string to a kernel string, or returns it unchanged if it is not valid UTF-8
+    pub fn into_kstring(self) -> core::result::Result<KString, OsString> {
+        self.into_string().map(KString)
+    }
+
+    pub fn push<S: AsRef<OsStr>>(&mut self, s: S) {
+        self.0.extend_from_slice(s.as_ref().as_bytes())
+    }
+}
+
+impl Deref for OsString {
+    type Target = OsStr;
+
+    fn deref(&self) -> &OsStr {
+        self.as_os_str()
+    }
+}
+
+impl AsRef<OsStr> for OsString {
+    fn as_ref(&self) -> &OsStr {
+        self.as_os_str()
+    }
+}
+
+impl core::borrow::Borrow<OsStr> for OsString {
+    fn borrow(&self) -> &OsStr {
+        self.as_os_str()
+    }
+}
+
+impl alloc::borrow::ToOwned for OsStr {
+    type Owned = OsString;
+
+    fn to_owned(&self) -> OsString {
+        self.to_os_string()
+    }
+}
+
+impl core::fmt::Debug for OsString {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        self.as_os_str().fmt(f)
+    }
+}
+
+impl core::fmt::Display for OsString {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        self.as_os_str().fmt(f)
+    }
+}
+
+impl From<String> for OsString {
+    fn from(st: String) -> Self {
+        Self(st.into_bytes())
+    }
+}
+
+impl From<&str> for OsString {
+    fn from(st: &str) -> Self {
+        Self(st.as_bytes().to_vec())
+    }
+}
+
+impl From<KString> for OsString {
+    fn from(st: KString) -> Self {
+        Self(st.0.into_bytes())
+    }
+}
+
+impl From<Vec<u8>> for OsString {
+    fn from(bytes: Vec<u8>) -> Self {
+        Self(bytes)
+    }
+}
+
+/// Grows `buf` to hold the string length the kernel stored in `kstr` after an [`INSUFFICIENT_LENGTH`][crate::sys::result::errors::INSUFFICIENT_LENGTH] error,
+///  and points `kstr` at the grown buffer.
+///
+/// Returns `false` if `buf` was already large enough, in which case the error was not caused by `kstr`.
+pub(crate) fn grow_buffer(kstr: &mut KStrPtr, buf: &mut Vec<u8>) -> bool {
+    if kstr.len <= buf.capacity() {
+        return false;
+    }
+    buf.reserve(kstr.len);
+    kstr.str_ptr = buf.as_mut_ptr();
+    kstr.len = buf.capacity();
+    true
+}
+
+/// Reads a string from the kernel by calling `f` with a buffer of `initial_cap` bytes, which `f` passes to a system call that writes a string to it.
+///
+/// If `f` returns [`INSUFFICIENT_LENGTH`][crate::sys::result::errors::INSUFFICIENT_LENGTH], or succeeds but reports a length longer than the buffer,
+///  the buffer is grown to the length the kernel stored in the [`KStrPtr`], and `f` is called again. Any other error is returned.
+///
+/// The length `f` leaves in the [`KStrPtr`] is never trusted beyond the buffer, so `f` cannot cause undefined behaviour by reporting a wrong length.
+///
+/// ## Errors
+/// Returns the error returned by `f`, or [`Error::InvalidString`] if the string is not valid UTF-8.
+///
+/// ## Example
+/// ```rust,ignore
+/// let target = with_growing_buffer(256, |kstr| unsafe {
+///     ReadSymbolicLink(HandlePtr::null(), path.to_kstr_raw(), kstr)
+/// })?;
+/// ```
+pub fn with_growing_buffer<F: FnMut(&mut KStrPtr) -> SysResult>(
+    initial_cap: usize,
+    mut f: F,
+) -> Result<String> {
+    // The buffer is kept initialized, so that whatever `f` reports as written is initialized memory
+    let mut buf = alloc::vec![0u8; initial_cap];
+
+    let mut kstr = KStrPtr {
+        str_ptr: buf.as_mut_ptr(),
+        len: buf.len(),
+    };
+
+    loop {
+        match Error::from_code(f(&mut kstr)) {
+            Ok(()) if kstr.len <= buf.len() => break,
+            Ok(()) => {}
+            Err(Error::InsufficientLength) if kstr.len > buf.len() => {}
+            Err(e) => return Err(e),
+        }
+        buf.resize(kstr.len, 0);
+        kstr.str_ptr = buf.as_mut_ptr();
+        kstr.len = buf.len();
+    }
+
+    buf.truncate(kstr.len);
+    String::from_utf8(buf).map_err(|_| Error::InvalidString)
+}
+
+/// Reads a string from the kernel, as by [`with_growing_buffer`].
+///
+/// Like [`with_growing_buffer`], this is safe for any `f`: the length `f` reports is bounded by the buffer, and the string is checked to be valid UTF-8.
+///
+/// ## Errors
+/// Returns the error returned by `f`, or [`Error::InvalidString`] if the string is not valid UTF-8.
+///
+/// ## Example
+/// ```rust,ignore
+/// let label = read_kstr(|kstr| unsafe { GetDeviceLabel(hdl, kstr) })?;
+/// ```
+pub fn read_kstr<F: FnMut(&mut KStrPtr) -> SysResult>(f: F) -> Result<KString> {
+    with_growing_buffer(32, f).map(KString)
+}
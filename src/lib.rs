@@ -1,13 +1,25 @@
 #![cfg_attr(not(feature = "std"), no_std)]
-#![feature(thread_local, never_type)]
+#![feature(thread_local, never_type, c_variadic)]
 //! High and Low-level bindings to the PhantomOS kernel interfaces
 
 extern crate alloc;
 
+#[cfg(feature = "api")]
+#[macro_use]
+mod trace;
+
 pub mod sys;
 
 pub mod uuid;
 
+#[cfg(feature = "api")]
+pub mod batch;
+#[cfg(feature = "api")]
+pub mod debug;
+#[cfg(feature = "api")]
+pub mod device;
+#[cfg(feature = "api")]
+pub mod except;
 #[cfg(feature = "api")]
 pub mod fs;
 #[cfg(feature = "api")]
@@ -15,8 +27,16 @@ pub mod handle;
 #[cfg(feature = "api")]
 pub mod io;
 #[cfg(feature = "api")]
+pub mod isolation;
+#[cfg(feature = "api")]
+pub mod kmgmt;
+#[cfg(feature = "api")]
 pub mod kstr;
 #[cfg(feature = "api")]
+pub mod mem;
+#[cfg(feature = "api")]
+pub mod net;
+#[cfg(feature = "api")]
 pub mod os;
 #[cfg(feature = "api")]
 pub mod process;
@@ -27,6 +47,9 @@ pub mod result;
 #[cfg(feature = "api")]
 pub mod security;
 
+#[cfg(feature = "api")]
+pub mod sync;
+
 #[cfg(feature = "api")]
 pub mod time;
 
@@ -35,3 +58,6 @@ pub mod thread;
 
 #[cfg(feature = "api")]
 pub mod info;
+
+#[cfg(all(feature = "panic-handler", not(feature = "std")))]
+pub mod panic;
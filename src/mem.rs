@@ -0,0 +1,336 @@
+//! Memory mappings, lazily populated memory regions, and secure memory for secrets.
+
+use core::{
+    ffi::c_void,
+    sync::atomic::{AtomicU8, Ordering},
+};
+
+use alloc::{boxed::Box, sync::Arc};
+
+use crate::{
+    except::{self, ExceptionKind, HookAction, HookHandle},
+    info,
+    result::{Error, Result},
+    sys::{
+        kstr::KCSlice,
+        process::{
+            ChangeMappingAttributes, CreateMapping, RemoveMapping, MAP_ATTR_EXEC,
+            MAP_ATTR_PROC_PRIVATE, MAP_ATTR_READ, MAP_ATTR_RESERVE, MAP_ATTR_THREAD_PRIVATE,
+            MAP_ATTR_WRITE, MAP_KIND_ENCRYPTED, MAP_KIND_NORMAL, MAP_KIND_SECURE,
+        },
+        thread::YieldThread,
+    },
+};
+
+bitflags::bitflags! {
+    /// The attributes of a memory mapping, made of the `MAP_ATTR_*` constants
+    #[repr(transparent)]
+    #[derive(Copy, Clone, Hash, PartialEq, Eq, Debug)]
+    pub struct MapAttrs : u32 {
+        const READ = MAP_ATTR_READ;
+        const WRITE = MAP_ATTR_WRITE;
+        const EXEC = MAP_ATTR_EXEC;
+        const THREAD_PRIVATE = MAP_ATTR_THREAD_PRIVATE;
+        const PROC_PRIVATE = MAP_ATTR_PROC_PRIVATE;
+        /// Reserves the region without allocating memory for it. Accesses to the region fault until its attributes are changed.
+        const RESERVE = MAP_ATTR_RESERVE;
+    }
+}
+
+const PAGE_ABSENT: u8 = 0;
+const PAGE_POPULATING: u8 = 1;
+const PAGE_PRESENT: u8 = 2;
+
+type PopulateFn = dyn Fn(usize, &mut [u8]) + Send + Sync;
+
+struct Inner {
+    base: usize,
+    page_size: usize,
+    attrs: MapAttrs,
+    pages: Box<[AtomicU8]>,
+    populate: Box<PopulateFn>,
+}
+
+impl Inner {
+    fn page_of(&self, addr: usize) -> Option<usize> {
+        let idx = addr.checked_sub(self.base)? / self.page_size;
+        (idx < self.pages.len()).then_some(idx)
+    }
+
+    /// Makes page `idx` accessible and fills it, or waits for another thread that is doing so.
+    ///
+    /// Returns `false` if the page was already present, in which case a fault on it was not caused by the page being absent.
+    fn populate_page(&self, idx: usize) -> Result<bool> {
+        let state = &self.pages[idx];
+        let mut waited = false;
+        loop {
+            match state.compare_exchange(
+                PAGE_ABSENT,
+                PAGE_POPULATING,
+                Ordering::Acquire,
+                Ordering::Acquire,
+            ) {
+                Ok(_) => break,
+                Err(PAGE_PRESENT) => return Ok(waited),
+                Err(_) => {
+                    waited = true;
+                    unsafe { YieldThread() }
+                }
+            }
+        }
+
+        let page = (self.base + idx * self.page_size) as *mut c_void;
+        let res = (|| {
+            Error::from_code(unsafe {
+                ChangeMappingAttributes(page, 1, (MapAttrs::READ | MapAttrs::WRITE).bits())
+            })?;
+            (self.populate)(idx, unsafe {
+                core::slice::from_raw_parts_mut(page.cast(), self.page_size)
+            });
+            if self.attrs != MapAttrs::READ | MapAttrs::WRITE {
+                Error::from_code(unsafe { ChangeMappingAttributes(page, 1, self.attrs.bits()) })?;
+            }
+            Ok(())
+        })();
+
+        state.store(
+            if res.is_ok() {
+                PAGE_PRESENT
+            } else {
+                PAGE_ABSENT
+            },
+            Ordering::Release,
+        );
+        res.map(|()| true)
+    }
+}
+
+/// A reserved region of memory whose pages are populated by a callback when they are first accessed.
+///
+/// Accessing an absent page raises a memory access violation, which a hook registered by the region handles by mapping the page,
+///  calling the callback to fill it, and resuming the thread. A page is populated at most once, even if several threads access it at the same time.
+///
+/// Only threads that have installed the exception handler of [`crate::except`] can fault pages in. Other threads that access an absent page are terminated.
+/// Threads can call [`LazyRegion::populate`] or [`LazyRegion::page`] to populate pages explicitly instead.
+pub struct LazyRegion {
+    inner: Arc<Inner>,
+    /// Taken in `drop`, so that the hook is unregistered before the region is unmapped
+    hook: Option<HookHandle>,
+}
+
+impl core::fmt::Debug for LazyRegion {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("LazyRegion")
+            .field("base", &(self.inner.base as *mut u8))
+            .field("page_count", &self.inner.pages.len())
+            .finish_non_exhaustive()
+    }
+}
+
+impl LazyRegion {
+    /// Reserves `page_count` pages, which are populated by `populate(page_index, page)` on first access.
+    /// Each page is zeroed before `populate` is called.
+    ///
+    /// If `writable` is `false`, the pages are made read-only after they are populated.
+    ///
+    /// The calling thread installs the exception handler, as by [`except::install_handler`].
+    pub fn new<F: Fn(usize, &mut [u8]) + Send + Sync + 'static>(
+        page_count: usize,
+        writable: bool,
+        populate: F,
+    ) -> Result<Self> {
+        let mut base = core::ptr::null_mut();
+        Error::from_code(unsafe {
+            CreateMapping(
+                &mut base,
+                page_count as _,
+                MapAttrs::RESERVE.bits(),
+                MAP_KIND_NORMAL,
+                &KCSlice::empty(),
+            )
+        })?;
+
+        let inner = Arc::new(Inner {
+            base: base as usize,
+            page_size: info::page_size(),
+            attrs: if writable {
+                MapAttrs::READ | MapAttrs::WRITE
+            } else {
+                MapAttrs::READ
+            },
+            pages: (0..page_count)
+                .map(|_| AtomicU8::new(PAGE_ABSENT))
+                .collect(),
+            populate: Box::new(populate),
+        });
+
+        let hook_inner = inner.clone();
+        let hook = except::register_hook(move |except, _| {
+            if except.kind() != ExceptionKind::MemoryAccessViolation {
+                return HookAction::Continue;
+            }
+            match hook_inner.page_of(except.info() as usize) {
+                // A fault on a page that was already present, such as a write to a read-only page, is not handled by the region
+                Some(idx) if matches!(hook_inner.populate_page(idx), Ok(true)) => {
+                    HookAction::Resume
+                }
+                _ => HookAction::Continue,
+            }
+        });
+        let hook = match hook {
+            Ok(hook) => hook,
+            Err(e) => {
+                unsafe { RemoveMapping(base, page_count as _) };
+                return Err(e);
+            }
+        };
+
+        Ok(Self {
+            inner,
+            hook: Some(hook),
+        })
+    }
+
+    /// The base address of the region
+    pub fn as_ptr(&self) -> *mut u8 {
+        self.inner.base as *mut u8
+    }
+
+    /// The number of pages in the region
+    pub fn page_count(&self) -> usize {
+        self.inner.pages.len()
+    }
+
+    /// The length of the region in bytes
+    pub fn len(&self) -> usize {
+        self.page_count() * self.inner.page_size
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.inner.pages.is_empty()
+    }
+
+    /// Whether page `idx` has been populated
+    ///
+    /// ## Panics
+    /// Panics if `idx` is not less than [`LazyRegion::page_count`].
+    pub fn is_populated(&self, idx: usize) -> bool {
+        self.inner.pages[idx].load(Ordering::Acquire) == PAGE_PRESENT
+    }
+
+    /// Populates page `idx` if it has not been populated, without faulting.
+    ///
+    /// ## Panics
+    /// Panics if `idx` is not less than [`LazyRegion::page_count`].
+    pub fn populate(&self, idx: usize) -> Result<()> {
+        self.inner.populate_page(idx).map(|_| ())
+    }
+
+    /// Populates page `idx` if necessary, and returns its contents.
+    ///
+    /// ## Panics
+    /// Panics if `idx` is not less than [`LazyRegion::page_count`].
+    pub fn page(&self, idx: usize) -> Result<&[u8]> {
+        self.populate(idx)?;
+        let page_size = self.inner.page_size;
+        Ok(unsafe { core::slice::from_raw_parts(self.as_ptr().add(idx * page_size), page_size) })
+    }
+}
+
+impl Drop for LazyRegion {
+    fn drop(&mut self) {
+        // Otherwise, the hook could handle faults for a new mapping placed at the same address
+        drop(self.hook.take());
+        unsafe {
+            RemoveMapping(self.inner.base as *mut c_void, self.page_count() as _);
+        }
+    }
+}
+
+/// A buffer of secret data, such as key material, in a `MAP_KIND_SECURE` or `MAP_KIND_ENCRYPTED` mapping.
+///
+/// The memory of a [`SecureBuffer::new`] buffer is locked in physical memory, as though by `mlock` on other systems - it is never written to swap or other persistent storage.
+/// Unlike `mlock`, this is a property of the mapping rather than a request that can fail afterwards, and the buffer counts against the memory resource limits of the process for its whole lifetime.
+/// The memory of a [`SecureBuffer::new_encrypted`] buffer may be paged out, but only in encrypted form.
+/// Neither kind of mapping is written to core dumps, such as by [`crate::debug::coredump::write`].
+///
+/// The contents are only accessible through [`SecureBuffer::expose_secret`], so that the secret is not accidentally copied or printed. The buffer is zeroed before its memory is unmapped when it is dropped.
+pub struct SecureBuffer {
+    base: *mut u8,
+    len: usize,
+    page_count: usize,
+}
+
+// SAFETY: The buffer owns its mapping, and its contents are only accessed through `&mut self`
+unsafe impl Send for SecureBuffer {}
+unsafe impl Sync for SecureBuffer {}
+
+impl core::fmt::Debug for SecureBuffer {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("SecureBuffer")
+            .field("len", &self.len)
+            .finish_non_exhaustive()
+    }
+}
+
+impl SecureBuffer {
+    fn with_kind(len: usize, kind: u32) -> Result<Self> {
+        let page_count = len.div_ceil(info::page_size()).max(1);
+        let mut base = core::ptr::null_mut();
+        Error::from_code(unsafe {
+            CreateMapping(
+                &mut base,
+                page_count as _,
+                (MapAttrs::READ | MapAttrs::WRITE | MapAttrs::PROC_PRIVATE).bits(),
+                kind,
+                &KCSlice::empty(),
+            )
+        })?;
+        Ok(Self {
+            base: base.cast(),
+            len,
+            page_count,
+        })
+    }
+
+    /// Allocates a zeroed buffer of `len` bytes in a `MAP_KIND_SECURE` mapping, which is never paged out.
+    ///
+    /// ## Errors
+    /// Returns [`Error::InsufficientMemory`] if the memory cannot be locked in physical memory, such as because the resource limits of the process are exceeded.
+    pub fn new(len: usize) -> Result<Self> {
+        Self::with_kind(len, MAP_KIND_SECURE)
+    }
+
+    /// Allocates a zeroed buffer of `len` bytes in a `MAP_KIND_ENCRYPTED` mapping, which is encrypted when it is paged out.
+    pub fn new_encrypted(len: usize) -> Result<Self> {
+        Self::with_kind(len, MAP_KIND_ENCRYPTED)
+    }
+
+    /// The length of the buffer in bytes
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Calls `f` with the contents of the buffer, and returns its result.
+    ///
+    /// `f` should not copy the secret out of the buffer, unless it is copied into another [`SecureBuffer`].
+    pub fn expose_secret<R, F: FnOnce(&mut [u8]) -> R>(&mut self, f: F) -> R {
+        f(unsafe { core::slice::from_raw_parts_mut(self.base, self.len) })
+    }
+}
+
+impl Drop for SecureBuffer {
+    fn drop(&mut self) {
+        for i in 0..self.page_count * info::page_size() {
+            // Volatile, so that the writes are not removed because the memory is never read again
+            unsafe { self.base.add(i).write_volatile(0) };
+        }
+        unsafe {
+            RemoveMapping(self.base.cast(), self.page_count as _);
+        }
+    }
+}
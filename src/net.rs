@@ -0,0 +1,793 @@
+//! Safe interfaces for sending data over sockets, and resolving host names
+
+use core::{
+    ffi::{c_ulong, c_void},
+    mem::{size_of, MaybeUninit},
+    ops::{Bound, Deref, RangeBounds},
+};
+
+pub use core::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6};
+
+use alloc::{string::String, vec::Vec};
+use bytemuck::Zeroable;
+
+use crate::{
+    handle::{AsHandle, BorrowedHandle, OwnedHandle},
+    result::{Error, Result, ResultExt},
+    sys::{
+        fs::FileHandle,
+        handle::HandlePtr,
+        io::{IOCopy, IOCopyFull, IOHandle, IOSeekFar, SEEK_FROM_START},
+        ipc::ConnectToNamed,
+        kstr::{KCSlice, KSlice, KStrCPtr},
+        socket::{
+            self as sys, MulticastMembership, ResolverRecord, ResolverRequest, ResolverResponse,
+            SocketAddress, SocketCreateOption, SocketCreateOptionSharedAddress, SocketHandle,
+            ADDR_FAMILY_IPV4, ADDR_FAMILY_IPV6, RESOLVER_CHANNEL,
+        },
+    },
+    time::Duration,
+};
+
+/// The largest number of bytes copied by a single `IOCopy` call, which is the largest length representable as a positive [`SysResult`][crate::sys::result::SysResult]
+const MAX_COPY: u64 = i64::MAX as u64;
+
+/// Sends the bytes in `range` of `file` to `socket`, without copying them through the current process, like `sendfile`.
+///
+/// If `range` has a start bound, `file` is seeked to it first. Otherwise, the data is sent from the current position of `file`.
+/// If `range` has no end bound, the data is sent until the end of `file`.
+///
+/// Returns the number of bytes sent, which is less than the length of `range` if the end of `file` is reached first.
+///
+/// ## Errors
+/// If either handle is non-blocking or asynchronous, and the copy cannot continue immediately, the number of bytes sent so far is returned.
+/// If no bytes were sent, returns [`Error::WouldBlock`] or [`Error::Pending`] instead.
+///
+/// If any other error occurs, it is returned, and the number of bytes that were sent is unspecified.
+pub fn send_file<
+    'a,
+    'b,
+    S: AsHandle<'a, SocketHandle>,
+    F: AsHandle<'b, FileHandle>,
+    R: RangeBounds<u64>,
+>(
+    socket: S,
+    file: F,
+    range: R,
+) -> Result<u64> {
+    let dest: HandlePtr<IOHandle> = socket.as_handle().cast();
+    let src: HandlePtr<IOHandle> = file.as_handle().cast();
+
+    let start = match range.start_bound() {
+        Bound::Included(&start) => Some(start),
+        Bound::Excluded(&start) => Some(start.checked_add(1).ok_or(Error::InvalidOperation)?),
+        Bound::Unbounded => None,
+    };
+    let len = match range.end_bound() {
+        Bound::Included(&end) => Some(end.saturating_add(1).saturating_sub(start.unwrap_or(0))),
+        Bound::Excluded(&end) => Some(end.saturating_sub(start.unwrap_or(0))),
+        Bound::Unbounded => None,
+    };
+
+    if let Some(start) = start {
+        unsafe { syscall!(IOSeekFar(src, SEEK_FROM_START, start as i128)) }.into_size()?;
+    }
+
+    let mut sent = 0u64;
+
+    loop {
+        let res = match len {
+            Some(len) if sent == len => break,
+            Some(len) => unsafe { syscall!(IOCopy(src, dest, (len - sent).min(MAX_COPY) as _)) },
+            None => unsafe { syscall!(IOCopyFull(src, dest)) },
+        };
+
+        match res.into_size() {
+            Ok(0) => break,
+            Ok(n) => sent += n,
+            Err(Error::Interrupted) => continue,
+            Err(Error::WouldBlock | Error::Pending) if sent != 0 => break,
+            Err(e) => return Err(e),
+        }
+    }
+
+    Ok(sent)
+}
+
+fn resolve(host: &str, port: u16) -> Result<LookupHost> {
+    let mut hdl = MaybeUninit::uninit();
+    Error::from_code(unsafe {
+        syscall!(ConnectToNamed(
+            hdl.as_mut_ptr(),
+            HandlePtr::null(),
+            KStrCPtr::from_str(RESOLVER_CHANNEL),
+        ))
+    })?;
+    let conn = unsafe { OwnedHandle::<IOHandle>::take_ownership(hdl.assume_init().cast()) };
+
+    let req = ResolverRequest {
+        name_len: host.len() as u64,
+    };
+    conn.write_all(bytemuck::bytes_of(&req))?;
+    conn.write_all(host.as_bytes())?;
+
+    let mut resp = ResolverResponse::zeroed();
+    conn.read_exact(bytemuck::bytes_of_mut(&mut resp))?;
+    Error::from_code(resp.status)?;
+
+    let mut addrs = Vec::new();
+    for _ in 0..resp.count {
+        let mut rec = ResolverRecord::zeroed();
+        conn.read_exact(bytemuck::bytes_of_mut(&mut rec))?;
+        addrs.push(match rec.family {
+            ADDR_FAMILY_IPV4 => {
+                let [a, b, c, d, ..] = rec.addr;
+                SocketAddr::V4(SocketAddrV4::new(Ipv4Addr::new(a, b, c, d), port))
+            }
+            ADDR_FAMILY_IPV6 => SocketAddr::V6(SocketAddrV6::new(
+                Ipv6Addr::from(rec.addr),
+                port,
+                rec.flowinfo,
+                rec.scope_id,
+            )),
+            _ => continue,
+        });
+    }
+
+    Ok(LookupHost(addrs.into_iter()))
+}
+
+/// An iterator over the addresses a host name resolves to, returned by [`lookup_host`]
+#[derive(Clone, Debug)]
+pub struct LookupHost(alloc::vec::IntoIter<SocketAddr>);
+
+impl Iterator for LookupHost {
+    type Item = SocketAddr;
+
+    fn next(&mut self) -> Option<SocketAddr> {
+        self.0.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl ExactSizeIterator for LookupHost {}
+
+/// Resolves `name`, of the form `host:port`, to the addresses of `host` with the port `port`, using the system resolver service.
+///
+/// If `host` is an IP address, it is returned without contacting the resolver.
+///
+/// ## Errors
+/// Returns [`Error::InvalidString`] if `name` does not contain a valid port.
+///
+/// Returns [`Error::DoesNotExist`] if `host` has no addresses, or any error reported by the resolver service.
+pub fn lookup_host(name: &str) -> Result<LookupHost> {
+    if let Ok(addr) = name.parse::<SocketAddr>() {
+        return Ok(LookupHost(alloc::vec![addr].into_iter()));
+    }
+    let (host, port) = name.rsplit_once(':').ok_or(Error::InvalidString)?;
+    let port = port.parse().map_err(|_| Error::InvalidString)?;
+    resolve(host, port)
+}
+
+/// Types that can be converted to one or more socket addresses, resolving host names if necessary, similar to `std::net::ToSocketAddrs`
+pub trait ToSocketAddrs {
+    /// The iterator over the addresses
+    type Iter: Iterator<Item = SocketAddr>;
+
+    /// Converts `self` to the addresses it designates
+    fn to_socket_addrs(&self) -> Result<Self::Iter>;
+}
+
+impl ToSocketAddrs for SocketAddr {
+    type Iter = core::option::IntoIter<SocketAddr>;
+
+    fn to_socket_addrs(&self) -> Result<Self::Iter> {
+        Ok(Some(*self).into_iter())
+    }
+}
+
+impl ToSocketAddrs for SocketAddrV4 {
+    type Iter = core::option::IntoIter<SocketAddr>;
+
+    fn to_socket_addrs(&self) -> Result<Self::Iter> {
+        SocketAddr::V4(*self).to_socket_addrs()
+    }
+}
+
+impl ToSocketAddrs for SocketAddrV6 {
+    type Iter = core::option::IntoIter<SocketAddr>;
+
+    fn to_socket_addrs(&self) -> Result<Self::Iter> {
+        SocketAddr::V6(*self).to_socket_addrs()
+    }
+}
+
+macro_rules! impl_to_socket_addrs_ip {
+    ($($ty:ty),*) => {
+        $(
+            impl ToSocketAddrs for ($ty, u16) {
+                type Iter = core::option::IntoIter<SocketAddr>;
+
+                fn to_socket_addrs(&self) -> Result<Self::Iter> {
+                    SocketAddr::new(self.0.into(), self.1).to_socket_addrs()
+                }
+            }
+        )*
+    };
+}
+
+impl_to_socket_addrs_ip!(IpAddr, Ipv4Addr, Ipv6Addr);
+
+impl ToSocketAddrs for (&str, u16) {
+    type Iter = LookupHost;
+
+    fn to_socket_addrs(&self) -> Result<LookupHost> {
+        match self.0.parse::<IpAddr>() {
+            Ok(addr) => Ok(LookupHost(
+                alloc::vec![SocketAddr::new(addr, self.1)].into_iter(),
+            )),
+            Err(_) => resolve(self.0, self.1),
+        }
+    }
+}
+
+impl ToSocketAddrs for str {
+    type Iter = LookupHost;
+
+    fn to_socket_addrs(&self) -> Result<LookupHost> {
+        lookup_host(self)
+    }
+}
+
+impl ToSocketAddrs for String {
+    type Iter = LookupHost;
+
+    fn to_socket_addrs(&self) -> Result<LookupHost> {
+        lookup_host(self)
+    }
+}
+
+impl<'a> ToSocketAddrs for &'a [SocketAddr] {
+    type Iter = core::iter::Copied<core::slice::Iter<'a, SocketAddr>>;
+
+    fn to_socket_addrs(&self) -> Result<Self::Iter> {
+        Ok(self.iter().copied())
+    }
+}
+
+impl<T: ToSocketAddrs + ?Sized> ToSocketAddrs for &T {
+    type Iter = T::Iter;
+
+    fn to_socket_addrs(&self) -> Result<T::Iter> {
+        (**self).to_socket_addrs()
+    }
+}
+
+/// An open socket
+#[derive(Debug, Hash, PartialEq, Eq)]
+pub struct Socket(OwnedHandle<SocketHandle>);
+
+impl Socket {
+    /// # Safety
+    /// `hdl` must be a valid socket handle that is not owned by anything else
+    pub const unsafe fn from_handle(hdl: HandlePtr<SocketHandle>) -> Self {
+        Self(OwnedHandle::take_ownership(hdl))
+    }
+
+    pub fn as_raw(&self) -> HandlePtr<SocketHandle> {
+        self.0.as_raw()
+    }
+
+    /// Borrows the socket as an [`IOHandle`], to read from and write to it
+    pub fn as_io_handle(&self) -> BorrowedHandle<'_, IOHandle> {
+        unsafe { BorrowedHandle::from_raw(self.0.as_raw().cast()) }
+    }
+
+    fn create(family: u32, kind: u32, options: &[SocketCreateOption]) -> Result<Self> {
+        let mut hdl = MaybeUninit::uninit();
+        Error::from_code(unsafe {
+            syscall!(sys::CreateSocket(
+                hdl.as_mut_ptr(),
+                family,
+                kind,
+                &KCSlice::from_slice(options)
+            ))
+        })?;
+        Ok(unsafe { Self::from_handle(hdl.assume_init()) })
+    }
+
+    fn set_option<T: Copy>(&self, opt: u32, val: T) -> Result<()> {
+        Error::from_code(unsafe {
+            syscall!(sys::SetSocketOption(
+                self.0.as_raw(),
+                opt,
+                (&val as *const T).cast::<c_void>(),
+                size_of::<T>() as c_ulong,
+            ))
+        })
+    }
+
+    fn option<T: Zeroable>(&self, opt: u32) -> Result<T> {
+        let mut val = T::zeroed();
+        Error::from_code(unsafe {
+            syscall!(sys::GetSocketOption(
+                self.0.as_raw(),
+                opt,
+                (&mut val as *mut T).cast::<c_void>(),
+                size_of::<T>() as c_ulong,
+            ))
+        })?;
+        Ok(val)
+    }
+
+    /// Sends small writes immediately, rather than coalescing them into fewer packets
+    pub fn set_nodelay(&self, nodelay: bool) -> Result<()> {
+        self.set_option(sys::SOCKOPT_NODELAY, nodelay as u32)
+    }
+
+    pub fn nodelay(&self) -> Result<bool> {
+        self.option::<u32>(sys::SOCKOPT_NODELAY).map(|v| v != 0)
+    }
+
+    /// Sends keepalive probes while the connection is idle
+    pub fn set_keepalive(&self, keepalive: bool) -> Result<()> {
+        self.set_option(sys::SOCKOPT_KEEPALIVE, keepalive as u32)
+    }
+
+    pub fn keepalive(&self) -> Result<bool> {
+        self.option::<u32>(sys::SOCKOPT_KEEPALIVE).map(|v| v != 0)
+    }
+
+    /// Sets the size in bytes of the receive buffer of the socket
+    pub fn set_recv_buffer_size(&self, size: usize) -> Result<()> {
+        self.set_option(sys::SOCKOPT_RECV_BUFFER_SIZE, size as c_ulong)
+    }
+
+    pub fn recv_buffer_size(&self) -> Result<usize> {
+        self.option::<c_ulong>(sys::SOCKOPT_RECV_BUFFER_SIZE)
+            .map(|v| v as usize)
+    }
+
+    /// Sets the size in bytes of the send buffer of the socket
+    pub fn set_send_buffer_size(&self, size: usize) -> Result<()> {
+        self.set_option(sys::SOCKOPT_SEND_BUFFER_SIZE, size as c_ulong)
+    }
+
+    pub fn send_buffer_size(&self) -> Result<usize> {
+        self.option::<c_ulong>(sys::SOCKOPT_SEND_BUFFER_SIZE)
+            .map(|v| v as usize)
+    }
+
+    fn set_timeout(&self, opt: u32, timeout: Option<Duration>) -> Result<()> {
+        match timeout {
+            Some(dur) if dur == Duration::ZERO || dur.is_negative() => Err(Error::InvalidOption),
+            dur => self.set_option(opt, dur.unwrap_or(Duration::ZERO).into_system()),
+        }
+    }
+
+    fn timeout(&self, opt: u32) -> Result<Option<Duration>> {
+        let dur = Duration::from_system(self.option(opt)?);
+        Ok(Some(dur).filter(|dur| *dur != Duration::ZERO))
+    }
+
+    /// Sets the timeout of blocking reads from the socket, or clears it if `timeout` is `None`.
+    ///
+    /// The timeout applies only to this socket, instead of the blocking timeout of the thread.
+    ///
+    /// ## Errors
+    /// Returns [`Error::InvalidOption`] if `timeout` is zero or negative.
+    pub fn set_read_timeout(&self, timeout: Option<Duration>) -> Result<()> {
+        self.set_timeout(sys::SOCKOPT_READ_TIMEOUT, timeout)
+    }
+
+    pub fn read_timeout(&self) -> Result<Option<Duration>> {
+        self.timeout(sys::SOCKOPT_READ_TIMEOUT)
+    }
+
+    /// Sets the timeout of blocking writes to the socket, or clears it if `timeout` is `None`.
+    ///
+    /// The timeout applies only to this socket, instead of the blocking timeout of the thread.
+    ///
+    /// ## Errors
+    /// Returns [`Error::InvalidOption`] if `timeout` is zero or negative.
+    pub fn set_write_timeout(&self, timeout: Option<Duration>) -> Result<()> {
+        self.set_timeout(sys::SOCKOPT_WRITE_TIMEOUT, timeout)
+    }
+
+    pub fn write_timeout(&self) -> Result<Option<Duration>> {
+        self.timeout(sys::SOCKOPT_WRITE_TIMEOUT)
+    }
+
+    /// The address of the remote end of the socket
+    pub fn peer_addr(&self) -> Result<SocketAddr> {
+        let mut addr = SocketAddress::zeroed();
+        Error::from_code(unsafe {
+            syscall!(sys::GetSocketPeerAddress(self.0.as_raw(), &mut addr))
+        })?;
+        decode_addr(&addr)
+    }
+
+    /// The address the socket is bound to
+    pub fn local_addr(&self) -> Result<SocketAddr> {
+        let mut addr = SocketAddress::zeroed();
+        Error::from_code(unsafe {
+            syscall!(sys::GetSocketLocalAddress(self.0.as_raw(), &mut addr))
+        })?;
+        decode_addr(&addr)
+    }
+}
+
+fn encode_addr(addr: &SocketAddr) -> SocketAddress {
+    let mut raw = SocketAddress::zeroed();
+    raw.port = addr.port();
+    match addr {
+        SocketAddr::V4(addr) => {
+            raw.family = ADDR_FAMILY_IPV4;
+            raw.addr[..4].copy_from_slice(&addr.ip().octets());
+        }
+        SocketAddr::V6(addr) => {
+            raw.family = ADDR_FAMILY_IPV6;
+            raw.addr = addr.ip().octets();
+            raw.flowinfo = addr.flowinfo();
+            raw.scope_id = addr.scope_id();
+        }
+    }
+    raw
+}
+
+fn decode_addr(addr: &SocketAddress) -> Result<SocketAddr> {
+    match addr.family {
+        ADDR_FAMILY_IPV4 => {
+            let [a, b, c, d, ..] = addr.addr;
+            Ok(SocketAddr::V4(SocketAddrV4::new(
+                Ipv4Addr::new(a, b, c, d),
+                addr.port,
+            )))
+        }
+        ADDR_FAMILY_IPV6 => Ok(SocketAddr::V6(SocketAddrV6::new(
+            Ipv6Addr::from(addr.addr),
+            addr.port,
+            addr.flowinfo,
+            addr.scope_id,
+        ))),
+        _ => Err(Error::InvalidState),
+    }
+}
+
+unsafe impl<'a> AsHandle<'a, SocketHandle> for &'a Socket {
+    fn as_handle(&self) -> HandlePtr<SocketHandle> {
+        self.0.as_raw()
+    }
+}
+
+/// A datagram socket, for sending and receiving individual messages, such as over UDP
+#[derive(Debug, Hash, PartialEq, Eq)]
+pub struct UdpSocket(Socket);
+
+impl UdpSocket {
+    /// Creates a datagram socket bound to `addr`.
+    ///
+    /// If `addr` designates multiple addresses, binds to the first one that succeeds, or returns the error of the last one.
+    pub fn bind<A: ToSocketAddrs + ?Sized>(addr: &A) -> Result<Self> {
+        Self::bind_with(addr, &[])
+    }
+
+    /// Creates a datagram socket bound to `addr`, which other sockets created by `bind_shared` can be bound to as well, as by [`SocketCreateOptionSharedAddress`].
+    ///
+    /// Multicast and broadcast datagrams are delivered to every socket bound to the address, so this allows several programs to receive on the well-known port of a discovery protocol, such as mDNS or SSDP.
+    pub fn bind_shared<A: ToSocketAddrs + ?Sized>(addr: &A) -> Result<Self> {
+        Self::bind_with(
+            addr,
+            &[SocketCreateOption {
+                shared_address: SocketCreateOptionSharedAddress::NULL,
+            }],
+        )
+    }
+
+    fn bind_with<A: ToSocketAddrs + ?Sized>(
+        addr: &A,
+        options: &[SocketCreateOption],
+    ) -> Result<Self> {
+        let mut last_err = Error::DoesNotExist;
+        for addr in addr.to_socket_addrs()? {
+            match Self::bind_one(&addr, options) {
+                Ok(sock) => return Ok(sock),
+                Err(e) => last_err = e,
+            }
+        }
+        Err(last_err)
+    }
+
+    fn bind_one(addr: &SocketAddr, options: &[SocketCreateOption]) -> Result<Self> {
+        let addr = encode_addr(addr);
+        let sock = Socket::create(addr.family, sys::SOCK_DATAGRAM, options)?;
+        Error::from_code(unsafe { syscall!(sys::BindSocket(sock.as_raw(), &addr)) })?;
+        Ok(Self(sock))
+    }
+
+    /// Sends `buf` as a single datagram to the first address designated by `addr`, and returns the number of bytes sent
+    pub fn send_to<A: ToSocketAddrs + ?Sized>(&self, buf: &[u8], addr: &A) -> Result<usize> {
+        self.send_to_vectored(&[buf], addr)
+    }
+
+    /// Sends the buffers in `bufs`, in order, as a single datagram to the first address designated by `addr`, and returns the number of bytes sent
+    pub fn send_to_vectored<A: ToSocketAddrs + ?Sized>(
+        &self,
+        bufs: &[&[u8]],
+        addr: &A,
+    ) -> Result<usize> {
+        let addr = addr.to_socket_addrs()?.next().ok_or(Error::DoesNotExist)?;
+        let addr = encode_addr(&addr);
+        let bufs = bufs
+            .iter()
+            .map(|buf| KCSlice::from_slice(buf))
+            .collect::<Vec<_>>();
+        unsafe {
+            syscall!(sys::SocketSendTo(
+                self.as_raw(),
+                bufs.as_ptr(),
+                bufs.len() as c_ulong,
+                &addr
+            ))
+        }
+        .into_index()
+    }
+
+    /// Receives a single datagram into `buf`, and returns the number of bytes received and the address it was sent from.
+    ///
+    /// If the datagram does not fit in `buf`, the remainder is discarded.
+    pub fn recv_from(&self, buf: &mut [u8]) -> Result<(usize, SocketAddr)> {
+        self.recv_from_vectored(&mut [buf])
+    }
+
+    /// Receives a single datagram, filling the buffers in `bufs` in order, and returns the number of bytes received and the address it was sent from.
+    ///
+    /// If the datagram does not fit in `bufs`, the remainder is discarded.
+    pub fn recv_from_vectored(&self, bufs: &mut [&mut [u8]]) -> Result<(usize, SocketAddr)> {
+        let bufs = bufs
+            .iter_mut()
+            .map(|buf| KSlice::from_slice_mut(buf))
+            .collect::<Vec<_>>();
+        let mut addr = SocketAddress::zeroed();
+        let len = unsafe {
+            syscall!(sys::SocketRecvFrom(
+                self.as_raw(),
+                bufs.as_ptr(),
+                bufs.len() as c_ulong,
+                &mut addr,
+            ))
+        }
+        .into_index()?;
+        Ok((len, decode_addr(&addr)?))
+    }
+
+    /// Allows sending datagrams to broadcast addresses
+    pub fn set_broadcast(&self, broadcast: bool) -> Result<()> {
+        self.set_option(sys::SOCKOPT_BROADCAST, broadcast as u32)
+    }
+
+    pub fn broadcast(&self) -> Result<bool> {
+        self.option::<u32>(sys::SOCKOPT_BROADCAST).map(|v| v != 0)
+    }
+
+    /// Sets the time-to-live (or IPv6 hop limit) of unicast datagrams sent by the socket
+    pub fn set_ttl(&self, ttl: u32) -> Result<()> {
+        self.set_option(sys::SOCKOPT_TTL, ttl)
+    }
+
+    pub fn ttl(&self) -> Result<u32> {
+        self.option(sys::SOCKOPT_TTL)
+    }
+
+    /// Sets the time-to-live (or IPv6 hop limit) of multicast datagrams sent by the socket
+    pub fn set_multicast_ttl(&self, ttl: u32) -> Result<()> {
+        self.set_option(sys::SOCKOPT_MULTICAST_TTL, ttl)
+    }
+
+    pub fn multicast_ttl(&self) -> Result<u32> {
+        self.option(sys::SOCKOPT_MULTICAST_TTL)
+    }
+
+    /// Delivers multicast datagrams sent by the socket back to the local host
+    pub fn set_multicast_loop(&self, multicast_loop: bool) -> Result<()> {
+        self.set_option(sys::SOCKOPT_MULTICAST_LOOP, multicast_loop as u32)
+    }
+
+    pub fn multicast_loop(&self) -> Result<bool> {
+        self.option::<u32>(sys::SOCKOPT_MULTICAST_LOOP)
+            .map(|v| v != 0)
+    }
+
+    fn membership_v4(group: Ipv4Addr, interface: Ipv4Addr) -> MulticastMembership {
+        let mut membership = MulticastMembership::zeroed();
+        membership.family = ADDR_FAMILY_IPV4;
+        membership.group[..4].copy_from_slice(&group.octets());
+        membership.interface_addr[..4].copy_from_slice(&interface.octets());
+        membership
+    }
+
+    fn membership_v6(group: Ipv6Addr, interface: u32) -> MulticastMembership {
+        let mut membership = MulticastMembership::zeroed();
+        membership.family = ADDR_FAMILY_IPV6;
+        membership.group = group.octets();
+        membership.interface = interface;
+        membership
+    }
+
+    /// Joins the IPv4 multicast group `group` on the interface with the address `interface`, or any interface if it is [`Ipv4Addr::UNSPECIFIED`]
+    pub fn join_multicast_v4(&self, group: Ipv4Addr, interface: Ipv4Addr) -> Result<()> {
+        self.set_option(
+            sys::SOCKOPT_JOIN_MULTICAST,
+            Self::membership_v4(group, interface),
+        )
+    }
+
+    /// Leaves the IPv4 multicast group `group` on the interface with the address `interface`
+    pub fn leave_multicast_v4(&self, group: Ipv4Addr, interface: Ipv4Addr) -> Result<()> {
+        self.set_option(
+            sys::SOCKOPT_LEAVE_MULTICAST,
+            Self::membership_v4(group, interface),
+        )
+    }
+
+    /// Joins the IPv6 multicast group `group` on the interface with the index `interface`, or any interface if it is `0`
+    pub fn join_multicast_v6(&self, group: Ipv6Addr, interface: u32) -> Result<()> {
+        self.set_option(
+            sys::SOCKOPT_JOIN_MULTICAST,
+            Self::membership_v6(group, interface),
+        )
+    }
+
+    /// Leaves the IPv6 multicast group `group` on the interface with the index `interface`
+    pub fn leave_multicast_v6(&self, group: Ipv6Addr, interface: u32) -> Result<()> {
+        self.set_option(
+            sys::SOCKOPT_LEAVE_MULTICAST,
+            Self::membership_v6(group, interface),
+        )
+    }
+
+    pub fn into_socket(self) -> Socket {
+        self.0
+    }
+}
+
+impl Deref for UdpSocket {
+    type Target = Socket;
+
+    fn deref(&self) -> &Socket {
+        &self.0
+    }
+}
+
+unsafe impl<'a> AsHandle<'a, SocketHandle> for &'a UdpSocket {
+    fn as_handle(&self) -> HandlePtr<SocketHandle> {
+        self.0.as_raw()
+    }
+}
+
+/// A connected stream socket, such as a TCP connection.
+///
+/// ## Using the stream as an [`IOHandle`]
+/// Every socket is an [`IOHandle`], so the stream can be passed to code that only reads and writes streams, such as a TLS implementation,
+///  either by borrowing it with [`Socket::as_io_handle`] or by converting it with [`TcpStream::into_io_handle`].
+///
+/// The [characteristics][crate::io::characteristics] of the handle are readable and writable, and never seekable or random access.
+/// Reads block until data is available, and return `0` once the remote end has closed the connection and all data has been read,
+///  unless the blocking mode or a [read timeout][Socket::set_read_timeout] of the socket says otherwise.
+/// Writes may write fewer bytes than requested, so callers should write in a loop.
+#[derive(Debug, Hash, PartialEq, Eq)]
+pub struct TcpStream(Socket);
+
+impl TcpStream {
+    /// Connects to `addr`.
+    ///
+    /// If `addr` designates multiple addresses, connects to the first one that succeeds, or returns the error of the last one.
+    pub fn connect<A: ToSocketAddrs + ?Sized>(addr: &A) -> Result<Self> {
+        let mut last_err = Error::DoesNotExist;
+        for addr in addr.to_socket_addrs()? {
+            match Self::connect_one(&addr) {
+                Ok(sock) => return Ok(sock),
+                Err(e) => last_err = e,
+            }
+        }
+        Err(last_err)
+    }
+
+    fn connect_one(addr: &SocketAddr) -> Result<Self> {
+        let addr = encode_addr(addr);
+        let sock = Socket::create(addr.family, sys::SOCK_STREAM, &[])?;
+        Error::from_code(unsafe { syscall!(sys::ConnectSocket(sock.as_raw(), &addr)) })?;
+        Ok(Self(sock))
+    }
+
+    pub fn read(&self, buf: &mut [u8]) -> Result<usize> {
+        self.as_io_handle().read(buf)
+    }
+
+    pub fn write(&self, buf: &[u8]) -> Result<usize> {
+        self.as_io_handle().write(buf)
+    }
+
+    /// Converts the stream into an [`IOHandle`] for the same connection.
+    ///
+    /// The handle can be converted back with [`TcpStream::from_io_handle`].
+    pub fn into_io_handle(self) -> OwnedHandle<IOHandle> {
+        let hdl = self.0 .0.release_ownership();
+        unsafe { OwnedHandle::take_ownership(hdl.cast()) }
+    }
+
+    /// Converts `hdl` into a stream, if it is a stream socket, such as one returned by [`TcpStream::into_io_handle`].
+    ///
+    /// ## Errors
+    /// Returns `hdl` unchanged if it is not a stream socket.
+    pub fn from_io_handle(
+        hdl: OwnedHandle<IOHandle>,
+    ) -> core::result::Result<Self, OwnedHandle<IOHandle>> {
+        if unsafe { syscall!(sys::IsSocket(hdl.as_raw())) } != 1 {
+            return Err(hdl);
+        }
+        let sock = unsafe { Socket::from_handle(hdl.release_ownership().cast()) };
+        match sock.option::<u32>(sys::SOCKOPT_KIND) {
+            Ok(sys::SOCK_STREAM) => Ok(Self(sock)),
+            _ => Err(unsafe { OwnedHandle::take_ownership(sock.0.release_ownership().cast()) }),
+        }
+    }
+
+    pub fn into_socket(self) -> Socket {
+        self.0
+    }
+}
+
+impl Deref for TcpStream {
+    type Target = Socket;
+
+    fn deref(&self) -> &Socket {
+        &self.0
+    }
+}
+
+unsafe impl<'a> AsHandle<'a, SocketHandle> for &'a TcpStream {
+    fn as_handle(&self) -> HandlePtr<SocketHandle> {
+        self.0.as_raw()
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::io::Read for &TcpStream {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        TcpStream::read(self, buf).map_err(Into::into)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::io::Write for &TcpStream {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        TcpStream::write(self, buf).map_err(Into::into)
+    }
+
+    /// Writes to a socket are not buffered in userspace, so this does nothing
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::io::Read for TcpStream {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        TcpStream::read(self, buf).map_err(Into::into)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::io::Write for TcpStream {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        TcpStream::write(self, buf).map_err(Into::into)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
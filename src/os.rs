@@ -1,30 +1,117 @@
+pub mod auxv;
+#[cfg(feature = "rtld")]
+pub mod dl;
+pub mod elf;
+pub mod klog;
+
+pub use crate::kstr::OsString;
+
+use core::mem::MaybeUninit;
+
+use crate::{
+    fs::{OwnedFile, PathBuf},
+    result::{Error, Result},
+    sys::fs as sys,
+};
+
+/// The current directory of the current thread, as an absolute physical path. See [`canonicalize`][crate::fs::canonicalize].
+pub fn current_dir() -> Result<PathBuf> {
+    crate::fs::canonicalize(".")
+}
+
+/// Opens the current directory of the current thread
+pub fn current_dir_handle() -> Result<OwnedFile> {
+    let mut hdl = MaybeUninit::uninit();
+    Error::from_code(unsafe { sys::GetCurrentDirectory(hdl.as_mut_ptr()) })?;
+    Ok(unsafe { OwnedFile::from_handle(hdl.assume_init()) })
+}
+
+/// Sets the current directory of the current thread to the directory at `path`.
+///
+/// The current directory is per-thread, and is not changed for other threads of the process.
+pub fn set_current_dir<P: AsRef<crate::kstr::OsStr>>(path: P) -> Result<()> {
+    set_current_dir_handle(&OwnedFile::open_dir(path)?)
+}
+
+/// Sets the current directory of the current thread to `dir`, which must be opened as a directory (such as by [`OwnedFile::open_dir`])
+pub fn set_current_dir_handle(dir: &OwnedFile) -> Result<()> {
+    Error::from_code(unsafe { sys::SetCurrentDirectory(dir.as_raw()) })
+}
+
+/// Opens the resolution root of the current thread, which absolute paths are resolved against
+pub fn resolution_root() -> Result<OwnedFile> {
+    let mut hdl = MaybeUninit::uninit();
+    Error::from_code(unsafe { sys::GetResolutionRoot(hdl.as_mut_ptr()) })?;
+    Ok(unsafe { OwnedFile::from_handle(hdl.assume_init()) })
+}
+
+/// Sets the resolution root of the current thread to `dir`, which must be opened as a directory (such as by [`OwnedFile::open_dir`]).
+///
+/// Absolute paths, and `..` components that reach the root, are resolved against the new root, so paths outside of it can no longer be named.
+pub fn set_resolution_root(dir: &OwnedFile) -> Result<()> {
+    Error::from_code(unsafe { sys::SetResolutionRoot(dir.as_raw()) })
+}
+
+struct RestoreCurrentDir(OwnedFile);
+
+impl Drop for RestoreCurrentDir {
+    fn drop(&mut self) {
+        let _ = set_current_dir_handle(&self.0);
+    }
+}
+
+/// Calls `f` with the current directory of the current thread set to `dir`, and then restores the previous current directory, even if `f` panics.
+///
+/// Only the current thread is affected, so other threads can continue to resolve relative paths against their own current directory while `f` runs.
+pub fn with_current_dir<R, F: FnOnce() -> R>(dir: &OwnedFile, f: F) -> Result<R> {
+    let _restore = RestoreCurrentDir(current_dir_handle()?);
+    set_current_dir_handle(dir)?;
+    Ok(f())
+}
+
+/// A string slice passed to the operating system.
+///
+/// This type only holds valid UTF-8. [`kstr::OsStr`][crate::kstr::OsStr] holds arbitrary bytes, and is accepted by the functions of [`fs`][crate::fs].
+#[deprecated = "use `kstr::OsStr`, which also holds strings that are not valid UTF-8"]
 #[repr(transparent)]
 pub struct OsStr(str);
 
+#[allow(deprecated)]
 impl AsRef<OsStr> for str {
     fn as_ref(&self) -> &OsStr {
         OsStr::from_str(self)
     }
 }
 
+#[allow(deprecated)]
 impl AsMut<OsStr> for str {
     fn as_mut(&mut self) -> &mut OsStr {
         OsStr::from_str_mut(self)
     }
 }
 
+#[allow(deprecated)]
 impl AsRef<str> for OsStr {
     fn as_ref(&self) -> &str {
         self.as_str()
     }
 }
 
+#[allow(deprecated)]
 impl AsMut<str> for OsStr {
     fn as_mut(&mut self) -> &mut str {
         self.as_str_mut()
     }
 }
 
+#[allow(deprecated)]
+impl AsRef<crate::kstr::OsStr> for OsStr {
+    fn as_ref(&self) -> &crate::kstr::OsStr {
+        crate::kstr::OsStr::from_str(&self.0)
+    }
+}
+
+#[allow(deprecated)]
 impl OsStr {
     pub fn new<S: AsRef<OsStr> + ?Sized>(x: &S) -> &OsStr {
         x.as_ref()
@@ -35,8 +122,10 @@ impl OsStr {
     }
 }
 
+#[allow(deprecated)]
 impl OsStr {
     #[inline]
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(x: &str) -> &OsStr {
         unsafe { &*(x as *const str as *const OsStr) }
     }
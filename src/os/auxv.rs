@@ -0,0 +1,100 @@
+//! Access to the auxiliary vector of the current process.
+
+use core::ffi::{c_ulong, c_void, CStr};
+
+pub use crate::sys::elf::AuxvEntry;
+use crate::sys::{
+    elf::{
+        ElfPhdr, __AUXV, AT_BASE, AT_ENTRY, AT_EXECFN, AT_LILIUM_INIT_HANDLES,
+        AT_LILIUM_INIT_HANDLES_LEN, AT_NULL, AT_PAGESZ, AT_PHDR, AT_PHENT, AT_PHNUM, AT_RANDOM,
+    },
+    handle::{Handle, HandlePtr},
+};
+
+/// An iterator over the entries of the auxiliary vector, returned by [`entries`]
+#[derive(Clone, Debug)]
+pub struct Entries {
+    ptr: *const AuxvEntry,
+}
+
+impl Iterator for Entries {
+    type Item = AuxvEntry;
+
+    fn next(&mut self) -> Option<AuxvEntry> {
+        if self.ptr.is_null() {
+            return None;
+        }
+        let entry = unsafe { self.ptr.read() };
+        if entry.a_type == AT_NULL {
+            self.ptr = core::ptr::null();
+            None
+        } else {
+            self.ptr = unsafe { self.ptr.add(1) };
+            Some(entry)
+        }
+    }
+}
+
+impl core::iter::FusedIterator for Entries {}
+
+/// Iterates over the entries of the auxiliary vector, excluding the terminating [`AT_NULL`] entry.
+///
+/// If the auxiliary vector was not provided to the process, the iterator is empty.
+pub fn entries() -> Entries {
+    Entries {
+        ptr: unsafe { __AUXV },
+    }
+}
+
+/// Gets the value of the first entry of the auxiliary vector with type `ty`, or `None` if there is no such entry.
+pub fn get(ty: c_ulong) -> Option<usize> {
+    entries().find(|e| e.a_type == ty).map(|e| e.a_val)
+}
+
+fn get_ptr<T>(ty: c_ulong) -> Option<*const T> {
+    get(ty).filter(|&val| val != 0).map(|val| val as *const T)
+}
+
+/// The entry point of the executable
+pub fn entry_point() -> Option<*const c_void> {
+    get_ptr(AT_ENTRY)
+}
+
+/// The base address the interpreter was loaded at, if the executable was loaded by an interpreter
+pub fn interp_base() -> Option<*const c_void> {
+    get_ptr(AT_BASE)
+}
+
+/// The size of a page of memory, as reported by the kernel
+pub fn page_size() -> Option<usize> {
+    get(AT_PAGESZ)
+}
+
+/// The program headers of the executable.
+///
+/// Returns `None` if the program headers are not present, or have a different size than [`ElfPhdr`].
+pub fn program_headers() -> Option<&'static [ElfPhdr]> {
+    let ptr = get_ptr::<ElfPhdr>(AT_PHDR)?;
+    let count = get(AT_PHNUM)?;
+    if get(AT_PHENT)? != core::mem::size_of::<ElfPhdr>() {
+        return None;
+    }
+    Some(unsafe { core::slice::from_raw_parts(ptr, count) })
+}
+
+/// The handles passed to the process by its parent. See [`crate::process::init_handles`] for a safe accessor.
+pub fn init_handles() -> Option<&'static [HandlePtr<Handle>]> {
+    let ptr = get_ptr::<HandlePtr<Handle>>(AT_LILIUM_INIT_HANDLES)?;
+    let len = get(AT_LILIUM_INIT_HANDLES_LEN)?;
+    Some(unsafe { core::slice::from_raw_parts(ptr, len) })
+}
+
+/// 16 random bytes provided by the kernel, for seeding stack protectors and hash tables
+pub fn random_bytes() -> Option<&'static [u8; 16]> {
+    get_ptr::<[u8; 16]>(AT_RANDOM).map(|ptr| unsafe { &*ptr })
+}
+
+/// The path the executable was started from
+pub fn exec_path() -> Option<&'static CStr> {
+    get_ptr(AT_EXECFN).map(|ptr| unsafe { CStr::from_ptr(ptr) })
+}
@@ -0,0 +1,154 @@
+//! Loading shared objects at runtime, such as plugins, with the USI dynamic loader.
+//!
+//! ## Example
+//! ```rust,ignore
+//! let lib = Library::open("libplugin.so")?;
+//! let init = unsafe { lib.get::<extern "C" fn() -> i32>("plugin_init")? };
+//! init();
+//! ```
+
+use core::{ffi::c_void, marker::PhantomData, ops::Deref, ptr::NonNull};
+
+use alloc::string::String;
+
+use crate::{
+    kstr::with_growing_buffer,
+    result::Error,
+    sys::{kstr::KStrCPtr, rtld as sys},
+};
+
+/// An error from the dynamic loader, with the description it reported
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub struct DlError {
+    error: Error,
+    message: Option<String>,
+}
+
+impl DlError {
+    fn last(error: Error) -> Self {
+        Self {
+            error,
+            message: with_growing_buffer(128, |msg| unsafe { sys::RtldLastError(msg) }).ok(),
+        }
+    }
+
+    pub fn error(&self) -> Error {
+        self.error
+    }
+
+    /// The description reported by the dynamic loader, if any
+    pub fn message(&self) -> Option<&str> {
+        self.message.as_deref()
+    }
+}
+
+impl core::fmt::Display for DlError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match &self.message {
+            Some(message) => f.write_fmt(format_args!("{}: {}", self.error, message)),
+            None => self.error.fmt(f),
+        }
+    }
+}
+
+impl core::error::Error for DlError {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+impl From<DlError> for Error {
+    fn from(e: DlError) -> Self {
+        e.error
+    }
+}
+
+pub type Result<T> = core::result::Result<T, DlError>;
+
+fn check(code: crate::sys::result::SysResult) -> Result<()> {
+    Error::from_code(code).map_err(DlError::last)
+}
+
+/// A loaded shared object, which is unloaded when dropped (once no other `Library` refers to it)
+#[derive(Debug)]
+pub struct Library(NonNull<sys::RtldModule>);
+
+impl Library {
+    /// Loads the shared object `name`, resolving every symbol immediately.
+    ///
+    /// If `name` contains a `/`, it is a path. Otherwise, it is searched for in the library search path of the process.
+    pub fn open(name: &str) -> Result<Self> {
+        Self::open_with_flags(name, sys::RTLD_NOW | sys::RTLD_LOCAL)
+    }
+
+    /// Loads the shared object `name` with the given `RTLD_*` flags
+    pub fn open_with_flags(name: &str, flags: u32) -> Result<Self> {
+        let mut module = core::ptr::null_mut();
+        check(unsafe { sys::RtldOpen(&mut module, KStrCPtr::from_str(name), flags) })?;
+        Ok(Self(
+            NonNull::new(module).expect("RtldOpen succeeded without a module"),
+        ))
+    }
+
+    /// Resolves the symbol `name`, as a value of type `T`, which is usually a function pointer or a raw pointer.
+    ///
+    /// # Safety
+    /// `T` must be the type of the symbol. For a function pointer, that includes its ABI, parameters, and return type.
+    ///
+    /// ## Panics
+    /// Panics if `T` is not the size of a pointer.
+    pub unsafe fn get<T: Copy>(&self, name: &str) -> Result<Symbol<'_, T>> {
+        assert_eq!(
+            core::mem::size_of::<T>(),
+            core::mem::size_of::<*mut c_void>(),
+            "A symbol must be accessed as a pointer-sized type"
+        );
+        let mut sym = core::ptr::null_mut();
+        check(unsafe { sys::RtldSymbol(self.0.as_ptr(), KStrCPtr::from_str(name), &mut sym) })?;
+        Ok(Symbol {
+            value: unsafe { core::mem::transmute_copy::<*mut c_void, T>(&sym) },
+            _lib: PhantomData,
+        })
+    }
+
+    pub fn as_raw(&self) -> *mut sys::RtldModule {
+        self.0.as_ptr()
+    }
+}
+
+impl Drop for Library {
+    fn drop(&mut self) {
+        unsafe {
+            sys::RtldClose(self.0.as_ptr());
+        }
+    }
+}
+
+// The dynamic loader synchronizes access to modules
+unsafe impl Send for Library {}
+unsafe impl Sync for Library {}
+
+/// A symbol resolved from a [`Library`], which cannot outlive it
+#[derive(Copy, Clone, Debug)]
+pub struct Symbol<'lib, T> {
+    value: T,
+    _lib: PhantomData<&'lib Library>,
+}
+
+impl<T: Copy> Symbol<'_, T> {
+    /// Detaches the symbol from the lifetime of its library.
+    ///
+    /// # Safety
+    /// The value must not be used after the library is unloaded.
+    pub unsafe fn into_raw(self) -> T {
+        self.value
+    }
+}
+
+impl<T> Deref for Symbol<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.value
+    }
+}
@@ -0,0 +1,390 @@
+//! Read-only parsing of ELF images, for loaders and debuggers.
+//!
+//! [`ElfImage`] accepts 32-bit and 64-bit images of the native byte order, and presents both with the 64-bit structures of [`sys::elf`][crate::sys::elf].
+//! Offsets and sizes in the image are checked against its length, so a malformed image produces an [`ElfError`] rather than a panic.
+
+use alloc::{borrow::Cow, vec::Vec};
+
+use crate::{
+    handle::HandleRef,
+    io::{IOHandle, SeekFrom},
+    result::Error,
+    sys::elf::*,
+};
+
+/// An error parsing an ELF image
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+pub enum ElfError {
+    /// The image does not start with [`ELFMAG`]
+    BadMagic,
+    /// The class in `e_ident` is not [`ELFCLASS32`] or [`ELFCLASS64`]
+    UnsupportedClass(u8),
+    /// The data encoding in `e_ident` is not the byte order of the current target
+    UnsupportedEncoding(u8),
+    /// A header or segment extends past the end of the image
+    Truncated,
+    /// A header has a size or value inconsistent with the class of the image
+    Malformed,
+    /// Reading the image failed
+    Io(Error),
+}
+
+impl core::fmt::Display for ElfError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::BadMagic => f.write_str("not an ELF image"),
+            Self::UnsupportedClass(class) => {
+                f.write_fmt(format_args!("unsupported ELF class {}", class))
+            }
+            Self::UnsupportedEncoding(data) => {
+                f.write_fmt(format_args!("unsupported ELF data encoding {}", data))
+            }
+            Self::Truncated => f.write_str("ELF image is truncated"),
+            Self::Malformed => f.write_str("ELF image is malformed"),
+            Self::Io(e) => e.fmt(f),
+        }
+    }
+}
+
+impl core::error::Error for ElfError {}
+
+impl From<Error> for ElfError {
+    fn from(e: Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+/// The class of an ELF image, which determines the size of its structures
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+pub enum ElfClass {
+    Elf32,
+    Elf64,
+}
+
+impl ElfClass {
+    /// The class of images loadable on the current target
+    #[cfg(target_pointer_width = "64")]
+    pub const NATIVE: Self = Self::Elf64;
+    /// The class of images loadable on the current target
+    #[cfg(not(target_pointer_width = "64"))]
+    pub const NATIVE: Self = Self::Elf32;
+}
+
+#[cfg(target_endian = "little")]
+pub(crate) const NATIVE_DATA: u8 = ELFDATA2LSB;
+#[cfg(target_endian = "big")]
+pub(crate) const NATIVE_DATA: u8 = ELFDATA2MSB;
+
+#[cfg(target_arch = "x86_64")]
+pub(crate) const NATIVE_MACHINE: u16 = EM_X86_64;
+#[cfg(target_arch = "x86")]
+pub(crate) const NATIVE_MACHINE: u16 = EM_386;
+#[cfg(target_arch = "aarch64")]
+pub(crate) const NATIVE_MACHINE: u16 = EM_AARCH64;
+#[cfg(target_arch = "arm")]
+pub(crate) const NATIVE_MACHINE: u16 = EM_ARM;
+#[cfg(any(target_arch = "riscv32", target_arch = "riscv64"))]
+pub(crate) const NATIVE_MACHINE: u16 = EM_RISCV;
+#[cfg(not(any(
+    target_arch = "x86_64",
+    target_arch = "x86",
+    target_arch = "aarch64",
+    target_arch = "arm",
+    target_arch = "riscv32",
+    target_arch = "riscv64"
+)))]
+pub(crate) const NATIVE_MACHINE: u16 = EM_NONE;
+
+fn read_pod<T: bytemuck::Pod>(data: &[u8], off: u64) -> Result<T, ElfError> {
+    let off = usize::try_from(off).map_err(|_| ElfError::Truncated)?;
+    let end = off
+        .checked_add(core::mem::size_of::<T>())
+        .ok_or(ElfError::Truncated)?;
+    data.get(off..end)
+        .map(bytemuck::pod_read_unaligned)
+        .ok_or(ElfError::Truncated)
+}
+
+fn slice_at(data: &[u8], off: u64, len: u64) -> Option<&[u8]> {
+    let off = usize::try_from(off).ok()?;
+    let len = usize::try_from(len).ok()?;
+    data.get(off..off.checked_add(len)?)
+}
+
+/// A parsed ELF image, borrowed from a mapping or read from a stream.
+///
+/// The file header and every program header are validated when the image is parsed.
+///
+/// ## Example
+/// ```rust,ignore
+/// let file = OwnedFile::open("/bin/init")?;
+/// let image = ElfImage::read(&file.as_io_handle())?;
+/// if let Some(interp) = image.interpreter() {
+///     println!("interpreter: {}", interp);
+/// }
+/// for phdr in image.program_headers().filter(|phdr| phdr.p_type == PT_LOAD) {
+///     println!("{:#x}..{:#x}", phdr.p_vaddr, phdr.p_vaddr + phdr.p_memsz);
+/// }
+/// ```
+#[derive(Clone, Debug)]
+pub struct ElfImage<'a> {
+    data: Cow<'a, [u8]>,
+    class: ElfClass,
+    header: Elf64Ehdr,
+}
+
+impl<'a> ElfImage<'a> {
+    /// Parses the image contained in `data`, such as a mapping of an executable
+    pub fn parse(data: &'a [u8]) -> Result<Self, ElfError> {
+        Self::from_cow(Cow::Borrowed(data))
+    }
+
+    fn from_cow(data: Cow<'a, [u8]>) -> Result<Self, ElfError> {
+        let ident: [u8; EI_NIDENT] = read_pod(&data, 0)?;
+        if ident[..4] != ELFMAG {
+            return Err(ElfError::BadMagic);
+        }
+        if ident[EI_DATA] != NATIVE_DATA {
+            return Err(ElfError::UnsupportedEncoding(ident[EI_DATA]));
+        }
+
+        let (class, header) = match ident[EI_CLASS] {
+            ELFCLASS64 => (ElfClass::Elf64, read_pod::<Elf64Ehdr>(&data, 0)?),
+            ELFCLASS32 => {
+                let hdr = read_pod::<Elf32Ehdr>(&data, 0)?;
+                let header = Elf64Ehdr {
+                    e_ident: hdr.e_ident,
+                    e_type: hdr.e_type,
+                    e_machine: hdr.e_machine,
+                    e_version: hdr.e_version,
+                    e_entry: hdr.e_entry.into(),
+                    e_phoff: hdr.e_phoff.into(),
+                    e_shoff: hdr.e_shoff.into(),
+                    e_flags: hdr.e_flags,
+                    e_ehsize: hdr.e_ehsize,
+                    e_phentsize: hdr.e_phentsize,
+                    e_phnum: hdr.e_phnum,
+                    e_shentsize: hdr.e_shentsize,
+                    e_shnum: hdr.e_shnum,
+                    e_shstrndx: hdr.e_shstrndx,
+                };
+                (ElfClass::Elf32, header)
+            }
+            class => return Err(ElfError::UnsupportedClass(class)),
+        };
+
+        let phentsize = match class {
+            ElfClass::Elf32 => core::mem::size_of::<Elf32Phdr>(),
+            ElfClass::Elf64 => core::mem::size_of::<Elf64Phdr>(),
+        };
+        if header.e_phnum != 0 && usize::from(header.e_phentsize) < phentsize {
+            return Err(ElfError::Malformed);
+        }
+        let phdrs_len = u64::from(header.e_phentsize) * u64::from(header.e_phnum);
+        if slice_at(&data, header.e_phoff, phdrs_len).is_none() {
+            return Err(ElfError::Truncated);
+        }
+
+        let image = Self {
+            data,
+            class,
+            header,
+        };
+
+        for phdr in image.program_headers() {
+            if phdr.p_filesz > phdr.p_memsz && phdr.p_type == PT_LOAD {
+                return Err(ElfError::Malformed);
+            }
+            if slice_at(&image.data, phdr.p_offset, phdr.p_filesz).is_none() {
+                return Err(ElfError::Truncated);
+            }
+        }
+
+        Ok(image)
+    }
+
+    /// The bytes of the image
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// Converts the image into one that owns its bytes
+    pub fn into_owned(self) -> ElfImage<'static> {
+        ElfImage {
+            data: Cow::Owned(self.data.into_owned()),
+            class: self.class,
+            header: self.header,
+        }
+    }
+
+    pub fn class(&self) -> ElfClass {
+        self.class
+    }
+
+    /// The file header of the image. The header of a 32-bit image is widened.
+    pub fn header(&self) -> &Elf64Ehdr {
+        &self.header
+    }
+
+    /// The type of the image, one of the `ET_*` constants
+    pub fn image_type(&self) -> u16 {
+        self.header.e_type
+    }
+
+    /// The machine the image targets, one of the `EM_*` constants
+    pub fn machine(&self) -> u16 {
+        self.header.e_machine
+    }
+
+    /// The entry point of the image, relative to its load base if it is [`ET_DYN`]
+    pub fn entry(&self) -> u64 {
+        self.header.e_entry
+    }
+
+    /// Checks if the image can be loaded on the current target: it is an [`ET_EXEC`] or [`ET_DYN`] image of the native class and machine
+    pub fn is_native(&self) -> bool {
+        self.class == ElfClass::NATIVE
+            && self.header.e_machine == NATIVE_MACHINE
+            && matches!(self.header.e_type, ET_EXEC | ET_DYN)
+    }
+
+    /// The program headers of the image. The program headers of a 32-bit image are widened.
+    pub fn program_headers(&self) -> ProgramHeaders<'_> {
+        ProgramHeaders {
+            data: &self.data,
+            class: self.class,
+            off: self.header.e_phoff,
+            entsize: self.header.e_phentsize.into(),
+            remaining: self.header.e_phnum,
+        }
+    }
+
+    /// The bytes of the image described by `phdr`, which are the initial contents of a [`PT_LOAD`] segment.
+    ///
+    /// Returns `None` if `phdr` extends past the end of the image, which is not the case for any header returned by [`ElfImage::program_headers`].
+    pub fn segment_data(&self, phdr: &Elf64Phdr) -> Option<&[u8]> {
+        slice_at(&self.data, phdr.p_offset, phdr.p_filesz)
+    }
+
+    /// The path of the interpreter requested by the [`PT_INTERP`] program header, or `None` if the image does not have one, or it is not valid UTF-8
+    pub fn interpreter(&self) -> Option<&str> {
+        let phdr = self
+            .program_headers()
+            .find(|phdr| phdr.p_type == PT_INTERP)?;
+        let bytes = self.segment_data(&phdr)?;
+        let bytes = bytes.split(|&b| b == 0).next().unwrap_or(bytes);
+        core::str::from_utf8(bytes).ok()
+    }
+
+    /// The entries of the dynamic section described by the [`PT_DYNAMIC`] program header, up to [`DT_NULL`].
+    ///
+    /// Returns `None` if the image does not have a dynamic section. The entries of a 32-bit image are widened, with `d_tag` sign-extended.
+    pub fn dynamic(&self) -> Option<DynamicEntries<'_>> {
+        let phdr = self
+            .program_headers()
+            .find(|phdr| phdr.p_type == PT_DYNAMIC)?;
+        Some(DynamicEntries {
+            data: self.segment_data(&phdr)?,
+            class: self.class,
+        })
+    }
+}
+
+impl ElfImage<'static> {
+    /// Reads and parses the whole of `io`, starting from the beginning of the stream
+    pub fn read(io: &HandleRef<IOHandle>) -> Result<Self, ElfError> {
+        io.seek(SeekFrom::Start(0))?;
+        let mut data = Vec::new();
+        let mut chunk = [0u8; 4096];
+        loop {
+            match io.read(&mut chunk)? {
+                0 => break,
+                n => data.extend_from_slice(&chunk[..n]),
+            }
+        }
+        Self::from_cow(Cow::Owned(data))
+    }
+}
+
+/// An iterator over the program headers of an [`ElfImage`]
+#[derive(Clone, Debug)]
+pub struct ProgramHeaders<'a> {
+    data: &'a [u8],
+    class: ElfClass,
+    off: u64,
+    entsize: u64,
+    remaining: u16,
+}
+
+impl Iterator for ProgramHeaders<'_> {
+    type Item = Elf64Phdr;
+
+    fn next(&mut self) -> Option<Elf64Phdr> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        let off = self.off;
+        self.off += self.entsize;
+
+        // The table was checked to be in bounds when the image was parsed
+        match self.class {
+            ElfClass::Elf64 => read_pod(self.data, off).ok(),
+            ElfClass::Elf32 => {
+                let phdr: Elf32Phdr = read_pod(self.data, off).ok()?;
+                Some(Elf64Phdr {
+                    p_type: phdr.p_type,
+                    p_flags: phdr.p_flags,
+                    p_offset: phdr.p_offset.into(),
+                    p_vaddr: phdr.p_vaddr.into(),
+                    p_paddr: phdr.p_paddr.into(),
+                    p_filesz: phdr.p_filesz.into(),
+                    p_memsz: phdr.p_memsz.into(),
+                    p_align: phdr.p_align.into(),
+                })
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining.into(), Some(self.remaining.into()))
+    }
+}
+
+impl ExactSizeIterator for ProgramHeaders<'_> {}
+
+/// An iterator over the entries of the dynamic section of an [`ElfImage`]
+#[derive(Clone, Debug)]
+pub struct DynamicEntries<'a> {
+    data: &'a [u8],
+    class: ElfClass,
+}
+
+impl Iterator for DynamicEntries<'_> {
+    type Item = Elf64Dyn;
+
+    fn next(&mut self) -> Option<Elf64Dyn> {
+        let (entry, size) = match self.class {
+            ElfClass::Elf64 => (
+                read_pod::<Elf64Dyn>(self.data, 0).ok()?,
+                core::mem::size_of::<Elf64Dyn>(),
+            ),
+            ElfClass::Elf32 => {
+                let dyn_: Elf32Dyn = read_pod(self.data, 0).ok()?;
+                (
+                    Elf64Dyn {
+                        d_tag: dyn_.d_tag.into(),
+                        d_val: dyn_.d_val.into(),
+                    },
+                    core::mem::size_of::<Elf32Dyn>(),
+                )
+            }
+        };
+        if entry.d_tag == DT_NULL {
+            self.data = &[];
+            return None;
+        }
+        self.data = &self.data[size..];
+        Some(entry)
+    }
+}
@@ -0,0 +1,207 @@
+//! The kernel message buffer, which holds the system log.
+//!
+//! Messages are written with [`write`], and read back in order by a [`Reader`].
+//! With the `log-backend` feature, [`KernelLogger`] sends the messages of the `log` crate to the kernel message buffer.
+
+use alloc::string::String;
+
+use crate::{
+    kstr::with_growing_buffer,
+    result::{Error, Result},
+    sys::{
+        kmgmt::{self as sys, KernelLogEntry},
+        kstr::{KStrCPtr, KStrPtr},
+    },
+    time::{Duration, SystemClock, TimePoint},
+};
+
+/// The severity of a kernel log message
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl Level {
+    /// The `KLOG_LEVEL_*` constant for the level
+    pub const fn into_raw(self) -> u32 {
+        match self {
+            Self::Error => sys::KLOG_LEVEL_ERROR,
+            Self::Warn => sys::KLOG_LEVEL_WARN,
+            Self::Info => sys::KLOG_LEVEL_INFO,
+            Self::Debug => sys::KLOG_LEVEL_DEBUG,
+            Self::Trace => sys::KLOG_LEVEL_TRACE,
+        }
+    }
+
+    /// The level for a `KLOG_LEVEL_*` constant, or `None` if `level` is not a level
+    pub const fn from_raw(level: u32) -> Option<Self> {
+        match level {
+            sys::KLOG_LEVEL_ERROR => Some(Self::Error),
+            sys::KLOG_LEVEL_WARN => Some(Self::Warn),
+            sys::KLOG_LEVEL_INFO => Some(Self::Info),
+            sys::KLOG_LEVEL_DEBUG => Some(Self::Debug),
+            sys::KLOG_LEVEL_TRACE => Some(Self::Trace),
+            _ => None,
+        }
+    }
+}
+
+/// Writes `message` to the kernel message buffer at the level `level`.
+///
+/// ## Errors
+/// Returns [`Error::Permission`] if the current thread does not have the [`WRITE_KERNEL_LOG`][crate::security::kernel_perm::WRITE_KERNEL_LOG] kernel permission.
+pub fn write(level: Level, message: &str) -> Result<()> {
+    Error::from_code(unsafe { sys::WriteKernelLog(level.into_raw(), KStrCPtr::from_str(message)) })
+}
+
+/// A message read from the kernel message buffer
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub struct Entry {
+    seq: u64,
+    timestamp: TimePoint<SystemClock>,
+    level: Level,
+    message: String,
+}
+
+impl Entry {
+    /// The sequence number of the message, which is one more than that of the message written before it
+    pub const fn seq(&self) -> u64 {
+        self.seq
+    }
+
+    /// The time the message was written
+    pub const fn timestamp(&self) -> TimePoint<SystemClock> {
+        self.timestamp
+    }
+
+    pub const fn level(&self) -> Level {
+        self.level
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    pub fn into_message(self) -> String {
+        self.message
+    }
+}
+
+/// Reads the messages of the kernel message buffer in order, starting from the oldest message that has not been discarded.
+///
+/// As an [`Iterator`], it stops once it has read every message written so far. Messages written afterwards can be read by iterating again.
+///
+/// ## Example
+/// ```rust,ignore
+/// for entry in Reader::new() {
+///     let entry = entry?;
+///     println!("[{:?}] {}", entry.level(), entry.message());
+/// }
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct Reader {
+    next: u64,
+}
+
+impl Reader {
+    /// A reader that starts from the oldest message
+    pub const fn new() -> Self {
+        Self { next: 0 }
+    }
+
+    /// A reader that starts from the message with the sequence number `seq`, or the oldest message after it if it was discarded
+    pub const fn starting_at(seq: u64) -> Self {
+        Self { next: seq }
+    }
+
+    /// Reads the next message, or returns `None` if every message written so far has been read.
+    ///
+    /// ## Errors
+    /// Returns [`Error::Permission`] if the current thread does not have the [`READ_KERNEL_LOG`][crate::security::kernel_perm::READ_KERNEL_LOG] kernel permission.
+    pub fn read_next(&mut self) -> Result<Option<Entry>> {
+        let mut entry = KernelLogEntry {
+            seq: 0,
+            timestamp: Duration::ZERO.into_system(),
+            level: 0,
+            message: KStrPtr::empty(),
+        };
+        let message = match with_growing_buffer(256, |kstr| {
+            entry.message = *kstr;
+            let res = unsafe { sys::ReadKernelLog(self.next, &mut entry) };
+            *kstr = entry.message;
+            res
+        }) {
+            Ok(message) => message,
+            Err(Error::DoesNotExist) => return Ok(None),
+            Err(e) => return Err(e),
+        };
+
+        self.next = entry.seq + 1;
+
+        Ok(Some(Entry {
+            seq: entry.seq,
+            timestamp: TimePoint::from_epoch_offset(Duration::from_system(entry.timestamp)),
+            level: Level::from_raw(entry.level).ok_or(Error::InvalidState)?,
+            message,
+        }))
+    }
+}
+
+impl Iterator for Reader {
+    type Item = Result<Entry>;
+
+    fn next(&mut self) -> Option<Result<Entry>> {
+        self.read_next().transpose()
+    }
+}
+
+/// A [`log::Log`] implementation that writes to the kernel message buffer.
+///
+/// Messages are written as `target: message`. Errors writing them are ignored.
+#[cfg(feature = "log-backend")]
+#[derive(Copy, Clone, Debug, Default)]
+pub struct KernelLogger;
+
+#[cfg(feature = "log-backend")]
+impl KernelLogger {
+    /// Installs the kernel logger as the logger of the `log` crate, and sets the maximum level to `max_level`
+    pub fn init(max_level: log::LevelFilter) -> core::result::Result<(), log::SetLoggerError> {
+        static LOGGER: KernelLogger = KernelLogger;
+        log::set_logger(&LOGGER)?;
+        log::set_max_level(max_level);
+        Ok(())
+    }
+}
+
+#[cfg(feature = "log-backend")]
+impl From<log::Level> for Level {
+    fn from(level: log::Level) -> Self {
+        match level {
+            log::Level::Error => Self::Error,
+            log::Level::Warn => Self::Warn,
+            log::Level::Info => Self::Info,
+            log::Level::Debug => Self::Debug,
+            log::Level::Trace => Self::Trace,
+        }
+    }
+}
+
+#[cfg(feature = "log-backend")]
+impl log::Log for KernelLogger {
+    fn enabled(&self, metadata: &log::Metadata) -> bool {
+        metadata.level() <= log::max_level()
+    }
+
+    fn log(&self, record: &log::Record) {
+        if self.enabled(record.metadata()) {
+            let message = alloc::format!("{}: {}", record.target(), record.args());
+            let _ = write(record.level().into(), &message);
+        }
+    }
+
+    fn flush(&self) {}
+}
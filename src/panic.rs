@@ -0,0 +1,105 @@
+//! A `#[panic_handler]` for `no_std` Lilium programs, enabled by the `panic-handler` feature.
+//!
+//! A panic writes its message to the standard error stream, then terminates the thread with an unmanaged [`EXCEPT_RUST_PANIC`] exception.
+//! The exception refers to the panic message, so a debugger (or the USI) can recover it from the exception status.
+
+use core::{
+    cell::{Cell, UnsafeCell},
+    ffi::{c_ulong, c_void},
+    fmt::Write,
+    panic::PanicInfo,
+    sync::atomic::{AtomicPtr, Ordering},
+};
+
+use crate::sys::{
+    except::{ExceptionStatusInfo, UnmanagedException, EXCEPT_RUST_PANIC},
+    io::{IOWrite, __HANDLE_IO_STDERR},
+};
+
+/// The maximum length of a panic message. Longer messages are truncated.
+pub const MESSAGE_CAPACITY: usize = 1024;
+
+/// A hook called with the panic, and the formatted panic message, before the panic is reported.
+pub type PanicHook = fn(&PanicInfo, &str);
+
+static HOOK: AtomicPtr<()> = AtomicPtr::new(core::ptr::null_mut());
+
+/// Sets the hook called on every panic, and returns the previous hook.
+///
+/// The hook is not called for a panic that occurs while the thread is already panicking.
+pub fn set_hook(hook: Option<PanicHook>) -> Option<PanicHook> {
+    let ptr = hook.map_or(core::ptr::null_mut(), |hook| hook as *mut ());
+    let prev = HOOK.swap(ptr, Ordering::AcqRel);
+    // SAFETY: `HOOK` only ever holds null or a `PanicHook`
+    (!prev.is_null()).then(|| unsafe { core::mem::transmute::<*mut (), PanicHook>(prev) })
+}
+
+struct MessageBuf {
+    buf: [u8; MESSAGE_CAPACITY],
+    len: usize,
+}
+
+impl MessageBuf {
+    fn as_str(&self) -> &str {
+        // Truncation can split a character, so only the valid prefix is used
+        match core::str::from_utf8(&self.buf[..self.len]) {
+            Ok(s) => s,
+            Err(e) => unsafe { core::str::from_utf8_unchecked(&self.buf[..e.valid_up_to()]) },
+        }
+    }
+}
+
+impl Write for MessageBuf {
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        let n = s.len().min(MESSAGE_CAPACITY - self.len);
+        self.buf[self.len..][..n].copy_from_slice(&s.as_bytes()[..n]);
+        self.len += n;
+        Ok(())
+    }
+}
+
+#[thread_local]
+static MESSAGE: UnsafeCell<MessageBuf> = UnsafeCell::new(MessageBuf {
+    buf: [0; MESSAGE_CAPACITY],
+    len: 0,
+});
+
+#[thread_local]
+static PANICKING: Cell<bool> = Cell::new(false);
+
+#[panic_handler]
+fn panic(info: &PanicInfo) -> ! {
+    // SAFETY: The message is only accessed by the panic handler of the current thread, and never reentrantly
+    let msg = unsafe { &mut *MESSAGE.get() };
+
+    if !PANICKING.replace(true) {
+        msg.len = 0;
+        let _ = match info.location() {
+            Some(loc) => writeln!(msg, "thread panicked at {}:\n{}", loc, info.message()),
+            None => writeln!(msg, "thread panicked:\n{}", info.message()),
+        };
+
+        let hook = HOOK.load(Ordering::Acquire);
+        if !hook.is_null() {
+            let hook = unsafe { core::mem::transmute::<*mut (), PanicHook>(hook) };
+            hook(info, msg.as_str());
+        }
+
+        let s = msg.as_str();
+        unsafe {
+            IOWrite(
+                __HANDLE_IO_STDERR,
+                s.as_ptr().cast::<c_void>(),
+                s.len() as c_ulong,
+            );
+        }
+    }
+
+    let s = msg.as_str();
+    let status = ExceptionStatusInfo {
+        except_code: EXCEPT_RUST_PANIC,
+        except_info: s.as_ptr() as u64,
+        except_reason: s.len() as u64,
+    };
+    unsafe { UnmanagedException(&status) }
+}
@@ -1,8 +1,9 @@
 use core::ffi::c_void;
 use core::{
+    any::Any,
     ffi::{c_long, c_ulong},
     marker::PhantomData,
-    mem::MaybeUninit,
+    mem::{ManuallyDrop, MaybeUninit},
     ops::Deref,
 };
 
@@ -21,31 +22,44 @@ unsafe fn debug_unreachable() -> ! {
 }
 
 use alloc::{
+    boxed::Box,
     string::{String, ToString},
     vec::Vec,
 };
 
 use crate::sys::except::{ExceptionInfo, ExceptionStatusInfo};
 use crate::{
+    debug::Debugger,
     fs::{Path, PathBuf},
-    handle::{AsHandle, BorrowedHandle},
-    io::IOHandle,
-    result::Result,
+    handle::{AsHandle, BorrowedHandle, OwnedHandle},
+    io::{IOHandle, IoChars},
+    result::{ContextError, Result},
     security::SecurityContext,
     sys::{
         fs::FileHandle,
         handle::{Handle, HandlePtr},
         io::{__HANDLE_IO_STDERR, __HANDLE_IO_STDIN, __HANDLE_IO_STDOUT},
         isolation::NamespaceHandle,
+        kstr::KCSlice,
         kstr::KStrCPtr,
         process::{
-            self as sys, CreateProcess, EnumerateProcessHandle, EnvironmentMapHandle,
-            ProcessHandle, ProcessStartContext,
+            self as sys, CreateProcess, CreateProcessOption, CreateProcessOptionResourceLimits,
+            CreateProcessOptionWorkingDir, EnumerateProcessHandle, EnvironmentMapHandle,
+            ProcessHandle, ProcessResourceLimit, ProcessStartContext,
         },
+        thread::ThreadHandle,
     },
+    uuid::Uuid,
 };
 
+mod job;
+mod termination;
+
+pub use job::Job;
+pub use termination::{abort, exit, on_termination, TerminationHook, TerminationReason};
+
 bitflags::bitflags! {
+    #[derive(Copy, Clone, Hash, PartialEq, Eq, Debug)]
     pub struct ProcessStartFlags : c_long{
         const START_SUSPENDED = sys::FLAG_START_SUSPENDED;
         const NON_PRIVLAGED = sys::FLAG_NON_PRIVILAGED;
@@ -56,16 +70,80 @@ bitflags::bitflags! {
     }
 }
 
+bitflags::bitflags! {
+    /// Flags for enumerating processes, made of the `ENUMERATE_*` constants
+    #[repr(transparent)]
+    #[derive(Copy, Clone, Hash, PartialEq, Eq, Debug)]
+    pub struct EnumerateFlags : u32 {
+        /// Includes processes started with [`ProcessStartFlags::HIDE_PROCESS`]
+        const VIEW_HIDDEN = sys::ENUMERATE_VIEW_HIDDEN;
+        /// Includes processes of every principal, not just the current primary principal
+        const VIEW_ALL = sys::ENUMERATE_VIEW_ALL;
+        /// Does not fail on processes the current thread cannot access
+        const NO_FAIL = sys::ENUMERATE_NO_FAIL;
+    }
+}
+
+/// A resource that can be limited by [`Command::limit`]
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Limit {
+    /// The total virtual memory allocated for mappings, in bytes
+    Memory,
+    /// The total physical memory allocated for mappings, in bytes
+    PhysMemory,
+    /// The number of handles open at once
+    Handles,
+    /// The number of anonymous objects, such as pipes and memory buffers, created by the process
+    AnonObjects,
+    /// The number of threads running at once
+    Threads,
+    /// A limit not known to this library
+    Other(Uuid),
+}
+
+impl Limit {
+    pub const fn from_uuid(id: Uuid) -> Self {
+        match id {
+            sys::PROCESS_LIMIT_MEMORY => Self::Memory,
+            sys::PROCESS_LIMIT_PHYS_MEMORY => Self::PhysMemory,
+            sys::PROCESS_LIMIT_HANDLES => Self::Handles,
+            sys::PROCESS_LIMIT_ANON_OBJECTS => Self::AnonObjects,
+            sys::PROCESS_LIMIT_THREADS => Self::Threads,
+            id => Self::Other(id),
+        }
+    }
+
+    pub const fn into_uuid(self) -> Uuid {
+        match self {
+            Self::Memory => sys::PROCESS_LIMIT_MEMORY,
+            Self::PhysMemory => sys::PROCESS_LIMIT_PHYS_MEMORY,
+            Self::Handles => sys::PROCESS_LIMIT_HANDLES,
+            Self::AnonObjects => sys::PROCESS_LIMIT_ANON_OBJECTS,
+            Self::Threads => sys::PROCESS_LIMIT_THREADS,
+            Self::Other(id) => id,
+        }
+    }
+}
+
 pub struct Command<'a> {
     resolution_base: HandlePtr<FileHandle>,
     cmd: PathBuf,
     env: HandlePtr<EnvironmentMapHandle>,
     namespace: HandlePtr<NamespaceHandle>,
     start_security_context: HandlePtr<SecurityContext>,
+    cwd_base: HandlePtr<FileHandle>,
+    cwd_path: Option<PathBuf>,
+    resolution_root: HandlePtr<FileHandle>,
     args: Vec<String>,
-    init_handles: Vec<HandlePtr<Handle>>,
+    env_clear: bool,
+    env_vars: Vec<(String, String)>,
+    limits: Vec<ProcessResourceLimit>,
+    pub(crate) init_handles: Vec<HandlePtr<Handle>>,
     label: String,
-    flags: ProcessStartFlags,
+    pub(crate) flags: ProcessStartFlags,
+    /// Handles opened on behalf of the command, such as by [`Sandbox::apply`][crate::security::Sandbox::apply], which are closed when it is dropped
+    pub(crate) owned: Vec<Box<dyn Any>>,
     _handles: PhantomData<BorrowedHandle<'a, Handle>>,
 }
 
@@ -77,8 +155,12 @@ impl CommandResult {
     fn join(self) -> crate::result::Result<CommandStatus> {
         let mut sigterminfo = MaybeUninit::zeroed();
         loop {
-            let ret =
-                unsafe { crate::sys::process::JoinProcess(self.hdl, sigterminfo.as_mut_ptr()) };
+            let ret = unsafe {
+                syscall!(crate::sys::process::JoinProcess(
+                    self.hdl,
+                    sigterminfo.as_mut_ptr()
+                ))
+            };
             match crate::result::Error::from_code(ret) {
                 Ok(()) => break Ok(CommandStatus::Normal(ret as i32)), // Note: Lilium guarantees it will be a positive i32
                 Err(crate::result::Error::Signaled) => {
@@ -98,22 +180,105 @@ impl CommandResult {
     }
 
     fn detach(self) -> crate::result::Result<()> {
-        crate::result::Error::from_code(unsafe { crate::sys::process::DetachProcess(self.hdl) })
+        crate::result::Error::from_code(unsafe {
+            syscall!(crate::sys::process::DetachProcess(self.hdl))
+        })
     }
 }
 
 impl Command<'_> {
-    fn spawn_with_result(&mut self) -> crate::result::Result<CommandResult> {
+    /// Builds the environment map to start the process with, if the environment was modified.
+    fn build_env(&self) -> crate::result::Result<Option<HandlePtr<EnvironmentMapHandle>>> {
+        if !self.env_clear && self.env_vars.is_empty() {
+            return Ok(None);
+        }
+
+        let mut env = MaybeUninit::uninit();
+        if self.env_clear {
+            crate::result::Error::from_code(unsafe {
+                syscall!(sys::CreateEnvironment(env.as_mut_ptr()))
+            })?;
+        } else {
+            let base = if self.env == HandlePtr::null() {
+                let mut cur = MaybeUninit::uninit();
+                crate::result::Error::from_code(unsafe {
+                    syscall!(sys::GetCurrentEnvironment(cur.as_mut_ptr()))
+                })?;
+                unsafe { cur.assume_init() }
+            } else {
+                self.env
+            };
+            crate::result::Error::from_code(unsafe {
+                syscall!(sys::CopyEnvironment(env.as_mut_ptr(), base))
+            })?;
+        }
+        let env = unsafe { env.assume_init() };
+
+        for (key, val) in &self.env_vars {
+            if let Err(e) = crate::result::Error::from_code(unsafe {
+                syscall!(sys::SetEnvironmentVariable(
+                    env,
+                    KStrCPtr::from_str(key),
+                    KStrCPtr::from_str(val)
+                ))
+            }) {
+                unsafe { syscall!(sys::DestroyEnvironment(env)) };
+                return Err(e);
+            }
+        }
+
+        Ok(Some(env))
+    }
+
+    fn spawn_with_result(&mut self) -> core::result::Result<CommandResult, ContextError> {
+        let env = self.build_env()?;
+        let res = self.spawn_with_env(env.unwrap_or(self.env));
+        if let Some(env) = env {
+            unsafe { syscall!(sys::DestroyEnvironment(env)) };
+        }
+        res
+    }
+
+    /// Spawns the process. The error context is captured before any other system call is made, such as to destroy `env`.
+    fn spawn_with_env(
+        &mut self,
+        env: HandlePtr<EnvironmentMapHandle>,
+    ) -> core::result::Result<CommandResult, ContextError> {
         let proc_args = self
             .args
             .iter()
             .map(Deref::deref)
             .map(KStrCPtr::from_str)
             .collect::<Vec<_>>();
+        let mut options = Vec::new();
+        if !self.limits.is_empty() {
+            options.push(CreateProcessOption {
+                resource_limits: CreateProcessOptionResourceLimits {
+                    limits: KCSlice::from_slice(&self.limits),
+                    ..CreateProcessOptionResourceLimits::NULL
+                },
+            });
+        }
+        if self.cwd_base != HandlePtr::null()
+            || self.cwd_path.is_some()
+            || self.resolution_root != HandlePtr::null()
+        {
+            options.push(CreateProcessOption {
+                working_dir: CreateProcessOptionWorkingDir {
+                    resolution_base: self.cwd_base,
+                    path: self
+                        .cwd_path
+                        .as_ref()
+                        .map_or(KStrCPtr::empty(), |path| KStrCPtr::from_str(path.as_str())),
+                    resolution_root: self.resolution_root,
+                    ..CreateProcessOptionWorkingDir::NULL
+                },
+            });
+        }
         let start_ctx = ProcessStartContext {
             prg_resolution_base: self.resolution_base,
             prg_path: KStrCPtr::from_str(self.cmd.as_str()),
-            environment: self.env,
+            environment: env,
             start_flags: self.flags.bits(),
             start_security_context: self.start_security_context,
             init_handles_len: self.init_handles.len() as c_ulong,
@@ -122,37 +287,81 @@ impl Command<'_> {
             proc_args_len: proc_args.len() as c_ulong,
             proc_args: proc_args.as_ptr(),
             init_namespace: self.namespace,
+            extended_options: KCSlice::from_slice(&options),
         };
 
         let mut hdl = MaybeUninit::uninit();
 
-        crate::result::Error::from_code(unsafe { CreateProcess(&start_ctx, hdl.as_mut_ptr()) })?;
+        ContextError::from_code(unsafe { syscall!(CreateProcess(&start_ctx, hdl.as_mut_ptr())) })?;
 
         Ok(CommandResult {
             hdl: unsafe { hdl.assume_init() },
         })
     }
 
-    unsafe fn spawn_replace_image(&mut self) -> crate::result::Result<!> {
+    unsafe fn spawn_replace_image(&mut self) -> core::result::Result<!, ContextError> {
+        let flags = self.flags;
         self.flags |= ProcessStartFlags::REPLACE_IMAGE;
-        self.spawn_with_result().map(|_| debug_unreachable())
+        let res = self.spawn_with_result().map(|_| debug_unreachable());
+        self.flags = flags;
+        res
     }
 }
 
-pub struct Stdio<'a>(
-    HandlePtr<IOHandle>,
-    PhantomData<BorrowedHandle<'a, IOHandle>>,
-);
+/// A standard stream for a spawned process, which may be passed to [`Command::stdin`], [`Command::stdout`], or [`Command::stderr`] by reference.
+pub struct Stdio<'a> {
+    hdl: HandlePtr<IOHandle>,
+    /// The restricted handle opened by [`Stdio::restricted`], which `hdl` refers to
+    _owned: Option<OwnedHandle<IOHandle>>,
+    _borrow: PhantomData<BorrowedHandle<'a, IOHandle>>,
+}
 
 impl<'a> Stdio<'a> {
     pub const fn null() -> Self {
-        Self(HandlePtr::null(), PhantomData)
+        Self {
+            hdl: HandlePtr::null(),
+            _owned: None,
+            _borrow: PhantomData,
+        }
+    }
+
+    /// A new handle to the stream `hdl`, which can only perform the operations in `chars_mask`. See [`HandleRef::duplicate_restricted`][crate::handle::HandleRef::duplicate_restricted].
+    ///
+    /// The handle is closed when the `Stdio` is dropped, which does not affect a process it has been passed to.
+    pub fn restricted<H: AsHandle<'a, IOHandle>>(hdl: H, chars_mask: IoChars) -> Result<Self> {
+        let owned = unsafe { BorrowedHandle::from_raw(hdl.as_handle()) }
+            .duplicate_restricted(chars_mask)?;
+        Ok(Self {
+            hdl: owned.as_raw(),
+            _owned: Some(owned),
+            _borrow: PhantomData,
+        })
+    }
+
+    /// A read-only view of the stream `hdl`, such as to pass a stream open for reading and writing as the standard input of a process
+    pub fn read_only<H: AsHandle<'a, IOHandle>>(hdl: H) -> Result<Self> {
+        Self::restricted(hdl, IoChars::all().difference(IoChars::WRITABLE))
+    }
+
+    /// A write-only view of the stream `hdl`
+    pub fn write_only<H: AsHandle<'a, IOHandle>>(hdl: H) -> Result<Self> {
+        Self::restricted(hdl, IoChars::all().difference(IoChars::READABLE))
     }
 }
 
 impl<'a, H: AsHandle<'a, IOHandle>> From<H> for Stdio<'a> {
     fn from(hdl: H) -> Stdio<'a> {
-        Self(hdl.as_handle(), PhantomData)
+        Self {
+            hdl: hdl.as_handle(),
+            _owned: None,
+            _borrow: PhantomData,
+        }
+    }
+}
+
+unsafe impl<'a> AsHandle<'a, IOHandle> for &'a Stdio<'_> {
+    fn as_handle(&self) -> HandlePtr<IOHandle> {
+        self.hdl
     }
 }
 
@@ -164,7 +373,13 @@ impl<'a> Command<'a> {
             env: HandlePtr::null(),
             namespace: HandlePtr::null(),
             start_security_context: HandlePtr::null(),
+            cwd_base: HandlePtr::null(),
+            cwd_path: None,
+            resolution_root: HandlePtr::null(),
             args: alloc::vec![cmd.as_ref().to_string()],
+            env_clear: false,
+            env_vars: Vec::new(),
+            limits: Vec::new(),
             init_handles: alloc::vec![
                 unsafe { __HANDLE_IO_STDIN }.cast(),
                 unsafe { __HANDLE_IO_STDOUT }.cast(),
@@ -172,6 +387,7 @@ impl<'a> Command<'a> {
             ],
             label: String::new(),
             flags: ProcessStartFlags::empty(),
+            owned: Vec::new(),
             _handles: PhantomData,
         }
     }
@@ -183,7 +399,13 @@ impl<'a> Command<'a> {
             env: HandlePtr::null(),
             namespace: HandlePtr::null(),
             start_security_context: HandlePtr::null(),
+            cwd_base: HandlePtr::null(),
+            cwd_path: None,
+            resolution_root: HandlePtr::null(),
             args: alloc::vec![cmd.as_ref().to_string()],
+            env_clear: false,
+            env_vars: Vec::new(),
+            limits: Vec::new(),
             init_handles: alloc::vec![
                 unsafe { __HANDLE_IO_STDIN }.cast(),
                 unsafe { __HANDLE_IO_STDOUT }.cast(),
@@ -191,6 +413,7 @@ impl<'a> Command<'a> {
             ],
             label: String::new(),
             flags: ProcessStartFlags::empty(),
+            owned: Vec::new(),
             _handles: PhantomData,
         }
     }
@@ -214,6 +437,208 @@ impl<'a> Command<'a> {
         self.init_handles[2] = hdl.as_handle().cast();
         self
     }
+
+    /// Sets the security context the process starts with, instead of the security context of the current thread.
+    ///
+    /// See [`ProcessStartContext::start_security_context`] for how this interacts with privilaged programs.
+    pub fn security_context<P: AsHandle<'a, SecurityContext>>(&mut self, ctx: P) -> &mut Self {
+        self.start_security_context = ctx.as_handle();
+        self
+    }
+
+    /// Appends an argument to pass to the process
+    pub fn arg<S: AsRef<str>>(&mut self, arg: S) -> &mut Self {
+        self.args.push(arg.as_ref().to_string());
+        self
+    }
+
+    /// Appends each argument in `args` to pass to the process
+    pub fn args<I: IntoIterator>(&mut self, args: I) -> &mut Self
+    where
+        I::Item: AsRef<str>,
+    {
+        self.args
+            .extend(args.into_iter().map(|arg| arg.as_ref().to_string()));
+        self
+    }
+
+    /// Sets the first argument passed to the process, which defaults to the command
+    pub fn arg0<S: AsRef<str>>(&mut self, arg0: S) -> &mut Self {
+        self.args[0] = arg0.as_ref().to_string();
+        self
+    }
+
+    /// Sets the environment variable `key` to `val` in the process
+    pub fn env<K: AsRef<str>, V: AsRef<str>>(&mut self, key: K, val: V) -> &mut Self {
+        self.env_vars
+            .push((key.as_ref().to_string(), val.as_ref().to_string()));
+        self
+    }
+
+    /// Sets each environment variable in `vars` in the process
+    pub fn envs<I: IntoIterator<Item = (K, V)>, K: AsRef<str>, V: AsRef<str>>(
+        &mut self,
+        vars: I,
+    ) -> &mut Self {
+        for (key, val) in vars {
+            self.env(key, val);
+        }
+        self
+    }
+
+    /// Starts the process with an empty environment, except for variables set by [`Command::env`] or [`Command::envs`]
+    pub fn env_clear(&mut self) -> &mut Self {
+        self.env_clear = true;
+        self.env_vars.clear();
+        self
+    }
+
+    /// Starts the process with the environment map `env` instead of the environment of the current process.
+    ///
+    /// Variables set by [`Command::env`] or [`Command::envs`] are applied to a copy of `env`, which is not modified.
+    pub fn environment<P: AsHandle<'a, EnvironmentMapHandle>>(&mut self, env: P) -> &mut Self {
+        self.env = env.as_handle();
+        self.env_clear = false;
+        self
+    }
+
+    /// Sets the current directory of the process to `path`, resolved against the current directory of the current thread
+    pub fn current_dir<P: AsRef<Path>>(&mut self, path: P) -> &mut Self {
+        self.cwd_base = HandlePtr::null();
+        self.cwd_path = Some(path.as_ref().to_path_buf());
+        self
+    }
+
+    /// Sets the current directory of the process to the directory `dir`
+    pub fn current_dir_handle<P: AsHandle<'a, FileHandle>>(&mut self, dir: P) -> &mut Self {
+        self.cwd_base = dir.as_handle();
+        self.cwd_path = None;
+        self
+    }
+
+    /// Sets the resolution root of the process to `root`, so that absolute paths in the process are resolved against `root` instead of the root of the current namespace.
+    ///
+    /// Note that this is not a security boundary: `..` components can still leave `root`.
+    pub fn chroot_like_root<P: AsHandle<'a, FileHandle>>(&mut self, root: P) -> &mut Self {
+        self.resolution_root = root.as_handle();
+        self
+    }
+
+    /// Sets the label of the process, which identifies it when enumerating processes
+    pub fn label<S: AsRef<str>>(&mut self, label: S) -> &mut Self {
+        self.label = label.as_ref().to_string();
+        self
+    }
+
+    /// Places the process in the namespace `ns`, instead of the namespace of the current process
+    pub fn namespace<P: AsHandle<'a, NamespaceHandle>>(&mut self, ns: P) -> &mut Self {
+        self.namespace = ns.as_handle();
+        self
+    }
+
+    /// Replaces the image running in the current process with the program, as though by [`ProcessStartFlags::REPLACE_IMAGE`].
+    ///
+    /// On success, this function does not return, and every thread in the current process is terminated.
+    /// Otherwise, the error that prevented the program from being loaded is returned, and the current process is unaffected.
+    pub fn exec(&mut self) -> crate::result::Error {
+        self.exec_with_context().error()
+    }
+
+    /// Same as [`Command::exec`], but also returns the context the kernel recorded for the error, such as the extended option or path component that was rejected
+    pub fn exec_with_context(&mut self) -> ContextError {
+        match unsafe { self.spawn_replace_image() } {
+            Ok(never) => never,
+            Err(e) => e,
+        }
+    }
+
+    /// Limits the amount of `limit` the process can use to `value`.
+    ///
+    /// Limits greater than the corresponding limit of the current process require the [`SET_PROCESS_LIMITS`][crate::security::kernel_perm::SET_PROCESS_LIMITS] kernel permission.
+    pub fn limit(&mut self, limit: Limit, value: u64) -> &mut Self {
+        self.limits.push(ProcessResourceLimit {
+            limit: limit.into_uuid(),
+            value,
+        });
+        self
+    }
+
+    /// Sets the flags used to start the process
+    pub fn flags(&mut self, flags: ProcessStartFlags) -> &mut Self {
+        self.flags = flags;
+        self
+    }
+
+    /// Spawns the process, and returns a [`Child`] to wait for it with.
+    ///
+    /// ## Errors
+    /// Returns an error if the process cannot be spawned, with the context recorded by the kernel.
+    pub fn spawn(&mut self) -> core::result::Result<Child, ContextError> {
+        self.spawn_with_result().map(|res| Child { hdl: res.hdl })
+    }
+
+    /// Spawns the process suspended, as though by [`ProcessStartFlags::START_SUSPENDED`], and attaches a [`Debugger`] to its initial thread.
+    ///
+    /// The process does not run until it is resumed by the debugger, such as by [`Debugger::resume`], so the debugger observes it from its first instruction.
+    ///
+    /// ## Errors
+    /// Returns an error if the process cannot be spawned, or the debugger cannot be attached. In the latter case, the process is terminated before the error is returned.
+    /// The error has the context recorded by the kernel if the process cannot be spawned.
+    pub fn traced(&mut self) -> core::result::Result<(Child, Debugger), ContextError> {
+        let flags = self.flags;
+        self.flags |= ProcessStartFlags::START_SUSPENDED;
+        let res = self.spawn_with_result();
+        self.flags = flags;
+        let child = Child { hdl: res?.hdl };
+
+        let attach = || {
+            let mut th = MaybeUninit::uninit();
+            crate::result::Error::from_code(unsafe {
+                syscall!(sys::GetInitialThread(child.hdl, th.as_mut_ptr()))
+            })?;
+            let th = unsafe { OwnedHandle::<ThreadHandle>::take_ownership(th.assume_init()) };
+            Debugger::attach(&th)
+        };
+
+        match attach() {
+            Ok(debugger) => Ok((child, debugger)),
+            Err(e) => {
+                let _ = child.kill();
+                Err(e.into())
+            }
+        }
+    }
+}
+
+/// A process spawned by [`Command::spawn`] or [`Command::traced`].
+///
+/// If the `Child` is dropped without being waited for, the process is detached, and continues to run.
+#[derive(Debug)]
+pub struct Child {
+    hdl: HandlePtr<ProcessHandle>,
+}
+
+impl Child {
+    pub fn as_raw(&self) -> HandlePtr<ProcessHandle> {
+        self.hdl
+    }
+
+    /// Terminates the process, as though by an unmanaged `RemoteStop` exception
+    pub fn kill(&self) -> crate::result::Result<()> {
+        crate::result::Error::from_code(unsafe { syscall!(sys::TerminateProcess(self.hdl)) })
+    }
+
+    /// Waits for the process to exit, and returns its exit status
+    pub fn wait(self) -> crate::result::Result<ExitStatus> {
+        let this = ManuallyDrop::new(self);
+        CommandResult { hdl: this.hdl }.join().map(ExitStatus)
+    }
+}
+
+impl Drop for Child {
+    fn drop(&mut self) {
+        let _ = CommandResult { hdl: self.hdl }.detach();
+    }
 }
 
 pub struct ProcessIterator {
@@ -245,11 +670,31 @@ impl ExitStatus {
     }
 }
 
+impl ExitStatus {
+    /// Interprets the result of joining a process, given the exception information written by the kernel if the result is `SIGNALED`
+    pub(crate) fn from_join(
+        ret: crate::sys::result::SysResult,
+        termsiginfo: ExceptionStatusInfo,
+    ) -> crate::result::Result<Self> {
+        match crate::result::Error::from_code(ret) {
+            Ok(()) => Ok(Self(CommandStatus::Normal(ret as i32))),
+            Err(crate::result::Error::Signaled) => {
+                Ok(Self(CommandStatus::UnmanagedException(termsiginfo)))
+            }
+            Err(crate::result::Error::Killed) => Ok(Self(CommandStatus::Killed)),
+            Err(e) => Err(e),
+        }
+    }
+}
+
 impl ExitStatus {
     pub fn throw_except(&self) -> crate::result::Result<()> {
         if let CommandStatus::UnmanagedException(except) = &self.0 {
             crate::result::Error::from_code(unsafe {
-                crate::sys::except::ExceptHandleSynchronous(except, core::ptr::null())
+                syscall!(crate::sys::except::ExceptHandleSynchronous(
+                    except,
+                    core::ptr::null()
+                ))
             })
         } else {
             Ok(())
@@ -297,7 +742,10 @@ impl ExitStatusError {
     pub fn throw_except(&self) -> crate::result::Result<()> {
         if let CommandStatus::UnmanagedException(except) = &self.0 {
             crate::result::Error::from_code(unsafe {
-                crate::sys::except::ExceptHandleSynchronous(except, core::ptr::null())
+                syscall!(crate::sys::except::ExceptHandleSynchronous(
+                    except,
+                    core::ptr::null()
+                ))
             })
         } else {
             Ok(())
@@ -337,3 +785,69 @@ impl From<ExitStatusError> for ExitStatus {
         ExitStatus(value.0)
     }
 }
+
+/// The handles passed to the current process by its parent, as given by [`init_handles`].
+///
+/// By convention, the first three handles are the standard input, standard output, and standard error streams.
+///
+/// The handles are owned by the initial thread of the process, and can only be used on that thread.
+#[derive(Copy, Clone, Debug)]
+pub struct InitHandles {
+    handles: &'static [HandlePtr<Handle>],
+}
+
+impl InitHandles {
+    /// The number of init handles
+    pub fn len(&self) -> usize {
+        self.handles.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.handles.is_empty()
+    }
+
+    /// Gets the init handle at `idx`, or `None` if `idx` is out of range or the handle is null.
+    pub fn get(&self, idx: usize) -> Option<BorrowedHandle<'static, Handle>> {
+        self.get_as(idx)
+    }
+
+    /// Gets the init handle at `idx` as a handle of type `T`, or `None` if `idx` is out of range or the handle is null.
+    ///
+    /// The type of the handle is not checked. Operations on a handle of the wrong type fail with [`Error::InvalidHandle`][crate::result::Error::InvalidHandle].
+    pub fn get_as<T>(&self, idx: usize) -> Option<BorrowedHandle<'static, T>> {
+        self.handles
+            .get(idx)
+            .copied()
+            .filter(|hdl| *hdl != HandlePtr::null())
+            .map(|hdl| unsafe { BorrowedHandle::from_raw(hdl.cast()) })
+    }
+
+    /// The standard input stream passed to the process
+    pub fn stdin(&self) -> Option<BorrowedHandle<'static, IOHandle>> {
+        self.get_as(0)
+    }
+
+    /// The standard output stream passed to the process
+    pub fn stdout(&self) -> Option<BorrowedHandle<'static, IOHandle>> {
+        self.get_as(1)
+    }
+
+    /// The standard error stream passed to the process
+    pub fn stderr(&self) -> Option<BorrowedHandle<'static, IOHandle>> {
+        self.get_as(2)
+    }
+
+    /// Iterates over the init handles, including null handles
+    pub fn iter(&self) -> impl Iterator<Item = HandlePtr<Handle>> + 'static {
+        self.handles.iter().copied()
+    }
+}
+
+/// Gets the handles passed to the current process by its parent, from the [`AT_LILIUM_INIT_HANDLES`][crate::sys::elf::AT_LILIUM_INIT_HANDLES] entry of the auxiliary vector.
+///
+/// If the entry is not present, no handles are returned.
+pub fn init_handles() -> InitHandles {
+    InitHandles {
+        handles: crate::os::auxv::init_handles().unwrap_or(&[]),
+    }
+}
@@ -0,0 +1,123 @@
+use alloc::vec::Vec;
+
+use crate::{
+    result::{Error, Result},
+    sync::event::{self, Event, ProcessExit, TypedEvent},
+    sys::{
+        handle::HandlePtr,
+        process::{DetachProcess, ProcessHandle, TerminateProcess},
+    },
+};
+
+use super::{Command, ExitStatus};
+
+/// A group of child processes that are managed together.
+///
+/// By default, every process still in the job is terminated when the job is dropped. Use [`Job::terminate_on_drop`] to detach them instead.
+#[derive(Debug)]
+pub struct Job {
+    children: Vec<HandlePtr<ProcessHandle>>,
+    terminate_on_drop: bool,
+}
+
+impl Job {
+    pub const fn new() -> Self {
+        Self {
+            children: Vec::new(),
+            terminate_on_drop: true,
+        }
+    }
+
+    /// Sets whether the processes in the job are terminated when it is dropped.
+    /// If `false`, the processes are detached and continue running.
+    pub fn terminate_on_drop(&mut self, terminate: bool) -> &mut Self {
+        self.terminate_on_drop = terminate;
+        self
+    }
+
+    /// Spawns `cmd` as a process in the job
+    pub fn spawn(&mut self, cmd: &mut Command) -> Result<()> {
+        let res = cmd.spawn_with_result()?;
+        self.children.push(res.hdl);
+        Ok(())
+    }
+
+    /// The number of processes in the job
+    pub fn len(&self) -> usize {
+        self.children.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.children.is_empty()
+    }
+
+    /// Terminates every process in the job, as though by an unmanaged `RemoteStop` exception.
+    ///
+    /// The processes remain in the job until they are joined by [`Job::join_all`].
+    /// If terminating any process fails, the remaining processes are still terminated, and the first error is returned.
+    pub fn terminate_all(&self) -> Result<()> {
+        let mut res = Ok(());
+        for &hdl in &self.children {
+            let r = Error::from_code(unsafe { syscall!(TerminateProcess(hdl)) });
+            if res.is_ok() {
+                res = r;
+            }
+        }
+        res
+    }
+
+    /// Waits for every process in the job to exit, and returns the exit status of each, in the order they were spawned.
+    ///
+    /// On success, the job is empty afterwards. If waiting fails (for example, because the current thread was interrupted), every process remains in the job.
+    /// If releasing the handle to an exited process fails, that process remains in the job, and the error is returned.
+    pub fn join_all(&mut self) -> Result<Vec<ExitStatus>> {
+        let exits = self
+            .children
+            .iter()
+            .map(|&hdl| ProcessExit::new(hdl))
+            .collect::<Vec<_>>();
+        let events = exits.iter().map(|e| e as &dyn Event).collect::<Vec<_>>();
+        let results = event::block_on_all(&events)?;
+
+        let status = exits
+            .iter()
+            .zip(results)
+            .map(|(exit, result)| exit.output(result))
+            .collect::<Result<Vec<_>>>();
+
+        // The handles remain valid after the processes exit, so they are released here
+        let mut res = Ok(());
+        self.children.retain(|&hdl| {
+            match Error::from_code(unsafe { syscall!(DetachProcess(hdl)) }) {
+                Ok(()) => false,
+                Err(e) => {
+                    if res.is_ok() {
+                        res = Err(e);
+                    }
+                    true
+                }
+            }
+        });
+        res?;
+        status
+    }
+}
+
+impl Default for Job {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for Job {
+    fn drop(&mut self) {
+        if self.terminate_on_drop {
+            let _ = self.terminate_all();
+        }
+        for &hdl in &self.children {
+            unsafe {
+                syscall!(DetachProcess(hdl));
+            }
+        }
+    }
+}
@@ -0,0 +1,132 @@
+use core::sync::atomic::{AtomicBool, Ordering};
+
+use alloc::sync::Arc;
+
+use crate::{
+    except::{registry::Registry, Exception, ExceptionContext, HookAction},
+    result::Result,
+    sys::{
+        except::{
+            self as sys, ExceptionStatusInfo, EXCEPT_PROCESS_ABORT, EXCEPT_REMOTE_STOP,
+            EXCEPT_SIGNAL_HANGUP, EXCEPT_SIGNAL_INTERRUPT, EXCEPT_SIGNAL_TERMINATE,
+        },
+        process::ExitProcess,
+    },
+};
+
+/// Why the process is terminating, passed to the hooks registered by [`on_termination`]
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum TerminationReason {
+    /// [`exit`] was called with the given code
+    Exit(i32),
+    /// An interrupt was requested, such as by `Ctrl+C` (`SIGINT`)
+    Interrupt,
+    /// Termination was requested (`SIGTERM`)
+    Terminate,
+    /// The controlling terminal was closed (`SIGHUP`)
+    Hangup,
+    /// The thread that received the exception was asked to stop
+    RemoteStop,
+    /// The process was aborted with the given exit code
+    Abort(u64),
+}
+
+impl TerminationReason {
+    fn from_exception(except: &Exception) -> Option<Self> {
+        Some(match except.code() {
+            EXCEPT_SIGNAL_INTERRUPT => Self::Interrupt,
+            EXCEPT_SIGNAL_TERMINATE => Self::Terminate,
+            EXCEPT_SIGNAL_HANGUP => Self::Hangup,
+            EXCEPT_REMOTE_STOP => Self::RemoteStop,
+            EXCEPT_PROCESS_ABORT => Self::Abort(except.info()),
+            _ => return None,
+        })
+    }
+}
+
+type TerminationFn = dyn Fn(TerminationReason) + Send + Sync;
+
+static HOOKS: Registry<TerminationFn> = Registry::new();
+static TERMINATING: AtomicBool = AtomicBool::new(false);
+
+/// Runs the termination hooks, unless they have already been run
+fn run_hooks(reason: TerminationReason) {
+    if TERMINATING.swap(true, Ordering::AcqRel) {
+        return;
+    }
+    HOOKS.read(|hooks| {
+        for hook in hooks {
+            hook(reason);
+        }
+    });
+}
+
+fn termination_hook(except: &Exception, _: &mut ExceptionContext) -> HookAction {
+    if let Some(reason) = TerminationReason::from_exception(except) {
+        run_hooks(reason);
+    }
+    HookAction::Continue
+}
+
+/// Removes a hook registered by [`on_termination`] when dropped.
+#[must_use = "the hook is removed immediately if the handle is dropped"]
+#[derive(Debug)]
+pub struct TerminationHook(usize);
+
+impl TerminationHook {
+    /// Keeps the hook registered for the remainder of the process
+    pub fn forget(self) {
+        core::mem::forget(self)
+    }
+}
+
+impl Drop for TerminationHook {
+    fn drop(&mut self) {
+        HOOKS.remove(self.0);
+    }
+}
+
+/// Registers `hook` to be called once before the process terminates, such as to flush buffers, remove temporary files, or restore the mode of a terminal.
+///
+/// The hooks are called in the order they were registered, on the first of:
+/// * A call to [`exit`],
+/// * An interrupt, termination, or hangup signal (see [`except::signals`][crate::except::signals]),
+/// * A remote stop or process abort exception.
+///
+/// The exception then has its usual effect, which is to terminate the thread or process (unless a [hook][crate::except::register_hook] registered earlier resumes the thread,
+///  or it is caught by [`catch_exception`][crate::except::catch_exception]). Hooks are not called again if the process continues running.
+/// They are not called if the process is terminated in a way that cannot be intercepted, such as by [`abort`], or if the last thread exits normally.
+///
+/// Signals and exceptions are only intercepted on threads that have installed the exception handler, as by [`install_handler`][crate::except::install_handler].
+/// The calling thread installs it, and asynchronous signals are received by the signal handling thread of the process.
+///
+/// A hook must not register or remove termination hooks.
+pub fn on_termination<F: Fn(TerminationReason) + Send + Sync + 'static>(
+    hook: F,
+) -> Result<TerminationHook> {
+    let id = HOOKS.register_with_hook(termination_hook, Arc::new(hook))?;
+    Ok(TerminationHook(id))
+}
+
+/// Runs the hooks registered by [`on_termination`], then exits the process with `code`.
+///
+/// Other threads are terminated without unwinding. Destructors are not run for values on the stack of any thread, or for thread-local values.
+/// Values that need to be cleaned up (such as buffered output, which [`stdout`][crate::io::stdio::stdout] flushes when its lock is dropped) should be cleaned up by a termination hook, or before calling `exit`.
+pub fn exit(code: i32) -> ! {
+    run_hooks(TerminationReason::Exit(code));
+    unsafe { syscall!(!ExitProcess(code as u32)) }
+}
+
+/// Aborts the process immediately, by reporting a process abort exception to the kernel as unmanaged.
+///
+/// Neither the hooks registered by [`on_termination`], nor exception handlers, nor any destructors are run.
+pub fn abort() -> ! {
+    let status = ExceptionStatusInfo {
+        except_code: EXCEPT_PROCESS_ABORT,
+        // The exit status POSIX shells report for a process terminated by `SIGABRT`
+        except_info: 134,
+        except_reason: 0,
+    };
+    unsafe { syscall!(!sys::UnmanagedException(&status)) }
+}
@@ -1,6 +1,23 @@
+//! Random number generation from the kernel's random devices.
+//!
+//! The free functions in this module read from [`RandomDevice::SYSRANDOM`], which draws from the global entropy pool.
+//! Reads from the pool do not block: if the pool is exhausted, [`fill`] returns [`Error::DeviceUnavailable`] immediately,
+//!  while [`fill_exact`] yields the current thread and retries until the pool has been replenished.
+//!
+//! With the `getrandom-backend` feature, the global entropy pool is registered as the custom backend of the `getrandom` crate,
+//!  so crates that obtain randomness through `getrandom` (such as `rand`, or hash map seeding) work on Lilium.
+
 use core::ffi::c_void;
 
-use crate::uuid::Uuid;
+use crate::{
+    device::Device,
+    result::{Error, Result},
+    sys::{
+        random::{GetDeviceRandomBytes, SeedDeviceRandomBytes, SeedRandomBytes},
+        thread::YieldThread,
+    },
+    uuid::Uuid,
+};
 
 #[derive(Copy, Clone, Debug)]
 pub struct RandomDevice(Uuid);
@@ -12,10 +29,150 @@ impl RandomDevice {
 
     pub const SYSRANDOM: Self = Self(crate::sys::random::RANDOM_DEVICE);
 
+    /// The device id of the random device
+    pub const fn device_id(&self) -> Uuid {
+        self.0
+    }
+
+    /// Fills `bytes` with random data from the device.
+    ///
+    /// Returns [`Error::DeviceUnavailable`] without blocking if the device's entropy pool is exhausted.
     pub fn read_bytes(&self, bytes: &mut [u8]) -> crate::result::Result<()> {
         let len = bytes.len();
         crate::result::Error::from_code(unsafe {
             crate::sys::random::GetRandomBytes(bytes as *mut [u8] as *mut c_void, len, &self.0)
         })
     }
+
+    /// Fills `bytes` with random data from the device, yielding the current thread and retrying while the device's entropy pool is exhausted.
+    pub fn read_bytes_exact(&self, bytes: &mut [u8]) -> Result<()> {
+        loop {
+            match self.read_bytes(bytes) {
+                Err(Error::DeviceUnavailable) => unsafe { YieldThread() },
+                r => break r,
+            }
+        }
+    }
+
+    /// Reseeds the device with `seed`. The exact effect is device-specific.
+    ///
+    /// For [`RandomDevice::SYSRANDOM`], `seed` is digested and mixed into the global entropy pool,
+    ///  which requires the [`WRITE_ENTROPY_POOL`][crate::security::kernel_perm::WRITE_ENTROPY_POOL] kernel permission.
+    ///
+    /// Returns [`Error::InvalidOperation`] if the device does not support reseeding,
+    ///  and [`Error::Permission`] if the current thread is denied the `SeedRandomBytes` right on the device.
+    pub fn reseed(&self, seed: &[u8]) -> Result<()> {
+        Error::from_code(unsafe { SeedRandomBytes(seed.as_ptr().cast(), seed.len(), &self.0) })
+    }
 }
+
+impl Device {
+    /// Fills `bytes` with random data from the device, which must support the `RandomBytes` feature.
+    ///
+    /// Returns [`Error::Permission`] if the handle does not have the [`GetRandomBytes`][crate::handle::DeviceRight::GetRandomBytes] right.
+    pub fn read_random_bytes(&self, bytes: &mut [u8]) -> Result<()> {
+        Error::from_code(unsafe {
+            GetDeviceRandomBytes(self.as_raw(), bytes.as_mut_ptr().cast(), bytes.len())
+        })
+    }
+
+    /// Reseeds the device, which must support the `RandomBytes` feature, with `seed`.
+    ///
+    /// Returns [`Error::Permission`] if the handle does not have the [`SeedRandomBytes`][crate::handle::DeviceRight::SeedRandomBytes] right.
+    pub fn seed_random_bytes(&self, seed: &[u8]) -> Result<()> {
+        Error::from_code(unsafe {
+            SeedDeviceRandomBytes(self.as_raw(), seed.as_ptr().cast(), seed.len())
+        })
+    }
+}
+
+/// Fills `bytes` with random data from the global entropy pool.
+///
+/// Returns [`Error::DeviceUnavailable`] without blocking if the pool is exhausted.
+pub fn fill(bytes: &mut [u8]) -> Result<()> {
+    RandomDevice::SYSRANDOM.read_bytes(bytes)
+}
+
+/// Fills `bytes` with random data from the global entropy pool, waiting for the pool to be replenished if it is exhausted.
+pub fn fill_exact(bytes: &mut [u8]) -> Result<()> {
+    RandomDevice::SYSRANDOM.read_bytes_exact(bytes)
+}
+
+/// Generates a random `u64`, waiting for the global entropy pool to be replenished if it is exhausted.
+pub fn u64() -> Result<u64> {
+    let mut bytes = [0; 8];
+    fill_exact(&mut bytes)?;
+    Ok(u64::from_ne_bytes(bytes))
+}
+
+/// Generates a random (version 4) [`Uuid`], waiting for the global entropy pool to be replenished if it is exhausted.
+pub fn uuid() -> Result<Uuid> {
+    Uuid::new_v4()
+}
+
+/// The system random number generator, which reads from [`RandomDevice::SYSRANDOM`].
+///
+/// With the `rand_core` feature, this implements [`rand_core::RngCore`] and [`rand_core::CryptoRng`].
+/// The infallible methods of [`RngCore`][rand_core::RngCore] wait for the entropy pool to be replenished if it is exhausted, and panic on any other error.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct SystemRandom;
+
+impl SystemRandom {
+    pub const fn new() -> Self {
+        Self
+    }
+
+    /// Reseeds the global entropy pool with `seed`. See [`RandomDevice::reseed`].
+    pub fn reseed(&self, seed: &[u8]) -> Result<()> {
+        RandomDevice::SYSRANDOM.reseed(seed)
+    }
+}
+
+/// Encodes `e` as a custom error code for the `rand_core` and `getrandom` error types, which share the same custom range
+#[cfg(any(feature = "rand_core", feature = "getrandom-backend"))]
+fn custom_error_code(e: Error, custom_start: u32) -> core::num::NonZeroU32 {
+    let code = (e.into_code() as u32).wrapping_neg();
+    core::num::NonZeroU32::new(custom_start.wrapping_add(code)).unwrap()
+}
+
+#[cfg(feature = "rand_core")]
+fn rand_error(e: Error) -> rand_core::Error {
+    custom_error_code(e, rand_core::Error::CUSTOM_START).into()
+}
+
+#[cfg(feature = "rand_core")]
+impl rand_core::RngCore for SystemRandom {
+    fn next_u32(&mut self) -> u32 {
+        let mut bytes = [0; 4];
+        self.fill_bytes(&mut bytes);
+        u32::from_ne_bytes(bytes)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        let mut bytes = [0; 8];
+        self.fill_bytes(&mut bytes);
+        u64::from_ne_bytes(bytes)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        if let Err(e) = fill_exact(dest) {
+            panic!("Failed to read from the system random device: {:?}", e)
+        }
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> core::result::Result<(), rand_core::Error> {
+        fill_exact(dest).map_err(rand_error)
+    }
+}
+
+#[cfg(feature = "rand_core")]
+impl rand_core::CryptoRng for SystemRandom {}
+
+/// The `getrandom` custom backend, which fills the buffer from the global entropy pool as by [`fill_exact`].
+#[cfg(feature = "getrandom-backend")]
+fn getrandom_backend(dest: &mut [u8]) -> core::result::Result<(), getrandom::Error> {
+    fill_exact(dest).map_err(|e| custom_error_code(e, getrandom::Error::CUSTOM_START).into())
+}
+
+#[cfg(feature = "getrandom-backend")]
+getrandom::register_custom_getrandom!(getrandom_backend);
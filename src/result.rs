@@ -1,4 +1,12 @@
-use crate::sys::result::SysResult;
+use alloc::{string::String, vec::Vec};
+
+use crate::{
+    sys::{error as sys, result::SysResult},
+    uuid::Uuid,
+};
+
+#[cfg(feature = "errno-compat")]
+pub mod errno;
 
 pub type Result<T> = core::result::Result<T, Error>;
 
@@ -20,6 +28,13 @@ macro_rules! error_def{
                         x => Err(Self::Unknown(x))
                     }
                 }
+
+                pub const fn into_code(self) -> SysResult{
+                    match self{
+                        Self::Unknown(x) => x,
+                        $(Self::[<$name:camel>] => crate::sys::result::errors::$name,)*
+                    }
+                }
             }
         }
 
@@ -30,3 +45,358 @@ with_builtin_macros::with_builtin! {
         error_def!{$file}
     }
 }
+
+/// Errors are serialized as their error code
+#[cfg(feature = "serde")]
+impl serde::Serialize for Error {
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> core::result::Result<S::Ok, S::Error> {
+        serializer.serialize_i64(self.into_code() as i64)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Error {
+    fn deserialize<D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> core::result::Result<Self, D::Error> {
+        let code = <SysResult as serde::Deserialize>::deserialize(deserializer)?;
+        match Error::from_code(code) {
+            Ok(()) => Err(serde::de::Error::invalid_value(
+                serde::de::Unexpected::Signed(code as i64),
+                &"a negative error code",
+            )),
+            Err(e) => Ok(e),
+        }
+    }
+}
+
+impl core::fmt::Display for Error {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Unknown(code) => f.write_fmt(format_args!("Unknown error ({})", code)),
+            e => f.write_fmt(format_args!("{:?} ({})", e, e.into_code())),
+        }
+    }
+}
+
+impl core::error::Error for Error {}
+
+/// A general category of [`Error`], for handling errors without matching on every error code
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// The object does not exist
+    NotFound,
+    /// The operation was denied by the security policy of the thread
+    PermissionDenied,
+    /// The object already exists
+    AlreadyExists,
+    /// The operation would need to block, and blocking was not requested
+    WouldBlock,
+    /// The operation was interrupted before it completed
+    Interrupted,
+    /// The operation did not complete before its timeout elapsed
+    TimedOut,
+    /// A parameter passed to the operation was invalid
+    InvalidInput,
+    /// The object is in use
+    ResourceBusy,
+    /// The device has no space left
+    StorageFull,
+    /// Not enough memory was available to complete the operation
+    OutOfMemory,
+    /// The operation is not supported by the object or the kernel
+    Unsupported,
+    /// The other end of a stream or connection was closed
+    BrokenPipe,
+    /// The connection was interrupted
+    ConnectionReset,
+    /// Any other error
+    Other,
+}
+
+impl Error {
+    /// The general category of the error
+    pub const fn kind(self) -> ErrorKind {
+        match self {
+            Error::Permission | Error::PrivilegeCheckFailed => ErrorKind::PermissionDenied,
+            Error::DoesNotExist => ErrorKind::NotFound,
+            Error::AlreadyExists => ErrorKind::AlreadyExists,
+            Error::Interrupted | Error::Signaled => ErrorKind::Interrupted,
+            Error::Timeout => ErrorKind::TimedOut,
+            Error::WouldBlock => ErrorKind::WouldBlock,
+            Error::InvalidString | Error::InvalidOption => ErrorKind::InvalidInput,
+            Error::Busy => ErrorKind::ResourceBusy,
+            Error::DeviceFull => ErrorKind::StorageFull,
+            Error::InsufficientMemory => ErrorKind::OutOfMemory,
+            Error::UnsupportedOperation | Error::UnsupportedKernelFunction => {
+                ErrorKind::Unsupported
+            }
+            Error::ClosedRemotely => ErrorKind::BrokenPipe,
+            Error::ConnectionInterrupted => ErrorKind::ConnectionReset,
+            _ => ErrorKind::Other,
+        }
+    }
+}
+
+impl Error {
+    /// Converts `code`, which is a size or offset on success, to a result.
+    ///
+    /// This is equivalent to `code.into_size()` (see [`ResultExt::into_size`]).
+    pub const fn check_size(code: SysResult) -> Result<u64> {
+        match Self::from_code(code) {
+            Ok(()) => Ok(code as u64),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+mod private {
+    pub trait Sealed {}
+}
+
+impl private::Sealed for SysResult {}
+
+/// Decodes the value returned by a system call that encodes a value in a non-negative [`SysResult`], and an error in a negative one.
+pub trait ResultExt: private::Sealed {
+    /// Converts the result to the value of type `T` it encodes, or the error it encodes.
+    ///
+    /// Returns [`Error::InvalidState`] if the value is not in range of `T`, which indicates the system call does not return a `T`.
+    fn into_value<T: TryFrom<SysResult>>(self) -> Result<T>;
+
+    /// Converts the result to the index (such as of an event or element of an array) it encodes, or the error it encodes.
+    fn into_index(self) -> Result<usize>;
+
+    /// Converts the result to the size or offset it encodes, or the error it encodes. See [`Error::check_size`].
+    fn into_size(self) -> Result<u64>;
+}
+
+impl ResultExt for SysResult {
+    fn into_value<T: TryFrom<SysResult>>(self) -> Result<T> {
+        Error::from_code(self)?;
+        T::try_from(self).map_err(|_| Error::InvalidState)
+    }
+
+    fn into_index(self) -> Result<usize> {
+        Error::from_code(self).map(|()| self as usize)
+    }
+
+    fn into_size(self) -> Result<u64> {
+        Error::check_size(self)
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<ErrorKind> for std::io::ErrorKind {
+    fn from(kind: ErrorKind) -> Self {
+        match kind {
+            ErrorKind::NotFound => Self::NotFound,
+            ErrorKind::PermissionDenied => Self::PermissionDenied,
+            ErrorKind::AlreadyExists => Self::AlreadyExists,
+            ErrorKind::WouldBlock => Self::WouldBlock,
+            ErrorKind::Interrupted => Self::Interrupted,
+            ErrorKind::TimedOut => Self::TimedOut,
+            ErrorKind::InvalidInput => Self::InvalidInput,
+            ErrorKind::ResourceBusy => Self::ResourceBusy,
+            ErrorKind::StorageFull => Self::StorageFull,
+            ErrorKind::OutOfMemory => Self::OutOfMemory,
+            ErrorKind::Unsupported => Self::Unsupported,
+            ErrorKind::BrokenPipe => Self::BrokenPipe,
+            ErrorKind::ConnectionReset => Self::ConnectionReset,
+            ErrorKind::Other => Self::Other,
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<Error> for std::io::Error {
+    fn from(e: Error) -> Self {
+        std::io::Error::new(e.kind().into(), e)
+    }
+}
+
+/// An entry of context describing why a system call failed, recorded by the kernel. See [`Error::with_context`].
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ErrorContext {
+    /// The option at `index` of the options passed to the system call, of type `option_type`, was invalid or unsupported
+    InvalidOption { index: usize, option_type: Uuid },
+    /// The permission `permission` was denied
+    Permission {
+        permission: String,
+        /// The principal the permission was checked for, or [`Uuid::NIL`] if the kernel did not report it
+        principal: Uuid,
+    },
+    /// Resolving the path failed at the component `component`, which is at `component_index` (starting from `0`) in the path
+    PathResolution {
+        component_index: usize,
+        component: String,
+    },
+    /// An entry of a kind not known to this crate
+    Other {
+        kind: Uuid,
+        subject: Uuid,
+        index: usize,
+        name: String,
+    },
+}
+
+impl ErrorContext {
+    fn from_raw(entry: &sys::ErrorContextEntry, name: String) -> Self {
+        match entry.kind {
+            sys::ERROR_CONTEXT_INVALID_OPTION => Self::InvalidOption {
+                index: entry.index,
+                option_type: entry.subject,
+            },
+            sys::ERROR_CONTEXT_PERMISSION => Self::Permission {
+                permission: name,
+                principal: entry.subject,
+            },
+            sys::ERROR_CONTEXT_PATH_RESOLUTION => Self::PathResolution {
+                component_index: entry.index,
+                component: name,
+            },
+            kind => Self::Other {
+                kind,
+                subject: entry.subject,
+                index: entry.index,
+                name,
+            },
+        }
+    }
+}
+
+impl core::fmt::Display for ErrorContext {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::InvalidOption { index, option_type } => f.write_fmt(format_args!(
+                "invalid option {} (of type {})",
+                index, option_type
+            )),
+            Self::Permission { permission, .. } => {
+                f.write_fmt(format_args!("permission {} denied", permission))
+            }
+            Self::PathResolution {
+                component_index,
+                component,
+            } => f.write_fmt(format_args!(
+                "could not resolve path component {} ({:?})",
+                component_index, component
+            )),
+            Self::Other {
+                kind, index, name, ..
+            } => f.write_fmt(format_args!("{} (index {}, {:?})", kind, index, name)),
+        }
+    }
+}
+
+/// Reads the error context recorded for the last failed system call on the current thread that returned `code`
+fn read_error_context(code: SysResult) -> Vec<ErrorContext> {
+    let count = unsafe { sys::GetErrorContextCount() };
+
+    (0..count.max(0) as usize)
+        .filter_map(|index| {
+            let mut entry = None;
+            let name = crate::kstr::with_growing_buffer(32, |kstr| {
+                let mut raw = sys::ErrorContextEntry {
+                    kind: Uuid::NIL,
+                    subject: Uuid::NIL,
+                    code: 0,
+                    index: 0,
+                    name: *kstr,
+                };
+                let res = unsafe { sys::GetErrorContext(index, &mut raw) };
+                kstr.len = raw.name.len;
+                entry = Some(raw);
+                res
+            })
+            .ok()?;
+            entry
+                .filter(|entry| entry.code == code)
+                .map(|entry| ErrorContext::from_raw(&entry, name))
+        })
+        .collect()
+}
+
+impl Error {
+    /// Captures the context the kernel recorded for the last failed system call on the current thread, which should be the call that returned `self`.
+    ///
+    /// Entries recorded for a different error are discarded. If the kernel did not record any context, the context is empty.
+    pub fn with_context(self) -> ContextError {
+        ContextError {
+            error: self,
+            context: read_error_context(self.into_code()),
+        }
+    }
+}
+
+/// An [`Error`], together with the context the kernel recorded for it.
+///
+/// The [`Display`][core::fmt::Display] impl prints the error followed by its context, so the error is not also reported as the [`source`][core::error::Error::source].
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub struct ContextError {
+    error: Error,
+    context: Vec<ErrorContext>,
+}
+
+impl ContextError {
+    /// Converts `code` to a result as by [`Error::from_code`], capturing the error context if it is an error.
+    ///
+    /// This must be called immediately after the system call that returned `code`.
+    pub fn from_code(code: SysResult) -> core::result::Result<(), Self> {
+        Error::from_code(code).map_err(Error::with_context)
+    }
+
+    pub const fn error(&self) -> Error {
+        self.error
+    }
+
+    pub fn context(&self) -> &[ErrorContext] {
+        &self.context
+    }
+
+    pub fn into_parts(self) -> (Error, Vec<ErrorContext>) {
+        (self.error, self.context)
+    }
+}
+
+impl From<Error> for ContextError {
+    /// Wraps `error` without any context. To capture the context recorded by the kernel, use [`Error::with_context`].
+    fn from(error: Error) -> Self {
+        Self {
+            error,
+            context: Vec::new(),
+        }
+    }
+}
+
+impl From<ContextError> for Error {
+    fn from(e: ContextError) -> Self {
+        e.error
+    }
+}
+
+impl core::fmt::Display for ContextError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        self.error.fmt(f)?;
+        let mut sep = ": ";
+        for ctx in &self.context {
+            f.write_str(sep)?;
+            ctx.fmt(f)?;
+            sep = ", ";
+        }
+        Ok(())
+    }
+}
+
+impl core::error::Error for ContextError {}
+
+#[cfg(feature = "std")]
+impl From<ContextError> for std::io::Error {
+    fn from(e: ContextError) -> Self {
+        std::io::Error::new(e.error.kind().into(), e)
+    }
+}
@@ -0,0 +1,127 @@
+//! Conversion between [`Error`] and the `errno` values of POSIX-style C libraries, for code that bridges such libraries and Lilium system calls.
+//!
+//! The values are those used by Linux and the libc of most Unix-like systems.
+//! The mapping is lossy in both directions: several errors share an `errno` value, and so does the reverse.
+
+use super::Error;
+
+/// Operation not permitted
+pub const EPERM: i32 = 1;
+/// No such file or directory
+pub const ENOENT: i32 = 2;
+/// Interrupted system call
+pub const EINTR: i32 = 4;
+/// I/O error
+pub const EIO: i32 = 5;
+/// Bad file descriptor
+pub const EBADF: i32 = 9;
+/// Resource temporarily unavailable, or the operation would block
+pub const EAGAIN: i32 = 11;
+/// Out of memory
+pub const ENOMEM: i32 = 12;
+/// Permission denied
+pub const EACCES: i32 = 13;
+/// Bad address
+pub const EFAULT: i32 = 14;
+/// Device or resource busy
+pub const EBUSY: i32 = 16;
+/// File exists
+pub const EEXIST: i32 = 17;
+/// No such device
+pub const ENODEV: i32 = 19;
+/// Not a directory
+pub const ENOTDIR: i32 = 20;
+/// Is a directory
+pub const EISDIR: i32 = 21;
+/// Invalid argument
+pub const EINVAL: i32 = 22;
+/// Too many open files in the system
+pub const ENFILE: i32 = 23;
+/// Too many open files
+pub const EMFILE: i32 = 24;
+/// No space left on device
+pub const ENOSPC: i32 = 28;
+/// Broken pipe
+pub const EPIPE: i32 = 32;
+/// Result too large
+pub const ERANGE: i32 = 34;
+/// Function not implemented
+pub const ENOSYS: i32 = 38;
+/// Too many levels of symbolic links
+pub const ELOOP: i32 = 40;
+/// Invalid or incomplete multibyte or wide character
+pub const EILSEQ: i32 = 84;
+/// Operation not supported
+pub const EOPNOTSUPP: i32 = 95;
+/// Connection reset by peer
+pub const ECONNRESET: i32 = 104;
+/// Connection timed out
+pub const ETIMEDOUT: i32 = 110;
+/// Operation now in progress
+pub const EINPROGRESS: i32 = 115;
+/// Operation canceled
+pub const ECANCELED: i32 = 125;
+
+impl Error {
+    /// The `errno` value that best describes the error. Errors without a close equivalent, including [`Error::Unknown`], are [`EIO`].
+    pub const fn to_errno(self) -> i32 {
+        match self {
+            Error::Permission => EACCES,
+            Error::PrivilegeCheckFailed => EPERM,
+            Error::InvalidHandle => EBADF,
+            Error::InvalidMemory | Error::MappingInaccessible => EFAULT,
+            Error::Busy => EBUSY,
+            Error::InvalidOperation | Error::InvalidState | Error::InvalidOption => EINVAL,
+            Error::InvalidString => EILSEQ,
+            Error::InsufficientLength => ERANGE,
+            Error::ResourceLimitExhausted => EMFILE,
+            Error::InsufficientMemory => ENOMEM,
+            Error::UnsupportedKernelFunction => ENOSYS,
+            Error::FinishedEnumerate | Error::DoesNotExist => ENOENT,
+            Error::Timeout => ETIMEDOUT,
+            Error::Interrupted | Error::Signaled => EINTR,
+            Error::Killed => ECANCELED,
+            Error::UnsupportedOperation => EOPNOTSUPP,
+            Error::Pending => EINPROGRESS,
+            Error::AlreadyExists => EEXIST,
+            Error::UnknownDevice => ENODEV,
+            Error::WouldBlock => EAGAIN,
+            Error::DeviceFull => ENOSPC,
+            Error::LinkResolutionLoop => ELOOP,
+            Error::ClosedRemotely => EPIPE,
+            Error::ConnectionInterrupted => ECONNRESET,
+            Error::DeviceUnavailable | Error::Unknown(_) => EIO,
+        }
+    }
+
+    /// The error that best describes `errno`, or `None` if `errno` is `0` or has no equivalent.
+    pub const fn from_errno(errno: i32) -> Option<Self> {
+        Some(match errno {
+            EPERM | EACCES => Error::Permission,
+            EBADF => Error::InvalidHandle,
+            EFAULT => Error::InvalidMemory,
+            EBUSY => Error::Busy,
+            EINVAL | ENOTDIR | EISDIR => Error::InvalidOperation,
+            EILSEQ => Error::InvalidString,
+            ERANGE => Error::InsufficientLength,
+            EMFILE | ENFILE => Error::ResourceLimitExhausted,
+            ENOMEM => Error::InsufficientMemory,
+            ENOSYS => Error::UnsupportedKernelFunction,
+            ENOENT => Error::DoesNotExist,
+            ETIMEDOUT => Error::Timeout,
+            EINTR => Error::Interrupted,
+            ECANCELED => Error::Killed,
+            EOPNOTSUPP => Error::UnsupportedOperation,
+            EINPROGRESS => Error::Pending,
+            EEXIST => Error::AlreadyExists,
+            ENODEV => Error::UnknownDevice,
+            EAGAIN => Error::WouldBlock,
+            ENOSPC => Error::DeviceFull,
+            ELOOP => Error::LinkResolutionLoop,
+            EPIPE => Error::ClosedRemotely,
+            ECONNRESET => Error::ConnectionInterrupted,
+            EIO => Error::DeviceUnavailable,
+            _ => return None,
+        })
+    }
+}
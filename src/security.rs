@@ -1,13 +1,92 @@
-use core::mem::MaybeUninit;
+use core::{
+    ffi::{c_long, c_ulong},
+    mem::MaybeUninit,
+};
+
+use alloc::vec::Vec;
 
 use crate::sys::handle::HandlePtr;
 use crate::sys::kstr::KStrCPtr;
 pub use crate::sys::permission::SecurityContext;
 
+use crate::sys::fs::{
+    FileHandle, LegacyGidToPrincipal, LegacyUidToPrincipal, PrincipalToLegacyGid,
+    PrincipalToLegacyUid,
+};
 use crate::sys::process::ProcessHandle;
+use crate::sys::result::SysResult;
 use crate::sys::thread::ThreadHandle;
+use crate::uuid::Uuid;
 use crate::{handle::*, result::Error, sys::permission::*};
 
+mod sandbox;
+pub use sandbox::Sandbox;
+
+/// Names of kernel permissions, for use with [`has_kernel_permission`] and [`HandleRef::grant_kernel_permission`] etc.
+pub mod kernel_perm {
+    /// Allows changing the principals of a security context to principals it does not already have.
+    pub const SECURITY_SET_CREDENTIAL: &str = "SECURITY_SET_CREDENTIAL";
+    /// Allows spawning processes with resource limits greater than those of the current process.
+    pub const SET_PROCESS_LIMITS: &str = "SET_PROCESS_LIMITS";
+    /// Allows loading kernel modules.
+    pub const LOAD_KERNEL_MODULE: &str = "LOAD_KERNEL_MODULE";
+    /// Allows unloading kernel modules.
+    pub const UNLOAD_KERNEL_MODULE: &str = "UNLOAD_KERNEL_MODULE";
+    /// Allows changing system-wide limits.
+    pub const SET_SYSTEM_LIMIT: &str = "SET_SYSTEM_LIMIT";
+    /// Allows reading the offset of a clock from the monotonic clock.
+    pub const READ_CLOCK_OFFSET: &str = "READ_CLOCK_OFFSET";
+    /// Allows reading the granularity of a clock.
+    pub const READ_CLOCK_GRANULARITY: &str = "READ_CLOCK_GRANULARITY";
+    /// Allows setting the realtime clock.
+    pub const WRITE_REALTIME_CLOCK: &str = "WRITE_REALTIME_CLOCK";
+    /// Allows reseeding the kernel's random number generator.
+    pub const WRITE_ENTROPY_POOL: &str = "WRITE_ENTHROPY_POOL";
+    /// Allows creating block devices.
+    pub const CREATE_BLOCK_DEVICE: &str = "CREATE_BLOCK_DEVICE";
+    /// Allows assigning an explicit ID to a created device outside of a device-isolated namespace.
+    pub const ASSIGN_DEVICE_ID: &str = "ASSIGN_DEVICE_ID";
+    /// Allows bypassing exclusive locks held on filesystem objects.
+    pub const BYPASS_LOCK_EXCLUSIVE: &str = "BYPASS_LOCK_EXCLUSIVE";
+    /// Allows bypassing access control checks on filesystem objects.
+    pub const BYPASS_FILESYSTEM_ACCESS_CONTROL: &str = "BYPASS_FILESYSTEM_ACCESS_CONTROL";
+    /// Allows writing messages to the kernel log.
+    pub const WRITE_KERNEL_LOG: &str = "WRITE_KERNEL_LOG";
+    /// Allows reading messages from the kernel log.
+    pub const READ_KERNEL_LOG: &str = "READ_KERNEL_LOG";
+
+    /// Every kernel permission in this module.
+    pub const ALL: &[&str] = &[
+        SECURITY_SET_CREDENTIAL,
+        SET_PROCESS_LIMITS,
+        LOAD_KERNEL_MODULE,
+        UNLOAD_KERNEL_MODULE,
+        SET_SYSTEM_LIMIT,
+        READ_CLOCK_OFFSET,
+        READ_CLOCK_GRANULARITY,
+        WRITE_REALTIME_CLOCK,
+        WRITE_ENTROPY_POOL,
+        CREATE_BLOCK_DEVICE,
+        ASSIGN_DEVICE_ID,
+        BYPASS_LOCK_EXCLUSIVE,
+        BYPASS_FILESYSTEM_ACCESS_CONTROL,
+        WRITE_KERNEL_LOG,
+        READ_KERNEL_LOG,
+    ];
+}
+
+/// Names of thread permissions, for use with [`has_thread_permission`] and [`HandleRef::grant_thread_permission`] etc.
+pub mod thread_perm {
+    /// Allows attaching a debugger to the thread.
+    pub const DEBUG_ATTACH: &str = "DEBUG_ATTACH";
+}
+
+/// Names of process permissions, for use with [`has_process_permission`] and [`HandleRef::grant_process_permission`] etc.
+pub mod process_perm {
+    /// Allows suspending every thread in the process at once.
+    pub const DEBUG_SUSPEND_ALL: &str = "DEBUG_SUSPEND_ALL";
+}
+
 bitflags::bitflags! {
     #[repr(transparent)]
     #[derive(Copy, Clone, Hash, PartialEq, Debug)]
@@ -19,6 +98,120 @@ bitflags::bitflags! {
     }
 }
 
+/// Gets the primary principal of the current thread
+pub fn current_principal() -> crate::result::Result<Uuid> {
+    primary_principal(HandlePtr::null())
+}
+
+/// Gets the principals of the current thread, starting with the primary principal and followed by each secondary principal
+pub fn principals() -> crate::result::Result<Vec<Uuid>> {
+    all_principals(HandlePtr::null())
+}
+
+fn primary_principal(ctx: HandlePtr<SecurityContext>) -> crate::result::Result<Uuid> {
+    let mut principal = Uuid::NIL;
+    Error::from_code(unsafe { GetPrimaryPrincipal(ctx, &mut principal) })?;
+    Ok(principal)
+}
+
+fn secondary_principals(
+    ctx: HandlePtr<SecurityContext>,
+    principals: &mut Vec<Uuid>,
+) -> crate::result::Result<()> {
+    let base = principals.len();
+    loop {
+        let cap = principals.capacity() - base;
+        let mut len = cap as c_ulong;
+        Error::from_code(unsafe {
+            GetSecondaryPrincipals(ctx, principals.as_mut_ptr().add(base), &mut len)
+        })?;
+        let len = len as usize;
+        if len <= cap {
+            unsafe { principals.set_len(base + len) };
+            break Ok(());
+        }
+        principals.reserve(len);
+    }
+}
+
+fn all_principals(ctx: HandlePtr<SecurityContext>) -> crate::result::Result<Vec<Uuid>> {
+    let mut principals = Vec::with_capacity(1);
+    principals.push(primary_principal(ctx)?);
+    secondary_principals(ctx, &mut principals)?;
+    Ok(principals)
+}
+
+fn map_legacy_id(
+    f: unsafe extern "C" fn(HandlePtr<FileHandle>, c_long, *mut Uuid) -> SysResult,
+    fs: HandlePtr<FileHandle>,
+    id: u32,
+) -> crate::result::Result<Option<Uuid>> {
+    let mut principal = Uuid::NIL;
+    match Error::from_code(unsafe { f(fs, id as _, &mut principal) }) {
+        Ok(()) => Ok(Some(principal)),
+        Err(Error::DoesNotExist) => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+fn map_principal(
+    f: unsafe extern "C" fn(HandlePtr<FileHandle>, *const Uuid) -> SysResult,
+    fs: HandlePtr<FileHandle>,
+    principal: Uuid,
+) -> crate::result::Result<Option<u32>> {
+    let id = unsafe { f(fs, &principal) };
+    match Error::from_code(id) {
+        Ok(()) => Ok(Some(id as u32)),
+        Err(Error::DoesNotExist) => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+/// Maps the legacy `uid` to a principal, using the principal map of the filesystem `fs` resides on.
+///
+/// If `fs` is null, or its filesystem was mounted without a principal map, the default principal map is used.
+/// Returns `None` if the principal map has no entry for `uid`.
+pub fn principal_from_legacy_uid<'a, H: AsHandle<'a, FileHandle>>(
+    fs: H,
+    uid: u32,
+) -> crate::result::Result<Option<Uuid>> {
+    map_legacy_id(LegacyUidToPrincipal, fs.as_handle(), uid)
+}
+
+/// Maps the legacy `gid` to a principal, using the principal map of the filesystem `fs` resides on.
+///
+/// If `fs` is null, or its filesystem was mounted without a principal map, the default principal map is used.
+/// Returns `None` if the principal map has no entry for `gid`.
+pub fn principal_from_legacy_gid<'a, H: AsHandle<'a, FileHandle>>(
+    fs: H,
+    gid: u32,
+) -> crate::result::Result<Option<Uuid>> {
+    map_legacy_id(LegacyGidToPrincipal, fs.as_handle(), gid)
+}
+
+/// Maps `principal` to a legacy uid, using the principal map of the filesystem `fs` resides on.
+///
+/// If `fs` is null, or its filesystem was mounted without a principal map, the default principal map is used.
+/// Returns `None` if no uid maps to `principal`.
+pub fn legacy_uid_of<'a, H: AsHandle<'a, FileHandle>>(
+    fs: H,
+    principal: Uuid,
+) -> crate::result::Result<Option<u32>> {
+    map_principal(PrincipalToLegacyUid, fs.as_handle(), principal)
+}
+
+/// Maps `principal` to a legacy gid, using the principal map of the filesystem `fs` resides on.
+///
+/// If `fs` is null, or its filesystem was mounted without a principal map, the default principal map is used.
+/// Returns `None` if no gid maps to `principal`.
+pub fn legacy_gid_of<'a, H: AsHandle<'a, FileHandle>>(
+    fs: H,
+    principal: Uuid,
+) -> crate::result::Result<Option<u32>> {
+    map_principal(PrincipalToLegacyGid, fs.as_handle(), principal)
+}
+
+/// Checks whether the current thread has the kernel permission `perm`. See [`kernel_perm`] for the names of kernel permissions.
 pub fn has_kernel_permission(perm: &str) -> crate::result::Result<PermissionStatus> {
     let status = unsafe { HasKernelPermission(HandlePtr::null(), KStrCPtr::from_str(perm)) };
     Error::from_code(status)?;
@@ -57,6 +250,13 @@ impl SecurityContext {
         Error::from_code(unsafe { GetCurrentSecurityContext(ctx.as_mut_ptr()) })?;
         Ok(unsafe { OwnedHandle::take_ownership(ctx.assume_init()) })
     }
+
+    /// Replaces the security context of the current thread with a copy of `ctx`.
+    ///
+    /// Unless the current thread has the [`kernel_perm::SECURITY_SET_CREDENTIAL`] permission, `ctx` cannot have any principals or permissions that the current security context does not.
+    pub fn apply<'a, H: AsHandle<'a, Self>>(ctx: H) -> crate::result::Result<()> {
+        Error::from_code(unsafe { SetCurrentSecurityContext(ctx.as_handle()) })
+    }
 }
 
 impl HandleRef<SecurityContext> {
@@ -93,4 +293,176 @@ impl HandleRef<SecurityContext> {
         Error::from_code(status)?;
         Ok(PermissionStatus::from_bits_retain(status))
     }
+
+    /// Gets the primary principal of the security context
+    pub fn primary_principal(&self) -> crate::result::Result<Uuid> {
+        primary_principal(self.as_raw())
+    }
+
+    /// Gets the secondary principals of the security context
+    pub fn secondary_principals(&self) -> crate::result::Result<Vec<Uuid>> {
+        let mut principals = Vec::new();
+        secondary_principals(self.as_raw(), &mut principals)?;
+        Ok(principals)
+    }
+
+    /// Gets the principals of the security context, starting with the primary principal and followed by each secondary principal
+    pub fn principals(&self) -> crate::result::Result<Vec<Uuid>> {
+        all_principals(self.as_raw())
+    }
+
+    /// Sets the primary principal of the security context.
+    ///
+    /// Unless the current thread has the [`kernel_perm::SECURITY_SET_CREDENTIAL`] permission, `principal` must already be a principal of the context.
+    pub fn set_primary_principal(&self, principal: Uuid) -> crate::result::Result<()> {
+        Error::from_code(unsafe { SetPrimaryPrincipal(self.as_raw(), &principal) })
+    }
+
+    /// Adds a secondary principal to the security context.
+    ///
+    /// Unless the current thread has the [`kernel_perm::SECURITY_SET_CREDENTIAL`] permission, `principal` must already be a principal of the context.
+    pub fn add_secondary_principal(&self, principal: Uuid) -> crate::result::Result<()> {
+        Error::from_code(unsafe { AddSecondaryPrincipal(self.as_raw(), &principal) })
+    }
+
+    /// Grants a kernel permission in the security context with the given status.
+    pub fn grant_kernel_permission(
+        &self,
+        perm: &str,
+        status: PermissionStatus,
+    ) -> crate::result::Result<()> {
+        Error::from_code(unsafe {
+            GrantKernelPermission(self.as_raw(), KStrCPtr::from_str(perm), status.bits() as _)
+        })
+    }
+
+    /// Grants a permission to `th` in the security context with the given status.
+    pub fn grant_thread_permission(
+        &self,
+        th: &HandleRef<ThreadHandle>,
+        perm: &str,
+        status: PermissionStatus,
+    ) -> crate::result::Result<()> {
+        Error::from_code(unsafe {
+            GrantThreadPermission(
+                self.as_raw(),
+                th.as_raw(),
+                KStrCPtr::from_str(perm),
+                status.bits() as _,
+            )
+        })
+    }
+
+    /// Grants a permission to `ph` in the security context with the given status.
+    pub fn grant_process_permission(
+        &self,
+        ph: &HandleRef<ProcessHandle>,
+        perm: &str,
+        status: PermissionStatus,
+    ) -> crate::result::Result<()> {
+        Error::from_code(unsafe {
+            GrantProcessPermission(
+                self.as_raw(),
+                ph.as_raw(),
+                KStrCPtr::from_str(perm),
+                status.bits() as _,
+            )
+        })
+    }
+
+    /// Drops a kernel permission from the security context.
+    ///
+    /// If `status` contains [`PermissionStatus::RECOVERABLE`], the permission can be granted again later.
+    pub fn drop_kernel_permission(
+        &self,
+        perm: &str,
+        status: PermissionStatus,
+    ) -> crate::result::Result<()> {
+        Error::from_code(unsafe {
+            DropKernelPermission(self.as_raw(), KStrCPtr::from_str(perm), status.bits() as _)
+        })
+    }
+
+    /// Drops a permission to `th` from the security context.
+    ///
+    /// If `status` contains [`PermissionStatus::RECOVERABLE`], the permission can be granted again later.
+    pub fn drop_thread_permission(
+        &self,
+        th: &HandleRef<ThreadHandle>,
+        perm: &str,
+        status: PermissionStatus,
+    ) -> crate::result::Result<()> {
+        Error::from_code(unsafe {
+            DropThreadPermission(
+                self.as_raw(),
+                th.as_raw(),
+                KStrCPtr::from_str(perm),
+                status.bits() as _,
+            )
+        })
+    }
+
+    /// Drops a permission to `ph` from the security context.
+    ///
+    /// If `status` contains [`PermissionStatus::RECOVERABLE`], the permission can be granted again later.
+    pub fn drop_process_permission(
+        &self,
+        ph: &HandleRef<ProcessHandle>,
+        perm: &str,
+        status: PermissionStatus,
+    ) -> crate::result::Result<()> {
+        Error::from_code(unsafe {
+            DropProcessPermission(
+                self.as_raw(),
+                ph.as_raw(),
+                KStrCPtr::from_str(perm),
+                status.bits() as _,
+            )
+        })
+    }
+
+    /// Revokes a kernel permission in the security context, so that it cannot be granted again.
+    pub fn revoke_kernel_permission(&self, perm: &str) -> crate::result::Result<()> {
+        Error::from_code(unsafe { RevokeKernelPermission(self.as_raw(), KStrCPtr::from_str(perm)) })
+    }
+
+    /// Revokes a permission to `th` in the security context, so that it cannot be granted again.
+    pub fn revoke_thread_permission(
+        &self,
+        th: &HandleRef<ThreadHandle>,
+        perm: &str,
+    ) -> crate::result::Result<()> {
+        Error::from_code(unsafe {
+            RevokeThreadPermission(self.as_raw(), th.as_raw(), KStrCPtr::from_str(perm))
+        })
+    }
+
+    /// Revokes a permission to `ph` in the security context, so that it cannot be granted again.
+    pub fn revoke_process_permission(
+        &self,
+        ph: &HandleRef<ProcessHandle>,
+        perm: &str,
+    ) -> crate::result::Result<()> {
+        Error::from_code(unsafe {
+            RevokeProcessPermission(self.as_raw(), ph.as_raw(), KStrCPtr::from_str(perm))
+        })
+    }
+
+    /// Gets the limit on the kernel resource named `limit` in the security context.
+    ///
+    /// See [`GetKernelResourceLimit`] for the known resource limit names.
+    pub fn resource_limit(&self, limit: &str) -> crate::result::Result<u64> {
+        let mut value = 0;
+        Error::from_code(unsafe {
+            GetKernelResourceLimit(self.as_raw(), KStrCPtr::from_str(limit), &mut value)
+        })?;
+        Ok(value)
+    }
+
+    /// Sets the limit on the kernel resource named `limit` in the security context.
+    pub fn set_resource_limit(&self, limit: &str, value: u64) -> crate::result::Result<()> {
+        Error::from_code(unsafe {
+            SetKernelResourceLimit(self.as_raw(), KStrCPtr::from_str(limit), value)
+        })
+    }
 }
@@ -0,0 +1,243 @@
+use alloc::{
+    boxed::Box,
+    string::{String, ToString},
+    vec::Vec,
+};
+
+use crate::{
+    fs::{OwnedFile, Path, PathBuf},
+    handle::{BorrowedHandle, OwnedHandle},
+    io::{IOHandle, IoChars},
+    isolation::{DeviceGroup, NamespaceHandle},
+    process::{Command, Limit, ProcessStartFlags},
+    result::Result,
+    sys::handle::HandlePtr,
+};
+
+use super::{kernel_perm, SecurityContext};
+
+/// Restrictions on a process spawned by a [`Command`], which are applied by [`Sandbox::apply`].
+///
+/// A sandbox combines:
+/// * A copy of the current [`SecurityContext`], with kernel permissions revoked and kernel resource limits set,
+/// * A new isolation [`NamespaceHandle`], which hides devices, mount points, the filesystem, or other processes,
+/// * Restricted copies of the standard streams, which can only perform some operations, as by [`Stdio::restricted`][crate::process::Stdio::restricted],
+/// * [`ProcessStartFlags::NON_PRIVLAGED`], so that the program cannot regain permissions from the `InstallSecurityContext` stream or legacy SUID/SGID bits,
+/// * Resource limits on the process, as by [`Command::limit`].
+///
+/// Only the restrictions configured on the sandbox are applied - [`Sandbox::new`] only sets [`ProcessStartFlags::NON_PRIVLAGED`].
+///
+/// ## Example
+/// ```rust,ignore
+/// let mut cmd = Command::new("/usr/bin/untrusted");
+/// Sandbox::compute_only()
+///     .process_limit(Limit::Memory, 64 * 1024 * 1024)
+///     .apply(&mut cmd)?;
+/// let status = cmd.spawn()?.wait()?;
+/// ```
+#[derive(Clone, Debug)]
+pub struct Sandbox {
+    revoked_perms: Vec<String>,
+    resource_limits: Vec<(String, u64)>,
+    process_limits: Vec<(Limit, u64)>,
+    devices: Option<DeviceGroup>,
+    mounts: Option<Vec<String>>,
+    root: Option<PathBuf>,
+    isolate_processes: bool,
+    stdio: [Option<IoChars>; 3],
+}
+
+impl Sandbox {
+    pub const fn new() -> Self {
+        Self {
+            revoked_perms: Vec::new(),
+            resource_limits: Vec::new(),
+            process_limits: Vec::new(),
+            devices: None,
+            mounts: None,
+            root: None,
+            isolate_processes: false,
+            stdio: [None; 3],
+        }
+    }
+
+    /// A sandbox for programs that only compute on their standard streams.
+    ///
+    /// Every kernel permission in [`kernel_perm::ALL`] is revoked, only clocks and random devices are visible, no mount point is visible, and no other process is visible.
+    /// The standard input stream can only be read, and the standard output and error streams can only be written.
+    pub fn compute_only() -> Self {
+        let mut sandbox = Self::new();
+        for perm in kernel_perm::ALL {
+            sandbox.revoke_kernel_permission(perm);
+        }
+        sandbox
+            .devices(DeviceGroup::CLOCKS | DeviceGroup::RAND_DEVICES)
+            .mounts::<_, &str>([])
+            .isolate_processes(true)
+            .restrict_stdin(IoChars::READABLE)
+            .restrict_stdout(IoChars::WRITABLE)
+            .restrict_stderr(IoChars::WRITABLE);
+        sandbox
+    }
+
+    /// A sandbox for programs that read the mount points in `paths`.
+    ///
+    /// Every other mount point is hidden, and the kernel permissions that bypass filesystem access control and exclusive locks, or create block devices, are revoked.
+    /// The standard input stream can only be read, and the standard output and error streams can only be written.
+    ///
+    /// The namespace does not make the mount points read-only by itself - files remain writable by the process if their access control lists allow writes by the principals of the current security context.
+    pub fn readonly_fs<I: IntoIterator<Item = S>, S: AsRef<str>>(paths: I) -> Self {
+        let mut sandbox = Self::new();
+        sandbox
+            .revoke_kernel_permission(kernel_perm::BYPASS_FILESYSTEM_ACCESS_CONTROL)
+            .revoke_kernel_permission(kernel_perm::BYPASS_LOCK_EXCLUSIVE)
+            .revoke_kernel_permission(kernel_perm::CREATE_BLOCK_DEVICE)
+            .mounts(paths)
+            .restrict_stdin(IoChars::READABLE)
+            .restrict_stdout(IoChars::WRITABLE)
+            .restrict_stderr(IoChars::WRITABLE);
+        sandbox
+    }
+
+    /// Revokes the kernel permission named `perm` in the security context of the process, so that it cannot be granted again. See [`kernel_perm`] for the known names.
+    pub fn revoke_kernel_permission(&mut self, perm: &str) -> &mut Self {
+        self.revoked_perms.push(perm.to_string());
+        self
+    }
+
+    /// Sets the limit on the kernel resource named `limit` in the security context of the process, as by [`HandleRef::set_resource_limit`][crate::handle::HandleRef::set_resource_limit]
+    pub fn resource_limit(&mut self, limit: &str, value: u64) -> &mut Self {
+        self.resource_limits.push((limit.to_string(), value));
+        self
+    }
+
+    /// Limits the resource `limit` of the process, as by [`Command::limit`]
+    pub fn process_limit(&mut self, limit: Limit, value: u64) -> &mut Self {
+        self.process_limits.push((limit, value));
+        self
+    }
+
+    /// Hides every device from the process, except for those in `groups`
+    pub fn devices(&mut self, groups: DeviceGroup) -> &mut Self {
+        self.devices = Some(groups);
+        self
+    }
+
+    /// Hides every mount point from the process, except for those in `allowed`
+    pub fn mounts<I: IntoIterator<Item = S>, S: AsRef<str>>(&mut self, allowed: I) -> &mut Self {
+        self.mounts = Some(
+            allowed
+                .into_iter()
+                .map(|s| s.as_ref().to_string())
+                .collect(),
+        );
+        self
+    }
+
+    /// Makes the directory at `root` the root of the filesystem visible to the process
+    pub fn root<P: AsRef<Path>>(&mut self, root: P) -> &mut Self {
+        self.root = Some(root.as_ref().to_path_buf());
+        self
+    }
+
+    /// Hides every other process from the process, including the process that spawns it if `isolate` is `true`
+    pub fn isolate_processes(&mut self, isolate: bool) -> &mut Self {
+        self.isolate_processes = isolate;
+        self
+    }
+
+    /// Restricts the standard input stream of the process to the operations in `chars_mask`
+    pub fn restrict_stdin(&mut self, chars_mask: IoChars) -> &mut Self {
+        self.stdio[0] = Some(chars_mask);
+        self
+    }
+
+    /// Restricts the standard output stream of the process to the operations in `chars_mask`
+    pub fn restrict_stdout(&mut self, chars_mask: IoChars) -> &mut Self {
+        self.stdio[1] = Some(chars_mask);
+        self
+    }
+
+    /// Restricts the standard error stream of the process to the operations in `chars_mask`
+    pub fn restrict_stderr(&mut self, chars_mask: IoChars) -> &mut Self {
+        self.stdio[2] = Some(chars_mask);
+        self
+    }
+
+    fn needs_namespace(&self) -> bool {
+        self.devices.is_some()
+            || self.mounts.is_some()
+            || self.root.is_some()
+            || self.isolate_processes
+    }
+
+    /// Applies the sandbox to `cmd`, replacing its security context and namespace.
+    ///
+    /// The standard streams are restricted as they are set when this is called, so [`Command::stdin`] etc. should be called first.
+    /// The handles opened for the sandbox are closed when `cmd` is dropped.
+    ///
+    /// ## Errors
+    /// Returns [`Error::Permission`][crate::result::Error::Permission] if the current thread cannot revoke a permission or set a limit on the security context, or cannot create the namespace.
+    /// `cmd` is unmodified if an error is returned.
+    pub fn apply(&self, cmd: &mut Command) -> Result<()> {
+        let ctx = SecurityContext::current()?.clone()?;
+        for perm in &self.revoked_perms {
+            ctx.revoke_kernel_permission(perm)?;
+        }
+        for (limit, value) in &self.resource_limits {
+            ctx.set_resource_limit(limit, *value)?;
+        }
+
+        let ns = if self.needs_namespace() {
+            let ns = NamespaceHandle::new()?;
+            if let Some(groups) = self.devices {
+                ns.isolate_devices(groups, &[])?;
+            }
+            if let Some(mounts) = &self.mounts {
+                ns.isolate_mounts(mounts)?;
+            }
+            if let Some(root) = &self.root {
+                ns.isolate_filesystem(&OwnedFile::open_dir(root)?)?;
+            }
+            if self.isolate_processes {
+                ns.isolate_processes(false)?;
+            }
+            Some(ns)
+        } else {
+            None
+        };
+
+        let mut stdio: [Option<OwnedHandle<IOHandle>>; 3] = [None, None, None];
+        for (i, chars) in self.stdio.iter().enumerate() {
+            let hdl = cmd.init_handles[i].cast::<IOHandle>();
+            if let (Some(chars), false) = (chars, hdl == HandlePtr::null()) {
+                stdio[i] =
+                    Some(unsafe { BorrowedHandle::from_raw(hdl) }.duplicate_restricted(*chars)?);
+            }
+        }
+
+        cmd.security_context(ctx.as_raw());
+        cmd.owned.push(Box::new(ctx));
+        if let Some(ns) = ns {
+            cmd.namespace(ns.as_raw());
+            cmd.owned.push(Box::new(ns));
+        }
+        for (i, hdl) in stdio.into_iter().enumerate() {
+            if let Some(hdl) = hdl {
+                cmd.init_handles[i] = hdl.as_raw().cast();
+                cmd.owned.push(Box::new(hdl));
+            }
+        }
+        for (limit, value) in &self.process_limits {
+            cmd.limit(*limit, *value);
+        }
+        cmd.flags |= ProcessStartFlags::NON_PRIVLAGED;
+        Ok(())
+    }
+}
+
+impl Default for Sandbox {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,288 @@
+//! Synchronization primitives, built on the address-wait system calls ([`AwaitAddressMasked`][sys::AwaitAddressMasked] and [`NotifyAddress`][sys::NotifyAddress]).
+
+use core::{
+    ffi::c_void,
+    sync::atomic::{
+        AtomicBool, AtomicI16, AtomicI32, AtomicI8, AtomicIsize, AtomicPtr, AtomicU16, AtomicU32,
+        AtomicU8, AtomicUsize,
+    },
+};
+
+use crate::{
+    result::{Error, Result},
+    sys::thread as sys,
+    time::Duration,
+};
+
+mod barrier;
+mod condvar;
+mod lazy_lock;
+mod mutex;
+mod once;
+mod once_lock;
+mod rwlock;
+mod semaphore;
+
+pub mod event;
+
+pub use barrier::{Barrier, BarrierWaitResult};
+pub use condvar::Condvar;
+pub use lazy_lock::LazyLock;
+pub use mutex::{Mutex, MutexGuard, RawMutex};
+pub use once::{Once, OnceState};
+pub use once_lock::OnceLock;
+pub use rwlock::{RawRwLock, RwLock, RwLockReadGuard, RwLockUpgradableReadGuard, RwLockWriteGuard};
+pub use semaphore::{Semaphore, SemaphorePermit};
+
+/// Atomic types which can be waited on with [`AwaitAddressMasked`][sys::AwaitAddressMasked].
+pub trait AtomicWaitEx {
+    /// The type of value stored in the atomic
+    type Value: Copy;
+
+    /// Blocks the current thread until notified, if the atomic holds `current`.
+    ///
+    /// Returns immediately if the atomic holds any other value. The thread may also wake up spuriously, so callers should check the value again after this returns.
+    ///
+    /// ## Errors
+    /// Returns [`Error::Timeout`] if the blocking timeout of the current thread elapses, and [`Error::Interrupted`] if the thread is interrupted.
+    fn wait(&self, current: Self::Value) -> Result<()>;
+
+    /// Wakes one thread waiting on the atomic, and returns the number of threads woken
+    fn notify_one(&self) -> usize;
+
+    /// Wakes every thread waiting on the atomic, and returns the number of threads woken
+    fn notify_all(&self) -> usize;
+}
+
+/// Atomic integers which support waiting on, and notifying, a subset of their bits.
+pub trait AtomicMaskedWaitEx: AtomicWaitEx {
+    /// Blocks the current thread until notified, if the bits of the atomic not set in `ignore_mask` are equal to those bits of `current`.
+    ///
+    /// Only notifications with a wake mask that includes a bit not set in `ignore_mask` wake the thread.
+    fn wait_mask(&self, current: Self::Value, ignore_mask: Self::Value) -> Result<()>;
+
+    /// Wakes up to `count` threads waiting on the atomic whose wait includes any bit set in `wake_mask`, and returns the number of threads woken
+    fn notify_mask(&self, count: usize, wake_mask: Self::Value) -> usize;
+}
+
+/// Atomic types which support waiting with a timeout.
+pub trait AtomicTimedWaitEx: AtomicWaitEx {
+    /// Blocks the current thread until notified, or until `dur` elapses, if the atomic holds `current`.
+    ///
+    /// Returns `Ok(false)` if `dur` elapsed, and `Ok(true)` otherwise. As with [`AtomicWaitEx::wait`], the thread may wake up spuriously.
+    ///
+    /// The blocking timeout of the current thread is restored before returning.
+    ///
+    /// ## Errors
+    /// If the current thread already has a blocking timeout shorter than `dur`, returns [`Error::Timeout`] when that timeout elapses.
+    ///
+    /// Returns [`Error::Interrupted`] if the thread is interrupted.
+    fn wait_for(&self, current: Self::Value, dur: Duration) -> Result<bool>;
+}
+
+impl<A: AtomicWaitEx + ?Sized> AtomicTimedWaitEx for A {
+    fn wait_for(&self, current: Self::Value, dur: Duration) -> Result<bool> {
+        match crate::thread::blocking_timeout() {
+            Some(prev) if prev < dur => self.wait(current).map(|()| true),
+            _ => match crate::thread::with_timeout(dur, || self.wait(current)) {
+                Ok(()) => Ok(true),
+                Err(Error::Timeout) => Ok(false),
+                Err(e) => Err(e),
+            },
+        }
+    }
+}
+
+fn notify_word(addr: *mut c_void, count: usize, wake_mask: *const c_void) -> usize {
+    let code = unsafe { sys::NotifyAddress(addr, count, wake_mask) };
+    Error::from_code(code).map_or(0, |()| code as usize)
+}
+
+impl AtomicWaitEx for AtomicUsize {
+    type Value = usize;
+
+    fn wait(&self, current: usize) -> Result<()> {
+        Error::from_code(unsafe {
+            sys::AwaitAddressMasked(
+                self.as_ptr().cast(),
+                core::ptr::addr_of!(current).cast(),
+                core::ptr::null(),
+            )
+        })
+    }
+
+    fn notify_one(&self) -> usize {
+        notify_word(self.as_ptr().cast(), 1, core::ptr::null())
+    }
+
+    fn notify_all(&self) -> usize {
+        notify_word(self.as_ptr().cast(), usize::MAX, core::ptr::null())
+    }
+}
+
+impl AtomicMaskedWaitEx for AtomicUsize {
+    fn wait_mask(&self, current: usize, ignore_mask: usize) -> Result<()> {
+        Error::from_code(unsafe {
+            sys::AwaitAddressMasked(
+                self.as_ptr().cast(),
+                core::ptr::addr_of!(current).cast(),
+                core::ptr::addr_of!(ignore_mask).cast(),
+            )
+        })
+    }
+
+    fn notify_mask(&self, count: usize, wake_mask: usize) -> usize {
+        notify_word(
+            self.as_ptr().cast(),
+            count,
+            core::ptr::addr_of!(wake_mask).cast(),
+        )
+    }
+}
+
+impl<T> AtomicWaitEx for AtomicPtr<T> {
+    type Value = *mut T;
+
+    fn wait(&self, current: *mut T) -> Result<()> {
+        Error::from_code(unsafe {
+            sys::AwaitAddressMasked(
+                self.as_ptr().cast(),
+                core::ptr::addr_of!(current).cast(),
+                core::ptr::null(),
+            )
+        })
+    }
+
+    fn notify_one(&self) -> usize {
+        notify_word(self.as_ptr().cast(), 1, core::ptr::null())
+    }
+
+    fn notify_all(&self) -> usize {
+        notify_word(self.as_ptr().cast(), usize::MAX, core::ptr::null())
+    }
+}
+
+/// Locates the pointer-sized word containing the `size`-byte atomic at `ptr`.
+///
+/// Returns the aligned word, the bit offset of the atomic's value within it, and the bits of the word that hold the atomic's value.
+fn containing_word(ptr: *mut u8, size: usize) -> (*mut c_void, u32, usize) {
+    let offset = ptr.addr() & (core::mem::size_of::<usize>() - 1);
+    let word = ptr.wrapping_sub(offset).cast::<c_void>();
+    let byte_shift = if cfg!(target_endian = "little") {
+        offset
+    } else {
+        core::mem::size_of::<usize>() - offset - size
+    };
+    let shift = (byte_shift * 8) as u32;
+    let mask = if size == core::mem::size_of::<usize>() {
+        usize::MAX
+    } else {
+        ((1usize << (size * 8)) - 1) << shift
+    };
+    (word, shift, mask)
+}
+
+/// Waits on the word containing a `size`-byte atomic at `ptr`, comparing only the atomic's bits not set in `ignore_mask`.
+///
+/// `current` and `ignore_mask` hold the atomic's value in their low bits.
+fn wait_sub_word(ptr: *mut u8, size: usize, current: usize, ignore_mask: usize) -> Result<()> {
+    let (word, shift, mask) = containing_word(ptr, size);
+    let current = current << shift;
+    let ignore_mask = !mask | ((ignore_mask << shift) & mask);
+    Error::from_code(unsafe {
+        sys::AwaitAddressMasked(
+            word,
+            core::ptr::addr_of!(current).cast(),
+            core::ptr::addr_of!(ignore_mask).cast(),
+        )
+    })
+}
+
+/// Wakes up to `count` threads waiting on any bit of the `size`-byte atomic at `ptr` set in `wake_mask`.
+///
+/// Threads waiting on other atomics which share the same word are not woken.
+fn notify_sub_word(ptr: *mut u8, size: usize, count: usize, wake_mask: usize) -> usize {
+    let (word, shift, mask) = containing_word(ptr, size);
+    let wake_mask = (wake_mask << shift) & mask;
+    notify_word(word, count, core::ptr::addr_of!(wake_mask).cast())
+}
+
+macro_rules! impl_sub_word_wait {
+    ($($atomic:ty => $value:ty as $bits:ty;)*) => {
+        $(
+            impl AtomicWaitEx for $atomic {
+                type Value = $value;
+
+                fn wait(&self, current: $value) -> Result<()> {
+                    wait_sub_word(
+                        self.as_ptr().cast(),
+                        core::mem::size_of::<$value>(),
+                        current as $bits as usize,
+                        0,
+                    )
+                }
+
+                fn notify_one(&self) -> usize {
+                    notify_sub_word(self.as_ptr().cast(), core::mem::size_of::<$value>(), 1, usize::MAX)
+                }
+
+                fn notify_all(&self) -> usize {
+                    notify_sub_word(
+                        self.as_ptr().cast(),
+                        core::mem::size_of::<$value>(),
+                        usize::MAX,
+                        usize::MAX,
+                    )
+                }
+            }
+        )*
+    };
+}
+
+macro_rules! impl_sub_word_masked_wait {
+    ($($atomic:ty => $value:ty as $bits:ty;)*) => {
+        impl_sub_word_wait!($($atomic => $value as $bits;)*);
+        $(
+            impl AtomicMaskedWaitEx for $atomic {
+                fn wait_mask(&self, current: $value, ignore_mask: $value) -> Result<()> {
+                    wait_sub_word(
+                        self.as_ptr().cast(),
+                        core::mem::size_of::<$value>(),
+                        current as $bits as usize,
+                        ignore_mask as $bits as usize,
+                    )
+                }
+
+                fn notify_mask(&self, count: usize, wake_mask: $value) -> usize {
+                    notify_sub_word(
+                        self.as_ptr().cast(),
+                        core::mem::size_of::<$value>(),
+                        count,
+                        wake_mask as $bits as usize,
+                    )
+                }
+            }
+        )*
+    };
+}
+
+impl_sub_word_wait! {
+    AtomicBool => bool as u8;
+}
+
+impl_sub_word_masked_wait! {
+    AtomicU8 => u8 as u8;
+    AtomicI8 => i8 as u8;
+    AtomicU16 => u16 as u16;
+    AtomicI16 => i16 as u16;
+    AtomicU32 => u32 as u32;
+    AtomicI32 => i32 as u32;
+    AtomicIsize => isize as usize;
+}
+
+// `AwaitAddressMasked` compares a single pointer-sized word, so 64-bit atomics can only be waited on when they fit in one.
+#[cfg(target_pointer_width = "64")]
+impl_sub_word_masked_wait! {
+    core::sync::atomic::AtomicU64 => u64 as u64;
+    core::sync::atomic::AtomicI64 => i64 as u64;
+}
@@ -0,0 +1,74 @@
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+use super::AtomicMaskedWaitEx;
+
+// The low half of the state counts the threads which have arrived at the barrier, and the high half counts completed generations.
+// Completing a generation resets the count and increments the generation in a single atomic operation, so a thread reaching the barrier for the next generation can never observe a stale generation.
+const GEN_SHIFT: u32 = usize::BITS / 2;
+const COUNT_MASK: usize = (1 << GEN_SHIFT) - 1;
+const GEN_ONE: usize = 1 << GEN_SHIFT;
+
+/// A barrier, which blocks threads until a fixed number of threads have reached it.
+///
+/// The barrier is reusable: once the final thread arrives, every waiting thread is released, and the barrier blocks the next group of threads.
+pub struct Barrier {
+    state: AtomicUsize,
+    n: usize,
+}
+
+impl core::fmt::Debug for Barrier {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Barrier")
+            .field("n", &self.n)
+            .finish_non_exhaustive()
+    }
+}
+
+/// The result of [`Barrier::wait`]
+#[derive(Debug)]
+pub struct BarrierWaitResult(bool);
+
+impl BarrierWaitResult {
+    /// Whether the current thread is the leader of its generation. Exactly one thread in each generation is the leader.
+    pub fn is_leader(&self) -> bool {
+        self.0
+    }
+}
+
+impl Barrier {
+    /// Creates a barrier which releases threads in groups of `n`. A barrier created with `n == 0` behaves the same as with `n == 1`.
+    ///
+    /// ## Panics
+    /// Panics if `n` does not fit in half of a `usize`.
+    pub const fn new(n: usize) -> Self {
+        assert!(n <= COUNT_MASK, "Barrier size is too large");
+        Self {
+            state: AtomicUsize::new(0),
+            n: if n == 0 { 1 } else { n },
+        }
+    }
+
+    /// Blocks the current thread until `n` threads have called `wait` on this barrier.
+    ///
+    /// The final thread to arrive does not block, and is the leader of the generation.
+    /// Timeouts and interrupts do not cause the thread to leave the barrier early.
+    pub fn wait(&self) -> BarrierWaitResult {
+        let state = self.state.fetch_add(1, Ordering::AcqRel);
+        let generation = state & !COUNT_MASK;
+
+        if (state & COUNT_MASK) + 1 == self.n {
+            self.state
+                .fetch_add(GEN_ONE.wrapping_sub(self.n), Ordering::AcqRel);
+            self.state.notify_mask(usize::MAX, !COUNT_MASK);
+            BarrierWaitResult(true)
+        } else {
+            loop {
+                let state = self.state.load(Ordering::Acquire);
+                if state & !COUNT_MASK != generation {
+                    break BarrierWaitResult(false);
+                }
+                let _ = self.state.wait_mask(state, COUNT_MASK);
+            }
+        }
+    }
+}
@@ -0,0 +1,75 @@
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::result::Result;
+
+use super::{AtomicWaitEx, MutexGuard};
+
+/// A condition variable, used with a [`Mutex`][super::Mutex] to block threads until a condition holds.
+///
+/// The condition variable holds a sequence number, which every notification increments before waking threads.
+/// A waiting thread reads the sequence number while holding the mutex, and only blocks if the sequence number is unchanged when [`AwaitAddressMasked`][crate::sys::thread::AwaitAddressMasked] checks it.
+/// A notification issued after the mutex is released is therefore never lost.
+pub struct Condvar {
+    seq: AtomicUsize,
+}
+
+impl core::fmt::Debug for Condvar {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Condvar").finish_non_exhaustive()
+    }
+}
+
+impl Default for Condvar {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Condvar {
+    pub const fn new() -> Self {
+        Self {
+            seq: AtomicUsize::new(0),
+        }
+    }
+
+    /// Releases the mutex locked by `guard`, and blocks the current thread until this condition variable is notified. The mutex is locked again before returning.
+    ///
+    /// The thread may wake up spuriously, so the condition should be checked again after this returns. [`Condvar::wait_while`] does this automatically.
+    ///
+    /// ## Errors
+    /// Returns [`Error::Timeout`][crate::result::Error::Timeout] if the blocking timeout of the current thread elapses,
+    ///  and [`Error::Interrupted`][crate::result::Error::Interrupted] if the thread is interrupted. The mutex is locked again in either case.
+    pub fn wait<T>(&self, guard: &mut MutexGuard<'_, T>) -> Result<()> {
+        let seq = self.seq.load(Ordering::Relaxed);
+        MutexGuard::unlocked(guard, || self.seq.wait(seq))
+    }
+
+    /// Blocks the current thread until `condition` returns `false`, waiting on this condition variable whenever it returns `true`.
+    ///
+    /// `condition` is only called while the mutex is locked.
+    pub fn wait_while<T, F: FnMut(&mut T) -> bool>(
+        &self,
+        guard: &mut MutexGuard<'_, T>,
+        mut condition: F,
+    ) -> Result<()> {
+        while condition(&mut **guard) {
+            self.wait(guard)?;
+        }
+        Ok(())
+    }
+
+    /// Wakes one thread waiting on this condition variable
+    pub fn notify_one(&self) {
+        self.seq.fetch_add(1, Ordering::Release);
+        self.seq.notify_one();
+    }
+
+    /// Wakes every thread waiting on this condition variable.
+    ///
+    /// The woken threads then contend for the mutex. They are not requeued onto the mutex,
+    ///  as the kernel has no operation that moves the threads blocked on one address to another.
+    pub fn notify_all(&self) {
+        self.seq.fetch_add(1, Ordering::Release);
+        self.seq.notify_all();
+    }
+}
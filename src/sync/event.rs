@@ -0,0 +1,378 @@
+//! Blocking on any of several events at once, such as a timer elapsing, a thread exiting, or a stream becoming readable.
+//!
+//! [`block_on_any`] can be used in place of `select()`/`poll()` on other systems, and is not limited to I/O events.
+
+use core::{cell::UnsafeCell, ffi::c_ulong, marker::PhantomData, mem::MaybeUninit};
+
+use alloc::vec::Vec;
+
+use crate::{
+    handle::AsHandle,
+    process::ExitStatus,
+    result::{Error, Result, ResultExt},
+    sys::{
+        event::{self as sys, EventData, EventHandle, EventInfo, EventSleep},
+        except::ExceptionStatusInfo,
+        handle::HandlePtr,
+        io::IOHandle,
+        ipc::{IPCConnectionHandle, IPCServerHandle},
+        process::ProcessHandle,
+        result::{errors::PENDING, SysResult},
+        socket::SocketHandle,
+        thread::ThreadHandle,
+    },
+    time::{Clock, Duration, TimePoint},
+    uuid::Uuid,
+};
+
+/// An event that the current thread can block on.
+///
+/// ## Safety
+/// The event returned by [`Event::to_raw`] must be valid for the kernel to read from, and write to, for as long as `self` is borrowed.
+pub unsafe trait Event {
+    /// Describes the event to the kernel
+    fn to_raw(&self) -> EventInfo;
+}
+
+/// An event that produces a value when it occurs.
+pub trait TypedEvent: Event {
+    /// The value produced by the event
+    type Output;
+
+    /// Interprets the result of the event, set by the kernel when the event occurred
+    fn output(&self, result: SysResult) -> Result<Self::Output>;
+}
+
+pub(crate) fn handle_event<T>(kind: u32, hdl: HandlePtr<T>) -> EventInfo {
+    EventInfo {
+        kind,
+        flags: 0,
+        data: EventData {
+            handle: EventHandle {
+                hdl: hdl.cast(),
+                out: core::ptr::null_mut(),
+            },
+        },
+        result: PENDING,
+    }
+}
+
+/// Occurs once a [`Duration`] has elapsed, measured from when the current thread starts blocking on it.
+#[derive(Copy, Clone, Debug)]
+pub struct SleepFor(pub Duration);
+
+unsafe impl Event for SleepFor {
+    fn to_raw(&self) -> EventInfo {
+        EventInfo {
+            kind: sys::EVENT_SLEEP_FOR,
+            flags: 0,
+            data: EventData {
+                sleep: EventSleep {
+                    dur: self.0.into_system(),
+                    clock: Uuid::NIL,
+                },
+            },
+            result: PENDING,
+        }
+    }
+}
+
+impl TypedEvent for SleepFor {
+    type Output = ();
+
+    fn output(&self, result: SysResult) -> Result<()> {
+        Error::from_code(result)
+    }
+}
+
+/// Occurs once the clock `C` reaches a [`TimePoint`].
+#[derive(Copy, Clone, Debug)]
+pub struct SleepUntil<C>(pub TimePoint<C>);
+
+unsafe impl<C: Clock> Event for SleepUntil<C> {
+    fn to_raw(&self) -> EventInfo {
+        EventInfo {
+            kind: sys::EVENT_SLEEP_UNTIL,
+            flags: 0,
+            data: EventData {
+                sleep: EventSleep {
+                    dur: self.0.since_epoch().into_system(),
+                    clock: C::clock_uuid(),
+                },
+            },
+            result: PENDING,
+        }
+    }
+}
+
+impl<C: Clock> TypedEvent for SleepUntil<C> {
+    type Output = ();
+
+    fn output(&self, result: SysResult) -> Result<()> {
+        Error::from_code(result)
+    }
+}
+
+/// Occurs when a thread exits, producing its exit code. The thread is not detached or joined by the event.
+#[derive(Debug)]
+pub struct ThreadExit<'a>(HandlePtr<ThreadHandle>, PhantomData<&'a ThreadHandle>);
+
+impl<'a> ThreadExit<'a> {
+    pub fn new<H: AsHandle<'a, ThreadHandle>>(th: H) -> Self {
+        Self(th.as_handle(), PhantomData)
+    }
+}
+
+unsafe impl Event for ThreadExit<'_> {
+    fn to_raw(&self) -> EventInfo {
+        handle_event(sys::EVENT_JOIN_THREAD, self.0)
+    }
+}
+
+impl TypedEvent for ThreadExit<'_> {
+    type Output = i32;
+
+    /// Returns the exit code of the thread, or the error the thread was terminated with
+    fn output(&self, result: SysResult) -> Result<i32> {
+        result.into_value()
+    }
+}
+
+/// Occurs when a process exits, producing its [`ExitStatus`]. The process is not detached or joined by the event.
+#[derive(Debug)]
+pub struct ProcessExit<'a> {
+    hdl: HandlePtr<ProcessHandle>,
+    termsiginfo: UnsafeCell<MaybeUninit<ExceptionStatusInfo>>,
+    _phantom: PhantomData<&'a ProcessHandle>,
+}
+
+impl<'a> ProcessExit<'a> {
+    pub fn new<H: AsHandle<'a, ProcessHandle>>(hdl: H) -> Self {
+        Self {
+            hdl: hdl.as_handle(),
+            termsiginfo: UnsafeCell::new(MaybeUninit::zeroed()),
+            _phantom: PhantomData,
+        }
+    }
+}
+
+unsafe impl Event for ProcessExit<'_> {
+    fn to_raw(&self) -> EventInfo {
+        let mut info = handle_event(sys::EVENT_JOIN_PROCESS, self.hdl);
+        info.data.handle.out = self.termsiginfo.get().cast();
+        info
+    }
+}
+
+impl TypedEvent for ProcessExit<'_> {
+    type Output = ExitStatus;
+
+    fn output(&self, result: SysResult) -> Result<ExitStatus> {
+        // SAFETY: `termsiginfo` starts zeroed, which is a valid `ExceptionStatusInfo`, and the kernel only ever writes a valid one
+        ExitStatus::from_join(result, unsafe { (*self.termsiginfo.get()).assume_init() })
+    }
+}
+
+/// Occurs when the pending asynchronous operation on an [`IOHandle`] completes, producing the number of bytes transferred.
+#[derive(Debug)]
+pub struct IoComplete<'a>(HandlePtr<IOHandle>, PhantomData<&'a IOHandle>);
+
+impl<'a> IoComplete<'a> {
+    pub fn new<H: AsHandle<'a, IOHandle>>(hdl: H) -> Self {
+        Self(hdl.as_handle(), PhantomData)
+    }
+}
+
+unsafe impl Event for IoComplete<'_> {
+    fn to_raw(&self) -> EventInfo {
+        handle_event(sys::EVENT_IO_COMPLETE, self.0)
+    }
+}
+
+impl TypedEvent for IoComplete<'_> {
+    type Output = usize;
+
+    fn output(&self, result: SysResult) -> Result<usize> {
+        result.into_value()
+    }
+}
+
+mod private {
+    pub trait Sealed {}
+}
+
+/// Handles to streams which can be waited on by [`Readable`] and [`Writable`]
+pub trait StreamHandle: private::Sealed {}
+
+impl private::Sealed for IOHandle {}
+impl StreamHandle for IOHandle {}
+impl private::Sealed for SocketHandle {}
+impl StreamHandle for SocketHandle {}
+impl private::Sealed for IPCConnectionHandle {}
+impl StreamHandle for IPCConnectionHandle {}
+
+/// Occurs when a stream has data available to read, or has reached its end, producing the number of bytes available.
+#[derive(Debug)]
+pub struct Readable<'a, T>(HandlePtr<T>, PhantomData<&'a T>);
+
+impl<'a, T: StreamHandle> Readable<'a, T> {
+    pub fn new<H: AsHandle<'a, T>>(hdl: H) -> Self {
+        Self(hdl.as_handle(), PhantomData)
+    }
+}
+
+unsafe impl<T: StreamHandle> Event for Readable<'_, T> {
+    fn to_raw(&self) -> EventInfo {
+        handle_event(sys::EVENT_IO_READABLE, self.0)
+    }
+}
+
+impl<T: StreamHandle> TypedEvent for Readable<'_, T> {
+    type Output = usize;
+
+    fn output(&self, result: SysResult) -> Result<usize> {
+        result.into_value()
+    }
+}
+
+/// Occurs when a stream can accept a write without blocking.
+#[derive(Debug)]
+pub struct Writable<'a, T>(HandlePtr<T>, PhantomData<&'a T>);
+
+impl<'a, T: StreamHandle> Writable<'a, T> {
+    pub fn new<H: AsHandle<'a, T>>(hdl: H) -> Self {
+        Self(hdl.as_handle(), PhantomData)
+    }
+}
+
+unsafe impl<T: StreamHandle> Event for Writable<'_, T> {
+    fn to_raw(&self) -> EventInfo {
+        handle_event(sys::EVENT_IO_WRITABLE, self.0)
+    }
+}
+
+impl<T: StreamHandle> TypedEvent for Writable<'_, T> {
+    type Output = ();
+
+    fn output(&self, result: SysResult) -> Result<()> {
+        Error::from_code(result)
+    }
+}
+
+/// Occurs when a message or handle arrives on an IPC connection, producing the number of bytes available to read.
+#[derive(Debug)]
+pub struct IpcMessage<'a>(
+    HandlePtr<IPCConnectionHandle>,
+    PhantomData<&'a IPCConnectionHandle>,
+);
+
+impl<'a> IpcMessage<'a> {
+    pub fn new<H: AsHandle<'a, IPCConnectionHandle>>(hdl: H) -> Self {
+        Self(hdl.as_handle(), PhantomData)
+    }
+}
+
+unsafe impl Event for IpcMessage<'_> {
+    fn to_raw(&self) -> EventInfo {
+        handle_event(sys::EVENT_IPC_MESSAGE, self.0)
+    }
+}
+
+impl TypedEvent for IpcMessage<'_> {
+    type Output = usize;
+
+    fn output(&self, result: SysResult) -> Result<usize> {
+        result.into_value()
+    }
+}
+
+/// Occurs when a client connects to an IPC server. The connection is accepted with [`PollIPCConnect`][crate::sys::ipc::PollIPCConnect].
+#[derive(Debug)]
+pub struct IpcConnect<'a>(HandlePtr<IPCServerHandle>, PhantomData<&'a IPCServerHandle>);
+
+impl<'a> IpcConnect<'a> {
+    pub fn new<H: AsHandle<'a, IPCServerHandle>>(hdl: H) -> Self {
+        Self(hdl.as_handle(), PhantomData)
+    }
+}
+
+unsafe impl Event for IpcConnect<'_> {
+    fn to_raw(&self) -> EventInfo {
+        handle_event(sys::EVENT_IPC_CONNECT, self.0)
+    }
+}
+
+impl TypedEvent for IpcConnect<'_> {
+    type Output = ();
+
+    fn output(&self, result: SysResult) -> Result<()> {
+        Error::from_code(result)
+    }
+}
+
+/// The event that woke the current thread from [`block_on_any`]
+#[derive(Copy, Clone, Debug)]
+pub struct Ready {
+    index: usize,
+    result: SysResult,
+}
+
+impl Ready {
+    /// The index of the event that occurred
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// The raw result of the event that occurred
+    pub fn result(&self) -> SysResult {
+        self.result
+    }
+
+    /// Interprets the result using `event`, which should be the event at [`Ready::index`]
+    pub fn output<E: TypedEvent + ?Sized>(&self, event: &E) -> Result<E::Output> {
+        event.output(self.result)
+    }
+}
+
+/// Blocks the current thread until `event` occurs, and returns its output.
+///
+/// ## Errors
+/// Returns [`Error::Timeout`] if the blocking timeout of the current thread elapses, and [`Error::Interrupted`] if the thread is interrupted.
+/// Otherwise, returns any error produced by the event.
+pub fn block_on<E: TypedEvent + ?Sized>(event: &E) -> Result<E::Output> {
+    let mut raw = event.to_raw();
+    Error::from_code(unsafe { sys::BlockOnEventsAny(&mut raw, 1) })?;
+    event.output(raw.result)
+}
+
+/// Blocks the current thread until any of `events` occurs, and returns the event that occurred.
+///
+/// If multiple events occur at once, the one with the lowest index is returned.
+///
+/// ## Errors
+/// Returns [`Error::Timeout`] if the blocking timeout of the current thread elapses, and [`Error::Interrupted`] if the thread is interrupted.
+pub fn block_on_any(events: &[&dyn Event]) -> Result<Ready> {
+    let mut raw = events.iter().map(|ev| ev.to_raw()).collect::<Vec<_>>();
+    Error::from_code(unsafe { sys::BlockOnEventsAny(raw.as_mut_ptr(), raw.len() as c_ulong) })?;
+
+    let index = raw
+        .iter()
+        .position(|ev| ev.result != PENDING)
+        .expect("BlockOnEventsAny returned without an event occurring");
+    Ok(Ready {
+        index,
+        result: raw[index].result,
+    })
+}
+
+/// Blocks the current thread until every one of `events` occurs, and returns the raw result of each event, in order.
+///
+/// The result of each event can be interpreted using [`TypedEvent::output`].
+///
+/// ## Errors
+/// Returns [`Error::Timeout`] if the blocking timeout of the current thread elapses, and [`Error::Interrupted`] if the thread is interrupted, before every event occurs.
+pub fn block_on_all(events: &[&dyn Event]) -> Result<Vec<SysResult>> {
+    let mut raw = events.iter().map(|ev| ev.to_raw()).collect::<Vec<_>>();
+    Error::from_code(unsafe { sys::BlockOnEventsAll(raw.as_mut_ptr(), raw.len() as c_ulong) })?;
+    Ok(raw.into_iter().map(|ev| ev.result).collect())
+}
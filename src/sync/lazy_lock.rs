@@ -0,0 +1,72 @@
+use core::{cell::Cell, ops::Deref};
+
+use super::OnceLock;
+
+/// A value which is initialized on first access, and can be shared between threads.
+///
+/// Like [`OnceLock`], concurrent accesses during initialization block until the initializer finishes.
+pub struct LazyLock<T, F = fn() -> T> {
+    cell: OnceLock<T>,
+    init: Cell<Option<F>>,
+}
+
+// `init` is only accessed by the thread running the initializer, guarded by the `Once` in `cell`.
+unsafe impl<T: Send + Sync, F: Send> Sync for LazyLock<T, F> {}
+
+impl<T: core::fmt::Debug, F> core::fmt::Debug for LazyLock<T, F> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let mut d = f.debug_tuple("LazyLock");
+        match self.cell.get() {
+            Some(v) => d.field(v),
+            None => d.field(&format_args!("<uninit>")),
+        };
+        d.finish()
+    }
+}
+
+impl<T: Default> Default for LazyLock<T> {
+    fn default() -> Self {
+        Self::new(T::default)
+    }
+}
+
+impl<T, F> LazyLock<T, F> {
+    pub const fn new(init: F) -> Self {
+        Self {
+            cell: OnceLock::new(),
+            init: Cell::new(Some(init)),
+        }
+    }
+
+    /// Consumes the lazy value, returning its value if it was initialized, or the initializer otherwise
+    pub fn into_inner(this: Self) -> core::result::Result<T, F> {
+        match this.cell.into_inner() {
+            Some(v) => Ok(v),
+            None => Err(this
+                .init
+                .into_inner()
+                .expect("LazyLock instance has previously been poisoned")),
+        }
+    }
+}
+
+impl<T, F: FnOnce() -> T> LazyLock<T, F> {
+    /// Forces initialization of the value, and returns a reference to it.
+    ///
+    /// ## Panics
+    /// Panics if a previous initializer panicked.
+    pub fn force(this: &Self) -> &T {
+        this.cell.get_or_init(|| match this.init.take() {
+            Some(f) => f(),
+            None => panic!("LazyLock instance has previously been poisoned"),
+        })
+    }
+}
+
+impl<T, F: FnOnce() -> T> Deref for LazyLock<T, F> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        Self::force(self)
+    }
+}
@@ -0,0 +1,101 @@
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+use super::AtomicWaitEx;
+
+const UNLOCKED: usize = 0;
+const LOCKED: usize = 1;
+/// Locked, and at least one thread may be waiting for the lock
+const CONTENDED: usize = 2;
+
+/// The number of times to spin on a contended lock before waiting
+const SPIN_LIMIT: usize = 100;
+
+/// A raw mutual exclusion lock, for use with [`lock_api`]
+pub struct RawMutex(AtomicUsize);
+
+impl core::fmt::Debug for RawMutex {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("RawMutex")
+            .field("locked", &(self.0.load(Ordering::Relaxed) != UNLOCKED))
+            .finish()
+    }
+}
+
+impl RawMutex {
+    #[cold]
+    fn lock_contended(&self) {
+        let mut state = self.spin();
+
+        if state == UNLOCKED {
+            match self
+                .0
+                .compare_exchange(UNLOCKED, LOCKED, Ordering::Acquire, Ordering::Relaxed)
+            {
+                Ok(_) => return,
+                Err(s) => state = s,
+            }
+        }
+
+        loop {
+            if state != CONTENDED && self.0.swap(CONTENDED, Ordering::Acquire) == UNLOCKED {
+                return;
+            }
+
+            // Timeouts and interrupts cannot abandon the lock, so just try again
+            let _ = self.0.wait(CONTENDED);
+
+            state = self.spin();
+        }
+    }
+
+    fn spin(&self) -> usize {
+        let mut spin = SPIN_LIMIT;
+        loop {
+            let state = self.0.load(Ordering::Relaxed);
+            if state != LOCKED || spin == 0 {
+                return state;
+            }
+            core::hint::spin_loop();
+            spin -= 1;
+        }
+    }
+}
+
+unsafe impl lock_api::RawMutex for RawMutex {
+    #[allow(clippy::declare_interior_mutable_const)]
+    const INIT: Self = Self(AtomicUsize::new(UNLOCKED));
+
+    type GuardMarker = lock_api::GuardSend;
+
+    fn lock(&self) {
+        if self
+            .0
+            .compare_exchange(UNLOCKED, LOCKED, Ordering::Acquire, Ordering::Relaxed)
+            .is_err()
+        {
+            self.lock_contended();
+        }
+    }
+
+    fn try_lock(&self) -> bool {
+        self.0
+            .compare_exchange(UNLOCKED, LOCKED, Ordering::Acquire, Ordering::Relaxed)
+            .is_ok()
+    }
+
+    unsafe fn unlock(&self) {
+        if self.0.swap(UNLOCKED, Ordering::Release) == CONTENDED {
+            self.0.notify_one();
+        }
+    }
+
+    fn is_locked(&self) -> bool {
+        self.0.load(Ordering::Relaxed) != UNLOCKED
+    }
+}
+
+/// A mutual exclusion lock which blocks threads waiting for the lock
+pub type Mutex<T> = lock_api::Mutex<RawMutex, T>;
+
+/// An RAII guard for a locked [`Mutex`]
+pub type MutexGuard<'a, T> = lock_api::MutexGuard<'a, RawMutex, T>;
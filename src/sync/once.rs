@@ -0,0 +1,145 @@
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+use super::AtomicWaitEx;
+
+const INCOMPLETE: usize = 0;
+const RUNNING: usize = 1;
+/// Running, and at least one thread is waiting for completion
+const RUNNING_WAITERS: usize = 2;
+const COMPLETE: usize = 3;
+/// The initialization routine panicked
+const POISONED: usize = 4;
+
+/// Runs a one-time initialization routine.
+///
+/// Threads which call [`Once::call_once`] while the routine is running block until it completes.
+pub struct Once {
+    state: AtomicUsize,
+}
+
+/// The state of a [`Once`], passed to the routine given to [`Once::call_once_force`]
+#[derive(Debug)]
+pub struct OnceState {
+    poisoned: bool,
+}
+
+impl OnceState {
+    /// Whether a previous initialization routine panicked
+    pub fn is_poisoned(&self) -> bool {
+        self.poisoned
+    }
+}
+
+impl core::fmt::Debug for Once {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Once")
+            .field("completed", &self.is_completed())
+            .finish()
+    }
+}
+
+impl Default for Once {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Sets the state of the [`Once`] when the routine finishes, or unwinds
+struct CompletionGuard<'a> {
+    state: &'a AtomicUsize,
+    set_on_drop: usize,
+}
+
+impl Drop for CompletionGuard<'_> {
+    fn drop(&mut self) {
+        if self.state.swap(self.set_on_drop, Ordering::Release) == RUNNING_WAITERS {
+            self.state.notify_all();
+        }
+    }
+}
+
+impl Once {
+    pub const fn new() -> Self {
+        Self {
+            state: AtomicUsize::new(INCOMPLETE),
+        }
+    }
+
+    /// Whether an initialization routine has completed successfully
+    pub fn is_completed(&self) -> bool {
+        self.state.load(Ordering::Acquire) == COMPLETE
+    }
+
+    /// Runs `f` if no routine has completed yet on this [`Once`]. If another thread is running a routine, waits for it to complete.
+    ///
+    /// When this returns, a routine has completed, and its effects are visible to the current thread.
+    ///
+    /// ## Panics
+    /// Panics if a previous routine panicked. If `f` panics, this [`Once`] is poisoned.
+    pub fn call_once<F: FnOnce()>(&self, f: F) {
+        if self.is_completed() {
+            return;
+        }
+        let mut f = Some(f);
+        self.call(false, &mut |_| (f.take().unwrap())());
+    }
+
+    /// Same as [`Once::call_once`], except that `f` is run even if a previous routine panicked.
+    pub fn call_once_force<F: FnOnce(&OnceState)>(&self, f: F) {
+        if self.is_completed() {
+            return;
+        }
+        let mut f = Some(f);
+        self.call(true, &mut |state| (f.take().unwrap())(state));
+    }
+
+    #[cold]
+    fn call(&self, ignore_poison: bool, f: &mut dyn FnMut(&OnceState)) {
+        let mut state = self.state.load(Ordering::Acquire);
+        loop {
+            match state {
+                COMPLETE => return,
+                POISONED if !ignore_poison => {
+                    panic!("Once instance has previously been poisoned")
+                }
+                INCOMPLETE | POISONED => {
+                    if let Err(s) = self.state.compare_exchange_weak(
+                        state,
+                        RUNNING,
+                        Ordering::Acquire,
+                        Ordering::Acquire,
+                    ) {
+                        state = s;
+                        continue;
+                    }
+                    let mut guard = CompletionGuard {
+                        state: &self.state,
+                        set_on_drop: POISONED,
+                    };
+                    f(&OnceState {
+                        poisoned: state == POISONED,
+                    });
+                    guard.set_on_drop = COMPLETE;
+                    return;
+                }
+                RUNNING => {
+                    if let Err(s) = self.state.compare_exchange_weak(
+                        RUNNING,
+                        RUNNING_WAITERS,
+                        Ordering::Acquire,
+                        Ordering::Acquire,
+                    ) {
+                        state = s;
+                        continue;
+                    }
+                    state = RUNNING_WAITERS;
+                }
+                _ => {
+                    // Timeouts and interrupts cannot abandon the wait, so just check again
+                    let _ = self.state.wait(state);
+                    state = self.state.load(Ordering::Acquire);
+                }
+            }
+        }
+    }
+}
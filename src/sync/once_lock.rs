@@ -0,0 +1,118 @@
+use core::{cell::UnsafeCell, mem::MaybeUninit};
+
+use super::Once;
+
+/// A cell which can be written to only once, and shared between threads.
+pub struct OnceLock<T> {
+    once: Once,
+    value: UnsafeCell<MaybeUninit<T>>,
+}
+
+unsafe impl<T: Send> Send for OnceLock<T> {}
+unsafe impl<T: Send + Sync> Sync for OnceLock<T> {}
+
+impl<T: core::fmt::Debug> core::fmt::Debug for OnceLock<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let mut d = f.debug_tuple("OnceLock");
+        match self.get() {
+            Some(v) => d.field(v),
+            None => d.field(&format_args!("<uninit>")),
+        };
+        d.finish()
+    }
+}
+
+impl<T> Default for OnceLock<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> From<T> for OnceLock<T> {
+    fn from(value: T) -> Self {
+        let cell = Self::new();
+        let _ = cell.set(value);
+        cell
+    }
+}
+
+impl<T> OnceLock<T> {
+    pub const fn new() -> Self {
+        Self {
+            once: Once::new(),
+            value: UnsafeCell::new(MaybeUninit::uninit()),
+        }
+    }
+
+    /// Returns the value of the cell, or `None` if it has not been initialized.
+    ///
+    /// Does not block if another thread is initializing the cell.
+    pub fn get(&self) -> Option<&T> {
+        if self.once.is_completed() {
+            Some(unsafe { (*self.value.get()).assume_init_ref() })
+        } else {
+            None
+        }
+    }
+
+    /// Returns a mutable reference to the value of the cell, or `None` if it has not been initialized
+    pub fn get_mut(&mut self) -> Option<&mut T> {
+        if self.once.is_completed() {
+            Some(unsafe { self.value.get_mut().assume_init_mut() })
+        } else {
+            None
+        }
+    }
+
+    /// Initializes the cell with `value`. If the cell was already initialized, returns `value` back instead.
+    ///
+    /// Blocks if another thread is initializing the cell.
+    pub fn set(&self, value: T) -> core::result::Result<(), T> {
+        let mut value = Some(value);
+        self.get_or_init(|| value.take().unwrap());
+        match value {
+            None => Ok(()),
+            Some(value) => Err(value),
+        }
+    }
+
+    /// Returns the value of the cell, initializing it with `f` if it has not been initialized.
+    ///
+    /// If another thread is initializing the cell, blocks until it finishes. At most one call to `f` completes.
+    ///
+    /// ## Panics
+    /// If `f` panics, the panic is propagated and the cell remains uninitialized. The next call to `get_or_init` runs its function again.
+    pub fn get_or_init<F: FnOnce() -> T>(&self, f: F) -> &T {
+        if let Some(v) = self.get() {
+            return v;
+        }
+        let slot = &self.value;
+        self.once.call_once_force(|_| unsafe {
+            (*slot.get()).write(f());
+        });
+        unsafe { (*self.value.get()).assume_init_ref() }
+    }
+
+    /// Takes the value out of the cell, leaving it uninitialized
+    pub fn take(&mut self) -> Option<T> {
+        core::mem::take(self).into_inner()
+    }
+
+    /// Consumes the cell, returning its value if it was initialized
+    pub fn into_inner(self) -> Option<T> {
+        let this = core::mem::ManuallyDrop::new(self);
+        if this.once.is_completed() {
+            Some(unsafe { this.value.get().read().assume_init() })
+        } else {
+            None
+        }
+    }
+}
+
+impl<T> Drop for OnceLock<T> {
+    fn drop(&mut self) {
+        if self.once.is_completed() {
+            unsafe { self.value.get_mut().assume_init_drop() }
+        }
+    }
+}
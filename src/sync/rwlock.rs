@@ -0,0 +1,211 @@
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+use super::AtomicMaskedWaitEx;
+
+/// Set while a writer holds the lock
+const WRITER_LOCKED: usize = 0x1;
+/// Set while a writer (or a reader upgrading) waits for the lock. New readers block while this is set, so writers are not starved.
+const WRITER_WAITING: usize = 0x2;
+/// Set while an upgradable reader holds the lock
+const UPGRADABLE: usize = 0x4;
+const ONE_READER: usize = 0x8;
+const READERS_MASK: usize = !(ONE_READER - 1);
+
+// Threads waiting for shared access only compare (and are only woken by notifications of) the writer bits.
+// Threads waiting for exclusive access compare everything except `WRITER_WAITING`, so notifications which include `READERS_MASK` only wake those threads.
+const SHARED_IGNORE: usize = !(WRITER_LOCKED | WRITER_WAITING);
+const UPGRADABLE_IGNORE: usize = !(WRITER_LOCKED | WRITER_WAITING | UPGRADABLE);
+const EXCLUSIVE_IGNORE: usize = WRITER_WAITING;
+
+/// A raw reader-writer lock, for use with [`lock_api`].
+///
+/// Waiting writers take precedence over new readers.
+pub struct RawRwLock(AtomicUsize);
+
+impl core::fmt::Debug for RawRwLock {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let state = self.0.load(Ordering::Relaxed);
+        f.debug_struct("RawRwLock")
+            .field("writer", &(state & WRITER_LOCKED != 0))
+            .field("readers", &((state & READERS_MASK) / ONE_READER))
+            .finish()
+    }
+}
+
+impl RawRwLock {
+    fn try_lock_shared_with(
+        &self,
+        blocked_by: usize,
+        add: usize,
+    ) -> core::result::Result<(), usize> {
+        let mut state = self.0.load(Ordering::Relaxed);
+        loop {
+            if state & blocked_by != 0 || state & READERS_MASK == READERS_MASK {
+                return Err(state);
+            }
+            match self.0.compare_exchange_weak(
+                state,
+                state + add,
+                Ordering::Acquire,
+                Ordering::Relaxed,
+            ) {
+                Ok(_) => return Ok(()),
+                Err(s) => state = s,
+            }
+        }
+    }
+
+    fn try_lock_exclusive_from(&self, mut state: usize) -> core::result::Result<(), usize> {
+        while state & (WRITER_LOCKED | UPGRADABLE | READERS_MASK) == 0 {
+            match self.0.compare_exchange_weak(
+                state,
+                (state | WRITER_LOCKED) & !WRITER_WAITING,
+                Ordering::Acquire,
+                Ordering::Relaxed,
+            ) {
+                Ok(_) => return Ok(()),
+                Err(s) => state = s,
+            }
+        }
+        Err(state)
+    }
+
+    /// Sets [`WRITER_WAITING`], and waits for any other bit to change if the lock is still unavailable.
+    ///
+    /// If `upgrading` is set, the current thread holds the upgradable lock, and waits for the other readers to release the lock.
+    fn wait_exclusive(&self, upgrading: bool) {
+        let state = self.0.fetch_or(WRITER_WAITING, Ordering::Relaxed) | WRITER_WAITING;
+        let blocked = if upgrading {
+            state & READERS_MASK != ONE_READER
+        } else {
+            state & (WRITER_LOCKED | UPGRADABLE | READERS_MASK) != 0
+        };
+        if blocked {
+            // Timeouts and interrupts cannot abandon the lock, so the caller just tries again
+            let _ = self.0.wait_mask(state, EXCLUSIVE_IGNORE);
+        }
+    }
+
+    /// Wakes threads waiting for exclusive access if `state` (the state after a reader released the lock) has no other readers
+    fn wake_after_read(&self, state: usize) {
+        let readers = state & READERS_MASK;
+        let last = readers == 0 || (readers == ONE_READER && state & UPGRADABLE != 0);
+        if last && state & WRITER_WAITING != 0 {
+            self.0.notify_mask(usize::MAX, READERS_MASK);
+        }
+    }
+}
+
+unsafe impl lock_api::RawRwLock for RawRwLock {
+    #[allow(clippy::declare_interior_mutable_const)]
+    const INIT: Self = Self(AtomicUsize::new(0));
+
+    type GuardMarker = lock_api::GuardSend;
+
+    fn lock_shared(&self) {
+        while let Err(state) = self.try_lock_shared_with(WRITER_LOCKED | WRITER_WAITING, ONE_READER)
+        {
+            let _ = self.0.wait_mask(state, SHARED_IGNORE);
+        }
+    }
+
+    fn try_lock_shared(&self) -> bool {
+        self.try_lock_shared_with(WRITER_LOCKED | WRITER_WAITING, ONE_READER)
+            .is_ok()
+    }
+
+    unsafe fn unlock_shared(&self) {
+        let state = self.0.fetch_sub(ONE_READER, Ordering::Release) - ONE_READER;
+        self.wake_after_read(state);
+    }
+
+    fn lock_exclusive(&self) {
+        while self
+            .try_lock_exclusive_from(self.0.load(Ordering::Relaxed))
+            .is_err()
+        {
+            self.wait_exclusive(false);
+        }
+    }
+
+    fn try_lock_exclusive(&self) -> bool {
+        self.try_lock_exclusive_from(self.0.load(Ordering::Relaxed))
+            .is_ok()
+    }
+
+    unsafe fn unlock_exclusive(&self) {
+        self.0.fetch_and(!WRITER_LOCKED, Ordering::Release);
+        // Wake one writer, which sets `WRITER_WAITING` again before the readers can take the lock if it loses the race.
+        self.0.notify_mask(1, READERS_MASK);
+        self.0.notify_mask(usize::MAX, WRITER_WAITING);
+    }
+
+    fn is_locked(&self) -> bool {
+        self.0.load(Ordering::Relaxed) & (WRITER_LOCKED | READERS_MASK) != 0
+    }
+}
+
+unsafe impl lock_api::RawRwLockUpgrade for RawRwLock {
+    fn lock_upgradable(&self) {
+        while let Err(state) = self.try_lock_shared_with(
+            WRITER_LOCKED | WRITER_WAITING | UPGRADABLE,
+            ONE_READER | UPGRADABLE,
+        ) {
+            let _ = self.0.wait_mask(state, UPGRADABLE_IGNORE);
+        }
+    }
+
+    fn try_lock_upgradable(&self) -> bool {
+        self.try_lock_shared_with(
+            WRITER_LOCKED | WRITER_WAITING | UPGRADABLE,
+            ONE_READER | UPGRADABLE,
+        )
+        .is_ok()
+    }
+
+    unsafe fn unlock_upgradable(&self) {
+        let state = self.0.fetch_sub(ONE_READER | UPGRADABLE, Ordering::Release)
+            - (ONE_READER | UPGRADABLE);
+        // Wakes writers, and threads waiting for upgradable access
+        self.0.notify_mask(usize::MAX, UPGRADABLE);
+        self.wake_after_read(state);
+    }
+
+    unsafe fn upgrade(&self) {
+        while !unsafe { self.try_upgrade() } {
+            self.wait_exclusive(true);
+        }
+    }
+
+    unsafe fn try_upgrade(&self) -> bool {
+        let mut state = self.0.load(Ordering::Relaxed);
+        while state & READERS_MASK == ONE_READER {
+            match self.0.compare_exchange_weak(
+                state,
+                (state & !(READERS_MASK | UPGRADABLE | WRITER_WAITING)) | WRITER_LOCKED,
+                Ordering::Acquire,
+                Ordering::Relaxed,
+            ) {
+                Ok(_) => return true,
+                Err(s) => state = s,
+            }
+        }
+        false
+    }
+}
+
+/// A reader-writer lock, which allows either many readers or a single writer at a time.
+///
+/// Waiting writers block new readers, so a steady stream of readers cannot starve writers.
+pub type RwLock<T> = lock_api::RwLock<RawRwLock, T>;
+
+/// An RAII guard for shared access to a [`RwLock`]
+pub type RwLockReadGuard<'a, T> = lock_api::RwLockReadGuard<'a, RawRwLock, T>;
+
+/// An RAII guard for exclusive access to a [`RwLock`]
+pub type RwLockWriteGuard<'a, T> = lock_api::RwLockWriteGuard<'a, RawRwLock, T>;
+
+/// An RAII guard for upgradable shared access to a [`RwLock`], obtained by [`RwLock::upgradable_read`][lock_api::RwLock::upgradable_read].
+///
+/// At most one upgradable reader holds the lock at a time, alongside any number of readers.
+pub type RwLockUpgradableReadGuard<'a, T> = lock_api::RwLockUpgradableReadGuard<'a, RawRwLock, T>;
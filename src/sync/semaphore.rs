@@ -0,0 +1,106 @@
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::{result::Result, time::Duration};
+
+use super::{notify_word, AtomicWaitEx};
+
+/// A counting semaphore, which limits the number of threads which hold a permit at a time.
+pub struct Semaphore {
+    permits: AtomicUsize,
+}
+
+impl core::fmt::Debug for Semaphore {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Semaphore")
+            .field("permits", &self.available_permits())
+            .finish()
+    }
+}
+
+/// An RAII permit acquired from a [`Semaphore`]. The permit is returned to the semaphore when dropped.
+#[must_use = "the permit is released immediately if it is not held"]
+pub struct SemaphorePermit<'a> {
+    sem: &'a Semaphore,
+}
+
+impl core::fmt::Debug for SemaphorePermit<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("SemaphorePermit").finish_non_exhaustive()
+    }
+}
+
+impl SemaphorePermit<'_> {
+    /// Consumes the permit without returning it to the semaphore
+    pub fn forget(self) {
+        core::mem::forget(self)
+    }
+}
+
+impl Drop for SemaphorePermit<'_> {
+    fn drop(&mut self) {
+        self.sem.add_permits(1);
+    }
+}
+
+impl Semaphore {
+    /// Creates a semaphore with `permits` initially available
+    pub const fn new(permits: usize) -> Self {
+        Self {
+            permits: AtomicUsize::new(permits),
+        }
+    }
+
+    /// Returns the number of permits currently available
+    pub fn available_permits(&self) -> usize {
+        self.permits.load(Ordering::Relaxed)
+    }
+
+    /// Makes `n` additional permits available, and wakes up to `n` waiting threads
+    pub fn add_permits(&self, n: usize) {
+        self.permits.fetch_add(n, Ordering::Release);
+        notify_word(self.permits.as_ptr().cast(), n, core::ptr::null());
+    }
+
+    /// Acquires a permit if one is available, without blocking
+    pub fn try_acquire(&self) -> Option<SemaphorePermit<'_>> {
+        let mut permits = self.permits.load(Ordering::Relaxed);
+        while permits != 0 {
+            match self.permits.compare_exchange_weak(
+                permits,
+                permits - 1,
+                Ordering::Acquire,
+                Ordering::Relaxed,
+            ) {
+                Ok(_) => return Some(SemaphorePermit { sem: self }),
+                Err(p) => permits = p,
+            }
+        }
+        None
+    }
+
+    /// Acquires a permit, blocking the current thread until one is available.
+    ///
+    /// ## Errors
+    /// Returns [`Error::Timeout`][crate::result::Error::Timeout] if the blocking timeout of the current thread elapses,
+    ///  and [`Error::Interrupted`][crate::result::Error::Interrupted] if the thread is interrupted.
+    pub fn acquire(&self) -> Result<SemaphorePermit<'_>> {
+        loop {
+            if let Some(permit) = self.try_acquire() {
+                return Ok(permit);
+            }
+            self.permits.wait(0)?;
+        }
+    }
+
+    /// Acquires a permit, blocking the current thread for at most `dur` until one is available.
+    ///
+    /// ## Errors
+    /// Returns [`Error::Timeout`][crate::result::Error::Timeout] if no permit becomes available within `dur`,
+    ///  and [`Error::Interrupted`][crate::result::Error::Interrupted] if the thread is interrupted.
+    pub fn acquire_timeout(&self, dur: Duration) -> Result<SemaphorePermit<'_>> {
+        if let Some(permit) = self.try_acquire() {
+            return Ok(permit);
+        }
+        crate::thread::with_timeout(dur, || self.acquire())
+    }
+}
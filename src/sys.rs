@@ -4,6 +4,9 @@
 pub mod arch_ctl;
 pub mod debug;
 pub mod device;
+pub mod elf;
+pub mod error;
+pub mod event;
 pub mod except;
 pub mod fs;
 pub mod handle;
@@ -11,12 +14,17 @@ pub mod info;
 pub mod io;
 pub mod ipc;
 pub mod isolation;
+pub mod kmgmt;
 pub mod kstr;
+#[cfg(feature = "libc")]
+pub mod libc;
 pub mod option;
 pub mod permission;
 pub mod process;
 pub mod random;
 pub mod result;
+#[cfg(feature = "rtld")]
+pub mod rtld;
 pub mod signal;
 pub mod socket;
 pub mod thread;
@@ -1,16 +1,18 @@
 use core::mem::MaybeUninit;
 
+use crate::def_extended_option;
+
 use super::{
     kstr::{KCSlice, KSlice},
     option::ExtendedOptionHead,
     result::SysResult,
 };
 
-#[repr(C, align(32))]
-#[derive(Copy, Clone)]
-pub struct ArchConfigUnknownOption {
-    pub header: ExtendedOptionHead,
-    pub payload: [MaybeUninit<u8>; 32],
+def_extended_option! {
+    pub struct ArchConfigUnknownOption {
+        pub header: ExtendedOptionHead,
+        pub payload: [MaybeUninit<u8>; 32],
+    }
 }
 
 mod arch {
@@ -20,24 +22,25 @@ mod arch {
     pub use x86::ArchConfigArchOption;
 
     #[cfg(not(any(target_arch = "x86", target_arch = "x86_64")))]
-    #[repr(C, align(32))]
-    #[derive(Copy, Clone)]
-    pub union ArchConfigArchOption {
-        unknown: super::ArchConfigUnknownOption,
+    crate::def_extended_option! {
+        pub union ArchConfigArchOption {
+            unknown: super::ArchConfigUnknownOption,
+        }
     }
 }
 
 pub use arch::*;
 
-#[repr(C, align(32))]
-pub union ArchConfigOption {
-    /// The Header of the [`ArchConfigOption`].
-    ///
-    /// The following additional flags bits are defined:
-    /// * Bit 16: If set and bit `0` is clear, do not error for a recognized `ty` if unsupported options are set.
-    pub head: ExtendedOptionHead,
-    pub unknown: ArchConfigUnknownOption,
-    pub arch: ArchConfigArchOption,
+def_extended_option! {
+    pub union ArchConfigOption {
+        /// The Header of the [`ArchConfigOption`].
+        ///
+        /// The following additional flags bits are defined:
+        /// * Bit 16: If set and bit `0` is clear, do not error for a recognized `ty` if unsupported options are set.
+        pub head: ExtendedOptionHead,
+        pub unknown: ArchConfigUnknownOption,
+        pub arch: ArchConfigArchOption,
+    }
 }
 
 extern "system" {
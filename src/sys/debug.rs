@@ -1,9 +1,9 @@
 //! Debugging Interfaces for LiliumOS
 
-use core::ffi::c_void;
+use core::ffi::{c_ulong, c_void};
 
 use super::{
-    except::ExceptionInfo,
+    except::{ExceptionInfo, ExceptionStatusInfo},
     handle::{Handle, HandlePtr},
     kstr::KStrPtr,
     result::SysResult,
@@ -15,6 +15,7 @@ use super::{
 #[repr(transparent)]
 pub struct DebugHandle(Handle);
 
+/// Information about a mapping in the address space of a debugged thread, obtained from [`DebugGetMapping`]
 #[repr(C)]
 pub struct DebugMappingInfo {
     pub vaddr_lo: usize,
@@ -25,6 +26,9 @@ pub struct DebugMappingInfo {
     pub backing_paddr: u64,
 }
 
+/// The shift of the `MAP_KIND_*` constant in [`DebugMappingInfo::kind_and_attrs`]. The bits below it are the `MAP_ATTR_*` constants of the mapping.
+pub const DEBUG_MAPPING_KIND_SHIFT: u32 = 16;
+
 #[allow(improper_ctypes)]
 extern "C" {
     /// Attaches a debugger to the given thread, and returns a handle to that debugger.
@@ -324,4 +328,69 @@ extern "C" {
     ///
     /// If `dh` is not suspended, `INVALID_STATE` is returned.
     pub fn DebugResume(dh: HandlePtr<DebugHandle>) -> SysResult;
+
+    /// Resumes the thread referred to by `dh` for a single instruction, and suspends it again before the next instruction executes.
+    ///
+    /// This syscall waits until the thread is suspended again (this is not considered to be a blocking operation and is not subject to standard blocking syscall rules).
+    /// If executing the instruction raises an exception that is captured by `DebugCaptureSignal`, the thread is suspended at the capture instead.
+    ///
+    /// Only the thread referred to by `dh` is resumed - other threads suspended by `DebugSuspendAll` remain suspended.
+    ///
+    /// ## Errors
+    ///
+    /// If `dh` is not a valid `DebugHandle`, `INVALID_HANDLE` is returned.
+    ///
+    /// If `dh` is not suspended, `INVALID_STATE` is returned.
+    ///
+    /// If the thread terminates while executing the instruction, `KILLED` is returned.
+    ///
+    /// ## Architecture Specific Notes
+    ///
+    /// ### x86_64
+    ///
+    /// The step is performed by setting the trap flag (bit 8 of `rflags`) for the instruction. The trap flag is not visible to the thread or to `DebugReadRegister`.
+    pub fn DebugStep(dh: HandlePtr<DebugHandle>) -> SysResult;
+
+    /// Resumes all threads suspended by this handle, and determines what happens to the exception captured by `DebugCaptureSignal`, if any.
+    ///
+    /// If `except` is null, the captured exception is suppressed, and the thread resumes at the point it was suspended as though the exception did not occur.
+    /// Otherwise, the exception `except` is delivered to the thread in place of the captured exception (which may be `except` unmodified).
+    ///
+    /// If no exception was captured, and `except` is not null, `except` is delivered to the thread as an asynchronous exception.
+    ///
+    /// ## Errors
+    ///
+    /// If `dh` is not a valid `DebugHandle`, `INVALID_HANDLE` is returned.
+    ///
+    /// If `dh` is not suspended, `INVALID_STATE` is returned.
+    ///
+    /// If `except` is not null and does not point to suitable storage readable by the current thread, `INVALID_MEMORY` is returned.
+    pub fn DebugResumeWithException(
+        dh: HandlePtr<DebugHandle>,
+        except: *const ExceptionStatusInfo,
+    ) -> SysResult;
+
+    /// Obtains information about the mapping number `idx` in the address space of the thread debugged by `dh`. Mappings are numbered in order of their address, starting from `0`.
+    ///
+    /// `vaddr_lo` is the address of the first byte of the mapping, and `vaddr_hi` is the address one past its last byte.
+    /// The name of the mapping is written to `mapping_name`, and its length is updated to the length of the name.
+    ///
+    /// The thread must be suspended before this syscall is used.
+    ///
+    /// ## Errors
+    ///
+    /// If `dh` is not a valid `DebugHandle`, `INVALID_HANDLE` is returned.
+    ///
+    /// If `dh` is not suspended, `INVALID_STATE` is returned.
+    ///
+    /// If `info` does not point to suitable storage writable by the current thread, `INVALID_MEMORY` is returned.
+    ///
+    /// If `idx` is not less than the number of mappings, `FINISHED_ENUMERATE` is returned.
+    ///
+    /// If `mapping_name` is too short for the name, `INSUFFICIENT_LENGTH` is returned. The other fields are written in this case.
+    pub fn DebugGetMapping(
+        dh: HandlePtr<DebugHandle>,
+        idx: c_ulong,
+        info: *mut DebugMappingInfo,
+    ) -> SysResult;
 }
@@ -26,6 +26,7 @@ use super::{
     result::SysResult,
 };
 
+pub mod tty;
 pub mod udev;
 
 /// Configuration for a block device created by [`CreateBlockDevice`]
@@ -217,6 +218,20 @@ extern "C" {
         file: HandlePtr<FileHandle>,
     ) -> SysResult;
 
+    /// Opens the device that `hdl` performs I/O on, such as the terminal an inherited standard stream refers to.
+    ///
+    /// ## Errors
+    ///
+    /// Returns `INVALID_HANDLE` if `hdl` is not a valid `IOHandle`.
+    ///
+    /// Returns `UNSUPPORTED_OPERATION` if `hdl` does not refer to a device, such as a pipe or a regular file.
+    ///
+    /// Returns `PERMISSION` if the current thread does not have permission to open the device.
+    pub fn OpenDeviceFromIOHandle(
+        devhdl: *mut HandlePtr<DeviceHandle>,
+        hdl: HandlePtr<IOHandle>,
+    ) -> SysResult;
+
     /// Issues a Command to a device. The supported commands are device specific, and the parameters for each command is command specific
     pub fn IssueDeviceCommand(hdl: HandlePtr<DeviceHandle>, cmd: *const Uuid, ...) -> SysResult;
 
@@ -227,6 +242,19 @@ extern "C" {
         opts: *const MountOptions,
     ) -> SysResult;
 
+    /// Unmounts the filesystem mounted at `path`, resolved relative to `resolution_base`.
+    ///
+    /// ## Errors
+    ///
+    /// Returns `INVALID_HANDLE` if `resolution_base` is not a valid [`FileHandle`] and is not null.
+    ///
+    /// Returns `DOES_NOT_EXIST` if `path` does not exist, or `INVALID_OPERATION` if no filesystem is mounted at `path`.
+    ///
+    /// Returns `PERMISSION` if the current thread does not have permission to unmount the filesystem.
+    ///
+    /// Returns `BUSY` if the filesystem is in use and cannot be unmounted.
+    pub fn UnmountFilesystem(resolution_base: HandlePtr<FileHandle>, path: KStrCPtr) -> SysResult;
+
     pub fn RegisterDeviceCommand(
         devid: *const Uuid,
         cmdid: *mut Uuid,
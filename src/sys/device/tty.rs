@@ -0,0 +1,48 @@
+//! Device commands of terminal character devices, such as virtual consoles, serial lines, and pseudo-terminals.
+//!
+//! Terminal devices support the [`TERMINAL_FEATURE`] feature (see [`TestDeviceFeature`][super::TestDeviceFeature]),
+//! and the commands in this module, which are issued with [`IssueDeviceCommand`][super::IssueDeviceCommand].
+//!
+//! ## Errors
+//!
+//! Each command returns `UNSUPPORTED_OPERATION` if the device is not a terminal, and `PERMISSION` if the handle does not have the access the command requires
+//! (read access to query the terminal, and write access to change its mode).
+
+use crate::uuid::{parse_uuid, Uuid};
+
+/// The name of the device feature supported by every terminal device
+pub const TERMINAL_FEATURE: &str = "Terminal";
+
+/// Obtains the size of the terminal, in character cells.
+///
+/// Parameters:
+/// * `rows`: `DIR_INOUT` `PARAM_TY_LONG`, set to the number of rows.
+/// * `cols`: `DIR_INOUT` `PARAM_TY_LONG`, set to the number of columns.
+///
+/// Returns `UNSUPPORTED_OPERATION` if the size of the terminal is not known, such as for a serial line.
+pub const TERMINAL_CMD_GET_WINDOW_SIZE: Uuid = parse_uuid("be387ee9-3454-55a6-bfa7-dafa8d52aa18");
+
+/// Obtains the mode of the terminal, which is a combination of the `TERMINAL_MODE_*` flags.
+///
+/// Parameters:
+/// * `mode`: `DIR_INOUT` `PARAM_TY_INT`, set to the mode.
+pub const TERMINAL_CMD_GET_MODE: Uuid = parse_uuid("a62a2172-291f-5481-99d0-7d83d6792fb9");
+
+/// Sets the mode of the terminal, which is a combination of the `TERMINAL_MODE_*` flags.
+///
+/// The mode is a property of the terminal, and is shared by every handle to it.
+///
+/// Parameters:
+/// * `mode`: `DIR_IN` `PARAM_TY_INT`, the new mode.
+///
+/// Returns `INVALID_OPTION` if `mode` contains unknown flags.
+pub const TERMINAL_CMD_SET_MODE: Uuid = parse_uuid("96220c47-d96b-59f8-a051-d081600f37fb");
+
+/// Input characters are echoed to the terminal as they are typed
+pub const TERMINAL_MODE_ECHO: u32 = 0x01;
+/// Input is line-buffered and can be edited (such as by backspace) before it is read. Otherwise, each byte is available to read as soon as it is typed.
+pub const TERMINAL_MODE_CANONICAL: u32 = 0x02;
+/// Interrupt and suspend characters (such as `Ctrl+C`) raise signals in the foreground process, rather than being read as input
+pub const TERMINAL_MODE_SIGNALS: u32 = 0x04;
+/// Output newlines are translated to a carriage return and line feed
+pub const TERMINAL_MODE_OUTPUT_PROCESSING: u32 = 0x08;
@@ -0,0 +1,343 @@
+//! ELF definitions used by the program loader, including the Auxiliary Vector passed to a process when it is started.
+//!
+//! The auxiliary vector is an array of [`AuxvEntry`]s, terminated by an entry with type [`AT_NULL`].
+//! The types below `0x10000` have the same meaning as the ELF auxiliary vector entries on other System V platforms. Types from `0x10000` onwards are specific to Lilium.
+
+use core::ffi::c_ulong;
+
+/// The size of [`Elf64Ehdr::e_ident`] and [`Elf32Ehdr::e_ident`]
+pub const EI_NIDENT: usize = 16;
+/// The index of the class (one of the `ELFCLASS*` constants) in `e_ident`
+pub const EI_CLASS: usize = 4;
+/// The index of the data encoding (one of the `ELFDATA*` constants) in `e_ident`
+pub const EI_DATA: usize = 5;
+/// The index of the version of the ELF identification in `e_ident`, which is [`EV_CURRENT`]
+pub const EI_VERSION: usize = 6;
+/// The index of the OS ABI in `e_ident`
+pub const EI_OSABI: usize = 7;
+/// The index of the version of the OS ABI in `e_ident`
+pub const EI_ABIVERSION: usize = 8;
+
+/// The magic number at the start of `e_ident`
+pub const ELFMAG: [u8; 4] = *b"\x7fELF";
+
+/// The file uses 32-bit structures
+pub const ELFCLASS32: u8 = 1;
+/// The file uses 64-bit structures
+pub const ELFCLASS64: u8 = 2;
+
+/// The file is little-endian
+pub const ELFDATA2LSB: u8 = 1;
+/// The file is big-endian
+pub const ELFDATA2MSB: u8 = 2;
+
+/// The current version of ELF
+pub const EV_CURRENT: u32 = 1;
+
+/// A relocatable object file
+pub const ET_REL: u16 = 1;
+/// An executable loaded at a fixed address
+pub const ET_EXEC: u16 = 2;
+/// A shared object, or a position-independent executable
+pub const ET_DYN: u16 = 3;
+/// A core dump
+pub const ET_CORE: u16 = 4;
+
+pub const EM_NONE: u16 = 0;
+pub const EM_386: u16 = 3;
+pub const EM_ARM: u16 = 40;
+pub const EM_X86_64: u16 = 62;
+pub const EM_AARCH64: u16 = 183;
+pub const EM_RISCV: u16 = 243;
+
+/// An ELF file header of a 64-bit file
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Zeroable, bytemuck::Pod)]
+pub struct Elf64Ehdr {
+    pub e_ident: [u8; EI_NIDENT],
+    pub e_type: u16,
+    pub e_machine: u16,
+    pub e_version: u32,
+    pub e_entry: u64,
+    pub e_phoff: u64,
+    pub e_shoff: u64,
+    pub e_flags: u32,
+    pub e_ehsize: u16,
+    pub e_phentsize: u16,
+    pub e_phnum: u16,
+    pub e_shentsize: u16,
+    pub e_shnum: u16,
+    pub e_shstrndx: u16,
+}
+
+/// An ELF file header of a 32-bit file
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Zeroable, bytemuck::Pod)]
+pub struct Elf32Ehdr {
+    pub e_ident: [u8; EI_NIDENT],
+    pub e_type: u16,
+    pub e_machine: u16,
+    pub e_version: u32,
+    pub e_entry: u32,
+    pub e_phoff: u32,
+    pub e_shoff: u32,
+    pub e_flags: u32,
+    pub e_ehsize: u16,
+    pub e_phentsize: u16,
+    pub e_phnum: u16,
+    pub e_shentsize: u16,
+    pub e_shnum: u16,
+    pub e_shstrndx: u16,
+}
+
+/// An ELF program header of a 64-bit file
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Zeroable, bytemuck::Pod)]
+pub struct Elf64Phdr {
+    pub p_type: u32,
+    pub p_flags: u32,
+    pub p_offset: u64,
+    pub p_vaddr: u64,
+    pub p_paddr: u64,
+    pub p_filesz: u64,
+    pub p_memsz: u64,
+    pub p_align: u64,
+}
+
+/// An ELF program header of a 32-bit file
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Zeroable, bytemuck::Pod)]
+pub struct Elf32Phdr {
+    pub p_type: u32,
+    pub p_offset: u32,
+    pub p_vaddr: u32,
+    pub p_paddr: u32,
+    pub p_filesz: u32,
+    pub p_memsz: u32,
+    pub p_flags: u32,
+    pub p_align: u32,
+}
+
+/// An entry of the dynamic section of a 64-bit file
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Zeroable, bytemuck::Pod)]
+pub struct Elf64Dyn {
+    /// The type of the entry, one of the `DT_*` constants
+    pub d_tag: i64,
+    /// The value of the entry, which is an integer or an address depending on `d_tag`
+    pub d_val: u64,
+}
+
+/// An entry of the dynamic section of a 32-bit file
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Zeroable, bytemuck::Pod)]
+pub struct Elf32Dyn {
+    /// The type of the entry, one of the `DT_*` constants
+    pub d_tag: i32,
+    /// The value of the entry, which is an integer or an address depending on `d_tag`
+    pub d_val: u32,
+}
+
+cfg_if::cfg_if! {
+    if #[cfg(target_pointer_width = "64")] {
+        /// An ELF file header of the native class
+        pub type ElfEhdr = Elf64Ehdr;
+        /// An ELF program header of the native class, as pointed to by [`AT_PHDR`]
+        pub type ElfPhdr = Elf64Phdr;
+        /// An entry of the dynamic section of the native class
+        pub type ElfDyn = Elf64Dyn;
+    } else {
+        /// An ELF file header of the native class
+        pub type ElfEhdr = Elf32Ehdr;
+        /// An ELF program header of the native class, as pointed to by [`AT_PHDR`]
+        pub type ElfPhdr = Elf32Phdr;
+        /// An entry of the dynamic section of the native class
+        pub type ElfDyn = Elf32Dyn;
+    }
+}
+
+/// The program header is unused
+pub const PT_NULL: u32 = 0;
+/// The program header describes a loadable segment
+pub const PT_LOAD: u32 = 1;
+/// The program header describes the dynamic section
+pub const PT_DYNAMIC: u32 = 2;
+/// The program header names the interpreter of the program
+pub const PT_INTERP: u32 = 3;
+/// The program header describes auxiliary information, such as the build id
+pub const PT_NOTE: u32 = 4;
+/// Reserved
+pub const PT_SHLIB: u32 = 5;
+/// The program header describes the program headers themselves
+pub const PT_PHDR: u32 = 6;
+/// The program header describes the thread-local storage template
+pub const PT_TLS: u32 = 7;
+/// The program header describes the `.eh_frame_hdr` section, used for unwinding
+pub const PT_GNU_EH_FRAME: u32 = 0x6474e550;
+/// The flags of the program header determine whether the stack is executable
+pub const PT_GNU_STACK: u32 = 0x6474e551;
+/// The program header describes memory that is made read-only after relocation
+pub const PT_GNU_RELRO: u32 = 0x6474e552;
+
+/// The segment is executable
+pub const PF_X: u32 = 0x1;
+/// The segment is writable
+pub const PF_W: u32 = 0x2;
+/// The segment is readable
+pub const PF_R: u32 = 0x4;
+
+/// The header of a note in a [`PT_NOTE`] segment, which is followed by the name and then the descriptor, each padded to a multiple of 4 bytes
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Zeroable, bytemuck::Pod)]
+pub struct ElfNhdr {
+    pub n_namesz: u32,
+    pub n_descsz: u32,
+    pub n_type: u32,
+}
+
+/// The name of the notes specific to Lilium, including the nul terminator
+pub const NOTE_NAME_LILIUM: &[u8] = b"Lilium\0";
+
+/// A note of a core dump that contains the registers of a thread, as a sequence of [`CoreRegister`]s, each followed by the value of the register padded to a multiple of 8 bytes
+pub const NT_LILIUM_REGISTERS: u32 = 1;
+
+/// A register saved in a [`NT_LILIUM_REGISTERS`] note
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Zeroable, bytemuck::Pod)]
+pub struct CoreRegister {
+    /// The DWARF register number of the register
+    pub regno: u32,
+    /// The `meta` value the register was read with, as for [`DebugReadRegister`][super::debug::DebugReadRegister]
+    pub meta: u32,
+    /// The size of the value in bytes
+    pub size: u32,
+    pub __reserved: u32,
+}
+
+/// Terminates the dynamic section
+pub const DT_NULL: i64 = 0;
+/// The string table offset of the name of a needed shared object
+pub const DT_NEEDED: i64 = 1;
+/// The size of the relocations of the procedure linkage table
+pub const DT_PLTRELSZ: i64 = 2;
+/// The address of the procedure linkage table or global offset table
+pub const DT_PLTGOT: i64 = 3;
+/// The address of the symbol hash table
+pub const DT_HASH: i64 = 4;
+/// The address of the string table
+pub const DT_STRTAB: i64 = 5;
+/// The address of the symbol table
+pub const DT_SYMTAB: i64 = 6;
+/// The address of the relocations with addends
+pub const DT_RELA: i64 = 7;
+/// The size of [`DT_RELA`]
+pub const DT_RELASZ: i64 = 8;
+/// The size of each entry of [`DT_RELA`]
+pub const DT_RELAENT: i64 = 9;
+/// The size of the string table
+pub const DT_STRSZ: i64 = 10;
+/// The size of each symbol table entry
+pub const DT_SYMENT: i64 = 11;
+/// The address of the initialization function
+pub const DT_INIT: i64 = 12;
+/// The address of the finalization function
+pub const DT_FINI: i64 = 13;
+/// The string table offset of the name of the shared object
+pub const DT_SONAME: i64 = 14;
+/// The string table offset of the library search path. Superseded by [`DT_RUNPATH`].
+pub const DT_RPATH: i64 = 15;
+/// Symbols are resolved in the object itself first
+pub const DT_SYMBOLIC: i64 = 16;
+/// The address of the relocations without addends
+pub const DT_REL: i64 = 17;
+/// The size of [`DT_REL`]
+pub const DT_RELSZ: i64 = 18;
+/// The size of each entry of [`DT_REL`]
+pub const DT_RELENT: i64 = 19;
+/// The type of the relocations of the procedure linkage table, [`DT_REL`] or [`DT_RELA`]
+pub const DT_PLTREL: i64 = 20;
+/// Reserved for debuggers
+pub const DT_DEBUG: i64 = 21;
+/// Relocations may modify non-writable segments
+pub const DT_TEXTREL: i64 = 22;
+/// The address of the relocations of the procedure linkage table
+pub const DT_JMPREL: i64 = 23;
+/// Every relocation is processed when the object is loaded
+pub const DT_BIND_NOW: i64 = 24;
+/// The address of the array of initialization functions
+pub const DT_INIT_ARRAY: i64 = 25;
+/// The address of the array of finalization functions
+pub const DT_FINI_ARRAY: i64 = 26;
+/// The size of [`DT_INIT_ARRAY`]
+pub const DT_INIT_ARRAYSZ: i64 = 27;
+/// The size of [`DT_FINI_ARRAY`]
+pub const DT_FINI_ARRAYSZ: i64 = 28;
+/// The string table offset of the library search path
+pub const DT_RUNPATH: i64 = 29;
+/// Flags for the object
+pub const DT_FLAGS: i64 = 30;
+/// The address of the GNU-style symbol hash table
+pub const DT_GNU_HASH: i64 = 0x6ffffef5;
+
+/// An entry in the auxiliary vector
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Zeroable, bytemuck::Pod)]
+pub struct AuxvEntry {
+    /// The type of the entry, one of the `AT_*` constants
+    pub a_type: c_ulong,
+    /// The value of the entry. Whether the value is an integer or a pointer depends on `a_type`
+    pub a_val: usize,
+}
+
+/// Terminates the auxiliary vector
+pub const AT_NULL: c_ulong = 0;
+/// The entry should be ignored
+pub const AT_IGNORE: c_ulong = 1;
+/// The file descriptor of the executable, if it was not mapped by the kernel
+pub const AT_EXECFD: c_ulong = 2;
+/// The address of the program headers of the executable
+pub const AT_PHDR: c_ulong = 3;
+/// The size of each program header of the executable
+pub const AT_PHENT: c_ulong = 4;
+/// The number of program headers of the executable
+pub const AT_PHNUM: c_ulong = 5;
+/// The size of a page of memory
+pub const AT_PAGESZ: c_ulong = 6;
+/// The base address the interpreter was loaded at
+pub const AT_BASE: c_ulong = 7;
+/// Flags for the interpreter
+pub const AT_FLAGS: c_ulong = 8;
+/// The entry point of the executable
+pub const AT_ENTRY: c_ulong = 9;
+/// Nonzero if the program is not an ELF file
+pub const AT_NOTELF: c_ulong = 10;
+/// A pointer to a nul-terminated string that identifies the platform
+pub const AT_PLATFORM: c_ulong = 15;
+/// A bitmask of architecture-specific hardware capabilities
+pub const AT_HWCAP: c_ulong = 16;
+/// Nonzero if the program was started with elevated privileges, and should not trust its environment
+pub const AT_SECURE: c_ulong = 23;
+/// The address of 16 random bytes
+pub const AT_RANDOM: c_ulong = 25;
+/// Additional architecture-specific hardware capabilities
+pub const AT_HWCAP2: c_ulong = 26;
+/// A pointer to the path of the executable, as a nul-terminated string
+pub const AT_EXECFN: c_ulong = 31;
+/// The address of the ELF header of the vDSO, if the kernel provides one
+pub const AT_SYSINFO_EHDR: c_ulong = 33;
+
+/// A pointer to the array of handles passed in [`ProcessStartContext::init_handles`][crate::sys::process::ProcessStartContext::init_handles].
+///
+/// The handles are owned by the initial thread of the process.
+pub const AT_LILIUM_INIT_HANDLES: c_ulong = 0x10000;
+/// The length of the array given by [`AT_LILIUM_INIT_HANDLES`]
+pub const AT_LILIUM_INIT_HANDLES_LEN: c_ulong = 0x10001;
+
+extern "C" {
+    /// The auxiliary vector of the process, terminated by an [`AT_NULL`] entry, or null if the process was started without one.
+    ///
+    /// Like the standard stream handles in [`sys::io`][super::io], this is defined by the USI runtime, not the kernel.
+    /// The kernel places the auxiliary vector on the initial stack of the process, after the environment pointers, as in the System V ELF ABI,
+    ///  and the startup code of the USI runtime stores its address here before any user code runs, including initializers of shared objects.
+    pub static __AUXV: *const AuxvEntry;
+}
@@ -0,0 +1,61 @@
+//! Interfaces for retrieving the context of the errors returned to the current thread.
+//!
+//! When a system call fails, the kernel may record entries describing why it failed, such as which option was invalid, or which permission was denied.
+//! The entries are kept until the next system call on the thread that records error context, which replaces them.
+
+use crate::uuid::{parse_uuid, Uuid};
+
+use super::{kstr::KStrPtr, result::SysResult};
+
+/// An option passed to the system call was invalid or unsupported.
+///
+/// `index` is the index of the option in the option array passed to the system call, and `subject` is the type of the option.
+pub const ERROR_CONTEXT_INVALID_OPTION: Uuid = parse_uuid("f9e2be20-df74-5fc1-9ae4-2247bf733eb0");
+
+/// A permission check failed.
+///
+/// `name` is the name of the permission that was denied, and `subject` is the principal the check was made for (or nil).
+pub const ERROR_CONTEXT_PERMISSION: Uuid = parse_uuid("d55691b8-c1a1-5a46-963b-759a4ad3506e");
+
+/// Resolving a path failed.
+///
+/// `index` is the index (starting from `0`) of the path component that could not be resolved, and `name` is that component.
+pub const ERROR_CONTEXT_PATH_RESOLUTION: Uuid = parse_uuid("b55d64dd-c5ff-5ede-85da-dd699b92e4db");
+
+/// An entry of error context, which describes why a system call failed
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct ErrorContextEntry {
+    /// The kind of the entry, such as [`ERROR_CONTEXT_INVALID_OPTION`], which determines the meaning of the remaining fields
+    pub kind: Uuid,
+    /// The identifier associated with the entry, or nil if the kind does not have one
+    pub subject: Uuid,
+    /// The error code returned by the system call the entry describes
+    pub code: SysResult,
+    /// The index associated with the entry, or `0` if the kind does not have one
+    pub index: usize,
+    /// The name associated with the entry, or empty if the kind does not have one
+    pub name: KStrPtr,
+}
+
+#[allow(improper_ctypes)]
+extern "system" {
+    /// Returns the number of error context entries recorded for the last failed system call on the current thread, or `0` if there are none.
+    pub fn GetErrorContextCount() -> SysResult;
+
+    /// Reads the error context entry at `index` into `entry`.
+    ///
+    /// The caller sets `entry.name` to a buffer to store the name of the entry.
+    ///
+    /// ## Errors
+    ///
+    /// Returns `DOES_NOT_EXIST` if `index` is not less than the value returned by [`GetErrorContextCount`].
+    ///
+    /// Returns `INSUFFICIENT_LENGTH` if `entry.name` is too short to store the name, and sets `entry.name.len` to the required length. The other fields of `entry` are set.
+    ///
+    /// Returns `INVALID_MEMORY` if `entry` or `entry.name` does not point to writable memory.
+    pub fn GetErrorContext(index: usize, entry: *mut ErrorContextEntry) -> SysResult;
+
+    /// Discards the error context entries recorded for the current thread.
+    pub fn ClearErrorContext();
+}
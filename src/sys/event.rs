@@ -0,0 +1,121 @@
+//! Blocking on any of several events at once, such as the completion of an I/O operation, or the exit of a thread.
+
+use core::ffi::{c_ulong, c_void};
+
+use crate::uuid::Uuid;
+
+use super::{
+    handle::{Handle, HandlePtr},
+    result::SysResult,
+    time::Duration,
+};
+
+/// Occurs once `data.sleep.dur` has elapsed, measured from the start of the system call. `data.sleep.clock` is ignored.
+///
+/// The result is `0`.
+pub const EVENT_SLEEP_FOR: u32 = 0;
+/// Occurs once the clock designated by `data.sleep.clock` reaches the offset `data.sleep.dur`.
+///
+/// The result is `0`, or `UNKNOWN_DEVICE` if `data.sleep.clock` is not a valid Clock device id.
+pub const EVENT_SLEEP_UNTIL: u32 = 1;
+/// Occurs when the thread designated by `data.handle.hdl` exits. The handle remains valid.
+///
+/// The result is the same as the result of [`JoinThread`][super::thread::JoinThread] on the thread.
+pub const EVENT_JOIN_THREAD: u32 = 2;
+/// Occurs when the process designated by `data.handle.hdl` exits. The handle remains valid.
+///
+/// The result is the same as the result of [`JoinProcess`][super::process::JoinProcess] on the process.
+/// If the result is `SIGNALED`, the [`ExceptionStatusInfo`][super::except::ExceptionStatusInfo] that caused the termination is written to `data.handle.out`.
+pub const EVENT_JOIN_PROCESS: u32 = 3;
+/// Occurs when the pending asynchronous operation on the [`IOHandle`][super::io::IOHandle] designated by `data.handle.hdl` completes.
+///
+/// The result is the same as the result of [`IOJoin`][super::io::IOJoin] on the handle. If no operation is pending, the event occurs immediately with the result `0`.
+pub const EVENT_IO_COMPLETE: u32 = 4;
+/// Occurs when the stream designated by `data.handle.hdl` has data available to read, or has reached the end of the stream.
+///
+/// The handle may be an [`IOHandle`][super::io::IOHandle], a [`SocketHandle`][super::socket::SocketHandle], or an [`IPCConnectionHandle`][super::ipc::IPCConnectionHandle].
+///
+/// The result is the number of bytes available, as by [`IOPoll`][super::io::IOPoll].
+pub const EVENT_IO_READABLE: u32 = 5;
+/// Occurs when the stream designated by `data.handle.hdl` can accept a write without blocking.
+///
+/// The handle may be an [`IOHandle`][super::io::IOHandle], a [`SocketHandle`][super::socket::SocketHandle], or an [`IPCConnectionHandle`][super::ipc::IPCConnectionHandle].
+///
+/// The result is `0`.
+pub const EVENT_IO_WRITABLE: u32 = 6;
+/// Occurs when a message or a handle sent by the other end of the [`IPCConnectionHandle`][super::ipc::IPCConnectionHandle] designated by `data.handle.hdl` arrives.
+///
+/// The result is the number of bytes available to read.
+pub const EVENT_IPC_MESSAGE: u32 = 7;
+/// Occurs when a client connects to the [`IPCServerHandle`][super::ipc::IPCServerHandle] designated by `data.handle.hdl`.
+/// The connection is obtained by calling [`PollIPCConnect`][super::ipc::PollIPCConnect].
+///
+/// The result is `0`.
+pub const EVENT_IPC_CONNECT: u32 = 8;
+/// Occurs when the [`WatchHandle`][super::fs::WatchHandle] designated by `data.handle.hdl` has an event queued, to be read by [`ReadWatchEvent`][super::fs::ReadWatchEvent].
+///
+/// The result is the number of events queued.
+pub const EVENT_FS_WATCH: u32 = 9;
+
+/// The handle an event refers to
+#[repr(C)]
+#[derive(Copy, Clone, Debug)]
+pub struct EventHandle {
+    pub hdl: HandlePtr<Handle>,
+    /// An output parameter for the event, or null if the event has none
+    pub out: *mut c_void,
+}
+
+/// The time an event refers to
+#[repr(C)]
+#[derive(Copy, Clone, Debug)]
+pub struct EventSleep {
+    pub dur: Duration,
+    pub clock: Uuid,
+}
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub union EventData {
+    pub handle: EventHandle,
+    pub sleep: EventSleep,
+}
+
+/// An event for [`BlockOnEventsAny`] and [`BlockOnEventsAll`]
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct EventInfo {
+    /// One of the `EVENT_*` constants
+    pub kind: u32,
+    /// Reserved, must be `0`
+    pub flags: u32,
+    /// The object the event refers to, determined by `kind`
+    pub data: EventData,
+    /// Set to the result of the event when it occurs, or `PENDING` if it has not occured
+    pub result: SysResult,
+}
+
+#[allow(improper_ctypes)]
+extern "C" {
+    /// Blocks the current thread until at least one of the `count` events in `events` occurs.
+    ///
+    /// Sets the `result` field of each event that occurred, and sets the `result` field of each other event to `PENDING`. Returns the index of an event that occurred.
+    ///
+    /// ## Errors
+    ///
+    /// Returns `INVALID_MEMORY` if `events` does not point to `count` readable and writable events.
+    ///
+    /// Returns `INVALID_OPTION` if an event has an unknown `kind`, or sets a reserved flag.
+    ///
+    /// Returns `INVALID_HANDLE` if the handle of an event is not valid, or is not of the type that `kind` requires.
+    ///
+    /// Returns `TIMEOUT` if the blocking timeout of the current thread elapses, and `INTERRUPTED` if the thread is interrupted, before any event occurs.
+    pub fn BlockOnEventsAny(events: *mut EventInfo, count: c_ulong) -> SysResult;
+
+    /// Blocks the current thread until every one of the `count` events in `events` occurs, and sets the `result` field of each event.
+    ///
+    /// ## Errors
+    ///
+    /// Same as [`BlockOnEventsAny`]. If the call fails with `TIMEOUT` or `INTERRUPTED`, the `result` field of each event that occurred is set, and each other is set to `PENDING`.
+    pub fn BlockOnEventsAll(events: *mut EventInfo, count: c_ulong) -> SysResult;
+}
@@ -3,7 +3,9 @@ use core::{
     mem::MaybeUninit,
 };
 
-use crate::uuid::Uuid;
+use crate::uuid::{parse_uuid, Uuid};
+
+use crate::def_extended_option;
 
 use super::{
     handle::{Handle, HandlePtr},
@@ -16,6 +18,44 @@ use super::{
 #[repr(transparent)]
 pub struct ExceptionContextHandle(Handle);
 
+/// Raised in every thread of a process when the process is aborted. `except_info` is the exit code of the process.
+pub const EXCEPT_PROCESS_ABORT: Uuid = parse_uuid("171afd10-5db5-304f-b5b0-b5e816bd7d1b");
+/// Raised asynchronously in a thread to request that it stops. Unless handled, the thread is terminated.
+pub const EXCEPT_REMOTE_STOP: Uuid = parse_uuid("0395a97e-51a1-3150-9db0-a609af16be33");
+/// Raised by a breakpoint or debug trap instruction, or by single-stepping, when the thread is not being debugged.
+pub const EXCEPT_DEBUG_TRAP: Uuid = parse_uuid("5e25353a-fedb-3fe8-b22e-9339d7f2ea84");
+/// Raised by an access to memory that is unmapped, or not mapped with the required access. `except_info` is the address accessed.
+pub const EXCEPT_MEMORY_ACCESS_VIOLATION: Uuid = parse_uuid("4acfb7e6-f367-3e43-9d62-3e665335999c");
+/// Raised by executing an instruction that is invalid, or not permitted in userspace.
+pub const EXCEPT_ILLEGAL_INSTRUCTION: Uuid = parse_uuid("ca5c6091-f6e4-3c85-9f67-174ef9cf6964");
+/// Raised by an arithmetic instruction that faults, such as an integer division by zero.
+pub const EXCEPT_ARITHMETIC_ERROR: Uuid = parse_uuid("bec29c0f-b193-362d-8f57-6d0b55170452");
+/// Raised when the thread overflows its stack. The exception handler is run on an alternate stack, if one is set.
+pub const EXCEPT_STACK_OVERFLOW: Uuid = parse_uuid("7639defe-0d3c-34ed-88ed-af9d1ab83b50");
+/// Reported when a Rust program panics. `except_info` is the address of the UTF-8 panic message, and `except_reason` is its length in bytes.
+pub const EXCEPT_RUST_PANIC: Uuid = parse_uuid("6016cd99-07ec-3773-b9f3-9869eced2633");
+
+/// Raised asynchronously in the signal handling thread of a process when an interrupt is requested, such as by `Ctrl+C` on its terminal.
+pub const EXCEPT_SIGNAL_INTERRUPT: Uuid = parse_uuid("90b4a697-5ac0-3545-baaf-4201bc3e6803");
+/// Raised asynchronously in the signal handling thread of a process to request that the process terminates.
+pub const EXCEPT_SIGNAL_TERMINATE: Uuid = parse_uuid("71cd9a90-dcb1-36e3-a420-709fc6bf7ab5");
+/// Raised asynchronously in the signal handling thread of a process when its controlling terminal is closed.
+pub const EXCEPT_SIGNAL_HANGUP: Uuid = parse_uuid("519a6f3f-324f-33a7-86d6-6023b4441333");
+/// Raised asynchronously in the signal handling thread of a process when a quit is requested, such as by `Ctrl+\` on its terminal.
+pub const EXCEPT_SIGNAL_QUIT: Uuid = parse_uuid("cfe79598-d91e-3e6c-99d0-d415fde19cc9");
+/// Raised asynchronously in the signal handling thread of a process for a purpose defined by the program.
+pub const EXCEPT_SIGNAL_USER1: Uuid = parse_uuid("e7cffb5b-7d43-3391-b8de-d936af9ac1a9");
+/// Raised asynchronously in the signal handling thread of a process for a purpose defined by the program.
+pub const EXCEPT_SIGNAL_USER2: Uuid = parse_uuid("9b6c5e8c-5b0b-3a41-8db4-cce448f81cf0");
+/// Raised asynchronously in the signal handling thread of a process when one of its child processes exits.
+pub const EXCEPT_SIGNAL_CHILD: Uuid = parse_uuid("54d980dd-c86b-3397-bee6-8547966705a2");
+/// Raised asynchronously in the signal handling thread of a process when the size of its controlling terminal changes.
+pub const EXCEPT_SIGNAL_WINDOW_CHANGE: Uuid = parse_uuid("95338a5e-19f3-36d4-a392-77805fc2b443");
+/// Raised asynchronously in the signal handling thread of a process when it writes to a pipe or socket whose other end is closed.
+pub const EXCEPT_SIGNAL_BROKEN_PIPE: Uuid = parse_uuid("cb70aee9-dc08-30e8-9ccf-74ce6c546326");
+/// Raised asynchronously in the signal handling thread of a process when an alarm set by the process expires.
+pub const EXCEPT_SIGNAL_ALARM: Uuid = parse_uuid("61bfde47-8e16-3397-a5e5-dd37698fa728");
+
 #[repr(C)]
 #[derive(Copy, Clone, PartialEq, Eq, Debug)]
 pub struct ExceptionStatusInfo {
@@ -36,32 +76,33 @@ pub struct ExceptionInfo {
     pub trigger_code_stack_head: *mut c_void,
 }
 
-/// An option for opening the file
-#[repr(C, align(32))]
-#[derive(Copy, Clone)]
-pub struct UnknownExceptHandlerOption {
-    /// The header
-    pub head: ExtendedOptionHead,
-    /// The tail
-    pub tail: [MaybeUninit<u8>; 64],
+def_extended_option! {
+    /// An option for opening the file
+    pub struct UnknownExceptHandlerOption {
+        /// The header
+        pub head: ExtendedOptionHead,
+        /// The tail
+        pub tail: [MaybeUninit<u8>; 64],
+    }
 }
 
-#[repr(C, align(32))]
-pub union ExceptHandlerOption {
-    /// The Header: Must be present on all subfields
-    pub head: ExtendedOptionHead,
-    /// Fallback type for all fields
-    pub unknown: UnknownExceptHandlerOption,
+def_extended_option! {
+    pub union ExceptHandlerOption {
+        /// The Header: Must be present on all subfields
+        pub head: ExtendedOptionHead,
+        /// Fallback type for all fields
+        pub unknown: UnknownExceptHandlerOption,
+    }
 }
 
-/// An option for opening the file
-#[repr(C, align(32))]
-#[derive(Copy, Clone)]
-pub struct ExceptHandlerOptionSetStack {
-    /// The header
-    pub head: ExtendedOptionHead,
-    /// The stack pointer to set when execution the exception handler
-    pub stack_base_addr: *mut c_void,
+def_extended_option! {
+    /// An option for opening the file
+    pub struct ExceptHandlerOptionSetStack {
+        /// The header
+        pub head: ExtendedOptionHead,
+        /// The stack pointer to set when execution the exception handler
+        pub stack_base_addr: *mut c_void,
+    }
 }
 
 pub type ExceptHandler =
@@ -45,6 +45,8 @@ use core::{
 
 use crate::uuid::Uuid;
 
+use crate::def_extended_option;
+
 use super::{
     handle::{Handle, HandlePtr},
     io::IOHandle,
@@ -100,6 +102,8 @@ pub const ACCESS_LINK_STREAM_DIRECT: u32 = 0x200;
 pub const ACCESS_TRUNCATE: u32 = 0x400;
 /// Seek to the last byte of the file after opening. Has no effect if `ACCESS_TRUNCATE` is specified together with `ACCESS_WRITE`.
 pub const ACCESS_START_END: u32 = 0x800;
+/// When establishing a lock with `ACCESS_LOCK_SOFT` or `ACCESS_LOCK_HARD`, returns `WOULD_BLOCK` instead of blocking if the lock is held by another handle, regardless of the blocking mode.
+pub const ACCESS_LOCK_TRY: u32 = 0x1000;
 
 /// Performs the default operation on the stream being opened
 pub const OP_STREAM_DEFAULT: u32 = 0x00;
@@ -129,22 +133,23 @@ pub const OP_NO_ACCESS: u32 = 0x04;
 
 pub use super::io::{MODE_ASYNC, MODE_BLOCKING, MODE_NONBLOCKING};
 
-/// An option for opening the file
-#[repr(C, align(32))]
-#[derive(Copy, Clone)]
-pub struct UnknownFileOpenOption {
-    /// The header
-    pub head: ExtendedOptionHead,
-    /// The tail
-    pub tail: [MaybeUninit<u8>; 64],
+def_extended_option! {
+    /// An option for opening the file
+    pub struct UnknownFileOpenOption {
+        /// The header
+        pub head: ExtendedOptionHead,
+        /// The tail
+        pub tail: [MaybeUninit<u8>; 64],
+    }
 }
 
-#[repr(C, align(32))]
-pub union FileOpenOption {
-    /// The Header: Must be present on all subfields
-    pub head: ExtendedOptionHead,
-    /// Fallback type for all fields
-    pub unknown: UnknownFileOpenOption,
+def_extended_option! {
+    pub union FileOpenOption {
+        /// The Header: Must be present on all subfields
+        pub head: ExtendedOptionHead,
+        /// Fallback type for all fields
+        pub unknown: UnknownFileOpenOption,
+    }
 }
 
 #[repr(C)]
@@ -194,11 +199,80 @@ pub struct DaclRow {
     pub mode: u32,
 }
 
+/// Only synchronizes the content of the stream, and the metadata needed to read it back, with [`SyncStream`]
+pub const SYNC_DATA_ONLY: u32 = 0x01;
+
 pub const ACL_MODE_ALLOW: u32 = 0;
 pub const ACL_MODE_DENY: u32 = 1;
 pub const ACL_MODE_FORBID: u32 = 2;
 pub const ACL_MODE_INHERIT: u32 = 3;
 
+/// The filesystem supports opening explicit streams of an object, other than the standard streams
+pub const FS_FEATURE_STREAMS: u64 = 0x01;
+/// The filesystem stores access control lists for objects
+pub const FS_FEATURE_ACLS: u64 = 0x02;
+/// The filesystem supports creating anonymous objects, such as by [`CreatePrivateDirectory`]
+pub const FS_FEATURE_ANONYMOUS_OBJECTS: u64 = 0x04;
+
+/// Information about a mounted filesystem, filled by [`GetFilesystemInfo`]
+#[repr(C)]
+pub struct FilesystemInfo {
+    /// The id of the filesystem type (such as LiliumFS or FAT32)
+    pub fs_type: Uuid,
+    /// The id of the device the filesystem is mounted from, or the nil UUID for a virtual filesystem
+    pub device_id: Uuid,
+    /// The size in bytes of the blocks the filesystem allocates storage in
+    pub block_size: u64,
+    /// The total number of blocks in the filesystem
+    pub total_blocks: u64,
+    /// The number of blocks that are not allocated
+    pub free_blocks: u64,
+    /// The number of unallocated blocks that can be allocated by the current thread, which may be less than `free_blocks` if blocks are reserved
+    pub available_blocks: u64,
+    /// The features supported by the filesystem. Contains zero or more of the `FS_FEATURE_*` flags
+    pub features: u64,
+}
+
+/// The identity of a filesystem object, filled by [`GetObjectId`]
+#[repr(C)]
+#[derive(Copy, Clone, Hash, PartialEq, Eq, Debug)]
+pub struct ObjectId {
+    /// The id of the mounted filesystem the object resides on. This is unique among all filesystems mounted at the same time
+    pub filesystem: Uuid,
+    /// The id of the object within the filesystem, which is stable while the filesystem is mounted, and is not reused while the object exists
+    pub object: u128,
+}
+
+/// A handle to a filesystem watch, which queues an event when an object it watches changes.
+///
+/// Watch handles are created by [`CreateWatch`], and objects are added to them by [`AddWatch`].
+#[repr(transparent)]
+pub struct WatchHandle(Handle);
+
+/// An object was created in the watched directory
+pub const WATCH_CREATED: u32 = 0x01;
+/// The watched object, or an object in the watched directory, was written to, or had its metadata changed
+pub const WATCH_MODIFIED: u32 = 0x02;
+/// The watched object, or an object in the watched directory, was removed
+pub const WATCH_REMOVED: u32 = 0x04;
+/// The watched object, or an object in the watched directory, was renamed
+pub const WATCH_RENAMED: u32 = 0x08;
+/// Every kind of watch event
+pub const WATCH_ALL: u32 = WATCH_CREATED | WATCH_MODIFIED | WATCH_REMOVED | WATCH_RENAMED;
+
+/// An event read from a [`WatchHandle`] by [`ReadWatchEvent`]
+#[repr(C)]
+pub struct WatchEvent {
+    /// The id returned by [`AddWatch`] for the watched object the event occurred on
+    pub watch_id: u32,
+    /// One of the `WATCH_*` constants, other than [`WATCH_ALL`]
+    pub kind: u32,
+    /// The name of the object the event occurred on, relative to the watched directory, or empty if the event occurred on the watched object itself
+    pub name: KStrPtr,
+    /// For [`WATCH_RENAMED`], the new name of the object, relative to the watched directory. Otherwise, empty.
+    pub new_name: KStrPtr,
+}
+
 #[allow(improper_ctypes)]
 extern "C" {
     /// Opens a new file handle with the given path
@@ -246,6 +320,66 @@ extern "C" {
     pub fn StreamSize(hdl: HandlePtr<FileHandle>) -> SysResult;
     pub fn ObjectSize(hdl: HandlePtr<FileHandle>, size_out: *mut u128) -> SysResult;
 
+    /// Sets the size of the stream opened by `hdl` to `len` bytes, discarding the content past `len`, or extending the stream with zero bytes.
+    ///
+    /// ## Errors
+    ///
+    /// Returns `PERMISSION` if `hdl` was not opened with `ACCESS_WRITE`.
+    ///
+    /// Returns `DEVICE_FULL` if the stream is extended, and the filesystem has no space for it.
+    ///
+    /// Returns `UNSUPPORTED_OPERATION` if the size of the stream cannot be changed.
+    pub fn TruncateStream(hdl: HandlePtr<FileHandle>, len: u128) -> SysResult;
+
+    /// Allocates storage for `len` bytes of the stream opened by `hdl` starting at `offset`, so that writes to the range do not fail with `DEVICE_FULL`.
+    ///
+    /// If the range extends past the end of the stream, the stream is extended to the end of the range with zero bytes. The content within the range is not modified.
+    ///
+    /// ## Errors
+    ///
+    /// Returns `PERMISSION` if `hdl` was not opened with `ACCESS_WRITE`.
+    ///
+    /// Returns `DEVICE_FULL` if the filesystem has no space for the range.
+    ///
+    /// Returns `UNSUPPORTED_OPERATION` if the filesystem does not support allocating storage in advance.
+    pub fn AllocateStream(hdl: HandlePtr<FileHandle>, offset: u128, len: u128) -> SysResult;
+
+    /// Blocks until all writes to the stream opened by `hdl`, and its metadata, reach the underlying device.
+    ///
+    /// If `flags` contains [`SYNC_DATA_ONLY`], metadata is only written if it is needed to read the content back (such as the size of the stream), and not, for example, modification times.
+    ///
+    /// ## Errors
+    ///
+    /// Returns `INVALID_OPTION` if `flags` contains an unknown flag.
+    ///
+    /// Returns `DEVICE_UNAVAILABLE` if the device could not be written to.
+    pub fn SyncStream(hdl: HandlePtr<FileHandle>, flags: u32) -> SysResult;
+
+    /// Queries information about the filesystem that the object opened by `hdl` resides on, and stores it in `info`.
+    ///
+    /// `hdl` may be opened in any operation mode, including `OP_NO_ACCESS`.
+    ///
+    /// ## Errors
+    ///
+    /// Returns `INVALID_HANDLE` if `hdl` is not a valid `FileHandle`.
+    ///
+    /// Returns `INVALID_MEMORY` if `info` is not a valid pointer.
+    ///
+    /// Returns `DEVICE_UNAVAILABLE` if the device the filesystem is mounted from could not be read.
+    pub fn GetFilesystemInfo(hdl: HandlePtr<FileHandle>, info: *mut FilesystemInfo) -> SysResult;
+
+    /// Stores the identity of the object opened by `hdl` in `id`.
+    ///
+    /// Two handles that refer to the same object, including through different hard links or streams, have the same identity.
+    /// `hdl` may be opened in any operation mode, including `OP_NO_ACCESS`.
+    ///
+    /// ## Errors
+    ///
+    /// Returns `INVALID_HANDLE` if `hdl` is not a valid `FileHandle`.
+    ///
+    /// Returns `INVALID_MEMORY` if `id` is not a valid pointer.
+    pub fn GetObjectId(hdl: HandlePtr<FileHandle>, id: *mut ObjectId) -> SysResult;
+
     pub fn CreateAcl(hdl: *mut HandlePtr<FileHandle>) -> SysResult;
     pub fn DefaultAcl(hdl: *mut HandlePtr<FileHandle>) -> SysResult;
     pub fn ObjectOwner(hdl: HandlePtr<FileHandle>, uuid: *mut Uuid) -> SysResult;
@@ -267,6 +401,31 @@ extern "C" {
     pub fn AclSetLegacyMode(hdl: HandlePtr<FileHandle>, mode: u32) -> SysResult;
     pub fn AclSetLegacyUid(hdl: HandlePtr<FileHandle>, uid: c_long) -> SysResult;
     pub fn AclSetLegacyGid(hdl: HandlePtr<FileHandle>, gid: c_long) -> SysResult;
+
+    /// Maps the legacy `uid` to a principal, using the principal map of the filesystem `hdl` resides on, or the default principal map if `hdl` is null or the filesystem has no principal map.
+    ///
+    /// Returns `DOES_NOT_EXIST` if `uid` is not present in the principal map.
+    pub fn LegacyUidToPrincipal(
+        hdl: HandlePtr<FileHandle>,
+        uid: c_long,
+        principal: *mut Uuid,
+    ) -> SysResult;
+    /// Maps the legacy `gid` to a principal, using the principal map of the filesystem `hdl` resides on, or the default principal map if `hdl` is null or the filesystem has no principal map.
+    ///
+    /// Returns `DOES_NOT_EXIST` if `gid` is not present in the principal map.
+    pub fn LegacyGidToPrincipal(
+        hdl: HandlePtr<FileHandle>,
+        gid: c_long,
+        principal: *mut Uuid,
+    ) -> SysResult;
+    /// Maps `principal` to a legacy uid, using the principal map of the filesystem `hdl` resides on, or the default principal map if `hdl` is null or the filesystem has no principal map.
+    ///
+    /// Returns the uid, or `DOES_NOT_EXIST` if no uid maps to `principal`.
+    pub fn PrincipalToLegacyUid(hdl: HandlePtr<FileHandle>, principal: *const Uuid) -> SysResult;
+    /// Maps `principal` to a legacy gid, using the principal map of the filesystem `hdl` resides on, or the default principal map if `hdl` is null or the filesystem has no principal map.
+    ///
+    /// Returns the gid, or `DOES_NOT_EXIST` if no gid maps to `principal`.
+    pub fn PrincipalToLegacyGid(hdl: HandlePtr<FileHandle>, principal: *const Uuid) -> SysResult;
     pub fn OverwriteAcl(
         file_hdl: HandlePtr<FileHandle>,
         acl_hdl: HandlePtr<FileHandle>,
@@ -450,6 +609,21 @@ extern "C" {
         stream_name: KStrCPtr,
         flags: u64,
     ) -> SysResult;
+    /// Opens the stream named `stream_name` of the object designated by `file`, as though by [`OpenFile`] with `stream_override` set to `stream_name`.
+    ///
+    /// The `stream_override` field of `opts` is ignored. `ACCESS_CREATE` creates the stream, but never the object.
+    ///
+    /// ## Errors
+    ///
+    /// Returns `DOES_NOT_EXIST` if the object has no stream named `stream_name`, and `ACCESS_CREATE` is not set.
+    ///
+    /// Returns `PERMISSION` if the access mode is denied to the stream.
+    pub fn OpenStream(
+        stream_hdl: *mut HandlePtr<FileHandle>,
+        file: HandlePtr<FileHandle>,
+        stream_name: KStrCPtr,
+        opts: *const FileOpenOptions,
+    ) -> SysResult;
     pub fn RemoveStream(file: HandlePtr<FileHandle>, stream_name: KStrCPtr) -> SysResult;
 
     pub fn IsFileHandle(iohdl: HandlePtr<IOHandle>) -> SysResult;
@@ -469,6 +643,8 @@ extern "C" {
     /// Setting `new_op` to `0` does not alter the operation mode.
     ///
     /// Changing the lock mode of the handle is not possible via `ChangeFileAccessMode`, except that a new lock may be established
+    ///
+    /// If a new lock is established, it is held by `newhdl`, and released when `newhdl` is closed.
     pub fn ChangeFileAccessMode(
         newhdl: *mut HandlePtr<FileHandle>,
         oldhdl: HandlePtr<FileHandle>,
@@ -476,7 +652,81 @@ extern "C" {
         new_op: u32,
     ) -> SysResult;
 
+    /// Resolves `path` against `resolution_base` as described in [Path Resolution](self#path-resolution), and stores the resulting absolute physical path in `path_out`.
+    ///
+    /// The path contains no `.` or `..` components and no symbolic links. If the object is reached through a prefix designator, the path starts with that prefix designator.
+    ///
+    /// ## Errors
+    ///
+    /// Returns `DOES_NOT_EXIST` if any component of `path` does not exist.
+    ///
+    /// Returns `LINK_RESOLUTION_LOOP` if resolving symbolic links does not terminate.
+    ///
+    /// Returns `INSUFFICIENT_LENGTH` if `path_out` is too short to store the path, and sets `path_out.len` to the required length.
+    pub fn ResolvePath(
+        resolution_base: HandlePtr<FileHandle>,
+        path: KStrCPtr,
+        path_out: *mut KStrPtr,
+    ) -> SysResult;
+
+    /// Sets the current directory of the current thread, which relative paths are resolved against when the resolution base is null, to `dir`.
+    ///
+    /// `dir` must be opened with `OP_DIRECTORY_ACCESS`. The kernel keeps its own reference to the directory, so `dir` may be closed afterwards.
     pub fn SetCurrentDirectory(dir: HandlePtr<FileHandle>) -> SysResult;
 
+    /// Opens a new handle, in `OP_DIRECTORY_ACCESS` mode, to the current directory of the current thread
+    pub fn GetCurrentDirectory(dir: *mut HandlePtr<FileHandle>) -> SysResult;
+
+    /// Sets the resolution root of the current thread, which absolute paths are resolved against, to `res_base`.
+    ///
+    /// `res_base` must be opened with `OP_DIRECTORY_ACCESS`. The kernel keeps its own reference to the directory, so `res_base` may be closed afterwards.
     pub fn SetResolutionRoot(res_base: HandlePtr<FileHandle>) -> SysResult;
+
+    /// Opens a new handle, in `OP_DIRECTORY_ACCESS` mode, to the resolution root of the current thread
+    pub fn GetResolutionRoot(res_base: *mut HandlePtr<FileHandle>) -> SysResult;
+
+    /// Creates a new watch handle that does not watch any object
+    pub fn CreateWatch(hdl: *mut HandlePtr<WatchHandle>) -> SysResult;
+
+    /// Watches the object designated by `file` for the events in `mask` (a combination of the `WATCH_*` constants), and returns the id that identifies its events.
+    ///
+    /// If `file` is a directory, events on the objects directly contained in it are also reported.
+    ///
+    /// ## Errors
+    ///
+    /// Returns `INVALID_HANDLE` if either handle is not valid.
+    ///
+    /// Returns `PERMISSION` if `file` was not opened with `ACCESS_READ`.
+    ///
+    /// Returns `INVALID_OPTION` if `mask` is empty, or contains a bit that is not a `WATCH_*` constant.
+    ///
+    /// Returns `UNSUPPORTED_OPERATION` if the filesystem of `file` does not report changes.
+    ///
+    /// Returns `RESOURCE_LIMIT_EXHAUSTED` if the kernel resource limit on the number of watched objects is exceeded.
+    pub fn AddWatch(
+        hdl: HandlePtr<WatchHandle>,
+        file: HandlePtr<FileHandle>,
+        mask: u32,
+    ) -> SysResult;
+
+    /// Stops watching the object identified by `watch_id`. Events for the object that are already queued can still be read.
+    ///
+    /// Returns `DOES_NOT_EXIST` if `watch_id` does not identify an object watched by `hdl`.
+    pub fn RemoveWatch(hdl: HandlePtr<WatchHandle>, watch_id: u32) -> SysResult;
+
+    /// Removes the oldest queued event from `hdl`, and stores it in `event`.
+    ///
+    /// The caller sets `event.name` and `event.new_name` to buffers to store the names of the event.
+    ///
+    /// ## Errors
+    ///
+    /// Returns `WOULD_BLOCK` if no event is queued. Use [`EVENT_FS_WATCH`][super::event::EVENT_FS_WATCH] to block until one is.
+    ///
+    /// Returns `INSUFFICIENT_LENGTH` if either buffer is too short to store its name, and sets the `len` of both buffers to the required lengths. The event is not removed from the queue.
+    ///
+    /// Returns `INVALID_MEMORY` if `event` or either buffer does not point to writable memory.
+    pub fn ReadWatchEvent(hdl: HandlePtr<WatchHandle>, event: *mut WatchEvent) -> SysResult;
+
+    /// Closes the watch handle, discarding any queued events
+    pub fn CloseWatch(hdl: HandlePtr<WatchHandle>) -> SysResult;
 }
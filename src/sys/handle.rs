@@ -54,6 +54,9 @@ impl<T> Clone for HandlePtr<T> {
 
 impl<T> Copy for HandlePtr<T> {}
 
+// SAFETY: The all-zero `HandlePtr` is the null handle
+unsafe impl<T> bytemuck::Zeroable for HandlePtr<T> {}
+
 impl<T> core::hash::Hash for HandlePtr<T> {
     fn hash<H: core::hash::Hasher>(&self, hasher: &mut H) {
         self.0.hash(hasher);
@@ -152,11 +155,17 @@ extern "C" {
         hdl: HandlePtr<Handle>,
         flags: u32,
     ) -> SysResult;
+    /// Revokes the shared handle created from `hdl`. Threads that have not yet upgraded the shared handle can no longer do so.
+    ///
+    /// Handles already obtained from the shared handle by other threads are not affected.
     pub fn UnshareHandle(hdl: HandlePtr<Handle>) -> SysResult;
     pub fn UpgradeSharedHandle(
         hdlout: *mut HandlePtr<Handle>,
         shared_handle: SharedHandlePtr,
     ) -> SysResult;
+    /// Identifies the dynamic type of `hdl`.
+    ///
+    /// Returns one of the `HANDLE_TYPE_*` constants, or a `HANDLE_SUBTYPE_*` constant if the handle has a more specific subtype.
     pub fn IdentHandle(hdl: HandlePtr<Handle>) -> SysResult;
 
     /// Validates that the handle right `right` is is present on `hdl`.
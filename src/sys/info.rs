@@ -6,6 +6,7 @@ use super::{
 
 use core::mem::MaybeUninit;
 
+use crate::def_extended_option;
 use crate::uuid::{parse_uuid, Uuid};
 
 /// The Number of bytes for the body of a [`SysInfoRequest`] - large enough to store the larger of 8 pointers and 64 bytes.
@@ -15,75 +16,75 @@ pub const SYS_INFO_REQUEST_BODY_SIZE: usize = if core::mem::size_of::<usize>() >
     64
 };
 
-/// Fallback type to represent unknown requests
-#[repr(C, align(32))]
-#[derive(Copy, Clone)]
-pub struct SysInfoRequestUnknown {
-    /// The Header of the request
-    pub head: ExtendedOptionHead,
-    /// The body of the request, content depends on the type.
-    pub body: [MaybeUninit<u8>; SYS_INFO_REQUEST_BODY_SIZE],
+def_extended_option! {
+    /// Fallback type to represent unknown requests
+    pub struct SysInfoRequestUnknown {
+        /// The Header of the request
+        pub head: ExtendedOptionHead,
+        /// The body of the request, content depends on the type.
+        pub body: [MaybeUninit<u8>; SYS_INFO_REQUEST_BODY_SIZE],
+    }
 }
 
-/// Requests OS Version Information
-#[repr(C, align(32))]
-#[derive(Copy, Clone)]
-pub struct SysInfoRequestOsVersion {
-    /// The Header of the request
-    pub head: ExtendedOptionHead,
-    /// The name of the OS vendor
-    pub osvendor_name: KStrPtr,
-    /// The major OS version.
-    pub os_major: u32,
-    /// The minor os version
-    pub os_minor: u32,
+def_extended_option! {
+    /// Requests OS Version Information
+    pub struct SysInfoRequestOsVersion {
+        /// The Header of the request
+        pub head: ExtendedOptionHead(SYSINFO_REQUEST_OSVER),
+        /// The name of the OS vendor
+        pub osvendor_name: KStrPtr = KStrPtr::empty(),
+        /// The major OS version.
+        pub os_major: u32 = 0,
+        /// The minor os version
+        pub os_minor: u32 = 0,
+    }
 }
 
-/// Requests Kernel Vendor Name
-#[repr(C, align(32))]
-#[derive(Copy, Clone)]
-pub struct SysInfoRequestKernelVendor {
-    /// The Header of the request
-    pub head: ExtendedOptionHead,
-    /// The name of the kernel vendor
-    pub kvendor_name: KStrPtr,
-    /// The kernel build id
-    pub build_id: Uuid,
-    /// The major kernel version
-    pub kernel_major: u32,
-    /// The minor kernel version
-    pub kernel_minor: u32,
+def_extended_option! {
+    /// Requests Kernel Vendor Name
+    pub struct SysInfoRequestKernelVendor {
+        /// The Header of the request
+        pub head: ExtendedOptionHead(SYSINFO_REQUEST_KVENDOR),
+        /// The name of the kernel vendor
+        pub kvendor_name: KStrPtr = KStrPtr::empty(),
+        /// The kernel build id
+        pub build_id: Uuid = Uuid::NIL,
+        /// The major kernel version
+        pub kernel_major: u32 = 0,
+        /// The minor kernel version
+        pub kernel_minor: u32 = 0,
+    }
 }
 
-/// Requests Global Architecture Info
-#[repr(C, align(32))]
-#[derive(Copy, Clone)]
-pub struct SysInfoRequestArchInfo {
-    /// The Header of the request
-    pub head: ExtendedOptionHead,
-    /// The type of the architecture
-    pub arch_type: Uuid,
-    /// The architecture version.
-    ///
-    /// This is a generic version, intended to differentiate between similar targets sharing the same arch (IE. i386 vs. i686, or ABI microarchitecture versions of x86_64).
-    /// This may be different from vendor-specific processor versions.
-    pub arch_version: u32,
+def_extended_option! {
+    /// Requests Global Architecture Info
+    pub struct SysInfoRequestArchInfo {
+        /// The Header of the request
+        pub head: ExtendedOptionHead(SYSINFO_REQUEST_ARCH_INFO),
+        /// The type of the architecture
+        pub arch_type: Uuid = Uuid::NIL,
+        /// The architecture version.
+        ///
+        /// This is a generic version, intended to differentiate between similar targets sharing the same arch (IE. i386 vs. i686, or ABI microarchitecture versions of x86_64).
+        /// This may be different from vendor-specific processor versions.
+        pub arch_version: u32 = 0,
+    }
 }
 
-/// Requests computer name information
-#[repr(C, align(32))]
-#[derive(Copy, Clone)]
-pub struct SysInfoRequestComputerName {
-    /// The header of the request
-    pub head: ExtendedOptionHead,
-    /// The system host name
-    pub hostname: KStrPtr,
-    /// The system unique identifier
-    pub sys_id: Uuid,
-    /// The System Display Name
-    pub sys_display_name: KStrPtr,
-    /// The System Label
-    pub sys_label: KStrPtr,
+def_extended_option! {
+    /// Requests computer name information
+    pub struct SysInfoRequestComputerName {
+        /// The header of the request
+        pub head: ExtendedOptionHead(SYSINFO_REQUEST_COMPUTER_NAME),
+        /// The system host name
+        pub hostname: KStrPtr = KStrPtr::empty(),
+        /// The system unique identifier
+        pub sys_id: Uuid = Uuid::NIL,
+        /// The System Display Name
+        pub sys_display_name: KStrPtr = KStrPtr::empty(),
+        /// The System Label
+        pub sys_label: KStrPtr = KStrPtr::empty(),
+    }
 }
 
 pub mod arch_info {
@@ -102,73 +103,101 @@ pub mod arch_info {
     pub const ARCH_TYPE_RISCV64: Uuid = parse_uuid("d6129403-1104-5d03-8b4c-1176fc9f17fd");
 }
 
-#[repr(C, align(32))]
-#[derive(Copy, Clone)]
-pub struct SysInfoRequestPhysicalInfo {
-    /// The header of the request
-    pub head: ExtendedOptionHead,
-    /// The number of physical cores accross all active processors
-    pub physical_core_count: u32,
-    /// The number of logical cores (threads)
-    /// May be different if the processor supports hyperthreading or shared-state parallelism
-    pub logical_core_count: u32,
-    /// The number of physically installed discrete Processors
-    pub discrete_processor_count: u32,
+def_extended_option! {
+    pub struct SysInfoRequestPhysicalInfo {
+        /// The header of the request
+        pub head: ExtendedOptionHead(SYSINFO_REQUEST_PHYSICAL_INFO),
+        /// The number of physical cores accross all active processors
+        pub physical_core_count: u32 = 0,
+        /// The number of logical cores (threads)
+        /// May be different if the processor supports hyperthreading or shared-state parallelism
+        pub logical_core_count: u32 = 0,
+        /// The number of physically installed discrete Processors
+        pub discrete_processor_count: u32 = 0,
+    }
 }
 
-#[repr(C, align(32))]
-#[derive(Copy, Clone)]
-pub struct SysInfoRequestAddressSpace {
-    /// The header of the request
-    pub head: ExtendedOptionHead,
-    /// The Minimum Virtual address that a userspace program can allocate,
-    pub min_mapping_addr: usize,
-    /// The Maximum Virtual Address that a userspace program can allocate
-    pub max_mapping_addr: usize,
-    /// The Page Granularity
-    pub page_size: usize,
+def_extended_option! {
+    pub struct SysInfoRequestAddressSpace {
+        /// The header of the request
+        pub head: ExtendedOptionHead(SYSINFO_REQUEST_ADDRESS_SPACE),
+        /// The Minimum Virtual address that a userspace program can allocate,
+        pub min_mapping_addr: usize = 0,
+        /// The Maximum Virtual Address that a userspace program can allocate
+        pub max_mapping_addr: usize = 0,
+        /// The Page Granularity
+        pub page_size: usize = 0,
+    }
 }
 
-/// Option struct for obtaining information about the kernel
-///
-/// Additional extended option flags:
-/// * Bit 16: `SYSINFO_REQUEST_FLAG_SKIP` - used by USI impls to indicate that the kernel should treat the request as unrecognized. Must be set together with [`OPTION_FLAG_IGNORE`][super::option::OPTION_FLAG_IGNORE].
-///   This bit should not be set by users, and does not have an associated constant. USI impls are not required to request this flag for requests it fulfills, and may clear it when set by the user.
-#[repr(C, align(32))]
-#[derive(Copy, Clone)]
-pub union SysInfoRequest {
-    pub head: ExtendedOptionHead,
-    pub os_version: SysInfoRequestOsVersion,
-    pub kernel_vendor: SysInfoRequestKernelVendor,
-    pub arch_info: SysInfoRequestArchInfo,
-    pub computer_name: SysInfoRequestComputerName,
-    pub processor_info: SysInfoRequestPhysicalInfo,
-    pub addr_space: SysInfoRequestAddressSpace,
-    /// Allows querying information about processors common to all CPUs.
-    pub common_processor_info: ProcInfoRequest,
-    pub unknown: SysInfoRequestUnknown,
+def_extended_option! {
+    /// Requests information about a kernel subsystem.
+    ///
+    /// `subsystem_no` is an input field. If the subsystem is not supported by the kernel, the request is treated as unrecognized.
+    pub struct SysInfoRequestSupportedSubsystem {
+        /// The header of the request
+        pub head: ExtendedOptionHead(SYSINFO_REQUEST_SUPPORTED_SUBSYSTEM),
+        /// The number of the subsystem to query, which is the high bits of each system call number in the subsystem
+        pub subsystem_no: u16 = 0,
+        /// The highest system call number in the subsystem that the kernel supports
+        pub max_sysno: u16 = 0,
+        /// The version of the subsystem implemented by the kernel
+        pub subsystem_version: u32 = 0,
+        /// The name of the subsystem
+        pub subsystem_name: KStrPtr = KStrPtr::empty(),
+    }
+}
+
+def_extended_option! {
+    /// Option struct for obtaining information about the kernel
+    ///
+    /// Additional extended option flags:
+    /// * Bit 16: `SYSINFO_REQUEST_FLAG_SKIP` - used by USI impls to indicate that the kernel should treat the request as unrecognized. Must be set together with [`OPTION_FLAG_IGNORE`][super::option::OPTION_FLAG_IGNORE].
+    ///   This bit should not be set by users, and does not have an associated constant. USI impls are not required to request this flag for requests it fulfills, and may clear it when set by the user.
+    pub union SysInfoRequest {
+        pub head: ExtendedOptionHead,
+        pub os_version: SysInfoRequestOsVersion,
+        pub kernel_vendor: SysInfoRequestKernelVendor,
+        pub arch_info: SysInfoRequestArchInfo,
+        pub computer_name: SysInfoRequestComputerName,
+        pub processor_info: SysInfoRequestPhysicalInfo,
+        pub addr_space: SysInfoRequestAddressSpace,
+        pub supported_subsystem: SysInfoRequestSupportedSubsystem,
+        /// Allows querying information about processors common to all CPUs.
+        pub common_processor_info: ProcInfoRequest,
+        pub unknown: SysInfoRequestUnknown,
+    }
 }
 
 pub const SYSINFO_REQUEST_OSVER: Uuid = parse_uuid("22c479ab-c119-58d5-9c1e-fa03ddf9426a");
 pub const SYSINFO_REQUEST_KVENDOR: Uuid = parse_uuid("01adbfd8-3b43-5115-9abd-5b2974375358");
 pub const SYSINFO_REQUEST_ARCH_INFO: Uuid = parse_uuid("416eed18-85ca-53c9-849f-4b54bb0568b7");
 pub const SYSINFO_REQUEST_COMPUTER_NAME: Uuid = parse_uuid("82b314fe-0476-51ca-99de-bbd9711403cf");
-
-/// Fallback type to represent unknown requests
-#[repr(C, align(32))]
-#[derive(Copy, Clone)]
-pub struct ProcInfoRequestUnknown {
-    /// The Header of the request
-    pub head: ExtendedOptionHead,
-    /// The body of the request, content depends on the type.
-    pub body: [MaybeUninit<u8>; SYS_INFO_REQUEST_BODY_SIZE],
+pub const SYSINFO_REQUEST_PHYSICAL_INFO: Uuid = parse_uuid("92a08438-d86a-51df-a9de-84102471026b");
+pub const SYSINFO_REQUEST_ADDRESS_SPACE: Uuid = parse_uuid("4eb55c85-ac79-51bb-b0d5-f61683ba6d1e");
+pub const SYSINFO_REQUEST_SUPPORTED_SUBSYSTEM: Uuid =
+    parse_uuid("3d1d0352-de2d-53d5-951c-afdb749d6969");
+
+def_extended_option! {
+    /// Fallback type to represent unknown requests
+    pub struct ProcInfoRequestUnknown {
+        /// The Header of the request
+        pub head: ExtendedOptionHead,
+        /// The body of the request, content depends on the type.
+        pub body: [MaybeUninit<u8>; SYS_INFO_REQUEST_BODY_SIZE],
+    }
 }
 
 #[cfg(any(target_arch = "x86_64", target_arch = "x86"))]
 mod x86;
 
 #[cfg(any(target_arch = "x86_64", target_arch = "x86"))]
-pub use x86::ProcInfoArchRequest;
+pub use x86::{
+    ProcInfoArchRequest, ProcInfoX86Avx10Features, ProcInfoX86CpuidFeatures,
+    ProcInfoX86ProcessorBrand, ProcInfoX86ProcessorVersion, ProcInfoX86XsaveFeatures,
+    PROCINFO_REQUEST_X86_AVX10_FEATURES, PROCINFO_REQUEST_X86_CPUID_FEATURES,
+    PROCINFO_REQUEST_X86_XSAVE_FEATURES,
+};
 
 #[cfg(any(target_arch = "clever"))]
 mod clever;
@@ -177,18 +206,18 @@ mod clever;
 pub use clever::ProcInfoArchRequest;
 
 #[cfg(not(any(target_arch = "x86_64", target_arch = "x86", target_arch = "clever")))]
-#[repr(C, align(32))]
-#[derive(Copy, Clone)]
-pub union ProcInfoArchRequest {
-    unknown: ProcInfoRequestUnknown,
+def_extended_option! {
+    pub union ProcInfoArchRequest {
+        unknown: ProcInfoRequestUnknown,
+    }
 }
 
-#[repr(C, align(32))]
-#[derive(Copy, Clone)]
-pub union ProcInfoRequest {
-    pub head: ExtendedOptionHead,
-    pub unknown: ProcInfoRequestUnknown,
-    pub arch: ProcInfoArchRequest,
+def_extended_option! {
+    pub union ProcInfoRequest {
+        pub head: ExtendedOptionHead,
+        pub unknown: ProcInfoRequestUnknown,
+        pub arch: ProcInfoArchRequest,
+    }
 }
 
 extern "system" {
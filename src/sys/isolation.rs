@@ -39,6 +39,8 @@ pub const ISOLATE_PROCESSES_EXPOSE_SELF: u32 = 0x1;
 extern "C" {
     pub fn CreateNamespace(handle: *mut HandlePtr<NamespaceHandle>) -> SysResult;
     pub fn DisposeNamespace(handle: HandlePtr<NamespaceHandle>) -> SysResult;
+    /// Obtains a handle to the namespace the current thread is running in
+    pub fn GetCurrentNamespace(handle: *mut HandlePtr<NamespaceHandle>) -> SysResult;
 
     pub fn IsolateDevices(
         ns: HandlePtr<NamespaceHandle>,
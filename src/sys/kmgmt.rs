@@ -0,0 +1,126 @@
+//! Kernel management interfaces, which load and unload kernel modules and adjust system-wide limits.
+//!
+//! Every function in this module requires a kernel permission held by the current thread's security context.
+
+use crate::uuid::Uuid;
+
+use super::{
+    handle::HandlePtr,
+    io::IOHandle,
+    kstr::{KStrCPtr, KStrPtr},
+    result::SysResult,
+    time::Duration,
+};
+
+/// The subsystem number of the thread subsystem
+pub const SUBSYSTEM_THREAD: u16 = 1;
+/// The subsystem number of the io subsystem
+pub const SUBSYSTEM_IO: u16 = 2;
+/// The subsystem number of the process subsystem
+pub const SUBSYSTEM_PROCESS: u16 = 3;
+/// The subsystem number of the debug subsystem
+pub const SUBSYSTEM_DEBUG: u16 = 4;
+
+/// One more than the highest subsystem number the kernel may assign.
+pub const SUBSYSTEM_LIMIT: u16 = 64;
+
+/// The maximum number of threads that may exist on the system
+pub const SYSTEM_LIMIT_THREADS: &str = "threads";
+/// The maximum number of processes that may exist on the system
+pub const SYSTEM_LIMIT_PROCESSES: &str = "processes";
+/// The maximum number of handles that a single process may hold
+pub const SYSTEM_LIMIT_HANDLES_PER_PROCESS: &str = "handles_per_process";
+/// The maximum number of files that may be open on the system
+pub const SYSTEM_LIMIT_OPEN_FILES: &str = "open_files";
+
+/// A kernel log message about an error that prevented an operation from completing
+pub const KLOG_LEVEL_ERROR: u32 = 1;
+/// A kernel log message about a condition that may cause errors
+pub const KLOG_LEVEL_WARN: u32 = 2;
+/// A kernel log message about normal operation
+pub const KLOG_LEVEL_INFO: u32 = 3;
+/// A kernel log message for debugging
+pub const KLOG_LEVEL_DEBUG: u32 = 4;
+/// A detailed kernel log message for debugging
+pub const KLOG_LEVEL_TRACE: u32 = 5;
+
+/// An entry of the kernel message buffer, read by [`ReadKernelLog`]
+#[repr(C)]
+pub struct KernelLogEntry {
+    /// The sequence number of the entry. Each message written is assigned the next sequence number.
+    pub seq: u64,
+    /// The time the message was written, as an offset from the epoch of the system clock
+    pub timestamp: Duration,
+    /// One of the `KLOG_LEVEL_*` constants
+    pub level: u32,
+    /// The message. Before the call, this is the buffer to store the message in.
+    /// After the call, the length is set to the length of the message, even if it did not fit in the buffer.
+    pub message: KStrPtr,
+}
+
+#[allow(improper_ctypes)]
+extern "system" {
+    /// Loads a kernel module from the executable in `file`, and stores the id the kernel assigns to the module in `module_id`.
+    ///
+    /// ## Errors
+    ///
+    /// Returns `PERMISSION` if the current thread does not have the `LOAD_KERNEL_MODULE` kernel permission, or does not have read access to `file`.
+    ///
+    /// Returns `INVALID_HANDLE` if `file` is not a valid handle.
+    ///
+    /// Returns `INVALID_OPERATION` if `file` does not contain a kernel module supported by the kernel.
+    pub fn LoadKernelModule(module_id: *mut Uuid, file: HandlePtr<IOHandle>) -> SysResult;
+
+    /// Unloads the kernel module with the id `module_id`.
+    ///
+    /// ## Errors
+    ///
+    /// Returns `PERMISSION` if the current thread does not have the `UNLOAD_KERNEL_MODULE` kernel permission.
+    ///
+    /// Returns `DOES_NOT_EXIST` if no module with the id `module_id` is loaded.
+    ///
+    /// Returns `BUSY` if the module is in use and cannot be unloaded.
+    pub fn UnloadKernelModule(module_id: *const Uuid) -> SysResult;
+
+    /// Reads the current value of the system-wide limit `name` into `value`.
+    ///
+    /// ## Errors
+    ///
+    /// Returns `DOES_NOT_EXIST` if the kernel does not support the limit `name`.
+    pub fn GetSystemLimit(name: KStrCPtr, value: *mut u64) -> SysResult;
+
+    /// Sets the system-wide limit `name` to `value`.
+    ///
+    /// ## Errors
+    ///
+    /// Returns `PERMISSION` if the current thread does not have the `SET_SYSTEM_LIMIT` kernel permission.
+    ///
+    /// Returns `DOES_NOT_EXIST` if the kernel does not support the limit `name`.
+    ///
+    /// Returns `INVALID_OPERATION` if `value` is lower than the current usage of the limited resource.
+    pub fn SetSystemLimit(name: KStrCPtr, value: u64) -> SysResult;
+
+    /// Writes `message` to the kernel message buffer at the level `level`, one of the `KLOG_LEVEL_*` constants.
+    ///
+    /// ## Errors
+    ///
+    /// Returns `PERMISSION` if the current thread does not have the `WRITE_KERNEL_LOG` kernel permission.
+    ///
+    /// Returns `INVALID_OPTION` if `level` is not one of the `KLOG_LEVEL_*` constants.
+    ///
+    /// Returns `INVALID_STRING` if `message` is not valid UTF-8.
+    pub fn WriteKernelLog(level: u32, message: KStrCPtr) -> SysResult;
+
+    /// Reads the oldest entry of the kernel message buffer with a sequence number of at least `seq` into `entry`.
+    ///
+    /// The buffer holds a limited number of messages, so entries older than that are discarded, and the entry read may have a sequence number greater than `seq`.
+    ///
+    /// ## Errors
+    ///
+    /// Returns `PERMISSION` if the current thread does not have the `READ_KERNEL_LOG` kernel permission.
+    ///
+    /// Returns `DOES_NOT_EXIST` if no message with a sequence number of at least `seq` has been written yet.
+    ///
+    /// Returns `INSUFFICIENT_LENGTH` if the message does not fit in `entry.message`. The other fields of `entry` are filled in, and `entry.message.len` is set to the length of the message.
+    pub fn ReadKernelLog(seq: u64, entry: *mut KernelLogEntry) -> SysResult;
+}
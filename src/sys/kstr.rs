@@ -1,5 +1,5 @@
 #[repr(C)]
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, bytemuck::Zeroable)]
 pub struct KStrCPtr {
     pub str_ptr: *const u8,
     pub len: usize,
@@ -35,7 +35,7 @@ impl KStrCPtr {
 }
 
 #[repr(C)]
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, bytemuck::Zeroable)]
 pub struct KStrPtr {
     pub str_ptr: *mut u8,
     pub len: usize,
@@ -79,6 +79,17 @@ pub struct KCSlice<T> {
     pub len: usize,
 }
 
+impl<T> Clone for KCSlice<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for KCSlice<T> {}
+
+// SAFETY: The all-zero `KCSlice` is a null pointer with length zero
+unsafe impl<T> bytemuck::Zeroable for KCSlice<T> {}
+
 impl<T> KCSlice<T> {
     pub const fn empty() -> Self {
         Self {
@@ -105,6 +116,9 @@ pub struct KSlice<T> {
     pub len: usize,
 }
 
+// SAFETY: The all-zero `KSlice` is a null pointer with length zero
+unsafe impl<T> bytemuck::Zeroable for KSlice<T> {}
+
 impl<T> KSlice<T> {
     pub const fn empty() -> Self {
         Self {
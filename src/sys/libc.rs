@@ -0,0 +1,48 @@
+//! Declarations of the C library routines provided by the USI (Userspace System Interface), for programs that mix Rust and C code.
+//!
+//! The types are always available with the `libc` feature. The routines are declared only with the `link-usi` feature, as they are only present when the program links the USI C library.
+//!
+//! `errno` is thread-local, and accessed through [`__errno_location`]. Its values are those of [`result::errno`][crate::result::errno] (with the `errno-compat` feature).
+
+#![allow(non_camel_case_types)]
+
+use core::ffi::{c_char, c_int, c_void};
+
+pub type size_t = usize;
+pub type ssize_t = isize;
+
+#[cfg(feature = "link-usi")]
+#[allow(improper_ctypes)]
+extern "C" {
+    /// Returns a pointer to the `errno` of the current thread
+    pub fn __errno_location() -> *mut c_int;
+
+    pub fn malloc(size: size_t) -> *mut c_void;
+    pub fn calloc(count: size_t, size: size_t) -> *mut c_void;
+    pub fn realloc(ptr: *mut c_void, size: size_t) -> *mut c_void;
+    /// Allocates `size` bytes aligned to `align`, which must be a power of two. `size` must be a multiple of `align`.
+    pub fn aligned_alloc(align: size_t, size: size_t) -> *mut c_void;
+    pub fn free(ptr: *mut c_void);
+
+    pub fn memcpy(dest: *mut c_void, src: *const c_void, n: size_t) -> *mut c_void;
+    pub fn memmove(dest: *mut c_void, src: *const c_void, n: size_t) -> *mut c_void;
+    pub fn memset(dest: *mut c_void, c: c_int, n: size_t) -> *mut c_void;
+    pub fn memcmp(a: *const c_void, b: *const c_void, n: size_t) -> c_int;
+    pub fn memchr(s: *const c_void, c: c_int, n: size_t) -> *mut c_void;
+
+    pub fn strlen(s: *const c_char) -> size_t;
+    pub fn strnlen(s: *const c_char, max: size_t) -> size_t;
+    pub fn strcmp(a: *const c_char, b: *const c_char) -> c_int;
+    pub fn strncmp(a: *const c_char, b: *const c_char, n: size_t) -> c_int;
+    pub fn strcpy(dest: *mut c_char, src: *const c_char) -> *mut c_char;
+    pub fn strncpy(dest: *mut c_char, src: *const c_char, n: size_t) -> *mut c_char;
+    pub fn strcat(dest: *mut c_char, src: *const c_char) -> *mut c_char;
+    pub fn strncat(dest: *mut c_char, src: *const c_char, n: size_t) -> *mut c_char;
+    pub fn strchr(s: *const c_char, c: c_int) -> *mut c_char;
+    pub fn strrchr(s: *const c_char, c: c_int) -> *mut c_char;
+    pub fn strstr(haystack: *const c_char, needle: *const c_char) -> *mut c_char;
+    /// Copies `s` into memory allocated by [`malloc`]
+    pub fn strdup(s: *const c_char) -> *mut c_char;
+    /// Returns a description of the `errno` value `errnum`. The string must not be modified, and may be overwritten by the next call.
+    pub fn strerror(errnum: c_int) -> *mut c_char;
+}
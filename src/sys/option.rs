@@ -1,5 +1,9 @@
+use core::mem::MaybeUninit;
+
 use crate::uuid::Uuid;
 
+use super::kstr::KCSlice;
+
 /// The Header of a extended option type.
 /// This is common to all extended option types
 #[repr(C, align(32))]
@@ -26,3 +30,264 @@ impl ExtendedOptionHead {
         __reserved: [0; 3],
     };
 }
+
+/// A fixed-capacity array of extended options, for building the [`KCSlice`] passed to a system call such as [`OpenFile`][super::fs::OpenFile] or [`CreateProcess`][super::process::CreateProcess] without allocating.
+///
+/// `T` must be an extended option type (with the size and alignment of at least an [`ExtendedOptionHead`]), which is checked at compile time.
+///
+/// ## Example
+/// ```rust,ignore
+/// let mut opts = OptionsBuf::<CreateProcessOption, 2>::new();
+/// opts.push(CreateProcessOptionWorkingDir {
+///     path: KStrCPtr::from_str("/home"),
+///     ..CreateProcessOptionWorkingDir::NULL
+/// })
+/// .ok()
+/// .expect("capacity is 2");
+/// start_info.extended_options = opts.as_kslice();
+/// ```
+pub struct OptionsBuf<T, const N: usize> {
+    opts: [MaybeUninit<T>; N],
+    len: usize,
+}
+
+impl<T: Copy, const N: usize> OptionsBuf<T, N> {
+    const VALID: () = {
+        assert!(
+            core::mem::size_of::<T>() >= core::mem::size_of::<ExtendedOptionHead>(),
+            "OptionsBuf requires an extended option type, which must be at least as large as ExtendedOptionHead"
+        );
+        assert!(
+            core::mem::align_of::<T>() >= core::mem::align_of::<ExtendedOptionHead>(),
+            "OptionsBuf requires an extended option type, which must be aligned to at least 32 bytes"
+        );
+    };
+
+    /// Creates an empty buffer
+    pub const fn new() -> Self {
+        let () = Self::VALID;
+        Self {
+            opts: [const { MaybeUninit::uninit() }; N],
+            len: 0,
+        }
+    }
+
+    /// The number of options in the buffer
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Checks if no options have been pushed
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Checks if the buffer is at capacity, such that [`OptionsBuf::push`] will fail
+    pub const fn is_full(&self) -> bool {
+        self.len == N
+    }
+
+    /// The maximum number of options in the buffer, `N`
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+
+    /// Appends `opt` (or an arm of the option union `T`), returning it back as an error if the buffer is full
+    pub fn push<U: Into<T>>(&mut self, opt: U) -> Result<(), U> {
+        if self.is_full() {
+            return Err(opt);
+        }
+        self.opts[self.len].write(opt.into());
+        self.len += 1;
+        Ok(())
+    }
+
+    /// Removes every option from the buffer
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+
+    /// The options that have been pushed, in order
+    pub fn as_slice(&self) -> &[T] {
+        // SAFETY: The first `len` elements have been initialized by `push`
+        unsafe { core::slice::from_raw_parts(self.opts.as_ptr().cast(), self.len) }
+    }
+
+    /// The options that have been pushed, in order, as a mutable slice
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        // SAFETY: The first `len` elements have been initialized by `push`
+        unsafe { core::slice::from_raw_parts_mut(self.opts.as_mut_ptr().cast(), self.len) }
+    }
+
+    /// Obtains the [`KCSlice`] of the options that have been pushed, to pass to a system call.
+    ///
+    /// The result refers to `self`, and must not be used after `self` is moved, modified, or dropped.
+    pub fn as_kslice(&self) -> KCSlice<T> {
+        KCSlice::from_slice(self.as_slice())
+    }
+}
+
+impl<T: Copy, const N: usize> Default for OptionsBuf<T, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Copy, const N: usize> Clone for OptionsBuf<T, N> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T: Copy, const N: usize> Copy for OptionsBuf<T, N> {}
+
+#[doc(hidden)]
+pub use bytemuck as __bytemuck;
+
+/// Defines an extended option type, or a union of extended option types.
+///
+/// A struct begins with a field of type [`ExtendedOptionHead`], and is given the layout required of extended options (`repr(C, align(32))`).
+/// If the header is given a type, as `ExtendedOptionHead(ty)`, every other field must be given an initializer, and the struct has the following associated constants:
+/// * `TYPE`: The type of the option, `ty`,
+/// * `NULL`: The option with the header set to `TYPE` and no flags, and each other field set to its initializer.
+///
+/// Otherwise (such as for the fallback type of a union), the struct has an `INIT` constant with every byte (including the header) set to `0`.
+///
+/// A union has the same layout, and each field is an arm of the union. The union has an `INIT` constant with every byte set to `0`, and implements [`From`] for each arm.
+///
+/// Each type defined implements [`Copy`] and [`bytemuck::Zeroable`]. Each field must implement [`bytemuck::Zeroable`].
+///
+/// ## Example
+/// ```rust,ignore
+/// def_extended_option! {
+///     /// Sets the stack of the exception handler
+///     pub struct SetStackOption {
+///         pub head: ExtendedOptionHead(parse_uuid("...")),
+///         pub stack_base_addr: *mut c_void = core::ptr::null_mut(),
+///     }
+/// }
+///
+/// def_extended_option! {
+///     pub union HandlerOption {
+///         pub head: ExtendedOptionHead,
+///         pub set_stack: SetStackOption,
+///     }
+/// }
+/// ```
+#[macro_export]
+macro_rules! def_extended_option {
+    {
+        $(#[$meta:meta])*
+        $vis:vis struct $name:ident {
+            $(#[$hmeta:meta])*
+            $hvis:vis $head:ident: ExtendedOptionHead($ty:expr),
+            $($(#[$fmeta:meta])* $fvis:vis $field:ident: $fty:ty = $init:expr),* $(,)?
+        }
+    } => {
+        $crate::def_extended_option! {
+            @struct
+            $(#[$meta])*
+            $vis struct $name {
+                $(#[$hmeta])*
+                $hvis $head,
+                $($(#[$fmeta])* $fvis $field: $fty),*
+            }
+        }
+
+        impl $name {
+            /// The type of the option
+            pub const TYPE: $crate::uuid::Uuid = $ty;
+
+            /// The option with the type set and no flags
+            pub const NULL: Self = Self {
+                $head: $crate::sys::option::ExtendedOptionHead {
+                    ty: Self::TYPE,
+                    ..$crate::sys::option::ExtendedOptionHead::ZERO
+                },
+                $($field: $init),*
+            };
+        }
+    };
+    {
+        $(#[$meta:meta])*
+        $vis:vis struct $name:ident {
+            $(#[$hmeta:meta])*
+            $hvis:vis $head:ident: ExtendedOptionHead,
+            $($(#[$fmeta:meta])* $fvis:vis $field:ident: $fty:ty),* $(,)?
+        }
+    } => {
+        $crate::def_extended_option! {
+            @struct
+            $(#[$meta])*
+            $vis struct $name {
+                $(#[$hmeta])*
+                $hvis $head,
+                $($(#[$fmeta])* $fvis $field: $fty),*
+            }
+        }
+
+        impl $name {
+            /// The option with every byte set to `0`
+            pub const INIT: Self = unsafe { ::core::mem::zeroed() };
+        }
+    };
+    {
+        $(#[$meta:meta])*
+        $vis:vis union $name:ident {
+            $($(#[$fmeta:meta])* $fvis:vis $field:ident: $fty:ty),* $(,)?
+        }
+    } => {
+        $(#[$meta])*
+        #[repr(C, align(32))]
+        #[derive(Copy, Clone)]
+        $vis union $name {
+            $($(#[$fmeta])* $fvis $field: $fty),*
+        }
+
+        $crate::def_extended_option!(@zeroable $name, $($fty),*);
+
+        impl $name {
+            /// The option with every byte set to `0`
+            pub const INIT: Self = unsafe { ::core::mem::zeroed() };
+        }
+
+        $(
+            impl ::core::convert::From<$fty> for $name {
+                fn from($field: $fty) -> Self {
+                    Self { $field }
+                }
+            }
+        )*
+    };
+    {
+        @struct
+        $(#[$meta:meta])*
+        $vis:vis struct $name:ident {
+            $(#[$hmeta:meta])*
+            $hvis:vis $head:ident,
+            $($(#[$fmeta:meta])* $fvis:vis $field:ident: $fty:ty),*
+        }
+    } => {
+        $(#[$meta])*
+        #[repr(C, align(32))]
+        #[derive(Copy, Clone)]
+        $vis struct $name {
+            $(#[$hmeta])*
+            $hvis $head: $crate::sys::option::ExtendedOptionHead,
+            $($(#[$fmeta])* $fvis $field: $fty),*
+        }
+
+        $crate::def_extended_option!(@zeroable $name, $($fty),*);
+    };
+    (@zeroable $name:ident, $($fty:ty),*) => {
+        const _: () = {
+            // Unused by options with no fields after the header
+            #[allow(dead_code)]
+            const fn assert_zeroable<T: $crate::sys::option::__bytemuck::Zeroable>() {}
+            $(assert_zeroable::<$fty>();)*
+        };
+
+        // SAFETY: Every field is `Zeroable`, as checked above
+        unsafe impl $crate::sys::option::__bytemuck::Zeroable for $name {}
+    };
+}
@@ -28,6 +28,14 @@ extern "C" {
     ) -> SysResult;
     pub fn DestroySecurityContext(ctx: HandlePtr<SecurityContext>) -> SysResult;
     pub fn GetCurrentSecurityContext(nctx: *mut HandlePtr<SecurityContext>) -> SysResult;
+
+    /// Replaces the security context of the current thread with a copy of `ctx`.
+    ///
+    /// ## Permission Check
+    ///
+    /// Every principal of `ctx` must be a principal of the current security context, and every permission granted in `ctx` must be granted or recoverable in the current security context,
+    ///  or the thread must have the kernel permission SECURITY_SET_CREDENTIAL.
+    pub fn SetCurrentSecurityContext(ctx: HandlePtr<SecurityContext>) -> SysResult;
     pub fn HasKernelPermission(ctx: HandlePtr<SecurityContext>, perm: KStrCPtr) -> SysResult;
     pub fn HasThreadPermission(
         ctx: HandlePtr<SecurityContext>,
@@ -45,9 +53,20 @@ extern "C" {
     /// ## Permission Check
     ///
     /// ctx must have principal as one of it's principals (either an explicit primary or an explicit secondary principal), or the thread must have the kernel permission SECURITY_SET_CREDENTIAL.
-    pub fn SetPrimaryPrincipal(ctx: HandlePtr<SecurityContext>, principal: *const Uuid);
+    pub fn SetPrimaryPrincipal(
+        ctx: HandlePtr<SecurityContext>,
+        principal: *const Uuid,
+    ) -> SysResult;
 
-    pub fn AddSecondaryPrincipal(ctx: HandlePtr<SecurityContext>, principal: *const Uuid);
+    /// Adds a secondary principal to the security context.
+    ///
+    /// ## Permission Check
+    ///
+    /// ctx must already have principal as one of it's principals, or the thread must have the kernel permission SECURITY_SET_CREDENTIAL.
+    pub fn AddSecondaryPrincipal(
+        ctx: HandlePtr<SecurityContext>,
+        principal: *const Uuid,
+    ) -> SysResult;
 
     pub fn GrantKernelPermission(
         ctx: HandlePtr<SecurityContext>,
@@ -117,13 +136,44 @@ extern "C" {
         value: *mut u64,
     ) -> SysResult;
 
+    /// Encodes the principals, permissions, and resource limits of `ctx` into `buffer`, in the format of the `InstallSecurityContext` stream.
+    ///
+    /// On entry, `len` is the number of bytes `buffer` can store. On success, `len` is set to the number of bytes written.
+    ///
+    /// ## Errors
+    ///
+    /// If `buffer` is too small, `INSUFFICIENT_LENGTH` is returned, and `len` is set to the number of bytes needed.
+    ///
+    /// If `ctx` has a principal or a permission that the current thread does not have, `PERMISSION` is returned,
+    ///  unless the current thread has the `SECURITY_SET_CREDENTIAL` kernel permission.
     pub fn EncodeSecurityContext(
         ctx: HandlePtr<SecurityContext>,
         buffer: *mut u8,
         len: *mut usize,
     ) -> SysResult;
 
+    /// Creates a new security context from `buffer`, as encoded by [`EncodeSecurityContext`], such as the content of an `InstallSecurityContext` stream.
+    ///
+    /// Decoding a context never grants anything to the current thread - the context only takes effect when it is installed by a privilaged process.
+    ///
+    /// ## Errors
+    ///
+    /// If `buffer` is not a valid encoded security context, `INVALID_STATE` is returned.
+    ///
+    /// If the kernel limit `HANDLES` is exceeded by the current thread, `RESOURCE_LIMIT_EXHAUSTED` is returned.
+    pub fn DecodeSecurityContext(
+        nctx: *mut HandlePtr<SecurityContext>,
+        buffer: *const u8,
+        len: usize,
+    ) -> SysResult;
+
+    /// Gets the primary principal of the security context, or of the current thread if `ctx` is null.
     pub fn GetPrimaryPrincipal(ctx: HandlePtr<SecurityContext>, principal: *mut Uuid) -> SysResult;
+    /// Gets the secondary principals of the security context, or of the current thread if `ctx` is null.
+    ///
+    /// On entry, `principals_len` is the number of elements `principals` can store. At most that many principals are written,
+    ///  and `principals_len` is set to the total number of secondary principals in the context.
+    /// `principals` may be null if `principals_len` points to `0`.
     pub fn GetSecondaryPrincipals(
         ctx: HandlePtr<SecurityContext>,
         principals: *mut Uuid,
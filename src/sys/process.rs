@@ -8,7 +8,6 @@ use crate::{io::IOHandle, uuid::Uuid};
 
 use super::except::ExceptionStatusInfo;
 use super::kstr::KCSlice;
-use super::option::ExtendedOptionHead;
 use super::{
     fs::FileHandle,
     handle::{Handle, HandlePtr},
@@ -16,7 +15,9 @@ use super::{
     kstr::{KStrCPtr, KStrPtr},
     permission::SecurityContext,
     result::SysResult,
+    thread::ThreadHandle,
 };
+use crate::def_extended_option;
 
 #[repr(transparent)]
 pub struct ProcessHandle(Handle);
@@ -81,7 +82,7 @@ pub struct ProcessStartContext {
     /// The length of the array pointed to by `init_handles`
     pub init_handles_len: c_ulong,
     /// A pointer to an array of handles to pass into the spawned process.
-    /// A corresponding array of such handles is given by the AT_PHANTOM_INIT_HANDLES array in the spawned process
+    /// A corresponding array of such handles is given by the [`AT_LILIUM_INIT_HANDLES`][super::elf::AT_LILIUM_INIT_HANDLES] array in the spawned process
     ///
     /// By convention, this array starts with the standard input, standard output, and standard error streams.
     pub init_handles: *const HandlePtr<Handle>,
@@ -95,6 +96,74 @@ pub struct ProcessStartContext {
     pub proc_args: *const KStrCPtr,
     /// The namespace to place the process in
     pub init_namespace: HandlePtr<NamespaceHandle>,
+    /// Extended options for spawning the process
+    pub extended_options: KCSlice<CreateProcessOption>,
+}
+
+def_extended_option! {
+    /// An extended option for [`CreateProcess`] of an unknown type
+    pub struct CreateProcessOptionRaw {
+        pub header: ExtendedOptionHead,
+        pub data: [MaybeUninit<u8>; 64],
+    }
+}
+
+/// A limit on a resource used by a process, for [`CreateProcessOptionResourceLimits`]
+#[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ProcessResourceLimit {
+    /// The resource being limited, one of the `PROCESS_LIMIT_*` constants
+    pub limit: Uuid,
+    /// The maximum amount of the resource the process may use
+    pub value: u64,
+}
+
+/// The total virtual memory allocated for mappings in the process, in bytes
+pub const PROCESS_LIMIT_MEMORY: Uuid = parse_uuid("e9578888-10c2-5af5-aad3-77add821e48e");
+/// The total physical memory allocated for mappings in the process, in bytes
+pub const PROCESS_LIMIT_PHYS_MEMORY: Uuid = parse_uuid("c1417226-aa51-59fd-9e3e-760bd908e695");
+/// The number of handles that may be open at once by threads in the process
+pub const PROCESS_LIMIT_HANDLES: Uuid = parse_uuid("d4aa1a42-09bd-5ac5-aa26-36314ce915e8");
+/// The number of anonymous objects (such as pipes and memory buffers) that may be created by the process
+pub const PROCESS_LIMIT_ANON_OBJECTS: Uuid = parse_uuid("5a88f682-1549-5b5f-bc07-f4a0ae748a4e");
+/// The number of threads that may run in the process at once
+pub const PROCESS_LIMIT_THREADS: Uuid = parse_uuid("d055a0d0-f4a2-5fe6-93eb-ad91e0b1b6bc");
+
+def_extended_option! {
+    /// Limits the resources the spawned process can use.
+    ///
+    /// Each limit is capped to the corresponding limit of the calling process, unless the current thread has the `SET_PROCESS_LIMITS` kernel permission.
+    /// If a resource is given multiple limits, the smallest is used.
+    ///
+    /// If the kernel does not recognize a limit, `CreateProcess` fails with `INVALID_OPTION`, unless [`OPTION_FLAG_IGNORE`][super::option::OPTION_FLAG_IGNORE] is set.
+    pub struct CreateProcessOptionResourceLimits {
+        pub header: ExtendedOptionHead(parse_uuid("9eb4c8cd-b499-5f42-a096-8cb341f1c471")),
+        pub limits: KCSlice<ProcessResourceLimit> = KCSlice::empty(),
+    }
+}
+
+def_extended_option! {
+    /// Sets the current directory and resolution root of the spawned process.
+    ///
+    /// By default, the process inherits the current directory and resolution root of the calling thread.
+    pub struct CreateProcessOptionWorkingDir {
+        pub header: ExtendedOptionHead(parse_uuid("bc92927f-cce0-5f57-a38a-9ad1fdaf5e56")),
+        /// The base to resolve `path` against. If null, `path` is resolved against the current directory of the calling thread
+        pub resolution_base: HandlePtr<FileHandle> = HandlePtr::null(),
+        /// The path to the current directory of the process. If empty, `resolution_base` is used as the current directory
+        pub path: KStrCPtr = KStrCPtr::empty(),
+        /// The resolution root of the process, which absolute paths are resolved against.
+        /// If null, the process inherits the resolution root of the calling thread
+        pub resolution_root: HandlePtr<FileHandle> = HandlePtr::null(),
+    }
+}
+
+def_extended_option! {
+    pub union CreateProcessOption {
+        pub raw: CreateProcessOptionRaw,
+        pub resource_limits: CreateProcessOptionResourceLimits,
+        pub working_dir: CreateProcessOptionWorkingDir,
+    }
 }
 
 #[repr(transparent)]
@@ -156,55 +225,34 @@ pub const MAP_KIND_RESIDENT: u32 = 1;
 pub const MAP_KIND_SECURE: u32 = 2;
 pub const MAP_KIND_ENCRYPTED: u32 = 3;
 
-#[repr(C)]
-#[derive(Copy, Clone)]
-pub struct MapExtendedAttrRaw {
-    pub header: ExtendedOptionHead,
-    pub data: [MaybeUninit<u8>; 32],
+def_extended_option! {
+    pub struct MapExtendedAttrRaw {
+        pub header: ExtendedOptionHead,
+        pub data: [MaybeUninit<u8>; 32],
+    }
 }
 
-#[repr(C, align(32))]
-#[derive(Copy, Clone)]
-pub struct MapExtendedAttrBacking {
-    pub header: ExtendedOptionHead,
-    pub stream_base: u64,
-    pub backing_file: HandlePtr<IOHandle>,
+def_extended_option! {
+    pub struct MapExtendedAttrBacking {
+        pub header: ExtendedOptionHead(parse_uuid("294d5c4e-cdf4-53b3-bfbc-ed804526394b")),
+        pub stream_base: u64 = 0,
+        pub backing_file: HandlePtr<IOHandle> = HandlePtr::null(),
+    }
 }
 
-impl MapExtendedAttrBacking {
-    pub const NULL: Self = Self {
-        header: ExtendedOptionHead {
-            ty: parse_uuid("294d5c4e-cdf4-53b3-bfbc-ed804526394b"),
-            ..ExtendedOptionHead::ZERO
-        },
-        stream_base: 0,
-        backing_file: HandlePtr::null(),
-    };
+def_extended_option! {
+    pub struct MapExtendedAttrName {
+        pub header: ExtendedOptionHead(parse_uuid("90ded1b2-ba85-5d34-90b4-74e717444863")),
+        pub mapping_name: KStrCPtr = KStrCPtr::empty(),
+    }
 }
 
-#[repr(C, align(32))]
-#[derive(Copy, Clone)]
-pub struct MapExtendedAttrName {
-    pub header: ExtendedOptionHead,
-    pub mapping_name: KStrCPtr,
-}
-
-impl MapExtendedAttrName {
-    pub const NULL: Self = Self {
-        header: ExtendedOptionHead {
-            ty: parse_uuid("90ded1b2-ba85-5d34-90b4-74e717444863"),
-            ..ExtendedOptionHead::ZERO
-        },
-        mapping_name: KStrCPtr::empty(),
-    };
-}
-
-#[repr(C, align(32))]
-#[derive(Copy, Clone)]
-pub union MapExtendedAttr {
-    pub raw: MapExtendedAttrRaw,
-    pub backing: MapExtendedAttrBacking,
-    pub mapping_name: MapExtendedAttrName,
+def_extended_option! {
+    pub union MapExtendedAttr {
+        pub raw: MapExtendedAttrRaw,
+        pub backing: MapExtendedAttrBacking,
+        pub mapping_name: MapExtendedAttrName,
+    }
 }
 
 #[allow(improper_ctypes)]
@@ -233,6 +281,9 @@ extern "C" {
         map: HandlePtr<EnvironmentMapHandle>,
     ) -> SysResult;
 
+    /// Destroys the given environment map
+    pub fn DestroyEnvironment(hdl: HandlePtr<EnvironmentMapHandle>) -> SysResult;
+
     /// Enumerates over the list of key-value pairs in the environment map
     pub fn EnvironmentNextPair(
         hdl: HandlePtr<EnvironmentMapHandle>,
@@ -297,6 +348,24 @@ extern "C" {
     /// Detaches the given process from the handle
     pub fn DetachProcess(hdl: HandlePtr<ProcessHandle>) -> SysResult;
 
+    /// Obtains a handle to the initial thread of the process designated by `hdl`, which is the thread that runs the entry point of the program.
+    ///
+    /// For a process started with [`FLAG_START_SUSPENDED`], the thread is suspended before it executes any instruction of the program, so a debugger can be attached to it with [`DebugAttach`][super::debug::DebugAttach].
+    ///
+    /// ## Errors
+    ///
+    /// If `hdl` is not a valid `ProcessHandle`, `INVALID_HANDLE` is returned.
+    ///
+    /// If `th_out` does not point to appropriate writeable memory, `INVALID_MEMORY` is returned.
+    ///
+    /// If the initial thread has terminated, `DOES_NOT_EXIST` is returned.
+    ///
+    /// If the kernel limit `HANDLES` is exceeded by the current thread, `RESOURCE_LIMIT_EXHAUSTED` is returned.
+    pub fn GetInitialThread(
+        hdl: HandlePtr<ProcessHandle>,
+        th_out: *mut HandlePtr<ThreadHandle>,
+    ) -> SysResult;
+
     /// Terminates all threads as though by `DestroyThread` syscalls, and exits from the process with the given code
     ///
     /// The termination of other threads occurs at such a time as the thread might recieve a signal from `SignalThread`.
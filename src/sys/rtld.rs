@@ -0,0 +1,57 @@
+//! Interfaces to the USI runtime dynamic loader (`libusi-rtld.so`), which loads shared objects into the current process and resolves their symbols.
+
+use core::ffi::c_void;
+
+use super::{
+    kstr::{KStrCPtr, KStrPtr},
+    result::SysResult,
+};
+
+/// A shared object loaded by [`RtldOpen`]
+#[repr(C)]
+pub struct RtldModule {
+    _opaque: [u8; 0],
+}
+
+/// Relocates function symbols when they are first called, rather than when the module is loaded
+pub const RTLD_LAZY: u32 = 0x01;
+/// Relocates every symbol when the module is loaded, so that unresolved symbols are reported by [`RtldOpen`]
+pub const RTLD_NOW: u32 = 0x02;
+/// Makes the symbols of the module available to resolve the symbols of modules loaded afterwards
+pub const RTLD_GLOBAL: u32 = 0x100;
+/// Does not make the symbols of the module available to other modules. This is the default.
+pub const RTLD_LOCAL: u32 = 0x000;
+
+#[allow(improper_ctypes)]
+extern "C" {
+    /// Loads the shared object `name`, or increases its reference count if it is already loaded, and stores the module in `module`.
+    ///
+    /// If `name` contains a `/`, it is a path. Otherwise, it is searched for in the library search path of the process.
+    ///
+    /// ## Errors
+    ///
+    /// Returns `DOES_NOT_EXIST` if the shared object cannot be found.
+    ///
+    /// Returns `INVALID_STATE` if the shared object is malformed, or a symbol it needs cannot be resolved with `RTLD_NOW`.
+    ///
+    /// Returns `INVALID_OPTION` if `flags` contains an unknown flag, or both `RTLD_LAZY` and `RTLD_NOW`.
+    ///
+    /// On error, a description of the error is available from [`RtldLastError`].
+    pub fn RtldOpen(module: *mut *mut RtldModule, name: KStrCPtr, flags: u32) -> SysResult;
+
+    /// Decreases the reference count of `module`, and unloads it once the count reaches zero. Pointers to its symbols are invalid once it is unloaded.
+    pub fn RtldClose(module: *mut RtldModule) -> SysResult;
+
+    /// Resolves the symbol `name` of `module`, and stores its address in `sym`.
+    ///
+    /// ## Errors
+    ///
+    /// Returns `DOES_NOT_EXIST` if `module` does not define `name`.
+    pub fn RtldSymbol(module: *mut RtldModule, name: KStrCPtr, sym: *mut *mut c_void) -> SysResult;
+
+    /// Reads the description of the last error returned by a dynamic loader routine on the current thread, and clears it.
+    ///
+    /// Returns `0` if there is a description, and `DOES_NOT_EXIST` if there is none.
+    /// Returns `INSUFFICIENT_LENGTH` if `msg` is too short to store the description, and sets `msg.len` to the required length. The description is not cleared.
+    pub fn RtldLastError(msg: *mut KStrPtr) -> SysResult;
+}
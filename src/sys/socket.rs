@@ -1,7 +1,16 @@
+use core::{
+    ffi::{c_ulong, c_void},
+    mem::MaybeUninit,
+};
+
 use super::{
     handle::{Handle, HandlePtr},
+    io::IOHandle,
+    kstr::{KCSlice, KSlice},
+    option::ExtendedOptionHead,
     result::SysResult,
 };
+use crate::{def_extended_option, uuid::parse_uuid};
 
 #[repr(transparent)]
 pub struct SocketHandle(Handle);
@@ -12,6 +21,147 @@ pub struct ServerHandle(Handle);
 #[repr(C)]
 pub struct sockaddr {}
 
+/// The name of the IPC channel of the system resolver service, which is connected to by [`ConnectToNamed`][super::ipc::ConnectToNamed].
+///
+/// The service resolves host names to addresses. A client writes a [`ResolverRequest`] followed by the UTF-8 host name,
+///  and the service replies with a [`ResolverResponse`] followed by `count` [`ResolverRecord`]s.
+pub const RESOLVER_CHANNEL: &str = "/sys/ipc/resolver";
+
+/// The record is an IPv4 address, stored in the first 4 bytes of [`ResolverRecord::addr`]
+pub const ADDR_FAMILY_IPV4: u32 = 4;
+/// The record is an IPv6 address
+pub const ADDR_FAMILY_IPV6: u32 = 6;
+
+/// A request to the system resolver service
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Zeroable, bytemuck::Pod)]
+pub struct ResolverRequest {
+    /// The length in bytes of the host name that follows the request
+    pub name_len: u64,
+}
+
+/// The header of a reply from the system resolver service
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Zeroable, bytemuck::Pod)]
+pub struct ResolverResponse {
+    /// `0` if the name was resolved, or an error code. In particular, `DOES_NOT_EXIST` is returned if the name has no addresses.
+    pub status: SysResult,
+    /// The number of [`ResolverRecord`]s that follow the response
+    pub count: u64,
+}
+
+/// An address resolved by the system resolver service
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Zeroable, bytemuck::Pod)]
+pub struct ResolverRecord {
+    /// One of the `ADDR_FAMILY_*` constants
+    pub family: u32,
+    /// For [`ADDR_FAMILY_IPV6`], the scope id of the address. Otherwise, `0`.
+    pub scope_id: u32,
+    /// For [`ADDR_FAMILY_IPV6`], the flow information of the address. Otherwise, `0`.
+    pub flowinfo: u32,
+    #[doc(hidden)]
+    pub __reserved: u32,
+    /// The address, in network byte order
+    pub addr: [u8; 16],
+}
+
+/// The address of a socket, read by [`GetSocketPeerAddress`] and [`GetSocketLocalAddress`]
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Zeroable, bytemuck::Pod)]
+pub struct SocketAddress {
+    /// One of the `ADDR_FAMILY_*` constants
+    pub family: u32,
+    /// The port of the address, in native byte order
+    pub port: u16,
+    #[doc(hidden)]
+    pub __reserved: u16,
+    /// For [`ADDR_FAMILY_IPV6`], the flow information of the address. Otherwise, `0`.
+    pub flowinfo: u32,
+    /// For [`ADDR_FAMILY_IPV6`], the scope id of the address. Otherwise, `0`.
+    pub scope_id: u32,
+    /// The address, in network byte order. IPv4 addresses are stored in the first 4 bytes.
+    pub addr: [u8; 16],
+}
+
+/// A connected stream socket, which delivers data reliably and in order
+pub const SOCK_STREAM: u32 = 1;
+/// A datagram socket, which sends and receives individual messages that may be lost or reordered
+pub const SOCK_DATAGRAM: u32 = 2;
+
+def_extended_option! {
+    /// An option for creating a socket
+    pub struct UnknownSocketCreateOption {
+        /// The header
+        pub head: ExtendedOptionHead,
+        /// The tail
+        pub tail: [MaybeUninit<u8>; 64],
+    }
+}
+
+def_extended_option! {
+    /// Allows the socket to be bound to an address that other sockets are bound to, if every socket bound to it was created with this option.
+    ///
+    /// Datagrams sent to a multicast or broadcast address are delivered to every datagram socket bound to the address, and other datagrams to one of them.
+    /// This allows several programs to receive on the well-known port of a discovery protocol, such as mDNS or SSDP.
+    pub struct SocketCreateOptionSharedAddress {
+        pub header: ExtendedOptionHead(parse_uuid("802b7065-0c7e-5174-b4c3-3fc16c538e29")),
+    }
+}
+
+def_extended_option! {
+    pub union SocketCreateOption {
+        /// The Header: Must be present on all subfields
+        pub head: ExtendedOptionHead,
+        /// Fallback type for all fields
+        pub unknown: UnknownSocketCreateOption,
+        pub shared_address: SocketCreateOptionSharedAddress,
+    }
+}
+
+/// A multicast group membership, the value of [`SOCKOPT_JOIN_MULTICAST`] and [`SOCKOPT_LEAVE_MULTICAST`]
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Zeroable, bytemuck::Pod)]
+pub struct MulticastMembership {
+    /// One of the `ADDR_FAMILY_*` constants, which is the family of both `group` and `interface_addr`
+    pub family: u32,
+    /// For [`ADDR_FAMILY_IPV6`], the index of the interface to join the group on, or `0` to choose one. Otherwise, `0`.
+    pub interface: u32,
+    /// The address of the multicast group, in network byte order
+    pub group: [u8; 16],
+    /// For [`ADDR_FAMILY_IPV4`], the address of the interface to join the group on, or all zeroes to choose one. Otherwise, all zeroes.
+    pub interface_addr: [u8; 16],
+}
+
+/// Disables coalescing small writes into fewer packets (Nagle's algorithm). The value is a `u32`, `0` or `1`.
+pub const SOCKOPT_NODELAY: u32 = 1;
+/// Sends keepalive probes on an idle connection. The value is a `u32`, `0` or `1`.
+pub const SOCKOPT_KEEPALIVE: u32 = 2;
+/// The size in bytes of the receive buffer of the socket. The value is a `c_ulong`.
+pub const SOCKOPT_RECV_BUFFER_SIZE: u32 = 3;
+/// The size in bytes of the send buffer of the socket. The value is a `c_ulong`.
+pub const SOCKOPT_SEND_BUFFER_SIZE: u32 = 4;
+/// The blocking timeout of reads from the socket, which applies instead of the blocking timeout of the thread.
+/// The value is a [`Duration`][super::time::Duration], and a zero duration clears the timeout.
+pub const SOCKOPT_READ_TIMEOUT: u32 = 5;
+/// The blocking timeout of writes to the socket, which applies instead of the blocking timeout of the thread.
+/// The value is a [`Duration`][super::time::Duration], and a zero duration clears the timeout.
+pub const SOCKOPT_WRITE_TIMEOUT: u32 = 6;
+/// Allows sending datagrams to broadcast addresses. The value is a `u32`, `0` or `1`.
+pub const SOCKOPT_BROADCAST: u32 = 7;
+/// The time-to-live (or IPv6 hop limit) of unicast packets sent by the socket. The value is a `u32`.
+pub const SOCKOPT_TTL: u32 = 8;
+/// The time-to-live (or IPv6 hop limit) of multicast packets sent by the socket. The value is a `u32`.
+pub const SOCKOPT_MULTICAST_TTL: u32 = 9;
+/// Delivers multicast packets sent by the socket back to the local host. The value is a `u32`, `0` or `1`.
+pub const SOCKOPT_MULTICAST_LOOP: u32 = 10;
+/// Joins a multicast group, when set. The value is a [`MulticastMembership`]. This option cannot be read.
+pub const SOCKOPT_JOIN_MULTICAST: u32 = 11;
+/// Leaves a multicast group, when set. The value is a [`MulticastMembership`]. This option cannot be read.
+pub const SOCKOPT_LEAVE_MULTICAST: u32 = 12;
+/// The kind of the socket, one of the `SOCK_*` constants. The value is a `u32`. This option cannot be set.
+pub const SOCKOPT_KIND: u32 = 13;
+
 #[allow(improper_ctypes)]
 extern "C" {
     pub fn CreateServerSocket(servout: *mut HandlePtr<ServerHandle>) -> SysResult;
@@ -21,4 +171,136 @@ extern "C" {
         server: HandlePtr<ServerHandle>,
     ) -> SysResult;
 
+    /// Creates a socket of the kind `kind`, one of the `SOCK_*` constants, for addresses of the family `family`, one of the `ADDR_FAMILY_*` constants.
+    ///
+    /// ## Errors
+    ///
+    /// Returns `INVALID_OPTION` if `family` or `kind` is not known, or any extended option specified by `options` is invalid.
+    ///
+    /// Returns `UNSUPPORTED_OPERATION` if an extended option does not apply to `kind`, such as [`SocketCreateOptionSharedAddress`] for stream sockets.
+    ///
+    /// Returns `PERMISSION` if the current thread is not permitted to create sockets.
+    pub fn CreateSocket(
+        sockout: *mut HandlePtr<SocketHandle>,
+        family: u32,
+        kind: u32,
+        options: *const KCSlice<SocketCreateOption>,
+    ) -> SysResult;
+    /// Binds the socket `hdl` to the local address `addr`. A port of `0` binds the socket to an unused port.
+    ///
+    /// ## Errors
+    ///
+    /// Returns `INVALID_HANDLE` if `hdl` is not a valid `SocketHandle`.
+    ///
+    /// Returns `ALREADY_EXISTS` if the address is in use, unless every socket bound to it, including `hdl`, was created with [`SocketCreateOptionSharedAddress`].
+    /// Returns `PERMISSION` if the current thread may not bind to the address.
+    ///
+    /// Returns `INVALID_STATE` if the socket is already bound.
+    pub fn BindSocket(hdl: HandlePtr<SocketHandle>, addr: *const SocketAddress) -> SysResult;
+    /// Connects the stream socket `hdl` to the remote address `addr`, binding it to an unused local address if it is not bound.
+    ///
+    /// ## Errors
+    ///
+    /// Returns `INVALID_HANDLE` if `hdl` is not a valid `SocketHandle`.
+    ///
+    /// Returns `INVALID_STATE` if the socket is already connected, and `UNSUPPORTED_OPERATION` if it is not a stream socket.
+    ///
+    /// Returns `CLOSED_REMOTELY` if the connection is refused by `addr`, and `TIMEOUT` if the connection could not be established before the blocking timeout elapsed.
+    pub fn ConnectSocket(hdl: HandlePtr<SocketHandle>, addr: *const SocketAddress) -> SysResult;
+    /// Sends a single datagram, made of the `nbufs` buffers at `bufs` in order, from the datagram socket `hdl` to `addr`.
+    ///
+    /// Returns the number of bytes sent.
+    ///
+    /// ## Errors
+    ///
+    /// Returns `INVALID_HANDLE` if `hdl` is not a valid `SocketHandle`.
+    ///
+    /// Returns `UNSUPPORTED_OPERATION` if `hdl` is not a datagram socket, and `PERMISSION` if `addr` is a broadcast address and [`SOCKOPT_BROADCAST`] is not set.
+    ///
+    /// Returns `INVALID_OPERATION` if the datagram is too large to send.
+    pub fn SocketSendTo(
+        hdl: HandlePtr<SocketHandle>,
+        bufs: *const KCSlice<u8>,
+        nbufs: c_ulong,
+        addr: *const SocketAddress,
+    ) -> SysResult;
+    /// Receives a single datagram on the datagram socket `hdl`, filling the `nbufs` buffers at `bufs` in order, and stores the address it was sent from in `addr`.
+    ///
+    /// Returns the number of bytes received. If the datagram is larger than the buffers, the remainder is discarded.
+    ///
+    /// ## Errors
+    ///
+    /// Returns `INVALID_HANDLE` if `hdl` is not a valid `SocketHandle`.
+    ///
+    /// Returns `UNSUPPORTED_OPERATION` if `hdl` is not a datagram socket.
+    pub fn SocketRecvFrom(
+        hdl: HandlePtr<SocketHandle>,
+        bufs: *const KSlice<u8>,
+        nbufs: c_ulong,
+        addr: *mut SocketAddress,
+    ) -> SysResult;
+
+    /// Determines whether `iohdl` is a socket. Returns `1` if it is, and `0` if it is another kind of `IOHandle`.
+    ///
+    /// Every socket is an `IOHandle`, so a `HandlePtr<SocketHandle>` may be cast to a `HandlePtr<IOHandle>` to read from and write to it.
+    /// A `HandlePtr<IOHandle>` may only be cast to a `HandlePtr<SocketHandle>` if this function returns `1`.
+    ///
+    /// ## Errors
+    ///
+    /// Returns `INVALID_HANDLE` if `iohdl` is not a valid `IOHandle`.
+    pub fn IsSocket(iohdl: HandlePtr<IOHandle>) -> SysResult;
+
+    /// Sets the option `opt` of the socket `hdl`, one of the `SOCKOPT_*` constants, to the `len` bytes at `val`.
+    ///
+    /// ## Errors
+    ///
+    /// Returns `INVALID_HANDLE` if `hdl` is not a valid `SocketHandle`.
+    ///
+    /// Returns `INVALID_OPTION` if `opt` is not a known option, or `len` is not the size of its value.
+    ///
+    /// Returns `UNSUPPORTED_OPERATION` if the option does not apply to the socket (such as [`SOCKOPT_NODELAY`] on a datagram socket).
+    pub fn SetSocketOption(
+        hdl: HandlePtr<SocketHandle>,
+        opt: u32,
+        val: *const c_void,
+        len: c_ulong,
+    ) -> SysResult;
+    /// Reads the option `opt` of the socket `hdl`, one of the `SOCKOPT_*` constants, into the `len` bytes at `val`.
+    ///
+    /// ## Errors
+    ///
+    /// Returns `INVALID_HANDLE` if `hdl` is not a valid `SocketHandle`.
+    ///
+    /// Returns `INVALID_OPTION` if `opt` is not a known option, or `len` is not the size of its value.
+    ///
+    /// Returns `UNSUPPORTED_OPERATION` if the option does not apply to the socket.
+    pub fn GetSocketOption(
+        hdl: HandlePtr<SocketHandle>,
+        opt: u32,
+        val: *mut c_void,
+        len: c_ulong,
+    ) -> SysResult;
+    /// Reads the address of the remote end of the socket `hdl` into `addr`.
+    ///
+    /// ## Errors
+    ///
+    /// Returns `INVALID_HANDLE` if `hdl` is not a valid `SocketHandle`.
+    ///
+    /// Returns `INVALID_STATE` if the socket is not connected.
+    pub fn GetSocketPeerAddress(
+        hdl: HandlePtr<SocketHandle>,
+        addr: *mut SocketAddress,
+    ) -> SysResult;
+    /// Reads the address the socket `hdl` is bound to into `addr`.
+    ///
+    /// ## Errors
+    ///
+    /// Returns `INVALID_HANDLE` if `hdl` is not a valid `SocketHandle`.
+    ///
+    /// Returns `INVALID_STATE` if the socket is not bound.
+    pub fn GetSocketLocalAddress(
+        hdl: HandlePtr<SocketHandle>,
+        addr: *mut SocketAddress,
+    ) -> SysResult;
+
 }
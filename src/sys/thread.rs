@@ -2,7 +2,7 @@ use core::ffi::{c_int, c_long, c_ulong, c_void};
 
 use super::{
     handle::*,
-    kstr::{KStrCPtr, KStrPtr},
+    kstr::{KCSlice, KSlice, KStrCPtr, KStrPtr},
     result::SysResult,
     time::Duration,
 };
@@ -20,6 +20,20 @@ pub struct ThreadStartContext {
     pub __private: (),
 }
 
+/// The default time-sharing scheduling class. Priorities range from `-20` (most favourable) to `19` (least favourable).
+pub const SCHED_CLASS_NORMAL: u32 = 0;
+/// A time-sharing class for CPU-bound, non-interactive threads. Uses the same priority range as [`SCHED_CLASS_NORMAL`]
+pub const SCHED_CLASS_BATCH: u32 = 1;
+/// A class for threads which run only when no other thread is runnable. Priority is ignored.
+pub const SCHED_CLASS_IDLE: u32 = 2;
+/// A realtime class where a thread runs until it blocks, yields, or is preempted by a higher priority realtime thread.
+/// Priorities range from `1` (least favourable) to `99` (most favourable).
+///
+/// Setting a realtime class requires the `SetRealtimePriority` thread permission.
+pub const SCHED_CLASS_REALTIME_FIFO: u32 = 3;
+/// Same as [`SCHED_CLASS_REALTIME_FIFO`], except that threads of equal priority are scheduled round-robin.
+pub const SCHED_CLASS_REALTIME_RR: u32 = 4;
+
 #[allow(improper_ctypes)]
 extern "C" {
     pub fn StartThread(
@@ -30,6 +44,30 @@ extern "C" {
     pub fn UnparkThread(th: HandlePtr<ThreadHandle>) -> SysResult;
     pub fn YieldThread();
     pub fn AwaitAddress(addr: *mut c_void) -> SysResult;
+    /// Blocks the current thread on `addr` until it is notified, if the pointer-sized word at `addr` is equal to the value pointed to by `current_value`.
+    /// Bits set in the value pointed to by `ignore_mask` are ignored when comparing. If `ignore_mask` is null, no bits are ignored.
+    ///
+    /// The comparison and the start of the wait are performed atomically with respect to [`NotifyAddress`], [`NotifyOne`], and [`NotifyAll`] on `addr`,
+    ///  so a notification issued after the word at `addr` is modified is not lost.
+    ///
+    /// Returns `0` without blocking if the word is not equal to `current_value`. The thread may also wake up spuriously, and return `0`.
+    ///
+    /// ## Errors
+    ///
+    /// Returns `INVALID_MEMORY` if `addr` or `current_value` is null, not aligned to a pointer-sized word, or not readable.
+    ///
+    /// Returns `TIMEOUT` if the blocking timeout of the current thread elapses, and `INTERRUPTED` if the thread is interrupted.
+    pub fn AwaitAddressMasked(
+        addr: *mut c_void,
+        current_value: *const c_void,
+        ignore_mask: *const c_void,
+    ) -> SysResult;
+    /// Wakes up to `count` threads blocked on `addr` by [`AwaitAddress`] or [`AwaitAddressMasked`].
+    ///
+    /// If `wake_mask` is not null, only threads which are not ignoring every bit set in the value pointed to by `wake_mask` are woken.
+    ///
+    /// Returns the number of threads woken.
+    pub fn NotifyAddress(addr: *mut c_void, count: usize, wake_mask: *const c_void) -> SysResult;
     pub fn NotifyOne(addr: *mut c_void) -> SysResult;
     pub fn NotifyAll(addr: *mut c_void) -> SysResult;
     pub fn SetBlockingTimeout(dur: *const Duration);
@@ -38,6 +76,14 @@ extern "C" {
     pub fn InterruptThread(th: HandlePtr<ThreadHandle>) -> SysResult;
     pub fn Interrupted() -> SysResult;
     pub fn ClearBlockingTimeout();
+    /// Reads the blocking timeout of the current thread, set by [`SetBlockingTimeout`], into `dur`.
+    ///
+    /// ## Errors
+    ///
+    /// Returns `DOES_NOT_EXIST` if the current thread does not have a blocking timeout set.
+    ///
+    /// Returns `INVALID_MEMORY` if `dur` is not writable.
+    pub fn GetBlockingTimeout(dur: *mut Duration) -> SysResult;
     pub fn ThreadExit(thr: c_int) -> !;
     pub fn GetCurrentThread() -> HandlePtr<ThreadHandle>;
     pub fn GetTLSBaseAddr(th: HandlePtr<ThreadHandle>, addrout: *mut *mut c_void) -> SysResult;
@@ -48,6 +94,44 @@ extern "C" {
     pub fn SendHandle(toth: HandlePtr<ThreadHandle>, sendhdl: HandlePtr<Handle>) -> SysResult;
     pub fn RecieveHandle(out: *mut HandlePtr<Handle>) -> SysResult;
 
+    /// Sets the scheduling class and the priority within that class of `th`.
+    ///
+    /// ## Errors
+    ///
+    /// Returns `INVALID_HANDLE` if `th` is not a valid thread handle.
+    ///
+    /// Returns `INVALID_OPTION` if `sched_class` is not a recognized scheduling class, or `priority` is out of range for `sched_class`.
+    ///
+    /// Returns `PERMISSION` if the current thread does not have permission to set the scheduling class or priority.
+    pub fn SetThreadPriority(
+        th: HandlePtr<ThreadHandle>,
+        sched_class: u32,
+        priority: c_int,
+    ) -> SysResult;
+    /// Obtains the scheduling class and priority of `th`.
+    ///
+    /// ## Errors
+    ///
+    /// Returns `INVALID_HANDLE` if `th` is not a valid thread handle.
+    pub fn GetThreadPriority(
+        th: HandlePtr<ThreadHandle>,
+        sched_class: *mut u32,
+        priority: *mut c_int,
+    ) -> SysResult;
+    /// Restricts `th` to run only on the logical processors in `cpus`, a bitmask where bit `n % 8` of byte `n / 8` designates logical processor `n`.
+    ///
+    /// ## Errors
+    ///
+    /// Returns `INVALID_HANDLE` if `th` is not a valid thread handle.
+    ///
+    /// Returns `INVALID_OPERATION` if `cpus` does not contain any active logical processor.
+    pub fn SetThreadAffinity(th: HandlePtr<ThreadHandle>, cpus: KCSlice<u8>) -> SysResult;
+    /// Obtains the set of logical processors `th` may run on, in the same format as [`SetThreadAffinity`].
+    ///
+    /// If `cpus` is too short to contain the set, `cpus.len` is set to the required length and `INSUFFICIENT_LENGTH` is returned.
+    /// Otherwise, `cpus.len` is set to the number of bytes written.
+    pub fn GetThreadAffinity(th: HandlePtr<ThreadHandle>, cpus: *mut KSlice<u8>) -> SysResult;
+
     pub fn SetThreadName(th: HandlePtr<ThreadHandle>, name: KStrCPtr) -> SysResult;
     pub fn GetThreadName(th: HandlePtr<ThreadHandle>, name: KStrPtr) -> SysResult;
 
@@ -9,7 +9,7 @@ use super::{device::DeviceHandle, handle::WideHandle, result::SysResult};
 /// `Duration`s are signed, and can represent durations less than 0.
 ///
 /// The `seconds` are measured with a signed `i64`, so can measure durations in excess of +/-2.92e+11 years.
-#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord, bytemuck::Zeroable)]
 #[repr(C)]
 pub struct Duration {
     /// The number of seconds the duration represents, between [-1<<63,(1<<63)-1)
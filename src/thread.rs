@@ -1,10 +1,31 @@
-use core::marker::PhantomData;
+use core::{
+    cell::UnsafeCell,
+    ffi::c_void,
+    marker::PhantomData,
+    mem::MaybeUninit,
+    sync::atomic::{AtomicIsize, Ordering},
+};
+
+use alloc::{boxed::Box, string::String, sync::Arc, vec::Vec};
 
 use crate::{
+    handle::{AsHandle, BorrowedHandle, HandleRef, OwnedHandle},
+    info,
     result::{Error, Result},
-    sys::{handle::HandlePtr, thread as sys},
+    sys::{
+        handle::HandlePtr,
+        kstr::{KCSlice, KSlice, KStrCPtr},
+        process::{CreateMapping, RemoveMapping, MAP_ATTR_READ, MAP_ATTR_WRITE, MAP_KIND_NORMAL},
+        thread::{self as sys, ThreadHandle, ThreadStartContext},
+    },
+    time::Duration,
 };
 
+mod scoped;
+
+use scoped::ScopeMember;
+pub use scoped::{scope, Scope, ScopedJoinHandle};
+
 pub struct TlsKey<T>(isize, PhantomData<*mut T>);
 
 unsafe impl<T> Send for TlsKey<T> {}
@@ -69,6 +90,611 @@ impl<T> TlsKey<T> {
     pub unsafe fn dealloc(self) {
         sys::tls_free_dyn(self.0)
     }
+
+    /// Registers `dtor` to be called with a pointer to the current thread's value of the key, when each thread exits.
+    ///
+    /// # Safety
+    /// `dtor` must be sound to call on every thread that exits while the key is allocated,
+    ///  including threads that have not written a value. Values of the key start zeroed in every thread.
+    pub unsafe fn register_destructor(&self, dtor: fn(*mut T)) -> Result<()> {
+        let dtor = unsafe { core::mem::transmute::<fn(*mut T), fn(*mut c_void)>(dtor) };
+        Error::from_code(unsafe { sys::tls_register_destructor(dtor, self.0) })
+    }
+}
+
+const SLOT_UNINIT: u8 = 0;
+const SLOT_ALIVE: u8 = 1;
+const SLOT_DESTROYED: u8 = 2;
+
+/// The per-thread storage of a [`LocalKey`]. This is zeroed in a new thread, which is [`SLOT_UNINIT`].
+struct Slot<T> {
+    state: u8,
+    val: MaybeUninit<T>,
+}
+
+fn destroy_slot<T>(slot: *mut Slot<T>) {
+    unsafe {
+        if (*slot).state == SLOT_ALIVE {
+            (*slot).state = SLOT_DESTROYED;
+            (*slot).val.assume_init_drop()
+        }
+    }
+}
+
+/// A thread-local value, declared by [`thread_local_lilium!`][crate::thread_local_lilium].
+///
+/// The value is initialized the first time it is accessed on each thread, and dropped when the thread exits.
+pub struct LocalKey<T: 'static> {
+    key: AtomicIsize,
+    init: fn() -> T,
+}
+
+impl<T: 'static> core::fmt::Debug for LocalKey<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("LocalKey").finish_non_exhaustive()
+    }
+}
+
+const KEY_UNALLOCATED: isize = isize::MIN;
+
+impl<T: 'static> LocalKey<T> {
+    #[doc(hidden)]
+    pub const fn new(init: fn() -> T) -> Self {
+        Self {
+            key: AtomicIsize::new(KEY_UNALLOCATED),
+            init,
+        }
+    }
+
+    fn slot(&'static self) -> Result<*mut Slot<T>> {
+        let mut key = self.key.load(Ordering::Acquire);
+        if key == KEY_UNALLOCATED {
+            let new_key = TlsKey::<Slot<T>>::try_alloc()?;
+            if let Err(e) = unsafe { new_key.register_destructor(destroy_slot::<T>) } {
+                unsafe { new_key.dealloc() };
+                return Err(e);
+            }
+            match self.key.compare_exchange(
+                KEY_UNALLOCATED,
+                new_key.0,
+                Ordering::AcqRel,
+                Ordering::Acquire,
+            ) {
+                Ok(_) => key = new_key.0,
+                Err(cur) => {
+                    unsafe { new_key.dealloc() };
+                    key = cur;
+                }
+            }
+        }
+        Ok(TlsKey::<Slot<T>>(key, PhantomData).get())
+    }
+
+    /// Calls `f` with the current thread's value, initializing it if necessary.
+    ///
+    /// Returns `None` if the value has already been dropped because the thread is exiting.
+    ///
+    /// ## Errors
+    /// Returns an error if the thread-local storage for the key could not be allocated.
+    pub fn try_with<R, F: FnOnce(&T) -> R>(&'static self, f: F) -> Result<Option<R>> {
+        // Accessed only through raw pointers, as `init` and `f` may access the key again
+        let slot = self.slot()?;
+        match unsafe { (*slot).state } {
+            SLOT_UNINIT => {
+                let val = (self.init)();
+                unsafe {
+                    if (*slot).state == SLOT_UNINIT {
+                        (*slot).val.write(val);
+                        (*slot).state = SLOT_ALIVE;
+                    }
+                }
+            }
+            SLOT_ALIVE => {}
+            _ => return Ok(None),
+        }
+        Ok(Some(f(unsafe { (*slot).val.assume_init_ref() })))
+    }
+
+    /// Calls `f` with the current thread's value, initializing it if necessary.
+    ///
+    /// ## Panics
+    /// Panics if the thread-local storage cannot be allocated, or if the value has already been dropped because the thread is exiting.
+    pub fn with<R, F: FnOnce(&T) -> R>(&'static self, f: F) -> R {
+        match self.try_with(f) {
+            Ok(Some(val)) => val,
+            Ok(None) => panic!("cannot access a thread-local value after it has been destroyed"),
+            Err(e) => panic!("failed to allocate a thread-local value: {:?}", e),
+        }
+    }
+}
+
+/// Declares thread-local statics of type [`LocalKey`], without using `std`'s `thread_local!`.
+///
+/// Each value is initialized by its expression the first time it is accessed on a thread, and dropped when that thread exits.
+#[macro_export]
+macro_rules! thread_local_lilium {
+    () => {};
+    ($(#[$attr:meta])* $vis:vis static $name:ident: $ty:ty = $init:expr; $($rest:tt)*) => {
+        $crate::thread_local_lilium!($(#[$attr])* $vis static $name: $ty = $init);
+        $crate::thread_local_lilium!($($rest)*);
+    };
+    ($(#[$attr:meta])* $vis:vis static $name:ident: $ty:ty = $init:expr) => {
+        $(#[$attr])*
+        $vis static $name: $crate::thread::LocalKey<$ty> = {
+            fn __init() -> $ty {
+                $init
+            }
+            $crate::thread::LocalKey::new(__init)
+        };
+    };
+}
+
+/// The stack size of threads spawned by [`spawn`], or by a [`Builder`] without an explicit stack size
+pub const DEFAULT_STACK_SIZE: usize = 2 * 1024 * 1024;
+
+/// Why a thread could not be joined with a value
+#[derive(Debug)]
+pub enum JoinError {
+    /// The thread panicked, with the given payload
+    #[cfg(feature = "std")]
+    Panicked(Box<dyn core::any::Any + Send + 'static>),
+    /// The thread exited with the given code, without returning from its closure
+    Exited(i32),
+    /// The thread was terminated, such as by an unmanaged exception, or joining the thread failed
+    Terminated(Error),
+}
+
+impl core::fmt::Display for JoinError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            #[cfg(feature = "std")]
+            Self::Panicked(_) => f.write_str("thread panicked"),
+            Self::Exited(code) => f.write_fmt(format_args!("thread exited with code {}", code)),
+            Self::Terminated(e) => f.write_fmt(format_args!("thread terminated: {:?}", e)),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+type Outcome<T> = core::result::Result<T, Box<dyn core::any::Any + Send + 'static>>;
+#[cfg(not(feature = "std"))]
+type Outcome<T> = core::result::Result<T, !>;
+
+fn run_closure<T, F: FnOnce() -> T>(f: F) -> Outcome<T> {
+    cfg_if::cfg_if! {
+        if #[cfg(feature = "std")]{
+            std::panic::catch_unwind(std::panic::AssertUnwindSafe(f))
+        }else{
+            Ok(f())
+        }
+    }
+}
+
+struct Packet<T>(UnsafeCell<Option<Outcome<T>>>);
+
+// SAFETY: The packet is written only by the spawned thread, and read only after the thread is joined
+unsafe impl<T: Send> Sync for Packet<T> {}
+
+struct Stack {
+    base: *mut c_void,
+    page_count: usize,
+}
+
+impl Stack {
+    fn map(size: usize) -> Result<Self> {
+        let page_count = size.div_ceil(info::page_size()).max(1);
+        let mut base = core::ptr::null_mut();
+        Error::from_code(unsafe {
+            CreateMapping(
+                &mut base,
+                page_count as _,
+                MAP_ATTR_READ | MAP_ATTR_WRITE,
+                MAP_KIND_NORMAL,
+                &KCSlice::empty(),
+            )
+        })?;
+        Ok(Self { base, page_count })
+    }
+
+    fn top(&self) -> *mut c_void {
+        self.base
+            .cast::<u8>()
+            .wrapping_add(self.page_count * info::page_size())
+            .cast()
+    }
+
+    unsafe fn unmap(self) {
+        unsafe {
+            RemoveMapping(self.base, self.page_count as _);
+        }
+    }
+}
+
+type StartFn = Box<dyn FnOnce() + Send + 'static>;
+
+extern "C" fn thread_start(data: *mut c_void, _th: HandlePtr<ThreadHandle>, _tls: *mut c_void) {
+    let f = unsafe { Box::from_raw(data.cast::<StartFn>()) };
+    unsafe {
+        sys::thread_init_self();
+    }
+    f();
+    unsafe { sys::ThreadExit(0) }
+}
+
+/// Configures a new thread before spawning it
+#[derive(Clone, Debug, Default)]
+pub struct Builder {
+    name: Option<String>,
+    stack_size: Option<usize>,
+}
+
+impl Builder {
+    pub const fn new() -> Self {
+        Self {
+            name: None,
+            stack_size: None,
+        }
+    }
+
+    /// Sets the name of the new thread.
+    ///
+    /// Naming the thread is best-effort: if the name cannot be set, the thread is still spawned.
+    pub fn name(mut self, name: String) -> Self {
+        self.name = Some(name);
+        self
+    }
+
+    /// Sets the size of the stack mapped for the new thread. The size is rounded up to a whole number of pages.
+    pub fn stack_size(mut self, size: usize) -> Self {
+        self.stack_size = Some(size);
+        self
+    }
+
+    /// Spawns a new thread that runs `f`, and returns a [`JoinHandle`] which can obtain the value `f` returns.
+    pub fn spawn<F, T>(self, f: F) -> Result<JoinHandle<T>>
+    where
+        F: FnOnce() -> T + Send + 'static,
+        T: Send + 'static,
+    {
+        unsafe { self.spawn_unchecked(f, None) }.map(JoinHandle)
+    }
+
+    /// Spawns a thread which may borrow from outside `f`.
+    ///
+    /// # Safety
+    /// The thread must be joined before anything `f` or `T` borrows is invalidated.
+    unsafe fn spawn_unchecked<'a, F, T>(
+        self,
+        f: F,
+        scope: Option<Arc<ScopeMember>>,
+    ) -> Result<JoinInner<T>>
+    where
+        F: FnOnce() -> T + Send + 'a,
+        T: Send + 'a,
+    {
+        let stack = Stack::map(self.stack_size.unwrap_or(DEFAULT_STACK_SIZE))?;
+
+        let packet = Arc::new(Packet(UnsafeCell::new(None)));
+        let their_packet = packet.clone();
+        let their_scope = scope.clone();
+        let main: Box<dyn FnOnce() + Send + 'a> = Box::new(move || {
+            let res = run_closure(f);
+            unsafe { *their_packet.0.get() = Some(res) };
+            drop(their_packet);
+            if let Some(scope) = their_scope {
+                scope.leave();
+            }
+        });
+        // SAFETY: The caller guarantees that the thread is joined before `'a` ends
+        let main: StartFn = unsafe { core::mem::transmute(main) };
+        let data = Box::into_raw(Box::new(main));
+
+        let tsc = ThreadStartContext {
+            th_stack: stack.top(),
+            th_interal: data.cast(),
+            th_tlsbase: core::ptr::null_mut(),
+            th_start: thread_start,
+            __private: (),
+        };
+
+        let mut th = MaybeUninit::uninit();
+        if let Err(e) = Error::from_code(unsafe { sys::StartThread(&tsc, th.as_mut_ptr()) }) {
+            drop(unsafe { Box::from_raw(data) });
+            unsafe { stack.unmap() };
+            return Err(e);
+        }
+        let th = unsafe { OwnedHandle::take_ownership(th.assume_init()) };
+
+        // The thread is already running, so returning an error here would leave it running detached, on a stack that is never unmapped.
+        // The setup after starting is best-effort instead: the thread initializes itself in `thread_start`, and the name is only informational.
+        unsafe {
+            let _ = sys::thread_init_after_start(th.as_raw());
+            if let Some(name) = &self.name {
+                let _ = sys::SetThreadName(th.as_raw(), KStrCPtr::from_str(name));
+            }
+        }
+
+        Ok(JoinInner { th, stack, packet })
+    }
+}
+
+/// Spawns a new thread that runs `f`, with the default configuration.
+///
+/// ## Panics
+/// Panics if the thread cannot be spawned. Use [`Builder::spawn`] to handle the error instead.
+pub fn spawn<F, T>(f: F) -> JoinHandle<T>
+where
+    F: FnOnce() -> T + Send + 'static,
+    T: Send + 'static,
+{
+    match Builder::new().spawn(f) {
+        Ok(hdl) => hdl,
+        Err(e) => panic!("Failed to spawn thread: {:?}", e),
+    }
+}
+
+struct JoinInner<T> {
+    th: OwnedHandle<ThreadHandle>,
+    stack: Stack,
+    packet: Arc<Packet<T>>,
+}
+
+impl<T> JoinInner<T> {
+    fn join(self) -> core::result::Result<T, JoinError> {
+        let Self { th, stack, packet } = self;
+        let code = unsafe { sys::JoinThread(th.release_ownership()) };
+        if let Err(e) = Error::from_code(code) {
+            return Err(JoinError::Terminated(e));
+        }
+
+        unsafe { stack.unmap() };
+
+        match unsafe { (*packet.0.get()).take() } {
+            #[cfg(feature = "std")]
+            Some(Err(payload)) => Err(JoinError::Panicked(payload)),
+            Some(Ok(val)) => Ok(val),
+            None => Err(JoinError::Exited(code as i32)),
+        }
+    }
+}
+
+/// An owned permission to join a thread spawned by [`spawn`] or [`Builder::spawn`].
+///
+/// Dropping the [`JoinHandle`] detaches the thread. The stack of a detached thread is never unmapped.
+pub struct JoinHandle<T>(JoinInner<T>);
+
+impl<T> core::fmt::Debug for JoinHandle<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("JoinHandle")
+            .field("th", &self.0.th)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T> JoinHandle<T> {
+    pub fn as_raw(&self) -> HandlePtr<ThreadHandle> {
+        self.0.th.as_raw()
+    }
+
+    /// Borrows the handle to the thread
+    pub fn thread(&self) -> BorrowedHandle<'_, ThreadHandle> {
+        self.0.th.borrow()
+    }
+
+    /// Waits for the thread to finish, and returns the value returned by its closure.
+    ///
+    /// If the closure panicked (with the `std` feature), or the thread did not return from the closure, the reason is returned as a [`JoinError`].
+    pub fn join(self) -> core::result::Result<T, JoinError> {
+        self.0.join()
+    }
+}
+
+/// The scheduling class of a thread, which determines how the thread's priority is interpreted
+#[derive(Copy, Clone, Hash, PartialEq, Eq, Debug)]
+#[non_exhaustive]
+pub enum SchedulingClass {
+    /// The default time-sharing class. Priorities range from `-20` (most favourable) to `19` (least favourable)
+    Normal,
+    /// A time-sharing class for CPU-bound, non-interactive threads
+    Batch,
+    /// Runs only when no other thread is runnable
+    Idle,
+    /// Realtime, first-in first-out among threads of equal priority. Priorities range from `1` to `99` (most favourable)
+    RealtimeFifo,
+    /// Realtime, round-robin among threads of equal priority
+    RealtimeRoundRobin,
+    /// A scheduling class not known to this crate
+    Other(u32),
+}
+
+impl SchedulingClass {
+    pub const fn from_raw(class: u32) -> Self {
+        match class {
+            sys::SCHED_CLASS_NORMAL => Self::Normal,
+            sys::SCHED_CLASS_BATCH => Self::Batch,
+            sys::SCHED_CLASS_IDLE => Self::Idle,
+            sys::SCHED_CLASS_REALTIME_FIFO => Self::RealtimeFifo,
+            sys::SCHED_CLASS_REALTIME_RR => Self::RealtimeRoundRobin,
+            class => Self::Other(class),
+        }
+    }
+
+    pub const fn into_raw(self) -> u32 {
+        match self {
+            Self::Normal => sys::SCHED_CLASS_NORMAL,
+            Self::Batch => sys::SCHED_CLASS_BATCH,
+            Self::Idle => sys::SCHED_CLASS_IDLE,
+            Self::RealtimeFifo => sys::SCHED_CLASS_REALTIME_FIFO,
+            Self::RealtimeRoundRobin => sys::SCHED_CLASS_REALTIME_RR,
+            Self::Other(class) => class,
+        }
+    }
+}
+
+/// A set of logical processors, used as the affinity of a thread
+#[derive(Clone, Default, Hash, PartialEq, Eq)]
+pub struct CpuSet(Vec<u8>);
+
+impl core::fmt::Debug for CpuSet {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+impl CpuSet {
+    pub const fn new() -> Self {
+        Self(Vec::new())
+    }
+
+    pub fn insert(&mut self, cpu: usize) {
+        let byte = cpu / 8;
+        if byte >= self.0.len() {
+            self.0.resize(byte + 1, 0);
+        }
+        self.0[byte] |= 1 << (cpu % 8);
+    }
+
+    pub fn remove(&mut self, cpu: usize) {
+        if let Some(byte) = self.0.get_mut(cpu / 8) {
+            *byte &= !(1 << (cpu % 8));
+        }
+    }
+
+    pub fn contains(&self, cpu: usize) -> bool {
+        self.0
+            .get(cpu / 8)
+            .is_some_and(|byte| (byte & (1 << (cpu % 8))) != 0)
+    }
+
+    /// Iterates over the logical processors in the set, in ascending order
+    pub fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        self.0.iter().enumerate().flat_map(|(n, &byte)| {
+            (0..8)
+                .filter(move |bit| (byte & (1 << bit)) != 0)
+                .map(move |bit| n * 8 + bit)
+        })
+    }
+}
+
+impl FromIterator<usize> for CpuSet {
+    fn from_iter<I: IntoIterator<Item = usize>>(iter: I) -> Self {
+        let mut set = Self::new();
+        iter.into_iter().for_each(|cpu| set.insert(cpu));
+        set
+    }
+}
+
+impl HandleRef<ThreadHandle> {
+    /// Sets the scheduling class of the thread, and its priority within that class.
+    ///
+    /// Setting a realtime class requires the `SetRealtimePriority` thread permission.
+    pub fn set_priority(&self, class: SchedulingClass, priority: i32) -> Result<()> {
+        Error::from_code(unsafe {
+            sys::SetThreadPriority(self.as_raw(), class.into_raw(), priority)
+        })
+    }
+
+    /// Obtains the scheduling class of the thread, and its priority within that class
+    pub fn priority(&self) -> Result<(SchedulingClass, i32)> {
+        let mut class = 0;
+        let mut priority = 0;
+        Error::from_code(unsafe {
+            sys::GetThreadPriority(self.as_raw(), &mut class, &mut priority)
+        })?;
+        Ok((SchedulingClass::from_raw(class), priority))
+    }
+
+    /// Restricts the thread to run only on the logical processors in `cpus`
+    pub fn set_affinity(&self, cpus: &CpuSet) -> Result<()> {
+        Error::from_code(unsafe {
+            sys::SetThreadAffinity(self.as_raw(), KCSlice::from_slice(&cpus.0))
+        })
+    }
+
+    /// Obtains the set of logical processors the thread may run on
+    pub fn affinity(&self) -> Result<CpuSet> {
+        let mut buf = alloc::vec![0u8; 16];
+        loop {
+            let mut cpus = KSlice::from_slice_mut(&mut buf);
+            match Error::from_code(unsafe { sys::GetThreadAffinity(self.as_raw(), &mut cpus) }) {
+                Ok(()) => {
+                    buf.truncate(cpus.len);
+                    break Ok(CpuSet(buf));
+                }
+                Err(Error::InsufficientLength) => buf.resize(cpus.len, 0),
+                Err(e) => break Err(e),
+            }
+        }
+    }
+}
+
+/// Interrupts the thread designated by `th`.
+///
+/// If the thread is performing a blocking operation, that operation fails with [`Error::Interrupted`].
+/// Otherwise, the next blocking operation performed by the thread fails instead.
+pub fn interrupt<'a, H: AsHandle<'a, ThreadHandle>>(th: H) -> Result<()> {
+    Error::from_code(unsafe { sys::InterruptThread(th.as_handle()) })
+}
+
+/// Checks whether the current thread has a pending interrupt, and clears it.
+pub fn interrupted() -> bool {
+    unsafe { sys::Interrupted() != 0 }
+}
+
+/// Restores the previous blocking timeout of the current thread when dropped, or clears the blocking timeout if there was none.
+///
+/// Returned by [`set_blocking_timeout`].
+#[must_use = "the blocking timeout is restored immediately if the guard is dropped"]
+#[derive(Debug)]
+pub struct TimeoutGuard {
+    prev: Option<Duration>,
+    _marker: PhantomData<*mut ()>,
+}
+
+impl Drop for TimeoutGuard {
+    fn drop(&mut self) {
+        match self.prev {
+            Some(prev) => {
+                let prev = prev.into_system();
+                unsafe { sys::SetBlockingTimeout(&prev) }
+            }
+            None => unsafe { sys::ClearBlockingTimeout() },
+        }
+    }
+}
+
+/// Returns the timeout for blocking operations performed by the current thread, or `None` if no timeout is set.
+pub fn blocking_timeout() -> Option<Duration> {
+    let mut dur = MaybeUninit::uninit();
+    match Error::from_code(unsafe { sys::GetBlockingTimeout(dur.as_mut_ptr()) }) {
+        Ok(()) => Some(Duration::from_system(unsafe { dur.assume_init() })),
+        Err(_) => None,
+    }
+}
+
+/// Sets a timeout for blocking operations performed by the current thread, until the returned guard is dropped.
+///
+/// A blocking operation that does not complete within `dur` fails with [`Error::Timeout`].
+/// The timeout in effect before this call is restored when the guard is dropped, so guards may be nested.
+pub fn set_blocking_timeout(dur: Duration) -> TimeoutGuard {
+    let prev = blocking_timeout();
+    let dur = dur.into_system();
+    unsafe { sys::SetBlockingTimeout(&dur) }
+    TimeoutGuard {
+        prev,
+        _marker: PhantomData,
+    }
+}
+
+/// Calls `f` with a blocking timeout of `dur` set for the current thread, and restores the previous timeout afterwards, including if `f` panics.
+pub fn with_timeout<R, F: FnOnce() -> R>(dur: Duration, f: F) -> R {
+    let _guard = set_blocking_timeout(dur);
+    f()
+}
+
+/// Obtains a handle to the current thread
+pub fn current() -> BorrowedHandle<'static, ThreadHandle> {
+    unsafe { BorrowedHandle::from_raw(sys::GetCurrentThread()) }
 }
 
 cfg_if::cfg_if! {
@@ -0,0 +1,220 @@
+//! Threads which may borrow from the stack of the thread that spawns them
+
+use core::{
+    marker::PhantomData,
+    sync::atomic::{AtomicBool, AtomicUsize, Ordering},
+};
+
+use alloc::sync::Arc;
+
+use crate::{
+    result::{Error, Result},
+    sync::AtomicWaitEx,
+    sys::{handle::HandlePtr, thread::ThreadHandle},
+};
+
+use super::{Builder, JoinError, JoinInner};
+
+pub(super) struct ScopeData {
+    running: AtomicUsize,
+    #[cfg(feature = "std")]
+    a_thread_panicked: AtomicBool,
+}
+
+/// A thread spawned in a scope, which counts as running in the scope until it leaves it
+pub(super) struct ScopeMember {
+    data: Arc<ScopeData>,
+    left: AtomicBool,
+}
+
+impl ScopeMember {
+    fn enter(data: &Arc<ScopeData>) -> Arc<Self> {
+        data.running.fetch_add(1, Ordering::Relaxed);
+        Arc::new(Self {
+            data: data.clone(),
+            left: AtomicBool::new(false),
+        })
+    }
+
+    /// Removes the thread from the running threads of the scope. Only the first call has an effect.
+    ///
+    /// This must only be called once the thread can no longer access anything borrowed from the scope:
+    ///  either by the thread after its closure returned or panicked, or after the thread is known to have finished.
+    pub(super) fn leave(&self) {
+        if !self.left.swap(true, Ordering::AcqRel)
+            && self.data.running.fetch_sub(1, Ordering::Release) == 1
+        {
+            self.data.running.notify_all();
+        }
+    }
+}
+
+/// A scope in which threads may be spawned that borrow from outside the scope.
+///
+/// See [`scope`].
+pub struct Scope<'scope, 'env: 'scope> {
+    data: Arc<ScopeData>,
+    scope: PhantomData<&'scope mut &'scope ()>,
+    env: PhantomData<&'env mut &'env ()>,
+}
+
+impl core::fmt::Debug for Scope<'_, '_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Scope")
+            .field("running", &self.data.running.load(Ordering::Relaxed))
+            .finish_non_exhaustive()
+    }
+}
+
+/// Waits for every thread in the scope to finish, including when unwinding out of [`scope`].
+///
+/// The last thread to leave the scope notifies the count of running threads, which the guard blocks on.
+struct WaitGuard<'a>(&'a ScopeData);
+
+impl Drop for WaitGuard<'_> {
+    fn drop(&mut self) {
+        loop {
+            let running = self.0.running.load(Ordering::Acquire);
+            if running == 0 {
+                break;
+            }
+            // A timeout or an interrupt only means the count is checked again
+            let _ = self.0.running.wait(running);
+        }
+    }
+}
+
+/// Creates a scope for spawning threads which borrow non-`'static` data.
+///
+/// Every thread spawned in the scope is joined before this function returns. Threads whose [`ScopedJoinHandle`] is dropped are joined when it is dropped.
+///
+/// ## Panics
+/// With the `std` feature, panics if any thread that was joined automatically panicked.
+pub fn scope<'env, F, T>(f: F) -> T
+where
+    F: for<'scope> FnOnce(&'scope Scope<'scope, 'env>) -> T,
+{
+    let scope = Scope {
+        data: Arc::new(ScopeData {
+            running: AtomicUsize::new(0),
+            #[cfg(feature = "std")]
+            a_thread_panicked: AtomicBool::new(false),
+        }),
+        scope: PhantomData,
+        env: PhantomData,
+    };
+
+    let guard = WaitGuard(&scope.data);
+    let res = f(&scope);
+    drop(guard);
+
+    #[cfg(feature = "std")]
+    if scope.data.a_thread_panicked.load(Ordering::Relaxed) {
+        panic!("a scoped thread panicked")
+    }
+
+    res
+}
+
+impl<'scope, 'env> Scope<'scope, 'env> {
+    /// Spawns a new thread in the scope, with the default configuration.
+    ///
+    /// ## Panics
+    /// Panics if the thread cannot be spawned. Use [`Builder::spawn_scoped`] to handle the error instead.
+    pub fn spawn<F, T>(&'scope self, f: F) -> ScopedJoinHandle<'scope, T>
+    where
+        F: FnOnce() -> T + Send + 'scope,
+        T: Send + 'scope,
+    {
+        match Builder::new().spawn_scoped(self, f) {
+            Ok(hdl) => hdl,
+            Err(e) => panic!("Failed to spawn thread: {:?}", e),
+        }
+    }
+}
+
+impl Builder {
+    /// Spawns a new thread in `scope` that runs `f`, which may borrow from outside the scope.
+    pub fn spawn_scoped<'scope, 'env, F, T>(
+        self,
+        scope: &'scope Scope<'scope, 'env>,
+        f: F,
+    ) -> Result<ScopedJoinHandle<'scope, T>>
+    where
+        F: FnOnce() -> T + Send + 'scope,
+        T: Send + 'scope,
+    {
+        let member = ScopeMember::enter(&scope.data);
+        // SAFETY: `scope` waits for the thread to leave it before `'scope` ends
+        let inner = match unsafe { self.spawn_unchecked(f, Some(member.clone())) } {
+            Ok(inner) => inner,
+            Err(e) => {
+                // The thread was never started
+                member.leave();
+                return Err(e);
+            }
+        };
+        Ok(ScopedJoinHandle {
+            inner: Some(inner),
+            member,
+            scope: PhantomData,
+        })
+    }
+}
+
+/// An owned permission to join a thread spawned in a [`Scope`].
+///
+/// Dropping the [`ScopedJoinHandle`] joins the thread.
+pub struct ScopedJoinHandle<'scope, T> {
+    inner: Option<JoinInner<T>>,
+    member: Arc<ScopeMember>,
+    scope: PhantomData<&'scope ()>,
+}
+
+impl<T> core::fmt::Debug for ScopedJoinHandle<'_, T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("ScopedJoinHandle")
+            .field("th", &self.inner.as_ref().map(|inner| inner.th.as_raw()))
+            .finish_non_exhaustive()
+    }
+}
+
+impl<'scope, T> ScopedJoinHandle<'scope, T> {
+    pub fn as_raw(&self) -> HandlePtr<ThreadHandle> {
+        self.inner.as_ref().unwrap().th.as_raw()
+    }
+
+    fn join_inner(&mut self) -> core::result::Result<T, JoinError> {
+        let res = self.inner.take().unwrap().join();
+        // A thread that returned or panicked left the scope itself. A thread that exited or was killed did not, but it has finished.
+        // If joining failed for another reason, the thread may still be running in the closure, so the scope keeps waiting for it.
+        match &res {
+            Err(JoinError::Terminated(Error::Killed | Error::Signaled)) => self.member.leave(),
+            Err(JoinError::Terminated(_)) => {}
+            _ => self.member.leave(),
+        }
+        res
+    }
+
+    /// Waits for the thread to finish, and returns the value returned by its closure.
+    pub fn join(mut self) -> core::result::Result<T, JoinError> {
+        self.join_inner()
+    }
+}
+
+impl<T> Drop for ScopedJoinHandle<'_, T> {
+    fn drop(&mut self) {
+        if self.inner.is_none() {
+            return;
+        }
+        match self.join_inner() {
+            #[cfg(feature = "std")]
+            Err(JoinError::Panicked(_)) => self
+                .member
+                .data
+                .a_thread_panicked
+                .store(true, Ordering::Relaxed),
+            _ => {}
+        }
+    }
+}
@@ -11,6 +11,12 @@ use crate::{
     uuid::Uuid,
 };
 
+pub mod civil;
+
+mod timer;
+
+pub use timer::Timer;
+
 #[derive(Copy, Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
 pub struct Duration(sys::Duration);
 
@@ -47,6 +53,122 @@ impl Duration {
 
         Self(dur)
     }
+
+    pub const fn into_system(self) -> sys::Duration {
+        self.0
+    }
+
+    /// The largest representable duration
+    pub const MAX: Self = Self(sys::Duration {
+        seconds: i64::MAX,
+        nanos_of_second: 999_999_999,
+    });
+
+    /// The smallest (most negative) representable duration
+    pub const MIN: Self = Self(sys::Duration {
+        seconds: i64::MIN,
+        nanos_of_second: 0,
+    });
+
+    pub const fn is_negative(self) -> bool {
+        self.0.seconds < 0
+    }
+
+    /// Adds `rhs`, returning `None` on overflow
+    pub const fn checked_add(self, rhs: Self) -> Option<Self> {
+        let mut nanos = self.0.nanos_of_second + rhs.0.nanos_of_second;
+        let mut carry = 0;
+        if nanos >= 1_000_000_000 {
+            nanos -= 1_000_000_000;
+            carry = 1;
+        }
+        match self.0.seconds.checked_add(rhs.0.seconds) {
+            Some(seconds) => match seconds.checked_add(carry) {
+                Some(seconds) => Some(Self(sys::Duration {
+                    seconds,
+                    nanos_of_second: nanos,
+                })),
+                None => None,
+            },
+            None => None,
+        }
+    }
+
+    /// Subtracts `rhs`, returning `None` on overflow
+    pub const fn checked_sub(self, rhs: Self) -> Option<Self> {
+        let (mut nanos, wrap) = self
+            .0
+            .nanos_of_second
+            .overflowing_sub(rhs.0.nanos_of_second);
+        let mut borrow = 0;
+        if wrap {
+            nanos = nanos.wrapping_add(1_000_000_000);
+            borrow = 1;
+        }
+        match self.0.seconds.checked_sub(rhs.0.seconds) {
+            Some(seconds) => match seconds.checked_sub(borrow) {
+                Some(seconds) => Some(Self(sys::Duration {
+                    seconds,
+                    nanos_of_second: nanos,
+                })),
+                None => None,
+            },
+            None => None,
+        }
+    }
+
+    /// Adds `rhs`, saturating at [`Duration::MAX`] or [`Duration::MIN`]
+    pub const fn saturating_add(self, rhs: Self) -> Self {
+        match self.checked_add(rhs) {
+            Some(dur) => dur,
+            None if rhs.is_negative() => Self::MIN,
+            None => Self::MAX,
+        }
+    }
+
+    /// Subtracts `rhs`, saturating at [`Duration::MAX`] or [`Duration::MIN`]
+    pub const fn saturating_sub(self, rhs: Self) -> Self {
+        match self.checked_sub(rhs) {
+            Some(dur) => dur,
+            None if rhs.is_negative() => Self::MAX,
+            None => Self::MIN,
+        }
+    }
+}
+
+/// The error returned when converting between [`Duration`] and [`core::time::Duration`] fails,
+///  because the duration is negative or too long to be represented.
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+pub struct DurationOutOfRange;
+
+impl core::fmt::Display for DurationOutOfRange {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str("duration out of range")
+    }
+}
+
+impl core::error::Error for DurationOutOfRange {}
+
+impl TryFrom<core::time::Duration> for Duration {
+    type Error = DurationOutOfRange;
+
+    fn try_from(dur: core::time::Duration) -> core::result::Result<Self, DurationOutOfRange> {
+        match i64::try_from(dur.as_secs()) {
+            Ok(seconds) => Ok(Self::from_seconds_and_nanos(seconds, dur.subsec_nanos())),
+            Err(_) => Err(DurationOutOfRange),
+        }
+    }
+}
+
+impl TryFrom<Duration> for core::time::Duration {
+    type Error = DurationOutOfRange;
+
+    fn try_from(dur: Duration) -> core::result::Result<Self, DurationOutOfRange> {
+        match u64::try_from(dur.0.seconds) {
+            Ok(seconds) => Ok(Self::new(seconds, dur.0.nanos_of_second)),
+            Err(_) => Err(DurationOutOfRange),
+        }
+    }
 }
 
 impl AddAssign for Duration {
@@ -102,8 +224,66 @@ impl Sub for Duration {
     }
 }
 
+/// The serialized form of [`Duration`], which is also used for [`TimePoint`] as the offset from the epoch
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename = "Duration")]
+struct DurationRepr {
+    seconds: i64,
+    nanos: u32,
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for Duration {
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> core::result::Result<S::Ok, S::Error> {
+        DurationRepr {
+            seconds: self.0.seconds,
+            nanos: self.0.nanos_of_second,
+        }
+        .serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Duration {
+    fn deserialize<D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> core::result::Result<Self, D::Error> {
+        let DurationRepr { seconds, nanos } = DurationRepr::deserialize(deserializer)?;
+        if nanos >= 1_000_000_000 {
+            return Err(serde::de::Error::invalid_value(
+                serde::de::Unexpected::Unsigned(nanos as u64),
+                &"nanoseconds less than 1000000000",
+            ));
+        }
+        Ok(Self::from_seconds_and_nanos(seconds, nanos))
+    }
+}
+
 pub struct TimePoint<C>(sys::Duration, PhantomData<C>);
 
+#[cfg(feature = "serde")]
+impl<C> serde::Serialize for TimePoint<C> {
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> core::result::Result<S::Ok, S::Error> {
+        self.since_epoch().serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, C> serde::Deserialize<'de> for TimePoint<C> {
+    fn deserialize<D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> core::result::Result<Self, D::Error> {
+        Duration::deserialize(deserializer).map(Self::from_epoch_offset)
+    }
+}
+
 impl<C> Copy for TimePoint<C> {}
 
 impl<C> Clone for TimePoint<C> {
@@ -160,6 +340,87 @@ impl Clock for SystemClock {
     }
 }
 
+impl SystemClock {
+    /// Sets the system clock to `tp`. See [`ClockAdjuster::set`].
+    pub fn set(tp: TimePoint<SystemClock>) -> Result<()> {
+        ClockAdjuster::SYSTEM.set(tp.since_epoch())
+    }
+}
+
+/// Adjusts the offset of a modifiable clock, such as [`SystemClock`], for programs that keep the clock synchronized.
+///
+/// Adjusting the [`SystemClock`] requires the [`WRITE_REALTIME_CLOCK`][crate::security::kernel_perm::WRITE_REALTIME_CLOCK] kernel permission,
+///  and every adjustment returns [`Error::Permission`] if the current thread does not have it, or is denied write access to the clock device.
+/// The [`MonotonicClock`] cannot be adjusted, and adjustments return [`Error::InvalidOperation`].
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+pub struct ClockAdjuster(Uuid);
+
+impl ClockAdjuster {
+    /// Adjusts the [`SystemClock`]
+    pub const SYSTEM: Self = Self(sys::CLOCK_EPOCH);
+
+    /// Adjusts the clock device `clock`
+    pub const fn new(clock: Uuid) -> Self {
+        Self(clock)
+    }
+
+    /// The device id of the clock
+    pub const fn clock_id(&self) -> Uuid {
+        self.0
+    }
+
+    /// Checks whether the current thread is permitted to adjust the clock, without attempting an adjustment.
+    ///
+    /// For the [`SystemClock`], this checks the [`WRITE_REALTIME_CLOCK`][crate::security::kernel_perm::WRITE_REALTIME_CLOCK] kernel permission.
+    /// For other clocks, access is only checked when the clock is adjusted, so this returns `true` unless the clock is the [`MonotonicClock`].
+    pub fn permitted(&self) -> Result<bool> {
+        match self.0 {
+            sys::CLOCK_EPOCH => crate::security::has_kernel_permission(
+                crate::security::kernel_perm::WRITE_REALTIME_CLOCK,
+            )
+            .map(|status| status.contains(crate::security::PermissionStatus::ALLOWED)),
+            sys::CLOCK_MONOTONIC => Ok(false),
+            _ => Ok(true),
+        }
+    }
+
+    /// Reads the current offset of the clock from its epoch
+    pub fn offset(&self) -> Result<Duration> {
+        let mut offset = MaybeUninit::uninit();
+
+        Error::from_code(unsafe { GetClockOffset(offset.as_mut_ptr(), self.0) })?;
+
+        // SAFETY: Because `GetClockOffset` didn't return an error, it initialized `offset`
+        Ok(Duration(unsafe { offset.assume_init() }))
+    }
+
+    /// The smallest step the clock advances by
+    pub fn granularity(&self) -> Result<Duration> {
+        let mut granularity = MaybeUninit::uninit();
+
+        Error::from_code(unsafe { sys::GetClockGranularity(granularity.as_mut_ptr(), self.0) })?;
+
+        // SAFETY: Because `GetClockGranularity` didn't return an error, it initialized `granularity`
+        Ok(Duration(unsafe { granularity.assume_init() }))
+    }
+
+    /// Resets the clock to `offset` from its epoch
+    pub fn set(&self, offset: Duration) -> Result<()> {
+        Error::from_code(unsafe { sys::ResetClockOffset(offset.into_system(), self.0) })
+    }
+
+    /// Moves the clock forward by `delta` (or backward, if `delta` is negative), relative to its current offset, and returns the new offset.
+    ///
+    /// The clock is stepped at once. To slew the clock (so that it never jumps by a large amount), call this repeatedly with a small `delta`.
+    ///
+    /// The clock is read and then reset, so the time that elapses between the two is lost. This is generally much smaller than the [`granularity`][Self::granularity] of the clock.
+    pub fn adjust_by(&self, delta: Duration) -> Result<Duration> {
+        let offset = self.offset()?.saturating_add(delta);
+        self.set(offset)?;
+        Ok(offset)
+    }
+}
+
 #[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
 pub struct MonotonicClock;
 
@@ -169,6 +430,9 @@ impl Clock for MonotonicClock {
     }
 }
 
+/// A measurement of [`MonotonicClock`], for measuring elapsed time, like `std::time::Instant`.
+pub type Instant = TimePoint<MonotonicClock>;
+
 impl<C> TimePoint<C> {
     pub const EPOCH: Self = Self(
         sys::Duration {
@@ -205,6 +469,48 @@ impl<C> TimePoint<C> {
 
         Duration(dur)
     }
+
+    /// The duration from `earlier` to `self`, or `None` if `earlier` is after `self` (or the duration overflows)
+    pub const fn checked_duration_since(self, earlier: Self) -> Option<Duration> {
+        match Duration(self.0).checked_sub(Duration(earlier.0)) {
+            Some(dur) if !dur.is_negative() => Some(dur),
+            _ => None,
+        }
+    }
+
+    /// The duration from `earlier` to `self`, or [`Duration::ZERO`] if `earlier` is after `self`
+    pub const fn saturating_duration_since(self, earlier: Self) -> Duration {
+        match self.checked_duration_since(earlier) {
+            Some(dur) => dur,
+            None => Duration::ZERO,
+        }
+    }
+
+    /// Adds `dur`, returning `None` on overflow
+    pub const fn checked_add(self, dur: Duration) -> Option<Self> {
+        match Duration(self.0).checked_add(dur) {
+            Some(dur) => Some(Self::from_epoch_offset(dur)),
+            None => None,
+        }
+    }
+
+    /// Subtracts `dur`, returning `None` on overflow
+    pub const fn checked_sub(self, dur: Duration) -> Option<Self> {
+        match Duration(self.0).checked_sub(dur) {
+            Some(dur) => Some(Self::from_epoch_offset(dur)),
+            None => None,
+        }
+    }
+
+    /// Adds `dur`, saturating at the earliest or latest representable time point
+    pub const fn saturating_add(self, dur: Duration) -> Self {
+        Self::from_epoch_offset(Duration(self.0).saturating_add(dur))
+    }
+
+    /// Subtracts `dur`, saturating at the earliest or latest representable time point
+    pub const fn saturating_sub(self, dur: Duration) -> Self {
+        Self::from_epoch_offset(Duration(self.0).saturating_sub(dur))
+    }
 }
 
 impl<C> Add<Duration> for TimePoint<C> {
@@ -284,6 +590,11 @@ impl<C: Clock> TimePoint<C> {
         Ok(dur - inner)
     }
 
+    /// The time elapsed on the clock since `self`, or [`Duration::ZERO`] if `self` is in the future
+    pub fn elapsed(self) -> Result<Duration> {
+        Self::now().map(|now| now.saturating_duration_since(self))
+    }
+
     pub fn convert_to<C2: Clock>(self) -> Result<TimePoint<C2>> {
         let inner = self.since_epoch();
 
@@ -308,3 +619,19 @@ impl<C: Clock> TimePoint<C> {
         ))
     }
 }
+
+#[cfg(feature = "std")]
+impl From<TimePoint<SystemClock>> for std::time::SystemTime {
+    fn from(tp: TimePoint<SystemClock>) -> Self {
+        let dur = tp.since_epoch();
+        if dur.is_negative() {
+            // The magnitude of a negative `Duration` is at most `2^63` seconds, which always fits
+            let before = core::time::Duration::new(dur.0.seconds.unsigned_abs(), 0)
+                - core::time::Duration::new(0, dur.0.nanos_of_second);
+            std::time::SystemTime::UNIX_EPOCH - before
+        } else {
+            std::time::SystemTime::UNIX_EPOCH
+                + core::time::Duration::new(dur.0.seconds as u64, dur.0.nanos_of_second)
+        }
+    }
+}
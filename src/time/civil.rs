@@ -0,0 +1,465 @@
+//! Conversion between [`TimePoint<SystemClock>`] and calendar dates and times, in the proleptic Gregorian calendar in UTC.
+//!
+//! [`DateTime`] is formatted (with [`Display`][core::fmt::Display]) and parsed (with [`FromStr`]) as an [RFC 3339] timestamp, such as `2024-02-29T13:45:00.25Z`.
+//! Leap seconds are not represented, as [`SystemClock`] does not count them.
+//!
+//! [RFC 3339]: https://www.rfc-editor.org/rfc/rfc3339
+
+use core::str::FromStr;
+
+use crate::result::Result;
+
+use super::{Duration, SystemClock, TimePoint};
+
+const SECONDS_PER_DAY: i64 = 86400;
+
+/// A calendar date and time of day, in UTC
+///
+/// The fields are ordered from most to least significant, so the derived ordering is chronological for valid values.
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DateTime {
+    /// The year, where `0` is 1 BCE and negative years are before it
+    pub year: i64,
+    /// The month of the year, from `1` to `12`
+    pub month: u8,
+    /// The day of the month, from `1` to [`days_in_month`]
+    pub day: u8,
+    /// The hour of the day, from `0` to `23`
+    pub hour: u8,
+    /// The minute of the hour, from `0` to `59`
+    pub min: u8,
+    /// The second of the minute, from `0` to `59`
+    pub sec: u8,
+    /// The nanoseconds of the second, less than `1_000_000_000`
+    pub nanos: u32,
+}
+
+/// Checks if `year` is a leap year in the proleptic Gregorian calendar
+pub const fn is_leap_year(year: i64) -> bool {
+    year % 4 == 0 && (year % 100 != 0 || year % 400 == 0)
+}
+
+/// The number of days in `month` (from `1` to `12`) of `year`, or `0` if `month` is out of range
+pub const fn days_in_month(year: i64, month: u8) -> u8 {
+    match month {
+        1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+        4 | 6 | 9 | 11 => 30,
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        _ => 0,
+    }
+}
+
+// The day conversions are the `days_from_civil` and `civil_from_days` algorithms described at <https://howardhinnant.github.io/date_algorithms.html>
+
+/// Unwraps an `Option` in a `const fn`, returning `None` from the function if it is `None`
+macro_rules! try_opt {
+    ($e:expr) => {
+        match $e {
+            Some(val) => val,
+            None => return None,
+        }
+    };
+}
+
+/// The number of days from 1970-01-01 to the given date, or `None` on overflow
+const fn days_from_civil(year: i64, month: u8, day: u8) -> Option<i64> {
+    let (month, day) = (month as i64, day as i64);
+    let year = if month <= 2 {
+        try_opt!(year.checked_sub(1))
+    } else {
+        year
+    };
+    let era = (if year >= 0 {
+        year
+    } else {
+        try_opt!(year.checked_sub(399))
+    }) / 400;
+    let yoe = try_opt!(year.checked_sub(try_opt!(era.checked_mul(400))));
+    let doy = (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    try_opt!(era.checked_mul(146097)).checked_add(doe - 719468)
+}
+
+/// The date `days` days after 1970-01-01
+const fn civil_from_days(days: i64) -> (i64, u8, u8) {
+    let days = days + 719468;
+    let era = (if days >= 0 { days } else { days - 146096 }) / 146097;
+    let doe = days - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u8;
+    let month = (if mp < 10 { mp + 3 } else { mp - 9 }) as u8;
+    let year = yoe + era * 400;
+    (if month <= 2 { year + 1 } else { year }, month, day)
+}
+
+impl DateTime {
+    /// 1970-01-01T00:00:00Z, the epoch of [`SystemClock`]
+    pub const UNIX_EPOCH: Self = Self {
+        year: 1970,
+        month: 1,
+        day: 1,
+        hour: 0,
+        min: 0,
+        sec: 0,
+        nanos: 0,
+    };
+
+    /// Converts `tp` to a calendar date and time
+    pub const fn from_time_point(tp: TimePoint<SystemClock>) -> Self {
+        let dur = tp.since_epoch().into_system();
+        let days = dur.seconds.div_euclid(SECONDS_PER_DAY);
+        let secs = dur.seconds.rem_euclid(SECONDS_PER_DAY);
+        let (year, month, day) = civil_from_days(days);
+        Self {
+            year,
+            month,
+            day,
+            hour: (secs / 3600) as u8,
+            min: ((secs / 60) % 60) as u8,
+            sec: (secs % 60) as u8,
+            nanos: dur.nanos_of_second,
+        }
+    }
+
+    /// Converts the date and time to a [`TimePoint`].
+    ///
+    /// Returns `None` if any field is out of range (see [`DateTime::is_valid`]), or the time is too far from the epoch to be represented.
+    pub const fn to_time_point(&self) -> Option<TimePoint<SystemClock>> {
+        if !self.is_valid() {
+            return None;
+        }
+        let days = match days_from_civil(self.year, self.month, self.day) {
+            Some(days) => days,
+            None => return None,
+        };
+        let secs = (self.hour as i64) * 3600 + (self.min as i64) * 60 + (self.sec as i64);
+        match days.checked_mul(SECONDS_PER_DAY) {
+            Some(day_secs) => match day_secs.checked_add(secs) {
+                Some(seconds) => Some(TimePoint::from_epoch_offset(
+                    Duration::from_seconds_and_nanos(seconds, self.nanos),
+                )),
+                None => None,
+            },
+            None => None,
+        }
+    }
+
+    /// Checks that every field is in range, including that `day` exists in the month
+    pub const fn is_valid(&self) -> bool {
+        self.month >= 1
+            && self.month <= 12
+            && self.day >= 1
+            && self.day <= days_in_month(self.year, self.month)
+            && self.hour < 24
+            && self.min < 60
+            && self.sec < 60
+            && self.nanos < 1_000_000_000
+    }
+
+    /// The current date and time, according to [`SystemClock`]
+    pub fn now() -> Result<Self> {
+        TimePoint::<SystemClock>::now().map(Self::from_time_point)
+    }
+
+    /// Parses an RFC 3339 timestamp, such as `2024-02-29T13:45:00.25+01:00`, and converts it to UTC.
+    ///
+    /// The date and time may be separated by `T`, `t`, or a space.
+    pub fn parse_rfc3339(st: &str) -> core::result::Result<Self, ParseDateTimeError> {
+        let mut parser = Parser {
+            bytes: st.as_bytes(),
+            pos: 0,
+        };
+
+        let year = parser.digits(4)? as i64;
+        parser.expect(b"-")?;
+        let month = parser.digits(2)? as u8;
+        parser.expect(b"-")?;
+        let day = parser.digits(2)? as u8;
+        parser.expect(b"Tt ")?;
+        let hour = parser.digits(2)? as u8;
+        parser.expect(b":")?;
+        let min = parser.digits(2)? as u8;
+        parser.expect(b":")?;
+        let sec = parser.digits(2)? as u8;
+
+        let mut nanos = 0;
+        if parser.peek() == Some(b'.') {
+            parser.pos += 1;
+            let start = parser.pos;
+            while let Some(c @ b'0'..=b'9') = parser.peek() {
+                // Digits beyond nanosecond precision are truncated
+                if parser.pos - start < 9 {
+                    nanos = nanos * 10 + (c - b'0') as u32;
+                }
+                parser.pos += 1;
+            }
+            match parser.pos - start {
+                0 => return Err(parser.error()),
+                n @ 1..=8 => nanos *= 10u32.pow(9 - n as u32),
+                _ => {}
+            }
+        }
+
+        let offset = match parser.peek() {
+            Some(b'Z' | b'z') => {
+                parser.pos += 1;
+                0
+            }
+            Some(c @ (b'+' | b'-')) => {
+                parser.pos += 1;
+                let hours = parser.digits(2)? as i64;
+                parser.expect(b":")?;
+                let mins = parser.digits(2)? as i64;
+                if hours >= 24 || mins >= 60 {
+                    return Err(ParseDateTimeError::OutOfRange);
+                }
+                let offset = hours * 3600 + mins * 60;
+                if c == b'-' {
+                    -offset
+                } else {
+                    offset
+                }
+            }
+            _ => return Err(parser.error()),
+        };
+
+        if parser.pos != parser.bytes.len() {
+            return Err(ParseDateTimeError::UnexpectedChar(parser.pos));
+        }
+
+        let local = Self {
+            year,
+            month,
+            day,
+            hour,
+            min,
+            sec,
+            nanos,
+        };
+
+        match local.to_time_point() {
+            Some(tp) => Ok(Self::from_time_point(tp - Duration::from_seconds(offset))),
+            None => Err(ParseDateTimeError::OutOfRange),
+        }
+    }
+}
+
+impl From<TimePoint<SystemClock>> for DateTime {
+    fn from(tp: TimePoint<SystemClock>) -> Self {
+        Self::from_time_point(tp)
+    }
+}
+
+/// Formats the date and time as an RFC 3339 timestamp in UTC, such as `2024-02-29T12:45:00.25Z`.
+///
+/// The fractional seconds are omitted if they are zero, and otherwise written without trailing zeros.
+/// Years outside of `0000` to `9999`, which RFC 3339 cannot represent, are written with a sign, as in ISO 8601 expanded representation.
+impl core::fmt::Display for DateTime {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self.year {
+            0..=9999 => f.write_fmt(format_args!("{:04}", self.year))?,
+            year => f.write_fmt(format_args!("{:+05}", year))?,
+        }
+        f.write_fmt(format_args!(
+            "-{:02}-{:02}T{:02}:{:02}:{:02}",
+            self.month, self.day, self.hour, self.min, self.sec
+        ))?;
+
+        if self.nanos != 0 {
+            let mut nanos = self.nanos;
+            let mut width = 9;
+            while nanos.is_multiple_of(10) {
+                nanos /= 10;
+                width -= 1;
+            }
+            f.write_fmt(format_args!(".{:0width$}", nanos, width = width))?;
+        }
+
+        f.write_str("Z")
+    }
+}
+
+impl FromStr for DateTime {
+    type Err = ParseDateTimeError;
+
+    fn from_str(s: &str) -> core::result::Result<Self, Self::Err> {
+        Self::parse_rfc3339(s)
+    }
+}
+
+/// An error parsing an RFC 3339 timestamp
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+pub enum ParseDateTimeError {
+    /// The timestamp ended before it was complete
+    UnexpectedEnd,
+    /// The timestamp has an unexpected character at the given byte offset
+    UnexpectedChar(usize),
+    /// A field of the timestamp is out of range, such as a month of `13`, or the 30th of February
+    OutOfRange,
+}
+
+impl core::fmt::Display for ParseDateTimeError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::UnexpectedEnd => f.write_str("unexpected end of timestamp"),
+            Self::UnexpectedChar(pos) => {
+                f.write_fmt(format_args!("unexpected character at offset {}", pos))
+            }
+            Self::OutOfRange => f.write_str("timestamp field out of range"),
+        }
+    }
+}
+
+impl core::error::Error for ParseDateTimeError {}
+
+struct Parser<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<u8> {
+        self.bytes.get(self.pos).copied()
+    }
+
+    fn error(&self) -> ParseDateTimeError {
+        if self.pos < self.bytes.len() {
+            ParseDateTimeError::UnexpectedChar(self.pos)
+        } else {
+            ParseDateTimeError::UnexpectedEnd
+        }
+    }
+
+    fn digits(&mut self, count: usize) -> core::result::Result<u32, ParseDateTimeError> {
+        let mut val = 0;
+        for _ in 0..count {
+            match self.peek() {
+                Some(c @ b'0'..=b'9') => val = val * 10 + (c - b'0') as u32,
+                _ => return Err(self.error()),
+            }
+            self.pos += 1;
+        }
+        Ok(val)
+    }
+
+    fn expect(&mut self, any_of: &[u8]) -> core::result::Result<(), ParseDateTimeError> {
+        match self.peek() {
+            Some(c) if any_of.contains(&c) => {
+                self.pos += 1;
+                Ok(())
+            }
+            _ => Err(self.error()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use alloc::string::ToString;
+
+    use super::*;
+
+    #[test]
+    fn epoch() {
+        assert_eq!(days_from_civil(1970, 1, 1), Some(0));
+        assert_eq!(civil_from_days(0), (1970, 1, 1));
+        assert_eq!(
+            DateTime::UNIX_EPOCH.to_time_point().unwrap().since_epoch(),
+            Duration::from_seconds(0)
+        );
+        assert_eq!(
+            DateTime::from_time_point(TimePoint::from_epoch_offset(Duration::from_seconds(0))),
+            DateTime::UNIX_EPOCH
+        );
+    }
+
+    #[test]
+    fn leap_days() {
+        assert!(is_leap_year(2024));
+        assert!(!is_leap_year(2023));
+        assert_eq!(days_in_month(2024, 2), 29);
+        assert_eq!(days_in_month(2023, 2), 28);
+        assert_eq!(days_from_civil(2024, 2, 29), Some(19782));
+        assert_eq!(civil_from_days(19782), (2024, 2, 29));
+        assert_eq!(civil_from_days(19783), (2024, 3, 1));
+    }
+
+    #[test]
+    fn centuries() {
+        assert!(!is_leap_year(1900));
+        assert!(is_leap_year(2000));
+        assert_eq!(days_from_civil(1900, 2, 28), Some(-25509));
+        assert_eq!(days_from_civil(1900, 3, 1), Some(-25508));
+        assert_eq!(days_from_civil(2000, 2, 29), Some(11016));
+        assert_eq!(days_from_civil(2000, 3, 1), Some(11017));
+        assert_eq!(civil_from_days(-25508), (1900, 3, 1));
+        assert_eq!(civil_from_days(11016), (2000, 2, 29));
+    }
+
+    #[test]
+    fn negative_years() {
+        assert!(is_leap_year(0));
+        assert!(is_leap_year(-4));
+        assert!(!is_leap_year(-100));
+        assert_eq!(days_from_civil(1, 1, 1), Some(-719162));
+        assert_eq!(days_from_civil(0, 1, 1), Some(-719528));
+        assert_eq!(days_from_civil(-1, 12, 31), Some(-719529));
+        assert_eq!(civil_from_days(-719528), (0, 1, 1));
+        assert_eq!(civil_from_days(-719529), (-1, 12, 31));
+    }
+
+    #[test]
+    fn out_of_range_years() {
+        assert_eq!(days_from_civil(i64::MIN, 1, 1), None);
+        assert_eq!(days_from_civil(i64::MIN, 3, 1), None);
+        assert_eq!(days_from_civil(i64::MAX, 12, 31), None);
+        let min = DateTime {
+            year: i64::MIN,
+            ..DateTime::UNIX_EPOCH
+        };
+        assert_eq!(min.to_time_point(), None);
+    }
+
+    #[test]
+    fn rfc3339_round_trip() {
+        for st in [
+            "1970-01-01T00:00:00Z",
+            "2024-02-29T13:45:00.25Z",
+            "1969-12-31T23:59:59.999999999Z",
+            "2000-03-01T00:00:00.000001Z",
+        ] {
+            let dt = st.parse::<DateTime>().unwrap();
+            assert_eq!(dt.to_string(), st);
+            let tp = dt.to_time_point().unwrap();
+            assert_eq!(DateTime::from_time_point(tp), dt);
+        }
+    }
+
+    #[test]
+    fn rfc3339_offsets() {
+        let dt = DateTime::parse_rfc3339("2024-03-01T00:30:00+01:00").unwrap();
+        assert_eq!(dt.to_string(), "2024-02-29T23:30:00Z");
+        let dt = DateTime::parse_rfc3339("2023-12-31t23:30:00-00:45").unwrap();
+        assert_eq!(dt.to_string(), "2024-01-01T00:15:00Z");
+    }
+
+    #[test]
+    fn rfc3339_errors() {
+        assert_eq!(
+            DateTime::parse_rfc3339("2023-02-29T00:00:00Z"),
+            Err(ParseDateTimeError::OutOfRange)
+        );
+        assert_eq!(
+            DateTime::parse_rfc3339("2024-02-29T00:00:00"),
+            Err(ParseDateTimeError::UnexpectedEnd)
+        );
+        assert_eq!(
+            DateTime::parse_rfc3339("2024-02-29X00:00:00Z"),
+            Err(ParseDateTimeError::UnexpectedChar(10))
+        );
+    }
+}
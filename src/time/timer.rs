@@ -0,0 +1,155 @@
+use core::cell::Cell;
+
+use crate::{
+    result::{Error, Result},
+    sync::event::{block_on, Event, SleepUntil, TypedEvent},
+    sys::{event::EventInfo, result::SysResult},
+};
+
+use super::{Clock, Duration, MonotonicClock, TimePoint};
+
+/// A timer that fires once at a deadline, or periodically, measured by the clock `C`.
+///
+/// A `Timer` is an [`Event`], so it can be waited on with [`block_on`], or with [`block_on_any`][crate::sync::event::block_on_any] alongside other events.
+/// When a periodic timer fires, its output is the number of periods that have elapsed since it last fired (at least `1`), and it re-arms itself for the next period.
+/// A one-shot timer is disarmed after it fires, and a disarmed timer never fires.
+///
+/// The timer is only re-armed when its output is obtained (by [`block_on`], [`Ready::output`][crate::sync::event::Ready::output], or [`TypedEvent::output`]).
+///
+/// ## Example
+/// ```rust,ignore
+/// let tick = Timer::interval(Duration::from_seconds(1))?;
+/// let input = Readable::new(stdin);
+/// loop {
+///     let ready = block_on_any(&[&tick, &input])?;
+///     match ready.index() {
+///         0 => on_tick(ready.output(&tick)?),
+///         _ => on_input(ready.output(&input)?),
+///     }
+/// }
+/// ```
+#[derive(Debug)]
+pub struct Timer<C = MonotonicClock> {
+    deadline: Cell<Option<TimePoint<C>>>,
+    period: Option<Duration>,
+}
+
+fn total_nanos(dur: Duration) -> i128 {
+    let dur = dur.into_system();
+    (dur.seconds as i128) * 1_000_000_000 + (dur.nanos_of_second as i128)
+}
+
+fn from_total_nanos(nanos: i128) -> Option<Duration> {
+    let seconds = i64::try_from(nanos.div_euclid(1_000_000_000)).ok()?;
+    Some(Duration::from_seconds_and_nanos(
+        seconds,
+        nanos.rem_euclid(1_000_000_000) as u32,
+    ))
+}
+
+impl<C> Timer<C> {
+    /// A one-shot timer that fires at `deadline`
+    pub const fn at(deadline: TimePoint<C>) -> Self {
+        Self {
+            deadline: Cell::new(Some(deadline)),
+            period: None,
+        }
+    }
+
+    /// A periodic timer that first fires at `start`, and then every `period` after it.
+    ///
+    /// ## Panics
+    /// Panics if `period` is not positive.
+    pub fn interval_at(start: TimePoint<C>, period: Duration) -> Self {
+        assert!(
+            period > Duration::ZERO,
+            "The period of a Timer must be positive"
+        );
+        Self {
+            deadline: Cell::new(Some(start)),
+            period: Some(period),
+        }
+    }
+
+    /// The next time the timer fires, or `None` if it is disarmed
+    pub fn deadline(&self) -> Option<TimePoint<C>> {
+        self.deadline.get()
+    }
+
+    /// The period of the timer, or `None` if it is a one-shot timer
+    pub fn period(&self) -> Option<Duration> {
+        self.period
+    }
+
+    pub fn is_armed(&self) -> bool {
+        self.deadline.get().is_some()
+    }
+
+    /// Disarms the timer, so that it does not fire until it is [`reset`][Self::reset]
+    pub fn disarm(&self) {
+        self.deadline.set(None)
+    }
+
+    /// Re-arms the timer to next fire at `deadline`. A periodic timer keeps its period.
+    pub fn reset(&self, deadline: TimePoint<C>) {
+        self.deadline.set(Some(deadline))
+    }
+}
+
+impl<C: Clock> Timer<C> {
+    /// A one-shot timer that fires once `dur` has elapsed from now
+    pub fn after(dur: Duration) -> Result<Self> {
+        TimePoint::now().map(|now| Self::at(now + dur))
+    }
+
+    /// A periodic timer that fires every `period`, starting one `period` from now.
+    ///
+    /// ## Panics
+    /// Panics if `period` is not positive.
+    pub fn interval(period: Duration) -> Result<Self> {
+        TimePoint::now().map(|now| Self::interval_at(now + period, period))
+    }
+
+    /// Blocks the current thread until the timer fires, and returns the number of periods elapsed. See [`block_on`].
+    pub fn wait(&self) -> Result<u64> {
+        block_on(self)
+    }
+}
+
+unsafe impl<C: Clock> Event for Timer<C> {
+    fn to_raw(&self) -> EventInfo {
+        // A disarmed timer waits for the latest representable time, which is never reached
+        let deadline = self
+            .deadline
+            .get()
+            .unwrap_or(TimePoint::from_epoch_offset(Duration::MAX));
+        SleepUntil(deadline).to_raw()
+    }
+}
+
+impl<C: Clock> TypedEvent for Timer<C> {
+    type Output = u64;
+
+    fn output(&self, result: SysResult) -> Result<u64> {
+        Error::from_code(result)?;
+
+        let Some(deadline) = self.deadline.get() else {
+            return Ok(0);
+        };
+
+        match self.period {
+            None => {
+                self.deadline.set(None);
+                Ok(1)
+            }
+            Some(period) => {
+                let late = total_nanos(TimePoint::<C>::now()? - deadline).max(0);
+                let count = late / total_nanos(period) + 1;
+                let next = from_total_nanos(count * total_nanos(period))
+                    .and_then(|advance| deadline.checked_add(advance));
+                self.deadline.set(next);
+                Ok(count as u64)
+            }
+        }
+    }
+}
@@ -0,0 +1,117 @@
+//! Syscall-level instrumentation of the safe interfaces.
+//!
+//! With the `tracing` feature, each system call made through [`syscall!`] runs inside a `syscall` span at the `TRACE` level,
+//! which records the name of the system call, the handle it operates on (and, if the call succeeds, the type of that handle, from `IdentHandle`), and the result code.
+//! Without the feature, [`syscall!`] expands to the plain call.
+
+#[cfg(feature = "tracing")]
+use crate::sys::{
+    handle::{Handle, HandlePtr, IdentHandle},
+    result::SysResult,
+};
+
+/// Picks the handle a system call operates on from its first argument, if that argument is a handle.
+#[cfg(feature = "tracing")]
+pub(crate) trait TraceHandle {
+    fn trace_handle(&self) -> HandlePtr<Handle>;
+}
+
+#[cfg(feature = "tracing")]
+impl<T> TraceHandle for HandlePtr<T> {
+    fn trace_handle(&self) -> HandlePtr<Handle> {
+        self.cast()
+    }
+}
+
+/// The fallback of [`TraceHandle`] for first arguments that are not handles, which is selected by auto-ref.
+#[cfg(feature = "tracing")]
+pub(crate) trait TraceOther {
+    fn trace_handle(&self) -> HandlePtr<Handle> {
+        HandlePtr::null()
+    }
+}
+
+#[cfg(feature = "tracing")]
+impl<T: ?Sized> TraceOther for &T {}
+
+/// The span of a system call made through [`syscall!`], which is entered before the call and completed by [`SyscallSpan::exit`] after it
+#[cfg(feature = "tracing")]
+pub(crate) struct SyscallSpan {
+    span: tracing::span::EnteredSpan,
+    name: &'static str,
+}
+
+#[cfg(feature = "tracing")]
+impl SyscallSpan {
+    pub(crate) fn enter(path: &'static str) -> Self {
+        let name = path.rsplit(' ').next().unwrap_or(path);
+        let span = tracing::trace_span!(
+            "syscall",
+            name,
+            handle = tracing::field::Empty,
+            handle_type = tracing::field::Empty,
+            result = tracing::field::Empty,
+        );
+        Self {
+            span: span.entered(),
+            name,
+        }
+    }
+
+    pub(crate) fn exit(self, hdl: HandlePtr<Handle>, res: SysResult) -> SysResult {
+        let span = &self.span;
+        if !span.is_disabled() {
+            if hdl != HandlePtr::null() {
+                span.record("handle", tracing::field::debug(hdl));
+            }
+            // `IdentHandle` is not called after a failed call, as it could replace the error context recorded for that call.
+            // The handle may have been closed by a successful call, in which case the type is an error code
+            if hdl != HandlePtr::null() && res >= 0 {
+                span.record("handle_type", unsafe { IdentHandle(hdl) });
+            }
+            span.record("result", res);
+            if res < 0 {
+                let name = self.name;
+                tracing::trace!(parent: span, error = ?crate::result::Error::from_code(res).err(), "{name} failed");
+            }
+        }
+        res
+    }
+}
+
+/// Calls the system call `$f` with the arguments given, instrumented as described in the [module docs][self].
+///
+/// This must be used in an `unsafe` block, just as calling `$f` directly.
+///
+/// System calls that do not return are written `syscall!(!$f(...))`; their span is entered but never exited.
+#[cfg(feature = "tracing")]
+macro_rules! syscall {
+    (! $($f:ident)::+ ($($arg:expr),* $(,)?)) => {{
+        let _span = $crate::trace::SyscallSpan::enter(stringify!($($f)::+));
+        $($f)::+($($arg),*)
+    }};
+    ($($f:ident)::+ ()) => {{
+        let span = $crate::trace::SyscallSpan::enter(stringify!($($f)::+));
+        let res = $($f)::+();
+        span.exit($crate::sys::handle::HandlePtr::null(), res)
+    }};
+    ($($f:ident)::+ ($first:expr $(, $rest:expr)* $(,)?)) => {{
+        #[allow(unused_imports)]
+        use $crate::trace::{TraceHandle as _, TraceOther as _};
+        let first = $first;
+        let span = $crate::trace::SyscallSpan::enter(stringify!($($f)::+));
+        let res = $($f)::+(first $(, $rest)*);
+        // `first` is only inspected after the call, so that its type is inferred from the signature of `$f`
+        span.exit((&first).trace_handle(), res)
+    }};
+}
+
+#[cfg(not(feature = "tracing"))]
+macro_rules! syscall {
+    (! $($f:ident)::+ ($($arg:expr),* $(,)?)) => {
+        $($f)::+($($arg),*)
+    };
+    ($($f:ident)::+ ($($arg:expr),* $(,)?)) => {
+        $($f)::+($($arg),*)
+    };
+}
@@ -109,12 +109,247 @@ impl core::fmt::UpperHex for Uuid {
     }
 }
 
+/// Uuids are serialized as strings in human-readable formats, and as their 16 bytes (see [`Uuid::as_bytes`]) otherwise
+#[cfg(feature = "serde")]
+impl serde::Serialize for Uuid {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            serializer.collect_str(self)
+        } else {
+            serializer.serialize_bytes(&self.as_bytes())
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Uuid {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct UuidVisitor;
+
+        impl serde::de::Visitor<'_> for UuidVisitor {
+            type Value = Uuid;
+
+            fn expecting(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+                f.write_str("a UUID")
+            }
+
+            fn visit_str<E: serde::de::Error>(self, v: &str) -> Result<Uuid, E> {
+                try_parse_uuid(v)
+                    .map_err(|_| E::invalid_value(serde::de::Unexpected::Str(v), &self))
+            }
+
+            fn visit_bytes<E: serde::de::Error>(self, v: &[u8]) -> Result<Uuid, E> {
+                v.try_into()
+                    .map(Uuid::from_bytes)
+                    .map_err(|_| E::invalid_length(v.len(), &self))
+            }
+        }
+
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_str(UuidVisitor)
+        } else {
+            deserializer.deserialize_bytes(UuidVisitor)
+        }
+    }
+}
+
+/// The variant of a [`Uuid`], which determines the layout of the remaining bits
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+pub enum Variant {
+    /// Reserved for backwards compatibility with NCS UUIDs
+    Ncs,
+    /// The layout specified by RFC 4122, used by every UUID generated by this crate
+    Rfc4122,
+    /// Reserved for backwards compatibility with Microsoft GUIDs
+    Microsoft,
+    /// Reserved for future definition
+    Future,
+}
+
 impl Uuid {
     pub const NIL: Uuid = Uuid { minor: 0, major: 0 };
     pub const FULL: Uuid = Uuid {
         minor: !0,
         major: !0,
     };
+
+    /// The namespace for UUIDs generated from a fully-qualified domain name
+    pub const NAMESPACE_DNS: Uuid = parse_uuid("6ba7b810-9dad-11d1-80b4-00c04fd430c8");
+    /// The namespace for UUIDs generated from a URL, which is used for every UUID defined by Lilium
+    pub const NAMESPACE_URL: Uuid = parse_uuid("6ba7b811-9dad-11d1-80b4-00c04fd430c8");
+
+    /// Converts the UUID into its 16 bytes, in the big-endian order used by its string form
+    pub const fn as_bytes(&self) -> [u8; 16] {
+        let major = self.major.to_be_bytes();
+        let minor = self.minor.to_be_bytes();
+        let mut bytes = [0; 16];
+        let mut i = 0;
+        while i < 8 {
+            bytes[i] = major[i];
+            bytes[i + 8] = minor[i];
+            i += 1;
+        }
+        bytes
+    }
+
+    /// Constructs a UUID from its 16 bytes, in the big-endian order used by its string form
+    pub const fn from_bytes(bytes: [u8; 16]) -> Self {
+        let mut major = [0; 8];
+        let mut minor = [0; 8];
+        let mut i = 0;
+        while i < 8 {
+            major[i] = bytes[i];
+            minor[i] = bytes[i + 8];
+            i += 1;
+        }
+        Self {
+            major: u64::from_be_bytes(major),
+            minor: u64::from_be_bytes(minor),
+        }
+    }
+
+    /// The version number of the UUID. This is only meaningful if [`Uuid::variant`] is [`Variant::Rfc4122`].
+    pub const fn version(&self) -> u8 {
+        ((self.major >> 12) & 0xF) as u8
+    }
+
+    pub const fn variant(&self) -> Variant {
+        match self.minor >> 61 {
+            0..=3 => Variant::Ncs,
+            4 | 5 => Variant::Rfc4122,
+            6 => Variant::Microsoft,
+            _ => Variant::Future,
+        }
+    }
+
+    /// Sets the version number to `version`, and the variant to [`Variant::Rfc4122`]
+    const fn with_version(self, version: u8) -> Self {
+        Self {
+            major: (self.major & !0xF000) | ((version as u64) << 12),
+            minor: (self.minor & !(0xC << 60)) | (0x8 << 60),
+        }
+    }
+
+    /// Generates a random (version 4) UUID from the global entropy pool, as by [`crate::random::fill_exact`].
+    #[cfg(feature = "api")]
+    pub fn new_v4() -> crate::result::Result<Self> {
+        let mut bytes = [0; 16];
+        crate::random::fill_exact(&mut bytes)?;
+        Ok(Self::from_bytes(bytes).with_version(4))
+    }
+
+    /// Generates the name-based (version 5) UUID for `name` in `namespace`, using SHA-1.
+    pub const fn new_v5(namespace: Uuid, name: &[u8]) -> Self {
+        let digest = sha1::digest(&namespace.as_bytes(), name);
+        let mut bytes = [0; 16];
+        let mut i = 0;
+        while i < 16 {
+            bytes[i] = digest[i];
+            i += 1;
+        }
+        Self::from_bytes(bytes).with_version(5)
+    }
+}
+
+mod sha1 {
+    const fn block(state: &mut [u32; 5], block: &[u8; 64]) {
+        let mut w = [0u32; 80];
+        let mut i = 0;
+        while i < 16 {
+            w[i] = u32::from_be_bytes([
+                block[4 * i],
+                block[4 * i + 1],
+                block[4 * i + 2],
+                block[4 * i + 3],
+            ]);
+            i += 1;
+        }
+        while i < 80 {
+            w[i] = (w[i - 3] ^ w[i - 8] ^ w[i - 14] ^ w[i - 16]).rotate_left(1);
+            i += 1;
+        }
+
+        let [mut a, mut b, mut c, mut d, mut e] = *state;
+        i = 0;
+        while i < 80 {
+            let (f, k) = match i / 20 {
+                0 => ((b & c) | (!b & d), 0x5A827999),
+                1 => (b ^ c ^ d, 0x6ED9EBA1),
+                2 => ((b & c) | (b & d) | (c & d), 0x8F1BBCDC),
+                _ => (b ^ c ^ d, 0xCA62C1D6),
+            };
+            let t = a
+                .rotate_left(5)
+                .wrapping_add(f)
+                .wrapping_add(e)
+                .wrapping_add(k)
+                .wrapping_add(w[i]);
+            e = d;
+            d = c;
+            c = b.rotate_left(30);
+            b = a;
+            a = t;
+            i += 1;
+        }
+
+        state[0] = state[0].wrapping_add(a);
+        state[1] = state[1].wrapping_add(b);
+        state[2] = state[2].wrapping_add(c);
+        state[3] = state[3].wrapping_add(d);
+        state[4] = state[4].wrapping_add(e);
+    }
+
+    /// Computes the SHA-1 digest of `prefix` followed by `msg`
+    pub const fn digest(prefix: &[u8; 16], msg: &[u8]) -> [u8; 20] {
+        let mut state = [0x67452301, 0xEFCDAB89, 0x98BADCFE, 0x10325476, 0xC3D2E1F0];
+        let total = prefix.len() + msg.len();
+        let mut buf = [0u8; 64];
+        let mut fill = 0;
+        let mut i = 0;
+        while i < total {
+            buf[fill] = if i < prefix.len() {
+                prefix[i]
+            } else {
+                msg[i - prefix.len()]
+            };
+            fill += 1;
+            if fill == 64 {
+                block(&mut state, &buf);
+                fill = 0;
+            }
+            i += 1;
+        }
+
+        buf[fill] = 0x80;
+        fill += 1;
+        if fill > 56 {
+            while fill < 64 {
+                buf[fill] = 0;
+                fill += 1;
+            }
+            block(&mut state, &buf);
+            fill = 0;
+        }
+        while fill < 56 {
+            buf[fill] = 0;
+            fill += 1;
+        }
+        let bits = ((total as u64) * 8).to_be_bytes();
+        i = 0;
+        while i < 8 {
+            buf[56 + i] = bits[i];
+            i += 1;
+        }
+        block(&mut state, &buf);
+
+        let mut out = [0; 20];
+        i = 0;
+        while i < 20 {
+            out[i] = state[i / 4].to_be_bytes()[i % 4];
+            i += 1;
+        }
+        out
+    }
 }
 
 const fn to_hexdig(c: u8) -> Option<u64> {
@@ -250,3 +485,59 @@ pub const fn parse_uuid(st: &str) -> Uuid {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use alloc::{string::ToString, vec};
+
+    use super::*;
+
+    fn words(digest: [u8; 20]) -> [u32; 5] {
+        let mut words = [0; 5];
+        for (word, chunk) in words.iter_mut().zip(digest.chunks_exact(4)) {
+            *word = u32::from_be_bytes(chunk.try_into().unwrap());
+        }
+        words
+    }
+
+    #[test]
+    fn sha1_two_blocks() {
+        // FIPS 180-2, appendix A.2. The padding of a 56 byte message spills into a second block.
+        assert_eq!(
+            words(sha1::digest(
+                b"abcdbcdecdefdefg",
+                b"efghfghighijhijkijkljklmklmnlmnomnopnopq"
+            )),
+            [0x84983E44, 0x1C3BD26E, 0xBAAE4AA1, 0xF95129E5, 0xE54670F1]
+        );
+    }
+
+    #[test]
+    fn sha1_million_a() {
+        // FIPS 180-2, appendix A.3
+        let msg = vec![b'a'; 1_000_000 - 16];
+        assert_eq!(
+            words(sha1::digest(&[b'a'; 16], &msg)),
+            [0x34AA973C, 0xD4C4DAA4, 0xF61EEB2B, 0xDBAD2731, 0x6534016F]
+        );
+    }
+
+    #[test]
+    fn v5_known_answers() {
+        // RFC 9562, appendix A.4
+        assert_eq!(
+            Uuid::new_v5(Uuid::NAMESPACE_DNS, b"www.example.com"),
+            parse_uuid("2ed6657d-e927-568b-95e1-2665a8aea6a2")
+        );
+        assert_eq!(
+            Uuid::new_v5(Uuid::NAMESPACE_DNS, b"python.org"),
+            parse_uuid("886313e1-3b8a-5372-9b90-0c9aee199e5d")
+        );
+    }
+
+    #[test]
+    fn v5_is_const() {
+        const ID: Uuid = Uuid::new_v5(Uuid::NAMESPACE_DNS, b"python.org");
+        assert_eq!(ID.to_string(), "886313e1-3b8a-5372-9b90-0c9aee199e5d");
+    }
+}
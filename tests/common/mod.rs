@@ -0,0 +1,43 @@
+//! Helpers shared by the integration tests
+
+use lilium_sys::{
+    fs::{self, OwnedFile},
+    sys::handle::HandlePtr,
+};
+
+/// An empty directory for a single test, removed along with its contents when dropped
+pub struct ScratchDir(String);
+
+impl ScratchDir {
+    /// Creates the directory `name` for the tests in `suite`, first removing anything left there by an earlier run
+    pub fn new(suite: &str, name: &str) -> Self {
+        let path = format!("/tmp/lilium-sys-tests/{suite}/{name}");
+        remove_tree(&path);
+        fs::create_dir_all(&path).unwrap();
+        Self(path)
+    }
+
+    /// The absolute path to the directory
+    pub fn path(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Drop for ScratchDir {
+    fn drop(&mut self) {
+        remove_tree(&self.0);
+    }
+}
+
+/// Removes `path` and, if it is a directory, everything in it. Errors are ignored, so that cleanup never fails a test.
+fn remove_tree(path: &str) {
+    if let Ok(dir) = OwnedFile::open_dir(path) {
+        for entry in dir.read_dir().flatten() {
+            let name = entry.name().as_str();
+            if name != "." && name != ".." {
+                remove_tree(&format!("{path}/{name}"));
+            }
+        }
+    }
+    let _ = fs::remove_at(HandlePtr::null(), path);
+}
@@ -0,0 +1,27 @@
+//! Lexical path normalization. These tests do not access the filesystem.
+
+use lilium_sys::fs::Path;
+
+#[test]
+fn absolutize_joins_relative_paths_to_base() {
+    assert_eq!(
+        Path::new("b/../c").absolutize("/a").as_str(),
+        Path::new("/a/c").as_str()
+    );
+}
+
+#[test]
+fn absolutize_keeps_absolute_paths() {
+    assert_eq!(
+        Path::new("/x/./y").absolutize("/a").as_str(),
+        Path::new("/x/y").as_str()
+    );
+}
+
+#[test]
+fn absolutize_with_empty_base_stays_relative() {
+    assert_eq!(
+        Path::new("b/./c").absolutize("").as_str(),
+        Path::new("b/c").as_str()
+    );
+}
@@ -0,0 +1,54 @@
+//! Directory iteration, and operations on entries relative to their parent.
+
+mod common;
+
+use common::ScratchDir;
+use lilium_sys::{
+    fs::{self, FileAccess, FileOp, OwnedFile},
+    sys::handle::HandlePtr,
+};
+
+#[test]
+fn read_dir_yields_every_entry() {
+    let scratch = ScratchDir::new("read_dir", "every");
+    let dir = scratch.path();
+    fs::write(format!("{dir}/a"), b"a").unwrap();
+    fs::write(format!("{dir}/b"), b"b").unwrap();
+
+    let hdl = OwnedFile::open_dir(&dir).unwrap();
+    let mut names = hdl
+        .read_dir()
+        .map(|entry| entry.unwrap().name().as_str().to_string())
+        .filter(|name| name != "." && name != "..")
+        .collect::<Vec<_>>();
+    names.sort();
+    assert_eq!(names, ["a", "b"]);
+}
+
+#[test]
+fn entries_resolve_against_parent_after_rename() {
+    let scratch = ScratchDir::new("read_dir", "rename");
+    let dir = scratch.path();
+    fs::create_dir_all(format!("{dir}/before")).unwrap();
+    fs::write(format!("{dir}/before/file"), b"content").unwrap();
+
+    let hdl = OwnedFile::open_dir(format!("{dir}/before")).unwrap();
+    let entry = hdl
+        .read_dir()
+        .map(Result::unwrap)
+        .find(|entry| entry.name().as_str() == "file")
+        .unwrap();
+    fs::rename_at(
+        HandlePtr::null(),
+        format!("{dir}/before"),
+        HandlePtr::null(),
+        format!("{dir}/after"),
+    )
+    .unwrap();
+
+    // The entry is opened through the handle to its parent, not the old path
+    let file = entry.open(FileAccess::READ, FileOp::DataAccess).unwrap();
+    let mut buf = [0; 7];
+    file.as_io_handle().read_exact(&mut buf).unwrap();
+    assert_eq!(&buf, b"content");
+}
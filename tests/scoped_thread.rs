@@ -0,0 +1,46 @@
+//! Scoped threads that do not return from their closure.
+
+use lilium_sys::{
+    sys::thread::ThreadExit,
+    thread::{self, JoinError},
+};
+
+#[test]
+fn exited_scoped_thread_is_joined() {
+    let mut count = 0;
+    thread::scope(|s| {
+        let hdl = s.spawn(|| {
+            count += 1;
+            unsafe { ThreadExit(7) }
+        });
+        assert!(matches!(hdl.join(), Err(JoinError::Exited(7))));
+    });
+    assert_eq!(count, 1);
+}
+
+#[test]
+fn exited_scoped_thread_is_joined_on_drop() {
+    let mut count = 0;
+    thread::scope(|s| {
+        s.spawn(|| {
+            count += 1;
+            unsafe { ThreadExit(1) }
+        });
+    });
+    assert_eq!(count, 1);
+}
+
+#[test]
+fn scope_waits_for_exited_and_returning_threads() {
+    let data = [1, 2, 3];
+    let sum = thread::scope(|s| {
+        let exited = s.spawn(|| {
+            let _ = data.len();
+            unsafe { ThreadExit(0) }
+        });
+        let returned = s.spawn(|| data.iter().sum::<i32>());
+        assert!(matches!(exited.join(), Err(JoinError::Exited(0))));
+        returned.join().unwrap()
+    });
+    assert_eq!(sum, 6);
+}
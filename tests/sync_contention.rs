@@ -0,0 +1,89 @@
+//! Stress tests for the primitives built on `AwaitAddressMasked` and `NotifyAddress`.
+
+use core::sync::atomic::{AtomicU8, AtomicUsize, Ordering};
+
+use lilium_sys::{
+    sync::{AtomicWaitEx, Condvar, Mutex},
+    thread,
+};
+
+const THREADS: usize = 8;
+const ROUNDS: usize = 10_000;
+
+#[test]
+fn mutex_under_contention() {
+    let counter = Mutex::new(0usize);
+    thread::scope(|s| {
+        for _ in 0..THREADS {
+            s.spawn(|| {
+                for _ in 0..ROUNDS {
+                    *counter.lock() += 1;
+                }
+            });
+        }
+    });
+    assert_eq!(counter.into_inner(), THREADS * ROUNDS);
+}
+
+#[test]
+fn condvar_wakes_every_consumer() {
+    let queue = Mutex::new((0usize, false));
+    let ready = Condvar::new();
+    let consumed = AtomicUsize::new(0);
+    thread::scope(|s| {
+        for _ in 0..THREADS {
+            s.spawn(|| loop {
+                let mut guard = queue.lock();
+                ready
+                    .wait_while(&mut guard, |(items, done)| *items == 0 && !*done)
+                    .unwrap();
+                if guard.0 == 0 {
+                    break;
+                }
+                guard.0 -= 1;
+                consumed.fetch_add(1, Ordering::Relaxed);
+            });
+        }
+        for _ in 0..ROUNDS {
+            queue.lock().0 += 1;
+            ready.notify_one();
+        }
+        queue.lock().1 = true;
+        ready.notify_all();
+    });
+    assert_eq!(consumed.load(Ordering::Relaxed), ROUNDS);
+}
+
+#[repr(C, align(8))]
+struct SharedWord {
+    ping: AtomicU8,
+    pong: AtomicU8,
+}
+
+#[test]
+fn sub_word_atomics_in_one_word_do_not_lose_wakeups() {
+    let word = SharedWord {
+        ping: AtomicU8::new(0),
+        pong: AtomicU8::new(0),
+    };
+    thread::scope(|s| {
+        s.spawn(|| {
+            for round in 0..ROUNDS {
+                let val = round as u8;
+                while word.ping.load(Ordering::Acquire) == val {
+                    word.ping.wait(val).unwrap();
+                }
+                word.pong.store(val.wrapping_add(1), Ordering::Release);
+                word.pong.notify_all();
+            }
+        });
+        for round in 0..ROUNDS {
+            let val = round as u8;
+            word.ping.store(val.wrapping_add(1), Ordering::Release);
+            word.ping.notify_all();
+            while word.pong.load(Ordering::Acquire) == val {
+                word.pong.wait(val).unwrap();
+            }
+        }
+    });
+}
@@ -0,0 +1,86 @@
+//! Weak links, which do not keep the object they refer to alive.
+
+mod common;
+
+use common::ScratchDir;
+use lilium_sys::{
+    fs::{self, FileAccess, FileOp, OwnedFile},
+    result::Error,
+    sys::handle::HandlePtr,
+};
+
+#[test]
+fn weak_link_resolves_while_object_is_alive() {
+    let scratch = ScratchDir::new("weak_link", "alive");
+    let dir = scratch.path();
+    let original = format!("{dir}/original");
+    let link = format!("{dir}/link");
+    fs::write(&original, b"content").unwrap();
+
+    fs::weak_link(&original, &link).unwrap();
+    assert_eq!(fs::read(&link).unwrap(), b"content");
+    assert!(fs::same_object(&original, &link).unwrap());
+}
+
+#[test]
+fn weak_link_dangles_once_last_strong_link_is_removed() {
+    let scratch = ScratchDir::new("weak_link", "dangles");
+    let dir = scratch.path();
+    let original = format!("{dir}/original");
+    let link = format!("{dir}/link");
+    fs::write(&original, b"content").unwrap();
+
+    fs::weak_link(&original, &link).unwrap();
+    fs::remove_at(HandlePtr::null(), &original).unwrap();
+
+    assert_eq!(fs::read(&link), Err(Error::DoesNotExist));
+    assert_eq!(fs::upgrade_link(&link).err(), Some(Error::DoesNotExist));
+    // The weak link itself remains until it is removed
+    fs::remove_at(HandlePtr::null(), &link).unwrap();
+}
+
+#[test]
+fn upgraded_link_keeps_object_alive() {
+    let scratch = ScratchDir::new("weak_link", "upgrade");
+    let dir = scratch.path();
+    let original = format!("{dir}/original");
+    let link = format!("{dir}/link");
+    fs::write(&original, b"content").unwrap();
+
+    fs::weak_link(&original, &link).unwrap();
+    drop(fs::upgrade_link(&link).unwrap());
+    fs::remove_at(HandlePtr::null(), &original).unwrap();
+
+    assert_eq!(fs::read(&link).unwrap(), b"content");
+}
+
+#[test]
+fn downgraded_last_link_releases_object() {
+    let scratch = ScratchDir::new("weak_link", "downgrade");
+    let dir = scratch.path();
+    let original = format!("{dir}/original");
+    fs::write(&original, b"content").unwrap();
+
+    fs::downgrade_link(&original).unwrap();
+    assert_eq!(
+        OwnedFile::open_with(&original, FileAccess::READ, FileOp::DataAccess).err(),
+        Some(Error::DoesNotExist)
+    );
+}
+
+#[test]
+fn open_handle_outlives_removed_strong_link() {
+    let scratch = ScratchDir::new("weak_link", "handle");
+    let dir = scratch.path();
+    let original = format!("{dir}/original");
+    let link = format!("{dir}/link");
+    fs::write(&original, b"content").unwrap();
+
+    let file = fs::weak_link_handle(&original, &link).unwrap();
+    fs::remove_at(HandlePtr::null(), &original).unwrap();
+
+    // The object is destroyed once every handle to it is closed, and not before
+    assert!(file.object_id().is_ok());
+    drop(file);
+    assert_eq!(fs::read(&link), Err(Error::DoesNotExist));
+}